@@ -177,6 +177,7 @@ mod tests {
     #[actix_rt::test]
     async fn test_date_service_drop() {
         let service = Rc::new(DateService::new());
+        service.now();
 
         // yield so date service have a chance to register the spawned timer update task.
         yield_now().await;
@@ -203,6 +204,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_date_service_outside_runtime() {
+        // update task is not spawned until the date is first used
+        let settings = ServiceConfig::default();
+        drop(settings);
+    }
+
     #[test]
     fn test_date_len() {
         assert_eq!(DATE_VALUE_LENGTH, "Sun, 06 Nov 1994 08:49:37 GMT".len());
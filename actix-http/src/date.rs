@@ -1,5 +1,5 @@
 use std::{
-    cell::Cell,
+    cell::{Cell, OnceCell},
     fmt::{self, Write},
     rc::Rc,
     time::{Duration, Instant, SystemTime},
@@ -42,39 +42,53 @@ impl fmt::Write for Date {
 }
 
 /// Service for update Date and Instant periodically at 500 millis interval.
+///
+/// The update task is spawned when the date or instant is first read, so constructing the service
+/// does not require a running Actix runtime.
 pub(crate) struct DateService {
     current: Rc<Cell<(Date, Instant)>>,
-    handle: JoinHandle<()>,
+    handle: OnceCell<JoinHandle<()>>,
 }
 
 impl DateService {
     pub(crate) fn new() -> Self {
-        // shared date and timer for DateService and update async task.
-        let current = Rc::new(Cell::new((Date::new(), Instant::now())));
-        let current_clone = Rc::clone(&current);
-        // spawn an async task sleep for 500 millis and update current date/timer in a loop.
-        // handle is used to stop the task on DateService drop.
-        let handle = actix_rt::spawn(async move {
-            #[cfg(test)]
-            let _notify = crate::notify_on_drop::NotifyOnDrop::new();
-
-            let mut interval = interval(Duration::from_millis(500));
-            loop {
-                let now = interval.tick().await;
-                let date = Date::new();
-                current_clone.set((date, now.into_std()));
-            }
+        DateService {
+            current: Rc::new(Cell::new((Date::new(), Instant::now()))),
+            handle: OnceCell::new(),
+        }
+    }
+
+    fn current(&self) -> (Date, Instant) {
+        self.handle.get_or_init(|| {
+            // the value set on construction may be stale by the time the service is first used
+            self.current.set((Date::new(), Instant::now()));
+
+            // shared date and timer for DateService and update async task.
+            let current_clone = Rc::clone(&self.current);
+            // spawn an async task sleep for 500 millis and update current date/timer in a loop.
+            // handle is used to stop the task on DateService drop.
+            actix_rt::spawn(async move {
+                #[cfg(test)]
+                let _notify = crate::notify_on_drop::NotifyOnDrop::new();
+
+                let mut interval = interval(Duration::from_millis(500));
+                loop {
+                    let now = interval.tick().await;
+                    let date = Date::new();
+                    current_clone.set((date, now.into_std()));
+                }
+            })
         });
 
-        DateService { current, handle }
+        self.current.get()
     }
 
     pub(crate) fn now(&self) -> Instant {
-        self.current.get().1
+        self.current().1
     }
 
     pub(crate) fn with_date<F: FnMut(&Date)>(&self, mut f: F) {
-        f(&self.current.get().0);
+        f(&self.current().0);
     }
 }
 
@@ -87,6 +101,8 @@ impl fmt::Debug for DateService {
 impl Drop for DateService {
     fn drop(&mut self) {
         // stop the timer update async task on drop.
-        self.handle.abort();
+        if let Some(handle) = self.handle.get() {
+            handle.abort();
+        }
     }
 }
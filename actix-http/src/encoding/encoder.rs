@@ -60,6 +60,16 @@ impl<B: MessageBody> Encoder<B> {
     }
 
     pub fn response(encoding: ContentEncoding, head: &mut ResponseHead, body: B) -> Self {
+        Self::response_with_config(encoding, head, body, &EncoderConfig::default())
+    }
+
+    /// Constructs an encoder for a response body, using the compression settings in `config`.
+    pub fn response_with_config(
+        encoding: ContentEncoding,
+        head: &mut ResponseHead,
+        body: B,
+        config: &EncoderConfig,
+    ) -> Self {
         // no need to compress empty bodies
         match body.size() {
             BodySize::None => return Self::none(),
@@ -79,7 +89,7 @@ impl<B: MessageBody> Encoder<B> {
 
         if should_encode {
             // wrap body only if encoder is feature-enabled
-            if let Some(enc) = ContentEncoder::select(encoding) {
+            if let Some(enc) = ContentEncoder::select(encoding, config) {
                 update_head(encoding, head);
 
                 return Encoder {
@@ -262,6 +272,125 @@ where
     }
 }
 
+/// Compression settings used by [`Encoder`].
+///
+/// Higher levels compress better but use more CPU time. Larger windows can compress better but
+/// use more memory, both when compressing and when decompressing. Settings for encodings whose
+/// `compress-*` feature is not enabled have no effect.
+///
+/// Gzip and deflate always use a 32 KiB window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncoderConfig {
+    gzip_level: u32,
+    deflate_level: u32,
+    brotli_quality: u32,
+    brotli_window: u32,
+    zstd_level: i32,
+    zstd_window_log: Option<u32>,
+}
+
+impl EncoderConfig {
+    /// Constructs default compression settings, which favor speed over compression ratio.
+    pub const fn new() -> Self {
+        Self {
+            gzip_level: 1,
+            deflate_level: 1,
+            brotli_quality: 3,
+            brotli_window: 22,
+            zstd_level: 3,
+            zstd_window_log: None,
+        }
+    }
+
+    /// Sets the gzip compression level, from 0 (no compression) to 9 (best compression).
+    ///
+    /// Defaults to 1.
+    ///
+    /// # Panics
+    /// Panics if `level` is greater than 9.
+    pub fn gzip_level(mut self, level: u32) -> Self {
+        assert!(level <= 9, "gzip level must be between 0 and 9");
+        self.gzip_level = level;
+        self
+    }
+
+    /// Sets the deflate compression level, from 0 (no compression) to 9 (best compression).
+    ///
+    /// Defaults to 1.
+    ///
+    /// # Panics
+    /// Panics if `level` is greater than 9.
+    pub fn deflate_level(mut self, level: u32) -> Self {
+        assert!(level <= 9, "deflate level must be between 0 and 9");
+        self.deflate_level = level;
+        self
+    }
+
+    /// Sets the brotli quality, from 0 (fastest) to 11 (best compression).
+    ///
+    /// Defaults to 3.
+    ///
+    /// # Panics
+    /// Panics if `quality` is greater than 11.
+    pub fn brotli_quality(mut self, quality: u32) -> Self {
+        assert!(quality <= 11, "brotli quality must be between 0 and 11");
+        self.brotli_quality = quality;
+        self
+    }
+
+    /// Sets the base-2 logarithm of the brotli window size, from 10 (1 KiB) to 24 (16 MiB).
+    ///
+    /// Defaults to 22 (4 MiB).
+    ///
+    /// # Panics
+    /// Panics if `lgwin` is not between 10 and 24.
+    pub fn brotli_window(mut self, lgwin: u32) -> Self {
+        assert!(
+            (10..=24).contains(&lgwin),
+            "brotli window must be between 10 and 24"
+        );
+        self.brotli_window = lgwin;
+        self
+    }
+
+    /// Sets the zstd compression level, from 1 (fastest) to 22 (best compression).
+    ///
+    /// Defaults to 3.
+    ///
+    /// # Panics
+    /// Panics if `level` is not between 1 and 22.
+    pub fn zstd_level(mut self, level: i32) -> Self {
+        assert!(
+            (1..=22).contains(&level),
+            "zstd level must be between 1 and 22"
+        );
+        self.zstd_level = level;
+        self
+    }
+
+    /// Sets the base-2 logarithm of the zstd window size, from 10 (1 KiB) to 27 (128 MiB).
+    ///
+    /// By default, the window size is chosen based on the compression level. Browsers may refuse
+    /// to decompress responses with windows larger than 8 MiB, which is a window log of 23.
+    ///
+    /// # Panics
+    /// Panics if `window_log` is not between 10 and 27.
+    pub fn zstd_window_log(mut self, window_log: u32) -> Self {
+        assert!(
+            (10..=27).contains(&window_log),
+            "zstd window log must be between 10 and 27"
+        );
+        self.zstd_window_log = Some(window_log);
+        self
+    }
+}
+
+impl Default for EncoderConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 fn update_head(encoding: ContentEncoding, head: &mut ResponseHead) {
     head.headers_mut()
         .insert(header::CONTENT_ENCODING, encoding.to_header_value());
@@ -288,26 +417,35 @@ enum ContentEncoder {
 }
 
 impl ContentEncoder {
-    fn select(encoding: ContentEncoding) -> Option<Self> {
+    #[allow(unused_variables)] // config is only unused when no compress features enabled
+    fn select(encoding: ContentEncoding, config: &EncoderConfig) -> Option<Self> {
         match encoding {
             #[cfg(feature = "compress-gzip")]
             ContentEncoding::Deflate => Some(ContentEncoder::Deflate(ZlibEncoder::new(
                 Writer::new(),
-                flate2::Compression::fast(),
+                flate2::Compression::new(config.deflate_level),
             ))),
 
             #[cfg(feature = "compress-gzip")]
             ContentEncoding::Gzip => Some(ContentEncoder::Gzip(GzEncoder::new(
                 Writer::new(),
-                flate2::Compression::fast(),
+                flate2::Compression::new(config.gzip_level),
             ))),
 
             #[cfg(feature = "compress-brotli")]
-            ContentEncoding::Brotli => Some(ContentEncoder::Brotli(new_brotli_compressor())),
+            ContentEncoding::Brotli => Some(ContentEncoder::Brotli(new_brotli_compressor(
+                config.brotli_quality,
+                config.brotli_window,
+            ))),
 
             #[cfg(feature = "compress-zstd")]
             ContentEncoding::Zstd => {
-                let encoder = ZstdEncoder::new(Writer::new(), 3).ok()?;
+                let mut encoder = ZstdEncoder::new(Writer::new(), config.zstd_level).ok()?;
+
+                if let Some(window_log) = config.zstd_window_log {
+                    encoder.window_log(window_log).ok()?;
+                }
+
                 Some(ContentEncoder::Zstd(encoder))
             }
 
@@ -402,12 +540,12 @@ impl ContentEncoder {
 }
 
 #[cfg(feature = "compress-brotli")]
-fn new_brotli_compressor() -> Box<brotli::CompressorWriter<Writer>> {
+fn new_brotli_compressor(quality: u32, lgwin: u32) -> Box<brotli::CompressorWriter<Writer>> {
     Box::new(brotli::CompressorWriter::new(
         Writer::new(),
         32 * 1024, // 32 KiB buffer
-        3,         // BROTLI_PARAM_QUALITY
-        22,        // BROTLI_PARAM_LGWIN
+        quality,   // BROTLI_PARAM_QUALITY
+        lgwin,     // BROTLI_PARAM_LGWIN
     ))
 }
 
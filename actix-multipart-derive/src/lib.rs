@@ -9,10 +9,10 @@
 
 use std::collections::HashSet;
 
-use darling::{FromDeriveInput, FromField, FromMeta};
+use darling::{util::Override, FromDeriveInput, FromField, FromMeta, FromVariant};
 use parse_size::parse_size;
 use proc_macro::TokenStream;
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::quote;
 use syn::{parse_macro_input, Type};
 
@@ -34,6 +34,14 @@ impl Default for DuplicateField {
 struct MultipartFormAttrs {
     deny_unknown_fields: bool,
     duplicate_field: DuplicateField,
+    tag: Option<String>,
+    ordered: bool,
+}
+
+#[derive(FromVariant)]
+#[darling(attributes(multipart))]
+struct VariantAttrs {
+    rename: Option<String>,
 }
 
 #[allow(clippy::disallowed_names)] // false positive in macro expansion
@@ -42,12 +50,30 @@ struct MultipartFormAttrs {
 struct FieldAttrs {
     rename: Option<String>,
     limit: Option<String>,
+    #[darling(multiple)]
+    content_type: Vec<String>,
+    flatten: bool,
+    prefix: Option<String>,
+    max_items: Option<usize>,
+    #[darling(multiple)]
+    validate: Vec<syn::Path>,
+    #[darling(multiple)]
+    validate_async: Vec<syn::Path>,
+    default: Option<Override<syn::Expr>>,
 }
 
 struct ParsedField<'t> {
     serialization_name: String,
     rust_name: &'t Ident,
     limit: Option<usize>,
+    content_types: Vec<String>,
+    max_items: Option<usize>,
+    validators: Vec<syn::Path>,
+    async_validators: Vec<syn::Path>,
+    /// Value used when the field is missing.
+    default: Option<TokenStream2>,
+    /// Field name prefix, if this field is a flattened form.
+    flatten: Option<String>,
     ty: &'t Type,
 }
 
@@ -86,6 +112,25 @@ struct ParsedField<'t> {
 /// }
 /// ```
 ///
+/// # Default Values
+///
+/// Instead of using `Option<T>`, a missing field can be filled in using the `#[multipart(default)]`
+/// attribute, which uses the field type's `Default` implementation, or `#[multipart(default =
+/// "<expr>")]` to give the value explicitly.
+///
+/// ```
+/// use actix_multipart::form::{text::Text, MultipartForm};
+///
+/// #[derive(MultipartForm)]
+/// struct Form {
+///     #[multipart(default)]
+///     tags: Text<String>,
+///
+///     #[multipart(default = "Text(20)")]
+///     page_size: Text<u32>,
+/// }
+/// ```
+///
 /// # Field Renaming
 ///
 /// You can use the `#[multipart(rename = "foo")]` attribute to receive a field by a different name.
@@ -120,6 +165,168 @@ struct ParsedField<'t> {
 /// }
 /// ```
 ///
+/// # Item Limits
+///
+/// You can use the `#[multipart(max_items = <count>)]` attribute on `Vec` fields to limit the number
+/// of parts accepted under that field name. Additional parts are rejected with a
+/// `MultipartError::TooManyItems` error before any of their data is read.
+///
+/// Note: the total number of parts in a form can be limited using `MultipartFormConfig`.
+///
+/// ```
+/// use actix_multipart::form::{tempfile::TempFile, MultipartForm};
+///
+/// #[derive(MultipartForm)]
+/// struct Form {
+///     #[multipart(max_items = 10)]
+///     files: Vec<TempFile>,
+/// }
+/// ```
+///
+/// # Validation
+///
+/// You can use the `#[multipart(validate = "<path>")]` attribute to run a function after each part
+/// of a field has been read. The function receives a reference to the part's value (e.g., `&T` for
+/// both `T` and `Vec<T>` fields) and returns `Result<(), E>`, where `E: Into<actix_web::Error>`.
+/// Async functions can be used with `#[multipart(validate_async = "<path>")]` instead. Both
+/// attributes can be repeated.
+///
+/// Validation failures are returned as a `MultipartError::Validation` error, which responds with
+/// `422 Unprocessable Entity`.
+///
+/// ```
+/// use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+/// use actix_web::error::{Error, ErrorUnprocessableEntity};
+///
+/// fn not_empty(text: &Text<String>) -> Result<(), Error> {
+///     if text.is_empty() {
+///         return Err(ErrorUnprocessableEntity("must not be empty"));
+///     }
+///
+///     Ok(())
+/// }
+///
+/// async fn not_truncated(file: &TempFile) -> Result<(), Error> {
+///     if file.size < 8 {
+///         return Err(ErrorUnprocessableEntity("image is truncated"));
+///     }
+///
+///     Ok(())
+/// }
+///
+/// #[derive(MultipartForm)]
+/// struct Form {
+///     #[multipart(validate = "not_empty")]
+///     title: Text<String>,
+///
+///     #[multipart(validate_async = "not_truncated")]
+///     images: Vec<TempFile>,
+/// }
+/// ```
+///
+/// # Content-Type Restrictions
+///
+/// You can use the `#[multipart(content_type = "<pattern>")]` attribute to restrict the media types
+/// accepted for a field. Patterns are of the form `type/subtype`, where either part may be `*`. The
+/// attribute can be repeated to allow several patterns.
+///
+/// Parts with a disallowed media type are rejected with a
+/// `MultipartError::ContentTypeNotAllowed` error before any of their data is read. Parts without
+/// a Content-Type header are treated as `text/plain`, as per [RFC 7578 §4.4].
+///
+/// ```
+/// use actix_multipart::form::{tempfile::TempFile, MultipartForm};
+///
+/// #[derive(MultipartForm)]
+/// struct Form {
+///     #[multipart(content_type = "image/*")]
+///     avatar: TempFile,
+///
+///     #[multipart(content_type = "application/pdf", content_type = "text/plain")]
+///     documents: Vec<TempFile>,
+/// }
+/// ```
+///
+/// # Flattened Forms
+///
+/// A field whose type also derives `MultipartForm` can be marked with `#[multipart(flatten)]` to
+/// merge its fields into the parent form. An optional `prefix` can be given to namespace the nested
+/// fields; the prefix is prepended verbatim to each nested field name.
+///
+/// Flattened fields cannot be combined with any other field attributes except `prefix`; set those on
+/// the nested form's fields instead.
+///
+/// ```
+/// use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+///
+/// #[derive(MultipartForm)]
+/// struct Address {
+///     street: Text<String>,
+///     city: Text<String>,
+/// }
+///
+/// #[derive(MultipartForm)]
+/// struct Form {
+///     name: Text<String>,
+///
+///     // receives `address.street` and `address.city` fields
+///     #[multipart(flatten, prefix = "address.")]
+///     address: Address,
+/// }
+/// ```
+///
+/// # Enums
+///
+/// `MultipartForm` can also be derived for enums with named-field or unit variants. The value of a
+/// text field, named using the `#[multipart(tag = "<name>")]` attribute, selects the variant and the
+/// remaining fields populate it. By default the tag value must match the variant name; this can be
+/// changed using `#[multipart(rename = "<value>")]` on the variant.
+///
+/// Fields are dispatched to the selected variant as they are received, so the tag field must be sent
+/// before the fields of its variant. An unrecognized tag value results in a
+/// `MultipartError::UnknownTag` error.
+///
+/// ```
+/// use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+///
+/// #[derive(MultipartForm)]
+/// #[multipart(tag = "kind")]
+/// enum Attachment {
+///     #[multipart(rename = "file")]
+///     File { file: TempFile },
+///
+///     #[multipart(rename = "link")]
+///     Link { url: Text<String> },
+///
+///     #[multipart(rename = "none")]
+///     Nothing,
+/// }
+/// ```
+///
+/// # Ordered Fields
+///
+/// The `#[multipart(ordered)]` attribute requires fields to arrive in the order they are declared.
+/// A field received after one declared below it results in a `MultipartError::FieldOutOfOrder`
+/// error, and a field received before a required (i.e., not `Option` or `Vec`) field declared above
+/// it results in a `MultipartError::MissingField` error. Both errors are raised before any of the
+/// offending part's data is read.
+///
+/// Combined with validators, this allows metadata fields to be checked before a large file part
+/// is accepted. Unknown fields are not subject to ordering and flattened fields are not supported
+/// in ordered forms.
+///
+/// ```
+/// use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+///
+/// #[derive(MultipartForm)]
+/// #[multipart(ordered)]
+/// struct Form {
+///     token: Text<String>,
+///     description: Option<Text<String>>,
+///     file: TempFile,
+/// }
+/// ```
+///
 /// # Unknown Fields
 ///
 /// By default fields with an unknown name are ignored. They can be rejected using the
@@ -151,44 +358,243 @@ struct ParsedField<'t> {
 /// ```
 ///
 /// [parse_size]: https://docs.rs/parse-size/1/parse_size
+/// [RFC 7578 §4.4]: https://datatracker.ietf.org/doc/html/rfc7578#section-4.4
 #[proc_macro_derive(MultipartForm, attributes(multipart))]
 pub fn impl_multipart_form(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input: syn::DeriveInput = parse_macro_input!(input);
 
     let name = &input.ident;
 
-    let data_struct = match &input.data {
-        syn::Data::Struct(data_struct) => data_struct,
-        _ => {
-            return compile_err(syn::Error::new(
-                input.ident.span(),
-                "`MultipartForm` can only be derived for structs",
-            ))
-        }
+    let attrs = match MultipartFormAttrs::from_derive_input(&input) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.write_errors().into(),
     };
 
-    let fields = match &data_struct.fields {
-        syn::Fields::Named(fields_named) => fields_named,
-        _ => {
+    // Return value when a field name is not supported by the form
+    let unknown_field_result = if attrs.deny_unknown_fields {
+        quote!(::std::result::Result::Err(
+            ::actix_multipart::MultipartError::UnknownField(field.name().unwrap().to_string())
+        ))
+    } else {
+        quote!(::std::result::Result::Ok(()))
+    };
+
+    // Value for duplicate action
+    let duplicate_field = match attrs.duplicate_field {
+        DuplicateField::Ignore => quote!(::actix_multipart::form::DuplicateField::Ignore),
+        DuplicateField::Deny => quote!(::actix_multipart::form::DuplicateField::Deny),
+        DuplicateField::Replace => quote!(::actix_multipart::form::DuplicateField::Replace),
+    };
+
+    let form_impl = match &input.data {
+        syn::Data::Struct(data_struct) => {
+            if attrs.tag.is_some() {
+                return compile_err(syn::Error::new(
+                    input.ident.span(),
+                    "`tag` can only be used when deriving `MultipartForm` for enums",
+                ));
+            }
+
+            let fields = match &data_struct.fields {
+                syn::Fields::Named(fields_named) => fields_named,
+                _ => {
+                    let msg = "`MultipartForm` can only be derived for a struct with named fields";
+                    return compile_err(syn::Error::new(input.ident.span(), msg));
+                }
+            };
+
+            let parsed = match parse_fields(fields.named.iter(), attrs.ordered) {
+                Ok(parsed) => parsed,
+                Err(err) => return err,
+            };
+
+            form_impl(
+                &parsed,
+                quote!(Self),
+                &duplicate_field,
+                &unknown_field_result,
+                attrs.ordered,
+            )
+        }
+
+        syn::Data::Enum(data_enum) => {
+            let tag = match attrs.tag {
+                Some(tag) => tag,
+                None => {
+                    let msg =
+                        "`MultipartForm` enums require a `#[multipart(tag = \"...\")]` attribute";
+                    return compile_err(syn::Error::new(input.ident.span(), msg));
+                }
+            };
+
+            if data_enum.variants.is_empty() {
+                return compile_err(syn::Error::new(
+                    input.ident.span(),
+                    "`MultipartForm` can not be derived for enums without variants",
+                ));
+            }
+
+            let mut variants = Vec::with_capacity(data_enum.variants.len());
+            let mut set = HashSet::new();
+
+            for variant in &data_enum.variants {
+                let variant_attrs = match VariantAttrs::from_variant(variant) {
+                    Ok(attrs) => attrs,
+                    Err(err) => return err.write_errors().into(),
+                };
+
+                let fields = match &variant.fields {
+                    syn::Fields::Named(fields_named) => fields_named.named.iter().collect(),
+                    syn::Fields::Unit => Vec::new(),
+                    syn::Fields::Unnamed(_) => {
+                        let msg = "`MultipartForm` enum variants must have named fields or be unit variants";
+                        return compile_err(syn::Error::new(variant.ident.span(), msg));
+                    }
+                };
+
+                let parsed = match parse_fields(fields.into_iter(), attrs.ordered) {
+                    Ok(parsed) => parsed,
+                    Err(err) => return err,
+                };
+
+                if parsed.iter().any(|field| field.serialization_name == tag) {
+                    return compile_err(syn::Error::new(
+                        variant.ident.span(),
+                        format!("Field name conflicts with the enum tag: `{}`", tag),
+                    ));
+                }
+
+                let serialization_name = variant_attrs
+                    .rename
+                    .unwrap_or_else(|| variant.ident.to_string());
+
+                if !set.insert(serialization_name.clone()) {
+                    return compile_err(syn::Error::new(
+                        variant.ident.span(),
+                        format!("Multiple variants named: `{}`", serialization_name),
+                    ));
+                }
+
+                variants.push((serialization_name, &variant.ident, parsed));
+            }
+
+            enum_impl(
+                &tag,
+                &variants,
+                &duplicate_field,
+                &unknown_field_result,
+                attrs.ordered,
+            )
+        }
+
+        syn::Data::Union(_) => {
             return compile_err(syn::Error::new(
                 input.ident.span(),
-                "`MultipartForm` can only be derived for a struct with named fields",
+                "`MultipartForm` can only be derived for structs and enums",
             ))
         }
     };
 
-    let attrs = match MultipartFormAttrs::from_derive_input(&input) {
-        Ok(attrs) => attrs,
-        Err(err) => return err.write_errors().into(),
+    let FormImpl {
+        limit,
+        accepts_field,
+        handle_field,
+        from_state,
+    } = form_impl;
+
+    let gen = quote! {
+        impl ::actix_multipart::form::MultipartCollect for #name {
+            fn limit(field_name: &str) -> ::std::option::Option<usize> {
+                #limit
+            }
+
+            fn accepts_field(field_name: &str) -> bool {
+                #accepts_field
+            }
+
+            fn handle_field<'t>(
+                req: &'t ::actix_web::HttpRequest,
+                field: ::actix_multipart::Field,
+                limits: &'t mut ::actix_multipart::form::Limits,
+                state: &'t mut ::actix_multipart::form::State,
+            ) -> ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = ::std::result::Result<(), ::actix_multipart::MultipartError>> + 't>> {
+                #handle_field
+            }
+
+            fn from_state(mut state: ::actix_multipart::form::State) -> ::std::result::Result<Self, ::actix_multipart::MultipartError> {
+                #from_state
+            }
+
+        }
     };
+    gen.into()
+}
+
+/// Expressions making up the body of each `MultipartCollect` method.
+struct FormImpl {
+    limit: TokenStream2,
+    accepts_field: TokenStream2,
+    handle_field: TokenStream2,
+    from_state: TokenStream2,
+}
 
-    // Parse the field attributes
-    let parsed = match fields
-        .named
-        .iter()
+/// Parses the attributes of a set of named fields.
+fn parse_fields<'t>(
+    fields: impl Iterator<Item = &'t syn::Field>,
+    ordered: bool,
+) -> Result<Vec<ParsedField<'t>>, TokenStream> {
+    let parsed = fields
         .map(|field| {
             let rust_name = field.ident.as_ref().unwrap();
             let attrs = FieldAttrs::from_field(field).map_err(|err| err.write_errors())?;
+
+            if attrs.prefix.is_some() && !attrs.flatten {
+                return Err(compile_err(syn::Error::new(
+                    rust_name.span(),
+                    "`prefix` can only be used on `flatten` fields",
+                )));
+            }
+
+            if attrs.flatten
+                && (attrs.rename.is_some()
+                    || attrs.limit.is_some()
+                    || !attrs.content_type.is_empty()
+                    || attrs.max_items.is_some()
+                    || !attrs.validate.is_empty()
+                    || !attrs.validate_async.is_empty()
+                    || attrs.default.is_some())
+            {
+                return Err(compile_err(syn::Error::new(
+                    rust_name.span(),
+                    "`flatten` can only be combined with the `prefix` attribute",
+                )));
+            }
+
+            if attrs.default.is_some() && (is_vec(&field.ty) || is_option(&field.ty)) {
+                return Err(compile_err(syn::Error::new(
+                    rust_name.span(),
+                    "`default` can not be used on `Option` or `Vec` fields",
+                )));
+            }
+
+            if attrs.flatten && ordered {
+                return Err(compile_err(syn::Error::new(
+                    rust_name.span(),
+                    "`flatten` can not be used in `ordered` forms",
+                )));
+            }
+
+            if attrs.max_items.is_some() && !is_vec(&field.ty) {
+                return Err(compile_err(syn::Error::new(
+                    rust_name.span(),
+                    "`max_items` can only be used on `Vec` fields",
+                )));
+            }
+
+            let flatten = attrs
+                .flatten
+                .then(|| attrs.prefix.clone().unwrap_or_default());
+
             let serialization_name = attrs.rename.unwrap_or_else(|| rust_name.to_string());
 
             let limit = match attrs.limit.map(|limit| match parse_size(&limit) {
@@ -202,49 +608,67 @@ pub fn impl_multipart_form(input: proc_macro::TokenStream) -> proc_macro::TokenS
                 limit => limit.map(Result::unwrap),
             };
 
+            if let Some(pattern) = attrs
+                .content_type
+                .iter()
+                .find(|pattern| !is_valid_content_type_pattern(pattern))
+            {
+                return Err(compile_err(syn::Error::new(
+                    field.ident.as_ref().unwrap().span(),
+                    format!("Could not parse content type pattern `{}`", pattern),
+                )));
+            }
+
             Ok(ParsedField {
                 serialization_name,
                 rust_name,
                 limit,
+                content_types: attrs.content_type,
+                max_items: attrs.max_items,
+                validators: attrs.validate,
+                async_validators: attrs.validate_async,
+                default: attrs.default.map(|default| match default {
+                    Override::Inherit => quote!(::std::default::Default::default()),
+                    Override::Explicit(expr) => quote!(#expr),
+                }),
+                flatten,
                 ty: &field.ty,
             })
         })
-        .collect::<Result<Vec<_>, TokenStream>>()
-    {
-        Ok(attrs) => attrs,
-        Err(err) => return err,
-    };
+        .collect::<Result<Vec<_>, TokenStream>>()?;
 
     // Check that field names are unique
     let mut set = HashSet::new();
-    for field in &parsed {
+    for field in parsed.iter().filter(|field| field.flatten.is_none()) {
         if !set.insert(field.serialization_name.clone()) {
-            return compile_err(syn::Error::new(
+            return Err(compile_err(syn::Error::new(
                 field.rust_name.span(),
                 format!("Multiple fields named: `{}`", field.serialization_name),
-            ));
+            )));
         }
     }
 
-    // Return value when a field name is not supported by the form
-    let unknown_field_result = if attrs.deny_unknown_fields {
-        quote!(::std::result::Result::Err(
-            ::actix_multipart::MultipartError::UnknownField(field.name().unwrap().to_string())
-        ))
-    } else {
-        quote!(::std::result::Result::Ok(()))
-    };
+    Ok(parsed)
+}
 
-    // Value for duplicate action
-    let duplicate_field = match attrs.duplicate_field {
-        DuplicateField::Ignore => quote!(::actix_multipart::form::DuplicateField::Ignore),
-        DuplicateField::Deny => quote!(::actix_multipart::form::DuplicateField::Deny),
-        DuplicateField::Replace => quote!(::actix_multipart::form::DuplicateField::Replace),
-    };
+/// Generates the `MultipartCollect` method bodies for a set of named fields, constructing the
+/// value using `path` (e.g., `Self` or `Self::Variant`).
+///
+/// The generated expressions refer to the method arguments by name and never return early, so
+/// they can be embedded in the implementation for enums.
+fn form_impl(
+    fields: &[ParsedField<'_>],
+    path: TokenStream2,
+    duplicate_field: &TokenStream2,
+    unknown_field_result: &TokenStream2,
+    ordered: bool,
+) -> FormImpl {
+    let (flattened, fields): (Vec<_>, Vec<_>) =
+        fields.iter().partition(|field| field.flatten.is_some());
 
     // limit() implementation
     let mut limit_impl = quote!();
-    for field in &parsed {
+    for field in &fields {
         let name = &field.serialization_name;
         if let Some(value) = field.limit {
             limit_impl.extend(quote!(
@@ -253,60 +677,308 @@ pub fn impl_multipart_form(input: proc_macro::TokenStream) -> proc_macro::TokenS
         }
     }
 
+    // accepts_field() implementation
+    let mut accepts_impl = quote!();
+    for field in &fields {
+        let name = &field.serialization_name;
+        accepts_impl.extend(quote!(
+            #name => true,
+        ));
+    }
+
     // handle_field() implementation
     let mut handle_field_impl = quote!();
-    for field in &parsed {
+    for (position, field) in fields.iter().enumerate() {
         let name = &field.serialization_name;
         let ty = &field.ty;
 
-        handle_field_impl.extend(quote!(
-            #name => ::std::boxed::Box::pin(
-                <#ty as ::actix_multipart::form::FieldGroupReader>::handle_field(req, field, limits, state, #duplicate_field)
-            ),
-        ));
+        let handle_field = if field.validators.is_empty() && field.async_validators.is_empty() {
+            quote!(
+                ::std::boxed::Box::pin(
+                    <#ty as ::actix_multipart::form::FieldGroupReader>::handle_field(req, field, limits, state, #duplicate_field)
+                )
+            )
+        } else {
+            let validators = &field.validators;
+            let async_validators = &field.async_validators;
+
+            quote!(
+                ::std::boxed::Box::pin(async move {
+                    <#ty as ::actix_multipart::form::FieldGroupReader>::handle_field(req, field, &mut *limits, &mut *state, #duplicate_field).await?;
+
+                    if let ::std::option::Option::Some(item) = <#ty as ::actix_multipart::form::FieldGroupReader>::last_item(#name, state) {
+                        #(
+                            ::actix_multipart::form::validation_result(#name, #validators(item))?;
+                        )*
+                        #(
+                            ::actix_multipart::form::validation_result(#name, #async_validators(item).await)?;
+                        )*
+                    }
+
+                    ::std::result::Result::<(), ::actix_multipart::MultipartError>::Ok(())
+                })
+            )
+        };
+
+        // checks run before the field is read, each evaluating to `Result<(), MultipartError>`
+        let mut checks = Vec::new();
+
+        if ordered {
+            let preceding_required = fields[..position]
+                .iter()
+                .filter(|field| {
+                    field.default.is_none() && !is_vec(field.ty) && !is_option(field.ty)
+                })
+                .map(|field| &field.serialization_name);
+
+            checks.push(quote!(
+                ::actix_multipart::form::check_field_order(state, #name, #position, &[#(#preceding_required),*])
+            ));
+        }
+
+        if !field.content_types.is_empty() {
+            let patterns = &field.content_types;
+            checks.push(quote!(
+                ::actix_multipart::form::check_content_type(&field, &[#(#patterns),*])
+            ));
+        }
+
+        if let Some(max_items) = field.max_items {
+            checks.push(quote!(
+                ::actix_multipart::form::check_item_count(state, #name, #max_items)
+            ));
+        }
+
+        if checks.is_empty() {
+            handle_field_impl.extend(quote!(
+                #name => #handle_field,
+            ));
+        } else {
+            handle_field_impl.extend(quote!(
+                #name => match ::std::result::Result::Ok(()) #(.and_then(|()| #checks))* {
+                    ::std::result::Result::Ok(()) => #handle_field,
+                    ::std::result::Result::Err(err) => ::std::boxed::Box::pin(::std::future::ready(::std::result::Result::Err(err))),
+                },
+            ));
+        }
     }
 
     // from_state() implementation
     let mut from_state_impl = quote!();
-    for field in &parsed {
+    for field in &fields {
         let name = &field.serialization_name;
         let rust_name = &field.rust_name;
         let ty = &field.ty;
+
+        let value = quote!(
+            <#ty as ::actix_multipart::form::FieldGroupReader>::from_state(#name, &mut state)?
+        );
+
+        from_state_impl.extend(match &field.default {
+            Some(default) => quote!(
+                #rust_name: if state.contains_key(#name) { #value } else { #default },
+            ),
+            None => quote!(
+                #rust_name: #value,
+            ),
+        });
+    }
+
+    // Dispatch to flattened forms, used in the fallback arms of the generated `match`es
+    let mut flatten_limit_impl = quote!();
+    let mut flatten_accepts_impl = quote!();
+    let mut flatten_handle_field_impl = quote!();
+    for field in &flattened {
+        let prefix = field.flatten.as_deref().unwrap();
+        let rust_name = &field.rust_name;
+        let ty = &field.ty;
+        let key = format!("\0flatten.{}", rust_name);
+
+        flatten_limit_impl.extend(quote!(
+            if let ::std::option::Option::Some(inner_name) = field_name
+                .strip_prefix(#prefix)
+                .filter(|inner_name| <#ty as ::actix_multipart::form::MultipartCollect>::accepts_field(inner_name))
+            {
+                <#ty as ::actix_multipart::form::MultipartCollect>::limit(inner_name)
+            } else
+        ));
+
+        flatten_accepts_impl.extend(quote!(
+            field_name
+                .strip_prefix(#prefix)
+                .map_or(false, <#ty as ::actix_multipart::form::MultipartCollect>::accepts_field) ||
+        ));
+
+        flatten_handle_field_impl.extend(quote!(
+            if let ::std::option::Option::Some(inner_name) = ::actix_multipart::form::field_name(&field)
+                .strip_prefix(#prefix)
+                .filter(|inner_name| <#ty as ::actix_multipart::form::MultipartCollect>::accepts_field(inner_name))
+                .map(::std::borrow::ToOwned::to_owned)
+            {
+                let field = ::actix_multipart::form::rename_field(field, inner_name);
+                <#ty as ::actix_multipart::form::MultipartCollect>::handle_field(req, field, limits, state.nested(#key))
+            } else
+        ));
+
         from_state_impl.extend(quote!(
-            #rust_name: <#ty as ::actix_multipart::form::FieldGroupReader>::from_state(#name, &mut state)?,
+            #rust_name: <#ty as ::actix_multipart::form::MultipartCollect>::from_state(state.take_nested(#key))?,
         ));
     }
 
-    let gen = quote! {
-        impl ::actix_multipart::form::MultipartCollect for #name {
-            fn limit(field_name: &str) -> ::std::option::Option<usize> {
-                match field_name {
-                    #limit_impl
-                    _ => None,
-                }
+    FormImpl {
+        limit: quote!(
+            match field_name {
+                #limit_impl
+                _ => #flatten_limit_impl { ::std::option::Option::None },
+            }
+        ),
+        accepts_field: quote!(
+            match field_name {
+                #accepts_impl
+                _ => #flatten_accepts_impl false,
+            }
+        ),
+        handle_field: quote!(
+            match ::actix_multipart::form::field_name(&field) {
+                #handle_field_impl
+                _ => #flatten_handle_field_impl {
+                    ::std::boxed::Box::pin(::std::future::ready(#unknown_field_result))
+                },
             }
+        ),
+        from_state: quote!(
+            ::std::result::Result::Ok(#path {
+                #from_state_impl
+            })
+        ),
+    }
+}
 
-            fn handle_field<'t>(
-                req: &'t ::actix_web::HttpRequest,
-                field: ::actix_multipart::Field,
-                limits: &'t mut ::actix_multipart::form::Limits,
-                state: &'t mut ::actix_multipart::form::State,
-            ) -> ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = ::std::result::Result<(), ::actix_multipart::MultipartError>> + 't>> {
-                match field.name().unwrap() {
+/// Generates the `MultipartCollect` method bodies for an enum, where the value of the `tag` field
+/// selects the variant that subsequent fields are dispatched to.
+fn enum_impl(
+    tag: &str,
+    variants: &[(String, &Ident, Vec<ParsedField<'_>>)],
+    duplicate_field: &TokenStream2,
+    unknown_field_result: &TokenStream2,
+    ordered: bool,
+) -> FormImpl {
+    let variant_names = variants.iter().map(|(name, _, _)| name).collect::<Vec<_>>();
+
+    let mut limit_impl = quote!();
+    let mut accepts_impl = quote!();
+    let mut handle_field_impl = quote!();
+    let mut from_state_impl = quote!();
+
+    for (name, ident, fields) in variants {
+        let key = format!("\0variant.{}", ident);
+
+        let FormImpl {
+            limit,
+            accepts_field,
+            handle_field,
+            from_state,
+        } = form_impl(
+            fields,
+            quote!(Self::#ident),
+            duplicate_field,
+            unknown_field_result,
+            ordered,
+        );
+
+        limit_impl.extend(quote!(
+            .or_else(|| #limit)
+        ));
+
+        accepts_impl.extend(quote!(
+            || #accepts_field
+        ));
+
+        handle_field_impl.extend(quote!(
+            ::std::option::Option::Some(#name) => {
+                let state = state.nested(#key);
+                #handle_field
+            }
+        ));
+
+        from_state_impl.extend(quote!(
+            #name => {
+                let mut state = state.take_nested(#key);
+                #from_state
+            }
+        ));
+    }
+
+    FormImpl {
+        limit: quote!(
+            ::std::option::Option::<usize>::None #limit_impl
+        ),
+        accepts_field: quote!(
+            field_name == #tag #accepts_impl
+        ),
+        handle_field: quote!(
+            if ::actix_multipart::form::field_name(&field) == #tag {
+                ::actix_multipart::form::read_tag(req, field, limits, state, &[#(#variant_names),*])
+            } else {
+                match ::actix_multipart::form::selected_tag(state).map(::std::string::String::as_str) {
                     #handle_field_impl
-                    _ => return ::std::boxed::Box::pin(::std::future::ready(#unknown_field_result)),
+                    _ if <Self as ::actix_multipart::form::MultipartCollect>::accepts_field(::actix_multipart::form::field_name(&field)) => {
+                        // variant fields must be sent after the tag
+                        ::std::boxed::Box::pin(::std::future::ready(::std::result::Result::Err(
+                            ::actix_multipart::MultipartError::MissingField(#tag.to_owned())
+                        )))
+                    }
+                    _ => ::std::boxed::Box::pin(::std::future::ready(#unknown_field_result)),
                 }
             }
-
-            fn from_state(mut state: ::actix_multipart::form::State) -> ::std::result::Result<Self, ::actix_multipart::MultipartError> {
-                Ok(Self {
-                    #from_state_impl
-                })
+        ),
+        from_state: quote!(
+            match ::actix_multipart::form::take_tag(&mut state, #tag)?.as_str() {
+                #from_state_impl
+                _ => ::std::unreachable!("tag value is validated when it is received"),
             }
+        ),
+    }
+}
 
-        }
+/// Checks that a content type pattern is of the form `type/subtype`, where either part may be `*`.
+///
+/// A wildcard type with a concrete subtype (e.g., `*/json`) is not a meaningful pattern.
+fn is_valid_content_type_pattern(pattern: &str) -> bool {
+    let is_token = |part: &str| {
+        part == "*"
+            || (!part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c)))
     };
-    gen.into()
+
+    match pattern.split_once('/') {
+        Some(("*", subtype)) => subtype == "*",
+        Some((ty, subtype)) => is_token(ty) && is_token(subtype),
+        None => false,
+    }
+}
+
+/// Checks whether a type is syntactically a `Vec<T>`.
+fn is_vec(ty: &Type) -> bool {
+    last_segment_is(ty, "Vec")
+}
+
+/// Checks whether a type is syntactically an `Option<T>`.
+fn is_option(ty: &Type) -> bool {
+    last_segment_is(ty, "Option")
+}
+
+fn last_segment_is(ty: &Type, ident: &str) -> bool {
+    match ty {
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == ident),
+        _ => false,
+    }
 }
 
 /// Transform a syn error into a token stream for returning.
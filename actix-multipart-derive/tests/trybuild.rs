@@ -13,4 +13,24 @@ fn compile_macros() {
 
     t.pass("tests/trybuild/size-limits.rs");
     t.compile_fail("tests/trybuild/size-limit-parse-fail.rs");
+
+    t.pass("tests/trybuild/content-type.rs");
+    t.compile_fail("tests/trybuild/content-type-parse-fail.rs");
+
+    t.pass("tests/trybuild/flatten.rs");
+    t.compile_fail("tests/trybuild/flatten-fail.rs");
+
+    t.pass("tests/trybuild/enum.rs");
+    t.compile_fail("tests/trybuild/enum-fail.rs");
+
+    t.pass("tests/trybuild/max-items.rs");
+    t.compile_fail("tests/trybuild/max-items-fail.rs");
+
+    t.pass("tests/trybuild/validate.rs");
+
+    t.pass("tests/trybuild/ordered.rs");
+    t.compile_fail("tests/trybuild/ordered-fail.rs");
+
+    t.pass("tests/trybuild/default.rs");
+    t.compile_fail("tests/trybuild/default-fail.rs");
 }
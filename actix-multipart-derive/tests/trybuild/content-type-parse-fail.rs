@@ -0,0 +1,21 @@
+use actix_multipart::form::{text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+struct Form {
+    #[multipart(content_type = "image")]
+    description: Text<String>,
+}
+
+#[derive(MultipartForm)]
+struct Form2 {
+    #[multipart(content_type = "*/json")]
+    description: Text<String>,
+}
+
+#[derive(MultipartForm)]
+struct Form3 {
+    #[multipart(content_type = "text/plain; charset=utf-8")]
+    description: Text<String>,
+}
+
+fn main() {}
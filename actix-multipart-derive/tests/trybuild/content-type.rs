@@ -0,0 +1,24 @@
+use actix_web::{web, App, Responder};
+
+use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+struct Form {
+    #[multipart(content_type = "text/plain")]
+    description: Text<String>,
+
+    #[multipart(content_type = "image/png", content_type = "image/*")]
+    files: Vec<TempFile>,
+
+    #[multipart(content_type = "*/*")]
+    anything: Option<TempFile>,
+}
+
+async fn handler(_form: MultipartForm<Form>) -> impl Responder {
+    "Hello World!"
+}
+
+#[actix_web::main]
+async fn main() {
+    App::new().default_service(web::to(handler));
+}
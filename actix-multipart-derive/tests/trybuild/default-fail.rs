@@ -0,0 +1,9 @@
+use actix_multipart::form::{text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+struct Form {
+    #[multipart(default)]
+    description: Option<Text<String>>,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use actix_web::{web, App, Responder};
+
+use actix_multipart::form::{text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+struct Form {
+    #[multipart(default)]
+    name: Text<String>,
+
+    #[multipart(default = "Text(20)")]
+    page_size: Text<u32>,
+}
+
+async fn handler(_form: MultipartForm<Form>) -> impl Responder {
+    "Hello World!"
+}
+
+#[actix_web::main]
+async fn main() {
+    App::new().route("/", web::post().to(handler));
+}
@@ -0,0 +1,20 @@
+use actix_multipart::form::{text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+enum Form {
+    Link { url: Text<String> },
+}
+
+#[derive(MultipartForm)]
+#[multipart(tag = "kind")]
+enum Form2 {
+    Link(Text<String>),
+}
+
+#[derive(MultipartForm)]
+#[multipart(tag = "kind")]
+enum Form3 {
+    Link { kind: Text<String> },
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+use actix_web::{web, App, Responder};
+
+use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+#[multipart(tag = "kind")]
+enum Form {
+    #[multipart(rename = "file")]
+    File {
+        #[multipart(limit = "512 MiB")]
+        file: TempFile,
+        description: Option<Text<String>>,
+    },
+
+    Link {
+        url: Text<String>,
+    },
+
+    Nothing,
+}
+
+async fn handler(_form: MultipartForm<Form>) -> impl Responder {
+    "Hello World!"
+}
+
+#[actix_web::main]
+async fn main() {
+    App::new().default_service(web::to(handler));
+}
@@ -0,0 +1,20 @@
+use actix_multipart::form::{text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+struct Address {
+    street: Text<String>,
+}
+
+#[derive(MultipartForm)]
+struct Form {
+    #[multipart(prefix = "address.")]
+    address: Address,
+}
+
+#[derive(MultipartForm)]
+struct Form2 {
+    #[multipart(flatten, limit = "2 KiB")]
+    address: Address,
+}
+
+fn main() {}
@@ -0,0 +1,34 @@
+use actix_web::{web, App, Responder};
+
+use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+struct Address {
+    street: Text<String>,
+    city: Option<Text<String>>,
+}
+
+#[derive(MultipartForm)]
+struct Attachments {
+    files: Vec<TempFile>,
+}
+
+#[derive(MultipartForm)]
+struct Form {
+    name: Text<String>,
+
+    #[multipart(flatten, prefix = "address.")]
+    address: Address,
+
+    #[multipart(flatten)]
+    attachments: Attachments,
+}
+
+async fn handler(_form: MultipartForm<Form>) -> impl Responder {
+    "Hello World!"
+}
+
+#[actix_web::main]
+async fn main() {
+    App::new().default_service(web::to(handler));
+}
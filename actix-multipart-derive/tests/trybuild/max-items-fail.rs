@@ -0,0 +1,9 @@
+use actix_multipart::form::{text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+struct Form {
+    #[multipart(max_items = 5)]
+    description: Text<String>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use actix_multipart::form::{text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+struct Meta {
+    token: Text<String>,
+}
+
+#[derive(MultipartForm)]
+#[multipart(ordered)]
+struct Form {
+    #[multipart(flatten)]
+    meta: Meta,
+}
+
+fn main() {}
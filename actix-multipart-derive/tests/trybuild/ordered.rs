@@ -0,0 +1,36 @@
+use actix_web::{web, App, Responder};
+
+use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+
+#[derive(MultipartForm)]
+#[multipart(ordered)]
+struct Form {
+    token: Text<String>,
+    description: Option<Text<String>>,
+    tags: Vec<Text<String>>,
+
+    #[multipart(limit = "512 MiB")]
+    file: TempFile,
+}
+
+#[derive(MultipartForm)]
+#[multipart(tag = "kind", ordered)]
+enum Upload {
+    File { token: Text<String>, file: TempFile },
+    Nothing,
+}
+
+async fn handler(_form: MultipartForm<Form>) -> impl Responder {
+    "Hello World!"
+}
+
+async fn enum_handler(_form: MultipartForm<Upload>) -> impl Responder {
+    "Hello World!"
+}
+
+#[actix_web::main]
+async fn main() {
+    App::new()
+        .route("/", web::post().to(handler))
+        .route("/enum", web::post().to(enum_handler));
+}
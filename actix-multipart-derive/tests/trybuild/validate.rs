@@ -0,0 +1,40 @@
+use actix_web::{error::ErrorUnprocessableEntity, web, App, Error, Responder};
+
+use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+
+fn not_empty(text: &Text<String>) -> Result<(), Error> {
+    if text.is_empty() {
+        return Err(ErrorUnprocessableEntity("must not be empty"));
+    }
+
+    Ok(())
+}
+
+async fn not_truncated(file: &TempFile) -> Result<(), Error> {
+    if file.size < 8 {
+        return Err(ErrorUnprocessableEntity("file is truncated"));
+    }
+
+    Ok(())
+}
+
+#[derive(MultipartForm)]
+struct Form {
+    #[multipart(validate = "not_empty")]
+    title: Text<String>,
+
+    #[multipart(validate = "not_empty")]
+    subtitle: Option<Text<String>>,
+
+    #[multipart(validate_async = "not_truncated")]
+    files: Vec<TempFile>,
+}
+
+async fn handler(_form: MultipartForm<Form>) -> impl Responder {
+    "Hello World!"
+}
+
+#[actix_web::main]
+async fn main() {
+    App::new().default_service(web::to(handler));
+}
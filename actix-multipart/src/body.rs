@@ -0,0 +1,444 @@
+//! Multipart response bodies.
+//!
+//! [`MultipartBody`] streams a sequence of [`Part`]s, each with its own headers and body, as a
+//! `multipart/*` message body. It can be returned from handlers directly, in which case the
+//! `Content-Type` header (including the boundary) is set automatically.
+//!
+//! # Examples
+//!
+//! ```
+//! use actix_multipart::body::{MultipartBody, Part};
+//! use actix_web::{get, http::header::ContentType, Responder};
+//!
+//! #[get("/bundle")]
+//! async fn bundle() -> impl Responder {
+//!     MultipartBody::mixed()
+//!         .part(Part::new("Quarterly report attached.").insert_header(ContentType::plaintext()))
+//!         .part(
+//!             Part::new(&b"%PDF-1.7 ..."[..])
+//!                 .insert_header(("content-type", "application/pdf"))
+//!                 .insert_header(("content-disposition", "attachment; filename=\"report.pdf\"")),
+//!         )
+//! }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    error::Error as StdError,
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    body::{BodySize, BoxBody, MessageBody},
+    error::HttpError,
+    http::{
+        header::{
+            self, ContentDisposition, DispositionParam, DispositionType, HeaderName, HeaderValue,
+            TryIntoHeaderPair,
+        },
+        StatusCode,
+    },
+    web::{BufMut as _, Bytes, BytesMut},
+    HttpRequest, HttpResponse, Responder,
+};
+use mime::Mime;
+use rand::{
+    distributions::{Alphanumeric, DistString as _},
+    thread_rng,
+};
+
+const CRLF: &[u8] = b"\r\n";
+const HYPHENS: &[u8] = b"--";
+
+/// A single part of a [`MultipartBody`].
+pub struct Part {
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: BoxBody,
+}
+
+impl Part {
+    /// Constructs a part with the given body and no headers.
+    pub fn new<B>(body: B) -> Self
+    where
+        B: MessageBody + 'static,
+    {
+        Self {
+            headers: Vec::new(),
+            body: BoxBody::new(body),
+        }
+    }
+
+    /// Constructs a `multipart/form-data` field with the given name.
+    pub fn form_data<B>(name: impl Into<String>, body: B) -> Self
+    where
+        B: MessageBody + 'static,
+    {
+        Self::new(body).insert_header((
+            header::CONTENT_DISPOSITION,
+            ContentDisposition {
+                disposition: DispositionType::FormData,
+                parameters: vec![DispositionParam::Name(name.into())],
+            },
+        ))
+    }
+
+    /// Inserts a header, replacing any existing header with the same name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the header name or value is invalid.
+    pub fn insert_header(mut self, header: impl TryIntoHeaderPair) -> Self {
+        let (name, value) = header
+            .try_into_pair()
+            .map_err(Into::<HttpError>::into)
+            .expect("invalid part header");
+
+        self.headers.retain(|(existing, _)| *existing != name);
+        self.headers.push((name, value));
+        self
+    }
+
+    /// Appends a header, keeping any existing headers with the same name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the header name or value is invalid.
+    pub fn append_header(mut self, header: impl TryIntoHeaderPair) -> Self {
+        let (name, value) = header
+            .try_into_pair()
+            .map_err(Into::<HttpError>::into)
+            .expect("invalid part header");
+
+        self.headers.push((name, value));
+        self
+    }
+
+    /// Returns the size of the delimiter and header block that precedes the part's body.
+    fn head_len(&self, boundary: &str) -> usize {
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, value)| name.as_str().len() + 2 + value.len() + 2)
+            .sum::<usize>();
+
+        2 + boundary.len() + 2 + headers + 2
+    }
+
+    /// Writes the delimiter and header block that precedes the part's body.
+    fn head(&self, boundary: &str) -> Bytes {
+        let mut buf = BytesMut::with_capacity(self.head_len(boundary));
+
+        buf.put(HYPHENS);
+        buf.put(boundary.as_bytes());
+        buf.put(CRLF);
+
+        for (name, value) in &self.headers {
+            buf.put(name.as_str().as_bytes());
+            buf.put(&b": "[..]);
+            buf.put(value.as_bytes());
+            buf.put(CRLF);
+        }
+
+        buf.put(CRLF);
+        buf.freeze()
+    }
+}
+
+impl fmt::Debug for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Part")
+            .field("headers", &self.headers)
+            .field("body", &self.body.size())
+            .finish()
+    }
+}
+
+/// A streaming `multipart/*` message body.
+///
+/// See the [module documentation](self) for usage.
+pub struct MultipartBody {
+    subtype: &'static str,
+    boundary: String,
+    parts: VecDeque<Part>,
+    state: State,
+}
+
+enum State {
+    /// Writing the head of the next part, or the close delimiter if there are none left.
+    Head,
+
+    /// Streaming the body of the current part.
+    Body(BoxBody),
+
+    /// All data has been written.
+    Done,
+}
+
+impl MultipartBody {
+    /// Constructs an empty `multipart/form-data` body with a random boundary.
+    pub fn form_data() -> Self {
+        Self::new("form-data")
+    }
+
+    /// Constructs an empty `multipart/mixed` body with a random boundary.
+    pub fn mixed() -> Self {
+        Self::new("mixed")
+    }
+
+    fn new(subtype: &'static str) -> Self {
+        Self {
+            subtype,
+            boundary: Alphanumeric.sample_string(&mut thread_rng(), 32),
+            parts: VecDeque::new(),
+            state: State::Head,
+        }
+    }
+
+    /// Sets the boundary used to delimit parts.
+    ///
+    /// The boundary must not occur in any of the parts. Random boundaries are unlikely to do so.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the boundary is not 1 to 70 characters long or contains characters not allowed by
+    /// [RFC 2046 §5.1.1].
+    ///
+    /// [RFC 2046 §5.1.1]: https://datatracker.ietf.org/doc/html/rfc2046#section-5.1.1
+    pub fn boundary(mut self, boundary: impl Into<String>) -> Self {
+        let boundary = boundary.into();
+
+        assert!(
+            (1..=70).contains(&boundary.len())
+                && !boundary.ends_with(' ')
+                && boundary
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&b)),
+            "invalid multipart boundary"
+        );
+
+        self.boundary = boundary;
+        self
+    }
+
+    /// Appends a part.
+    pub fn part(mut self, part: Part) -> Self {
+        self.parts.push_back(part);
+        self
+    }
+
+    /// Returns the boundary used to delimit parts.
+    pub fn get_boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Returns the media type of the body, including its boundary parameter.
+    pub fn content_type(&self) -> Mime {
+        format!("multipart/{}; boundary=\"{}\"", self.subtype, self.boundary)
+            .parse()
+            .expect("boundary should be valid")
+    }
+}
+
+impl fmt::Debug for MultipartBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartBody")
+            .field("subtype", &self.subtype)
+            .field("boundary", &self.boundary)
+            .field("parts", &self.parts)
+            .finish()
+    }
+}
+
+impl MessageBody for MultipartBody {
+    type Error = Box<dyn StdError>;
+
+    fn size(&self) -> BodySize {
+        if !matches!(self.state, State::Head) {
+            return BodySize::Stream;
+        }
+
+        let mut size = (2 + self.boundary.len() + 2 + 2) as u64;
+
+        for part in &self.parts {
+            size += (part.head_len(&self.boundary) + 2) as u64;
+
+            match part.body.size() {
+                BodySize::None => {}
+                BodySize::Sized(len) => size += len,
+                BodySize::Stream => return BodySize::Stream,
+            }
+        }
+
+        BodySize::Sized(size)
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        loop {
+            match &mut this.state {
+                State::Head => {
+                    let Some(part) = this.parts.pop_front() else {
+                        this.state = State::Done;
+
+                        let mut buf = BytesMut::with_capacity(2 + this.boundary.len() + 4);
+                        buf.put(HYPHENS);
+                        buf.put(this.boundary.as_bytes());
+                        buf.put(HYPHENS);
+                        buf.put(CRLF);
+
+                        return Poll::Ready(Some(Ok(buf.freeze())));
+                    };
+
+                    let head = part.head(&this.boundary);
+                    this.state = State::Body(part.body);
+
+                    return Poll::Ready(Some(Ok(head)));
+                }
+
+                State::Body(body) => match Pin::new(body).poll_next(cx) {
+                    Poll::Ready(Some(Ok(chunk))) if chunk.is_empty() => continue,
+                    Poll::Ready(Some(Ok(chunk))) => return Poll::Ready(Some(Ok(chunk))),
+                    Poll::Ready(Some(Err(err))) => {
+                        this.state = State::Done;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    Poll::Ready(None) => {
+                        this.state = State::Head;
+                        return Poll::Ready(Some(Ok(Bytes::from_static(CRLF))));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl Responder for MultipartBody {
+    type Body = Self;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let content_type = HeaderValue::from_str(self.content_type().as_ref())
+            .expect("multipart content type should be a valid header value");
+
+        let mut res = HttpResponse::with_body(StatusCode::OK, self);
+        res.headers_mut().insert(header::CONTENT_TYPE, content_type);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        body::{self, BodyStream},
+        http::header::{ContentType, HeaderMap},
+        test::TestRequest,
+    };
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+    use crate::Multipart;
+
+    fn bundle() -> MultipartBody {
+        MultipartBody::mixed()
+            .boundary("abbc761f78ff4d7cb7573b5a23f96ef0")
+            .part(Part::new("hello").insert_header(ContentType::plaintext()))
+            .part(Part::form_data("file", Bytes::from_static(b"data")))
+    }
+
+    #[actix_rt::test]
+    async fn writes_parts() {
+        let body = bundle();
+        assert_eq!(body.size(), BodySize::Sized(211));
+
+        let bytes = body::to_bytes(body).await.unwrap();
+        assert_eq!(
+            bytes,
+            "--abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+             content-type: text/plain; charset=utf-8\r\n\
+             \r\n\
+             hello\r\n\
+             --abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+             content-disposition: form-data; name=file\r\n\
+             \r\n\
+             data\r\n\
+             --abbc761f78ff4d7cb7573b5a23f96ef0--\r\n"
+        );
+        assert_eq!(bytes.len(), 211);
+    }
+
+    #[actix_rt::test]
+    async fn empty_body() {
+        let body = MultipartBody::form_data().boundary("b");
+        assert_eq!(body.size(), BodySize::Sized(7));
+        assert_eq!(body::to_bytes(body).await.unwrap(), "--b--\r\n");
+    }
+
+    #[actix_rt::test]
+    async fn streaming_parts() {
+        let chunks = stream::iter([
+            Ok::<_, std::io::Error>(Bytes::from_static(b"da")),
+            Ok(Bytes::from_static(b"ta")),
+        ]);
+
+        let body = MultipartBody::form_data()
+            .part(Part::form_data("text", "hello"))
+            .part(
+                Part::form_data("file", BodyStream::new(chunks))
+                    .insert_header(ContentType::octet_stream()),
+            );
+        assert_eq!(body.size(), BodySize::Stream);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_str(body.content_type().as_ref()).unwrap(),
+        );
+
+        // round-trip through the multipart parser
+        let bytes = body::to_bytes(body).await.unwrap();
+        let mut multipart = Multipart::new(&headers, stream::once(async { Ok(bytes) }));
+
+        let mut field = multipart.next().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("text"));
+        assert_eq!(field.next().await.unwrap().unwrap(), "hello");
+        assert!(field.next().await.is_none());
+        drop(field);
+
+        let mut field = multipart.next().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("file"));
+        assert_eq!(
+            field.content_type().unwrap(),
+            &mime::APPLICATION_OCTET_STREAM
+        );
+        assert_eq!(field.next().await.unwrap().unwrap(), "data");
+        assert!(field.next().await.is_none());
+        drop(field);
+
+        assert!(multipart.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn responder_sets_content_type() {
+        let req = TestRequest::default().to_http_request();
+        let res = bundle().respond_to(&req);
+
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "multipart/mixed; boundary=\"abbc761f78ff4d7cb7573b5a23f96ef0\""
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_boundary() {
+        let _ = MultipartBody::mixed().boundary("contains\"quote");
+    }
+}
@@ -52,6 +52,9 @@ pub enum Error {
     ContentDispositionNameMissing,
 
     /// Nested multipart is not supported.
+    ///
+    /// Fields with nested multipart bodies can be yielded instead using
+    /// [`Multipart::allow_nested()`](crate::Multipart::allow_nested()).
     #[display("Nested multipart is not supported")]
     Nested,
 
@@ -78,6 +81,14 @@ pub enum Error {
         source: actix_web::Error,
     },
 
+    /// Field failed validation (for fields that opted-in to validation).
+    #[display("Field failed validation: {name}")]
+    #[from(ignore)]
+    Validation {
+        name: String,
+        source: actix_web::Error,
+    },
+
     /// Duplicate field found (for structure that opted-in to denying duplicate fields).
     #[display("Duplicate field found: {_0}")]
     #[from(ignore)]
@@ -88,18 +99,174 @@ pub enum Error {
     #[from(ignore)]
     MissingField(#[error(not(source))] String),
 
+    /// Field's Content-Type is not allowed (for fields that opted-in to content type restrictions).
+    #[display("Content-Type of field `{name}` is not allowed: {content_type}")]
+    #[from(ignore)]
+    ContentTypeNotAllowed { name: String, content_type: String },
+
+    /// Form contains more parts than allowed by [`MultipartFormConfig::max_parts()`].
+    ///
+    /// [`MultipartFormConfig::max_parts()`]: crate::form::MultipartFormConfig::max_parts()
+    #[display("Form contains too many parts")]
+    TooManyParts,
+
+    /// Field received more parts than allowed (for fields that opted-in to item limits).
+    #[display("Too many items for field: {_0}")]
+    #[from(ignore)]
+    TooManyItems(#[error(not(source))] String),
+
+    /// Field was received after a field declared later (for structures that opted-in to ordered
+    /// fields).
+    #[display("Field received out of order: {_0}")]
+    #[from(ignore)]
+    FieldOutOfOrder(#[error(not(source))] String),
+
+    /// Tag field value did not select any variant (for enums deriving `MultipartForm`).
+    #[display("Unknown value for tag field `{name}`: {value}")]
+    #[from(ignore)]
+    UnknownTag { name: String, value: String },
+
     /// Unknown field (for structure that opted-in to denying unknown fields).
     #[display("Unknown field: {_0}")]
     #[from(ignore)]
     UnknownField(#[error(not(source))] String),
 }
 
+impl Error {
+    /// Returns the kind of this error.
+    ///
+    /// Payload errors caused by exceeding a size limit are reported as
+    /// [`ErrorKind::LimitExceeded`]; all other payload errors are reported as
+    /// [`ErrorKind::Payload`]. This also applies to field errors whose source is such a limit
+    /// error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::ContentTypeMissing => ErrorKind::ContentTypeMissing,
+            Error::ContentTypeParse => ErrorKind::ContentTypeParse,
+            Error::ContentTypeIncompatible => ErrorKind::ContentTypeIncompatible,
+            Error::BoundaryMissing => ErrorKind::BoundaryMissing,
+            Error::ContentDispositionMissing => ErrorKind::ContentDispositionMissing,
+            Error::ContentDispositionNameMissing => ErrorKind::ContentDispositionNameMissing,
+            Error::Nested => ErrorKind::Nested,
+            Error::Incomplete => ErrorKind::Incomplete,
+            Error::Parse(_) => ErrorKind::Parse,
+            Error::Payload(PayloadError::Overflow) => ErrorKind::LimitExceeded,
+            Error::Payload(_) => ErrorKind::Payload,
+            Error::NotConsumed => ErrorKind::NotConsumed,
+            Error::Field { source, .. } if is_limit_exceeded(source) => ErrorKind::LimitExceeded,
+            Error::Field { .. } => ErrorKind::Field,
+            Error::Validation { .. } => ErrorKind::Validation,
+            Error::DuplicateField(_) => ErrorKind::DuplicateField,
+            Error::MissingField(_) => ErrorKind::MissingField,
+            Error::ContentTypeNotAllowed { .. } => ErrorKind::ContentTypeNotAllowed,
+            Error::TooManyParts => ErrorKind::TooManyParts,
+            Error::TooManyItems(_) => ErrorKind::TooManyItems,
+            Error::FieldOutOfOrder(_) => ErrorKind::FieldOutOfOrder,
+            Error::UnknownTag { .. } => ErrorKind::UnknownTag,
+            Error::UnknownField(_) => ErrorKind::UnknownField,
+        }
+    }
+}
+
+/// Returns true if the source of a field error was caused by exceeding a size limit.
+fn is_limit_exceeded(source: &actix_web::Error) -> bool {
+    if let Some(err) = source.as_error::<Error>() {
+        return err.kind() == ErrorKind::LimitExceeded;
+    }
+
+    matches!(
+        source.as_error::<PayloadError>(),
+        Some(PayloadError::Overflow)
+    )
+}
+
+/// The kind of a [`MultipartError`](enum@Error), without any of its associated data.
+///
+/// Used to configure per-kind error responses using
+/// [`MultipartFormConfig::error_status()`] and [`MultipartFormConfig::error_renderer()`].
+///
+/// [`MultipartFormConfig::error_status()`]: crate::form::MultipartFormConfig::error_status()
+/// [`MultipartFormConfig::error_renderer()`]: crate::form::MultipartFormConfig::error_renderer()
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// See [`Error::ContentTypeMissing`].
+    ContentTypeMissing,
+
+    /// See [`Error::ContentTypeParse`].
+    ContentTypeParse,
+
+    /// See [`Error::ContentTypeIncompatible`].
+    ContentTypeIncompatible,
+
+    /// See [`Error::BoundaryMissing`].
+    BoundaryMissing,
+
+    /// See [`Error::ContentDispositionMissing`].
+    ContentDispositionMissing,
+
+    /// See [`Error::ContentDispositionNameMissing`].
+    ContentDispositionNameMissing,
+
+    /// See [`Error::Nested`].
+    Nested,
+
+    /// See [`Error::Incomplete`].
+    Incomplete,
+
+    /// See [`Error::Parse`].
+    Parse,
+
+    /// An [`Error::Payload`] caused by exceeding a size limit, i.e., [`PayloadError::Overflow`], or
+    /// an [`Error::Field`] caused by such an error.
+    LimitExceeded,
+
+    /// Any other [`Error::Payload`].
+    Payload,
+
+    /// See [`Error::NotConsumed`].
+    NotConsumed,
+
+    /// See [`Error::Field`].
+    Field,
+
+    /// See [`Error::Validation`].
+    Validation,
+
+    /// See [`Error::DuplicateField`].
+    DuplicateField,
+
+    /// See [`Error::MissingField`].
+    MissingField,
+
+    /// See [`Error::ContentTypeNotAllowed`].
+    ContentTypeNotAllowed,
+
+    /// See [`Error::TooManyParts`].
+    TooManyParts,
+
+    /// See [`Error::TooManyItems`].
+    TooManyItems,
+
+    /// See [`Error::FieldOutOfOrder`].
+    FieldOutOfOrder,
+
+    /// See [`Error::UnknownTag`].
+    UnknownTag,
+
+    /// See [`Error::UnknownField`].
+    UnknownField,
+}
+
 /// Return `BadRequest` for `MultipartError`.
 impl ResponseError for Error {
     fn status_code(&self) -> StatusCode {
         match &self {
             Error::Field { source, .. } => source.as_response_error().status_code(),
-            Error::ContentTypeIncompatible => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Error::ContentTypeIncompatible | Error::ContentTypeNotAllowed { .. } => {
+                StatusCode::UNSUPPORTED_MEDIA_TYPE
+            }
+            Error::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
             _ => StatusCode::BAD_REQUEST,
         }
     }
@@ -113,5 +280,51 @@ mod tests {
     fn test_multipart_error() {
         let resp = Error::BoundaryMissing.error_response();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp = Error::ContentTypeNotAllowed {
+            name: "avatar".to_owned(),
+            content_type: "text/plain".to_owned(),
+        }
+        .error_response();
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[test]
+    fn test_error_kind() {
+        assert_eq!(Error::BoundaryMissing.kind(), ErrorKind::BoundaryMissing);
+        assert_eq!(
+            Error::Payload(PayloadError::Overflow).kind(),
+            ErrorKind::LimitExceeded
+        );
+        assert_eq!(
+            Error::Payload(PayloadError::Incomplete(None)).kind(),
+            ErrorKind::Payload
+        );
+        assert_eq!(
+            Error::UnknownField("foo".to_owned()).kind(),
+            ErrorKind::UnknownField
+        );
+    }
+
+    #[test]
+    fn test_field_error_kind() {
+        let field_err = |source: actix_web::Error| Error::Field {
+            name: "file".to_owned(),
+            source,
+        };
+
+        assert_eq!(
+            field_err(Error::Payload(PayloadError::Overflow).into()).kind(),
+            ErrorKind::LimitExceeded
+        );
+        assert_eq!(
+            field_err(PayloadError::Overflow.into()).kind(),
+            ErrorKind::LimitExceeded
+        );
+        assert_eq!(field_err(Error::Incomplete.into()).kind(), ErrorKind::Field);
+        assert_eq!(
+            field_err(actix_web::error::ErrorInternalServerError("io")).kind(),
+            ErrorKind::Field
+        );
     }
 }
@@ -2,7 +2,7 @@ use std::{
     cell::RefCell,
     cmp, fmt,
     future::poll_fn,
-    mem,
+    io, mem,
     pin::Pin,
     rc::Rc,
     task::{ready, Context, Poll},
@@ -15,12 +15,14 @@ use actix_web::{
 };
 use derive_more::derive::{Display, Error};
 use futures_core::Stream;
+use futures_util::{StreamExt as _, TryStreamExt as _};
 use mime::Mime;
 
 use crate::{
     error::Error,
     payload::{PayloadBuffer, PayloadRef},
     safety::Safety,
+    Multipart,
 };
 
 /// Error type returned from [`Field::bytes()`] when field data is larger than limit.
@@ -113,6 +115,84 @@ impl Field {
         self.content_disposition()?.get_name()
     }
 
+    /// Returns the field's file name, if set, after sanitizing it with [`filename::sanitize()`].
+    ///
+    /// Returns `None` if the file name is not set or if nothing remains of it after sanitization.
+    ///
+    /// [`filename::sanitize()`]: crate::filename::sanitize()
+    pub fn sanitized_filename(&self) -> Option<String> {
+        crate::filename::sanitize(self.content_disposition()?.get_filename()?)
+    }
+
+    /// Converts a field containing a nested multipart body into a [`Multipart`] stream of its parts.
+    ///
+    /// This is typically used for `multipart/mixed` bodies nested inside a `multipart/form-data`
+    /// field, as in the file-set encoding described by [RFC 2388 §5.2]. The nested parts are not
+    /// required to have a Content-Disposition header and their [`name()`](Self::name) may be
+    /// `None`.
+    ///
+    /// Fields with nested multipart bodies are only yielded by [`Multipart`] streams that
+    /// [allow them](crate::Multipart::allow_nested()).
+    ///
+    /// The field's data has to be consumed through the returned stream before the next field of
+    /// the outer stream can be read.
+    ///
+    /// # Errors
+    ///
+    /// If the field's Content-Type is not a `multipart/*` type, or it is missing a boundary
+    /// parameter, the returned stream yields a single error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use actix_multipart::Multipart;
+    /// use futures_util::TryStreamExt as _;
+    ///
+    /// async fn handler(payload: Multipart) -> actix_web::Result<()> {
+    ///     let mut payload = payload.allow_nested(true);
+    ///
+    ///     while let Some(field) = payload.try_next().await? {
+    ///         let is_nested = field
+    ///             .content_type()
+    ///             .is_some_and(|ct| ct.type_() == mime::MULTIPART);
+    ///
+    ///         if is_nested {
+    ///             let mut files = field.into_multipart();
+    ///
+    ///             while let Some(mut file) = files.try_next().await? {
+    ///                 while let Some(_chunk) = file.try_next().await? {}
+    ///             }
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`Multipart`]: crate::Multipart
+    /// [RFC 2388 §5.2]: https://datatracker.ietf.org/doc/html/rfc2388#section-5.2
+    pub fn into_multipart(self) -> Multipart {
+        let content_type = match &self.content_type {
+            Some(ct) if ct.type_() == mime::MULTIPART => ct.clone(),
+            _ => return Multipart::from_error(Error::ContentTypeIncompatible),
+        };
+
+        let Some(boundary) = content_type.get_param(mime::BOUNDARY) else {
+            return Multipart::from_error(Error::BoundaryMissing);
+        };
+        let boundary = boundary.as_str().to_owned();
+
+        let stream = self.fuse().map_err(|err| match err {
+            Error::Payload(err) => err,
+            err => PayloadError::Incomplete(Some(io::Error::new(
+                io::ErrorKind::InvalidData,
+                err.to_string(),
+            ))),
+        });
+
+        Multipart::from_ct_and_boundary(content_type, boundary, stream)
+    }
+
     /// Collects the raw field data, up to `limit` bytes.
     ///
     /// # Errors
@@ -186,7 +266,19 @@ impl Stream for Field {
             return Poll::Pending;
         }
 
-        inner.poll(&this.safety)
+        let res = inner.poll(&this.safety);
+
+        if res.is_pending() {
+            if let Some(mut buffer) = inner
+                .payload
+                .as_ref()
+                .and_then(|payload| payload.get_mut(&this.safety))
+            {
+                buffer.poll_stream_stalled(cx)?;
+            }
+        }
+
+        res
     }
 }
 
@@ -329,7 +421,8 @@ impl InnerField {
                 // check if we have enough data for boundary detection
                 if cur + 4 > len {
                     if cur > 0 {
-                        Poll::Ready(Some(Ok(payload.buf.split_to(cur).freeze())))
+                        let size = cmp::min(cur, payload.max_chunk_size);
+                        Poll::Ready(Some(Ok(payload.buf.split_to(size).freeze())))
                     } else {
                         Poll::Pending
                     }
@@ -342,7 +435,8 @@ impl InnerField {
                     {
                         if cur != 0 {
                             // return buffer
-                            Poll::Ready(Some(Ok(payload.buf.split_to(cur).freeze())))
+                            let size = cmp::min(cur, payload.max_chunk_size);
+                            Poll::Ready(Some(Ok(payload.buf.split_to(size).freeze())))
                         } else {
                             pos = cur + 1;
                             continue;
@@ -354,7 +448,8 @@ impl InnerField {
                     }
                 }
             } else {
-                Poll::Ready(Some(Ok(payload.buf.split().freeze())))
+                let size = cmp::min(len, payload.max_chunk_size);
+                Poll::Ready(Some(Ok(payload.buf.split_to(size).freeze())))
             };
         }
     }
@@ -0,0 +1,218 @@
+//! Sanitization of client-provided file names.
+//!
+//! The `filename` parameter of a field's Content-Disposition header is chosen by the client and
+//! should never be used as a path on the server as-is. The functions in this module reduce it to a
+//! single, portable path component.
+//!
+//! # Examples
+//! ```
+//! use actix_multipart::filename;
+//!
+//! assert_eq!(filename::sanitize("../../etc/passwd").unwrap(), "passwd");
+//! assert_eq!(filename::sanitize("C:\\Users\\me\\report.pdf").unwrap(), "report.pdf");
+//! assert_eq!(filename::sanitize_ascii("Résumé.pdf").unwrap(), "Resume.pdf");
+//! assert!(filename::sanitize("..").is_none());
+//! ```
+
+/// Maximum length, in bytes, of a sanitized file name; the limit of most filesystems.
+const MAX_LEN: usize = 255;
+
+/// Device names that cannot be used as file names on Windows, regardless of extension.
+const WINDOWS_RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Sanitizes a client-provided file name.
+///
+/// - Only the final component of any `/` or `\` separated path is kept.
+/// - Control characters are removed.
+/// - Characters reserved on Windows (`<>:"|?*`) are replaced with `_`.
+/// - Leading and trailing dots and whitespace are trimmed.
+/// - Windows device names (e.g., `CON`, `LPT1`) are prefixed with `_`.
+/// - The result is truncated to 255 bytes, preserving the extension where possible.
+///
+/// Returns `None` if nothing remains of the name.
+pub fn sanitize(name: &str) -> Option<String> {
+    sanitize_with(name, Some)
+}
+
+/// Sanitizes a client-provided file name, additionally restricting it to ASCII.
+///
+/// Applies the same rules as [`sanitize()`]. Latin letters with diacritics and common ligatures are
+/// transliterated (e.g., `é` becomes `e` and `ß` becomes `ss`); other non-ASCII characters are
+/// replaced with `_`.
+pub fn sanitize_ascii(name: &str) -> Option<String> {
+    sanitize_with(name, |c| c.is_ascii().then_some(c))
+}
+
+fn sanitize_with(name: &str, ascii: impl Fn(char) -> Option<char>) -> Option<String> {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+
+    let mut sanitized = String::with_capacity(name.len());
+
+    for c in name.chars().filter(|c| !c.is_control()) {
+        match c {
+            '<' | '>' | ':' | '"' | '|' | '?' | '*' => sanitized.push('_'),
+            c => match ascii(c) {
+                Some(c) => sanitized.push(c),
+                None => match transliterate(c) {
+                    Some(replacement) => sanitized.push_str(replacement),
+                    None => sanitized.push('_'),
+                },
+            },
+        }
+    }
+
+    let trimmed = sanitized.trim_matches(|c: char| c == '.' || c.is_whitespace());
+
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let stem = trimmed.split('.').next().unwrap_or_default().trim_end();
+    let mut sanitized = if WINDOWS_RESERVED
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+    {
+        format!("_{trimmed}")
+    } else {
+        trimmed.to_owned()
+    };
+
+    truncate(&mut sanitized);
+
+    Some(sanitized)
+}
+
+/// Truncates a file name to [`MAX_LEN`] bytes, keeping a short extension intact.
+fn truncate(name: &mut String) {
+    if name.len() <= MAX_LEN {
+        return;
+    }
+
+    let ext = match name.rfind('.') {
+        Some(idx) if name.len() - idx <= 16 => name[idx..].to_owned(),
+        _ => String::new(),
+    };
+
+    let mut end = MAX_LEN - ext.len();
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    name.truncate(end);
+    name.push_str(&ext);
+}
+
+/// Returns an ASCII replacement for common Latin characters.
+fn transliterate(c: char) -> Option<&'static str> {
+    Some(match c {
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ð' | 'Ď' | 'Đ' => "D",
+        'ð' | 'ď' | 'đ' => "d",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' => "k",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => "N",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'Þ' => "TH",
+        'þ' => "th",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ý' | 'Ŷ' | 'Ÿ' => "Y",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_paths() {
+        assert_eq!(sanitize("a/b/c.txt").unwrap(), "c.txt");
+        assert_eq!(sanitize("..\\..\\boot.ini").unwrap(), "boot.ini");
+        assert_eq!(sanitize("/").as_deref(), None);
+        assert_eq!(sanitize("dir/").as_deref(), None);
+    }
+
+    #[test]
+    fn removes_control_and_reserved_chars() {
+        assert_eq!(sanitize("a\0b\r\nc.txt").unwrap(), "abc.txt");
+        assert_eq!(sanitize("what?.txt").unwrap(), "what_.txt");
+        assert_eq!(sanitize("a<b>:c|d*.txt").unwrap(), "a_b__c_d_.txt");
+    }
+
+    #[test]
+    fn trims_dots_and_whitespace() {
+        assert_eq!(sanitize(" .hidden ").unwrap(), "hidden");
+        assert_eq!(sanitize("file.txt. . ").unwrap(), "file.txt");
+        assert_eq!(sanitize("...").as_deref(), None);
+        assert_eq!(sanitize("").as_deref(), None);
+    }
+
+    #[test]
+    fn escapes_windows_device_names() {
+        assert_eq!(sanitize("CON").unwrap(), "_CON");
+        assert_eq!(sanitize("lpt1.txt").unwrap(), "_lpt1.txt");
+        assert_eq!(sanitize("CONSOLE.txt").unwrap(), "CONSOLE.txt");
+    }
+
+    #[test]
+    fn preserves_unicode() {
+        assert_eq!(sanitize("Résumé 履歴書.pdf").unwrap(), "Résumé 履歴書.pdf");
+    }
+
+    #[test]
+    fn ascii_transliteration() {
+        assert_eq!(sanitize_ascii("Résumé.pdf").unwrap(), "Resume.pdf");
+        assert_eq!(sanitize_ascii("Straße.txt").unwrap(), "Strasse.txt");
+        assert_eq!(sanitize_ascii("履歴書.pdf").unwrap(), "___.pdf");
+    }
+
+    #[test]
+    fn truncates_long_names() {
+        let name = format!("{}.tar.gz", "a".repeat(300));
+        let sanitized = sanitize(&name).unwrap();
+        assert_eq!(sanitized.len(), MAX_LEN);
+        assert!(sanitized.ends_with(".gz"));
+
+        let name = "é".repeat(200);
+        let sanitized = sanitize(&name).unwrap();
+        assert!(sanitized.len() <= MAX_LEN);
+        assert!(sanitized.chars().all(|c| c == 'é'));
+    }
+}
@@ -14,7 +14,7 @@ use crate::{
 };
 
 /// Deserialize from JSON.
-#[derive(Debug, Deref, DerefMut)]
+#[derive(Debug, Default, Deref, DerefMut)]
 pub struct Json<T: DeserializeOwned>(pub T);
 
 impl<T: DeserializeOwned> Json<T> {
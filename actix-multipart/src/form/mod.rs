@@ -7,15 +7,21 @@ use std::{
     sync::Arc,
 };
 
-use actix_web::{dev, error::PayloadError, web, Error, FromRequest, HttpRequest};
+use actix_web::{
+    dev,
+    error::{InternalError, PayloadError},
+    http::StatusCode,
+    web, Error, FromRequest, HttpRequest, HttpResponse,
+};
 use derive_more::derive::{Deref, DerefMut};
 use futures_core::future::LocalBoxFuture;
 use futures_util::{TryFutureExt as _, TryStreamExt as _};
 
-use crate::{Field, Multipart, MultipartError};
+use crate::{Field, Multipart, MultipartError, MultipartErrorKind};
 
 pub mod bytes;
 pub mod json;
+pub mod storage;
 #[cfg(feature = "tempfile")]
 pub mod tempfile;
 pub mod text;
@@ -49,6 +55,156 @@ pub trait FieldReader<'t>: Sized + Any {
 #[derive(Default, Deref, DerefMut)]
 pub struct State(pub HashMap<String, Box<dyn Any>>);
 
+impl State {
+    /// Returns the state of a flattened form stored under `key`, creating it if necessary.
+    #[doc(hidden)]
+    pub fn nested(&mut self, key: &str) -> &mut State {
+        self.0
+            .entry(key.to_owned())
+            .or_insert_with(|| Box::<State>::default())
+            .downcast_mut::<State>()
+            .unwrap()
+    }
+
+    /// Removes the state of a flattened form stored under `key`.
+    #[doc(hidden)]
+    pub fn take_nested(&mut self, key: &str) -> State {
+        self.0
+            .remove(key)
+            .map(|m| *m.downcast::<State>().unwrap())
+            .unwrap_or_default()
+    }
+}
+
+/// Counts a part received for `name`, raising an error if there are now more than `max_items`.
+#[doc(hidden)]
+pub fn check_item_count(
+    state: &mut State,
+    name: &str,
+    max_items: usize,
+) -> Result<(), MultipartError> {
+    let count = state
+        .entry(format!("\0items.{name}"))
+        .or_insert_with(|| Box::new(0_usize))
+        .downcast_mut::<usize>()
+        .unwrap();
+
+    *count += 1;
+
+    if *count > max_items {
+        Err(MultipartError::TooManyItems(name.to_owned()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Records that a field at `position` of an ordered form was received, raising an error if a field
+/// declared later has already been received or if a required field declared earlier is missing.
+#[doc(hidden)]
+pub fn check_field_order(
+    state: &mut State,
+    name: &str,
+    position: usize,
+    preceding_required: &[&str],
+) -> Result<(), MultipartError> {
+    let last = state
+        .entry("\0position".to_owned())
+        .or_insert_with(|| Box::new(0_usize))
+        .downcast_mut::<usize>()
+        .unwrap();
+
+    if *last > position {
+        return Err(MultipartError::FieldOutOfOrder(name.to_owned()));
+    }
+
+    *last = position;
+
+    match preceding_required
+        .iter()
+        .find(|required| !state.contains_key(**required))
+    {
+        Some(required) => Err(MultipartError::MissingField((*required).to_owned())),
+        None => Ok(()),
+    }
+}
+
+/// Converts the result of a field validator into a [`MultipartError::Validation`] error.
+#[doc(hidden)]
+pub fn validation_result<E>(name: &str, res: Result<(), E>) -> Result<(), MultipartError>
+where
+    E: Into<Error>,
+{
+    res.map_err(|err| MultipartError::Validation {
+        name: name.to_owned(),
+        source: err.into(),
+    })
+}
+
+/// Key under which the tag of an enum form is stored.
+const TAG_KEY: &str = "\0tag";
+
+/// Reads the tag field of an enum form, which must name one of `variants`.
+#[doc(hidden)]
+pub fn read_tag<'t>(
+    req: &'t HttpRequest,
+    field: Field,
+    limits: &'t mut Limits,
+    state: &'t mut State,
+    variants: &'static [&'static str],
+) -> LocalBoxFuture<'t, Result<(), MultipartError>> {
+    if state.contains_key(TAG_KEY) {
+        return Box::pin(ready(Err(MultipartError::DuplicateField(
+            field.form_field_name,
+        ))));
+    }
+
+    Box::pin(async move {
+        let name = field.form_field_name.clone();
+        let tag = text::Text::<String>::read_field(req, field, limits)
+            .await?
+            .into_inner();
+
+        if !variants.contains(&tag.as_str()) {
+            return Err(MultipartError::UnknownTag { name, value: tag });
+        }
+
+        state.insert(TAG_KEY.to_owned(), Box::new(tag));
+        Ok(())
+    })
+}
+
+/// Returns the tag of an enum form, if it has been received.
+#[doc(hidden)]
+pub fn selected_tag(state: &State) -> Option<&String> {
+    state
+        .get(TAG_KEY)
+        .and_then(|tag| tag.downcast_ref::<String>())
+}
+
+/// Removes the tag of an enum form, raising an error if it was not received.
+#[doc(hidden)]
+pub fn take_tag(state: &mut State, name: &str) -> Result<String, MultipartError> {
+    state
+        .remove(TAG_KEY)
+        .map(|tag| *tag.downcast::<String>().unwrap())
+        .ok_or_else(|| MultipartError::MissingField(name.to_owned()))
+}
+
+/// Returns the name that a field is dispatched by; see [`rename_field`].
+#[doc(hidden)]
+pub fn field_name(field: &Field) -> &str {
+    &field.form_field_name
+}
+
+/// Changes the name that a field is dispatched by, e.g., to strip the prefix of a flattened form.
+///
+/// The Content-Disposition header, and therefore [`Field::name()`], is left untouched.
+#[doc(hidden)]
+pub fn rename_field(mut field: Field, name: String) -> Field {
+    field.form_field_name = name;
+    field
+}
+
 /// Trait that the field collection types implement, i.e. `Vec<T>`, `Option<T>`, or `T` itself.
 #[doc(hidden)]
 pub trait FieldGroupReader<'t>: Sized + Any {
@@ -65,6 +221,12 @@ pub trait FieldGroupReader<'t>: Sized + Any {
 
     /// Construct `Self` from the group of processed fields.
     fn from_state(name: &str, state: &'t mut State) -> Result<Self, MultipartError>;
+
+    /// The type of each processed field in the group.
+    type Item;
+
+    /// Returns the most recently processed field in the group, if any.
+    fn last_item<'s>(name: &str, state: &'s State) -> Option<&'s Self::Item>;
 }
 
 impl<'t, T> FieldGroupReader<'t> for Option<T>
@@ -105,6 +267,12 @@ where
     fn from_state(name: &str, state: &'t mut State) -> Result<Self, MultipartError> {
         Ok(state.remove(name).map(|m| *m.downcast::<T>().unwrap()))
     }
+
+    type Item = T;
+
+    fn last_item<'s>(name: &str, state: &'s State) -> Option<&'s Self::Item> {
+        state.get(name).and_then(|m| m.downcast_ref::<T>())
+    }
 }
 
 impl<'t, T> FieldGroupReader<'t> for Vec<T>
@@ -142,6 +310,15 @@ where
             .map(|m| *m.downcast::<Vec<T>>().unwrap())
             .unwrap_or_default())
     }
+
+    type Item = T;
+
+    fn last_item<'s>(name: &str, state: &'s State) -> Option<&'s Self::Item> {
+        state
+            .get(name)
+            .and_then(|m| m.downcast_ref::<Vec<T>>())
+            .and_then(|vec| vec.last())
+    }
 }
 
 impl<'t, T> FieldGroupReader<'t> for T
@@ -185,6 +362,45 @@ where
             .map(|m| *m.downcast::<T>().unwrap())
             .ok_or_else(|| MultipartError::MissingField(name.to_owned()))
     }
+
+    type Item = T;
+
+    fn last_item<'s>(name: &str, state: &'s State) -> Option<&'s Self::Item> {
+        state.get(name).and_then(|m| m.downcast_ref::<T>())
+    }
+}
+
+/// Checks a field's Content-Type against a set of `type/subtype` patterns, either part of which may
+/// be a `*` wildcard.
+///
+/// Fields without a Content-Type header are treated as `text/plain`, as per [RFC 7578 §4.4].
+///
+/// [RFC 7578 §4.4]: https://datatracker.ietf.org/doc/html/rfc7578#section-4.4
+#[doc(hidden)]
+pub fn check_content_type(field: &Field, allowed: &[&str]) -> Result<(), MultipartError> {
+    let content_type = field.content_type().unwrap_or(&mime::TEXT_PLAIN);
+
+    let is_allowed = allowed.iter().any(|pattern| {
+        let Some((ty, subtype)) = pattern.split_once('/') else {
+            return false;
+        };
+
+        (ty == "*" || content_type.type_().as_str().eq_ignore_ascii_case(ty))
+            && (subtype == "*"
+                || content_type
+                    .subtype()
+                    .as_str()
+                    .eq_ignore_ascii_case(subtype))
+    });
+
+    if is_allowed {
+        Ok(())
+    } else {
+        Err(MultipartError::ContentTypeNotAllowed {
+            name: field.form_field_name.clone(),
+            content_type: content_type.essence_str().to_owned(),
+        })
+    }
 }
 
 /// Trait that allows a type to be used in the [`struct@MultipartForm`] extractor.
@@ -195,6 +411,15 @@ pub trait MultipartCollect: Sized {
     /// across all fields sharing the same name.
     fn limit(field_name: &str) -> Option<usize>;
 
+    /// Returns true if this form handles fields with the given name.
+    ///
+    /// This is used to dispatch fields to forms that have been flattened into another. The default
+    /// implementation accepts all fields.
+    #[doc(hidden)]
+    fn accepts_field(_field_name: &str) -> bool {
+        true
+    }
+
     /// The extractor will call this function for each incoming field, the state can be updated
     /// with the processed field data.
     fn handle_field<'t>(
@@ -226,6 +451,7 @@ pub struct Limits {
     pub total_limit_remaining: usize,
     pub memory_limit_remaining: usize,
     pub field_limit_remaining: Option<usize>,
+    progress: Option<ProgressTracker>,
 }
 
 impl Limits {
@@ -234,6 +460,16 @@ impl Limits {
             total_limit_remaining: total_limit,
             memory_limit_remaining: memory_limit,
             field_limit_remaining: None,
+            progress: None,
+        }
+    }
+
+    /// Resets per-field progress when a new field is started.
+    fn start_field(&mut self, field_name: &str) {
+        if let Some(ref mut progress) = self.progress {
+            progress.field_name.clear();
+            progress.field_name.push_str(field_name);
+            progress.field_bytes = 0;
         }
     }
 
@@ -269,10 +505,50 @@ impl Limits {
             );
         }
 
+        if let Some(ref mut progress) = self.progress {
+            progress.field_bytes += bytes;
+            progress.total_bytes += bytes;
+
+            (progress.handler)(
+                &progress.req,
+                Progress {
+                    field_name: &progress.field_name,
+                    field_bytes: progress.field_bytes,
+                    total_bytes: progress.total_bytes,
+                },
+            );
+        }
+
         Ok(())
     }
 }
 
+/// Upload progress of a [`struct@MultipartForm`] extraction.
+///
+/// See [`MultipartFormConfig::progress_handler()`].
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct Progress<'a> {
+    /// Name of the field currently being read.
+    pub field_name: &'a str,
+
+    /// Bytes read so far from the current field.
+    pub field_bytes: usize,
+
+    /// Bytes read so far from all fields in the form.
+    pub total_bytes: usize,
+}
+
+type ProgressHandler = Arc<dyn Fn(&HttpRequest, Progress<'_>) + Send + Sync>;
+
+struct ProgressTracker {
+    handler: ProgressHandler,
+    req: HttpRequest,
+    field_name: String,
+    field_bytes: usize,
+    total_bytes: usize,
+}
+
 /// Typed `multipart/form-data` extractor.
 ///
 /// To extract typed data from a multipart stream, the inner type `T` must implement the
@@ -303,23 +579,44 @@ where
     #[inline]
     fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
         let mut multipart = Multipart::from_req(req, payload);
+        let config = MultipartFormConfig::from_req(req);
 
         let content_type = match multipart.content_type_or_bail() {
             Ok(content_type) => content_type,
-            Err(err) => return Box::pin(ready(Err(err.into()))),
+            Err(err) => return Box::pin(ready(Err(config.map_error(req, err)))),
         };
 
         if content_type.subtype() != mime::FORM_DATA {
             // this extractor only supports multipart/form-data
-            return Box::pin(ready(Err(MultipartError::ContentTypeIncompatible.into())));
+            let err = MultipartError::ContentTypeIncompatible;
+            return Box::pin(ready(Err(config.map_error(req, err))));
         };
 
-        let config = MultipartFormConfig::from_req(req);
         let mut limits = Limits::new(config.total_limit, config.memory_limit);
 
+        if let Some(high_watermark) = config.buffer_high_watermark {
+            multipart = multipart.buffer_high_watermark(high_watermark);
+        }
+
+        if let Some(max_chunk_size) = config.max_chunk_size {
+            multipart = multipart.max_chunk_size(max_chunk_size);
+        }
+
+        limits.progress = config
+            .progress_handler
+            .clone()
+            .map(|handler| ProgressTracker {
+                handler,
+                req: req.clone(),
+                field_name: String::new(),
+                field_bytes: 0,
+                total_bytes: 0,
+            });
+
         let req = req.clone();
         let req2 = req.clone();
-        let err_handler = config.err_handler.clone();
+        let max_parts = config.max_parts;
+        let config = config.clone();
 
         Box::pin(
             async move {
@@ -328,18 +625,27 @@ where
                 // ensure limits are shared for all fields with this name
                 let mut field_limits = HashMap::<String, Option<usize>>::new();
 
+                let mut parts = 0;
+
                 while let Some(field) = multipart.try_next().await? {
                     debug_assert!(
                         !field.form_field_name.is_empty(),
                         "multipart form fields should have names",
                     );
 
+                    parts += 1;
+
+                    if max_parts.is_some_and(|max_parts| parts > max_parts) {
+                        return Err(MultipartError::TooManyParts);
+                    }
+
                     // Retrieve the limit for this field
                     let entry = field_limits
                         .entry(field.form_field_name.clone())
                         .or_insert_with(|| T::limit(&field.form_field_name));
 
                     limits.field_limit_remaining.clone_from(entry);
+                    limits.start_field(&field.form_field_name);
 
                     T::handle_field(&req, field, &mut limits, &mut state).await?;
 
@@ -350,13 +656,7 @@ where
                 let inner = T::from_state(state)?;
                 Ok(MultipartForm(inner))
             }
-            .map_err(move |err| {
-                if let Some(handler) = err_handler {
-                    (*handler)(err, &req2)
-                } else {
-                    err.into()
-                }
-            }),
+            .map_err(move |err| config.map_error(&req2, err)),
         )
     }
 }
@@ -364,6 +664,15 @@ where
 type MultipartFormErrorHandler =
     Option<Arc<dyn Fn(MultipartError, &HttpRequest) -> Error + Send + Sync>>;
 
+type MultipartFormErrorRenderer =
+    Arc<dyn Fn(&MultipartError, &HttpRequest) -> HttpResponse + Send + Sync>;
+
+#[derive(Clone)]
+enum ErrorResponse {
+    Status(StatusCode),
+    Render(MultipartFormErrorRenderer),
+}
+
 /// [`struct@MultipartForm`] extractor configuration.
 ///
 /// Add to your app data to have it picked up by [`struct@MultipartForm`] extractors.
@@ -371,7 +680,12 @@ type MultipartFormErrorHandler =
 pub struct MultipartFormConfig {
     total_limit: usize,
     memory_limit: usize,
+    max_parts: Option<usize>,
+    buffer_high_watermark: Option<usize>,
+    max_chunk_size: Option<usize>,
     err_handler: MultipartFormErrorHandler,
+    err_responses: Option<Vec<(MultipartErrorKind, ErrorResponse)>>,
+    progress_handler: Option<ProgressHandler>,
 }
 
 impl MultipartFormConfig {
@@ -387,7 +701,43 @@ impl MultipartFormConfig {
         self
     }
 
+    /// Sets maximum number of parts accepted in the form, including unknown fields. By default the
+    /// number of parts is not limited.
+    pub fn max_parts(mut self, max_parts: usize) -> Self {
+        self.max_parts = Some(max_parts);
+        self
+    }
+
+    /// Sets the buffer size at which reading from the request payload is paused until field readers
+    /// catch up. By default all immediately available data is buffered.
+    ///
+    /// See [`Multipart::buffer_high_watermark()`].
+    pub fn buffer_high_watermark(mut self, bytes: usize) -> Self {
+        self.buffer_high_watermark = Some(bytes);
+        self
+    }
+
+    /// Sets the maximum size of chunks passed to field readers. By default chunk sizes are not
+    /// limited.
+    ///
+    /// See [`Multipart::max_chunk_size()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is 0.
+    pub fn max_chunk_size(mut self, bytes: usize) -> Self {
+        assert!(bytes > 0, "maximum chunk size must be greater than 0");
+        self.max_chunk_size = Some(bytes);
+        self
+    }
+
     /// Sets custom error handler.
+    ///
+    /// The handler is called for all extraction errors, including Content-Type and boundary errors
+    /// that occur before any field is read.
+    ///
+    /// Errors of a kind configured using [`error_status()`](Self::error_status) or
+    /// [`error_renderer()`](Self::error_renderer) are not passed to this handler.
     pub fn error_handler<F>(mut self, f: F) -> Self
     where
         F: Fn(MultipartError, &HttpRequest) -> Error + Send + Sync + 'static,
@@ -396,6 +746,95 @@ impl MultipartFormConfig {
         self
     }
 
+    /// Sets the status code of responses for errors of the given kind.
+    ///
+    /// The response body is the error's `Display` output, as with the default error response.
+    /// Replaces any status or renderer previously set for `kind`.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_multipart::{form::MultipartFormConfig, MultipartErrorKind};
+    /// use actix_web::http::StatusCode;
+    ///
+    /// let config = MultipartFormConfig::default()
+    ///     .error_status(MultipartErrorKind::LimitExceeded, StatusCode::PAYLOAD_TOO_LARGE)
+    ///     .error_status(MultipartErrorKind::UnknownField, StatusCode::UNPROCESSABLE_ENTITY);
+    /// ```
+    pub fn error_status(self, kind: MultipartErrorKind, status: StatusCode) -> Self {
+        self.set_error_response(kind, ErrorResponse::Status(status))
+    }
+
+    /// Sets a function that renders responses for errors of the given kind.
+    ///
+    /// Replaces any status or renderer previously set for `kind`.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_multipart::{form::MultipartFormConfig, MultipartErrorKind};
+    /// use actix_web::HttpResponse;
+    ///
+    /// let config = MultipartFormConfig::default().error_renderer(
+    ///     MultipartErrorKind::UnknownField,
+    ///     |err, _req| {
+    ///         HttpResponse::UnprocessableEntity().json(serde_json::json!({
+    ///             "error": err.to_string(),
+    ///         }))
+    ///     },
+    /// );
+    /// ```
+    pub fn error_renderer<F>(self, kind: MultipartErrorKind, f: F) -> Self
+    where
+        F: Fn(&MultipartError, &HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.set_error_response(kind, ErrorResponse::Render(Arc::new(f)))
+    }
+
+    fn set_error_response(mut self, kind: MultipartErrorKind, response: ErrorResponse) -> Self {
+        let responses = self.err_responses.get_or_insert_with(Vec::new);
+        responses.retain(|(k, _)| *k != kind);
+        responses.push((kind, response));
+        self
+    }
+
+    /// Sets a handler that is called with the upload progress each time a chunk of field data is
+    /// read.
+    ///
+    /// The handler receives the request being extracted, so that progress can be published (e.g.,
+    /// keyed by an upload ID taken from the query string) for another endpoint to report on.
+    ///
+    /// Note that only data read by field readers is counted; the data of ignored fields is not.
+    pub fn progress_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&HttpRequest, Progress<'_>) + Send + Sync + 'static,
+    {
+        self.progress_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Converts an extraction error into a response error, using the response configured for the
+    /// error's kind, then the custom error handler, and finally the error's default response.
+    fn map_error(&self, req: &HttpRequest, err: MultipartError) -> Error {
+        let kind = err.kind();
+
+        let response = self
+            .err_responses
+            .iter()
+            .flatten()
+            .find_map(|(k, response)| (*k == kind).then_some(response));
+
+        match response {
+            Some(ErrorResponse::Status(status)) => InternalError::new(err, *status).into(),
+            Some(ErrorResponse::Render(render)) => {
+                let res = (*render)(&err, req);
+                InternalError::from_response(err, res).into()
+            }
+            None => match self.err_handler {
+                Some(ref handler) => (*handler)(err, req),
+                None => err.into(),
+            },
+        }
+    }
+
     /// Extracts payload config from app data. Check both `T` and `Data<T>`, in that order, and fall
     /// back to the default payload config.
     fn from_req(req: &HttpRequest) -> &Self {
@@ -408,7 +847,12 @@ impl MultipartFormConfig {
 const DEFAULT_CONFIG: MultipartFormConfig = MultipartFormConfig {
     total_limit: 52_428_800, // 50 MiB
     memory_limit: 2_097_152, // 2 MiB
+    max_parts: None,
+    buffer_high_watermark: None,
+    max_chunk_size: None,
     err_handler: None,
+    err_responses: None,
+    progress_handler: None,
 };
 
 impl Default for MultipartFormConfig {
@@ -419,6 +863,11 @@ impl Default for MultipartFormConfig {
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        io::Cursor,
+        sync::{Arc, Mutex},
+    };
+
     use actix_http::encoding::Decoder;
     use actix_multipart_rfc7578::client::multipart;
     use actix_test::TestServer;
@@ -434,7 +883,7 @@ mod tests {
         form::{
             bytes::Bytes, tempfile::TempFile, text::Text, FieldReader, Limits, MultipartFormConfig,
         },
-        Field, MultipartError,
+        Field, MultipartError, MultipartErrorKind,
     };
 
     pub async fn send_form(
@@ -718,6 +1167,53 @@ mod tests {
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[actix_rt::test]
+    async fn test_error_responses() {
+        let srv = actix_test::start(|| {
+            App::new()
+                .route("/text", web::post().to(test_upload_limits_memory))
+                .route("/deny", web::post().to(test_deny_unknown_route))
+                .app_data(
+                    MultipartFormConfig::default()
+                        .total_limit(20)
+                        .error_handler(|err, _req| {
+                            actix_web::error::ErrorImATeapot(err.to_string())
+                        })
+                        .error_status(MultipartErrorKind::LimitExceeded, StatusCode::BAD_GATEWAY)
+                        .error_status(
+                            MultipartErrorKind::LimitExceeded,
+                            StatusCode::PAYLOAD_TOO_LARGE,
+                        )
+                        .error_renderer(MultipartErrorKind::UnknownField, |err, _req| {
+                            HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                                "error": err.to_string(),
+                            }))
+                        }),
+                )
+        });
+
+        let mut form = multipart::Form::default();
+        form.add_text("field", "this string is 28 bytes long");
+        let response = send_form(&srv, form, "/text").await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let mut form = multipart::Form::default();
+        form.add_text("unknown", "value");
+        let mut response = send_form(&srv, form, "/deny").await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = response.json::<serde_json::Value>().await.unwrap();
+        assert_eq!(body["error"], "Unknown field: unknown");
+
+        // kinds without a configured response fall back to the error handler
+        let response = Client::default()
+            .post(srv.url("/text"))
+            .insert_header(("content-type", "multipart/form-data"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::IM_A_TEAPOT);
+    }
+
     #[derive(MultipartForm)]
     struct TestFieldLevelLimits {
         #[multipart(limit = "30B")]
@@ -805,6 +1301,426 @@ mod tests {
         assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
     }
 
+    /// Test the `flatten` field attribute.
+    #[derive(MultipartForm)]
+    struct TestAddress {
+        street: Text<String>,
+        #[multipart(limit = "8B")]
+        city: Option<Text<String>>,
+    }
+
+    #[derive(MultipartForm)]
+    struct TestContact {
+        phone: Text<String>,
+    }
+
+    #[derive(MultipartForm)]
+    #[multipart(deny_unknown_fields)]
+    struct TestFlatten {
+        name: Text<String>,
+        #[multipart(flatten, prefix = "address.")]
+        address: TestAddress,
+        #[multipart(flatten)]
+        contact: TestContact,
+    }
+
+    async fn test_flatten_route(form: MultipartForm<TestFlatten>) -> impl Responder {
+        let form = form.into_inner();
+        assert_eq!(&*form.name, "Jane");
+        assert_eq!(&*form.address.street, "Main St");
+        assert_eq!(&**form.address.city.as_ref().unwrap(), "Paris");
+        assert_eq!(&*form.contact.phone, "555");
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_flatten() {
+        let srv = actix_test::start(|| App::new().route("/", web::post().to(test_flatten_route)));
+
+        let mut form = multipart::Form::default();
+        form.add_text("name", "Jane");
+        form.add_text("address.street", "Main St");
+        form.add_text("address.city", "Paris");
+        form.add_text("phone", "555");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // nested field limits are applied to prefixed names
+        let mut form = multipart::Form::default();
+        form.add_text("name", "Jane");
+        form.add_text("address.street", "Main St");
+        form.add_text("address.city", "Llanfairpwllgwyngyll");
+        form.add_text("phone", "555");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // un-prefixed nested field names are unknown to the parent form
+        let mut form = multipart::Form::default();
+        form.add_text("name", "Jane");
+        form.add_text("street", "Main St");
+        form.add_text("phone", "555");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// Test enum forms.
+    #[derive(MultipartForm)]
+    #[multipart(tag = "kind", deny_unknown_fields)]
+    enum TestEnum {
+        #[multipart(rename = "text")]
+        Message {
+            body: Text<String>,
+        },
+
+        #[multipart(rename = "list")]
+        List {
+            items: Vec<Text<String>>,
+        },
+
+        Empty,
+    }
+
+    async fn test_enum_route(
+        form: MultipartForm<TestEnum>,
+        path: web::Path<String>,
+    ) -> impl Responder {
+        match (form.into_inner(), path.as_str()) {
+            (TestEnum::Message { body }, "text") => assert_eq!(&*body, "hello"),
+            (TestEnum::List { items }, "list") => assert_eq!(items.len(), 2),
+            (TestEnum::Empty, "empty") => {}
+            _ => panic!("unexpected variant"),
+        }
+
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_enum() {
+        let srv =
+            actix_test::start(|| App::new().route("/{kind}", web::post().to(test_enum_route)));
+
+        let mut form = multipart::Form::default();
+        form.add_text("kind", "text");
+        form.add_text("body", "hello");
+        let response = send_form(&srv, form, "/text").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut form = multipart::Form::default();
+        form.add_text("kind", "list");
+        form.add_text("items", "a");
+        form.add_text("items", "b");
+        let response = send_form(&srv, form, "/list").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut form = multipart::Form::default();
+        form.add_text("kind", "Empty");
+        let response = send_form(&srv, form, "/empty").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // fields of other variants are unknown
+        let mut form = multipart::Form::default();
+        form.add_text("kind", "text");
+        form.add_text("body", "hello");
+        form.add_text("items", "a");
+        let response = send_form(&srv, form, "/text").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // tag must precede variant fields
+        let mut form = multipart::Form::default();
+        form.add_text("body", "hello");
+        form.add_text("kind", "text");
+        let response = send_form(&srv, form, "/text").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let mut form = multipart::Form::default();
+        form.add_text("kind", "unknown");
+        let response = send_form(&srv, form, "/text").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let form = multipart::Form::default();
+        let response = send_form(&srv, form, "/text").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// Test the `max_items` field attribute and `max_parts` config.
+    #[derive(MultipartForm)]
+    struct TestItemLimits {
+        #[multipart(max_items = 2)]
+        field: Vec<Text<String>>,
+    }
+
+    async fn test_item_limits_route(form: MultipartForm<TestItemLimits>) -> impl Responder {
+        assert!(form.field.len() <= 2);
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_item_limits() {
+        let srv =
+            actix_test::start(|| App::new().route("/", web::post().to(test_item_limits_route)));
+
+        let mut form = multipart::Form::default();
+        form.add_text("field", "1");
+        form.add_text("field", "2");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut form = multipart::Form::default();
+        form.add_text("field", "1");
+        form.add_text("field", "2");
+        form.add_text("field", "3");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn test_max_parts() {
+        let srv = actix_test::start(|| {
+            App::new()
+                .route("/", web::post().to(test_item_limits_route))
+                .app_data(MultipartFormConfig::default().max_parts(3))
+        });
+
+        let mut form = multipart::Form::default();
+        form.add_text("field", "1");
+        form.add_text("unknown", "2");
+        form.add_text("unknown", "3");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // unknown fields count towards the limit
+        let mut form = multipart::Form::default();
+        form.add_text("field", "1");
+        form.add_text("unknown", "2");
+        form.add_text("unknown", "3");
+        form.add_text("unknown", "4");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// Test the `default` field attribute.
+    #[derive(MultipartForm)]
+    struct TestDefaults {
+        #[multipart(default)]
+        name: Text<String>,
+        #[multipart(default = "Text(20)")]
+        page_size: Text<u32>,
+    }
+
+    async fn test_defaults_route(form: MultipartForm<TestDefaults>) -> impl Responder {
+        format!("{}:{}", *form.name, *form.page_size)
+    }
+
+    #[actix_rt::test]
+    async fn test_defaults() {
+        let srv = actix_test::start(|| App::new().route("/", web::post().to(test_defaults_route)));
+
+        let mut form = multipart::Form::default();
+        form.add_text("unknown", "x");
+        let mut response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().await.unwrap(), ":20");
+
+        let mut form = multipart::Form::default();
+        form.add_text("name", "actix");
+        form.add_text("page_size", "5");
+        let mut response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().await.unwrap(), "actix:5");
+    }
+
+    /// Test the `ordered` container attribute.
+    #[derive(MultipartForm)]
+    #[multipart(ordered)]
+    struct TestOrdered {
+        token: Text<String>,
+        note: Option<Text<String>>,
+        tags: Vec<Text<String>>,
+        body: Text<String>,
+    }
+
+    async fn test_ordered_route(form: MultipartForm<TestOrdered>) -> impl Responder {
+        assert_eq!(&*form.token, "secret");
+        assert_eq!(&*form.body, "hello");
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_ordered_fields() {
+        let srv = actix_test::start(|| App::new().route("/", web::post().to(test_ordered_route)));
+
+        let mut form = multipart::Form::default();
+        form.add_text("token", "secret");
+        form.add_text("note", "n");
+        form.add_text("tags", "a");
+        form.add_text("unknown", "ignored");
+        form.add_text("tags", "b");
+        form.add_text("body", "hello");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // optional fields may be skipped
+        let mut form = multipart::Form::default();
+        form.add_text("token", "secret");
+        form.add_text("body", "hello");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // field declared earlier received after a later one
+        let mut form = multipart::Form::default();
+        form.add_text("token", "secret");
+        form.add_text("tags", "a");
+        form.add_text("note", "n");
+        form.add_text("body", "hello");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // required field missing when a later field arrives
+        let mut form = multipart::Form::default();
+        form.add_text("body", "hello");
+        form.add_text("token", "secret");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn test_progress_handler() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events2 = Arc::clone(&events);
+
+        let srv = actix_test::start(move || {
+            let events = Arc::clone(&events2);
+
+            App::new()
+                .route("/", web::post().to(test_vec_route))
+                .app_data(
+                    MultipartFormConfig::default().progress_handler(move |req, progress| {
+                        assert_eq!(req.path(), "/");
+                        events.lock().unwrap().push((
+                            progress.field_name.to_owned(),
+                            progress.field_bytes,
+                            progress.total_bytes,
+                        ));
+                    }),
+                )
+        });
+
+        let mut form = multipart::Form::default();
+        form.add_text("list1", "value1");
+        form.add_text("list1", "value2");
+        form.add_text("list1", "value3");
+
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let events = events.lock().unwrap();
+        assert!(events
+            .iter()
+            .all(|(name, field_bytes, _)| name == "list1" && *field_bytes <= 6));
+        assert_eq!(events.last().unwrap().2, 18);
+    }
+
+    /// Test the `validate` and `validate_async` field attributes.
+    fn validate_not_empty(text: &Text<String>) -> Result<(), actix_web::Error> {
+        if text.is_empty() {
+            return Err(actix_web::error::ErrorBadRequest("empty"));
+        }
+
+        Ok(())
+    }
+
+    async fn validate_max_len(text: &Text<String>) -> Result<(), actix_web::Error> {
+        if text.len() > 5 {
+            return Err(actix_web::error::ErrorBadRequest("too long"));
+        }
+
+        Ok(())
+    }
+
+    #[derive(MultipartForm)]
+    struct TestValidation {
+        #[multipart(validate = "validate_not_empty", validate_async = "validate_max_len")]
+        title: Text<String>,
+
+        #[multipart(validate_async = "validate_max_len")]
+        tags: Vec<Text<String>>,
+    }
+
+    async fn test_validation_route(_form: MultipartForm<TestValidation>) -> impl Responder {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_validation() {
+        let srv =
+            actix_test::start(|| App::new().route("/", web::post().to(test_validation_route)));
+
+        let mut form = multipart::Form::default();
+        form.add_text("title", "valid");
+        form.add_text("tags", "a");
+        form.add_text("tags", "b");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut form = multipart::Form::default();
+        form.add_text("title", "");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let mut form = multipart::Form::default();
+        form.add_text("title", "too long");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let mut form = multipart::Form::default();
+        form.add_text("title", "valid");
+        form.add_text("tags", "a");
+        form.add_text("tags", "too long");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    /// Test the `content_type` field attribute.
+    #[derive(MultipartForm)]
+    struct TestContentTypeRestriction {
+        #[multipart(content_type = "image/*")]
+        image: Bytes,
+
+        #[multipart(content_type = "application/json", content_type = "text/plain")]
+        notes: Option<Text<String>>,
+    }
+
+    async fn test_content_type_restriction_route(
+        form: MultipartForm<TestContentTypeRestriction>,
+    ) -> impl Responder {
+        assert_eq!(form.image.content_type.as_ref().unwrap(), &mime::IMAGE_PNG);
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_content_type_restriction() {
+        let srv = actix_test::start(|| {
+            App::new().route("/", web::post().to(test_content_type_restriction_route))
+        });
+
+        let mut form = multipart::Form::default();
+        form.add_reader_file_with_mime("image", Cursor::new("png"), "a.png", mime::IMAGE_PNG);
+        form.add_text("notes", "no content type means text/plain");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut form = multipart::Form::default();
+        form.add_reader_file_with_mime("image", Cursor::new("txt"), "a.txt", mime::TEXT_PLAIN);
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let mut form = multipart::Form::default();
+        form.add_reader_file_with_mime("image", Cursor::new("png"), "a.png", mime::IMAGE_PNG);
+        form.add_reader_file_with_mime("notes", Cursor::new("<p>"), "a.html", mime::TEXT_HTML);
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
     #[should_panic(expected = "called `Result::unwrap()` on an `Err` value: Connect(Disconnected)")]
     #[actix_web::test]
     async fn field_try_next_panic() {
@@ -0,0 +1,356 @@
+//! Streams a field into a pluggable storage backend.
+//!
+//! Unlike [`TempFile`](super::tempfile::TempFile), which always writes to the local filesystem,
+//! the [`Stored`] field reader forwards each chunk to a user-provided [`StorageBackend`] as it
+//! arrives. This makes it possible to stream uploads directly to, e.g., an S3 multipart upload or
+//! other object stores without buffering them on disk first.
+
+use std::{fmt, future::ready};
+
+use actix_web::{http::StatusCode, web, Error, HttpRequest, ResponseError};
+use derive_more::derive::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use futures_util::TryStreamExt as _;
+use mime::Mime;
+
+use crate::{
+    form::{FieldReader, Limits},
+    Field, MultipartError,
+};
+
+/// A destination that file fields can be streamed into.
+///
+/// Backends are looked up from app data by the [`Stored`] field reader. Register them using
+/// either `B` or `Data<B>`, in the same way as other extractor configuration.
+///
+/// # Examples
+///
+/// ```
+/// use actix_multipart::form::{
+///     storage::{StorageBackend, StorageSink, Stored},
+///     MultipartForm,
+/// };
+/// use actix_web::{web::Bytes, Error, HttpRequest};
+/// use futures_core::future::LocalBoxFuture;
+///
+/// /// Counts the bytes of each upload without keeping them.
+/// struct Counter;
+///
+/// struct CounterSink(usize);
+///
+/// impl StorageBackend for Counter {
+///     type Sink = CounterSink;
+///
+///     fn create_sink<'a>(
+///         &'a self,
+///         _req: &'a HttpRequest,
+///         _field: &'a actix_multipart::Field,
+///     ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>> {
+///         Box::pin(async { Ok(CounterSink(0)) })
+///     }
+/// }
+///
+/// impl StorageSink for CounterSink {
+///     type Output = usize;
+///
+///     fn write_chunk(&mut self, chunk: Bytes) -> LocalBoxFuture<'_, Result<(), Error>> {
+///         self.0 += chunk.len();
+///         Box::pin(async { Ok(()) })
+///     }
+///
+///     fn finish(self) -> LocalBoxFuture<'static, Result<Self::Output, Error>> {
+///         Box::pin(async move { Ok(self.0) })
+///     }
+/// }
+///
+/// #[derive(MultipartForm)]
+/// struct Upload {
+///     file: Stored<Counter>,
+/// }
+/// ```
+pub trait StorageBackend: 'static {
+    /// The sink that a single field's data is written to.
+    type Sink: StorageSink;
+
+    /// Prepares a new sink for the given field.
+    ///
+    /// The field's headers (name, content type, filename, etc.) are available for inspection but
+    /// its payload must not be read here.
+    fn create_sink<'a>(
+        &'a self,
+        req: &'a HttpRequest,
+        field: &'a Field,
+    ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>>;
+}
+
+/// An in-progress write of a single field to a [`StorageBackend`].
+pub trait StorageSink: Sized + 'static {
+    /// The value produced once the field has been completely written, e.g., an object key.
+    type Output: 'static;
+
+    /// Writes a chunk of the field's data.
+    fn write_chunk(&mut self, chunk: web::Bytes) -> LocalBoxFuture<'_, Result<(), Error>>;
+
+    /// Completes the write after the field's data has been exhausted.
+    fn finish(self) -> LocalBoxFuture<'static, Result<Self::Output, Error>>;
+
+    /// Abandons the write after an error occurred while reading or writing the field.
+    ///
+    /// Backends that allocate remote resources up front (like S3 multipart uploads) should release
+    /// them here. The default implementation does nothing.
+    fn abort(self) -> LocalBoxFuture<'static, ()> {
+        Box::pin(ready(()))
+    }
+}
+
+/// Stream the field into a [`StorageBackend`] registered in app data.
+pub struct Stored<B: StorageBackend> {
+    /// The value produced by the backend's sink.
+    pub output: <B::Sink as StorageSink>::Output,
+
+    /// The value of the `content-type` header.
+    pub content_type: Option<Mime>,
+
+    /// The `filename` value in the `content-disposition` header.
+    pub file_name: Option<String>,
+
+    /// The size in bytes of the field.
+    pub size: usize,
+}
+
+impl<B> fmt::Debug for Stored<B>
+where
+    B: StorageBackend,
+    <B::Sink as StorageSink>::Output: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stored")
+            .field("output", &self.output)
+            .field("content_type", &self.content_type)
+            .field("file_name", &self.file_name)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl<'t, B: StorageBackend> FieldReader<'t> for Stored<B> {
+    type Future = LocalBoxFuture<'t, Result<Self, MultipartError>>;
+
+    fn read_field(req: &'t HttpRequest, mut field: Field, limits: &'t mut Limits) -> Self::Future {
+        Box::pin(async move {
+            let field_name = field.form_field_name.clone();
+            let map_err = |source: Error| MultipartError::Field {
+                name: field_name.clone(),
+                source,
+            };
+
+            let backend = req
+                .app_data::<B>()
+                .or_else(|| req.app_data::<web::Data<B>>().map(|d| d.as_ref()))
+                .ok_or_else(|| map_err(StorageError::BackendMissing.into()))?;
+
+            let mut sink = backend.create_sink(req, &field).await.map_err(map_err)?;
+            let mut size = 0;
+
+            loop {
+                let chunk = match field.try_next().await {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => break,
+                    Err(err) => {
+                        sink.abort().await;
+                        return Err(err);
+                    }
+                };
+
+                if let Err(err) = limits.try_consume_limits(chunk.len(), false) {
+                    sink.abort().await;
+                    return Err(err);
+                }
+
+                size += chunk.len();
+
+                if let Err(err) = sink.write_chunk(chunk).await {
+                    sink.abort().await;
+                    return Err(map_err(err));
+                }
+            }
+
+            let output = sink.finish().await.map_err(map_err)?;
+
+            Ok(Stored {
+                output,
+                content_type: field.content_type().map(ToOwned::to_owned),
+                file_name: field
+                    .content_disposition()
+                    .expect("multipart form fields should have a content-disposition header")
+                    .get_filename()
+                    .map(ToOwned::to_owned),
+                size,
+            })
+        })
+    }
+}
+
+/// Errors that can occur while streaming a field using the [`Stored`] field reader.
+///
+/// Errors returned by the storage backend itself are passed through unchanged.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum StorageError {
+    /// No [`StorageBackend`] was registered in app data.
+    ///
+    /// Responds with `500 Internal Server Error`.
+    #[display("Storage backend was not registered in app data")]
+    BackendMissing,
+}
+
+impl ResponseError for StorageError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use actix_multipart_rfc7578::client::multipart;
+    use actix_web::{http::StatusCode, web, App, Error, HttpRequest, HttpResponse, Responder};
+    use futures_core::future::LocalBoxFuture;
+
+    use super::{StorageBackend, StorageSink, Stored};
+    use crate::{
+        form::{tests::send_form, MultipartForm},
+        Field,
+    };
+
+    #[derive(Clone, Default)]
+    struct MemoryBackend {
+        aborted: Arc<AtomicUsize>,
+    }
+
+    struct MemorySink {
+        data: Vec<u8>,
+        fail: bool,
+        aborted: Arc<AtomicUsize>,
+    }
+
+    impl StorageBackend for MemoryBackend {
+        type Sink = MemorySink;
+
+        fn create_sink<'a>(
+            &'a self,
+            _req: &'a HttpRequest,
+            field: &'a Field,
+        ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>> {
+            let fail = field.name() == Some("broken");
+
+            Box::pin(async move {
+                Ok(MemorySink {
+                    data: Vec::new(),
+                    fail,
+                    aborted: Arc::clone(&self.aborted),
+                })
+            })
+        }
+    }
+
+    impl StorageSink for MemorySink {
+        type Output = Vec<u8>;
+
+        fn write_chunk(&mut self, chunk: web::Bytes) -> LocalBoxFuture<'_, Result<(), Error>> {
+            Box::pin(async move {
+                if self.fail {
+                    return Err(actix_web::error::ErrorBadGateway("upstream write failed"));
+                }
+
+                self.data.extend_from_slice(&chunk);
+                Ok(())
+            })
+        }
+
+        fn finish(self) -> LocalBoxFuture<'static, Result<Self::Output, Error>> {
+            Box::pin(async move { Ok(self.data) })
+        }
+
+        fn abort(self) -> LocalBoxFuture<'static, ()> {
+            self.aborted.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async {})
+        }
+    }
+
+    #[derive(MultipartForm)]
+    struct StoredForm {
+        file: Stored<MemoryBackend>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(MultipartForm)]
+    struct BrokenForm {
+        broken: Stored<MemoryBackend>,
+    }
+
+    async fn test_stored_route(form: MultipartForm<StoredForm>) -> impl Responder {
+        let form = form.into_inner();
+        assert_eq!(form.file.output, b"Hello, world!");
+        assert_eq!(form.file.size, 13);
+        assert_eq!(form.file.file_name.unwrap(), "testfile.txt");
+        assert_eq!(form.file.content_type.unwrap(), mime::TEXT_PLAIN);
+        HttpResponse::Ok().finish()
+    }
+
+    async fn test_broken_route(_form: MultipartForm<BrokenForm>) -> impl Responder {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_stored_upload() {
+        let srv = actix_test::start(|| {
+            App::new()
+                .app_data(MemoryBackend::default())
+                .route("/", web::post().to(test_stored_route))
+        });
+
+        let mut form = multipart::Form::default();
+        let bytes = Cursor::new("Hello, world!");
+        form.add_reader_file_with_mime("file", bytes, "testfile.txt", mime::TEXT_PLAIN);
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_backend_missing() {
+        let srv = actix_test::start(|| App::new().route("/", web::post().to(test_stored_route)));
+
+        let mut form = multipart::Form::default();
+        let bytes = Cursor::new("Hello, world!");
+        form.add_reader_file_with_mime("file", bytes, "testfile.txt", mime::TEXT_PLAIN);
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[actix_rt::test]
+    async fn test_sink_aborted_on_error() {
+        let backend = MemoryBackend::default();
+        let aborted = Arc::clone(&backend.aborted);
+
+        let srv = actix_test::start(move || {
+            App::new()
+                .app_data(backend.clone())
+                .route("/", web::post().to(test_broken_route))
+        });
+
+        let mut form = multipart::Form::default();
+        let bytes = Cursor::new("Hello, world!");
+        form.add_reader_file_with_mime("broken", bytes, "testfile.txt", mime::TEXT_PLAIN);
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(aborted.load(Ordering::SeqCst), 1);
+    }
+}
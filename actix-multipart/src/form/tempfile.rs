@@ -1,6 +1,7 @@
 //! Writes a field to a temporary file on disk.
 
 use std::{
+    fs::{self, File, Permissions},
     io,
     path::{Path, PathBuf},
     sync::Arc,
@@ -30,10 +31,137 @@ pub struct TempFile {
     pub content_type: Option<Mime>,
 
     /// The `filename` value in the `content-disposition` header.
+    ///
+    /// This is sanitized if [`TempFileConfig::sanitize_file_names()`] is enabled.
     pub file_name: Option<String>,
 
     /// The size in bytes of the file.
     pub size: usize,
+
+    /// The checksum of the file's contents.
+    ///
+    /// This is only computed if enabled using `TempFileConfig::checksum()`, which requires the
+    /// `checksum` crate feature; otherwise, it is always `None`.
+    pub checksum: Option<Checksum>,
+}
+
+impl TempFile {
+    /// Returns the client-provided file name after sanitizing it with [`filename::sanitize()`].
+    ///
+    /// [`filename::sanitize()`]: crate::filename::sanitize()
+    pub fn sanitized_file_name(&self) -> Option<String> {
+        crate::filename::sanitize(self.file_name.as_deref()?)
+    }
+
+    /// Atomically moves the file to `path`, replacing any existing file, and returns the opened
+    /// file.
+    ///
+    /// If the temp file is on a different device or filesystem than `path` (which is common when
+    /// containers mount upload directories as volumes), the contents are instead copied to a new
+    /// temp file next to `path` which is then atomically renamed into place.
+    ///
+    /// This performs blocking I/O; consider running it on a thread pool using [`web::block()`].
+    ///
+    /// # Errors
+    ///
+    /// On failure, the `TempFile` is returned inside the error so that it is not deleted.
+    pub fn persist_to(self, path: impl AsRef<Path>) -> Result<File, PersistError> {
+        self.persist_impl(path.as_ref(), false)
+    }
+
+    /// Atomically moves the file to `path`, failing if a file already exists there, and returns
+    /// the opened file.
+    ///
+    /// See [`persist_to()`](Self::persist_to) for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`io::ErrorKind::AlreadyExists`] if `path` exists. On failure,
+    /// the `TempFile` is returned inside the error so that it is not deleted.
+    pub fn persist_noclobber(self, path: impl AsRef<Path>) -> Result<File, PersistError> {
+        self.persist_impl(path.as_ref(), true)
+    }
+
+    fn persist_impl(mut self, path: &Path, noclobber: bool) -> Result<File, PersistError> {
+        let res = if noclobber {
+            self.file.persist_noclobber(path)
+        } else {
+            self.file.persist(path)
+        };
+
+        let error = match res {
+            Ok(file) => return Ok(file),
+            Err(err) => {
+                self.file = err.file;
+                err.error
+            }
+        };
+
+        if !is_cross_device(&error) {
+            return Err(PersistError {
+                error,
+                file: Box::new(self),
+            });
+        }
+
+        match self.persist_by_copy(path, noclobber) {
+            Ok(file) => Ok(file),
+            Err(error) => Err(PersistError {
+                error,
+                file: Box::new(self),
+            }),
+        }
+    }
+
+    /// Copies the file's contents to a temp file in the directory of `path` and renames that into
+    /// place, so that `path` is never observed with partial contents.
+    fn persist_by_copy(&self, path: &Path, noclobber: bool) -> io::Result<File> {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        let mut dest = tempfile::Builder::new().tempfile_in(dir)?;
+
+        io::copy(&mut self.file.reopen()?, dest.as_file_mut())?;
+        fs::set_permissions(dest.path(), self.file.as_file().metadata()?.permissions())?;
+        dest.as_file().sync_all()?;
+
+        let res = if noclobber {
+            dest.persist_noclobber(path)
+        } else {
+            dest.persist(path)
+        };
+
+        res.map_err(|err| err.error)
+    }
+}
+
+/// Returns true if `err` was caused by renaming a file across devices or filesystems.
+fn is_cross_device(err: &io::Error) -> bool {
+    // EXDEV on Unix platforms and ERROR_NOT_SAME_DEVICE on Windows
+    let code = if cfg!(windows) { 17 } else { 18 };
+    err.raw_os_error() == Some(code)
+}
+
+/// Error returned by [`TempFile::persist_to()`] and [`TempFile::persist_noclobber()`].
+#[derive(Debug, Display, Error)]
+#[display("Failed to persist temp file: {error}")]
+#[non_exhaustive]
+pub struct PersistError {
+    /// The underlying I/O error.
+    #[error(source)]
+    pub error: io::Error,
+
+    /// The temp file that could not be persisted.
+    #[error(not(source))]
+    pub file: Box<TempFile>,
+}
+
+impl From<PersistError> for io::Error {
+    fn from(err: PersistError) -> Self {
+        err.error
+    }
 }
 
 impl<'t> FieldReader<'t> for TempFile {
@@ -52,9 +180,17 @@ impl<'t> FieldReader<'t> for TempFile {
                 config.map_error(req, &field.form_field_name, TempFileError::FileIo(err))
             })?);
 
+            #[cfg(feature = "checksum")]
+            let mut hasher = config.checksum.map(Hasher::new);
+
             while let Some(chunk) = field.try_next().await? {
                 limits.try_consume_limits(chunk.len(), false)?;
                 size += chunk.len();
+
+                #[cfg(feature = "checksum")]
+                if let Some(ref mut hasher) = hasher {
+                    hasher.update(&chunk);
+                }
                 file_async.write_all(chunk.as_ref()).await.map_err(|err| {
                     config.map_error(req, &field.form_field_name, TempFileError::FileIo(err))
                 })?;
@@ -64,20 +200,122 @@ impl<'t> FieldReader<'t> for TempFile {
                 config.map_error(req, &field.form_field_name, TempFileError::FileIo(err))
             })?;
 
-            Ok(TempFile {
-                file,
-                content_type: field.content_type().map(ToOwned::to_owned),
-                file_name: field
+            let file_name = if config.sanitize_file_names {
+                field.sanitized_filename()
+            } else {
+                field
                     .content_disposition()
                     .expect("multipart form fields should have a content-disposition header")
                     .get_filename()
-                    .map(ToOwned::to_owned),
+                    .map(ToOwned::to_owned)
+            };
+
+            let temp_file = TempFile {
+                file,
+                content_type: field.content_type().map(ToOwned::to_owned),
+                file_name,
                 size,
-            })
+                #[cfg(feature = "checksum")]
+                checksum: hasher.map(Hasher::finish),
+                #[cfg(not(feature = "checksum"))]
+                checksum: None,
+            };
+
+            if let Some(ref inspector) = config.inspector {
+                (inspector)(req, &temp_file)
+                    .await
+                    .map_err(|source| MultipartError::Field {
+                        name: field.form_field_name.clone(),
+                        source,
+                    })?;
+            }
+
+            Ok(temp_file)
         })
     }
 }
 
+/// Hash algorithm used to compute a [`Checksum`] while streaming a field to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChecksumAlgorithm {
+    /// SHA-256.
+    Sha256,
+
+    /// MD5.
+    ///
+    /// MD5 is not collision resistant and should only be used for compatibility, e.g., with
+    /// `Content-MD5` headers or object store ETags.
+    Md5,
+}
+
+/// Checksum of a [`TempFile`]'s contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksum {
+    /// The algorithm used to compute the digest.
+    pub algorithm: ChecksumAlgorithm,
+
+    /// The raw digest bytes.
+    pub digest: Vec<u8>,
+}
+
+impl Checksum {
+    /// Returns the digest as a lowercase hex string.
+    pub fn to_hex(&self) -> String {
+        use std::fmt::Write as _;
+
+        self.digest.iter().fold(
+            String::with_capacity(self.digest.len() * 2),
+            |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            },
+        )
+    }
+}
+
+#[cfg(feature = "checksum")]
+enum Hasher {
+    Sha256(sha2::Sha256),
+    Md5(md5::Md5),
+}
+
+#[cfg(feature = "checksum")]
+impl Hasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        use sha2::Digest as _;
+
+        match algorithm {
+            ChecksumAlgorithm::Sha256 => Self::Sha256(sha2::Sha256::new()),
+            ChecksumAlgorithm::Md5 => Self::Md5(md5::Md5::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        use sha2::Digest as _;
+
+        match self {
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Md5(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finish(self) -> Checksum {
+        use sha2::Digest as _;
+
+        match self {
+            Self::Sha256(hasher) => Checksum {
+                algorithm: ChecksumAlgorithm::Sha256,
+                digest: hasher.finalize().to_vec(),
+            },
+            Self::Md5(hasher) => Checksum {
+                algorithm: ChecksumAlgorithm::Md5,
+                digest: hasher.finalize().to_vec(),
+            },
+        }
+    }
+}
+
 #[derive(Debug, Display, Error)]
 #[non_exhaustive]
 pub enum TempFileError {
@@ -92,19 +330,46 @@ impl ResponseError for TempFileError {
     }
 }
 
+type TempFileInspector = Arc<
+    dyn for<'a> Fn(&'a HttpRequest, &'a TempFile) -> LocalBoxFuture<'a, Result<(), Error>>
+        + Send
+        + Sync,
+>;
+
 /// Configuration for the [`TempFile`] field reader.
 #[derive(Clone)]
 pub struct TempFileConfig {
     err_handler: FieldErrorHandler<TempFileError>,
     directory: Option<PathBuf>,
+    prefix: Option<String>,
+    suffix: Option<String>,
+    permissions: Option<Permissions>,
+    sanitize_file_names: bool,
+    #[cfg(feature = "checksum")]
+    checksum: Option<ChecksumAlgorithm>,
+    inspector: Option<TempFileInspector>,
 }
 
 impl TempFileConfig {
     fn create_tempfile(&self) -> io::Result<NamedTempFile> {
+        let mut builder = tempfile::Builder::new();
+
+        if let Some(ref prefix) = self.prefix {
+            builder.prefix(prefix);
+        }
+
+        if let Some(ref suffix) = self.suffix {
+            builder.suffix(suffix);
+        }
+
+        if let Some(ref permissions) = self.permissions {
+            builder.permissions(permissions.clone());
+        }
+
         if let Some(ref dir) = self.directory {
-            NamedTempFile::new_in(dir)
+            builder.tempfile_in(dir)
         } else {
-            NamedTempFile::new()
+            builder.tempfile()
         }
     }
 }
@@ -147,11 +412,112 @@ impl TempFileConfig {
         self.directory = Some(dir.as_ref().to_owned());
         self
     }
+
+    /// Sets the prefix of temp file names.
+    ///
+    /// The default prefix is `.tmp`.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Sets the suffix of temp file names.
+    ///
+    /// By default temp file names have no suffix.
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = Some(suffix.into());
+        self
+    }
+
+    /// Sets the permissions that temp files will be created with.
+    ///
+    /// By default, temp files are only readable and writable by their owner on Unix platforms.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(unix)] {
+    /// use std::{fs::Permissions, os::unix::fs::PermissionsExt as _};
+    ///
+    /// use actix_multipart::form::tempfile::TempFileConfig;
+    ///
+    /// let config = TempFileConfig::default()
+    ///     .directory("/mnt/uploads")
+    ///     .permissions(Permissions::from_mode(0o640));
+    /// # }
+    /// ```
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = Some(permissions);
+        self
+    }
+
+    /// Sets whether [`TempFile::file_name`] is sanitized using [`filename::sanitize()`].
+    ///
+    /// Defaults to false.
+    ///
+    /// [`filename::sanitize()`]: crate::filename::sanitize()
+    pub fn sanitize_file_names(mut self, sanitize_file_names: bool) -> Self {
+        self.sanitize_file_names = sanitize_file_names;
+        self
+    }
+
+    /// Sets the algorithm used to compute a checksum of each file as it is written.
+    ///
+    /// The result is available as [`TempFile::checksum`]. By default no checksum is computed.
+    #[cfg(feature = "checksum")]
+    pub fn checksum(mut self, algorithm: ChecksumAlgorithm) -> Self {
+        self.checksum = Some(algorithm);
+        self
+    }
+
+    /// Sets an async function that inspects each file after it has been completely written and
+    /// before it is accepted, e.g., to scan it for viruses or to verify its magic bytes.
+    ///
+    /// Returning an error rejects the file, which is deleted, and aborts extraction of the rest of
+    /// the form. The error is returned as the `source` of a [`MultipartError::Field`] error, so its
+    /// status code is used for the response.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Read as _;
+    ///
+    /// use actix_multipart::form::tempfile::TempFileConfig;
+    /// use actix_web::error::ErrorUnsupportedMediaType;
+    ///
+    /// let config = TempFileConfig::default().inspect(|_req, temp_file| {
+    ///     Box::pin(async move {
+    ///         let mut magic = [0; 4];
+    ///         temp_file.file.reopen()?.read_exact(&mut magic)?;
+    ///
+    ///         if magic != *b"%PDF" {
+    ///             return Err(ErrorUnsupportedMediaType("expected a PDF file"));
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    /// });
+    /// ```
+    pub fn inspect<F>(mut self, f: F) -> Self
+    where
+        F: for<'a> Fn(&'a HttpRequest, &'a TempFile) -> LocalBoxFuture<'a, Result<(), Error>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.inspector = Some(Arc::new(f));
+        self
+    }
 }
 
 const DEFAULT_CONFIG: TempFileConfig = TempFileConfig {
     err_handler: None,
     directory: None,
+    prefix: None,
+    suffix: None,
+    permissions: None,
+    sanitize_file_names: false,
+    #[cfg(feature = "checksum")]
+    checksum: None,
+    inspector: None,
 };
 
 impl Default for TempFileConfig {
@@ -167,7 +533,8 @@ mod tests {
     use actix_multipart_rfc7578::client::multipart;
     use actix_web::{http::StatusCode, web, App, HttpResponse, Responder};
 
-    use crate::form::{tempfile::TempFile, tests::send_form, MultipartForm};
+    use super::TempFile;
+    use crate::form::{tests::send_form, MultipartForm};
 
     #[derive(MultipartForm)]
     struct FileForm {
@@ -194,4 +561,265 @@ mod tests {
         let response = send_form(&srv, form, "/").await;
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[actix_rt::test]
+    async fn test_sanitize_file_names() {
+        use crate::form::tempfile::TempFileConfig;
+
+        async fn raw_route(form: MultipartForm<FileForm>) -> impl Responder {
+            assert_eq!(form.file.file_name.as_deref(), Some("../secret.txt"));
+            assert_eq!(
+                form.file.sanitized_file_name().as_deref(),
+                Some("secret.txt")
+            );
+            HttpResponse::Ok().finish()
+        }
+
+        async fn sanitized_route(form: MultipartForm<FileForm>) -> impl Responder {
+            assert_eq!(form.file.file_name.as_deref(), Some("secret.txt"));
+            HttpResponse::Ok().finish()
+        }
+
+        let srv = actix_test::start(|| {
+            App::new().route("/raw", web::post().to(raw_route)).service(
+                web::resource("/sanitized")
+                    .app_data(TempFileConfig::default().sanitize_file_names(true))
+                    .route(web::post().to(sanitized_route)),
+            )
+        });
+
+        for path in ["/raw", "/sanitized"] {
+            let mut form = multipart::Form::default();
+            let bytes = Cursor::new("Hello, world!");
+            form.add_reader_file_with_mime("file", bytes, "../secret.txt", mime::TEXT_PLAIN);
+            let response = send_form(&srv, form, path).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_file_naming() {
+        use crate::form::tempfile::TempFileConfig;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().to_owned();
+
+        let srv = actix_test::start(move || {
+            let dir_path = dir_path.clone();
+
+            let mut config = TempFileConfig::default()
+                .directory(&dir_path)
+                .prefix("upload-")
+                .suffix(".bin");
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt as _;
+                config = config.permissions(std::fs::Permissions::from_mode(0o600));
+            }
+
+            App::new().app_data(config).route(
+                "/",
+                web::post().to(move |form: MultipartForm<FileForm>| {
+                    let dir_path = dir_path.clone();
+
+                    async move {
+                        let path = form.file.file.path();
+                        assert_eq!(path.parent().unwrap(), dir_path);
+
+                        let name = path.file_name().unwrap().to_str().unwrap();
+                        assert!(name.starts_with("upload-"));
+                        assert!(name.ends_with(".bin"));
+
+                        #[cfg(unix)]
+                        {
+                            use std::os::unix::fs::PermissionsExt as _;
+                            let mode = path.metadata().unwrap().permissions().mode();
+                            assert_eq!(mode & 0o777, 0o600);
+                        }
+
+                        HttpResponse::Ok().finish()
+                    }
+                }),
+            )
+        });
+
+        let mut form = multipart::Form::default();
+        let bytes = Cursor::new("Hello, world!");
+        form.add_reader_file_with_mime("file", bytes, "testfile.txt", mime::TEXT_PLAIN);
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    fn temp_file(dir: &std::path::Path, contents: &str) -> TempFile {
+        use std::io::Write as _;
+
+        let mut file = tempfile::NamedTempFile::new_in(dir).unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+
+        TempFile {
+            file,
+            content_type: None,
+            file_name: None,
+            size: contents.len(),
+            checksum: None,
+        }
+    }
+
+    #[test]
+    fn test_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("upload.txt");
+
+        temp_file(dir.path(), "first").persist_to(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first");
+
+        temp_file(dir.path(), "second").persist_to(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+
+        let err = temp_file(dir.path(), "third")
+            .persist_noclobber(&path)
+            .unwrap_err();
+        assert_eq!(err.error.kind(), std::io::ErrorKind::AlreadyExists);
+        assert!(err.file.file.path().exists());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+
+        let other_path = dir.path().join("other.txt");
+        err.file.persist_noclobber(&other_path).unwrap();
+        assert_eq!(std::fs::read_to_string(&other_path).unwrap(), "third");
+    }
+
+    #[test]
+    fn test_persist_by_copy() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let dest_dir = tempfile::tempdir().unwrap();
+        let path = dest_dir.path().join("upload.txt");
+
+        let file = temp_file(src_dir.path(), "copied");
+        file.persist_by_copy(&path, true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "copied");
+
+        // source file is left in place, to be cleaned up on drop
+        assert!(file.file.path().exists());
+
+        let err = file.persist_by_copy(&path, true).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+
+        // no stray temp files are left in the destination directory
+        assert_eq!(std::fs::read_dir(dest_dir.path()).unwrap().count(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_inspect() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        use crate::form::{tempfile::TempFileConfig, text::Text};
+
+        #[derive(MultipartForm)]
+        struct InspectForm {
+            file: TempFile,
+            after: Option<Text<String>>,
+        }
+
+        let after_reads = Arc::new(AtomicUsize::new(0));
+        let after_reads2 = Arc::clone(&after_reads);
+
+        let srv = actix_test::start(move || {
+            let after_reads = Arc::clone(&after_reads2);
+
+            App::new()
+                .app_data(TempFileConfig::default().inspect(|_req, temp_file| {
+                    Box::pin(async move {
+                        let mut contents = String::new();
+                        temp_file.file.reopen()?.read_to_string(&mut contents)?;
+
+                        if contents.contains("EICAR") {
+                            return Err(actix_web::error::ErrorUnprocessableEntity("infected"));
+                        }
+
+                        Ok(())
+                    })
+                }))
+                .route(
+                    "/",
+                    web::post().to(move |form: MultipartForm<InspectForm>| {
+                        if form.after.is_some() {
+                            after_reads.fetch_add(1, Ordering::SeqCst);
+                        }
+
+                        async { HttpResponse::Ok().finish() }
+                    }),
+                )
+        });
+
+        let mut form = multipart::Form::default();
+        let bytes = Cursor::new("Hello, world!");
+        form.add_reader_file_with_mime("file", bytes, "testfile.txt", mime::TEXT_PLAIN);
+        form.add_text("after", "value");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(after_reads.load(Ordering::SeqCst), 1);
+
+        let mut form = multipart::Form::default();
+        let bytes = Cursor::new("X5O!P%@AP EICAR-STANDARD-ANTIVIRUS-TEST-FILE!");
+        form.add_reader_file_with_mime("file", bytes, "eicar.txt", mime::TEXT_PLAIN);
+        form.add_text("after", "value");
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(after_reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "checksum")]
+    #[actix_rt::test]
+    async fn test_file_checksum() {
+        use crate::form::tempfile::{ChecksumAlgorithm, TempFileConfig};
+
+        async fn sha256_route(form: MultipartForm<FileForm>) -> impl Responder {
+            let checksum = form.file.checksum.as_ref().unwrap();
+            assert_eq!(checksum.algorithm, ChecksumAlgorithm::Sha256);
+            assert_eq!(
+                checksum.to_hex(),
+                "315f5bdb76d078c43b8ac0064e4a0164612b1fce77c869345bfc94c75894edd3",
+            );
+            HttpResponse::Ok().finish()
+        }
+
+        async fn md5_route(form: MultipartForm<FileForm>) -> impl Responder {
+            let checksum = form.file.checksum.as_ref().unwrap();
+            assert_eq!(checksum.algorithm, ChecksumAlgorithm::Md5);
+            assert_eq!(checksum.to_hex(), "6cd3556deb0da54bca060b4c39479839");
+            HttpResponse::Ok().finish()
+        }
+
+        async fn no_checksum_route(form: MultipartForm<FileForm>) -> impl Responder {
+            assert!(form.file.checksum.is_none());
+            HttpResponse::Ok().finish()
+        }
+
+        let srv = actix_test::start(|| {
+            App::new()
+                .service(
+                    web::resource("/sha256")
+                        .app_data(TempFileConfig::default().checksum(ChecksumAlgorithm::Sha256))
+                        .route(web::post().to(sha256_route)),
+                )
+                .service(
+                    web::resource("/md5")
+                        .app_data(TempFileConfig::default().checksum(ChecksumAlgorithm::Md5))
+                        .route(web::post().to(md5_route)),
+                )
+                .route("/none", web::post().to(no_checksum_route))
+        });
+
+        for path in ["/sha256", "/md5", "/none"] {
+            let mut form = multipart::Form::default();
+            let bytes = Cursor::new("Hello, world!");
+            form.add_reader_file_with_mime("file", bytes, "testfile.txt", mime::TEXT_PLAIN);
+            let response = send_form(&srv, form, path).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
 }
@@ -1,9 +1,10 @@
 //! Deserializes a field from plain text.
 
-use std::{str, sync::Arc};
+use std::{borrow::Cow, str, sync::Arc};
 
 use actix_web::{http::StatusCode, web, Error, HttpRequest, ResponseError};
 use derive_more::derive::{Deref, DerefMut, Display, Error};
+use encoding_rs::{Encoding, UTF_8};
 use futures_core::future::LocalBoxFuture;
 use serde::de::DeserializeOwned;
 
@@ -17,7 +18,11 @@ use crate::{
 ///
 /// Internally this uses [`serde_plain`] for deserialization, which supports primitive types
 /// including strings, numbers, and simple enums.
-#[derive(Debug, Deref, DerefMut)]
+///
+/// The field's data is decoded using the `charset` parameter of its Content-Type header, falling
+/// back to the [default charset](TextConfig::default_charset) (UTF-8, unless configured) when the
+/// parameter is absent.
+#[derive(Debug, Default, Deref, DerefMut)]
 pub struct Text<T: DeserializeOwned>(pub T);
 
 impl<T: DeserializeOwned> Text<T> {
@@ -56,14 +61,45 @@ where
 
             let form_field_name = field.form_field_name.clone();
 
-            let bytes = Bytes::read_field(req, field, limits).await?;
+            let charset = field
+                .content_type()
+                .and_then(|mime| mime.get_param(mime::CHARSET));
+
+            let encoding = match charset {
+                Some(label) => Encoding::for_label(label.as_str().as_bytes()).ok_or_else(|| {
+                    MultipartError::Field {
+                        name: form_field_name.clone(),
+                        source: config
+                            .map_error(req, TextError::UnknownCharset(label.as_str().to_owned())),
+                    }
+                })?,
+                None => config.default_charset.unwrap_or(UTF_8),
+            };
 
-            let text = str::from_utf8(&bytes.data).map_err(|err| MultipartError::Field {
-                name: form_field_name.clone(),
-                source: config.map_error(req, TextError::Utf8Error(err)),
-            })?;
+            let bytes = Bytes::read_field(req, field, limits).await?;
 
-            Ok(Text(serde_plain::from_str(text).map_err(|err| {
+            let text = if encoding == UTF_8 {
+                Cow::Borrowed(
+                    str::from_utf8(&bytes.data).map_err(|err| MultipartError::Field {
+                        name: form_field_name.clone(),
+                        source: config.map_error(req, TextError::Utf8Error(err)),
+                    })?,
+                )
+            } else {
+                encoding
+                    .decode_without_bom_handling_and_without_replacement(&bytes.data)
+                    .ok_or_else(|| MultipartError::Field {
+                        name: form_field_name.clone(),
+                        source: config.map_error(
+                            req,
+                            TextError::Decode {
+                                charset: encoding.name(),
+                            },
+                        ),
+                    })?
+            };
+
+            Ok(Text(serde_plain::from_str(&text).map_err(|err| {
                 MultipartError::Field {
                     name: form_field_name,
                     source: config.map_error(req, TextError::Deserialize(err)),
@@ -87,6 +123,14 @@ pub enum TextError {
     /// Content type error.
     #[display("Content type error")]
     ContentType,
+
+    /// Charset specified by the field's Content-Type is not supported.
+    #[display("Unknown charset: {_0}")]
+    UnknownCharset(#[error(not(source))] String),
+
+    /// Field data is not valid in its charset.
+    #[display("Text could not be decoded as {charset}")]
+    Decode { charset: &'static str },
 }
 
 impl ResponseError for TextError {
@@ -100,6 +144,7 @@ impl ResponseError for TextError {
 pub struct TextConfig {
     err_handler: FieldErrorHandler<TextError>,
     validate_content_type: bool,
+    default_charset: Option<&'static Encoding>,
 }
 
 impl TextConfig {
@@ -136,11 +181,28 @@ impl TextConfig {
         self.validate_content_type = validate_content_type;
         self
     }
+
+    /// Sets the charset used to decode fields whose `Content-Type` has no `charset` parameter.
+    ///
+    /// Defaults to UTF-8.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_multipart::form::text::TextConfig;
+    ///
+    /// // accept uploads from legacy clients that send Windows-1252 text without a charset
+    /// let config = TextConfig::default().default_charset(encoding_rs::WINDOWS_1252);
+    /// ```
+    pub fn default_charset(mut self, charset: &'static Encoding) -> Self {
+        self.default_charset = Some(charset);
+        self
+    }
 }
 
 const DEFAULT_CONFIG: TextConfig = TextConfig {
     err_handler: None,
     validate_content_type: true,
+    default_charset: None,
 };
 
 impl Default for TextConfig {
@@ -167,6 +229,19 @@ mod tests {
         number: Text<i32>,
     }
 
+    #[derive(MultipartForm)]
+    struct NameForm {
+        name: Text<String>,
+    }
+
+    async fn test_name_route(form: MultipartForm<NameForm>) -> impl Responder {
+        assert_eq!(*form.name, "Café Münster");
+        HttpResponse::Ok().finish()
+    }
+
+    /// "Café Münster" encoded as Windows-1252.
+    const WINDOWS_1252_NAME: &[u8] = b"Caf\xe9 M\xfcnster";
+
     async fn test_text_route(form: MultipartForm<TextForm>) -> impl Responder {
         assert_eq!(*form.number, 1025);
         HttpResponse::Ok().finish()
@@ -194,4 +269,58 @@ mod tests {
         let response = send_form(&srv, form, "/").await;
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[actix_rt::test]
+    async fn test_charset_decoding() {
+        let srv = actix_test::start(|| App::new().route("/", web::post().to(test_name_route)));
+
+        let mime = "text/plain; charset=windows-1252".parse().unwrap();
+        let mut form = multipart::Form::default();
+        form.add_reader_file_with_mime("name", Cursor::new(WINDOWS_1252_NAME), "", mime);
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // without a charset, the field is decoded as UTF-8
+        let mut form = multipart::Form::default();
+        form.add_reader_file_with_mime(
+            "name",
+            Cursor::new(WINDOWS_1252_NAME),
+            "",
+            mime::TEXT_PLAIN,
+        );
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let mime = "text/plain; charset=x-unknown".parse().unwrap();
+        let mut form = multipart::Form::default();
+        form.add_reader_file_with_mime("name", Cursor::new("Café Münster"), "", mime);
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn test_default_charset() {
+        let srv = actix_test::start(|| {
+            App::new()
+                .route("/", web::post().to(test_name_route))
+                .app_data(TextConfig::default().default_charset(encoding_rs::WINDOWS_1252))
+        });
+
+        let mut form = multipart::Form::default();
+        form.add_reader_file_with_mime(
+            "name",
+            Cursor::new(WINDOWS_1252_NAME),
+            "",
+            mime::TEXT_PLAIN,
+        );
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // an explicit charset takes precedence over the default
+        let mime = "text/plain; charset=utf-8".parse().unwrap();
+        let mut form = multipart::Form::default();
+        form.add_reader_file_with_mime("name", Cursor::new("Café Münster"), "", mime);
+        let response = send_form(&srv, form, "/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
 }
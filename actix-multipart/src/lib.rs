@@ -64,17 +64,21 @@
 #[cfg(test)]
 extern crate self as actix_multipart;
 
+pub mod body;
 mod error;
 mod extractor;
 pub(crate) mod field;
+pub mod filename;
 pub mod form;
 mod multipart;
 pub(crate) mod payload;
 pub(crate) mod safety;
 pub mod test;
+#[cfg(feature = "tus")]
+pub mod tus;
 
 pub use self::{
-    error::Error as MultipartError,
+    error::{Error as MultipartError, ErrorKind as MultipartErrorKind},
     field::{Field, LimitExceeded},
     multipart::Multipart,
 };
@@ -100,10 +100,58 @@ impl Multipart {
                 boundary,
                 state: State::FirstBoundary,
                 item: Item::None,
+                allow_nested: false,
             }),
         }
     }
 
+    /// Sets the buffer size, in bytes, at which reading from the underlying payload is paused.
+    ///
+    /// Data is only read from the payload while fields are being polled. By default, all data that
+    /// is immediately available is buffered, so a slow consumer can cause a large amount of memory
+    /// to be used. Setting a high watermark makes slow consumers apply backpressure to the client
+    /// instead.
+    ///
+    /// The buffer may still grow past the high watermark when more data is needed to parse a part,
+    /// e.g., when its headers are larger than the watermark.
+    pub fn buffer_high_watermark(self, bytes: usize) -> Self {
+        self.configure_buffer(|buffer| buffer.high_watermark = bytes)
+    }
+
+    /// Sets the maximum size, in bytes, of chunks yielded by [`Field`]s.
+    ///
+    /// By default, all buffered field data is yielded as a single chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is 0.
+    pub fn max_chunk_size(self, bytes: usize) -> Self {
+        assert!(bytes > 0, "maximum chunk size must be greater than 0");
+        self.configure_buffer(|buffer| buffer.max_chunk_size = bytes)
+    }
+
+    /// Sets whether fields with nested multipart bodies are yielded.
+    ///
+    /// The parts of such fields can be read using [`Field::into_multipart()`]. By default, fields
+    /// with a `multipart/*` content type cause an [`Error::Nested`] error.
+    pub fn allow_nested(mut self, allow: bool) -> Self {
+        if let Flow::InFlight(ref mut inner) = self.flow {
+            inner.allow_nested = allow;
+        }
+
+        self
+    }
+
+    fn configure_buffer(self, f: impl FnOnce(&mut PayloadBuffer)) -> Self {
+        if let Flow::InFlight(ref inner) = self.flow {
+            if let Some(mut buffer) = inner.payload.get_mut(&self.safety) {
+                f(&mut buffer);
+            }
+        }
+
+        self
+    }
+
     /// Constructs a new multipart reader from given `MultipartError`.
     pub(crate) fn from_error(err: Error) -> Multipart {
         Multipart {
@@ -141,7 +189,15 @@ impl Stream for Multipart {
                     return Poll::Pending;
                 }
 
-                inner.poll(&this.safety, cx)
+                let res = inner.poll(&this.safety, cx);
+
+                if res.is_pending() {
+                    if let Some(mut buffer) = inner.payload.get_mut(&this.safety) {
+                        buffer.poll_stream_stalled(cx)?;
+                    }
+                }
+
+                res
             }
 
             Flow::Error(ref mut err) => Poll::Ready(Some(Err(err
@@ -185,6 +241,9 @@ struct Inner {
 
     state: State,
     item: Item,
+
+    /// Whether fields with nested multipart bodies are yielded.
+    allow_nested: bool,
 }
 
 impl Inner {
@@ -438,10 +497,12 @@ impl Inner {
 
             self.state = State::Boundary;
 
-            // nested multipart stream is not supported
-            if let Some(mime) = &field_content_type {
-                if mime.type_() == mime::MULTIPART {
-                    return Poll::Ready(Some(Err(Error::Nested)));
+            // nested multipart bodies are only yielded when allowed; see `Field::into_multipart`
+            if !self.allow_nested {
+                if let Some(mime) = &field_content_type {
+                    if mime.type_() == mime::MULTIPART {
+                        return Poll::Ready(Some(Err(Error::Nested)));
+                    }
                 }
             }
 
@@ -718,6 +779,63 @@ mod tests {
         }
     }
 
+    #[actix_rt::test]
+    async fn test_buffer_high_watermark() {
+        let (bytes, headers) = create_simple_request_with_header();
+        let payload = stream::iter(
+            bytes
+                .chunks(7)
+                .map(Bytes::copy_from_slice)
+                .collect::<Vec<_>>(),
+        )
+        .map(Ok)
+        .interleave_pending();
+
+        // watermark is smaller than the field headers, which must still be parsed
+        let mut multipart = Multipart::new(&headers, payload).buffer_high_watermark(1);
+
+        let mut field = multipart.next().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("file"));
+        assert_eq!(get_whole_field(&mut field).await, "data");
+        drop(field);
+
+        assert!(multipart.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_max_chunk_size() {
+        let (sender, payload) = create_stream();
+        let (bytes, headers) = create_double_request_with_header();
+        sender.send(Ok(bytes)).unwrap();
+        drop(sender); // eof
+
+        let mut multipart = Multipart::new(&headers, payload).max_chunk_size(3);
+
+        // field with Content-Length
+        let mut field = multipart.next().await.unwrap().unwrap();
+        assert_eq!(field.next().await.unwrap().unwrap(), "tes");
+        assert_eq!(field.next().await.unwrap().unwrap(), "t");
+        assert!(field.next().await.is_none());
+        drop(field);
+
+        let mut field = multipart.next().await.unwrap().unwrap();
+        assert_eq!(get_whole_field(&mut field).await, "data");
+        drop(field);
+
+        let (sender, payload) = create_stream();
+        let (bytes, headers) = create_simple_request_with_header();
+        sender.send(Ok(bytes)).unwrap();
+        drop(sender); // eof
+
+        let mut multipart = Multipart::new(&headers, payload).max_chunk_size(3);
+
+        // field without Content-Length
+        let mut field = multipart.next().await.unwrap().unwrap();
+        assert_eq!(field.next().await.unwrap().unwrap(), "dat");
+        assert_eq!(field.next().await.unwrap().unwrap(), "a");
+        assert!(field.next().await.is_none());
+    }
+
     #[actix_rt::test]
     async fn test_multipart_from_error() {
         let err = Error::ContentTypeMissing;
@@ -880,4 +998,118 @@ mod tests {
         let _ = multipart.next().await.unwrap().unwrap();
         task.await.unwrap();
     }
+
+    #[actix_rt::test]
+    async fn nested_multipart_mixed() {
+        let bytes = Bytes::from(
+            "--abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+             Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+             photos\r\n\
+             --abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+             Content-Disposition: form-data; name=\"files\"\r\n\
+             Content-Type: multipart/mixed; boundary=BbC04y\r\n\r\n\
+             --BbC04y\r\n\
+             Content-Disposition: file; filename=\"file1.txt\"\r\n\
+             Content-Type: text/plain\r\n\r\n\
+             one\r\n\
+             --BbC04y\r\n\
+             Content-Disposition: file; filename=\"file2.txt\"\r\n\
+             Content-Type: text/plain\r\n\r\n\
+             two\r\n\
+             --BbC04y--\r\n\
+             --abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+             Content-Disposition: form-data; name=\"after\"\r\n\r\n\
+             done\r\n\
+             --abbc761f78ff4d7cb7573b5a23f96ef0--\r\n",
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static(
+                "multipart/form-data; boundary=\"abbc761f78ff4d7cb7573b5a23f96ef0\"",
+            ),
+        );
+
+        let payload = stream::iter(bytes)
+            .map(|byte| Ok(Bytes::copy_from_slice(&[byte])))
+            .interleave_pending();
+        let mut multipart = Multipart::new(&headers, payload).allow_nested(true);
+
+        let mut field = multipart.next().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("title"));
+        assert_eq!(get_whole_field(&mut field).await, "photos");
+        drop(field);
+
+        let field = multipart.next().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("files"));
+
+        let mut nested = field.into_multipart();
+
+        let mut file = nested.next().await.unwrap().unwrap();
+        assert_eq!(
+            file.headers().get(&header::CONTENT_DISPOSITION).unwrap(),
+            "file; filename=\"file1.txt\""
+        );
+        assert_eq!(get_whole_field(&mut file).await, "one");
+        drop(file);
+
+        let mut file = nested.next().await.unwrap().unwrap();
+        assert_eq!(
+            file.headers().get(&header::CONTENT_DISPOSITION).unwrap(),
+            "file; filename=\"file2.txt\""
+        );
+        assert_eq!(get_whole_field(&mut file).await, "two");
+        drop(file);
+
+        assert!(nested.next().await.is_none());
+        drop(nested);
+
+        let mut field = multipart.next().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("after"));
+        assert_eq!(get_whole_field(&mut field).await, "done");
+        drop(field);
+
+        assert!(multipart.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn nested_multipart_not_allowed_by_default() {
+        let bytes = Bytes::from(
+            "--abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+             Content-Disposition: form-data; name=\"files\"\r\n\
+             Content-Type: multipart/mixed; boundary=BbC04y\r\n\r\n\
+             --BbC04y\r\n\
+             Content-Disposition: file; filename=\"file1.txt\"\r\n\r\n\
+             one\r\n\
+             --BbC04y--\r\n\
+             --abbc761f78ff4d7cb7573b5a23f96ef0--\r\n",
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static(
+                "multipart/form-data; boundary=\"abbc761f78ff4d7cb7573b5a23f96ef0\"",
+            ),
+        );
+
+        let mut multipart = Multipart::new(&headers, stream::iter([Ok(bytes)]));
+        assert_matches!(multipart.next().await.unwrap().unwrap_err(), Error::Nested);
+    }
+
+    #[actix_rt::test]
+    async fn into_multipart_requires_multipart_content_type() {
+        let (sender, payload) = create_stream();
+        let (bytes, headers) = create_simple_request_with_header();
+        sender.send(Ok(bytes)).unwrap();
+        drop(sender); // eof
+
+        let mut multipart = Multipart::new(&headers, payload);
+        let field = multipart.next().await.unwrap().unwrap();
+
+        let mut nested = field.into_multipart();
+        assert_matches!(
+            nested.next().await.unwrap().unwrap_err(),
+            Error::ContentTypeIncompatible
+        );
+    }
 }
@@ -48,6 +48,13 @@ pub(crate) struct PayloadBuffer {
     pub(crate) buf: BytesMut,
     /// EOF flag. If true, no more payload reads will be attempted.
     pub(crate) eof: bool,
+    /// Buffer size at which reading from the stream is paused.
+    pub(crate) high_watermark: usize,
+    /// Maximum size of chunks yielded by fields.
+    pub(crate) max_chunk_size: usize,
+    /// True if the last read was paused by the high watermark, in which case no waker is
+    /// registered with the stream.
+    throttled: bool,
 }
 
 impl PayloadBuffer {
@@ -60,11 +67,22 @@ impl PayloadBuffer {
             stream: Box::pin(stream),
             buf: BytesMut::with_capacity(1_024), // pre-allocate 1KiB
             eof: false,
+            high_watermark: usize::MAX,
+            max_chunk_size: usize::MAX,
+            throttled: false,
         }
     }
 
+    /// Reads from the stream into the buffer until it is pending or the high watermark is reached.
     pub(crate) fn poll_stream(&mut self, cx: &mut Context<'_>) -> Result<(), PayloadError> {
         loop {
+            if self.buf.len() >= self.high_watermark {
+                self.throttled = true;
+                return Ok(());
+            }
+
+            self.throttled = false;
+
             match Pin::new(&mut self.stream).poll_next(cx) {
                 Poll::Ready(Some(Ok(data))) => {
                     self.buf.extend_from_slice(&data);
@@ -81,6 +99,34 @@ impl PayloadBuffer {
         }
     }
 
+    /// Reads from the stream past the high watermark, for use when the buffered data was not
+    /// enough to make progress.
+    ///
+    /// Without this, a parser waiting on more data than the high watermark allows would never be
+    /// woken up again.
+    pub(crate) fn poll_stream_stalled(&mut self, cx: &mut Context<'_>) -> Result<(), PayloadError> {
+        if !self.throttled || self.eof {
+            return Ok(());
+        }
+
+        self.throttled = false;
+
+        match Pin::new(&mut self.stream).poll_next(cx) {
+            Poll::Ready(Some(Ok(data))) => {
+                self.buf.extend_from_slice(&data);
+                cx.waker().wake_by_ref();
+            }
+            Poll::Ready(Some(Err(err))) => return Err(err),
+            Poll::Ready(None) => {
+                self.eof = true;
+                cx.waker().wake_by_ref();
+            }
+            Poll::Pending => {}
+        }
+
+        Ok(())
+    }
+
     /// Reads exact number of bytes.
     #[cfg(test)]
     pub(crate) fn read_exact(&mut self, size: usize) -> Option<Bytes> {
@@ -93,7 +139,8 @@ impl PayloadBuffer {
 
     pub(crate) fn read_max(&mut self, size: u64) -> Result<Option<Bytes>, Error> {
         if !self.buf.is_empty() {
-            let size = cmp::min(self.buf.len() as u64, size) as usize;
+            let size = cmp::min(self.buf.len() as u64, size);
+            let size = cmp::min(size, self.max_chunk_size as u64) as usize;
             Ok(Some(self.buf.split_to(size).freeze()))
         } else if self.eof {
             Err(Error::Incomplete)
@@ -252,4 +299,39 @@ mod tests {
         );
         assert_eq!(payload.buf.len(), 0);
     }
+
+    #[actix_rt::test]
+    async fn high_watermark() {
+        let (mut sender, payload) = h1::Payload::create(false);
+        let mut payload = PayloadBuffer::new(payload);
+        payload.high_watermark = 5;
+
+        sender.feed_data(Bytes::from("line1"));
+        sender.feed_data(Bytes::from("line2"));
+        lazy(|cx| payload.poll_stream(cx)).await.unwrap();
+        assert_eq!(payload.buf.len(), 5);
+        assert!(payload.throttled);
+
+        // stalled reads ignore the watermark
+        lazy(|cx| payload.poll_stream_stalled(cx)).await.unwrap();
+        assert_eq!(payload.buf.len(), 10);
+        assert!(!payload.throttled);
+
+        assert_eq!(Some(Bytes::from("line1")), payload.read_max(5).unwrap());
+        lazy(|cx| payload.poll_stream(cx)).await.unwrap();
+        assert!(payload.throttled);
+    }
+
+    #[actix_rt::test]
+    async fn max_chunk_size() {
+        let (mut sender, payload) = h1::Payload::create(false);
+        let mut payload = PayloadBuffer::new(payload);
+        payload.max_chunk_size = 3;
+
+        sender.feed_data(Bytes::from("line1"));
+        lazy(|cx| payload.poll_stream(cx)).await.unwrap();
+
+        assert_eq!(Some(Bytes::from("lin")), payload.read_max(10).unwrap());
+        assert_eq!(Some(Bytes::from("e1")), payload.read_max(10).unwrap());
+    }
 }
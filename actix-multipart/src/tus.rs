@@ -0,0 +1,1311 @@
+//! Resumable uploads using the [tus] protocol.
+//!
+//! The [`scope()`] function creates a service that implements version 1.0.0 of the core protocol,
+//! along with the `creation`, `checksum`, and `expiration` extensions. Uploads are stored by a
+//! [`ResumableBackend`], which is a [`StorageBackend`] that also stores the state of uploads, so
+//! that they can be resumed on any worker. The service is configured using [`TusUploads`] in app
+//! data.
+//!
+//! Each `PATCH` request resumes the upload's sink and appends its body as it arrives. If a request
+//! is interrupted, the data received so far is kept and the client can resume from the offset
+//! reported by a `HEAD` request. Once all of an upload's data has been received, its sink is
+//! finished and the output is passed to [`ResumableBackend::complete_upload()`].
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{collections::HashMap, sync::Mutex, time::{Duration, SystemTime}};
+//!
+//! use actix_multipart::{
+//!     form::storage::{StorageBackend, StorageSink},
+//!     tus::{self, ResumableBackend, TusUploads, UploadInfo},
+//!     Field,
+//! };
+//! use actix_web::{web, App, Error, HttpRequest};
+//! use futures_core::future::LocalBoxFuture;
+//!
+//! /// Keeps uploads in memory.
+//! #[derive(Default)]
+//! struct Memory {
+//!     uploads: Mutex<HashMap<String, UploadInfo>>,
+//!     data: Mutex<HashMap<String, Vec<u8>>>,
+//! }
+//!
+//! struct MemorySink(Vec<u8>);
+//!
+//! impl StorageBackend for Memory {
+//!     type Sink = MemorySink;
+//!
+//!     fn create_sink<'a>(
+//!         &'a self,
+//!         _req: &'a HttpRequest,
+//!         _field: &'a Field,
+//!     ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>> {
+//!         Box::pin(async { Ok(MemorySink(Vec::new())) })
+//!     }
+//! }
+//!
+//! impl ResumableBackend for Memory {
+//!     fn create_upload_sink<'a>(
+//!         &'a self,
+//!         _req: &'a HttpRequest,
+//!         _upload: &'a UploadInfo,
+//!     ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>> {
+//!         Box::pin(async { Ok(MemorySink(Vec::new())) })
+//!     }
+//!
+//!     fn resume_upload_sink<'a>(
+//!         &'a self,
+//!         _req: &'a HttpRequest,
+//!         upload: &'a UploadInfo,
+//!     ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>> {
+//!         let mut data = self.data.lock().unwrap().remove(&upload.id).unwrap_or_default();
+//!         data.truncate(upload.offset as usize);
+//!         Box::pin(async { Ok(MemorySink(data)) })
+//!     }
+//!
+//!     fn suspend_upload_sink<'a>(
+//!         &'a self,
+//!         sink: Self::Sink,
+//!         upload: &'a UploadInfo,
+//!     ) -> LocalBoxFuture<'a, Result<(), Error>> {
+//!         self.data.lock().unwrap().insert(upload.id.clone(), sink.0);
+//!         Box::pin(async { Ok(()) })
+//!     }
+//!
+//!     fn complete_upload<'a>(
+//!         &'a self,
+//!         upload: &'a UploadInfo,
+//!         output: Vec<u8>,
+//!     ) -> LocalBoxFuture<'a, Result<(), Error>> {
+//!         println!("received {} bytes for upload {}", output.len(), upload.id);
+//!         Box::pin(async { Ok(()) })
+//!     }
+//!
+//!     fn save_upload<'a>(&'a self, upload: &'a UploadInfo) -> LocalBoxFuture<'a, Result<(), Error>> {
+//!         self.uploads.lock().unwrap().insert(upload.id.clone(), upload.clone());
+//!         Box::pin(async { Ok(()) })
+//!     }
+//!
+//!     fn load_upload<'a>(
+//!         &'a self,
+//!         id: &'a str,
+//!     ) -> LocalBoxFuture<'a, Result<Option<UploadInfo>, Error>> {
+//!         let upload = self.uploads.lock().unwrap().get(id).cloned();
+//!         Box::pin(async { Ok(upload) })
+//!     }
+//!
+//!     fn remove_upload<'a>(&'a self, id: &'a str) -> LocalBoxFuture<'a, Result<(), Error>> {
+//!         self.uploads.lock().unwrap().remove(id);
+//!         self.data.lock().unwrap().remove(id);
+//!         Box::pin(async { Ok(()) })
+//!     }
+//!
+//!     fn incomplete_uploads(&self) -> LocalBoxFuture<'_, Result<usize, Error>> {
+//!         let uploads = self.uploads.lock().unwrap();
+//!         let count = uploads.values().filter(|upload| !upload.is_complete()).count();
+//!         Box::pin(async move { Ok(count) })
+//!     }
+//!
+//!     fn remove_expired_uploads(&self, now: SystemTime) -> LocalBoxFuture<'_, Result<(), Error>> {
+//!         let mut uploads = self.uploads.lock().unwrap();
+//!         let mut data = self.data.lock().unwrap();
+//!
+//!         uploads.retain(|id, upload| {
+//!             let expired = upload.expires.is_some_and(|expires| expires <= now);
+//!             if expired {
+//!                 data.remove(id);
+//!             }
+//!             !expired
+//!         });
+//!
+//!         Box::pin(async { Ok(()) })
+//!     }
+//! }
+//!
+//! impl StorageSink for MemorySink {
+//!     type Output = Vec<u8>;
+//!
+//!     fn write_chunk(&mut self, chunk: web::Bytes) -> LocalBoxFuture<'_, Result<(), Error>> {
+//!         self.0.extend_from_slice(&chunk);
+//!         Box::pin(async { Ok(()) })
+//!     }
+//!
+//!     fn finish(self) -> LocalBoxFuture<'static, Result<Self::Output, Error>> {
+//!         Box::pin(async move { Ok(self.0) })
+//!     }
+//! }
+//!
+//! // shared between workers so that uploads can be resumed on any of them
+//! let backend = web::Data::new(Memory::default());
+//! let uploads = web::Data::new(
+//!     TusUploads::new()
+//!         .max_size(1024 * 1024 * 1024)
+//!         .max_uploads(100)
+//!         .expiry(Duration::from_secs(24 * 60 * 60)),
+//! );
+//!
+//! let app = App::new()
+//!     .app_data(backend.clone())
+//!     .app_data(uploads.clone())
+//!     .service(tus::scope::<Memory>("/uploads"));
+//! ```
+//!
+//! [tus]: https://tus.io/protocols/resumable-upload
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    future::ready,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, SystemTime},
+};
+
+use actix_web::{
+    error::PayloadError,
+    http::{
+        header::{self, HeaderMap, HeaderName, HeaderValue, HttpDate},
+        Method, StatusCode,
+    },
+    web, Error, HttpRequest, HttpResponse, ResponseError, Scope,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use derive_more::derive::{Display, Error};
+use futures_core::future::LocalBoxFuture;
+use futures_util::StreamExt as _;
+use rand::{
+    distributions::{Alphanumeric, DistString as _},
+    thread_rng,
+};
+use sha1::{Digest as _, Sha1};
+use sha2::Sha256;
+
+use crate::form::storage::{StorageBackend, StorageSink};
+
+/// Version of the tus protocol implemented by this module.
+const TUS_VERSION: &str = "1.0.0";
+
+/// Extensions of the tus protocol implemented by this module, except for `expiration`.
+const TUS_EXTENSIONS: &str = "creation,checksum";
+
+/// Checksum algorithms supported by the `checksum` extension.
+const TUS_CHECKSUM_ALGORITHMS: &str = "sha1,sha256";
+
+/// Media type of `PATCH` request bodies.
+const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+
+const TUS_RESUMABLE: HeaderName = HeaderName::from_static("tus-resumable");
+const TUS_VERSION_HEADER: HeaderName = HeaderName::from_static("tus-version");
+const TUS_EXTENSION: HeaderName = HeaderName::from_static("tus-extension");
+const TUS_MAX_SIZE: HeaderName = HeaderName::from_static("tus-max-size");
+const TUS_CHECKSUM_ALGORITHM: HeaderName = HeaderName::from_static("tus-checksum-algorithm");
+const UPLOAD_OFFSET: HeaderName = HeaderName::from_static("upload-offset");
+const UPLOAD_LENGTH: HeaderName = HeaderName::from_static("upload-length");
+const UPLOAD_METADATA: HeaderName = HeaderName::from_static("upload-metadata");
+const UPLOAD_CHECKSUM: HeaderName = HeaderName::from_static("upload-checksum");
+const UPLOAD_EXPIRES: HeaderName = HeaderName::from_static("upload-expires");
+
+/// A [`StorageBackend`] that can also store resumable uploads.
+///
+/// Along with the data of uploads, the backend stores their state, so that they can be resumed on
+/// any worker. An upload's sink receives its data in order, across as many requests as the client
+/// needs to send it: each request resumes the sink and suspends it once the request's data has been
+/// written. Once all of the data has been received, the sink is finished instead.
+pub trait ResumableBackend: StorageBackend {
+    /// Prepares a new sink for an upload that is being created.
+    fn create_upload_sink<'a>(
+        &'a self,
+        req: &'a HttpRequest,
+        upload: &'a UploadInfo,
+    ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>>;
+
+    /// Reopens the sink of an upload, so that data can be appended at its offset.
+    ///
+    /// Any data written past the upload's offset, such as by a request that was dropped before the
+    /// upload's state was saved, must be discarded.
+    fn resume_upload_sink<'a>(
+        &'a self,
+        req: &'a HttpRequest,
+        upload: &'a UploadInfo,
+    ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>>;
+
+    /// Closes the sink of an upload that is not complete, after a request has written to it.
+    ///
+    /// The default implementation drops the sink.
+    fn suspend_upload_sink<'a>(
+        &'a self,
+        sink: Self::Sink,
+        upload: &'a UploadInfo,
+    ) -> LocalBoxFuture<'a, Result<(), Error>> {
+        let _ = (sink, upload);
+        Box::pin(ready(Ok(())))
+    }
+
+    /// Receives the output of an upload's sink once all of its data has been received.
+    fn complete_upload<'a>(
+        &'a self,
+        upload: &'a UploadInfo,
+        output: <Self::Sink as StorageSink>::Output,
+    ) -> LocalBoxFuture<'a, Result<(), Error>>;
+
+    /// Stores the state of an upload, replacing any previously stored state.
+    fn save_upload<'a>(&'a self, upload: &'a UploadInfo) -> LocalBoxFuture<'a, Result<(), Error>>;
+
+    /// Loads the state of the upload with the given ID, if it exists.
+    fn load_upload<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> LocalBoxFuture<'a, Result<Option<UploadInfo>, Error>>;
+
+    /// Removes the state of an upload, along with its data if it is not complete.
+    fn remove_upload<'a>(&'a self, id: &'a str) -> LocalBoxFuture<'a, Result<(), Error>>;
+
+    /// Returns the number of stored uploads that are not complete.
+    ///
+    /// Only called when creating uploads if their number is limited using
+    /// [`TusUploads::max_uploads()`].
+    fn incomplete_uploads(&self) -> LocalBoxFuture<'_, Result<usize, Error>>;
+
+    /// Removes the uploads that expired at or before `now`, along with their data if they are not
+    /// complete.
+    ///
+    /// Only called when creating uploads if they expire, as set using [`TusUploads::expiry()`].
+    fn remove_expired_uploads(&self, now: SystemTime) -> LocalBoxFuture<'_, Result<(), Error>>;
+}
+
+/// Information about a resumable upload.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct UploadInfo {
+    /// The upload's ID, used as the last segment of its URL.
+    pub id: String,
+
+    /// The total size of the upload in bytes, given by the client when it was created.
+    pub length: u64,
+
+    /// The number of bytes received so far.
+    pub offset: u64,
+
+    /// The key-value pairs given by the client in the `Upload-Metadata` header.
+    pub metadata: HashMap<String, Option<String>>,
+
+    /// The time after which the upload can no longer be resumed, if it expires.
+    pub expires: Option<SystemTime>,
+}
+
+impl UploadInfo {
+    /// Constructs information about an upload with no data received, no metadata, and no expiry,
+    /// such as when loading an upload's state.
+    pub fn new(id: impl Into<String>, length: u64) -> Self {
+        Self {
+            id: id.into(),
+            length,
+            offset: 0,
+            metadata: HashMap::new(),
+            expires: None,
+        }
+    }
+
+    /// Returns true if all of the upload's data has been received.
+    pub fn is_complete(&self) -> bool {
+        self.offset >= self.length
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires
+            .is_some_and(|expires| expires <= SystemTime::now())
+    }
+}
+
+/// Configuration of the resumable uploads served by [`scope()`].
+///
+/// Add to your app data, either as `TusUploads` or `Data<TusUploads>`. Requests that write to an
+/// upload while another request is writing to it are rejected; using `Data` created outside of the
+/// app factory shares this between workers.
+pub struct TusUploads {
+    max_size: Option<u64>,
+    checksum_limit: usize,
+    expiry: Option<Duration>,
+    max_uploads: Option<usize>,
+
+    /// IDs of the uploads that `PATCH` requests are writing to.
+    busy: Mutex<HashSet<String>>,
+}
+
+impl TusUploads {
+    /// Constructs the default upload configuration.
+    pub fn new() -> Self {
+        Self {
+            max_size: None,
+            checksum_limit: 16_777_216, // 16 MiB
+            expiry: None,
+            max_uploads: None,
+            busy: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Sets the maximum size of an upload in bytes. By default the size is not limited.
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Sets the maximum size of a `PATCH` request body that has an `Upload-Checksum` header. By
+    /// default this limit is 16MiB.
+    ///
+    /// Since data must not be written until its checksum has been verified, such request bodies
+    /// are buffered in memory.
+    pub fn checksum_limit(mut self, checksum_limit: usize) -> Self {
+        self.checksum_limit = checksum_limit;
+        self
+    }
+
+    /// Sets how long uploads can be resumed after they are created. By default uploads do not
+    /// expire.
+    ///
+    /// Expired uploads are removed from the backend before new uploads are created, or when a
+    /// request tries to resume them.
+    pub fn expiry(mut self, expiry: Duration) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    /// Sets the maximum number of uploads that are not complete. By default the number of uploads
+    /// is not limited.
+    ///
+    /// Once the limit is reached, requests to create uploads are rejected with
+    /// `503 Service Unavailable`.
+    pub fn max_uploads(mut self, max_uploads: usize) -> Self {
+        self.max_uploads = Some(max_uploads);
+        self
+    }
+
+    /// Marks an upload as being written to until the returned guard is dropped.
+    fn lock<'a>(&'a self, id: &str) -> Result<BusyGuard<'a>, TusError> {
+        if !self.busy().insert(id.to_owned()) {
+            return Err(TusError::Locked);
+        }
+
+        Ok(BusyGuard {
+            uploads: self,
+            id: id.to_owned(),
+        })
+    }
+
+    fn busy(&self) -> MutexGuard<'_, HashSet<String>> {
+        self.busy.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Extracts the configuration from app data. Check both `T` and `Data<T>`, in that order.
+    fn from_req(req: &HttpRequest) -> Result<&Self, TusError> {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .ok_or(TusError::UploadsMissing)
+    }
+}
+
+impl Default for TusUploads {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for TusUploads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TusUploads")
+            .field("max_size", &self.max_size)
+            .field("checksum_limit", &self.checksum_limit)
+            .field("expiry", &self.expiry)
+            .field("max_uploads", &self.max_uploads)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Marks an upload as being written to by a `PATCH` request.
+struct BusyGuard<'a> {
+    uploads: &'a TusUploads,
+    id: String,
+}
+
+impl Drop for BusyGuard<'_> {
+    fn drop(&mut self) {
+        self.uploads.busy().remove(&self.id);
+    }
+}
+
+/// Creates a service implementing the tus protocol at `path`.
+///
+/// Uploads are created by `POST` requests to `path` and accessed at `{path}/{id}`. The backend `B`
+/// and a [`TusUploads`] configuration must be registered in app data.
+pub fn scope<B: ResumableBackend>(path: &str) -> Scope {
+    web::scope(path)
+        .route("", web::post().to(create::<B>))
+        .route("", web::route().method(Method::OPTIONS).to(options))
+        .route("/{id}", web::head().to(head::<B>))
+        .route("/{id}", web::patch().to(patch::<B>))
+        .route("/{id}", web::route().method(Method::OPTIONS).to(options))
+}
+
+async fn options(req: HttpRequest) -> Result<HttpResponse, TusError> {
+    let uploads = TusUploads::from_req(&req)?;
+
+    let extensions = match uploads.expiry {
+        Some(_) => format!("{TUS_EXTENSIONS},expiration"),
+        None => TUS_EXTENSIONS.to_owned(),
+    };
+
+    let mut res = HttpResponse::NoContent();
+    res.insert_header((TUS_RESUMABLE, TUS_VERSION))
+        .insert_header((TUS_VERSION_HEADER, TUS_VERSION))
+        .insert_header((TUS_EXTENSION, extensions))
+        .insert_header((TUS_CHECKSUM_ALGORITHM, TUS_CHECKSUM_ALGORITHMS));
+
+    if let Some(max_size) = uploads.max_size {
+        res.insert_header((TUS_MAX_SIZE, max_size));
+    }
+
+    Ok(res.finish())
+}
+
+/// Extracts the backend from app data. Check both `B` and `Data<B>`, in that order.
+fn backend<B: ResumableBackend>(req: &HttpRequest) -> Result<&B, TusError> {
+    req.app_data::<B>()
+        .or_else(|| req.app_data::<web::Data<B>>().map(|d| d.as_ref()))
+        .ok_or(TusError::BackendMissing)
+}
+
+/// Loads the state of an upload, removing it if it has expired.
+async fn load_upload<B: ResumableBackend>(backend: &B, id: &str) -> Result<UploadInfo, TusError> {
+    let upload = backend
+        .load_upload(id)
+        .await
+        .map_err(TusError::Storage)?
+        .ok_or(TusError::NotFound)?;
+
+    if upload.is_expired() {
+        backend.remove_upload(id).await.map_err(TusError::Storage)?;
+        return Err(TusError::Expired);
+    }
+
+    Ok(upload)
+}
+
+/// Finishes the sink of an upload whose data has all been received, or suspends it otherwise, and
+/// saves the upload's state.
+async fn store_upload<B: ResumableBackend>(
+    backend: &B,
+    upload: &UploadInfo,
+    sink: B::Sink,
+) -> Result<(), TusError> {
+    if upload.is_complete() {
+        let output = match sink.finish().await {
+            Ok(output) => output,
+            Err(err) => {
+                // the upload can not be resumed once its sink failed
+                let _ = backend.remove_upload(&upload.id).await;
+                return Err(TusError::Storage(err));
+            }
+        };
+
+        backend
+            .complete_upload(upload, output)
+            .await
+            .map_err(TusError::Storage)?;
+    } else {
+        backend
+            .suspend_upload_sink(sink, upload)
+            .await
+            .map_err(TusError::Storage)?;
+    }
+
+    backend.save_upload(upload).await.map_err(TusError::Storage)
+}
+
+async fn create<B: ResumableBackend>(req: HttpRequest) -> Result<HttpResponse, TusError> {
+    check_version(req.headers())?;
+
+    let uploads = TusUploads::from_req(&req)?;
+    let backend = backend::<B>(&req)?;
+
+    let length =
+        parse_header::<u64>(req.headers(), &UPLOAD_LENGTH)?.ok_or(TusError::UploadLengthMissing)?;
+
+    if uploads.max_size.is_some_and(|max_size| length > max_size) {
+        return Err(TusError::TooLarge);
+    }
+
+    let metadata = match req.headers().get(&UPLOAD_METADATA) {
+        Some(value) => parse_metadata(value).ok_or(TusError::InvalidHeader(UPLOAD_METADATA))?,
+        None => HashMap::new(),
+    };
+
+    let now = SystemTime::now();
+
+    if uploads.expiry.is_some() {
+        backend
+            .remove_expired_uploads(now)
+            .await
+            .map_err(TusError::Storage)?;
+    }
+
+    if let Some(max_uploads) = uploads.max_uploads {
+        let count = backend
+            .incomplete_uploads()
+            .await
+            .map_err(TusError::Storage)?;
+
+        if count >= max_uploads {
+            return Err(TusError::TooManyUploads);
+        }
+    }
+
+    let mut info = UploadInfo::new(Alphanumeric.sample_string(&mut thread_rng(), 32), length);
+    info.metadata = metadata;
+    info.expires = uploads.expiry.map(|expiry| now + expiry);
+
+    let sink = backend
+        .create_upload_sink(&req, &info)
+        .await
+        .map_err(TusError::Storage)?;
+
+    store_upload(backend, &info, sink).await?;
+
+    let location = format!("{}/{}", req.path().trim_end_matches('/'), info.id);
+
+    let mut res = HttpResponse::Created();
+    res.insert_header((TUS_RESUMABLE, TUS_VERSION))
+        .insert_header((header::LOCATION, location));
+
+    if let Some(expires) = info.expires {
+        res.insert_header((UPLOAD_EXPIRES, HttpDate::from(expires)));
+    }
+
+    Ok(res.finish())
+}
+
+async fn head<B: ResumableBackend>(
+    req: HttpRequest,
+    id: web::Path<String>,
+) -> Result<HttpResponse, TusError> {
+    check_version(req.headers())?;
+
+    let info = load_upload(backend::<B>(&req)?, &id).await?;
+
+    let mut res = HttpResponse::Ok();
+    res.insert_header((TUS_RESUMABLE, TUS_VERSION))
+        .insert_header((UPLOAD_OFFSET, info.offset))
+        .insert_header((UPLOAD_LENGTH, info.length))
+        .insert_header((header::CACHE_CONTROL, "no-store"));
+
+    if !info.metadata.is_empty() {
+        res.insert_header((UPLOAD_METADATA, encode_metadata(&info.metadata)));
+    }
+
+    Ok(res.finish())
+}
+
+async fn patch<B: ResumableBackend>(
+    req: HttpRequest,
+    id: web::Path<String>,
+    mut payload: web::Payload,
+) -> Result<HttpResponse, TusError> {
+    check_version(req.headers())?;
+
+    let uploads = TusUploads::from_req(&req)?;
+    let backend = backend::<B>(&req)?;
+
+    let content_type = req.headers().get(header::CONTENT_TYPE);
+    if content_type.and_then(|ct| ct.to_str().ok()) != Some(OFFSET_OCTET_STREAM) {
+        return Err(TusError::ContentType);
+    }
+
+    let offset = parse_header::<u64>(req.headers(), &UPLOAD_OFFSET)?
+        .ok_or(TusError::InvalidHeader(UPLOAD_OFFSET))?;
+
+    let checksum = match req.headers().get(&UPLOAD_CHECKSUM) {
+        Some(value) => Some(Checksum::parse(value)?),
+        None => None,
+    };
+
+    let _busy = uploads.lock(&id)?;
+    let mut info = load_upload(backend, &id).await?;
+
+    if info.offset != offset {
+        return Err(TusError::OffsetMismatch);
+    }
+
+    if info.is_complete() {
+        return Ok(offset_response(&info));
+    }
+
+    let mut sink = backend
+        .resume_upload_sink(&req, &info)
+        .await
+        .map_err(TusError::Storage)?;
+
+    let written = write_body(
+        &mut sink,
+        &mut info,
+        &mut payload,
+        checksum,
+        uploads.checksum_limit,
+    )
+    .await;
+
+    if let Err(TusError::Storage(err)) = written {
+        // the upload can not be resumed once its sink failed
+        sink.abort().await;
+        let _ = backend.remove_upload(&info.id).await;
+        return Err(TusError::Storage(err));
+    }
+
+    // keep the data written before any other error, so that the client can resume after it
+    store_upload(backend, &info, sink).await?;
+    written?;
+
+    Ok(offset_response(&info))
+}
+
+/// Writes a `PATCH` request body to an upload's sink, advancing its offset.
+async fn write_body<S: StorageSink>(
+    sink: &mut S,
+    info: &mut UploadInfo,
+    payload: &mut web::Payload,
+    checksum: Option<Checksum>,
+    checksum_limit: usize,
+) -> Result<(), TusError> {
+    let check_remaining = |info: &UploadInfo, len: usize| {
+        if info.offset + len as u64 > info.length {
+            Err(TusError::ExceedsLength)
+        } else {
+            Ok(())
+        }
+    };
+
+    let Some(checksum) = checksum else {
+        while let Some(chunk) = payload.next().await {
+            let chunk = chunk.map_err(TusError::Payload)?;
+            check_remaining(info, chunk.len())?;
+
+            let len = chunk.len() as u64;
+            sink.write_chunk(chunk).await.map_err(TusError::Storage)?;
+            info.offset += len;
+        }
+
+        return Ok(());
+    };
+
+    let mut body = web::BytesMut::new();
+
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(TusError::Payload)?;
+
+        if body.len() + chunk.len() > checksum_limit {
+            return Err(TusError::TooLarge);
+        }
+
+        check_remaining(info, body.len() + chunk.len())?;
+        body.extend_from_slice(&chunk);
+    }
+
+    if !checksum.verify(&body) {
+        return Err(TusError::ChecksumMismatch);
+    }
+
+    let len = body.len() as u64;
+    sink.write_chunk(body.freeze())
+        .await
+        .map_err(TusError::Storage)?;
+    info.offset += len;
+
+    Ok(())
+}
+
+fn offset_response(info: &UploadInfo) -> HttpResponse {
+    let mut res = HttpResponse::NoContent();
+    res.insert_header((TUS_RESUMABLE, TUS_VERSION))
+        .insert_header((UPLOAD_OFFSET, info.offset));
+
+    if let Some(expires) = info.expires {
+        res.insert_header((UPLOAD_EXPIRES, HttpDate::from(expires)));
+    }
+
+    res.finish()
+}
+
+/// Value of an `Upload-Checksum` header.
+enum Checksum {
+    Sha1(Vec<u8>),
+    Sha256(Vec<u8>),
+}
+
+impl Checksum {
+    fn parse(value: &HeaderValue) -> Result<Self, TusError> {
+        let (algorithm, digest) = value
+            .to_str()
+            .ok()
+            .and_then(|value| value.split_once(' '))
+            .ok_or(TusError::InvalidHeader(UPLOAD_CHECKSUM))?;
+
+        let digest = STANDARD
+            .decode(digest)
+            .map_err(|_| TusError::InvalidHeader(UPLOAD_CHECKSUM))?;
+
+        match algorithm {
+            "sha1" => Ok(Checksum::Sha1(digest)),
+            "sha256" => Ok(Checksum::Sha256(digest)),
+            _ => Err(TusError::ChecksumAlgorithm),
+        }
+    }
+
+    fn verify(&self, data: &[u8]) -> bool {
+        match self {
+            Checksum::Sha1(digest) => Sha1::digest(data).as_slice() == digest.as_slice(),
+            Checksum::Sha256(digest) => Sha256::digest(data).as_slice() == digest.as_slice(),
+        }
+    }
+}
+
+/// Checks that the request uses the supported version of the protocol.
+fn check_version(headers: &HeaderMap) -> Result<(), TusError> {
+    match headers.get(&TUS_RESUMABLE) {
+        Some(version) if version == TUS_VERSION => Ok(()),
+        _ => Err(TusError::UnsupportedVersion),
+    }
+}
+
+fn parse_header<T: std::str::FromStr>(
+    headers: &HeaderMap,
+    name: &HeaderName,
+) -> Result<Option<T>, TusError> {
+    headers
+        .get(name)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| TusError::InvalidHeader(name.clone()))
+        })
+        .transpose()
+}
+
+/// Parses an `Upload-Metadata` header: comma-separated keys, each optionally followed by a space
+/// and a Base64-encoded value.
+fn parse_metadata(value: &HeaderValue) -> Option<HashMap<String, Option<String>>> {
+    let mut metadata = HashMap::new();
+
+    for pair in value.to_str().ok()?.split(',') {
+        let pair = pair.trim();
+
+        let (key, value) = match pair.split_once(' ') {
+            Some((key, value)) => {
+                let value = String::from_utf8(STANDARD.decode(value).ok()?).ok()?;
+                (key, Some(value))
+            }
+            None => (pair, None),
+        };
+
+        if key.is_empty() || metadata.insert(key.to_owned(), value).is_some() {
+            return None;
+        }
+    }
+
+    Some(metadata)
+}
+
+fn encode_metadata(metadata: &HashMap<String, Option<String>>) -> String {
+    metadata
+        .iter()
+        .map(|(key, value)| match value {
+            Some(value) => format!("{key} {}", STANDARD.encode(value)),
+            None => key.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Errors that can occur while serving resumable uploads.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum TusError {
+    /// Request did not specify the supported version of the protocol.
+    #[display("Unsupported tus protocol version")]
+    UnsupportedVersion,
+
+    /// `Upload-Length` header was not given when creating an upload.
+    #[display("Upload-Length header is missing")]
+    UploadLengthMissing,
+
+    /// A tus header could not be parsed.
+    #[display("Invalid {_0} header")]
+    InvalidHeader(#[error(not(source))] HeaderName),
+
+    /// Upload is larger than the configured maximum size.
+    #[display("Upload is too large")]
+    TooLarge,
+
+    /// Request body would extend the upload past its length.
+    #[display("Request body exceeds the upload length")]
+    ExceedsLength,
+
+    /// Upload was not found.
+    #[display("Upload not found")]
+    NotFound,
+
+    /// Upload has expired.
+    #[display("Upload has expired")]
+    Expired,
+
+    /// Maximum number of incomplete uploads has been reached.
+    #[display("Too many uploads")]
+    TooManyUploads,
+
+    /// `Upload-Offset` header did not match the upload's current offset.
+    #[display("Upload offset does not match")]
+    OffsetMismatch,
+
+    /// Another request is currently writing to the upload.
+    #[display("Upload is locked by another request")]
+    Locked,
+
+    /// `PATCH` request did not have an `application/offset+octet-stream` content type.
+    #[display("Content type must be application/offset+octet-stream")]
+    ContentType,
+
+    /// `Upload-Checksum` header used an unsupported algorithm.
+    #[display("Unsupported checksum algorithm")]
+    ChecksumAlgorithm,
+
+    /// Request body did not match the `Upload-Checksum` header.
+    #[display("Checksum mismatch")]
+    ChecksumMismatch,
+
+    /// Error while reading the request body.
+    #[display("Payload error")]
+    Payload(PayloadError),
+
+    /// Error returned by the storage backend.
+    #[display("Storage error")]
+    Storage(Error),
+
+    /// Storage backend was not registered in app data.
+    #[display("Storage backend was not registered in app data")]
+    BackendMissing,
+
+    /// Upload configuration was not registered in app data.
+    #[display("Upload configuration was not registered in app data")]
+    UploadsMissing,
+}
+
+impl ResponseError for TusError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            TusError::UnsupportedVersion => StatusCode::PRECONDITION_FAILED,
+            TusError::TooLarge | TusError::ExceedsLength => StatusCode::PAYLOAD_TOO_LARGE,
+            TusError::NotFound => StatusCode::NOT_FOUND,
+            TusError::Expired => StatusCode::GONE,
+            TusError::TooManyUploads => StatusCode::SERVICE_UNAVAILABLE,
+            TusError::OffsetMismatch => StatusCode::CONFLICT,
+            TusError::Locked => StatusCode::LOCKED,
+            TusError::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            // defined by the checksum extension
+            TusError::ChecksumMismatch => StatusCode::from_u16(460).unwrap(),
+            TusError::Payload(err) => err.status_code(),
+            TusError::Storage(err) => err.as_response_error().status_code(),
+            TusError::BackendMissing | TusError::UploadsMissing => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut res = HttpResponse::build(self.status_code());
+        res.insert_header((TUS_RESUMABLE, TUS_VERSION));
+
+        if let TusError::UnsupportedVersion = self {
+            res.insert_header((TUS_VERSION_HEADER, TUS_VERSION));
+        }
+
+        res.body(self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        dev::{Service, ServiceResponse},
+        http::StatusCode,
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+    use crate::Field;
+
+    #[derive(Default)]
+    struct MemoryBackend {
+        uploads: Mutex<HashMap<String, UploadInfo>>,
+        data: Mutex<HashMap<String, Vec<u8>>>,
+        completed: Mutex<HashMap<String, Vec<u8>>>,
+    }
+
+    impl MemoryBackend {
+        fn info(&self, id: &str) -> Option<UploadInfo> {
+            self.uploads.lock().unwrap().get(id).cloned()
+        }
+    }
+
+    struct MemorySink(Vec<u8>);
+
+    impl StorageBackend for MemoryBackend {
+        type Sink = MemorySink;
+
+        fn create_sink<'a>(
+            &'a self,
+            _req: &'a HttpRequest,
+            _field: &'a Field,
+        ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>> {
+            Box::pin(async { Ok(MemorySink(Vec::new())) })
+        }
+    }
+
+    impl ResumableBackend for MemoryBackend {
+        fn create_upload_sink<'a>(
+            &'a self,
+            _req: &'a HttpRequest,
+            _upload: &'a UploadInfo,
+        ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>> {
+            Box::pin(async { Ok(MemorySink(Vec::new())) })
+        }
+
+        fn resume_upload_sink<'a>(
+            &'a self,
+            _req: &'a HttpRequest,
+            upload: &'a UploadInfo,
+        ) -> LocalBoxFuture<'a, Result<Self::Sink, Error>> {
+            let mut data = self
+                .data
+                .lock()
+                .unwrap()
+                .remove(&upload.id)
+                .unwrap_or_default();
+            data.truncate(upload.offset as usize);
+            Box::pin(async { Ok(MemorySink(data)) })
+        }
+
+        fn suspend_upload_sink<'a>(
+            &'a self,
+            sink: Self::Sink,
+            upload: &'a UploadInfo,
+        ) -> LocalBoxFuture<'a, Result<(), Error>> {
+            self.data.lock().unwrap().insert(upload.id.clone(), sink.0);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn complete_upload<'a>(
+            &'a self,
+            upload: &'a UploadInfo,
+            output: Vec<u8>,
+        ) -> LocalBoxFuture<'a, Result<(), Error>> {
+            self.completed
+                .lock()
+                .unwrap()
+                .insert(upload.id.clone(), output);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn save_upload<'a>(
+            &'a self,
+            upload: &'a UploadInfo,
+        ) -> LocalBoxFuture<'a, Result<(), Error>> {
+            self.uploads
+                .lock()
+                .unwrap()
+                .insert(upload.id.clone(), upload.clone());
+            Box::pin(async { Ok(()) })
+        }
+
+        fn load_upload<'a>(
+            &'a self,
+            id: &'a str,
+        ) -> LocalBoxFuture<'a, Result<Option<UploadInfo>, Error>> {
+            let upload = self.info(id);
+            Box::pin(async { Ok(upload) })
+        }
+
+        fn remove_upload<'a>(&'a self, id: &'a str) -> LocalBoxFuture<'a, Result<(), Error>> {
+            self.uploads.lock().unwrap().remove(id);
+            self.data.lock().unwrap().remove(id);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn incomplete_uploads(&self) -> LocalBoxFuture<'_, Result<usize, Error>> {
+            let uploads = self.uploads.lock().unwrap();
+            let count = uploads
+                .values()
+                .filter(|upload| !upload.is_complete())
+                .count();
+            Box::pin(async move { Ok(count) })
+        }
+
+        fn remove_expired_uploads(&self, now: SystemTime) -> LocalBoxFuture<'_, Result<(), Error>> {
+            let mut uploads = self.uploads.lock().unwrap();
+            let mut data = self.data.lock().unwrap();
+
+            uploads.retain(|id, upload| {
+                let expired = upload.expires.is_some_and(|expires| expires <= now);
+                if expired {
+                    data.remove(id);
+                }
+                !expired
+            });
+
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    impl StorageSink for MemorySink {
+        type Output = Vec<u8>;
+
+        fn write_chunk(&mut self, chunk: web::Bytes) -> LocalBoxFuture<'_, Result<(), Error>> {
+            self.0.extend_from_slice(&chunk);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn finish(self) -> LocalBoxFuture<'static, Result<Self::Output, Error>> {
+            Box::pin(async move { Ok(self.0) })
+        }
+    }
+
+    async fn app(
+        backend: web::Data<MemoryBackend>,
+        uploads: TusUploads,
+    ) -> impl Service<actix_http::Request, Response = ServiceResponse, Error = Error> {
+        init_service(
+            App::new()
+                .app_data(backend)
+                .app_data(uploads)
+                .service(scope::<MemoryBackend>("/files")),
+        )
+        .await
+    }
+
+    fn create_request(length: u64) -> TestRequest {
+        TestRequest::post()
+            .uri("/files")
+            .insert_header((TUS_RESUMABLE, TUS_VERSION))
+            .insert_header((UPLOAD_LENGTH, length))
+    }
+
+    fn patch_request(location: &str, offset: u64, body: &'static str) -> TestRequest {
+        TestRequest::patch()
+            .uri(location)
+            .insert_header((TUS_RESUMABLE, TUS_VERSION))
+            .insert_header((header::CONTENT_TYPE, OFFSET_OCTET_STREAM))
+            .insert_header((UPLOAD_OFFSET, offset))
+            .set_payload(body)
+    }
+
+    fn location(res: &ServiceResponse) -> String {
+        res.headers()
+            .get(header::LOCATION)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[actix_rt::test]
+    async fn resumable_upload() {
+        let backend = web::Data::new(MemoryBackend::default());
+        let app = app(backend.clone(), TusUploads::new().max_size(64)).await;
+
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .uri("/files")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(res.headers().get(TUS_VERSION_HEADER).unwrap(), TUS_VERSION);
+        assert_eq!(res.headers().get(TUS_EXTENSION).unwrap(), TUS_EXTENSIONS);
+        assert_eq!(res.headers().get(TUS_MAX_SIZE).unwrap(), "64");
+
+        let req = create_request(11)
+            .insert_header((UPLOAD_METADATA, "filename aGVsbG8udHh0,public"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert!(res.headers().get(UPLOAD_EXPIRES).is_none());
+        let location = location(&res);
+        let id = location.strip_prefix("/files/").unwrap().to_owned();
+
+        let info = backend.info(&id).unwrap();
+        assert_eq!(info.metadata["filename"].as_deref(), Some("hello.txt"));
+        assert_eq!(info.metadata["public"], None);
+
+        let res = call_service(&app, patch_request(&location, 0, "hello ").to_request()).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(res.headers().get(UPLOAD_OFFSET).unwrap(), "6");
+
+        let req = TestRequest::default()
+            .method(Method::HEAD)
+            .uri(&location)
+            .insert_header((TUS_RESUMABLE, TUS_VERSION))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(UPLOAD_OFFSET).unwrap(), "6");
+        assert_eq!(res.headers().get(UPLOAD_LENGTH).unwrap(), "11");
+
+        // stale offset
+        let res = call_service(&app, patch_request(&location, 0, "world").to_request()).await;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        // checksum mismatch does not advance the upload
+        let req = patch_request(&location, 6, "world")
+            .insert_header((UPLOAD_CHECKSUM, "sha1 AAAAAAAAAAAAAAAAAAAAAAAAAAA="))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status().as_u16(), 460);
+        assert_eq!(backend.info(&id).unwrap().offset, 6);
+
+        assert!(backend.completed.lock().unwrap().is_empty());
+
+        let checksum = format!("sha1 {}", STANDARD.encode(Sha1::digest(b"world")));
+        let req = patch_request(&location, 6, "world")
+            .insert_header((UPLOAD_CHECKSUM, checksum))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(res.headers().get(UPLOAD_OFFSET).unwrap(), "11");
+
+        assert!(backend.info(&id).unwrap().is_complete());
+        assert_eq!(backend.completed.lock().unwrap()[&id], b"hello world");
+
+        // data sent after completion is not written
+        let res = call_service(&app, patch_request(&location, 11, "").to_request()).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(res.headers().get(UPLOAD_OFFSET).unwrap(), "11");
+    }
+
+    #[actix_rt::test]
+    async fn expiry() {
+        let backend = web::Data::new(MemoryBackend::default());
+        let app = app(backend.clone(), TusUploads::new().expiry(Duration::ZERO)).await;
+
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .uri("/files")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(
+            res.headers().get(TUS_EXTENSION).unwrap(),
+            "creation,checksum,expiration"
+        );
+
+        let res = call_service(&app, create_request(4).to_request()).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert!(res.headers().get(UPLOAD_EXPIRES).is_some());
+        let first = location(&res);
+
+        let res = call_service(&app, patch_request(&first, 0, "data").to_request()).await;
+        assert_eq!(res.status(), StatusCode::GONE);
+        assert!(backend.uploads.lock().unwrap().is_empty());
+
+        // expired uploads are removed when creating uploads
+        call_service(&app, create_request(4).to_request()).await;
+        call_service(&app, create_request(4).to_request()).await;
+        assert_eq!(backend.uploads.lock().unwrap().len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn max_uploads() {
+        let backend = web::Data::new(MemoryBackend::default());
+        let app = app(backend.clone(), TusUploads::new().max_uploads(1)).await;
+
+        let res = call_service(&app, create_request(4).to_request()).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let location = location(&res);
+
+        let res = call_service(&app, create_request(4).to_request()).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // completed uploads do not count towards the limit
+        let res = call_service(&app, patch_request(&location, 0, "data").to_request()).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+        let res = call_service(&app, create_request(4).to_request()).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+    }
+
+    #[actix_rt::test]
+    async fn locked_upload() {
+        let backend = web::Data::new(MemoryBackend::default());
+        let uploads = web::Data::new(TusUploads::new());
+
+        let app = init_service(
+            App::new()
+                .app_data(backend.clone())
+                .app_data(uploads.clone())
+                .service(scope::<MemoryBackend>("/files")),
+        )
+        .await;
+
+        let res = call_service(&app, create_request(4).to_request()).await;
+        let location = location(&res);
+        let id = location.strip_prefix("/files/").unwrap();
+
+        let busy = uploads.lock(id).unwrap();
+        let res = call_service(&app, patch_request(&location, 0, "data").to_request()).await;
+        assert_eq!(res.status(), StatusCode::LOCKED);
+
+        drop(busy);
+        let res = call_service(&app, patch_request(&location, 0, "data").to_request()).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[actix_rt::test]
+    async fn protocol_errors() {
+        let backend = web::Data::new(MemoryBackend::default());
+        let app = app(backend, TusUploads::new().max_size(8)).await;
+
+        let req = TestRequest::post()
+            .uri("/files")
+            .insert_header((UPLOAD_LENGTH, 4))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(res.headers().get(TUS_VERSION_HEADER).unwrap(), TUS_VERSION);
+
+        let req = TestRequest::post()
+            .uri("/files")
+            .insert_header((TUS_RESUMABLE, TUS_VERSION))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = call_service(&app, create_request(9).to_request()).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let res = call_service(
+            &app,
+            patch_request("/files/unknown", 0, "data").to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = call_service(&app, create_request(4).to_request()).await;
+        let location = location(&res);
+
+        let req = patch_request(&location, 0, "data")
+            .insert_header((header::CONTENT_TYPE, "text/plain"))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let res = call_service(&app, patch_request(&location, 0, "data!").to_request()).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
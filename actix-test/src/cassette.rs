@@ -0,0 +1,401 @@
+//! Client middleware that records and replays HTTP interactions.
+
+use std::{
+    cell::RefCell,
+    fs,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_http::{
+    body::{to_bytes, BodySize, MessageBody as _},
+    header::{HeaderMap, HeaderName, HeaderValue},
+    StatusCode,
+};
+use actix_service::Service;
+use actix_web::web::Bytes;
+use awc::{
+    error::SendRequestError, middleware::Transform, test::TestResponse, AnyBody, ConnectRequest,
+    ConnectResponse,
+};
+use serde::{Deserialize, Serialize};
+
+/// Value stored in place of redacted header values.
+const REDACTED: &str = "[redacted]";
+
+/// Whether a [`Cassette`] records or replays interactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum CassetteMode {
+    /// Replays interactions if the cassette file exists and records them otherwise.
+    #[default]
+    Auto,
+
+    /// Sends requests and records the interactions, replacing any existing cassette file.
+    Record,
+
+    /// Replays interactions from the cassette file without sending any requests.
+    Replay,
+}
+
+/// Client middleware that records HTTP interactions to a cassette file and replays them in later
+/// runs.
+///
+/// Useful for deterministic tests of code that calls third-party APIs. Wrap the `awc` client used
+/// by the application under test using `ClientBuilder::wrap()`. On the first run, requests are sent
+/// as usual and each request and response is written to the cassette file, which can be committed
+/// alongside the tests. When the file exists, responses are replayed from it without any network
+/// access. See [`CassetteMode`] for forcing either behavior.
+///
+/// Cassettes are JSON files. Recorded requests are matched by method and URL, and requests that
+/// match more than one interaction receive the recorded responses in order. Replaying a request
+/// that was not recorded fails with a [`SendRequestError::Custom`] error. Response bodies are
+/// recorded as received, so compressed bodies are decompressed by the client on replay as usual.
+///
+/// Header values containing secrets, such as API keys, can be kept out of cassette files using
+/// [`redact_header()`](Self::redact_header()).
+///
+/// # Panics
+/// Building a client panics if interactions are replayed and the cassette file can not be read.
+/// Sending a request panics if an interaction is recorded and the cassette file can not be written.
+///
+/// # Examples
+/// ```no_run
+/// use actix_test::Cassette;
+///
+/// let client = awc::Client::builder()
+///     .wrap(Cassette::new("tests/cassettes/github.json").redact_header("authorization"))
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    redacted: Vec<HeaderName>,
+}
+
+impl Cassette {
+    /// Constructs a cassette middleware that uses the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Cassette {
+            path: path.into(),
+            mode: CassetteMode::Auto,
+            redacted: Vec::new(),
+        }
+    }
+
+    /// Sets whether interactions are recorded or replayed.
+    ///
+    /// Defaults to [`CassetteMode::Auto`].
+    pub fn mode(mut self, mode: CassetteMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Replaces values of the header `name` in recorded requests and responses.
+    ///
+    /// May be called repeatedly to redact more headers.
+    ///
+    /// # Panics
+    /// Panics if `name` is not a valid header name.
+    pub fn redact_header(mut self, name: &str) -> Self {
+        let name =
+            HeaderName::try_from(name).unwrap_or_else(|_| panic!("invalid header name: {name:?}"));
+
+        self.redacted.push(name);
+        self
+    }
+}
+
+impl<S> Transform<S, ConnectRequest> for Cassette
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Transform = CassetteService<S>;
+
+    fn new_transform(self, service: S) -> Self::Transform {
+        let replay = match self.mode {
+            CassetteMode::Auto => self.path.exists(),
+            CassetteMode::Record => false,
+            CassetteMode::Replay => true,
+        };
+
+        let interactions = if replay {
+            read_cassette(&self.path)
+                .unwrap_or_else(|err| panic!("failed to read cassette {:?}: {err}", self.path))
+        } else {
+            Vec::new()
+        };
+
+        CassetteService {
+            state: Rc::new(CassetteState {
+                cassette: self,
+                replay,
+                used: RefCell::new(vec![false; interactions.len()]),
+                interactions: RefCell::new(interactions),
+            }),
+            service: Rc::new(service),
+        }
+    }
+}
+
+/// Service created by the [`Cassette`] middleware.
+pub struct CassetteService<S> {
+    state: Rc<CassetteState>,
+    service: Rc<S>,
+}
+
+struct CassetteState {
+    cassette: Cassette,
+    replay: bool,
+    interactions: RefCell<Vec<Interaction>>,
+    used: RefCell<Vec<bool>>,
+}
+
+impl<S> Service<ConnectRequest> for CassetteService<S>
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ConnectRequest) -> Self::Future {
+        let state = Rc::clone(&self.state);
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            // tunnels, used by WebSocket connections, are neither recorded nor replayed
+            let ConnectRequest::Client(head, body, addr) = req else {
+                return service.call(req).await;
+            };
+
+            let method = head.as_ref().method.to_string();
+            let uri = head.as_ref().uri.to_string();
+
+            if state.replay {
+                return state.replay(&method, &uri);
+            }
+
+            let mut headers = head.as_ref().headers.clone();
+            if let Some(extra) = head.extra_headers() {
+                for (name, value) in extra {
+                    headers.insert(name.clone(), value.clone());
+                }
+            }
+
+            // bodiless requests are sent as they are so that no `Content-Length` header is added
+            let (body, recorded_body) = if body.size() == BodySize::None {
+                (body, Bytes::new())
+            } else {
+                let bytes = to_bytes(body).await.map_err(SendRequestError::Body)?;
+                (
+                    AnyBody::Bytes {
+                        body: bytes.clone(),
+                    },
+                    bytes,
+                )
+            };
+
+            let request = RecordedRequest {
+                method,
+                uri,
+                headers: state.record_headers(&headers),
+                body: RecordedBody::from(&recorded_body),
+            };
+
+            let res = service
+                .call(ConnectRequest::Client(head, body, addr))
+                .await?;
+
+            let mut res = match res {
+                ConnectResponse::Client(res) => res,
+                res => return Ok(res),
+            };
+
+            let status = res.status();
+            let res_headers = res.headers().clone();
+            let body = res
+                .body()
+                .limit(usize::MAX)
+                .await
+                .map_err(|err| SendRequestError::Custom(err.into(), Box::new("Cassette")))?;
+
+            state.record(Interaction {
+                request,
+                response: RecordedResponse {
+                    status: status.as_u16(),
+                    headers: state.record_headers(&res_headers),
+                    body: RecordedBody::from(&body),
+                },
+            });
+
+            Ok(ConnectResponse::Client(response(status, res_headers, body)))
+        })
+    }
+}
+
+impl CassetteState {
+    /// Returns the next unused response recorded for the request.
+    fn replay(&self, method: &str, uri: &str) -> Result<ConnectResponse, SendRequestError> {
+        let interactions = self.interactions.borrow();
+        let mut used = self.used.borrow_mut();
+
+        let idx = interactions
+            .iter()
+            .zip(used.iter())
+            .position(|(interaction, &used)| {
+                !used && interaction.request.method == method && interaction.request.uri == uri
+            })
+            .ok_or_else(|| {
+                SendRequestError::Custom(
+                    format!(
+                        "no recorded interaction for {method} {uri} in cassette {:?}",
+                        self.cassette.path
+                    )
+                    .into(),
+                    Box::new("Cassette"),
+                )
+            })?;
+
+        used[idx] = true;
+
+        let recorded = &interactions[idx].response;
+
+        let status = StatusCode::from_u16(recorded.status)
+            .map_err(|err| SendRequestError::Custom(err.into(), Box::new("Cassette")))?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &recorded.headers {
+            let name = HeaderName::try_from(name.as_str())
+                .map_err(|err| SendRequestError::Custom(err.into(), Box::new("Cassette")))?;
+            let value = HeaderValue::try_from(value.as_str())
+                .map_err(|err| SendRequestError::Custom(err.into(), Box::new("Cassette")))?;
+
+            headers.append(name, value);
+        }
+
+        Ok(ConnectResponse::Client(response(
+            status,
+            headers,
+            recorded.body.to_bytes(),
+        )))
+    }
+
+    /// Adds an interaction to the cassette and writes it to disk.
+    ///
+    /// # Panics
+    /// Panics if the cassette file can not be written.
+    fn record(&self, interaction: Interaction) {
+        let mut interactions = self.interactions.borrow_mut();
+        interactions.push(interaction);
+
+        write_cassette(&self.cassette.path, &interactions).unwrap_or_else(|err| {
+            panic!("failed to write cassette {:?}: {err}", self.cassette.path)
+        });
+    }
+
+    /// Converts headers to their recorded form, redacting values as configured.
+    fn record_headers(&self, headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.cassette.redacted.contains(name) {
+                    REDACTED.to_owned()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+
+                (name.as_str().to_owned(), value)
+            })
+            .collect()
+    }
+}
+
+/// Constructs a client response with an in-memory body.
+fn response(status: StatusCode, headers: HeaderMap, body: Bytes) -> awc::ClientResponse {
+    TestResponse::default()
+        .set_payload(body)
+        .finish()
+        .map_body(|head, payload| {
+            head.status = status;
+            head.headers = headers;
+            payload
+        })
+}
+
+fn read_cassette(path: &Path) -> io::Result<Vec<Interaction>> {
+    let file: CassetteFile = serde_json::from_slice(&fs::read(path)?)?;
+    Ok(file.interactions)
+}
+
+fn write_cassette(path: &Path, interactions: &[Interaction]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let json = serde_json::to_vec_pretty(&CassetteFileRef { interactions })?;
+    fs::write(path, json)
+}
+
+#[derive(Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Serialize)]
+struct CassetteFileRef<'a> {
+    interactions: &'a [Interaction],
+}
+
+#[derive(Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    uri: String,
+    headers: Vec<(String, String)>,
+    body: RecordedBody,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: RecordedBody,
+}
+
+/// Body stored as text when it is valid UTF-8 and as an array of bytes otherwise.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RecordedBody {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl From<&Bytes> for RecordedBody {
+    fn from(body: &Bytes) -> Self {
+        match std::str::from_utf8(body) {
+            Ok(text) => RecordedBody::Text(text.to_owned()),
+            Err(_) => RecordedBody::Binary(body.to_vec()),
+        }
+    }
+}
+
+impl RecordedBody {
+    fn to_bytes(&self) -> Bytes {
+        match self {
+            RecordedBody::Text(text) => Bytes::copy_from_slice(text.as_bytes()),
+            RecordedBody::Binary(bytes) => Bytes::copy_from_slice(bytes),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Rustls client configs that trust any server certificate, for connecting to test servers.
+
+/// ALPN protocols offered by test clients.
+#[cfg(any(
+    feature = "rustls-0_20",
+    feature = "rustls-0_21",
+    feature = "rustls-0_22",
+    feature = "rustls-0_23",
+))]
+fn alpn_protocols() -> Vec<Vec<u8>> {
+    vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+}
+
+#[cfg(any(feature = "rustls-0_20", feature = "rustls-0_21"))]
+macro_rules! legacy_client_config {
+    ($name:ident, $version:literal, $rustls:ident) => {
+        #[doc = concat!("Returns a Rustls v", $version, " client config that trusts any server certificate.")]
+        ///
+        /// Test servers configured with Rustls use this config for their client automatically.
+        /// It is useful for clients constructed by the code under test. Never use it outside tests.
+        pub fn $name() -> $rustls::ClientConfig {
+            use std::{sync::Arc, time::SystemTime};
+
+            use $rustls::{
+                client::{ServerCertVerified, ServerCertVerifier, ServerName},
+                Certificate, ClientConfig, Error,
+            };
+
+            struct NoCertificateVerification;
+
+            impl ServerCertVerifier for NoCertificateVerification {
+                fn verify_server_cert(
+                    &self,
+                    _end_entity: &Certificate,
+                    _intermediates: &[Certificate],
+                    _server_name: &ServerName,
+                    _scts: &mut dyn Iterator<Item = &[u8]>,
+                    _ocsp_response: &[u8],
+                    _now: SystemTime,
+                ) -> Result<ServerCertVerified, Error> {
+                    Ok(ServerCertVerified::assertion())
+                }
+            }
+
+            let mut config = ClientConfig::builder()
+                .with_safe_defaults()
+                .with_custom_certificate_verifier(Arc::new(NoCertificateVerification))
+                .with_no_client_auth();
+
+            config.alpn_protocols = alpn_protocols();
+            config
+        }
+    };
+}
+
+#[cfg(any(feature = "rustls-0_22", feature = "rustls-0_23"))]
+macro_rules! client_config {
+    ($name:ident, $version:literal, $rustls:ident) => {
+        #[doc = concat!("Returns a Rustls v", $version, " client config that trusts any server certificate.")]
+        ///
+        /// Test servers configured with Rustls use this config for their client automatically.
+        /// It is useful for clients constructed by the code under test. Never use it outside tests.
+        pub fn $name() -> $rustls::ClientConfig {
+            use std::sync::Arc;
+
+            use $rustls::{
+                client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+                pki_types::{CertificateDer, ServerName, UnixTime},
+                ClientConfig, DigitallySignedStruct, Error, SignatureScheme,
+            };
+
+            #[derive(Debug)]
+            struct NoCertificateVerification;
+
+            impl ServerCertVerifier for NoCertificateVerification {
+                fn verify_server_cert(
+                    &self,
+                    _end_entity: &CertificateDer<'_>,
+                    _intermediates: &[CertificateDer<'_>],
+                    _server_name: &ServerName<'_>,
+                    _ocsp_response: &[u8],
+                    _now: UnixTime,
+                ) -> Result<ServerCertVerified, Error> {
+                    Ok(ServerCertVerified::assertion())
+                }
+
+                fn verify_tls12_signature(
+                    &self,
+                    _message: &[u8],
+                    _cert: &CertificateDer<'_>,
+                    _dss: &DigitallySignedStruct,
+                ) -> Result<HandshakeSignatureValid, Error> {
+                    Ok(HandshakeSignatureValid::assertion())
+                }
+
+                fn verify_tls13_signature(
+                    &self,
+                    _message: &[u8],
+                    _cert: &CertificateDer<'_>,
+                    _dss: &DigitallySignedStruct,
+                ) -> Result<HandshakeSignatureValid, Error> {
+                    Ok(HandshakeSignatureValid::assertion())
+                }
+
+                fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+                    vec![
+                        SignatureScheme::ECDSA_NISTP256_SHA256,
+                        SignatureScheme::ECDSA_NISTP384_SHA384,
+                        SignatureScheme::ECDSA_NISTP521_SHA512,
+                        SignatureScheme::ED25519,
+                        SignatureScheme::RSA_PSS_SHA256,
+                        SignatureScheme::RSA_PSS_SHA384,
+                        SignatureScheme::RSA_PSS_SHA512,
+                        SignatureScheme::RSA_PKCS1_SHA256,
+                        SignatureScheme::RSA_PKCS1_SHA384,
+                        SignatureScheme::RSA_PKCS1_SHA512,
+                    ]
+                }
+            }
+
+            let mut config = ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(NoCertificateVerification))
+                .with_no_client_auth();
+
+            config.alpn_protocols = alpn_protocols();
+            config
+        }
+    };
+}
+
+#[cfg(feature = "rustls-0_20")]
+legacy_client_config!(rustls_0_20_client_config, "0.20", tls_rustls_0_20);
+
+#[cfg(feature = "rustls-0_21")]
+legacy_client_config!(rustls_0_21_client_config, "0.21", tls_rustls_0_21);
+
+#[cfg(feature = "rustls-0_22")]
+client_config!(rustls_0_22_client_config, "0.22", tls_rustls_0_22);
+
+#[cfg(feature = "rustls-0_23")]
+client_config!(rustls_0_23_client_config, "0.23", tls_rustls_0_23);
@@ -0,0 +1,180 @@
+//! Client middleware that stores cookies set by the test server and sends them back.
+
+use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc};
+
+use actix_http::{
+    header::{self, HeaderMap, HeaderValue},
+    RequestHeadType,
+};
+use actix_service::Service;
+use awc::{
+    cookie::{time::OffsetDateTime, Cookie, CookieJar},
+    error::SendRequestError,
+    middleware::Transform,
+    ConnectRequest, ConnectResponse,
+};
+
+/// Cookie store shared between a test server's client and the [`TestServer`](crate::TestServer).
+pub(crate) type SharedJar = Rc<RefCell<CookieJar>>;
+
+/// Client middleware that records `Set-Cookie` response headers in a jar and sends matching cookies
+/// with subsequent requests.
+///
+/// Cookies already present in a request's `Cookie` header take precedence over stored ones. When
+/// there is no jar, requests and responses pass through unchanged.
+pub(crate) struct CookieStore {
+    jar: Option<SharedJar>,
+}
+
+impl CookieStore {
+    pub(crate) fn new(jar: Option<SharedJar>) -> Self {
+        Self { jar }
+    }
+}
+
+impl<S> Transform<S, ConnectRequest> for CookieStore
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Transform = CookieStoreService<S>;
+
+    fn new_transform(self, service: S) -> Self::Transform {
+        CookieStoreService {
+            jar: self.jar,
+            service: Rc::new(service),
+        }
+    }
+}
+
+pub(crate) struct CookieStoreService<S> {
+    jar: Option<SharedJar>,
+    service: Rc<S>,
+}
+
+impl<S> Service<ConnectRequest> for CookieStoreService<S>
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ConnectRequest) -> Self::Future {
+        let Some(jar) = &self.jar else {
+            return Box::pin(self.service.call(req));
+        };
+
+        let req = match req {
+            ConnectRequest::Client(mut head, body, addr) => {
+                add_cookie_header(&jar.borrow(), &mut head);
+                ConnectRequest::Client(head, body, addr)
+            }
+            req @ ConnectRequest::Tunnel(..) => req,
+        };
+
+        let jar = Rc::clone(jar);
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+
+            let headers = match &res {
+                ConnectResponse::Client(res) => res.headers(),
+                ConnectResponse::Tunnel(head, _) => &head.headers,
+            };
+
+            store_cookies(&mut jar.borrow_mut(), headers);
+
+            Ok(res)
+        })
+    }
+}
+
+/// Adds stored cookies whose path matches the request to its `Cookie` header.
+fn add_cookie_header(jar: &CookieJar, head: &mut RequestHeadType) {
+    let (path, existing) = match head {
+        RequestHeadType::Owned(head) => (head.uri.path(), head.headers.get(header::COOKIE)),
+        RequestHeadType::Rc(head, extra) => (
+            head.uri.path(),
+            extra
+                .as_ref()
+                .and_then(|extra| extra.get(header::COOKIE))
+                .or_else(|| head.headers.get(header::COOKIE)),
+        ),
+    };
+
+    let existing = existing
+        .and_then(|val| val.to_str().ok())
+        .map(str::to_owned)
+        .unwrap_or_default();
+
+    let existing_names = existing
+        .split(';')
+        .filter_map(|pair| pair.split('=').next())
+        .map(str::trim)
+        .collect::<Vec<_>>();
+
+    let stored = jar
+        .iter()
+        .filter(|cookie| path_matches(path, cookie.path().unwrap_or("/")))
+        .filter(|cookie| !existing_names.contains(&cookie.name()))
+        .map(|cookie| cookie.stripped().encoded().to_string())
+        .collect::<Vec<_>>();
+
+    if stored.is_empty() {
+        return;
+    }
+
+    let value = if existing.is_empty() {
+        stored.join("; ")
+    } else {
+        format!("{existing}; {}", stored.join("; "))
+    };
+
+    let Ok(value) = HeaderValue::from_str(&value) else {
+        return;
+    };
+
+    match head {
+        RequestHeadType::Owned(head) => {
+            head.headers.insert(header::COOKIE, value);
+        }
+        RequestHeadType::Rc(_, extra) => {
+            extra
+                .get_or_insert_with(HeaderMap::new)
+                .insert(header::COOKIE, value);
+        }
+    }
+}
+
+/// Updates the jar from `Set-Cookie` response headers, removing expired cookies.
+fn store_cookies(jar: &mut CookieJar, headers: &HeaderMap) {
+    let cookies = headers
+        .get_all(header::SET_COOKIE)
+        .filter_map(|val| val.to_str().ok())
+        .filter_map(|val| Cookie::parse_encoded(val.to_owned()).ok());
+
+    for cookie in cookies {
+        let expired = cookie
+            .max_age()
+            .is_some_and(|max_age| !max_age.is_positive())
+            || cookie
+                .expires_datetime()
+                .is_some_and(|expires| expires <= OffsetDateTime::now_utc());
+
+        if expired {
+            jar.force_remove(&cookie);
+        } else {
+            jar.add(cookie);
+        }
+    }
+}
+
+/// Returns true if a request to `path` should include a cookie scoped to `cookie_path`.
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    path == cookie_path
+        || (path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || path[cookie_path.len()..].starts_with('/')))
+}
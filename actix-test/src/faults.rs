@@ -0,0 +1,265 @@
+//! Client middleware that injects network faults.
+
+use std::{
+    cell::Cell,
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher as _, Hasher as _},
+    io,
+    pin::Pin,
+    rc::Rc,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+
+use actix_http::{error::PayloadError, BoxedPayloadStream, Payload};
+use actix_service::Service;
+use actix_web::{rt, web::Bytes};
+use awc::{error::SendRequestError, middleware::Transform, ConnectRequest, ConnectResponse};
+use futures_core::Stream;
+
+/// Client middleware that injects latency, connection resets, and truncated response bodies at
+/// configurable probabilities.
+///
+/// Useful for testing resilience logic, such as retries and timeouts, either of a
+/// [`TestServer`](crate::TestServer)'s client, using [`TestServerConfig::faults()`], or of any
+/// `awc` client used by the application under test, using `ClientBuilder::wrap()`.
+///
+/// Faults are decided independently for each request:
+/// - **latency** delays sending the request;
+/// - **connection reset** discards the response after the request has reached the server and
+///   fails with a [`ConnectionReset`](io::ErrorKind::ConnectionReset) error;
+/// - **truncated body** cuts the response body short, so that reading it fails with
+///   [`PayloadError::Incomplete`].
+///
+/// Faults are chosen using a pseudo-random generator that can be [seeded](Self::seed()) to make
+/// tests reproducible.
+///
+/// [`TestServerConfig::faults()`]: crate::TestServerConfig::faults()
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_test::FaultInjection;
+///
+/// let faults = FaultInjection::new()
+///     .latency(0.5, Duration::from_millis(100))
+///     .connection_reset(0.1)
+///     .truncated_body(0.1)
+///     .seed(42);
+///
+/// let client = awc::Client::builder().wrap(faults).finish();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjection {
+    latency: Option<(f64, Duration)>,
+    connection_reset: f64,
+    truncated_body: f64,
+    seed: Option<u64>,
+}
+
+impl FaultInjection {
+    /// Constructs a fault injection middleware that injects no faults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delays requests by `delay` with the given probability.
+    ///
+    /// # Panics
+    /// Panics if `probability` is not in the range `0.0..=1.0`.
+    pub fn latency(mut self, probability: f64, delay: Duration) -> Self {
+        assert_probability(probability);
+        self.latency = Some((probability, delay));
+        self
+    }
+
+    /// Resets connections after sending requests with the given probability.
+    ///
+    /// # Panics
+    /// Panics if `probability` is not in the range `0.0..=1.0`.
+    pub fn connection_reset(mut self, probability: f64) -> Self {
+        assert_probability(probability);
+        self.connection_reset = probability;
+        self
+    }
+
+    /// Truncates response bodies with the given probability.
+    ///
+    /// # Panics
+    /// Panics if `probability` is not in the range `0.0..=1.0`.
+    pub fn truncated_body(mut self, probability: f64) -> Self {
+        assert_probability(probability);
+        self.truncated_body = probability;
+        self
+    }
+
+    /// Seeds the generator used to decide which requests are faulted.
+    ///
+    /// By default, a random seed is used.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+fn assert_probability(probability: f64) {
+    assert!(
+        (0.0..=1.0).contains(&probability),
+        "fault probability must be in the range 0.0..=1.0"
+    );
+}
+
+impl<S> Transform<S, ConnectRequest> for FaultInjection
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Transform = FaultInjectionService<S>;
+
+    fn new_transform(self, service: S) -> Self::Transform {
+        let seed = self
+            .seed
+            .unwrap_or_else(|| RandomState::new().build_hasher().finish());
+
+        FaultInjectionService {
+            faults: self,
+            rng: Rng(Cell::new(seed)),
+            service: Rc::new(service),
+        }
+    }
+}
+
+/// Service created by the [`FaultInjection`] middleware.
+pub struct FaultInjectionService<S> {
+    faults: FaultInjection,
+    rng: Rng,
+    service: Rc<S>,
+}
+
+impl<S> Service<ConnectRequest> for FaultInjectionService<S>
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ConnectRequest) -> Self::Future {
+        let delay = self
+            .faults
+            .latency
+            .filter(|&(probability, _)| self.rng.chance(probability))
+            .map(|(_, delay)| delay);
+        let reset = self.rng.chance(self.faults.connection_reset);
+        let truncate = self.rng.chance(self.faults.truncated_body);
+
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            if let Some(delay) = delay {
+                rt::time::sleep(delay).await;
+            }
+
+            let res = service.call(req).await?;
+
+            if reset {
+                return Err(SendRequestError::Send(
+                    io::ErrorKind::ConnectionReset.into(),
+                ));
+            }
+
+            Ok(match res {
+                ConnectResponse::Client(res) if truncate => {
+                    ConnectResponse::Client(res.map_body(|_, payload| Payload::<
+                        BoxedPayloadStream,
+                    >::Stream {
+                        payload: Box::pin(Truncated::new(payload)),
+                    }))
+                }
+                res => res,
+            })
+        })
+    }
+}
+
+/// SplitMix64 pseudo-random generator.
+struct Rng(Cell<u64>);
+
+impl Rng {
+    /// Returns true with the given probability.
+    fn chance(&self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+
+    /// Returns a number in the range `0.0..1.0`.
+    fn next_f64(&self) -> f64 {
+        let state = self.0.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.0.set(state);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Payload that yields half of the first chunk of the inner payload and then fails.
+struct Truncated {
+    payload: Pin<Box<Payload>>,
+    state: TruncatedState,
+}
+
+enum TruncatedState {
+    First,
+    Fail,
+    Done,
+}
+
+impl Truncated {
+    fn new(payload: Payload) -> Self {
+        Truncated {
+            payload: Box::pin(payload),
+            state: TruncatedState::First,
+        }
+    }
+}
+
+impl Stream for Truncated {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.state {
+            TruncatedState::First => match ready!(self.payload.as_mut().poll_next(cx)) {
+                Some(Ok(mut chunk)) => {
+                    chunk.truncate(chunk.len() / 2);
+
+                    if chunk.is_empty() {
+                        self.state = TruncatedState::Done;
+                        Poll::Ready(Some(Err(PayloadError::Incomplete(None))))
+                    } else {
+                        self.state = TruncatedState::Fail;
+                        Poll::Ready(Some(Ok(chunk)))
+                    }
+                }
+
+                // empty bodies and errors are passed through
+                item => {
+                    self.state = TruncatedState::Done;
+                    Poll::Ready(item)
+                }
+            },
+
+            TruncatedState::Fail => {
+                self.state = TruncatedState::Done;
+                Poll::Ready(Some(Err(PayloadError::Incomplete(None))))
+            }
+
+            TruncatedState::Done => Poll::Ready(None),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! DNS overrides for clients of test servers.
+
+use std::{error::Error as StdError, net, rc::Rc};
+
+use actix_rt::task::spawn_blocking;
+use actix_tls::connect::Resolve;
+use futures_core::future::LocalBoxFuture;
+
+/// Resolves mapped hostnames to the test server's address and other hostnames using the system
+/// resolver.
+///
+/// Since only resolution is overridden, requests keep their original hostname for the `Host`
+/// header and TLS SNI.
+pub(crate) struct HostResolver {
+    hosts: Rc<[String]>,
+    addr: net::SocketAddr,
+}
+
+impl HostResolver {
+    pub(crate) fn new(hosts: &[String], addr: net::SocketAddr) -> Self {
+        Self {
+            hosts: hosts.into(),
+            addr,
+        }
+    }
+}
+
+impl Resolve for HostResolver {
+    fn lookup<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> LocalBoxFuture<'a, Result<Vec<net::SocketAddr>, Box<dyn StdError>>> {
+        Box::pin(async move {
+            if self.hosts.iter().any(|h| h.eq_ignore_ascii_case(host)) {
+                return Ok(vec![self.addr]);
+            }
+
+            let host = format!("{host}:{port}");
+            let addrs =
+                spawn_blocking(move || net::ToSocketAddrs::to_socket_addrs(&host)).await??;
+
+            Ok(addrs.collect())
+        })
+    }
+}
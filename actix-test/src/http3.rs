@@ -0,0 +1,433 @@
+//! HTTP/3 listener and client for test servers, using Quinn.
+
+use std::{
+    error::Error as StdError,
+    fmt,
+    future::{poll_fn, ready},
+    net,
+    pin::pin,
+    rc::Rc,
+    sync::Arc,
+};
+
+use actix_http::{
+    body::{BodySize, BoxBody, MessageBody},
+    header::{self, HeaderMap, HeaderName, HeaderValue, TryIntoHeaderPair},
+    BoxedPayloadStream, Method, Payload, Request, Response, StatusCode, Uri, Version,
+};
+use actix_service::{map_config, IntoServiceFactory, ServiceFactory, ServiceFactoryExt as _};
+use actix_web::{
+    dev::{AppConfig, Service},
+    rt,
+    web::{Bytes, BytesMut},
+    Error,
+};
+use bytes::BufMut as _;
+use futures_util::future::{self, Either};
+use quinn::crypto::rustls::{QuicClientConfig, QuicServerConfig};
+
+use crate::{client_tls::rustls_0_23_client_config, ConnectionCounters};
+
+/// ALPN protocol identifying HTTP/3.
+const ALPN: &[u8] = b"h3";
+
+/// The `H3_NO_ERROR` code, used when closing client connections.
+const NO_ERROR: quinn::VarInt = quinn::VarInt::from_u32(0x100);
+
+/// Binds a QUIC endpoint to `addr` and spawns its accept loop on the current runtime.
+///
+/// # Panics
+/// Panics if `tls` is not usable for QUIC or the endpoint can not be bound.
+pub(crate) fn listen<F, I, S, B>(
+    addr: net::SocketAddr,
+    mut tls: tls_rustls_0_23::ServerConfig,
+    factory: F,
+    counters: ConnectionCounters,
+) where
+    F: Fn() -> I,
+    I: IntoServiceFactory<S, Request>,
+    S: ServiceFactory<Request, Config = AppConfig> + 'static,
+    S::Error: Into<Error> + 'static,
+    S::InitError: fmt::Debug,
+    S::Response: Into<Response<B>> + 'static,
+    <S::Service as Service<Request>>::Future: 'static,
+    B: MessageBody + 'static,
+{
+    tls.alpn_protocols = vec![ALPN.to_vec()];
+
+    let crypto = QuicServerConfig::try_from(tls)
+        .expect("HTTP/3 test server requires a TLS 1.3 server config");
+    let endpoint =
+        quinn::Endpoint::server(quinn::ServerConfig::with_crypto(Arc::new(crypto)), addr)
+            .expect("HTTP/3 test server could not be bound");
+
+    let app_cfg = AppConfig::__priv_test_new(true, addr.to_string(), addr);
+
+    let fac = factory()
+        .into_factory()
+        .map_err(|err| err.into().error_response());
+    let fac = map_config(fac, move |_| app_cfg.clone());
+
+    rt::spawn(async move {
+        let service = Rc::new(
+            fac.new_service(())
+                .await
+                .expect("test server could not be created"),
+        );
+
+        while let Some(incoming) = endpoint.accept().await {
+            let service = Rc::clone(&service);
+            let counters = counters.clone();
+
+            rt::spawn(async move {
+                if let Err(err) = serve_connection(incoming, service, counters).await {
+                    log::debug!("HTTP/3 test server connection failed: {err}");
+                }
+            });
+        }
+    });
+}
+
+/// Serves the requests of a single connection, each in its own task.
+async fn serve_connection<T, B>(
+    incoming: quinn::Incoming,
+    service: Rc<T>,
+    counters: ConnectionCounters,
+) -> Result<(), Box<dyn StdError>>
+where
+    T: Service<Request> + 'static,
+    T::Error: Into<Response<BoxBody>>,
+    T::Response: Into<Response<B>>,
+    B: MessageBody + 'static,
+{
+    let conn = incoming.await?;
+    let peer_addr = conn.remote_address();
+
+    // counts the connection until it is closed
+    let _active = counters.connection();
+
+    let mut conn = h3::server::Connection::<_, Bytes>::new(h3_quinn::Connection::new(conn)).await?;
+
+    while let Some(resolver) = conn.accept().await? {
+        let service = Rc::clone(&service);
+
+        rt::spawn(async move {
+            let (req, mut stream) = match resolver.resolve_request().await {
+                Ok(req) => req,
+                Err(err) => {
+                    log::debug!("HTTP/3 test server could not read request: {err}");
+                    return;
+                }
+            };
+
+            let is_head = req.method() == http_1::Method::HEAD;
+
+            let mut body = BytesMut::new();
+            loop {
+                match stream.recv_data().await {
+                    Ok(Some(chunk)) => body.put(chunk),
+                    Ok(None) => break,
+                    Err(err) => {
+                        log::debug!("HTTP/3 test server could not read request body: {err}");
+                        return;
+                    }
+                }
+            }
+
+            let req = match into_request(req, body.freeze(), peer_addr) {
+                Ok(req) => req,
+                Err(err) => {
+                    log::debug!("HTTP/3 test server received an invalid request: {err}");
+                    return;
+                }
+            };
+
+            let res = match service.call(req).await {
+                Ok(res) => res.into().map_into_boxed_body(),
+                Err(res) => res.into(),
+            };
+
+            if let Err(err) = send_response(&mut stream, res, is_head).await {
+                log::debug!("HTTP/3 test server could not send response: {err}");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Converts a request received by the listener into one for the app, with a buffered payload.
+fn into_request(
+    req: http_1::Request<()>,
+    body: Bytes,
+    peer_addr: net::SocketAddr,
+) -> Result<Request, Box<dyn StdError>> {
+    let (parts, ()) = req.into_parts();
+
+    let payload: BoxedPayloadStream = Box::pin(futures_util::stream::once(ready(Ok(body))));
+    let mut req = Request::with_payload(Payload::from(payload));
+
+    let head = req.head_mut();
+    head.method = Method::from_bytes(parts.method.as_str().as_bytes())?;
+    head.uri = Uri::try_from(parts.uri.to_string())?;
+    head.version = Version::HTTP_3;
+    head.peer_addr = Some(peer_addr);
+
+    for (name, value) in &parts.headers {
+        head.headers.append(
+            HeaderName::from_bytes(name.as_str().as_bytes())?,
+            HeaderValue::from_bytes(value.as_bytes())?,
+        );
+    }
+
+    Ok(req)
+}
+
+/// Sends the app's response on the request's stream.
+async fn send_response<S>(
+    stream: &mut h3::server::RequestStream<S, Bytes>,
+    res: Response<BoxBody>,
+    is_head: bool,
+) -> Result<(), Box<dyn StdError>>
+where
+    S: h3::quic::BidiStream<Bytes>,
+{
+    let (res, body) = res.into_parts();
+
+    let mut head = http_1::Response::builder().status(res.status().as_u16());
+
+    for (name, value) in res.headers() {
+        // connection-specific headers are not allowed in HTTP/3
+        if matches!(
+            *name,
+            header::CONNECTION | header::TRANSFER_ENCODING | header::UPGRADE
+        ) {
+            continue;
+        }
+
+        head = head.header(name.as_str(), value.as_bytes());
+    }
+
+    if let BodySize::Sized(len) = body.size() {
+        if !res.headers().contains_key(header::CONTENT_LENGTH) {
+            head = head.header(http_1::header::CONTENT_LENGTH, len);
+        }
+    }
+
+    stream.send_response(head.body(())?).await?;
+
+    if !is_head {
+        let mut body = pin!(body);
+
+        while let Some(chunk) = poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+            let chunk = chunk?;
+
+            if !chunk.is_empty() {
+                stream.send_data(chunk).await?;
+            }
+        }
+    }
+
+    stream.finish().await?;
+
+    Ok(())
+}
+
+/// A request to a test server's HTTP/3 listener.
+///
+/// Each request is sent on a new QUIC connection, which is closed once the response has been
+/// received. Constructed using [`TestServer::h3_request()`](crate::TestServer::h3_request()).
+pub struct H3ClientRequest {
+    addr: net::SocketAddr,
+    method: Method,
+    url: String,
+    headers: HeaderMap,
+    err: Option<Box<dyn StdError>>,
+}
+
+impl H3ClientRequest {
+    pub(crate) fn new(addr: net::SocketAddr, method: Method, url: String) -> Self {
+        H3ClientRequest {
+            addr,
+            method,
+            url,
+            headers: HeaderMap::new(),
+            err: None,
+        }
+    }
+
+    /// Inserts a header, replacing any that were set with an equivalent field name.
+    pub fn insert_header(mut self, header: impl TryIntoHeaderPair) -> Self {
+        match header.try_into_pair() {
+            Ok((key, value)) => {
+                self.headers.insert(key, value);
+            }
+            Err(err) => self.err = Some(err.into().into()),
+        }
+
+        self
+    }
+
+    /// Sends the request without a body.
+    pub async fn send(self) -> Result<H3ClientResponse, H3ClientError> {
+        self.send_body(Bytes::new()).await
+    }
+
+    /// Sends the request with `body`.
+    pub async fn send_body(
+        self,
+        body: impl Into<Bytes>,
+    ) -> Result<H3ClientResponse, H3ClientError> {
+        let body = body.into();
+
+        if let Some(err) = self.err {
+            return Err(H3ClientError(err));
+        }
+
+        let mut head = http_1::Request::builder()
+            .method(self.method.as_str())
+            .uri(&self.url);
+
+        for (name, value) in &self.headers {
+            head = head.header(name.as_str(), value.as_bytes());
+        }
+
+        let head = head.body(()).map_err(H3ClientError::new)?;
+
+        let bind_addr = match self.addr {
+            net::SocketAddr::V4(_) => net::SocketAddr::from((net::Ipv4Addr::UNSPECIFIED, 0)),
+            net::SocketAddr::V6(_) => net::SocketAddr::from((net::Ipv6Addr::UNSPECIFIED, 0)),
+        };
+
+        let mut endpoint = quinn::Endpoint::client(bind_addr).map_err(H3ClientError::new)?;
+        endpoint.set_default_client_config(client_config());
+
+        let conn = endpoint
+            .connect(self.addr, "localhost")
+            .map_err(H3ClientError::new)?
+            .await
+            .map_err(H3ClientError::new)?;
+
+        let (mut driver, mut send_request) =
+            h3::client::new(h3_quinn::Connection::new(conn.clone()))
+                .await
+                .map_err(H3ClientError::new)?;
+
+        let exchange = pin!(async move {
+            let mut stream = send_request.send_request(head).await?;
+
+            if !body.is_empty() {
+                stream.send_data(body).await?;
+            }
+
+            stream.finish().await?;
+
+            let res = stream.recv_response().await?;
+
+            let mut body = BytesMut::new();
+            while let Some(chunk) = stream.recv_data().await? {
+                body.put(chunk);
+            }
+
+            Ok::<_, Box<dyn StdError>>((res, body.freeze()))
+        });
+
+        // the connection must be driven while the request is in flight
+        let drive = pin!(poll_fn(|cx| driver.poll_close(cx)));
+
+        let res = match future::select(exchange, drive).await {
+            Either::Left((res, _)) => res,
+            Either::Right((err, _)) => Err(err.into()),
+        };
+
+        conn.close(NO_ERROR, b"");
+
+        let (res, body) = res.map_err(H3ClientError)?;
+        let (parts, ()) = res.into_parts();
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &parts.headers {
+            headers.append(
+                HeaderName::from_bytes(name.as_str().as_bytes()).map_err(H3ClientError::new)?,
+                HeaderValue::from_bytes(value.as_bytes()).map_err(H3ClientError::new)?,
+            );
+        }
+
+        Ok(H3ClientResponse {
+            status: StatusCode::from_u16(parts.status.as_u16()).map_err(H3ClientError::new)?,
+            headers,
+            body,
+        })
+    }
+}
+
+impl fmt::Debug for H3ClientRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("H3ClientRequest")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A response received from a test server's HTTP/3 listener, with its body read in full.
+#[derive(Debug, Clone)]
+pub struct H3ClientResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl H3ClientResponse {
+    /// Returns the response status code.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the response headers.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the response body.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// Consumes the response, returning its body.
+    pub fn into_body(self) -> Bytes {
+        self.body
+    }
+}
+
+/// An error sending a request to a test server's HTTP/3 listener.
+#[derive(Debug)]
+pub struct H3ClientError(Box<dyn StdError>);
+
+impl H3ClientError {
+    fn new(err: impl StdError + 'static) -> Self {
+        H3ClientError(Box::new(err))
+    }
+}
+
+impl fmt::Display for H3ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HTTP/3 request failed: {}", self.0)
+    }
+}
+
+impl StdError for H3ClientError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.0)
+    }
+}
+
+/// Returns a QUIC client config that trusts any server certificate.
+fn client_config() -> quinn::ClientConfig {
+    let mut tls = rustls_0_23_client_config();
+    tls.alpn_protocols = vec![ALPN.to_vec()];
+
+    let crypto = QuicClientConfig::try_from(tls).expect("test client config should support QUIC");
+    quinn::ClientConfig::new(Arc::new(crypto))
+}
@@ -0,0 +1,212 @@
+//! Structural JSON assertions.
+
+use serde_json::Value;
+
+use crate::SnapshotResponse;
+
+/// Compares two JSON values structurally and describes each difference.
+///
+/// Differences are reported at their [JSON Pointer] path, e.g. `/items/0/name`. Values at the
+/// `ignore` paths, and anything nested in them, are not compared, which is useful for volatile
+/// fields such as timestamps and generated IDs. Returns an empty list if the values are equal.
+///
+/// See also [`assert_json_eq!`](crate::assert_json_eq) and
+/// [`assert_body_json_eq!`](crate::assert_body_json_eq).
+///
+/// # Examples
+/// ```
+/// use serde_json::json;
+///
+/// let diffs = actix_test::json_diff(
+///     &json!({ "id": 42, "name": "alice", "tags": ["a"] }),
+///     &json!({ "id": 1, "name": "bob", "tags": ["a", "b"] }),
+///     &["/id"],
+/// );
+///
+/// assert_eq!(
+///     diffs,
+///     [
+///         r#"/name: expected "bob", found "alice""#,
+///         r#"/tags/1: missing element, expected "b""#,
+///     ],
+/// );
+/// ```
+///
+/// [JSON Pointer]: https://datatracker.ietf.org/doc/html/rfc6901
+pub fn json_diff(actual: &Value, expected: &Value, ignore: &[&str]) -> Vec<String> {
+    let mut diffs = Vec::new();
+    diff_at(&mut String::new(), actual, expected, ignore, &mut diffs);
+    diffs
+}
+
+fn diff_at(
+    path: &mut String,
+    actual: &Value,
+    expected: &Value,
+    ignore: &[&str],
+    diffs: &mut Vec<String>,
+) {
+    if ignore.contains(&path.as_str()) {
+        return;
+    }
+
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => {
+            for (key, expected) in expected {
+                with_segment(path, key, |path| match actual.get(key) {
+                    Some(actual) => diff_at(path, actual, expected, ignore, diffs),
+                    None if ignore.contains(&path.as_str()) => {}
+                    None => diffs.push(format!("{path}: missing field, expected {expected}")),
+                });
+            }
+
+            for (key, actual) in actual {
+                if expected.contains_key(key) {
+                    continue;
+                }
+
+                with_segment(path, key, |path| {
+                    if !ignore.contains(&path.as_str()) {
+                        diffs.push(format!("{path}: unexpected field with value {actual}"));
+                    }
+                });
+            }
+        }
+
+        (Value::Array(actual), Value::Array(expected)) => {
+            for idx in 0..actual.len().max(expected.len()) {
+                with_segment(path, &idx.to_string(), |path| {
+                    match (actual.get(idx), expected.get(idx)) {
+                        _ if ignore.contains(&path.as_str()) => {}
+                        (Some(actual), Some(expected)) => {
+                            diff_at(path, actual, expected, ignore, diffs)
+                        }
+                        (None, Some(expected)) => {
+                            diffs.push(format!("{path}: missing element, expected {expected}"))
+                        }
+                        (Some(actual), None) => {
+                            diffs.push(format!("{path}: unexpected element {actual}"))
+                        }
+                        (None, None) => unreachable!(),
+                    }
+                });
+            }
+        }
+
+        _ if actual == expected => {}
+
+        _ => {
+            let path = if path.is_empty() {
+                "(root)"
+            } else {
+                path.as_str()
+            };
+            diffs.push(format!("{path}: expected {expected}, found {actual}"));
+        }
+    }
+}
+
+/// Calls `f` with `segment` appended to the JSON Pointer `path`.
+fn with_segment(path: &mut String, segment: &str, f: impl FnOnce(&mut String)) {
+    let len = path.len();
+
+    path.push('/');
+    path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+
+    f(path);
+
+    path.truncate(len);
+}
+
+/// Panics with a description of the differences between two JSON values, if there are any.
+#[doc(hidden)]
+#[track_caller]
+pub fn __assert_json_eq(actual: &Value, expected: &Value, ignore: &[&str]) {
+    let diffs = json_diff(actual, expected, ignore);
+
+    if !diffs.is_empty() {
+        panic!(
+            "JSON values are not equal:\n- {}\n\nactual:\n{}",
+            diffs.join("\n- "),
+            serde_json::to_string_pretty(actual).unwrap(),
+        );
+    }
+}
+
+/// Reads a response body as JSON.
+#[doc(hidden)]
+pub async fn __read_body_json(res: impl SnapshotResponse) -> Value {
+    let (_, _, body) = res.into_snapshot_parts().await;
+
+    serde_json::from_slice(&body).unwrap_or_else(|err| {
+        panic!(
+            "response body is not valid JSON ({err}): {}",
+            String::from_utf8_lossy(&body)
+        )
+    })
+}
+
+/// Asserts that two JSON values are structurally equal.
+///
+/// On failure, panics with the [JSON Pointer] path of each difference instead of the full
+/// serialized values. Paths listed in `ignore` are not compared. See [`json_diff()`] for details.
+///
+/// # Examples
+/// ```
+/// use actix_test::assert_json_eq;
+/// use serde_json::json;
+///
+/// let actual = json!({ "id": 42, "user": { "name": "alice" } });
+///
+/// assert_json_eq!(actual, json!({ "id": 42, "user": { "name": "alice" } }));
+/// assert_json_eq!(actual, json!({ "id": 1, "user": { "name": "alice" } }), ignore = ["/id"]);
+/// ```
+///
+/// [JSON Pointer]: https://datatracker.ietf.org/doc/html/rfc6901
+#[macro_export]
+macro_rules! assert_json_eq {
+    ($actual:expr, $expected:expr $(, ignore = [$($path:expr),* $(,)?])? $(,)?) => {{
+        let ignore: &[&str] = &[$($($path),*)?];
+        $crate::__assert_json_eq(&$actual, &$expected, ignore);
+    }};
+}
+
+/// Asserts that a response body is JSON structurally equal to an expected value.
+///
+/// Works with [`ServiceResponse`](actix_web::dev::ServiceResponse)s and a
+/// [`TestServer`](crate::TestServer)'s [`ClientResponse`](crate::ClientResponse)s, and must be used
+/// in an async context. Paths listed in `ignore` are not compared. See
+/// [`assert_json_eq!`](crate::assert_json_eq) for details.
+///
+/// # Examples
+/// ```
+/// use actix_test::assert_body_json_eq;
+/// use actix_web::{test, web, App, HttpResponse};
+/// use serde_json::json;
+///
+/// #[actix_web::test]
+/// async fn test_create_user() {
+///     let app = test::init_service(App::new().route(
+///         "/users",
+///         web::post().to(|| async {
+///             HttpResponse::Created().json(json!({ "id": 7, "name": "alice", "created_at": 1700000000 }))
+///         }),
+///     ))
+///     .await;
+///
+///     let res = test::call_service(&app, test::TestRequest::post().uri("/users").to_request()).await;
+///
+///     assert_body_json_eq!(
+///         res,
+///         json!({ "id": 0, "name": "alice", "created_at": 0 }),
+///         ignore = ["/id", "/created_at"],
+///     );
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_body_json_eq {
+    ($res:expr, $expected:expr $(, ignore = [$($path:expr),* $(,)?])? $(,)?) => {{
+        let actual = $crate::__read_body_json($res).await;
+        $crate::assert_json_eq!(actual, $expected $(, ignore = [$($path),*])?);
+    }};
+}
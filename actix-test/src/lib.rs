@@ -34,27 +34,153 @@
 #[cfg(feature = "openssl")]
 extern crate tls_openssl as openssl;
 
-use std::{fmt, net, thread, time::Duration};
+mod cassette;
+mod client_tls;
+mod cookies;
+mod faults;
+mod hosts;
+#[cfg(feature = "http3")]
+mod http3;
+mod json;
+mod load;
+mod logs;
+mod mock;
+mod paused;
+mod snapshot;
+mod stream;
+#[cfg(unix)]
+mod uds;
+mod ws;
+
+use std::{
+    cell::RefCell,
+    fmt, net,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
 
 use actix_codec::{AsyncRead, AsyncWrite, Framed};
 pub use actix_http::{body::to_bytes, test::TestBuffer};
-use actix_http::{header::HeaderMap, ws, HttpService, Method, Request, Response};
+use actix_http::{header::HeaderMap, HttpService, Method, Request, Response};
 pub use actix_http_test::unused_addr;
+use actix_server::ServerBuilder;
 use actix_service::{map_config, IntoServiceFactory, ServiceFactory, ServiceFactoryExt as _};
+use actix_tls::connect::{Connector as TcpConnector, Resolver};
 pub use actix_web::test::{
     call_and_read_body, call_and_read_body_json, call_service, init_service, ok_service, read_body,
-    read_body_json, status_service, TestRequest,
+    read_body_json, status_service, MultipartBuilder, TestRequest,
 };
 use actix_web::{
     body::MessageBody,
-    dev::{AppConfig, Server, ServerHandle, Service},
+    dev::{AppConfig, Extensions, Server, ServerHandle, Service},
     rt::{self, System},
     web, Error,
 };
+use awc::cookie::{Cookie, CookieJar};
 pub use awc::{error::PayloadError, Client, ClientRequest, ClientResponse, Connector};
 use futures_core::Stream;
 use tokio::sync::mpsc;
 
+pub use self::{
+    cassette::{Cassette, CassetteMode, CassetteService},
+    faults::{FaultInjection, FaultInjectionService},
+    json::{__assert_json_eq, __read_body_json, json_diff},
+    load::{call_concurrently, LoadReport, LoadTarget},
+    logs::{capture_logs, LogCapture, LogRecord},
+    mock::{Expectation, MockResponse, MockServer},
+    snapshot::{snapshot, SnapshotConfig, SnapshotResponse},
+    stream::{BodyChunks, SseEvent, StreamReader},
+    ws::{TextMatcher, WsSession},
+};
+
+#[cfg(feature = "rustls-0_20")]
+pub use self::client_tls::rustls_0_20_client_config;
+#[cfg(feature = "rustls-0_21")]
+pub use self::client_tls::rustls_0_21_client_config;
+#[cfg(feature = "rustls-0_22")]
+pub use self::client_tls::rustls_0_22_client_config;
+#[cfg(feature = "rustls-0_23")]
+pub use self::client_tls::rustls_0_23_client_config;
+#[cfg(feature = "http3")]
+pub use self::http3::{H3ClientError, H3ClientRequest, H3ClientResponse};
+
+/// Utilities for controlling time in tests.
+///
+/// Pausing time makes tests of timeouts and other time-based behavior deterministic. Use
+/// [`pause()`](time::pause()) at the start of tests using `init_service`, or
+/// [`TestServerConfig::paused_time()`] for test servers.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{rt, test, web, App, HttpResponse};
+///
+/// #[actix_web::test]
+/// async fn test_timeout() {
+///     actix_test::time::pause();
+///
+///     let app = test::init_service(App::new().route(
+///         "/",
+///         web::get().to(|| async {
+///             match rt::time::timeout(Duration::from_secs(30), std::future::pending::<()>()).await {
+///                 Ok(_) => HttpResponse::Ok(),
+///                 Err(_) => HttpResponse::GatewayTimeout(),
+///             }
+///         }),
+///     ))
+///     .await;
+///
+///     let res = test::call_service(&app, test::TestRequest::get().to_request()).await;
+///     assert_eq!(res.status(), 504);
+/// }
+/// ```
+pub mod time {
+    pub use tokio::time::{advance, pause, resume, Instant};
+}
+
+/// Extension methods for sending test request bodies from a [`ClientRequest`].
+pub trait ClientRequestExt: sealed::Sealed {
+    /// Builds a `multipart/form-data` body from `form` and sends it.
+    ///
+    /// The `Content-Type` header is set to `multipart/form-data` with the form's boundary.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_test::{ClientRequestExt as _, MultipartBuilder};
+    /// use actix_web::{web, App, HttpResponse};
+    ///
+    /// #[actix_web::test]
+    /// async fn test_upload() {
+    ///     let srv = actix_test::start(|| App::new().route("/", web::post().to(HttpResponse::Ok)));
+    ///
+    ///     let form = MultipartBuilder::new().text("title", "Holiday");
+    ///     let res = srv.post("/").send_multipart(form).await.unwrap();
+    ///
+    ///     assert!(res.status().is_success());
+    /// }
+    /// ```
+    fn send_multipart(self, form: MultipartBuilder) -> awc::SendClientRequest;
+}
+
+impl ClientRequestExt for ClientRequest {
+    fn send_multipart(self, form: MultipartBuilder) -> awc::SendClientRequest {
+        let (body, content_type) = form.build();
+        self.content_type(content_type).send_body(body)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for awc::ClientRequest {}
+}
+
 /// Start default [`TestServer`].
 ///
 /// # Examples
@@ -148,21 +274,108 @@ where
         StreamType::Rustls023(_) => true,
     };
 
+    #[cfg(unix)]
+    assert!(
+        cfg.uds.is_none() || !tls,
+        "test servers bound to a Unix domain socket do not support TLS"
+    );
+
+    let counters = ConnectionCounters::default();
+
+    #[cfg(all(unix, feature = "http3"))]
+    assert!(
+        cfg.uds.is_none() || cfg.h3.is_none(),
+        "test servers bound to a Unix domain socket do not support HTTP/3"
+    );
+
+    if cfg.paused_time || cfg.same_thread {
+        assert!(!tls, "test servers on the test's thread do not support TLS");
+
+        #[cfg(feature = "http3")]
+        assert!(
+            cfg.h3.is_none(),
+            "test servers on the test's thread do not support HTTP/3"
+        );
+
+        #[cfg(unix)]
+        assert!(
+            cfg.uds.is_none(),
+            "test servers on the test's thread do not support Unix domain sockets"
+        );
+
+        if cfg.paused_time {
+            time::pause();
+        }
+
+        let (server, addr) = paused::LocalServer::start(&cfg, factory, &counters);
+
+        return test_server(cfg, Backend::Local(server), addr, tls, counters);
+    }
+
     let client_cfg = cfg.clone();
 
+    let srv_counters = counters.clone();
+
     // run server in separate orphaned thread
     thread::spawn(move || {
         rt::System::new().block_on(async move {
-            let tcp = net::TcpListener::bind((cfg.listen_address.clone(), cfg.port)).unwrap();
+            #[cfg(unix)]
+            if let Some(path) = cfg.uds.clone() {
+                use actix_http::Protocol;
+                use actix_rt::net::UnixStream;
+                use actix_service::fn_service;
+
+                let local_addr = uds_addr();
+                let timeout = cfg.client_request_timeout;
+                let protocol = match cfg.tp {
+                    HttpVer::Http2 => Protocol::Http2,
+                    HttpVer::Http1 | HttpVer::Both => Protocol::Http1,
+                };
+
+                let srv = cfg
+                    .server_builder()
+                    .bind_uds("test", path, move || {
+                        let app_cfg =
+                            AppConfig::__priv_test_new(false, local_addr.to_string(), local_addr);
+
+                        let fac = factory()
+                            .into_factory()
+                            .map_err(|err| err.into().error_response());
+
+                        fn_service(move |io: UnixStream| async move { Ok((io, protocol, None)) })
+                            .and_then(
+                                HttpService::build()
+                                    .client_request_timeout(timeout)
+                                    .on_connect_ext(srv_counters.on_connect())
+                                    .finish(map_config(fac, move |_| app_cfg.clone())),
+                            )
+                    })
+                    .expect("test server could not be created")
+                    .run();
+
+                started_tx
+                    .send((System::current(), srv.handle(), local_addr))
+                    .unwrap();
+
+                // drive server loop
+                srv.await.unwrap();
+
+                return;
+            }
+
+            let tcp = cfg.bind_tcp();
             let local_addr = tcp.local_addr().unwrap();
             let factory = factory.clone();
             let srv_cfg = cfg.clone();
+
+            #[cfg(feature = "http3")]
+            if let Some(config) = srv_cfg.h3.clone() {
+                http3::listen(local_addr, config, factory.clone(), srv_counters.clone());
+            }
+
             let timeout = cfg.client_request_timeout;
 
-            let builder = Server::build()
-                .workers(cfg.workers)
-                .disable_signals()
-                .system_exit();
+            let builder = cfg.server_builder();
 
             let srv = match srv_cfg.stream {
                 StreamType::Tcp => match srv_cfg.tp {
@@ -176,6 +389,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h1(map_config(fac, move |_| app_cfg.clone()))
                             .tcp()
                     }),
@@ -189,6 +403,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h2(map_config(fac, move |_| app_cfg.clone()))
                             .tcp()
                     }),
@@ -202,6 +417,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .finish(map_config(fac, move |_| app_cfg.clone()))
                             .tcp()
                     }),
@@ -218,6 +434,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h1(map_config(fac, move |_| app_cfg.clone()))
                             .openssl(acceptor.clone())
                     }),
@@ -231,6 +448,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h2(map_config(fac, move |_| app_cfg.clone()))
                             .openssl(acceptor.clone())
                     }),
@@ -244,6 +462,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .finish(map_config(fac, move |_| app_cfg.clone()))
                             .openssl(acceptor.clone())
                     }),
@@ -260,6 +479,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h1(map_config(fac, move |_| app_cfg.clone()))
                             .rustls(config.clone())
                     }),
@@ -273,6 +493,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h2(map_config(fac, move |_| app_cfg.clone()))
                             .rustls(config.clone())
                     }),
@@ -286,6 +507,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .finish(map_config(fac, move |_| app_cfg.clone()))
                             .rustls(config.clone())
                     }),
@@ -302,6 +524,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h1(map_config(fac, move |_| app_cfg.clone()))
                             .rustls_021(config.clone())
                     }),
@@ -315,6 +538,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h2(map_config(fac, move |_| app_cfg.clone()))
                             .rustls_021(config.clone())
                     }),
@@ -328,6 +552,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .finish(map_config(fac, move |_| app_cfg.clone()))
                             .rustls_021(config.clone())
                     }),
@@ -344,6 +569,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h1(map_config(fac, move |_| app_cfg.clone()))
                             .rustls_0_22(config.clone())
                     }),
@@ -357,6 +583,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h2(map_config(fac, move |_| app_cfg.clone()))
                             .rustls_0_22(config.clone())
                     }),
@@ -370,6 +597,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .finish(map_config(fac, move |_| app_cfg.clone()))
                             .rustls_0_22(config.clone())
                     }),
@@ -386,6 +614,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h1(map_config(fac, move |_| app_cfg.clone()))
                             .rustls_0_23(config.clone())
                     }),
@@ -399,6 +628,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .h2(map_config(fac, move |_| app_cfg.clone()))
                             .rustls_0_23(config.clone())
                     }),
@@ -412,6 +642,7 @@ where
 
                         HttpService::build()
                             .client_request_timeout(timeout)
+                            .on_connect_ext(srv_counters.on_connect())
                             .finish(map_config(fac, move |_| app_cfg.clone()))
                             .rustls_0_23(config.clone())
                     }),
@@ -437,49 +668,151 @@ where
 
     let (system, server, addr) = started_rx.recv().unwrap();
 
-    let client = {
-        let connector = {
-            #[cfg(feature = "openssl")]
-            {
-                use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
-
-                let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
-                builder.set_verify(SslVerifyMode::NONE);
-                let _ = builder
-                    .set_alpn_protos(b"\x02h2\x08http/1.1")
-                    .map_err(|err| log::error!("Can not set alpn protocol: {err:?}"));
-                Connector::new()
-                    .conn_lifetime(Duration::from_secs(0))
-                    .timeout(Duration::from_millis(30000))
-                    .openssl(builder.build())
+    let backend = Backend::Thread {
+        server,
+        system,
+        thread_stop_rx,
+    };
+
+    test_server(client_cfg, backend, addr, tls, counters)
+}
+
+/// Builds the client of a started test server.
+fn test_server(
+    client_cfg: TestServerConfig,
+    backend: Backend,
+    addr: net::SocketAddr,
+    tls: bool,
+    counters: ConnectionCounters,
+) -> TestServer {
+    let jar = client_cfg
+        .cookie_store
+        .then(|| Rc::new(RefCell::new(CookieJar::new())));
+
+    let client = 'client: {
+        let resolver = Resolver::custom(hosts::HostResolver::new(&client_cfg.hosts, addr));
+
+        #[allow(unused_mut)]
+        let mut connector = Connector::new()
+            .connector(TcpConnector::new(resolver).service())
+            .conn_lifetime(Duration::from_secs(0))
+            .timeout(Duration::from_millis(30000));
+
+        #[cfg(feature = "openssl")]
+        {
+            use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+
+            let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+            builder.set_verify(SslVerifyMode::NONE);
+            let _ = builder
+                .set_alpn_protos(b"\x02h2\x08http/1.1")
+                .map_err(|err| log::error!("Can not set alpn protocol: {err:?}"));
+            connector = connector.openssl(builder.build());
+        }
+
+        // use a Rustls client of the same version as a Rustls server
+        #[allow(clippy::match_single_binding)]
+        match client_cfg.stream {
+            #[cfg(feature = "rustls-0_20")]
+            StreamType::Rustls020(_) => {
+                connector = connector.rustls(Arc::new(rustls_0_20_client_config()));
             }
-            #[cfg(not(feature = "openssl"))]
-            {
-                Connector::new()
-                    .conn_lifetime(Duration::from_secs(0))
-                    .timeout(Duration::from_millis(30000))
+            #[cfg(feature = "rustls-0_21")]
+            StreamType::Rustls021(_) => {
+                connector = connector.rustls_021(Arc::new(rustls_0_21_client_config()));
             }
-        };
+            #[cfg(feature = "rustls-0_22")]
+            StreamType::Rustls022(_) => {
+                connector = connector.rustls_0_22(Arc::new(rustls_0_22_client_config()));
+            }
+            #[cfg(feature = "rustls-0_23")]
+            StreamType::Rustls023(_) => {
+                connector = connector.rustls_0_23(Arc::new(rustls_0_23_client_config()));
+            }
+            _ => {}
+        }
 
-        let mut client_builder = Client::builder().connector(connector);
+        let mut client_builder = Client::builder();
 
         if client_cfg.disable_redirects {
             client_builder = client_builder.disable_redirects();
         }
 
-        client_builder.finish()
+        // with paused time, the timeout would expire as soon as the client awaits a response
+        if client_cfg.paused_time {
+            client_builder = client_builder.disable_timeout();
+        }
+
+        #[cfg(unix)]
+        if let Some(path) = &client_cfg.uds {
+            break 'client client_builder
+                .connector(connector.connector(uds::UdsConnector::new(path)))
+                .wrap(cookies::CookieStore::new(jar.clone()))
+                .wrap(client_cfg.faults.clone().unwrap_or_default())
+                .finish();
+        }
+
+        client_builder
+            .connector(connector)
+            .wrap(cookies::CookieStore::new(jar.clone()))
+            .wrap(client_cfg.faults.clone().unwrap_or_default())
+            .finish()
     };
 
     TestServer {
-        server,
-        thread_stop_rx,
+        backend,
         client,
-        system,
         addr,
         tls,
+        #[cfg(unix)]
+        uds: client_cfg.uds,
+        counters,
+        jar,
+        #[cfg(feature = "http3")]
+        h3: client_cfg.h3.is_some(),
+    }
+}
+
+/// Shared counters of connections to a test server.
+#[derive(Debug, Clone, Default)]
+struct ConnectionCounters {
+    accepted: Arc<AtomicUsize>,
+    active: Arc<AtomicUsize>,
+}
+
+impl ConnectionCounters {
+    /// Returns an on-connect callback that counts the connection until its data is dropped.
+    fn on_connect<T>(&self) -> impl Fn(&T, &mut Extensions) + 'static {
+        let counters = self.clone();
+
+        move |_: &T, ext: &mut Extensions| {
+            ext.insert(counters.connection());
+        }
+    }
+
+    /// Counts a new connection, which stays active until the returned guard is dropped.
+    fn connection(&self) -> ActiveConnection {
+        self.accepted.fetch_add(1, Ordering::SeqCst);
+        self.active.fetch_add(1, Ordering::SeqCst);
+        ActiveConnection(Arc::clone(&self.active))
     }
 }
 
+/// Decrements the active connection count when dropped along with its connection.
+struct ActiveConnection(Arc<AtomicUsize>);
+
+impl Drop for ActiveConnection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Placeholder address reported for servers bound to a Unix domain socket.
+#[cfg(unix)]
+fn uds_addr() -> net::SocketAddr {
+    net::SocketAddr::new(net::IpAddr::V4(net::Ipv4Addr::LOCALHOST), 8080)
+}
+
 #[derive(Debug, Clone)]
 enum HttpVer {
     Http1,
@@ -517,6 +850,17 @@ pub struct TestServerConfig {
     port: u16,
     workers: usize,
     disable_redirects: bool,
+    #[cfg(unix)]
+    uds: Option<std::path::PathBuf>,
+    shutdown_timeout: Option<Duration>,
+    cookie_store: bool,
+    paused_time: bool,
+    faults: Option<FaultInjection>,
+    hosts: Vec<String>,
+    bind_retry: Duration,
+    same_thread: bool,
+    #[cfg(feature = "http3")]
+    h3: Option<tls_rustls_0_23::ServerConfig>,
 }
 
 impl Default for TestServerConfig {
@@ -536,6 +880,17 @@ impl TestServerConfig {
             port: 0,
             workers: 1,
             disable_redirects: false,
+            #[cfg(unix)]
+            uds: None,
+            shutdown_timeout: None,
+            cookie_store: false,
+            paused_time: false,
+            faults: None,
+            hosts: Vec::new(),
+            bind_retry: Duration::ZERO,
+            same_thread: false,
+            #[cfg(feature = "http3")]
+            h3: None,
         }
     }
 
@@ -602,6 +957,41 @@ impl TestServerConfig {
         self
     }
 
+    /// Also accepts HTTP/3 connections, secured using `config`, on a QUIC listener bound to the
+    /// same port as the TCP listener.
+    ///
+    /// The config must support TLS 1.3; its ALPN protocols are replaced with `h3`. Requests are
+    /// sent to the listener using [`TestServer::h3_request()`]. The listener counts towards
+    /// [connection counts](TestServer::accepted_connections()) but is not shut down gracefully.
+    ///
+    /// # Panics
+    /// Starting the server panics if the listener can not be bound, or time is
+    /// [paused](Self::paused_time()), the server runs on the [same thread](Self::same_thread()),
+    /// or a Unix domain socket is also configured.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use actix_web::{web, App};
+    ///
+    /// # fn tls_config() -> tls_rustls_0_23::ServerConfig { unimplemented!() }
+    /// #[actix_web::test]
+    /// async fn test_h3() {
+    ///     let srv = actix_test::start_with(actix_test::config().h3_listener(tls_config()), || {
+    ///         App::new().route("/", web::get().to(|| async { "hello" }))
+    ///     });
+    ///
+    ///     let res = srv.h3_get("/").send().await.unwrap();
+    ///
+    ///     assert!(res.status().is_success());
+    ///     assert_eq!(res.body(), "hello");
+    /// }
+    /// ```
+    #[cfg(feature = "http3")]
+    pub fn h3_listener(mut self, config: tls_rustls_0_23::ServerConfig) -> Self {
+        self.h3 = Some(config);
+        self
+    }
+
     /// Sets client timeout for first request.
     pub fn client_request_timeout(mut self, dur: Duration) -> Self {
         self.client_request_timeout = dur;
@@ -624,6 +1014,16 @@ impl TestServerConfig {
         self
     }
 
+    /// Retries binding the configured [port](Self::port()) for up to `dur` while it is in use.
+    ///
+    /// Useful for tests that need a stable port, such as for a callback URL registered elsewhere,
+    /// when the port may still be held by the server of a previous test that is shutting down.
+    /// By default, starting the server panics immediately if the port is in use.
+    pub fn bind_retry(mut self, dur: Duration) -> Self {
+        self.bind_retry = dur;
+        self
+    }
+
     /// Sets number of workers for the test server.
     ///
     /// By default, the server uses 1 worker
@@ -640,6 +1040,221 @@ impl TestServerConfig {
         self.disable_redirects = true;
         self
     }
+
+    /// Enables a cookie store for the test server's client.
+    ///
+    /// Cookies set by responses are stored and sent with subsequent requests to matching paths,
+    /// like a browser would, until they expire or are removed by the server. Cookies added to a
+    /// request explicitly take precedence over stored cookies of the same name.
+    ///
+    /// See [`TestServer::cookie()`] for inspecting stored cookies.
+    pub fn cookie_store(mut self) -> Self {
+        self.cookie_store = true;
+        self
+    }
+
+    /// Resolves `host` to the server's address in the test server's client.
+    ///
+    /// Requests to the mapped hostname, on any port, connect to the test server while keeping the
+    /// hostname in the `Host` header and TLS SNI, so that host-based routing and
+    /// [`guard::Host`](actix_web::guard::Host) can be tested. Hostnames are matched
+    /// case-insensitively and may be mapped repeatedly to add more of them. Other hostnames are
+    /// resolved as usual. See also [`TestServer::url_for_host()`].
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{guard, http::Method, web, App, HttpResponse};
+    ///
+    /// #[actix_web::test]
+    /// async fn test_tenant_routing() {
+    ///     let srv = actix_test::start_with(
+    ///         actix_test::config().map_host("tenant1.example.test"),
+    ///         || {
+    ///             App::new().service(
+    ///                 web::scope("")
+    ///                     .guard(guard::Host("tenant1.example.test"))
+    ///                     .route("/", web::get().to(|| async { "tenant 1" })),
+    ///             )
+    ///         },
+    ///     );
+    ///
+    ///     let url = srv.url_for_host("tenant1.example.test", "/");
+    ///     let mut res = srv.request(Method::GET, url).send().await.unwrap();
+    ///
+    ///     assert_eq!(res.body().await.unwrap(), "tenant 1");
+    /// }
+    /// ```
+    pub fn map_host(mut self, host: impl Into<String>) -> Self {
+        self.hosts.push(host.into());
+        self
+    }
+
+    /// Injects faults into requests sent by the test server's client.
+    ///
+    /// See [`FaultInjection`] for the available faults.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_test::FaultInjection;
+    /// use actix_web::{web, App, HttpResponse};
+    ///
+    /// #[actix_web::test]
+    /// async fn test_reset() {
+    ///     let srv = actix_test::start_with(
+    ///         actix_test::config().faults(FaultInjection::new().connection_reset(1.0)),
+    ///         || App::new().route("/", web::get().to(HttpResponse::Ok)),
+    ///     );
+    ///
+    ///     assert!(srv.get("/").send().await.is_err());
+    /// }
+    /// ```
+    pub fn faults(mut self, faults: FaultInjection) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Sets the time allowed for in-flight requests to complete during a graceful shutdown.
+    ///
+    /// Connections still open after the timeout are aborted. The timeout is rounded up to whole
+    /// seconds. By default, the timeout is 30 seconds.
+    ///
+    /// See [`TestServer::stop_gracefully()`].
+    pub fn shutdown_timeout(mut self, dur: Duration) -> Self {
+        self.shutdown_timeout = Some(dur);
+        self
+    }
+
+    /// Runs the server on the test's runtime with time paused.
+    ///
+    /// While time is paused, pending timers fire as soon as the runtime has no other work to do, so
+    /// client request timeouts, keep-alive expiry, and other time-based behavior can be tested
+    /// deterministically without real waiting. Time can also be moved forward explicitly with
+    /// [`time::advance()`]. Pausing affects all timers on the test's runtime, including those of
+    /// the test itself.
+    ///
+    /// The server accepts connections on the test's runtime instead of a separate thread, so the
+    /// [`workers()`](Self::workers()) setting is ignored. The client's request timeout is disabled.
+    ///
+    /// # Panics
+    /// Starting the server panics if time is already paused, the runtime is multi-threaded, or TLS
+    /// or a Unix domain socket is also configured.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use actix_test::time::Instant;
+    /// use actix_web::{rt, web, App};
+    ///
+    /// #[actix_web::test]
+    /// async fn test_slow_handler() {
+    ///     let srv = actix_test::start_with(actix_test::config().paused_time(), || {
+    ///         App::new().route(
+    ///             "/",
+    ///             web::get().to(|| async {
+    ///                 rt::time::sleep(Duration::from_secs(60 * 60)).await;
+    ///                 "done"
+    ///             }),
+    ///         )
+    ///     });
+    ///
+    ///     let start = Instant::now();
+    ///     let res = srv.get("/").send().await.unwrap();
+    ///
+    ///     assert!(res.status().is_success());
+    ///     assert!(start.elapsed() >= Duration::from_secs(60 * 60));
+    /// }
+    /// ```
+    pub fn paused_time(mut self) -> Self {
+        self.paused_time = true;
+        self
+    }
+
+    /// Runs the server on the test's runtime, on the same thread as the client.
+    ///
+    /// Requests and responses are then processed in an order determined by a single scheduler,
+    /// rather than by two threads racing each other, which makes regression tests of race
+    /// conditions reproducible. Handlers that block the thread also block the client.
+    ///
+    /// As with [`paused_time()`](Self::paused_time()), the [`workers()`](Self::workers()) setting
+    /// is ignored.
+    ///
+    /// # Panics
+    /// Starting the server panics if the runtime is multi-threaded, or TLS or a Unix domain socket
+    /// is also configured.
+    pub fn same_thread(mut self) -> Self {
+        self.same_thread = true;
+        self
+    }
+
+    /// Binds the server to a Unix domain socket at `path` instead of a TCP port.
+    ///
+    /// Any existing file at `path` is removed before binding and the socket file is removed again
+    /// when the server is dropped. The test server's client connects through the socket, whatever
+    /// the host in request URLs. Connections use plaintext HTTP/1.1, or HTTP/2 with prior knowledge
+    /// when combined with [`h2()`](Self::h2()).
+    ///
+    /// # Panics
+    /// Starting the server panics if TLS is also configured.
+    #[cfg(unix)]
+    pub fn uds(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.uds = Some(path.into());
+        self
+    }
+}
+
+impl TestServerConfig {
+    /// Returns a server builder with the configured worker and shutdown settings.
+    fn server_builder(&self) -> ServerBuilder {
+        let mut builder = Server::build()
+            .workers(self.workers)
+            .disable_signals()
+            .system_exit();
+
+        if let Some(dur) = self.shutdown_timeout {
+            let secs = dur.as_secs() + u64::from(dur.subsec_nanos() > 0);
+            builder = builder.shutdown_timeout(secs);
+        }
+
+        builder
+    }
+
+    /// Binds a TCP listener to the configured address, retrying while the port is in use.
+    ///
+    /// # Panics
+    /// Panics if the listener can not be bound.
+    pub(crate) fn bind_tcp(&self) -> net::TcpListener {
+        let deadline = std::time::Instant::now() + self.bind_retry;
+
+        loop {
+            match net::TcpListener::bind((self.listen_address.as_str(), self.port)) {
+                Ok(tcp) => return tcp,
+                Err(err)
+                    if err.kind() == std::io::ErrorKind::AddrInUse
+                        && std::time::Instant::now() < deadline =>
+                {
+                    thread::sleep(Duration::from_millis(50));
+                }
+                Err(err) => panic!(
+                    "test server could not bind to {}:{}: {err}",
+                    self.listen_address, self.port
+                ),
+            }
+        }
+    }
+}
+
+/// Where a test server runs.
+enum Backend {
+    /// Server running on its own system, in a separate thread.
+    Thread {
+        server: ServerHandle,
+        system: rt::System,
+        thread_stop_rx: mpsc::Receiver<()>,
+    },
+
+    /// Server running on the test's runtime, when time is paused or the thread is shared.
+    Local(paused::LocalServer),
 }
 
 /// A basic HTTP server controller that simplifies the process of writing integration tests for
@@ -647,12 +1262,16 @@ impl TestServerConfig {
 ///
 /// See [`start`] for usage example.
 pub struct TestServer {
-    server: ServerHandle,
-    thread_stop_rx: mpsc::Receiver<()>,
+    backend: Backend,
     client: awc::Client,
-    system: rt::System,
     addr: net::SocketAddr,
     tls: bool,
+    #[cfg(unix)]
+    uds: Option<std::path::PathBuf>,
+    counters: ConnectionCounters,
+    jar: Option<cookies::SharedJar>,
+    #[cfg(feature = "http3")]
+    h3: bool,
 }
 
 impl TestServer {
@@ -661,6 +1280,14 @@ impl TestServer {
         self.addr
     }
 
+    /// Returns the path of the Unix domain socket the server is bound to, if any.
+    ///
+    /// See [`TestServerConfig::uds()`].
+    #[cfg(unix)]
+    pub fn uds_path(&self) -> Option<&std::path::Path> {
+        self.uds.as_deref()
+    }
+
     /// Construct test server url
     pub fn url(&self, uri: &str) -> String {
         let scheme = if self.tls { "https" } else { "http" };
@@ -672,6 +1299,19 @@ impl TestServer {
         }
     }
 
+    /// Constructs a URL for `host`, without a port, on the test server.
+    ///
+    /// The host must be mapped to the server with [`TestServerConfig::map_host()`].
+    pub fn url_for_host(&self, host: &str, uri: &str) -> String {
+        let scheme = if self.tls { "https" } else { "http" };
+
+        if uri.starts_with('/') {
+            format!("{}://{}{}", scheme, host, uri)
+        } else {
+            format!("{}://{}/{}", scheme, host, uri)
+        }
+    }
+
     /// Create `GET` request.
     pub fn get(&self, path: impl AsRef<str>) -> ClientRequest {
         self.client.get(self.url(path.as_ref()).as_str())
@@ -712,6 +1352,36 @@ impl TestServer {
         self.client.request(method, path.as_ref())
     }
 
+    /// Creates a request to the server's HTTP/3 listener with given method and path.
+    ///
+    /// # Panics
+    /// Panics if the server was not configured with an [HTTP/3
+    /// listener](TestServerConfig::h3_listener()).
+    #[cfg(feature = "http3")]
+    pub fn h3_request(&self, method: Method, path: impl AsRef<str>) -> H3ClientRequest {
+        assert!(
+            self.h3,
+            "HTTP/3 requests require a test server configured with `h3_listener()`"
+        );
+
+        let path = path.as_ref();
+        let url = if path.starts_with('/') {
+            format!("https://{}{}", self.addr, path)
+        } else {
+            format!("https://{}/{}", self.addr, path)
+        };
+
+        H3ClientRequest::new(self.addr, method, url)
+    }
+
+    /// Creates a `GET` request to the server's HTTP/3 listener.
+    ///
+    /// See [`h3_request()`](Self::h3_request()).
+    #[cfg(feature = "http3")]
+    pub fn h3_get(&self, path: impl AsRef<str>) -> H3ClientRequest {
+        self.h3_request(Method::GET, path)
+    }
+
     pub async fn load_body<S>(
         &mut self,
         mut response: ClientResponse<S>,
@@ -726,7 +1396,8 @@ impl TestServer {
     pub async fn ws_at(
         &mut self,
         path: &str,
-    ) -> Result<Framed<impl AsyncRead + AsyncWrite, ws::Codec>, awc::error::WsClientError> {
+    ) -> Result<Framed<impl AsyncRead + AsyncWrite, actix_http::ws::Codec>, awc::error::WsClientError>
+    {
         let url = self.url(path);
         let connect = self.client.ws(url).connect();
         connect.await.map(|(_, framed)| framed)
@@ -735,10 +1406,46 @@ impl TestServer {
     /// Connect to a WebSocket server.
     pub async fn ws(
         &mut self,
-    ) -> Result<Framed<impl AsyncRead + AsyncWrite, ws::Codec>, awc::error::WsClientError> {
+    ) -> Result<Framed<impl AsyncRead + AsyncWrite, actix_http::ws::Codec>, awc::error::WsClientError>
+    {
         self.ws_at("/").await
     }
 
+    /// Connect to WebSocket server at a given path, returning a session with frame assertion
+    /// helpers.
+    ///
+    /// See [`WsSession`] for usage example.
+    pub async fn ws_session_at(
+        &mut self,
+        path: &str,
+    ) -> Result<WsSession, awc::error::WsClientError> {
+        let url = self.url(path);
+        let (_, framed) = self.client.ws(url).connect().await?;
+        Ok(WsSession::new(framed))
+    }
+
+    /// Connect to a WebSocket server, returning a session with frame assertion helpers.
+    ///
+    /// See [`WsSession`] for usage example.
+    pub async fn ws_session(&mut self) -> Result<WsSession, awc::error::WsClientError> {
+        self.ws_session_at("/").await
+    }
+
+    /// Returns the cookie named `name` from the client's cookie store.
+    ///
+    /// Returns `None` if the cookie is not stored or the [cookie
+    /// store](TestServerConfig::cookie_store()) is not enabled.
+    pub fn cookie(&self, name: &str) -> Option<Cookie<'static>> {
+        self.jar.as_ref()?.borrow().get(name).cloned()
+    }
+
+    /// Removes all cookies from the client's cookie store.
+    pub fn clear_cookies(&self) {
+        if let Some(jar) = &self.jar {
+            *jar.borrow_mut() = CookieJar::new();
+        }
+    }
+
     /// Get default HeaderMap of Client.
     ///
     /// Returns Some(&mut HeaderMap) when Client object is unique
@@ -747,19 +1454,85 @@ impl TestServer {
         self.client.headers()
     }
 
+    /// Returns the number of connections accepted by the server so far.
+    pub fn accepted_connections(&self) -> usize {
+        self.counters.accepted.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of connections currently open, including those with in-flight requests.
+    pub fn active_connections(&self) -> usize {
+        self.counters.active.load(Ordering::SeqCst)
+    }
+
+    /// Gracefully stops the HTTP server.
+    ///
+    /// The server stops accepting new connections and waits for open connections to close, or
+    /// until the [shutdown timeout](TestServerConfig::shutdown_timeout()) passes and remaining
+    /// connections are aborted. Requests sent before calling this method can be awaited
+    /// concurrently to assert whether they complete.
+    ///
+    /// Unlike [`stop()`](Self::stop()), the server is not consumed so that its connection counters
+    /// can be inspected afterwards.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use actix_web::{rt, web, App};
+    ///
+    /// #[actix_web::test]
+    /// async fn test_graceful_shutdown() {
+    ///     let srv = actix_test::start(|| {
+    ///         App::new().route(
+    ///             "/",
+    ///             web::get().to(|| async {
+    ///                 rt::time::sleep(Duration::from_millis(100)).await;
+    ///                 "done"
+    ///             }),
+    ///         )
+    ///     });
+    ///
+    ///     let in_flight = rt::spawn(srv.get("/").send());
+    ///     rt::time::sleep(Duration::from_millis(20)).await;
+    ///     assert_eq!(srv.active_connections(), 1);
+    ///
+    ///     srv.stop_gracefully().await;
+    ///
+    ///     assert!(in_flight.await.unwrap().unwrap().status().is_success());
+    ///     assert_eq!(srv.active_connections(), 0);
+    /// }
+    /// ```
+    pub async fn stop_gracefully(&self) {
+        match &self.backend {
+            Backend::Thread { server, .. } => server.stop(true).await,
+            Backend::Local(server) => server.stop(true).await,
+        }
+    }
+
     /// Stop HTTP server.
     ///
     /// Waits for spawned `Server` and `System` to shutdown (force) shutdown.
     pub async fn stop(mut self) {
-        // signal server to stop
-        self.server.stop(false).await;
-
-        // also signal system to stop
-        // though this is handled by `ServerBuilder::exit_system` too
-        self.system.stop();
+        match &mut self.backend {
+            Backend::Thread {
+                server,
+                system,
+                thread_stop_rx,
+            } => {
+                // signal server to stop
+                server.stop(false).await;
+
+                // also signal system to stop
+                // though this is handled by `ServerBuilder::exit_system` too
+                system.stop();
+
+                // wait for thread to be stopped but don't care about result
+                let _ = thread_stop_rx.recv().await;
+            }
 
-        // wait for thread to be stopped but don't care about result
-        let _ = self.thread_stop_rx.recv().await;
+            // the test's own system must keep running
+            Backend::Local(server) => server.stop(false).await,
+        }
     }
 }
 
@@ -768,11 +1541,18 @@ impl Drop for TestServer {
         // calls in this Drop impl should be enough to shut down the server, system, and thread
         // without needing to await anything
 
-        // signal server to stop
-        #[allow(clippy::let_underscore_future)]
-        let _ = self.server.stop(true);
+        if let Backend::Thread { server, system, .. } = &self.backend {
+            // signal server to stop
+            #[allow(clippy::let_underscore_future)]
+            let _ = server.stop(true);
 
-        // signal system to stop
-        self.system.stop();
+            // signal system to stop
+            system.stop();
+        }
+
+        #[cfg(unix)]
+        if let Some(path) = &self.uds {
+            let _ = std::fs::remove_file(path);
+        }
     }
 }
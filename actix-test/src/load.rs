@@ -0,0 +1,257 @@
+//! Concurrent load harness for services and test servers.
+
+use std::{
+    collections::BTreeMap, error::Error as StdError, fmt, future::Future, pin::Pin, time::Duration,
+};
+
+use actix_http::{Request, StatusCode};
+use actix_web::{
+    body::MessageBody,
+    dev::{Service, ServiceResponse},
+    rt::time::Instant,
+    Error,
+};
+use awc::ClientRequest;
+use futures_util::{stream, StreamExt as _};
+
+use crate::TestServer;
+
+/// Body size limit when reading client responses.
+const BODY_LIMIT: usize = 10_485_760;
+
+type CallFuture<'a> = Pin<Box<dyn Future<Output = Result<StatusCode, String>> + 'a>>;
+
+/// A service or server that requests can be sent to concurrently.
+///
+/// Implemented for services returned by [`init_service()`](crate::init_service()), which take
+/// [`Request`]s, and for [`TestServer`], which takes [`ClientRequest`]s.
+pub trait LoadTarget: sealed::Sealed {
+    /// Type of requests sent to the target.
+    type Request;
+
+    #[doc(hidden)]
+    fn call_one(&self, req: Self::Request) -> CallFuture<'_>;
+}
+
+impl<S, B> LoadTarget for S
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody + 'static,
+{
+    type Request = Request;
+
+    fn call_one(&self, req: Request) -> CallFuture<'_> {
+        Box::pin(async move {
+            let res = self.call(req).await.map_err(|err| err.to_string())?;
+            let status = res.status();
+
+            actix_http::body::to_bytes(res.into_body())
+                .await
+                .map_err(|err| Into::<Box<dyn StdError>>::into(err).to_string())?;
+
+            Ok(status)
+        })
+    }
+}
+
+impl LoadTarget for TestServer {
+    type Request = ClientRequest;
+
+    fn call_one(&self, req: ClientRequest) -> CallFuture<'_> {
+        Box::pin(async move {
+            let mut res = req.send().await.map_err(|err| err.to_string())?;
+            let status = res.status();
+
+            res.body()
+                .limit(BODY_LIMIT)
+                .await
+                .map_err(|err| err.to_string())?;
+
+            Ok(status)
+        })
+    }
+}
+
+mod sealed {
+    use actix_http::Request;
+    use actix_web::dev::{Service, ServiceResponse};
+
+    pub trait Sealed {}
+
+    impl<S, B> Sealed for S where S: Service<Request, Response = ServiceResponse<B>> {}
+    impl Sealed for crate::TestServer {}
+}
+
+/// Sends `requests` to a service or test server, with up to `concurrency` requests in flight at a
+/// time, and reports the distribution of response statuses and latencies.
+///
+/// Each request's latency covers sending it and reading its response body to completion. Requests
+/// that fail to produce a response are counted as errors.
+///
+/// Useful as a quick regression check for contention bugs, such as deadlocks or lost updates in
+/// shared state, that only show up when requests overlap.
+///
+/// # Panics
+/// Panics if `concurrency` is 0.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::StatusCode, test, web, App};
+///
+/// #[actix_web::test]
+/// async fn test_concurrent_requests() {
+///     let app = test::init_service(App::new().route("/", web::get().to(|| async { "hello" }))).await;
+///
+///     let requests = (0..100).map(|_| test::TestRequest::get().to_request());
+///     let report = actix_test::call_concurrently(&app, requests, 10).await;
+///
+///     assert_eq!(report.requests(), 100);
+///     assert_eq!(report.status_count(StatusCode::OK), 100);
+///     assert!(report.errors().is_empty());
+/// }
+/// ```
+pub async fn call_concurrently<T, I>(target: &T, requests: I, concurrency: usize) -> LoadReport
+where
+    T: LoadTarget,
+    I: IntoIterator<Item = T::Request>,
+{
+    assert!(concurrency > 0, "concurrency must be at least 1");
+
+    let start = Instant::now();
+
+    let mut results = stream::iter(requests)
+        .map(|req| async move {
+            let start = Instant::now();
+            let result = target.call_one(req).await;
+            (start.elapsed(), result)
+        })
+        .buffer_unordered(concurrency);
+
+    let mut report = LoadReport {
+        concurrency,
+        elapsed: Duration::ZERO,
+        latencies: Vec::new(),
+        statuses: BTreeMap::new(),
+        errors: Vec::new(),
+    };
+
+    while let Some((latency, result)) = results.next().await {
+        report.latencies.push(latency);
+
+        match result {
+            Ok(status) => *report.statuses.entry(status).or_default() += 1,
+            Err(err) => report.errors.push(err),
+        }
+    }
+
+    report.elapsed = start.elapsed();
+    report.latencies.sort_unstable();
+
+    report
+}
+
+/// Statuses and latencies of requests sent by [`call_concurrently()`].
+///
+/// The `Display` implementation renders a short human-readable summary.
+#[derive(Debug, Clone)]
+pub struct LoadReport {
+    concurrency: usize,
+    elapsed: Duration,
+    latencies: Vec<Duration>,
+    statuses: BTreeMap<StatusCode, usize>,
+    errors: Vec<String>,
+}
+
+impl LoadReport {
+    /// Returns the number of requests sent, including those that failed.
+    pub fn requests(&self) -> usize {
+        self.latencies.len()
+    }
+
+    /// Returns the time taken to complete all requests.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the number of responses received for each status code.
+    pub fn statuses(&self) -> &BTreeMap<StatusCode, usize> {
+        &self.statuses
+    }
+
+    /// Returns the number of responses received with status code `status`.
+    pub fn status_count(&self, status: StatusCode) -> usize {
+        self.statuses.get(&status).copied().unwrap_or(0)
+    }
+
+    /// Returns descriptions of the errors of requests that did not produce a response.
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// Returns the latency below which `percentile` percent of requests completed.
+    ///
+    /// Returns `None` if no requests were sent.
+    ///
+    /// # Panics
+    /// Panics if `percentile` is not in the range `0.0..=100.0`.
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        assert!(
+            (0.0..=100.0).contains(&percentile),
+            "latency percentile must be in the range 0.0..=100.0"
+        );
+
+        // nearest-rank method
+        let rank = (percentile / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        self.latencies.get(rank.saturating_sub(1)).copied()
+    }
+
+    /// Returns the shortest request latency, or `None` if no requests were sent.
+    pub fn min_latency(&self) -> Option<Duration> {
+        self.latencies.first().copied()
+    }
+
+    /// Returns the longest request latency, or `None` if no requests were sent.
+    pub fn max_latency(&self) -> Option<Duration> {
+        self.latencies.last().copied()
+    }
+
+    /// Returns the mean request latency, or `None` if no requests were sent.
+    pub fn mean_latency(&self) -> Option<Duration> {
+        let count = u32::try_from(self.latencies.len())
+            .ok()
+            .filter(|&n| n > 0)?;
+        Some(self.latencies.iter().sum::<Duration>() / count)
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} requests in {:?} (concurrency {})",
+            self.requests(),
+            self.elapsed,
+            self.concurrency
+        )?;
+
+        let statuses = self
+            .statuses
+            .iter()
+            .map(|(status, count)| format!("{status}: {count}"))
+            .collect::<Vec<_>>();
+
+        writeln!(f, "statuses: {}", statuses.join(", "))?;
+        writeln!(f, "errors: {}", self.errors.len())?;
+
+        match (self.min_latency(), self.max_latency()) {
+            (Some(min), Some(max)) => write!(
+                f,
+                "latency: min {min:?}, p50 {:?}, p90 {:?}, p99 {:?}, max {max:?}",
+                self.latency_percentile(50.0).unwrap(),
+                self.latency_percentile(90.0).unwrap(),
+                self.latency_percentile(99.0).unwrap(),
+            ),
+            _ => write!(f, "latency: n/a"),
+        }
+    }
+}
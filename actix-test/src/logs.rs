@@ -0,0 +1,195 @@
+//! Capture of log records emitted during a test.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex, Once, Weak},
+};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+type Records = Mutex<Vec<LogRecord>>;
+
+/// Buffers of all live captures.
+static CAPTURES: Mutex<Vec<Weak<Records>>> = Mutex::new(Vec::new());
+
+static INSTALL: Once = Once::new();
+
+/// Global logger that copies records into every live capture.
+struct CapturingLogger;
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        let mut captures = CAPTURES.lock().unwrap_or_else(|err| err.into_inner());
+
+        // drop buffers of finished captures
+        captures.retain(|capture| capture.strong_count() > 0);
+
+        if captures.is_empty() {
+            return;
+        }
+
+        let record = LogRecord {
+            level: record.level(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+        };
+
+        for records in captures.iter().filter_map(Weak::upgrade) {
+            lock(&records).push(record.clone());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Starts capturing log records.
+///
+/// Records are captured from all threads, including those of [`TestServer`](crate::TestServer)s,
+/// until the returned [`LogCapture`] is dropped. Events from the `tracing` crate are also captured
+/// if its `log` feature is enabled and no `tracing` subscriber is installed, which is the case for
+/// events emitted by Actix Web crates.
+///
+/// Since captures are not isolated from tests running in parallel, assertions should look for
+/// records that are specific to the test, e.g., by using a unique
+/// [log target](actix_web::middleware::Logger::log_target()).
+///
+/// # Panics
+/// Panics on first use if a different global logger has already been installed, such as by
+/// `env_logger::init()`.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::Logger, test, web, App, HttpResponse};
+///
+/// #[actix_web::test]
+/// async fn test_access_log() {
+///     let logs = actix_test::capture_logs();
+///
+///     let app = test::init_service(
+///         App::new()
+///             .wrap(Logger::new("%s %U").log_target("test_access_log"))
+///             .route("/", web::get().to(HttpResponse::InternalServerError)),
+///     )
+///     .await;
+///
+///     let res = test::call_service(&app, test::TestRequest::get().to_request()).await;
+///     drop(res);
+///
+///     logs.assert_logged(|record| {
+///         record.target() == "test_access_log" && record.message() == "500 /"
+///     });
+/// }
+/// ```
+pub fn capture_logs() -> LogCapture {
+    INSTALL.call_once(|| {
+        log::set_logger(&CapturingLogger)
+            .expect("log capture requires that no other global logger is installed");
+        log::set_max_level(LevelFilter::Trace);
+    });
+
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    CAPTURES
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .push(Arc::downgrade(&records));
+
+    LogCapture { records }
+}
+
+/// Log records captured since a call to [`capture_logs()`].
+///
+/// Capturing stops when this is dropped.
+pub struct LogCapture {
+    records: Arc<Records>,
+}
+
+impl LogCapture {
+    /// Returns the records captured so far, in the order they were logged.
+    pub fn records(&self) -> Vec<LogRecord> {
+        lock(&self.records).clone()
+    }
+
+    /// Returns true if any record captured so far satisfies `predicate`.
+    pub fn logged(&self, predicate: impl Fn(&LogRecord) -> bool) -> bool {
+        lock(&self.records).iter().any(predicate)
+    }
+
+    /// Returns true if any record captured so far has the given level and contains `text` in its
+    /// message.
+    pub fn contains(&self, level: Level, text: &str) -> bool {
+        self.logged(|record| record.level() == level && record.message().contains(text))
+    }
+
+    /// Asserts that a record satisfying `predicate` has been captured.
+    ///
+    /// # Panics
+    /// Panics, listing the captured records, if no record satisfies `predicate`.
+    #[track_caller]
+    pub fn assert_logged(&self, predicate: impl Fn(&LogRecord) -> bool) {
+        let records = lock(&self.records);
+
+        if !records.iter().any(predicate) {
+            let records = records
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            panic!("no matching log record was captured; captured records:\n{records}");
+        }
+    }
+
+    /// Discards the records captured so far.
+    pub fn clear(&self) {
+        lock(&self.records).clear();
+    }
+}
+
+impl fmt::Debug for LogCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogCapture")
+            .field("records", &*lock(&self.records))
+            .finish()
+    }
+}
+
+/// A captured log record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    level: Level,
+    target: String,
+    message: String,
+}
+
+impl LogRecord {
+    /// Returns the record's level.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Returns the record's target, usually the path of the module that emitted it.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the formatted message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{} {}] {}", self.level, self.target, self.message)
+    }
+}
+
+/// Locks a capture buffer, ignoring poisoning caused by panicking tests.
+fn lock(records: &Records) -> std::sync::MutexGuard<'_, Vec<LogRecord>> {
+    records.lock().unwrap_or_else(|err| err.into_inner())
+}
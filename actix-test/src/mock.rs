@@ -0,0 +1,393 @@
+//! Mock upstream HTTP server with request expectations.
+
+use std::{
+    fmt, net,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use actix_http::{
+    header::{HeaderName, HeaderValue, TryIntoHeaderPair},
+    Method, StatusCode,
+};
+use actix_web::{
+    http::header::ContentType,
+    web::{self, Bytes},
+    App, HttpRequest, HttpResponse,
+};
+
+use crate::TestServer;
+
+/// A mock upstream HTTP server that responds to expected requests with canned responses.
+///
+/// Useful for testing services that call other HTTP APIs. Point the service under test at
+/// [`url()`](Self::url()), declare the requests it should make using [`expect()`](Self::expect()),
+/// and the mock server will answer them.
+///
+/// Requests are matched against expectations in the order they were declared. Requests that
+/// match no expectation receive a `404 Not Found` response and are recorded as unexpected.
+///
+/// When the mock server is dropped, it panics unless all expectations were met and no unexpected
+/// requests were received. Use [`verify()`](Self::verify()) to check this earlier.
+///
+/// # Examples
+/// ```
+/// use actix_test::{Expectation, MockResponse, MockServer};
+/// use actix_web::http::{Method, StatusCode};
+///
+/// #[actix_web::test]
+/// async fn test_upstream() {
+///     let upstream = MockServer::start();
+///
+///     upstream.expect(
+///         Expectation::new(Method::GET, "/users/1")
+///             .header(("authorization", "Bearer token"))
+///             .times(1)
+///             .respond_with(MockResponse::new(StatusCode::OK).json(serde_json::json!({ "id": 1 }))),
+///     );
+///
+///     let res = awc::Client::new()
+///         .get(upstream.url("/users/1"))
+///         .insert_header(("authorization", "Bearer token"))
+///         .send()
+///         .await
+///         .unwrap();
+///
+///     assert_eq!(res.status(), StatusCode::OK);
+///
+///     // expectations are verified when `upstream` is dropped
+/// }
+/// ```
+pub struct MockServer {
+    srv: TestServer,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockServer {
+    /// Starts a mock server with no expectations.
+    pub fn start() -> Self {
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let srv = crate::start({
+            let state = Arc::clone(&state);
+
+            move || {
+                App::new()
+                    .app_data(web::Data::from(Arc::clone(&state)))
+                    .default_service(web::to(respond))
+            }
+        });
+
+        MockServer { srv, state }
+    }
+
+    /// Adds an expected request.
+    pub fn expect(&self, expectation: Expectation) -> &Self {
+        lock(&self.state).expectations.push((expectation, 0));
+        self
+    }
+
+    /// Returns the address of the mock server.
+    pub fn addr(&self) -> net::SocketAddr {
+        self.srv.addr()
+    }
+
+    /// Constructs a URL for `path` on the mock server.
+    pub fn url(&self, path: &str) -> String {
+        self.srv.url(path)
+    }
+
+    /// Returns number of requests received that matched an expectation, so far.
+    pub fn matched_requests(&self) -> usize {
+        lock(&self.state)
+            .expectations
+            .iter()
+            .map(|(_, hits)| hits)
+            .sum()
+    }
+
+    /// Checks that all expectations were met and that no unexpected requests were received.
+    ///
+    /// # Panics
+    /// Panics with a description of every failed expectation and unexpected request.
+    pub fn verify(&self) {
+        let state = lock(&self.state);
+
+        let mut failures = Vec::new();
+
+        for (expectation, hits) in &state.expectations {
+            let met = match expectation.times {
+                Some(times) => *hits == times,
+                None => *hits > 0,
+            };
+
+            if !met {
+                let expected = match expectation.times {
+                    Some(times) => format!("exactly {times}"),
+                    None => "at least 1".to_owned(),
+                };
+
+                failures.push(format!(
+                    "expected {expected} request(s) matching {expectation}, received {hits}"
+                ));
+            }
+        }
+
+        for request in &state.unexpected {
+            failures.push(format!("received unexpected request {request}"));
+        }
+
+        if !failures.is_empty() {
+            panic!(
+                "mock server expectations failed:\n- {}",
+                failures.join("\n- ")
+            );
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        // avoid a double panic, which would abort and hide the original failure
+        if !std::thread::panicking() {
+            self.verify();
+        }
+    }
+}
+
+/// An expected request and the response to send when it is received.
+///
+/// Requests must match the method and path, plus any query parameters, headers, and body that are
+/// specified. Unless a count is set with [`times()`](Self::times()), the expectation is met once it
+/// has been matched at least once.
+#[derive(Debug, Clone)]
+pub struct Expectation {
+    method: Method,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: Option<Bytes>,
+    json: Option<serde_json::Value>,
+    times: Option<usize>,
+    response: MockResponse,
+}
+
+impl Expectation {
+    /// Constructs an expectation of a request with the given method and path.
+    ///
+    /// The response defaults to an empty `200 OK`.
+    pub fn new(method: Method, path: impl Into<String>) -> Self {
+        Expectation {
+            method,
+            path: path.into(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+            json: None,
+            times: None,
+            response: MockResponse::new(StatusCode::OK),
+        }
+    }
+
+    /// Requires the query parameter `name` with the given value.
+    pub fn query(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((name.into(), value.into()));
+        self
+    }
+
+    /// Requires the given header to be present with the given value.
+    ///
+    /// # Panics
+    /// Panics if the header name or value is invalid.
+    pub fn header(mut self, header: impl TryIntoHeaderPair) -> Self {
+        let (name, value) = header
+            .try_into_pair()
+            .unwrap_or_else(|_| panic!("invalid header in mock expectation"));
+
+        self.headers.push((name, value));
+        self
+    }
+
+    /// Requires the request body to be exactly `body`.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Requires the request body to be JSON equal to `json`, ignoring formatting and key order.
+    pub fn json(mut self, json: serde_json::Value) -> Self {
+        self.json = Some(json);
+        self
+    }
+
+    /// Requires that exactly `times` matching requests are received.
+    pub fn times(mut self, times: usize) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    /// Sets the response to send to matching requests.
+    pub fn respond_with(mut self, response: MockResponse) -> Self {
+        self.response = response;
+        self
+    }
+
+    fn matches(&self, req: &HttpRequest, body: &Bytes) -> bool {
+        if req.method() != self.method || req.path() != self.path {
+            return false;
+        }
+
+        if !self.query.is_empty() {
+            let query = serde_urlencoded::from_str::<Vec<(String, String)>>(req.query_string())
+                .unwrap_or_default();
+
+            if !self.query.iter().all(|param| query.contains(param)) {
+                return false;
+            }
+        }
+
+        let headers_match = self
+            .headers
+            .iter()
+            .all(|(name, value)| req.headers().get_all(name).any(|val| val == value));
+
+        if !headers_match {
+            return false;
+        }
+
+        if let Some(expected) = &self.body {
+            if expected != body {
+                return false;
+            }
+        }
+
+        if let Some(expected) = &self.json {
+            match serde_json::from_slice::<serde_json::Value>(body) {
+                Ok(json) if &json == expected => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+impl fmt::Display for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.path)?;
+
+        for (name, value) in &self.query {
+            write!(f, " [query {name}={value}]")?;
+        }
+
+        for (name, value) in &self.headers {
+            write!(f, " [header {name}: {value:?}]")?;
+        }
+
+        if let Some(body) = &self.body {
+            write!(f, " [body {body:?}]")?;
+        }
+
+        if let Some(json) = &self.json {
+            write!(f, " [json {json}]")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A canned response sent by a [`MockServer`].
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: StatusCode,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: Bytes,
+}
+
+impl MockResponse {
+    /// Constructs an empty response with the given status code.
+    pub fn new(status: StatusCode) -> Self {
+        MockResponse {
+            status,
+            headers: Vec::new(),
+            body: Bytes::new(),
+        }
+    }
+
+    /// Appends a header to the response.
+    ///
+    /// # Panics
+    /// Panics if the header name or value is invalid.
+    pub fn header(mut self, header: impl TryIntoHeaderPair) -> Self {
+        let (name, value) = header
+            .try_into_pair()
+            .unwrap_or_else(|_| panic!("invalid header in mock response"));
+
+        self.headers.push((name, value));
+        self
+    }
+
+    /// Sets the response body.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Sets a JSON response body and the `Content-Type: application/json` header.
+    pub fn json(self, json: serde_json::Value) -> Self {
+        let body = serde_json::to_vec(&json).unwrap();
+
+        self.header(ContentType::json()).body(body)
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    /// Expectations with the number of requests that matched them.
+    expectations: Vec<(Expectation, usize)>,
+
+    /// Descriptions of requests that matched no expectation.
+    unexpected: Vec<String>,
+}
+
+/// Locks mock state, ignoring poisoning caused by panicking tests.
+fn lock(state: &Mutex<MockState>) -> MutexGuard<'_, MockState> {
+    state.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+async fn respond(
+    req: HttpRequest,
+    body: Bytes,
+    state: web::Data<Mutex<MockState>>,
+) -> HttpResponse {
+    let mut state = lock(&state);
+
+    let matched = state
+        .expectations
+        .iter_mut()
+        .find(|(expectation, _)| expectation.matches(&req, &body));
+
+    match matched {
+        Some((expectation, hits)) => {
+            *hits += 1;
+
+            let mut res = HttpResponse::build(expectation.response.status);
+
+            for header in &expectation.response.headers {
+                res.append_header(header.clone());
+            }
+
+            res.body(expectation.response.body.clone())
+        }
+
+        None => {
+            let request = match req.query_string() {
+                "" => format!("{} {}", req.method(), req.path()),
+                query => format!("{} {}?{query}", req.method(), req.path()),
+            };
+
+            state.unexpected.push(request.clone());
+
+            HttpResponse::NotFound().body(format!("no mock expectation matches {request}"))
+        }
+    }
+}
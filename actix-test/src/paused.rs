@@ -0,0 +1,156 @@
+//! Test server that runs on the test's own runtime, for use with paused time or a shared thread.
+
+use std::{cell::RefCell, fmt, mem, net, rc::Rc, time::Duration};
+
+use actix_http::{HttpService, Request, Response};
+use actix_rt::net::{TcpListener, TcpStream};
+use actix_service::{map_config, IntoServiceFactory, ServiceFactory, ServiceFactoryExt as _};
+use actix_web::{
+    body::MessageBody,
+    dev::{AppConfig, Service},
+    rt, Error,
+};
+use tokio::task::JoinHandle;
+
+use crate::{ConnectionCounters, HttpVer, TestServerConfig};
+
+/// Tasks driving the server's open connections.
+type Connections = Rc<RefCell<Vec<JoinHandle<()>>>>;
+
+/// A test server whose accept loop and connections are tasks on the current runtime.
+///
+/// Unlike servers started on their own thread, timers used by the server are affected by pausing
+/// the test's clock.
+pub(crate) struct LocalServer {
+    acceptor: JoinHandle<()>,
+    connections: Connections,
+    shutdown_timeout: Duration,
+}
+
+impl LocalServer {
+    /// Binds the server and spawns its accept loop on the current runtime.
+    pub(crate) fn start<F, I, S, B>(
+        cfg: &TestServerConfig,
+        factory: F,
+        counters: &ConnectionCounters,
+    ) -> (Self, net::SocketAddr)
+    where
+        F: Fn() -> I,
+        I: IntoServiceFactory<S, Request>,
+        S: ServiceFactory<Request, Config = AppConfig> + 'static,
+        S::Error: Into<Error> + 'static,
+        S::InitError: fmt::Debug,
+        S::Response: Into<Response<B>> + 'static,
+        <S::Service as Service<Request>>::Future: 'static,
+        B: MessageBody + 'static,
+    {
+        let tcp = cfg.bind_tcp();
+        let local_addr = tcp.local_addr().unwrap();
+        tcp.set_nonblocking(true).unwrap();
+        let listener = TcpListener::from_std(tcp).unwrap();
+
+        let app_cfg = AppConfig::__priv_test_new(false, local_addr.to_string(), local_addr);
+
+        let fac = factory()
+            .into_factory()
+            .map_err(|err| err.into().error_response());
+        let fac = map_config(fac, move |_| app_cfg.clone());
+
+        let builder = HttpService::build()
+            .client_request_timeout(cfg.client_request_timeout)
+            .on_connect_ext(counters.on_connect());
+
+        let connections = Connections::default();
+
+        let acceptor = match cfg.tp {
+            HttpVer::Http1 => rt::spawn(accept(
+                listener,
+                builder.h1(fac).tcp(),
+                Rc::clone(&connections),
+            )),
+            HttpVer::Http2 => rt::spawn(accept(
+                listener,
+                builder.h2(fac).tcp(),
+                Rc::clone(&connections),
+            )),
+            HttpVer::Both => rt::spawn(accept(
+                listener,
+                builder.finish(fac).tcp(),
+                Rc::clone(&connections),
+            )),
+        };
+
+        let srv = LocalServer {
+            acceptor,
+            connections,
+            shutdown_timeout: cfg.shutdown_timeout.unwrap_or(Duration::from_secs(30)),
+        };
+
+        (srv, local_addr)
+    }
+
+    /// Stops accepting connections and, if `graceful`, waits for open connections to close until
+    /// the shutdown timeout passes. Remaining connections are aborted.
+    pub(crate) async fn stop(&self, graceful: bool) {
+        self.acceptor.abort();
+
+        let mut connections = mem::take(&mut *self.connections.borrow_mut());
+
+        if graceful {
+            let _ = rt::time::timeout(self.shutdown_timeout, async {
+                for conn in &mut connections {
+                    let _ = conn.await;
+                }
+            })
+            .await;
+        }
+
+        for conn in connections {
+            conn.abort();
+        }
+    }
+}
+
+impl Drop for LocalServer {
+    fn drop(&mut self) {
+        self.acceptor.abort();
+
+        for conn in self.connections.borrow().iter() {
+            conn.abort();
+        }
+    }
+}
+
+/// Accepts connections and spawns a task serving each of them.
+async fn accept<T>(listener: TcpListener, factory: T, connections: Connections)
+where
+    T: ServiceFactory<TcpStream, Config = ()> + 'static,
+    T::Service: 'static,
+    T::InitError: fmt::Debug,
+{
+    let service = Rc::new(
+        factory
+            .new_service(())
+            .await
+            .expect("test server could not be created"),
+    );
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                log::error!("test server could not accept connection: {err}");
+                continue;
+            }
+        };
+
+        let service = Rc::clone(&service);
+        let conn = rt::spawn(async move {
+            let _ = service.call(stream).await;
+        });
+
+        let mut connections = connections.borrow_mut();
+        connections.retain(|conn| !conn.is_finished());
+        connections.push(conn);
+    }
+}
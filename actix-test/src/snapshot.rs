@@ -0,0 +1,303 @@
+//! Canonical text snapshots of responses, for use with snapshot assertion libraries.
+
+use std::{error::Error as StdError, fmt::Write as _, future::Future, pin::Pin, rc::Rc};
+
+use actix_http::{
+    header::{HeaderMap, HeaderName},
+    StatusCode,
+};
+use actix_web::{body::MessageBody, dev::ServiceResponse, web::Bytes};
+use awc::{error::PayloadError, ClientResponse};
+use futures_core::Stream;
+
+/// Placeholder for redacted header values and JSON fields.
+const REDACTED: &str = "[redacted]";
+
+/// Body size limit when reading client responses.
+const BODY_LIMIT: usize = 10_485_760;
+
+type BodyHook = Rc<dyn Fn(String) -> String>;
+
+/// A response that can be snapshotted.
+///
+/// Implemented for [`ServiceResponse`]s returned by services under test and [`ClientResponse`]s
+/// returned by a [`TestServer`](crate::TestServer)'s client.
+pub trait SnapshotResponse: sealed::Sealed {
+    #[doc(hidden)]
+    fn into_snapshot_parts(self) -> Pin<Box<dyn Future<Output = (StatusCode, HeaderMap, Bytes)>>>;
+}
+
+impl<B> SnapshotResponse for ServiceResponse<B>
+where
+    B: MessageBody + 'static,
+{
+    fn into_snapshot_parts(self) -> Pin<Box<dyn Future<Output = (StatusCode, HeaderMap, Bytes)>>> {
+        Box::pin(async move {
+            let status = self.status();
+            let headers = self.headers().clone();
+            let body = actix_http::body::to_bytes(self.into_body())
+                .await
+                .map_err(Into::<Box<dyn StdError>>::into)
+                .expect("error reading test response body");
+
+            (status, headers, body)
+        })
+    }
+}
+
+impl<S> SnapshotResponse for ClientResponse<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>> + Unpin + 'static,
+{
+    fn into_snapshot_parts(
+        mut self,
+    ) -> Pin<Box<dyn Future<Output = (StatusCode, HeaderMap, Bytes)>>> {
+        Box::pin(async move {
+            let status = self.status();
+            let headers = self.headers().clone();
+            let body = self
+                .body()
+                .limit(BODY_LIMIT)
+                .await
+                .expect("error reading test response body");
+
+            (status, headers, body)
+        })
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl<B> Sealed for actix_web::dev::ServiceResponse<B> {}
+    impl<S> Sealed for awc::ClientResponse<S> {}
+}
+
+/// Takes a snapshot of a response using the default [`SnapshotConfig`].
+///
+/// See [`SnapshotConfig`] for the snapshot format and options for redacting volatile data.
+///
+/// # Examples
+/// ```
+/// use actix_web::{test, web, App, HttpResponse};
+///
+/// #[actix_web::test]
+/// async fn test_snapshot() {
+///     let app = test::init_service(App::new().route(
+///         "/",
+///         web::get().to(|| async { HttpResponse::Ok().json(serde_json::json!({ "b": 2, "a": 1 })) }),
+///     ))
+///     .await;
+///
+///     let res = test::call_service(&app, test::TestRequest::get().to_request()).await;
+///
+///     assert_eq!(
+///         actix_test::snapshot(res).await,
+///         "200 OK\ncontent-type: application/json\n\n{\n  \"a\": 1,\n  \"b\": 2\n}\n",
+///     );
+/// }
+/// ```
+pub async fn snapshot(res: impl SnapshotResponse) -> String {
+    SnapshotConfig::new().snapshot(res).await
+}
+
+/// Configures how responses are rendered into snapshots.
+///
+/// A snapshot is a canonical, human-readable rendering of a response, made up of:
+/// - the status line, e.g. `200 OK`;
+/// - the selected headers, sorted by name, one line per value;
+/// - a blank line;
+/// - the body.
+///
+/// JSON bodies are pretty-printed with object keys sorted, so that snapshots don't depend on
+/// serialization order. Other UTF-8 bodies are included verbatim and binary bodies are summarized
+/// by their length. Only the `Content-Type` header is included by default.
+///
+/// Volatile data, such as timestamps and generated IDs, can be redacted from headers with
+/// [`redact_header()`](Self::redact_header()), from JSON bodies with
+/// [`redact_json()`](Self::redact_json()), and from any body with [`redact()`](Self::redact()).
+///
+/// # Examples
+/// ```
+/// use actix_test::SnapshotConfig;
+/// use actix_web::{http::header, test, web, App, HttpResponse};
+///
+/// #[actix_web::test]
+/// async fn test_snapshot() {
+///     let app = test::init_service(App::new().route(
+///         "/",
+///         web::post().to(|| async {
+///             HttpResponse::Created()
+///                 .insert_header((header::LOCATION, "/items/1"))
+///                 .insert_header((header::DATE, "Tue, 15 Nov 1994 08:12:31 GMT"))
+///                 .json(serde_json::json!({ "id": 1, "created_at": 784887151 }))
+///         }),
+///     ))
+///     .await;
+///
+///     let res = test::call_service(&app, test::TestRequest::post().to_request()).await;
+///
+///     let snapshot = SnapshotConfig::new()
+///         .header(header::LOCATION)
+///         .redact_header(header::DATE)
+///         .redact_json("/created_at")
+///         .snapshot(res)
+///         .await;
+///
+///     assert_eq!(
+///         snapshot,
+///         "201 Created\n\
+///          content-type: application/json\n\
+///          date: [redacted]\n\
+///          location: /items/1\n\
+///          \n\
+///          {\n  \"created_at\": \"[redacted]\",\n  \"id\": 1\n}\n",
+///     );
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SnapshotConfig {
+    headers: Vec<HeaderName>,
+    redacted_headers: Vec<HeaderName>,
+    redacted_json: Vec<String>,
+    hooks: Vec<BodyHook>,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        SnapshotConfig::new()
+    }
+}
+
+impl SnapshotConfig {
+    /// Constructs default snapshot configuration.
+    pub fn new() -> Self {
+        SnapshotConfig {
+            headers: vec![actix_http::header::CONTENT_TYPE],
+            redacted_headers: Vec::new(),
+            redacted_json: Vec::new(),
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Includes header `name` in snapshots.
+    pub fn header(mut self, name: HeaderName) -> Self {
+        if !self.headers.contains(&name) {
+            self.headers.push(name);
+        }
+        self
+    }
+
+    /// Includes header `name` in snapshots with its values replaced by `[redacted]`.
+    pub fn redact_header(mut self, name: HeaderName) -> Self {
+        self.redacted_headers.push(name.clone());
+        self.header(name)
+    }
+
+    /// Replaces the JSON body value at `pointer` with `"[redacted]"`, if present.
+    ///
+    /// The pointer uses [JSON Pointer] syntax, e.g. `/user/id` or `/items/0/created_at`.
+    ///
+    /// [JSON Pointer]: https://datatracker.ietf.org/doc/html/rfc6901
+    pub fn redact_json(mut self, pointer: impl Into<String>) -> Self {
+        self.redacted_json.push(pointer.into());
+        self
+    }
+
+    /// Adds a hook that rewrites the rendered body, after JSON redaction and formatting.
+    ///
+    /// Hooks run in the order they were added and are not run for binary bodies.
+    pub fn redact<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(String) -> String + 'static,
+    {
+        self.hooks.push(Rc::new(hook));
+        self
+    }
+
+    /// Takes a snapshot of `res`, reading its body to completion.
+    ///
+    /// # Panics
+    /// Panics if reading the response body fails.
+    pub async fn snapshot(&self, res: impl SnapshotResponse) -> String {
+        let (status, headers, body) = res.into_snapshot_parts().await;
+
+        let mut snapshot = String::new();
+
+        let reason = status.canonical_reason().unwrap_or("<unknown status code>");
+        writeln!(snapshot, "{} {reason}", status.as_u16()).unwrap();
+
+        let mut names = self.headers.clone();
+        names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        for name in &names {
+            let redacted = self.redacted_headers.contains(name);
+
+            for value in headers.get_all(name) {
+                let value = if redacted {
+                    REDACTED
+                } else {
+                    value.to_str().unwrap_or("[non-visible ASCII]")
+                };
+
+                writeln!(snapshot, "{name}: {value}").unwrap();
+            }
+        }
+
+        snapshot.push('\n');
+
+        let body = self.render_body(&body);
+        snapshot.push_str(&body);
+
+        if !body.is_empty() && !body.ends_with('\n') {
+            snapshot.push('\n');
+        }
+
+        snapshot
+    }
+
+    /// Renders the body as redacted text, or a summary of its length if it is binary.
+    fn render_body(&self, body: &Bytes) -> String {
+        let text = match serde_json::from_slice::<serde_json::Value>(body) {
+            Ok(mut json) => {
+                for pointer in &self.redacted_json {
+                    if let Some(value) = json.pointer_mut(pointer) {
+                        *value = serde_json::Value::String(REDACTED.to_owned());
+                    }
+                }
+
+                sort_keys(&mut json);
+                serde_json::to_string_pretty(&json).unwrap()
+            }
+            Err(_) => match std::str::from_utf8(body) {
+                Ok(text) => text.to_owned(),
+                Err(_) => return format!("[binary body; {} bytes]", body.len()),
+            },
+        };
+
+        self.hooks.iter().fold(text, |text, hook| hook(text))
+    }
+}
+
+/// Sorts the keys of all objects in `value`.
+///
+/// JSON maps keep their insertion order when serde_json's `preserve_order` feature is enabled by
+/// any crate in the dependency graph, so sorting can not be left to them.
+fn sort_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries = std::mem::take(map).into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            for (_, value) in &mut entries {
+                sort_keys(value);
+            }
+
+            *map = entries.into_iter().collect();
+        }
+
+        serde_json::Value::Array(values) => values.iter_mut().for_each(sort_keys),
+
+        _ => {}
+    }
+}
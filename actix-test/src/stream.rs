@@ -0,0 +1,340 @@
+//! Helpers for consuming streaming response bodies and server-sent events.
+
+use std::{error::Error as StdError, fmt, pin::Pin, time::Duration};
+
+use actix_web::{
+    body::MessageBody,
+    rt,
+    web::{Bytes, BytesMut},
+};
+use futures_core::Stream;
+use futures_util::{future::poll_fn, stream::LocalBoxStream, StreamExt as _};
+
+/// Default time to wait for a chunk.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Boxed chunk stream of a [`MessageBody`], used by [`StreamReader::from_body()`].
+pub type BodyChunks = LocalBoxStream<'static, Result<Bytes, Box<dyn StdError>>>;
+
+/// An event received from a `text/event-stream` response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Event type, from the `event` field. `None` for the default `message` type.
+    pub event: Option<String>,
+
+    /// Event data, from the `data` fields joined by newlines.
+    pub data: String,
+
+    /// Event ID, from the `id` field.
+    pub id: Option<String>,
+
+    /// Reconnection time in milliseconds, from the `retry` field.
+    pub retry: Option<u64>,
+}
+
+/// Reads a streaming response body incrementally, with helpers for asserting on chunks and
+/// server-sent events.
+///
+/// Wraps a [`ClientResponse`](crate::ClientResponse) from a [`TestServer`](crate::TestServer), or
+/// any other stream of bytes, and a response body from `init_service` using
+/// [`from_body()`](Self::from_body()). Each read waits for data for up to the reader's
+/// [timeout](Self::timeout()), which defaults to 5 seconds, and panics if it does not arrive or the
+/// stream errors.
+///
+/// Chunks are returned as they are received from the stream. When reading a response over a real
+/// connection, chunks flushed by the server in quick succession may be received as one, so tests
+/// asserting on flush boundaries should wait for each chunk before the server sends the next.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_test::StreamReader;
+/// use actix_web::{web, App, HttpResponse};
+///
+/// # async fn events() -> HttpResponse { unimplemented!() }
+/// #[actix_web::test]
+/// async fn test_events() {
+///     let srv = actix_test::start(|| App::new().route("/events", web::get().to(events)));
+///
+///     let res = srv.get("/events").send().await.unwrap();
+///     let mut reader = StreamReader::new(res).timeout(Duration::from_secs(1));
+///
+///     let events = reader.collect_events(2).await;
+///     assert_eq!(events[0].data, "first");
+///     assert_eq!(events[1].event.as_deref(), Some("update"));
+/// }
+/// ```
+pub struct StreamReader<S> {
+    stream: Pin<Box<S>>,
+    buf: BytesMut,
+    timeout: Duration,
+    eof: bool,
+}
+
+impl<S, E> StreamReader<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: fmt::Display,
+{
+    /// Wraps a stream of body chunks.
+    pub fn new(stream: S) -> Self {
+        StreamReader {
+            stream: Box::pin(stream),
+            buf: BytesMut::new(),
+            timeout: DEFAULT_TIMEOUT,
+            eof: false,
+        }
+    }
+
+    /// Sets the time to wait for each read.
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.timeout = dur;
+        self
+    }
+
+    /// Waits for the next chunk from the stream, returning `None` at the end of the stream.
+    ///
+    /// Data left over from [`read_until()`](Self::read_until()) or event reads is returned first.
+    ///
+    /// # Panics
+    /// Panics if no chunk arrives before the timeout or the stream errors.
+    pub async fn next_chunk(&mut self) -> Option<Bytes> {
+        if !self.buf.is_empty() {
+            return Some(self.buf.split().freeze());
+        }
+
+        self.poll_chunk().await
+    }
+
+    /// Waits for the next chunk and asserts it is equal to `expected`.
+    ///
+    /// # Panics
+    /// Panics if the next chunk is not equal to `expected`, the stream ends, no chunk arrives
+    /// before the timeout, or the stream errors.
+    pub async fn expect_chunk(&mut self, expected: impl AsRef<[u8]>) -> Bytes {
+        match self.next_chunk().await {
+            Some(chunk) => {
+                assert_eq!(chunk, expected.as_ref(), "unexpected chunk");
+                chunk
+            }
+            None => panic!("stream ended while waiting for a chunk"),
+        }
+    }
+
+    /// Reads from the stream until `needle` is received and returns the data up to and including
+    /// it. Data after the needle is kept for subsequent reads.
+    ///
+    /// # Panics
+    /// Panics if the stream ends before `needle` is received, a chunk does not arrive before the
+    /// timeout, or the stream errors.
+    pub async fn read_until(&mut self, needle: impl AsRef<[u8]>) -> Bytes {
+        let needle = needle.as_ref();
+        assert!(!needle.is_empty(), "needle must not be empty");
+
+        loop {
+            if let Some(pos) = self
+                .buf
+                .windows(needle.len())
+                .position(|window| window == needle)
+            {
+                return self.buf.split_to(pos + needle.len()).freeze();
+            }
+
+            match self.poll_chunk().await {
+                Some(chunk) => self.buf.extend_from_slice(&chunk),
+                None => panic!(
+                    "stream ended while waiting for {:?}; received {:?}",
+                    Bytes::copy_from_slice(needle),
+                    self.buf,
+                ),
+            }
+        }
+    }
+
+    /// Asserts that the stream ends without any further data.
+    ///
+    /// # Panics
+    /// Panics if more data is received, the stream does not end before the timeout, or the stream
+    /// errors.
+    pub async fn expect_end(&mut self) {
+        if let Some(chunk) = self.next_chunk().await {
+            panic!("expected end of stream, received {chunk:?}");
+        }
+    }
+
+    /// Waits for the next server-sent event, returning `None` at the end of the stream.
+    ///
+    /// Comments and blocks of fields without data are skipped.
+    ///
+    /// # Panics
+    /// Panics if a chunk does not arrive before the timeout, the stream errors, or an event is not
+    /// valid UTF-8.
+    pub async fn next_event(&mut self) -> Option<SseEvent> {
+        let mut event = SseEvent::default();
+        let mut has_data = false;
+
+        loop {
+            let line = match self.next_line().await {
+                Some(line) => line,
+
+                // an incomplete event at the end of the stream is discarded
+                None => return None,
+            };
+
+            if line.is_empty() {
+                if has_data {
+                    return Some(event);
+                }
+
+                event = SseEvent::default();
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                // comment
+                Some(("", _)) => continue,
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line.as_str(), ""),
+            };
+
+            match field {
+                "event" => event.event = Some(value.to_owned()),
+                "data" => {
+                    if has_data {
+                        event.data.push('\n');
+                    }
+                    event.data.push_str(value);
+                    has_data = true;
+                }
+                "id" => event.id = Some(value.to_owned()),
+                "retry" => {
+                    if let Ok(retry) = value.parse() {
+                        event.retry = Some(retry);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Collects the next `n` server-sent events.
+    ///
+    /// The timeout applies to collecting all the events rather than to each read.
+    ///
+    /// # Panics
+    /// Panics if `n` events are not received before the timeout, the stream ends or errors, or an
+    /// event is not valid UTF-8.
+    pub async fn collect_events(&mut self, n: usize) -> Vec<SseEvent> {
+        let timeout = self.timeout;
+        let mut events = Vec::with_capacity(n);
+
+        let res = rt::time::timeout(timeout, async {
+            while events.len() < n {
+                match self.next_event().await {
+                    Some(event) => events.push(event),
+                    None => return false,
+                }
+            }
+
+            true
+        })
+        .await;
+
+        match res {
+            Ok(true) => events,
+            Ok(false) => panic!(
+                "stream ended after {} of {n} events: {events:?}",
+                events.len()
+            ),
+            Err(_) => panic!(
+                "timed out after {timeout:?} with {} of {n} events: {events:?}",
+                events.len()
+            ),
+        }
+    }
+
+    /// Returns any buffered data that has not been read.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns the underlying stream. Buffered data is discarded.
+    pub fn into_inner(self) -> Pin<Box<S>> {
+        self.stream
+    }
+
+    /// Waits for the next chunk from the underlying stream.
+    async fn poll_chunk(&mut self) -> Option<Bytes> {
+        if self.eof {
+            return None;
+        }
+
+        loop {
+            match rt::time::timeout(self.timeout, self.stream.next()).await {
+                // skip empty chunks so that they are not mistaken for flush boundaries
+                Ok(Some(Ok(chunk))) if chunk.is_empty() => continue,
+                Ok(Some(Ok(chunk))) => return Some(chunk),
+                Ok(Some(Err(err))) => panic!("error reading stream: {err}"),
+                Ok(None) => {
+                    self.eof = true;
+                    return None;
+                }
+                Err(_) => panic!("timed out after {:?} waiting for a chunk", self.timeout),
+            }
+        }
+    }
+
+    /// Reads the next line, without its line ending, returning `None` if the stream ends first.
+    async fn next_line(&mut self) -> Option<String> {
+        loop {
+            if let Some(pos) = self.buf.iter().position(|&b| b == b'\n' || b == b'\r') {
+                // wait for more data to see whether a trailing CR is followed by LF
+                if self.buf[pos] == b'\r' && pos + 1 == self.buf.len() && !self.eof {
+                    if let Some(chunk) = self.poll_chunk().await {
+                        self.buf.extend_from_slice(&chunk);
+                        continue;
+                    }
+                }
+
+                let line = self.buf.split_to(pos);
+                let crlf = self.buf.starts_with(b"\r\n");
+                let _ = self.buf.split_to(if crlf { 2 } else { 1 });
+
+                let line = String::from_utf8(line.to_vec())
+                    .unwrap_or_else(|_| panic!("event stream is not valid UTF-8: {line:?}"));
+
+                return Some(line);
+            }
+
+            let chunk = self.poll_chunk().await?;
+            self.buf.extend_from_slice(&chunk);
+        }
+    }
+}
+
+impl StreamReader<BodyChunks> {
+    /// Wraps a response body, such as that of a response from
+    /// [`call_service()`](crate::call_service()).
+    pub fn from_body<B>(body: B) -> Self
+    where
+        B: MessageBody + 'static,
+    {
+        let stream = futures_util::stream::unfold(Box::pin(body), |mut body| async move {
+            let chunk = poll_fn(|cx| body.as_mut().poll_next(cx)).await?;
+            Some((chunk.map_err(Into::into), body))
+        });
+
+        StreamReader::new(stream.boxed_local())
+    }
+}
+
+impl<S> fmt::Debug for StreamReader<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamReader")
+            .field("buffered", &self.buf)
+            .field("timeout", &self.timeout)
+            .field("eof", &self.eof)
+            .finish_non_exhaustive()
+    }
+}
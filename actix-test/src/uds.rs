@@ -0,0 +1,40 @@
+//! Unix Domain Socket (UDS) connector for clients of test servers bound to a socket path.
+
+use std::{future::Future, path::Path, pin::Pin, rc::Rc};
+
+use actix_http::Uri;
+use actix_rt::net::UnixStream;
+use actix_service::Service;
+use actix_tls::connect::{ConnectError, ConnectInfo, Connection};
+
+/// Connects to the socket at a fixed path, regardless of the requested URI's host.
+#[derive(Clone)]
+pub(crate) struct UdsConnector {
+    path: Rc<Path>,
+}
+
+impl UdsConnector {
+    pub(crate) fn new(path: &Path) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Service<ConnectInfo<Uri>> for UdsConnector {
+    type Response = Connection<Uri, UnixStream>;
+    type Error = ConnectError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_service::always_ready!();
+
+    fn call(&self, req: ConnectInfo<Uri>) -> Self::Future {
+        let path = Rc::clone(&self.path);
+        let uri = req.request().clone();
+
+        Box::pin(async move {
+            let io = UnixStream::connect(&*path)
+                .await
+                .map_err(ConnectError::Io)?;
+            Ok(Connection::new(uri, io))
+        })
+    }
+}
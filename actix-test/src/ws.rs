@@ -0,0 +1,225 @@
+//! WebSocket test session with frame assertion helpers.
+
+use std::{fmt, time::Duration};
+
+use actix_codec::Framed;
+use actix_http::ws::{self, CloseCode, CloseReason};
+use actix_web::{rt, web::Bytes};
+use awc::BoxedSocket;
+use futures_util::{SinkExt as _, StreamExt as _};
+
+/// Default time to wait for a frame.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A matcher for the contents of text frames, used by [`WsSession::expect_text()`].
+///
+/// Implemented for strings, which match text exactly, and for predicates of type
+/// `Fn(&str) -> bool`.
+pub trait TextMatcher {
+    /// Returns true if `text` is matched.
+    fn matches(&self, text: &str) -> bool;
+}
+
+impl TextMatcher for &str {
+    fn matches(&self, text: &str) -> bool {
+        *self == text
+    }
+}
+
+impl TextMatcher for String {
+    fn matches(&self, text: &str) -> bool {
+        self == text
+    }
+}
+
+impl<F> TextMatcher for F
+where
+    F: Fn(&str) -> bool,
+{
+    fn matches(&self, text: &str) -> bool {
+        self(text)
+    }
+}
+
+/// A client WebSocket connection to a [`TestServer`](crate::TestServer) with helpers for sending
+/// messages and asserting on received frames.
+///
+/// Frames are received as-is; continuation frames are not reassembled and pings are not answered
+/// automatically. Every `expect_*()` method waits for the next frame for up to the session's
+/// [timeout](Self::timeout()), which defaults to 5 seconds, and panics if the frame does not arrive
+/// or does not match.
+///
+/// # Examples
+/// ```
+/// use actix_http::ws::CloseCode;
+/// use actix_web::{web, App, HttpRequest, HttpResponse};
+///
+/// # async fn echo(req: HttpRequest, body: web::Payload) -> actix_web::Result<HttpResponse> {
+/// #     unimplemented!()
+/// # }
+/// #[actix_web::test]
+/// async fn test_echo() {
+///     let mut srv = actix_test::start(|| App::new().route("/", web::get().to(echo)));
+///
+///     let mut ws = srv.ws_session().await.unwrap();
+///
+///     ws.send_text("hello").await;
+///     ws.expect_text("hello").await;
+///
+///     ws.send_text("hi there").await;
+///     ws.expect_text(|text: &str| text.starts_with("hi")).await;
+///
+///     ws.close(CloseCode::Normal).await;
+///     ws.expect_close(CloseCode::Normal).await;
+/// }
+/// ```
+pub struct WsSession {
+    framed: Framed<BoxedSocket, ws::Codec>,
+    timeout: Duration,
+}
+
+impl WsSession {
+    /// Wraps a client WebSocket connection.
+    pub fn new(framed: Framed<BoxedSocket, ws::Codec>) -> Self {
+        WsSession {
+            framed,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Sets the time to wait for each expected frame.
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.timeout = dur;
+        self
+    }
+
+    /// Sends a text message.
+    ///
+    /// # Panics
+    /// Panics if the message cannot be sent.
+    pub async fn send_text(&mut self, text: impl Into<String>) {
+        self.send(ws::Message::Text(text.into().into())).await;
+    }
+
+    /// Sends a binary message.
+    ///
+    /// # Panics
+    /// Panics if the message cannot be sent.
+    pub async fn send_binary(&mut self, data: impl Into<Bytes>) {
+        self.send(ws::Message::Binary(data.into())).await;
+    }
+
+    /// Sends a ping message.
+    ///
+    /// # Panics
+    /// Panics if the message cannot be sent.
+    pub async fn send_ping(&mut self, data: impl Into<Bytes>) {
+        self.send(ws::Message::Ping(data.into())).await;
+    }
+
+    /// Sends a close message with the given reason.
+    ///
+    /// # Panics
+    /// Panics if the message cannot be sent.
+    pub async fn close(&mut self, reason: impl Into<CloseReason>) {
+        self.send(ws::Message::Close(Some(reason.into()))).await;
+    }
+
+    /// Sends a message.
+    ///
+    /// # Panics
+    /// Panics if the message cannot be sent.
+    pub async fn send(&mut self, msg: ws::Message) {
+        self.framed
+            .send(msg)
+            .await
+            .expect("failed to send WebSocket message");
+    }
+
+    /// Waits for the next frame.
+    ///
+    /// # Panics
+    /// Panics if no frame arrives before the timeout, the connection is closed, or a protocol error
+    /// occurs.
+    pub async fn next_frame(&mut self) -> ws::Frame {
+        match rt::time::timeout(self.timeout, self.framed.next()).await {
+            Ok(Some(Ok(frame))) => frame,
+            Ok(Some(Err(err))) => panic!("WebSocket protocol error: {err}"),
+            Ok(None) => panic!("WebSocket connection closed while waiting for a frame"),
+            Err(_) => panic!("timed out after {:?} waiting for a frame", self.timeout),
+        }
+    }
+
+    /// Waits for a text frame whose contents satisfy `matcher` and returns its contents.
+    ///
+    /// # Panics
+    /// Panics if the next frame is not a text frame, its contents are not valid UTF-8 or are not
+    /// matched, or it does not arrive before the timeout.
+    pub async fn expect_text(&mut self, matcher: impl TextMatcher) -> String {
+        match self.next_frame().await {
+            ws::Frame::Text(bytes) => {
+                let text = String::from_utf8(bytes.to_vec())
+                    .unwrap_or_else(|_| panic!("text frame is not valid UTF-8: {bytes:?}"));
+
+                assert!(matcher.matches(&text), "unexpected text frame: {text:?}");
+
+                text
+            }
+            frame => panic!("expected text frame, received {frame:?}"),
+        }
+    }
+
+    /// Waits for a binary frame with contents equal to `expected`.
+    ///
+    /// # Panics
+    /// Panics if the next frame is not a binary frame with the expected contents, or it does not
+    /// arrive before the timeout.
+    pub async fn expect_binary(&mut self, expected: impl AsRef<[u8]>) -> Bytes {
+        match self.next_frame().await {
+            ws::Frame::Binary(bytes) => {
+                assert_eq!(bytes, expected.as_ref(), "unexpected binary frame");
+                bytes
+            }
+            frame => panic!("expected binary frame, received {frame:?}"),
+        }
+    }
+
+    /// Waits for a pong frame and returns its payload.
+    ///
+    /// # Panics
+    /// Panics if the next frame is not a pong frame, or it does not arrive before the timeout.
+    pub async fn expect_pong(&mut self) -> Bytes {
+        match self.next_frame().await {
+            ws::Frame::Pong(bytes) => bytes,
+            frame => panic!("expected pong frame, received {frame:?}"),
+        }
+    }
+
+    /// Waits for a close frame with the given close code and returns its reason.
+    ///
+    /// # Panics
+    /// Panics if the next frame is not a close frame with the expected code, or it does not arrive
+    /// before the timeout.
+    pub async fn expect_close(&mut self, code: CloseCode) -> CloseReason {
+        match self.next_frame().await {
+            ws::Frame::Close(Some(reason)) => {
+                assert_eq!(reason.code, code, "unexpected close code");
+                reason
+            }
+            frame => panic!("expected close frame, received {frame:?}"),
+        }
+    }
+
+    /// Returns the underlying framed connection.
+    pub fn into_inner(self) -> Framed<BoxedSocket, ws::Codec> {
+        self.framed
+    }
+}
+
+impl fmt::Debug for WsSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WsSession")
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
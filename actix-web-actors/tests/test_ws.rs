@@ -54,6 +54,36 @@ async fn common_test_code(mut srv: actix_test::TestServer, frame_size: usize) {
     assert_eq!(item, ws::Frame::Close(Some(ws::CloseCode::Normal.into())));
 }
 
+#[actix_rt::test]
+async fn session_helpers() {
+    let mut srv = actix_test::start(|| {
+        App::new().service(web::resource("/").to(
+            |req: HttpRequest, stream: web::Payload| async move {
+                ws::WsResponseBuilder::new(Ws, &req, stream).start()
+            },
+        ))
+    });
+
+    let mut session = srv.ws_session().await.unwrap();
+
+    session.send_text("text").await;
+    assert_eq!(session.expect_text("text").await, "text");
+
+    session.send_text("hello world").await;
+    session
+        .expect_text(|text: &str| text.starts_with("hello"))
+        .await;
+
+    session.send_binary(Bytes::from_static(b"bin")).await;
+    session.expect_binary(b"bin").await;
+
+    session.send_ping(Bytes::from_static(b"ping")).await;
+    assert_eq!(session.expect_pong().await, Bytes::from_static(b"ping"));
+
+    session.close(ws::CloseCode::Normal).await;
+    session.expect_close(ws::CloseCode::Normal).await;
+}
+
 #[actix_rt::test]
 async fn simple_builder() {
     let srv = actix_test::start(|| {
@@ -0,0 +1,204 @@
+use proc_macro::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{punctuated::Punctuated, Token};
+
+use crate::{
+    input_and_compile_error,
+    route::{parse_route_attr, validate_path_params, Args, GuardExpr},
+};
+
+struct ControllerArgs {
+    prefix: Option<syn::LitStr>,
+    guards: Vec<GuardExpr>,
+    wrappers: Vec<syn::Expr>,
+}
+
+impl syn::parse::Parse for ControllerArgs {
+    fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
+        let prefix = if input.peek(syn::LitStr) {
+            let prefix = input.parse::<syn::LitStr>()?;
+
+            if prefix.value().ends_with('/') {
+                return Err(syn::Error::new(
+                    prefix.span(),
+                    "controller prefixes should not have trailing slashes; see https://docs.rs/actix-web/4/actix_web/struct.Scope.html#avoid-trailing-slashes",
+                ));
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+
+            Some(prefix)
+        } else {
+            None
+        };
+
+        let mut guards = Vec::new();
+        let mut wrappers = Vec::new();
+
+        // zero or more options: wrap = "Middleware"
+        let options = Punctuated::<syn::MetaNameValue, Token![,]>::parse_terminated(input)?;
+
+        for nv in options {
+            let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) = &nv.value
+            else {
+                return Err(syn::Error::new_spanned(
+                    nv.value,
+                    "Controller attributes expect a literal string",
+                ));
+            };
+
+            if nv.path.is_ident("guard") {
+                guards.push(GuardExpr::new(lit.parse()?)?);
+            } else if nv.path.is_ident("wrap") {
+                wrappers.push(lit.parse()?);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    nv.path,
+                    "Unknown attribute key is specified; allowed: guard and wrap",
+                ));
+            }
+        }
+
+        Ok(Self {
+            prefix,
+            guards,
+            wrappers,
+        })
+    }
+}
+
+/// A handler in the controller's impl block.
+struct Handler {
+    name: syn::Ident,
+    args: Vec<Args>,
+}
+
+pub(crate) fn with_controller(args: TokenStream, input: TokenStream) -> TokenStream {
+    match with_controller_inner(args, input.clone()) {
+        Ok(stream) => stream,
+        Err(err) => input_and_compile_error(input, err),
+    }
+}
+
+fn with_controller_inner(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let ControllerArgs {
+        prefix,
+        guards,
+        wrappers,
+    } = syn::parse(args)?;
+
+    let mut ast = syn::parse::<syn::ItemImpl>(input).map_err(|err| {
+        syn::Error::new(
+            err.span(),
+            "#[controller] macro must be attached to an impl block",
+        )
+    })?;
+
+    if let Some((_, trait_, _)) = &ast.trait_ {
+        return Err(syn::Error::new_spanned(
+            trait_,
+            "#[controller] macro can not be attached to trait impl blocks",
+        ));
+    }
+
+    let mut handlers = Vec::new();
+
+    for item in &mut ast.items {
+        let syn::ImplItem::Fn(fun) = item else {
+            continue;
+        };
+
+        let mut args = Vec::new();
+        let mut attrs = Vec::with_capacity(fun.attrs.len());
+
+        // the `routes` attribute is redundant here since all routing attributes are collected
+        for attr in fun.attrs.drain(..) {
+            if attr.path().is_ident("routes") {
+                continue;
+            }
+
+            match parse_route_attr(&attr) {
+                Some(parsed) => args.push(parsed?),
+                None => attrs.push(attr),
+            }
+        }
+
+        fun.attrs = attrs;
+
+        if args.is_empty() {
+            continue;
+        }
+
+        if let Some(receiver) = fun.sig.receiver() {
+            return Err(syn::Error::new_spanned(
+                receiver,
+                "controller handlers must be associated functions without a `self` receiver",
+            ));
+        }
+
+        if matches!(fun.sig.output, syn::ReturnType::Default) {
+            return Err(syn::Error::new_spanned(
+                &fun.sig,
+                "Function has no return type. Cannot be used as handler",
+            ));
+        }
+
+        for args in &args {
+            validate_path_params(args, &fun.sig)?;
+
+            if args.client {
+                return Err(syn::Error::new_spanned(
+                    &args.path,
+                    "client stubs are not supported by the #[controller] macro",
+                ));
+            }
+
+            if args.auto_register {
+                return Err(syn::Error::new_spanned(
+                    &args.path,
+                    "auto_register is not supported by the #[controller] macro",
+                ));
+            }
+        }
+
+        handlers.push(Handler {
+            name: fun.sig.ident.clone(),
+            args,
+        });
+    }
+
+    let resources = handlers.iter().flat_map(|handler| {
+        let name = &handler.name;
+        let handler_path = quote! { Self::#name };
+
+        handler
+            .args
+            .iter()
+            .map(move |args| args.resource(&name.to_string(), &handler_path))
+    });
+
+    let prefix = prefix.map_or_else(String::new, |prefix| prefix.value());
+    let self_ty = &ast.self_ty;
+    let (impl_generics, _, where_clause) = ast.generics.split_for_impl();
+
+    let stream = quote! {
+        #ast
+
+        impl #impl_generics ::actix_web::dev::HttpServiceFactory for #self_ty #where_clause {
+            fn register(self, __config: &mut ::actix_web::dev::AppService) {
+                let __scope = ::actix_web::Scope::new(#prefix)
+                    #(.guard(#guards))*
+                    #(.service(#resources))*
+                    #(.wrap(#wrappers))*;
+                ::actix_web::dev::HttpServiceFactory::register(__scope, __config);
+            }
+        }
+    };
+
+    Ok(stream.into_token_stream().into())
+}
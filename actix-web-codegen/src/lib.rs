@@ -61,7 +61,132 @@
 //! }
 //! ```
 //!
+//! # Guards
+//! The `guard` attribute of routing macros accepts:
+//! - a path to a function, which is used with `actix_web::guard::fn_guard`;
+//! - any other expression evaluating to a type implementing `Guard`, e.g. `guard::Header(..)`;
+//! - the `any(..)`, `all(..)`, and `not(..)` combinators of the above.
+//!
+//! ```
+//! # use actix_web::{guard::GuardContext, HttpResponse};
+//! # use actix_web_codegen::get;
+//! fn is_internal(ctx: &GuardContext<'_>) -> bool {
+//!     ctx.head().peer_addr.is_some_and(|addr| addr.ip().is_loopback())
+//! }
+//!
+//! #[get(
+//!     "/admin",
+//!     guard = "any(is_internal, actix_web::guard::Header(\"x-admin\", \"true\"))",
+//! )]
+//! async fn admin() -> HttpResponse {
+//!     HttpResponse::Ok().finish()
+//! }
+//! ```
+//!
+//! # Typed Path Parameters
+//! Dynamic path segments in routing macros can declare a type using the `{name:type}` syntax,
+//! where `type` is a primitive integer, float, `bool`, `char`, or `String`. Integer parameters
+//! only match values of the correct form, using a regex. If the handler takes a `Path` extractor
+//! of a tuple or single supported type, its arity and types are checked against the route path at
+//! compile time.
+//!
+//! For untyped paths, the arity of the extractor is checked too. Tuple fields are extracted from
+//! the route path's parameters in order, so a tuple with more fields than the route path has
+//! parameters is a compile error, as is a single type used with a route path that does not have
+//! exactly one parameter.
+//!
+//! ```
+//! # use actix_web::{web, HttpResponse};
+//! # use actix_web_codegen::get;
+//! #[get("/users/{id:u64}/posts/{slug}")]
+//! async fn post(path: web::Path<(u64, String)>) -> HttpResponse {
+//!     let (user_id, slug) = path.into_inner();
+//!     HttpResponse::Ok().body(format!("post {slug} by user {user_id}"))
+//! }
+//! ```
+//!
+//! # Trailing Slashes
+//! By default, routes only match their path as written. The `trailing_slash` attribute of routing
+//! macros sets a different policy for the route:
+//! - `"strict"`: Only the path as written is matched. This is the default.
+//! - `"match"`: The path is also matched with its trailing slash toggled.
+//! - `"redirect"`: Requests to the path with its trailing slash toggled are permanently redirected
+//!   (308) to the path as written, keeping the query string.
+//!
+//! ```
+//! # use actix_web::HttpResponse;
+//! # use actix_web_codegen::get;
+//! // "/things/" is redirected to "/things"
+//! #[get("/things", trailing_slash = "redirect")]
+//! async fn things() -> HttpResponse {
+//!     HttpResponse::Ok().finish()
+//! }
+//! ```
+//!
+//! # Route Metadata
+//! Routing macros generate a `ROUTES` constant on the handler with metadata about each route it
+//! registers, for use by API documentation (e.g., OpenAPI) generators. Metadata is taken from the
+//! handler's doc comment and the optional `summary`, `tag` (repeatable), and `response`
+//! (repeatable, as `"<status>: <type>"`) attributes. See `actix_web::dev::RouteMetadata`.
+//!
+//! ```
+//! # use actix_web::{web::Json, HttpResponse};
+//! # use actix_web_codegen::get;
+//! /// Returns the names of all users.
+//! #[get("/users", summary = "List users", tag = "users", response = "200: Json<Vec<String>>")]
+//! async fn list_users() -> Json<Vec<String>> {
+//!     Json(vec![])
+//! }
+//!
+//! assert_eq!(list_users::ROUTES[0].summary(), Some("List users"));
+//! ```
+//!
+//! # Route Names and URLs
+//! Routing macros generate `PATH` and `NAME` constants on the handler, containing its route's
+//! path pattern and resource name, and a `url_for` function taking the values of the path's
+//! dynamic segments in order. Typed path parameters are taken as their declared type and other
+//! parameters as any `Display` type. Values are percent-encoded, so they always form a single
+//! path segment except in tail segments. These are not generated when a handler registers routes
+//! with differing paths or names using [macro@routes].
+//!
+//! ```
+//! # use actix_web::{web, HttpResponse};
+//! # use actix_web_codegen::get;
+//! #[get("/users/{id:u64}/posts/{slug}")]
+//! async fn post(path: web::Path<(u64, String)>) -> HttpResponse {
+//!     HttpResponse::Ok().finish()
+//! }
+//!
+//! assert_eq!(post::NAME, "post");
+//! assert_eq!(post::url_for(42, "hello"), "/users/42/posts/hello");
+//! ```
+//!
+//! # Client Stubs
+//! Handlers declared with `client = true` also get a `client_request` function. It takes an
+//! [`awc`] client, a base URL, and the same parameters as `url_for`, and returns a request for the
+//! route, so that other services' calls to it stay in sync with the server definition. The
+//! handler's routes must share a single path and method, and crates generating client stubs must
+//! depend on `awc`.
+//!
+//! ```ignore
+//! #[get("/users/{id:u64}", client = true)]
+//! async fn get_user(id: web::Path<u64>) -> impl Responder {
+//!     // ...
+//! }
+//!
+//! let client = awc::Client::default();
+//! let res = get_user::client_request(&client, "http://localhost:8080", 42)
+//!     .send()
+//!     .await?;
+//! ```
+//!
+//! # WebSocket Handlers
+//! The [macro@ws] macro generates the handshake boilerplate for [`actix-ws`] based WebSocket
+//! handlers.
+//!
 //! [actix-web attributes docs]: https://docs.rs/actix-web/latest/actix_web/#attributes
+//! [`actix-ws`]: https://docs.rs/actix-ws
+//! [`awc`]: https://docs.rs/awc
 //! [GET]: macro@get
 //! [POST]: macro@post
 //! [PUT]: macro@put
@@ -80,8 +205,11 @@
 use proc_macro::TokenStream;
 use quote::quote;
 
+mod controller;
 mod route;
+mod runtime;
 mod scope;
+mod ws;
 
 /// Creates resource handler, allowing multiple HTTP method guards.
 ///
@@ -91,13 +219,22 @@ mod scope;
 /// ```
 ///
 /// # Attributes
-/// - `"path"`: Raw literal string with path for which to register handler.
+/// - `"path"`: Raw literal string with path for which to register handler. Path parameters may
+///   declare a type; see [typed path parameters](crate#typed-path-parameters).
 /// - `name = "resource_name"`: Specifies resource name for the handler. If not set, the function
 ///   name of handler is used.
 /// - `method = "HTTP_METHOD"`: Registers HTTP method to provide guard for. Upper-case string,
 ///   "GET", "POST" for example.
 /// - `guard = "function_name"`: Registers function as guard using `actix_web::guard::fn_guard`.
+///   Guard expressions and combinators are also accepted; see [guards](crate#guards).
 /// - `wrap = "Middleware"`: Registers a resource middleware.
+/// - `summary = "..."`, `tag = "..."`, `response = "200: Type"`: Route metadata; see
+///   [route metadata](crate#route-metadata).
+/// - `trailing_slash = "strict" | "match" | "redirect"`: Trailing slash policy; see
+///   [trailing slashes](crate#trailing-slashes).
+/// - `client = true`: Generates an `awc` request function; see [client stubs](crate#client-stubs).
+/// - `auto_register = true`: Registers the handler for collection by Actix Web's
+///   `collect_services!` macro, which requires its `auto-register` crate feature.
 ///
 /// # Notes
 /// Function name can be specified as any expression that is going to be accessible to the generate
@@ -160,11 +297,21 @@ macro_rules! method_macro {
         /// ```
         ///
         /// # Attributes
-        /// - `"path"`: Raw literal string with path for which to register handler.
+        /// - `"path"`: Raw literal string with path for which to register handler. Path parameters
+        ///   may declare a type; see [typed path parameters](crate#typed-path-parameters).
         /// - `name = "resource_name"`: Specifies resource name for the handler. If not set, the
         ///   function name of handler is used.
         /// - `guard = "function_name"`: Registers function as guard using `actix_web::guard::fn_guard`.
+        ///   Guard expressions and combinators are also accepted; see [guards](crate#guards).
         /// - `wrap = "Middleware"`: Registers a resource middleware.
+        /// - `summary = "..."`, `tag = "..."`, `response = "200: Type"`: Route metadata; see
+        ///   [route metadata](crate#route-metadata).
+        /// - `trailing_slash = "strict" | "match" | "redirect"`: Trailing slash policy; see
+        ///   [trailing slashes](crate#trailing-slashes).
+        /// - `client = true`: Generates an `awc` request function; see
+        ///   [client stubs](crate#client-stubs).
+        /// - `auto_register = true`: Registers the handler for collection by Actix Web's
+        ///   `collect_services!` macro, which requires its `auto-register` crate feature.
         ///
         /// # Notes
         /// Function name can be specified as any expression that is going to be accessible to the
@@ -196,6 +343,82 @@ method_macro!(Options, options);
 method_macro!(Trace, trace);
 method_macro!(Patch, patch);
 
+/// Creates route handler for a single, possibly non-standard, HTTP method.
+///
+/// Useful for extension methods, e.g. `PURGE` used by caching proxies or `PROPFIND` used by
+/// WebDAV, which have no dedicated macro.
+///
+/// # Syntax
+/// ```plain
+/// #[method("HTTP_METHOD", "path"[, attributes])]
+/// ```
+///
+/// # Attributes
+/// - `"HTTP_METHOD"`: Upper-case string with the HTTP method to provide guard for.
+/// - `"path"`: Raw literal string with path for which to register handler.
+/// - Other attributes are the same as the [single method handlers](crate#single-method-handler).
+///
+/// # Examples
+/// ```
+/// # use actix_web::HttpResponse;
+/// # use actix_web_codegen::method;
+/// #[method("PURGE", "/cache/{key}")]
+/// async fn purge() -> HttpResponse {
+///     HttpResponse::Ok().finish()
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn method(args: TokenStream, input: TokenStream) -> TokenStream {
+    route::with_custom_method(args, input)
+}
+
+/// Creates a WebSocket handler using [`actix-ws`](https://docs.rs/actix-ws).
+///
+/// The annotated function takes an `actix_ws::Session` and an `actix_ws::MessageStream`. The
+/// generated service performs the WebSocket handshake for GET requests with an
+/// `Upgrade: websocket` header, responds with the handshake response, and spawns the function to
+/// drive the connection.
+///
+/// Crates using this macro must depend on `actix-ws`.
+///
+/// # Syntax
+/// ```plain
+/// #[ws("path"[, attributes])]
+/// ```
+///
+/// # Attributes
+/// - `"path"`: Raw literal string with path for which to register handler.
+/// - `name = "resource_name"`: Specifies resource name for the handler. If not set, the function
+///   name of handler is used.
+/// - `guard = "function_name"`: Registers function as guard using `actix_web::guard::fn_guard`.
+///   Guard expressions and combinators are also accepted; see [guards](crate#guards).
+/// - `wrap = "Middleware"`: Registers a resource middleware.
+/// - `summary = "..."`, `tag = "..."`, `response = "200: Type"`: Route metadata; see
+///   [route metadata](crate#route-metadata).
+///
+/// # Examples
+/// ```
+/// use actix_web_codegen::ws;
+/// use futures_util::StreamExt as _;
+///
+/// #[ws("/echo")]
+/// async fn echo(mut session: actix_ws::Session, mut msg_stream: actix_ws::MessageStream) {
+///     while let Some(Ok(msg)) = msg_stream.next().await {
+///         if let actix_ws::Message::Text(text) = msg {
+///             if session.text(text).await.is_err() {
+///                 return;
+///             }
+///         }
+///     }
+///
+///     let _ = session.close(None).await;
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn ws(args: TokenStream, input: TokenStream) -> TokenStream {
+    ws::with_ws(args, input)
+}
+
 /// Prepends a path prefix to all handlers using routing macros inside the attached module.
 ///
 /// # Syntax
@@ -211,6 +434,15 @@ method_macro!(Patch, patch);
 /// # Arguments
 ///
 /// - `"/prefix"` - Raw literal string to be prefixed onto contained handlers' paths.
+/// - `guard = "function_name"` - Registers a guard on all contained handlers, accepting the same
+///   expressions as the `guard` attribute of routing macros.
+/// - `wrap = "Middleware"` - Registers a middleware on all contained handlers, outside any
+///   middleware registered by the handlers themselves.
+///
+/// # Duplicate Routes
+///
+/// Handlers in the module that register the same method and path cause a compile error, since
+/// the latter would be silently shadowed. Handlers with a `guard` attribute are not checked.
 ///
 /// # Example
 ///
@@ -233,12 +465,75 @@ pub fn scope(args: TokenStream, input: TokenStream) -> TokenStream {
     scope::with_scope(args, input)
 }
 
+/// Registers route handlers declared as associated functions of an impl block.
+///
+/// Routing macro attributes on the associated functions are collected and the type implements
+/// [`HttpServiceFactory`](https://docs.rs/actix-web/4/actix_web/dev/trait.HttpServiceFactory.html)
+/// so a value of it can be passed to `App::service`. Handlers are referred to as `Self::handler`
+/// and must not take a `self` receiver; shared state is still accessed through extractors.
+///
+/// # Syntax
+///
+/// ```
+/// # use actix_web_codegen::controller;
+/// # struct UserController;
+/// #[controller("/prefix")]
+/// impl UserController {
+///     // ...
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// - `"/prefix"` - Optional raw literal string to be prefixed onto contained handlers' paths.
+/// - `guard = "function_name"` - Registers a guard on all contained handlers, accepting the same
+///   expressions as the `guard` attribute of routing macros.
+/// - `wrap = "Middleware"` - Registers a middleware on all contained handlers.
+///
+/// # Example
+///
+/// ```
+/// # use actix_web_codegen::controller;
+/// # use actix_web::{web, App, HttpResponse, Responder};
+/// struct UserController;
+///
+/// #[controller("/users")]
+/// impl UserController {
+///     #[get("/{id}")]
+///     async fn show(id: web::Path<u32>) -> impl Responder {
+///         // this has path /users/{id}
+///         format!("user {id}")
+///     }
+///
+///     #[post("")]
+///     async fn create() -> impl Responder {
+///         HttpResponse::Created()
+///     }
+/// }
+///
+/// let app = App::new().service(UserController);
+/// ```
+#[proc_macro_attribute]
+pub fn controller(args: TokenStream, input: TokenStream) -> TokenStream {
+    controller::with_controller(args, input)
+}
+
 /// Marks async main function as the Actix Web system entry-point.
 ///
 /// Note that Actix Web also works under `#[tokio::main]` since version 4.0. However, this macro is
 /// still necessary for actor support (since actors use a `System`). Read more in the
 /// [`actix_web::rt`](https://docs.rs/actix-web/4/actix_web/rt) module docs.
 ///
+/// # Arguments
+///
+/// - `flavor = "multi_thread"` - Runs the system on a multi-threaded Tokio runtime, which tasks
+///   spawned using `tokio::spawn` run on. Defaults to `"current_thread"`.
+/// - `worker_threads = 4` - Sets the number of worker threads of the multi-threaded runtime,
+///   implying the `"multi_thread"` flavor. This does not affect the number of HTTP server workers,
+///   which are configured using `HttpServer::workers`.
+///
+/// The multi-threaded flavor requires Actix Web's `macros-multi-thread` crate feature.
+///
 /// # Examples
 /// ```
 /// #[actix_web::main]
@@ -246,8 +541,19 @@ pub fn scope(args: TokenStream, input: TokenStream) -> TokenStream {
 ///     async { println!("Hello world"); }.await
 /// }
 /// ```
+///
+/// ```
+/// #[actix_web::main(flavor = "multi_thread", worker_threads = 4)]
+/// async fn main() {
+///     async { println!("Hello world"); }.await
+/// }
+/// ```
 #[proc_macro_attribute]
-pub fn main(_: TokenStream, item: TokenStream) -> TokenStream {
+pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return runtime::with_main(args, item);
+    }
+
     let mut output: TokenStream = (quote! {
         #[::actix_web::rt::main(system = "::actix_web::rt::System")]
     })
@@ -259,15 +565,36 @@ pub fn main(_: TokenStream, item: TokenStream) -> TokenStream {
 
 /// Marks async test functions to use the Actix Web system entry-point.
 ///
+/// # Arguments
+///
+/// - `flavor = "multi_thread"` - Runs the system on a multi-threaded Tokio runtime, which tasks
+///   spawned using `tokio::spawn` run on. Defaults to `"current_thread"`.
+/// - `worker_threads = 4` - Sets the number of worker threads of the multi-threaded runtime,
+///   implying the `"multi_thread"` flavor.
+/// - `timeout = "30s"` - Fails the test if it does not complete within the given duration. Units
+///   of `ms`, `s`, `m`, and `h` are supported. Only tests that await are interrupted; blocking code
+///   runs to completion.
+///
+/// The multi-threaded flavor requires Actix Web's `macros-multi-thread` crate feature.
+///
 /// # Examples
 /// ```
 /// #[actix_web::test]
 /// async fn test() {
 ///     assert_eq!(async { "Hello world" }.await, "Hello world");
 /// }
+///
+/// #[actix_web::test(worker_threads = 2, timeout = "30s")]
+/// async fn test_with_config() {
+///     assert_eq!(async { "Hello world" }.await, "Hello world");
+/// }
 /// ```
 #[proc_macro_attribute]
-pub fn test(_: TokenStream, item: TokenStream) -> TokenStream {
+pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return runtime::with_test(args, item);
+    }
+
     let mut output: TokenStream = (quote! {
         #[::actix_web::rt::test(system = "::actix_web::rt::System")]
     })
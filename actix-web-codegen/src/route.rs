@@ -3,7 +3,7 @@ use std::collections::HashSet;
 use actix_router::ResourceDef;
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::{quote, ToTokens, TokenStreamExt};
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
 use syn::{punctuated::Punctuated, Ident, LitStr, Path, Token};
 
 use crate::input_and_compile_error;
@@ -55,6 +55,47 @@ impl syn::parse::Parse for RouteArgs {
     }
 }
 
+/// Args of the `#[method]` macro: an HTTP method followed by the same args as other routing macros.
+#[derive(Debug)]
+pub(crate) struct MethodArgs {
+    pub(crate) method: syn::LitStr,
+    pub(crate) args: RouteArgs,
+}
+
+impl syn::parse::Parse for MethodArgs {
+    fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
+        let method = input.parse::<syn::LitStr>().map_err(|mut err| {
+            err.combine(syn::Error::new(
+                err.span(),
+                r#"invalid service definition, expected #[method("<METHOD>", "<path>")]"#,
+            ));
+
+            err
+        })?;
+
+        input.parse::<Token![,]>()?;
+        let args = input.parse::<RouteArgs>()?;
+
+        if let Some(option) = args.options.iter().find(|nv| nv.path.is_ident("method")) {
+            return Err(syn::Error::new_spanned(
+                option,
+                "HTTP method forbidden here; to handle multiple methods, use `route` instead",
+            ));
+        }
+
+        Ok(Self { method, args })
+    }
+}
+
+impl MethodArgs {
+    /// Converts these args into the equivalent `#[route]` args.
+    pub(crate) fn into_route_args(self) -> RouteArgs {
+        let Self { method, mut args } = self;
+        args.options.push(syn::parse_quote! { method = #method });
+        args
+    }
+}
+
 macro_rules! standard_method_type {
     (
         $($variant:ident, $upper:ident, $lower:ident,)+
@@ -67,7 +108,7 @@ macro_rules! standard_method_type {
         }
 
         impl MethodType {
-            fn as_str(&self) -> &'static str {
+            pub(crate) fn as_str(&self) -> &'static str {
                 match self {
                     $(Self::$variant => stringify!($variant),)+
                 }
@@ -222,20 +263,133 @@ impl TryFrom<&syn::LitStr> for MethodTypeExt {
     }
 }
 
-struct Args {
-    path: syn::LitStr,
-    resource_name: Option<syn::LitStr>,
-    guards: Vec<Path>,
-    wrappers: Vec<syn::Expr>,
+/// A guard expression passed to the `guard` attribute.
+pub(crate) enum GuardExpr {
+    /// Path to a function, used with `actix_web::guard::fn_guard`.
+    Fn(Path),
+
+    /// Expression evaluating to a type implementing `Guard`.
+    Guard(syn::Expr),
+
+    /// `any(..)` combinator.
+    Any(Vec<GuardExpr>),
+
+    /// `all(..)` combinator.
+    All(Vec<GuardExpr>),
+
+    /// `not(..)` combinator.
+    Not(Box<GuardExpr>),
+}
+
+impl GuardExpr {
+    pub(crate) fn new(expr: syn::Expr) -> syn::Result<Self> {
+        let call = match expr {
+            syn::Expr::Path(syn::ExprPath {
+                qself: None, path, ..
+            }) => return Ok(Self::Fn(path)),
+            syn::Expr::Call(call) => call,
+            expr => return Ok(Self::Guard(expr)),
+        };
+
+        let combinator = match &*call.func {
+            syn::Expr::Path(syn::ExprPath {
+                qself: None, path, ..
+            }) => path
+                .get_ident()
+                .map(ToString::to_string)
+                .filter(|ident| matches!(ident.as_str(), "any" | "all" | "not")),
+            _ => None,
+        };
+
+        let Some(combinator) = combinator else {
+            return Ok(Self::Guard(syn::Expr::Call(call)));
+        };
+
+        let mut guards = call
+            .args
+            .iter()
+            .cloned()
+            .map(Self::new)
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        match combinator.as_str() {
+            "not" if guards.len() == 1 => Ok(Self::Not(Box::new(guards.remove(0)))),
+            "not" => Err(syn::Error::new_spanned(
+                call,
+                "guard combinator `not` expects exactly one guard",
+            )),
+            _ if guards.is_empty() => Err(syn::Error::new_spanned(
+                call,
+                format!("guard combinator `{combinator}` expects at least one guard"),
+            )),
+            "any" => Ok(Self::Any(guards)),
+            _ => Ok(Self::All(guards)),
+        }
+    }
+}
+
+impl ToTokens for GuardExpr {
+    fn to_tokens(&self, stream: &mut TokenStream2) {
+        let tokens = match self {
+            GuardExpr::Fn(path) => quote! { ::actix_web::guard::fn_guard(#path) },
+            GuardExpr::Guard(expr) => expr.to_token_stream(),
+            GuardExpr::Any(guards) => {
+                let (first, others) = guards.split_first().unwrap();
+                quote! { ::actix_web::guard::Any(#first)#(.or(#others))* }
+            }
+            GuardExpr::All(guards) => {
+                let (first, others) = guards.split_first().unwrap();
+                quote! { ::actix_web::guard::All(#first)#(.and(#others))* }
+            }
+            GuardExpr::Not(guard) => quote! { ::actix_web::guard::Not(#guard) },
+        };
+
+        stream.extend(tokens);
+    }
+}
+
+/// Trailing slash policy of a route.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum TrailingSlash {
+    /// Only the path as written is matched.
+    #[default]
+    Strict,
+
+    /// The path is matched both with and without a trailing slash.
+    Match,
+
+    /// Requests to the path with the trailing slash toggled are permanently redirected.
+    Redirect,
+}
+
+pub(crate) struct Args {
+    pub(crate) path: syn::LitStr,
+    path_params: Vec<PathParam>,
+    path_suffix: String,
+    pub(crate) resource_name: Option<syn::LitStr>,
+    pub(crate) guards: Vec<GuardExpr>,
+    pub(crate) wrappers: Vec<syn::Expr>,
     methods: HashSet<MethodTypeExt>,
+    summary: Option<LitStr>,
+    tags: Vec<LitStr>,
+    responses: Vec<(syn::LitInt, syn::Type)>,
+    trailing_slash: TrailingSlash,
+    pub(crate) client: bool,
+    pub(crate) auto_register: bool,
 }
 
 impl Args {
-    fn new(args: RouteArgs, method: Option<MethodType>) -> syn::Result<Self> {
+    pub(crate) fn new(args: RouteArgs, method: Option<MethodType>) -> syn::Result<Self> {
         let mut resource_name = None;
         let mut guards = Vec::new();
         let mut wrappers = Vec::new();
         let mut methods = HashSet::new();
+        let mut summary = None;
+        let mut tags = Vec::new();
+        let mut responses = Vec::new();
+        let mut trailing_slash = None;
+        let mut client = false;
+        let mut auto_register = false;
 
         let is_route_macro = method.is_none();
         if let Some(method) = method {
@@ -262,7 +416,7 @@ impl Args {
                     ..
                 }) = nv.value
                 {
-                    guards.push(lit.parse::<Path>()?);
+                    guards.push(GuardExpr::new(lit.parse()?)?);
                 } else {
                     return Err(syn::Error::new_spanned(
                         nv.value,
@@ -305,22 +459,797 @@ impl Args {
                         "Attribute method expects literal string",
                     ));
                 }
+            } else if nv.path.is_ident("summary") {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) = nv.value
+                {
+                    if summary.replace(lit).is_some() {
+                        return Err(syn::Error::new_spanned(
+                            nv.path,
+                            "Attribute summary defined more than once",
+                        ));
+                    }
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        nv.value,
+                        "Attribute summary expects literal string",
+                    ));
+                }
+            } else if nv.path.is_ident("tag") {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) = nv.value
+                {
+                    tags.push(lit);
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        nv.value,
+                        "Attribute tag expects literal string",
+                    ));
+                }
+            } else if nv.path.is_ident("response") {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) = nv.value
+                {
+                    responses.push(lit.parse_with(parse_response)?);
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        nv.value,
+                        r#"Attribute response expects literal string, e.g. "200: Json<User>""#,
+                    ));
+                }
+            } else if nv.path.is_ident("trailing_slash") {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) = nv.value
+                {
+                    let policy = match lit.value().as_str() {
+                        "strict" => TrailingSlash::Strict,
+                        "match" => TrailingSlash::Match,
+                        "redirect" => TrailingSlash::Redirect,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                r#"Attribute trailing_slash expects "strict", "match", or "redirect""#,
+                            ))
+                        }
+                    };
+
+                    if trailing_slash.replace((lit, policy)).is_some() {
+                        return Err(syn::Error::new_spanned(
+                            nv.path,
+                            "Attribute trailing_slash defined more than once",
+                        ));
+                    }
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        nv.value,
+                        "Attribute trailing_slash expects literal string",
+                    ));
+                }
+            } else if nv.path.is_ident("client") {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Bool(lit),
+                    ..
+                }) = nv.value
+                {
+                    client = lit.value;
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        nv.value,
+                        "Attribute client expects literal boolean",
+                    ));
+                }
+            } else if nv.path.is_ident("auto_register") {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Bool(lit),
+                    ..
+                }) = nv.value
+                {
+                    auto_register = lit.value;
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        nv.value,
+                        "Attribute auto_register expects literal boolean",
+                    ));
+                }
             } else {
                 return Err(syn::Error::new_spanned(
                     nv.path,
-                    "Unknown attribute key is specified; allowed: guard, method and wrap",
+                    "Unknown attribute key is specified; \
+                     allowed: guard, method, wrap, summary, tag, response, trailing_slash, client \
+                     and auto_register",
                 ));
             }
         }
 
+        let (path, path_params, path_suffix) = parse_path_params(&args.path.value());
+
+        // only rewrite the path if needed, to preserve the original literal's formatting
+        let path = if path_params.iter().any(|param| param.ty.is_some()) {
+            LitStr::new(&path, args.path.span())
+        } else {
+            args.path
+        };
+
+        let trailing_slash = match trailing_slash {
+            Some((lit, policy)) if policy != TrailingSlash::Strict => {
+                let path = path.value();
+
+                if path.is_empty() || path == "/" || path.ends_with('*') {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "trailing_slash can not be used with root or tail match paths",
+                    ));
+                }
+
+                policy
+            }
+            _ => TrailingSlash::Strict,
+        };
+
         Ok(Args {
-            path: args.path,
+            path,
+            path_params,
+            path_suffix,
             resource_name,
             guards,
             wrappers,
             methods,
+            summary,
+            tags,
+            responses,
+            trailing_slash,
+            client,
+            auto_register,
         })
     }
+
+    /// Returns true if a trailing slash policy other than `strict` was specified.
+    pub(crate) fn has_trailing_slash_policy(&self) -> bool {
+        self.trailing_slash != TrailingSlash::Strict
+    }
+
+    /// Returns an expression building the resource for these args, with the given default
+    /// resource name and handler.
+    pub(crate) fn resource(&self, default_name: &str, handler: impl ToTokens) -> TokenStream2 {
+        let Args {
+            path,
+            resource_name,
+            guards,
+            wrappers,
+            methods,
+            ..
+        } = self;
+
+        let resource_name = resource_name
+            .as_ref()
+            .map_or_else(|| default_name.to_owned(), LitStr::value);
+
+        let method_guards = {
+            debug_assert!(!methods.is_empty(), "Args::methods should not be empty");
+
+            let mut others = methods.iter();
+            let first = others.next().unwrap();
+
+            if methods.len() > 1 {
+                let other_method_guards = others
+                    .map(|method_ext| method_ext.to_tokens_multi_guard_or_chain())
+                    .collect();
+
+                first.to_tokens_multi_guard(other_method_guards)
+            } else {
+                first.to_tokens_single_guard()
+            }
+        };
+
+        let alt_path = match path.value().strip_suffix('/') {
+            Some(stripped) => LitStr::new(stripped, path.span()),
+            None => LitStr::new(&format!("{}/", path.value()), path.span()),
+        };
+
+        let patterns = match self.trailing_slash {
+            TrailingSlash::Match => quote! { [#path, #alt_path] },
+            TrailingSlash::Strict | TrailingSlash::Redirect => quote! { #path },
+        };
+
+        let resource = quote! {
+            ::actix_web::Resource::new(#patterns)
+                .name(#resource_name)
+                #method_guards
+                #(.guard(#guards))*
+                #(.wrap(#wrappers))*
+                .to(#handler)
+        };
+
+        if self.trailing_slash != TrailingSlash::Redirect {
+            return resource;
+        }
+
+        let location = if path.value().ends_with('/') {
+            quote! { ::std::format!("{}/", path) }
+        } else {
+            quote! { path.strip_suffix('/').unwrap_or(path).to_owned() }
+        };
+
+        quote! {
+            (
+                #resource,
+                ::actix_web::Resource::new(#alt_path)
+                    #method_guards
+                    #(.guard(#guards))*
+                    .to(|req: ::actix_web::HttpRequest| async move {
+                        // escape backslashes and leading double slashes, which browsers would
+                        // otherwise resolve as a location on another host
+                        let path = req.path().replace('\\', "%5C");
+                        let path = path.as_str();
+                        let mut location = #location;
+
+                        if location.starts_with("//") {
+                            location.replace_range(1..2, "%2F");
+                        }
+
+                        if !req.query_string().is_empty() {
+                            location.push('?');
+                            location.push_str(req.query_string());
+                        }
+
+                        ::actix_web::HttpResponse::PermanentRedirect()
+                            .insert_header((::actix_web::http::header::LOCATION, location))
+                            .finish()
+                    }),
+            )
+        }
+    }
+}
+
+/// Parses the args of a `#[route]` or `#[<method>]` attribute.
+///
+/// Returns `None` if the attribute is not a routing macro attribute.
+pub(crate) fn parse_route_attr(attr: &syn::Attribute) -> Option<syn::Result<Args>> {
+    if attr.path().is_ident("method") {
+        let args = attr
+            .parse_args::<MethodArgs>()
+            .and_then(|args| Args::new(args.into_route_args(), None));
+
+        return Some(args);
+    }
+
+    let method = match MethodType::from_path(attr.path()) {
+        Ok(method) => Some(method),
+        Err(_) if attr.path().is_ident("route") => None,
+        Err(_) => return None,
+    };
+
+    let args = attr
+        .parse_args()
+        .and_then(|args| Args::new(args, method))
+        .and_then(|args| {
+            if args.methods.is_empty() {
+                Err(syn::Error::new_spanned(
+                    attr,
+                    "The #[route(..)] macro requires at least one `method` attribute",
+                ))
+            } else {
+                Ok(args)
+            }
+        });
+
+    Some(args)
+}
+
+/// Parses the value of a `response` attribute, e.g. `200: Json<User>`.
+fn parse_response(input: syn::parse::ParseStream<'_>) -> syn::Result<(syn::LitInt, syn::Type)> {
+    let status = input.parse::<syn::LitInt>()?;
+
+    if !matches!(status.base10_parse::<u16>(), Ok(100..=999)) {
+        return Err(syn::Error::new(
+            status.span(),
+            "response status code must be between 100 and 999",
+        ));
+    }
+
+    input.parse::<Token![:]>()?;
+    let ty = input.parse()?;
+
+    Ok((status, ty))
+}
+
+/// Returns the contents of doc comment attributes, with leading whitespace trimmed from each line.
+fn doc_comment(doc_attributes: &[syn::Attribute]) -> Option<String> {
+    let lines = doc_attributes
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit),
+                        ..
+                    }),
+                ..
+            }) => Some(lit.value()),
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.split('\n')
+                .map(|line| line.trim_start().to_owned())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let doc = lines.join("\n").trim().to_owned();
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// Generates the submission of the handler for collection by `actix_web::collect_services!`.
+///
+/// Nothing is generated unless one of the handler's routes opts in using `auto_register = true`.
+pub(crate) fn auto_register(name: &syn::Ident, args: &[&Args]) -> TokenStream2 {
+    if !args.iter().any(|args| args.auto_register) {
+        return TokenStream2::new();
+    }
+
+    let path = &args[0].path;
+
+    quote! {
+        const _: () = {
+            fn __register(__config: &mut ::actix_web::dev::AppService) {
+                ::actix_web::dev::HttpServiceFactory::register(#name, __config);
+            }
+
+            ::actix_web::dev::inventory::submit! {
+                ::actix_web::dev::AutoService::__new(#path, __register)
+            }
+        };
+    }
+}
+
+/// Generates the `ROUTES` constant containing the metadata of the routes registered by a handler.
+pub(crate) fn route_metadata(
+    name: &syn::Ident,
+    args: &[&Args],
+    doc_attributes: &[syn::Attribute],
+) -> TokenStream2 {
+    let description = match doc_comment(doc_attributes) {
+        Some(doc) => quote! { ::std::option::Option::Some(#doc) },
+        None => quote! { ::std::option::Option::None },
+    };
+
+    let routes = args.iter().map(|args| {
+        let Args {
+            path,
+            resource_name,
+            methods,
+            summary,
+            tags,
+            responses,
+            ..
+        } = args;
+
+        let resource_name = resource_name
+            .as_ref()
+            .map_or_else(|| name.to_string(), LitStr::value);
+
+        let mut methods = methods
+            .iter()
+            .map(|method| match method {
+                MethodTypeExt::Standard(method) => method.as_str().to_uppercase(),
+                MethodTypeExt::Custom(lit) => lit.value(),
+            })
+            .collect::<Vec<_>>();
+        methods.sort_unstable();
+
+        let summary = match summary {
+            Some(summary) => quote! { ::std::option::Option::Some(#summary) },
+            None => quote! { ::std::option::Option::None },
+        };
+
+        let responses = responses.iter().map(|(status, ty)| {
+            quote! {
+                ::actix_web::dev::ResponseMetadata::__new(
+                    #status,
+                    || ::std::any::type_name::<#ty>(),
+                )
+            }
+        });
+
+        quote! {
+            ::actix_web::dev::RouteMetadata::__new(
+                #resource_name,
+                #path,
+                &[#(#methods),*],
+                #summary,
+                #description,
+                &[#(#tags),*],
+                &[#(#responses),*],
+            )
+        }
+    });
+
+    quote! {
+        impl #name {
+            /// Metadata of the routes registered by this handler.
+            #[allow(dead_code)]
+            pub const ROUTES: &'static [::actix_web::dev::RouteMetadata] = &[#(#routes),*];
+        }
+    }
+}
+
+/// Returns the route registered by a handler and its resource name.
+///
+/// Returns `None` if the handler registers routes with differing paths or names, since there is
+/// no single route to refer to.
+fn single_route<'a>(name: &syn::Ident, args: &[&'a Args]) -> Option<(&'a Args, String)> {
+    let (first, others) = args
+        .split_first()
+        .expect("handler should have at least one route");
+
+    let resource_name = |args: &Args| {
+        args.resource_name
+            .as_ref()
+            .map_or_else(|| name.to_string(), LitStr::value)
+    };
+
+    let path = first.path.value();
+    let first_name = resource_name(first);
+
+    if others
+        .iter()
+        .any(|args| args.path.value() != path || resource_name(args) != first_name)
+    {
+        return None;
+    }
+
+    Some((first, first_name))
+}
+
+/// Returns the parameter names and typed parameter declarations of a route's `url_for` function.
+fn url_params(args: &Args) -> (Vec<Ident>, Vec<TokenStream2>) {
+    args.path_params
+        .iter()
+        .enumerate()
+        .map(|(idx, param)| {
+            let ident = syn::parse_str::<syn::Ident>(&param.name)
+                .unwrap_or_else(|_| format_ident!("param_{}", idx));
+
+            let ty = match param.ty {
+                Some(ty) if ty != "String" => {
+                    let ty = Ident::new(ty, Span::call_site());
+                    quote! { #ty }
+                }
+                _ => quote! { impl ::std::fmt::Display },
+            };
+
+            let param = quote! { #ident: #ty };
+            (ident, param)
+        })
+        .unzip()
+}
+
+/// Generates the `PATH` and `NAME` constants and the `url_for` helper of a handler.
+///
+/// Nothing is generated if the handler registers routes with differing paths or names.
+pub(crate) fn route_constants(name: &syn::Ident, args: &[&Args]) -> TokenStream2 {
+    let Some((route, resource_name)) = single_route(name, args) else {
+        return TokenStream2::new();
+    };
+
+    let path = &route.path;
+    let (idents, params) = url_params(route);
+    let prefixes = route.path_params.iter().map(|param| &param.prefix);
+    let tails = route.path_params.iter().map(|param| param.tail);
+    let suffix = &route.path_suffix;
+
+    // hygienic, so that it can not collide with the parameters
+    let url = Ident::new("url", Span::mixed_site());
+
+    quote! {
+        #[allow(dead_code)]
+        impl #name {
+            /// Path pattern of the route registered by this handler.
+            pub const PATH: &'static str = #path;
+
+            /// Name of the resource registered by this handler.
+            pub const NAME: &'static str = #resource_name;
+
+            /// Returns the path of the route registered by this handler, with the given values of
+            /// its dynamic segments.
+            ///
+            /// Values are percent-encoded; slashes are only preserved in values of tail segments.
+            /// Prefixes added by scopes at runtime are not included.
+            pub fn url_for(#(#params),*) -> ::std::string::String {
+                let mut #url = ::std::string::String::with_capacity(Self::PATH.len());
+                #(
+                    #url.push_str(#prefixes);
+                    ::actix_web::dev::push_encoded_segment(&mut #url, &#idents, #tails);
+                )*
+                #url.push_str(#suffix);
+                #url
+            }
+        }
+    }
+}
+
+/// Returns the method of a handler's client stub, checking that its routes share a single path,
+/// name, and method.
+fn client_method<'a>(name: &syn::Ident, args: &[&'a Args]) -> syn::Result<&'a MethodTypeExt> {
+    let methods = args
+        .iter()
+        .flat_map(|args| &args.methods)
+        .collect::<HashSet<_>>();
+
+    match methods.into_iter().collect::<Vec<_>>()[..] {
+        [method] if single_route(name, args).is_some() => Ok(method),
+        _ => Err(syn::Error::new_spanned(
+            &args[0].path,
+            "client stubs can only be generated for handlers with a single path and method",
+        )),
+    }
+}
+
+/// Generates the `client_request` function of a handler, building an `awc` request for its route.
+///
+/// Nothing is generated unless one of the handler's routes opts in using `client = true`.
+fn client_stub(name: &syn::Ident, args: &[&Args]) -> TokenStream2 {
+    if !args.iter().any(|args| args.client) {
+        return TokenStream2::new();
+    }
+
+    // checked when the route is constructed
+    let Ok(method) = client_method(name, args) else {
+        return TokenStream2::new();
+    };
+
+    let method = match method {
+        MethodTypeExt::Standard(method) => {
+            let method = Ident::new(&method.as_str().to_uppercase(), Span::call_site());
+            quote! { ::awc::http::Method::#method }
+        }
+        MethodTypeExt::Custom(lit) => {
+            let bytes = syn::LitByteStr::new(lit.value().as_bytes(), lit.span());
+            quote! { ::awc::http::Method::from_bytes(#bytes).unwrap() }
+        }
+    };
+
+    let (idents, params) = url_params(args[0]);
+
+    quote! {
+        #[allow(dead_code)]
+        impl #name {
+            /// Returns a client request for the route registered by this handler, with the given
+            /// values of its dynamic segments, appended to `base_url`.
+            pub fn client_request(
+                client: &::awc::Client,
+                base_url: &str,
+                #(#params),*
+            ) -> ::awc::ClientRequest {
+                let url = ::std::format!("{}{}", base_url, Self::url_for(#(#idents),*));
+                client.request(#method, url)
+            }
+        }
+    }
+}
+
+/// A dynamic segment of a route path.
+#[derive(Debug)]
+struct PathParam {
+    name: String,
+
+    /// Type declared using the `{name:type}` syntax, if any.
+    ty: Option<&'static str>,
+
+    /// Static text of the path preceding this segment.
+    prefix: String,
+
+    /// True if this is a tail segment (e.g., `{tail}*`).
+    tail: bool,
+}
+
+const UNSIGNED_INT_TYPES: &[&str] = &["u8", "u16", "u32", "u64", "u128", "usize"];
+const SIGNED_INT_TYPES: &[&str] = &["i8", "i16", "i32", "i64", "i128", "isize"];
+const OTHER_PARAM_TYPES: &[&str] = &["f32", "f64", "bool", "char", "String"];
+
+/// Returns the type name if `name` is a type that can be declared for a path parameter.
+fn path_param_type(name: &str) -> Option<&'static str> {
+    UNSIGNED_INT_TYPES
+        .iter()
+        .chain(SIGNED_INT_TYPES)
+        .chain(OTHER_PARAM_TYPES)
+        .find(|ty| **ty == name)
+        .copied()
+}
+
+/// Parses the dynamic segments of a route path.
+///
+/// Returns the path with typed segments (e.g., `{id:u64}`) rewritten into a pattern that the
+/// router understands; integer types are matched using a regex so that other values are not
+/// routed to the handler. Segments with a custom regex are left as-is. The static text following
+/// the last segment is also returned.
+fn parse_path_params(path: &str) -> (String, Vec<PathParam>, String) {
+    let mut rewritten = String::with_capacity(path.len());
+    let mut params = Vec::new();
+    let mut rest = path;
+
+    while let Some(start) = rest.find('{') {
+        let prefix = &rest[..start];
+        rewritten.push_str(prefix);
+        rest = &rest[start..];
+
+        // find the matching closing brace, since custom regexes may contain braces
+        let mut depth = 0;
+        let end = rest.char_indices().find_map(|(idx, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        return Some(idx);
+                    }
+                }
+                _ => {}
+            }
+
+            None
+        });
+
+        // malformed paths are reported by the router
+        let Some(end) = end else {
+            break;
+        };
+
+        let segment = &rest[1..end];
+
+        let (name, ty) = match segment.split_once(':') {
+            Some((name, pattern)) => (name, path_param_type(pattern.trim())),
+            None => (segment, None),
+        };
+
+        match ty {
+            Some(ty) if UNSIGNED_INT_TYPES.contains(&ty) => {
+                rewritten.push_str(&format!(r"{{{name}:\d+}}"));
+            }
+            Some(ty) if SIGNED_INT_TYPES.contains(&ty) => {
+                rewritten.push_str(&format!(r"{{{name}:-?\d+}}"));
+            }
+            Some(_) => rewritten.push_str(&format!("{{{name}}}")),
+            None => rewritten.push_str(&rest[..=end]),
+        }
+
+        rest = &rest[end + 1..];
+
+        let tail = rest == "*";
+        if tail {
+            rewritten.push('*');
+            rest = "";
+        }
+
+        params.push(PathParam {
+            name: name.to_owned(),
+            ty,
+            prefix: prefix.to_owned(),
+            tail,
+        });
+    }
+
+    rewritten.push_str(rest);
+
+    (rewritten, params, rest.to_owned())
+}
+
+/// Returns the inner type of a `Path<T>` extractor type.
+fn path_extractor_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(ty) = ty else {
+        return None;
+    };
+
+    let segment = ty.path.segments.last()?;
+
+    if segment.ident != "Path" {
+        return None;
+    }
+
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// Returns the name of a simple type, e.g. `u64` or `std::string::String`.
+fn simple_type_name(ty: &syn::Type) -> Option<String> {
+    let syn::Type::Path(ty) = ty else {
+        return None;
+    };
+
+    let segment = ty.path.segments.last()?;
+
+    segment
+        .arguments
+        .is_empty()
+        .then(|| segment.ident.to_string())
+}
+
+/// Checks that any `Path` extractors taken by the handler match the route's path params.
+///
+/// Extractors with fewer fields than the route path has parameters are always rejected. Since
+/// scopes registered at runtime can contribute parameters, extractors with more fields are only
+/// rejected when the route path declares at least one typed parameter. `Path` extractors of types
+/// other than tuples and the supported parameter types (e.g., structs) are not checked.
+pub(crate) fn validate_path_params(args: &Args, sig: &syn::Signature) -> syn::Result<()> {
+    let params = &args.path_params;
+    let is_typed = params.iter().any(|param| param.ty.is_some());
+
+    for input in &sig.inputs {
+        let syn::FnArg::Typed(input) = input else {
+            continue;
+        };
+
+        let Some(inner) = path_extractor_inner(&input.ty) else {
+            continue;
+        };
+
+        // tuples are deserialized from the leading path parameters, while single values require
+        // exactly one parameter
+        let (elems, arity_matches) = match inner {
+            syn::Type::Tuple(tuple) => (
+                tuple.elems.iter().collect::<Vec<_>>(),
+                tuple.elems.len() <= params.len(),
+            ),
+            ty if simple_type_name(ty).is_some_and(|name| path_param_type(&name).is_some()) => {
+                (vec![ty], params.len() == 1)
+            }
+            _ => continue,
+        };
+
+        if !arity_matches {
+            let mut err = syn::Error::new_spanned(
+                &args.path,
+                format!(
+                    "route path has {} parameter(s) but `Path` extractor has {} field(s)",
+                    params.len(),
+                    elems.len(),
+                ),
+            );
+            err.combine(syn::Error::new_spanned(
+                &input.ty,
+                "`Path` extractor declared here",
+            ));
+            return Err(err);
+        }
+
+        if !is_typed {
+            continue;
+        }
+
+        for (param, elem) in params.iter().zip(elems) {
+            let Some(ty) = param.ty else {
+                continue;
+            };
+
+            if simple_type_name(elem).as_deref() != Some(ty) {
+                return Err(syn::Error::new_spanned(
+                    elem,
+                    format!(
+                        "path parameter `{}` is declared as `{ty}` but extracted as `{}`",
+                        param.name,
+                        elem.to_token_stream(),
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(())
 }
 
 pub struct Route {
@@ -353,6 +1282,11 @@ impl Route {
             .collect();
 
         let args = Args::new(args, method)?;
+        validate_path_params(&args, &ast.sig)?;
+
+        if args.client {
+            client_method(&name, &[&args])?;
+        }
 
         if args.methods.is_empty() {
             return Err(syn::Error::new(
@@ -379,6 +1313,14 @@ impl Route {
     fn multiple(args: Vec<Args>, ast: syn::ItemFn) -> syn::Result<Self> {
         let name = ast.sig.ident.clone();
 
+        for args in &args {
+            validate_path_params(args, &ast.sig)?;
+        }
+
+        if args.iter().any(|args| args.client) {
+            client_method(&name, &args.iter().collect::<Vec<_>>())?;
+        }
+
         // Try and pull out the doc comments so that we can reapply them to the generated struct.
         // Note that multi line doc comments are converted to multiple doc attributes.
         let doc_attributes = ast
@@ -423,47 +1365,21 @@ impl ToTokens for Route {
         let registrations: TokenStream2 = args
             .iter()
             .map(|args| {
-                let Args {
-                    path,
-                    resource_name,
-                    guards,
-                    wrappers,
-                    methods,
-                } = args;
-
-                let resource_name = resource_name
-                    .as_ref()
-                    .map_or_else(|| name.to_string(), LitStr::value);
-
-                let method_guards = {
-                    debug_assert!(!methods.is_empty(), "Args::methods should not be empty");
-
-                    let mut others = methods.iter();
-                    let first = others.next().unwrap();
-
-                    if methods.len() > 1 {
-                        let other_method_guards = others
-                            .map(|method_ext| method_ext.to_tokens_multi_guard_or_chain())
-                            .collect();
-
-                        first.to_tokens_multi_guard(other_method_guards)
-                    } else {
-                        first.to_tokens_single_guard()
-                    }
-                };
+                let resource = args.resource(&name.to_string(), name);
 
                 quote! {
-                    let __resource = ::actix_web::Resource::new(#path)
-                        .name(#resource_name)
-                        #method_guards
-                        #(.guard(::actix_web::guard::fn_guard(#guards)))*
-                        #(.wrap(#wrappers))*
-                        .to(#name);
+                    let __resource = #resource;
                     ::actix_web::dev::HttpServiceFactory::register(__resource, __config);
                 }
             })
             .collect();
 
+        let args_refs = args.iter().collect::<Vec<_>>();
+        let metadata = route_metadata(name, &args_refs, doc_attributes);
+        let constants = route_constants(name, &args_refs);
+        let client_stub = client_stub(name, &args_refs);
+        let auto_register = auto_register(name, &args_refs);
+
         let stream = quote! {
             #(#doc_attributes)*
             #[allow(non_camel_case_types, missing_docs)]
@@ -475,6 +1391,11 @@ impl ToTokens for Route {
                     #registrations
                 }
             }
+
+            #metadata
+            #constants
+            #client_stub
+            #auto_register
         };
 
         output.extend(stream);
@@ -505,6 +1426,26 @@ pub(crate) fn with_method(
     }
 }
 
+pub(crate) fn with_custom_method(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = match syn::parse::<MethodArgs>(args) {
+        Ok(args) => args.into_route_args(),
+        // on parse error, make IDEs happy; see fn docs
+        Err(err) => return input_and_compile_error(input, err),
+    };
+
+    let ast = match syn::parse::<syn::ItemFn>(input.clone()) {
+        Ok(ast) => ast,
+        // on parse error, make IDEs happy; see fn docs
+        Err(err) => return input_and_compile_error(input, err),
+    };
+
+    match Route::new(args, ast, None) {
+        Ok(route) => route.into_token_stream().into(),
+        // on macro related error, make IDEs happy; see fn docs
+        Err(err) => input_and_compile_error(input, err),
+    }
+}
+
 pub(crate) fn with_methods(input: TokenStream) -> TokenStream {
     let mut ast = match syn::parse::<syn::ItemFn>(input.clone()) {
         Ok(ast) => ast,
@@ -0,0 +1,283 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{punctuated::Punctuated, Token};
+
+use crate::input_and_compile_error;
+
+/// Flavor of the Tokio runtime underlying the `System`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Flavor {
+    CurrentThread,
+    MultiThread,
+}
+
+/// Arguments of the runtime macros, configuring the `System` they run in.
+#[derive(Default)]
+struct RuntimeArgs {
+    flavor: Option<(syn::LitStr, Flavor)>,
+    worker_threads: Option<syn::LitInt>,
+    timeout: Option<(syn::LitStr, u64)>,
+}
+
+impl RuntimeArgs {
+    /// Parses the arguments of the `main` or, if `is_test` is true, `test` macro.
+    fn parse(args: TokenStream, is_test: bool) -> syn::Result<Self> {
+        let options = syn::parse::Parser::parse(
+            Punctuated::<syn::MetaNameValue, Token![,]>::parse_terminated,
+            args,
+        )?;
+
+        let mut runtime_args = Self::default();
+
+        for nv in options {
+            if nv.path.is_ident("flavor") {
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) = nv.value
+                else {
+                    return Err(syn::Error::new_spanned(
+                        nv.value,
+                        "Attribute flavor expects literal string",
+                    ));
+                };
+
+                let flavor = match lit.value().as_str() {
+                    "current_thread" => Flavor::CurrentThread,
+                    "multi_thread" => Flavor::MultiThread,
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            lit,
+                            r#"unknown runtime flavor; expected "current_thread" or "multi_thread""#,
+                        ))
+                    }
+                };
+
+                if runtime_args.flavor.replace((lit, flavor)).is_some() {
+                    return Err(syn::Error::new_spanned(
+                        nv.path,
+                        "Attribute flavor defined more than once",
+                    ));
+                }
+            } else if nv.path.is_ident("worker_threads") {
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(lit),
+                    ..
+                }) = nv.value
+                else {
+                    return Err(syn::Error::new_spanned(
+                        nv.value,
+                        "Attribute worker_threads expects an integer literal",
+                    ));
+                };
+
+                if lit.base10_parse::<usize>()? == 0 {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "Attribute worker_threads must be greater than 0",
+                    ));
+                }
+
+                if runtime_args.worker_threads.replace(lit).is_some() {
+                    return Err(syn::Error::new_spanned(
+                        nv.path,
+                        "Attribute worker_threads defined more than once",
+                    ));
+                }
+            } else if is_test && nv.path.is_ident("timeout") {
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) = nv.value
+                else {
+                    return Err(syn::Error::new_spanned(
+                        nv.value,
+                        r#"Attribute timeout expects literal string, e.g. "30s""#,
+                    ));
+                };
+
+                let millis = parse_duration_millis(&lit.value())
+                    .ok_or_else(|| {
+                        syn::Error::new_spanned(
+                            &lit,
+                            r#"invalid timeout; expected an integer followed by a unit of "ms", "s", "m", or "h", e.g. "30s""#,
+                        )
+                    })?;
+
+                if runtime_args.timeout.replace((lit, millis)).is_some() {
+                    return Err(syn::Error::new_spanned(
+                        nv.path,
+                        "Attribute timeout defined more than once",
+                    ));
+                }
+            } else {
+                let allowed = if is_test {
+                    "flavor, worker_threads and timeout"
+                } else {
+                    "flavor and worker_threads"
+                };
+
+                return Err(syn::Error::new_spanned(
+                    nv.path,
+                    format!("Unknown attribute key is specified; allowed: {allowed}"),
+                ));
+            }
+        }
+
+        if let (Some((lit, Flavor::CurrentThread)), Some(_)) =
+            (&runtime_args.flavor, &runtime_args.worker_threads)
+        {
+            return Err(syn::Error::new_spanned(
+                lit,
+                "worker_threads can only be used with the \"multi_thread\" runtime flavor",
+            ));
+        }
+
+        Ok(runtime_args)
+    }
+
+    /// Returns an expression creating the `SystemRunner`.
+    fn system(&self) -> TokenStream2 {
+        let flavor = match (&self.flavor, &self.worker_threads) {
+            (Some((_, flavor)), _) => *flavor,
+            // configuring worker threads implies a multi-threaded runtime
+            (None, Some(_)) => Flavor::MultiThread,
+            (None, None) => Flavor::CurrentThread,
+        };
+
+        if flavor == Flavor::CurrentThread {
+            return quote! { ::actix_web::rt::System::new() };
+        }
+
+        let worker_threads = match &self.worker_threads {
+            Some(worker_threads) => quote! { ::std::option::Option::Some(#worker_threads) },
+            None => quote! { ::std::option::Option::None },
+        };
+
+        quote! {
+            ::actix_web::rt::System::with_tokio_rt(|| {
+                ::actix_web::rt::__multi_thread_runtime(#worker_threads)
+            })
+        }
+    }
+}
+
+/// Parses a duration such as `30s` or `500ms` into milliseconds.
+fn parse_duration_millis(duration: &str) -> Option<u64> {
+    let duration = duration.trim();
+    let unit_start = duration.find(|c: char| !c.is_ascii_digit())?;
+    let (value, unit) = duration.split_at(unit_start);
+    let value = value.parse::<u64>().ok()?;
+
+    let multiplier = match unit.trim() {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => return None,
+    };
+
+    value.checked_mul(multiplier)
+}
+
+pub(crate) fn with_main(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = match RuntimeArgs::parse(args, false) {
+        Ok(args) => args,
+        Err(err) => return input_and_compile_error(item, err),
+    };
+
+    let mut input = match syn::parse::<syn::ItemFn>(item.clone()) {
+        Ok(input) => input,
+        Err(err) => return input_and_compile_error(item, err),
+    };
+
+    if input.sig.asyncness.take().is_none() {
+        return input_and_compile_error(
+            item,
+            syn::Error::new_spanned(
+                input.sig.fn_token,
+                "the async keyword is missing from the function declaration",
+            ),
+        );
+    }
+
+    let syn::ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = &input;
+
+    let system = args.system();
+
+    (quote! {
+        #(#attrs)*
+        #vis #sig {
+            #system.block_on(async move #block)
+        }
+    })
+    .into()
+}
+
+pub(crate) fn with_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = match RuntimeArgs::parse(args, true) {
+        Ok(args) => args,
+        Err(err) => return input_and_compile_error(item, err),
+    };
+
+    let mut input = match syn::parse::<syn::ItemFn>(item.clone()) {
+        Ok(input) => input,
+        Err(err) => return input_and_compile_error(item, err),
+    };
+
+    if input.sig.asyncness.take().is_none() {
+        return input_and_compile_error(
+            item,
+            syn::Error::new_spanned(
+                input.sig.fn_token,
+                "the async keyword is missing from the function declaration",
+            ),
+        );
+    }
+
+    let syn::ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = &input;
+
+    let missing_test_attr = if attrs.iter().any(|attr| attr.path().is_ident("test")) {
+        quote! {}
+    } else {
+        quote! { #[::core::prelude::v1::test] }
+    };
+
+    let body = match &args.timeout {
+        Some((lit, millis)) => {
+            let message = format!("test timed out after {}", lit.value());
+
+            quote! {
+                ::actix_web::rt::time::timeout(
+                    ::std::time::Duration::from_millis(#millis),
+                    async #block,
+                )
+                .await
+                .expect(#message)
+            }
+        }
+        None => quote! { #block },
+    };
+
+    let system = args.system();
+
+    (quote! {
+        #missing_test_attr
+        #(#attrs)*
+        #vis #sig {
+            #system.block_on(async { #body })
+        }
+    })
+    .into()
+}
@@ -1,10 +1,13 @@
+use std::collections::HashMap;
+
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens as _};
+use syn::{punctuated::Punctuated, Token};
 
 use crate::{
     input_and_compile_error,
-    route::{MethodType, RouteArgs},
+    route::{MethodArgs, MethodType, RouteArgs},
 };
 
 pub fn with_scope(args: TokenStream, input: TokenStream) -> TokenStream {
@@ -14,6 +17,58 @@ pub fn with_scope(args: TokenStream, input: TokenStream) -> TokenStream {
     }
 }
 
+struct ScopeArgs {
+    prefix: syn::LitStr,
+    options: Punctuated<syn::MetaNameValue, Token![,]>,
+}
+
+impl syn::parse::Parse for ScopeArgs {
+    fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
+        let prefix = input.parse::<syn::LitStr>().map_err(|err| {
+            syn::Error::new(
+                err.span(),
+                "argument to scope macro is not a string literal, expected: #[scope(\"/prefix\")]",
+            )
+        })?;
+
+        if input.is_empty() {
+            return Ok(Self {
+                prefix,
+                options: Punctuated::new(),
+            });
+        }
+
+        input.parse::<Token![,]>()?;
+
+        // zero or more options: wrap = "Middleware"
+        let options = input.parse_terminated(syn::MetaNameValue::parse, Token![,])?;
+
+        for option in &options {
+            if !option.path.is_ident("guard") && !option.path.is_ident("wrap") {
+                return Err(syn::Error::new_spanned(
+                    &option.path,
+                    "Unknown attribute key is specified; allowed: guard and wrap",
+                ));
+            }
+
+            if !matches!(
+                option.value,
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(_),
+                    ..
+                })
+            ) {
+                return Err(syn::Error::new_spanned(
+                    &option.value,
+                    "Scope attributes expect a literal string",
+                ));
+            }
+        }
+
+        Ok(Self { prefix, options })
+    }
+}
+
 fn with_scope_inner(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
     if args.is_empty() {
         return Err(syn::Error::new(
@@ -22,12 +77,10 @@ fn with_scope_inner(args: TokenStream, input: TokenStream) -> syn::Result<TokenS
         ));
     }
 
-    let scope_prefix = syn::parse::<syn::LitStr>(args.clone()).map_err(|err| {
-        syn::Error::new(
-            err.span(),
-            "argument to scope macro is not a string literal, expected: #[scope(\"/prefix\")]",
-        )
-    })?;
+    let ScopeArgs {
+        prefix: scope_prefix,
+        options: scope_options,
+    } = syn::parse(args)?;
 
     let scope_prefix_value = scope_prefix.value();
 
@@ -45,15 +98,21 @@ fn with_scope_inner(args: TokenStream, input: TokenStream) -> syn::Result<TokenS
         syn::Error::new(err.span(), "#[scope] macro must be attached to a module")
     })?;
 
-    // modify any routing macros (method or route[s]) attached to
-    // functions by prefixing them with this scope macro's argument
+    if let Some((_, items)) = &module.content {
+        check_duplicate_routes(items)?;
+    }
+
+    // modify any routing macros (method or route[s]) attached to functions by prefixing them with
+    // this scope macro's argument and appending its guards and middleware
     if let Some((_, items)) = &mut module.content {
         for item in items {
             if let syn::Item::Fn(fun) = item {
                 fun.attrs = fun
                     .attrs
                     .iter()
-                    .map(|attr| modify_attribute_with_scope(attr, &scope_prefix_value))
+                    .map(|attr| {
+                        modify_attribute_with_scope(attr, &scope_prefix_value, &scope_options)
+                    })
                     .collect();
             }
         }
@@ -62,15 +121,113 @@ fn with_scope_inner(args: TokenStream, input: TokenStream) -> syn::Result<TokenS
     Ok(module.to_token_stream().into())
 }
 
+/// Checks that no two handlers in the module register the same method and path.
+///
+/// Routes with guards are skipped since guards can be used to disambiguate otherwise identical
+/// routes.
+fn check_duplicate_routes(items: &[syn::Item]) -> syn::Result<()> {
+    let mut routes = HashMap::<(String, String), &syn::Attribute>::new();
+
+    let attrs = items.iter().flat_map(|item| match item {
+        syn::Item::Fn(fun) => fun.attrs.as_slice(),
+        _ => &[][..],
+    });
+
+    for attr in attrs {
+        for (method, path) in route_registrations(attr) {
+            if let Some(first) = routes.insert((method.clone(), path.clone()), attr) {
+                let mut err = syn::Error::new_spanned(
+                    attr,
+                    format!("duplicate route: {method} {path} is already registered in this scope"),
+                );
+                err.combine(syn::Error::new_spanned(
+                    first,
+                    format!("{method} {path} first registered here"),
+                ));
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the method and path pairs registered by a routing macro attribute.
+fn route_registrations(attr: &syn::Attribute) -> Vec<(String, String)> {
+    let route_args = if attr.path().is_ident("method") {
+        attr.parse_args::<MethodArgs>()
+            .map(MethodArgs::into_route_args)
+    } else {
+        attr.parse_args::<RouteArgs>()
+    };
+
+    let Ok(route_args) = route_args else {
+        return Vec::new();
+    };
+
+    let has_guard = route_args
+        .options
+        .iter()
+        .any(|option| option.path.is_ident("guard"));
+
+    if has_guard {
+        return Vec::new();
+    }
+
+    let path = route_args.path.value();
+
+    let methods = if attr.path().is_ident("ws") {
+        vec!["GET".to_owned()]
+    } else if let Ok(method) = MethodType::from_path(attr.path()) {
+        vec![method.as_str().to_uppercase()]
+    } else if attr.path().is_ident("route") || attr.path().is_ident("method") {
+        route_args
+            .options
+            .iter()
+            .filter(|option| option.path.is_ident("method"))
+            .filter_map(|option| match &option.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) => Some(lit.value()),
+                _ => None,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    methods
+        .into_iter()
+        .map(|method| (method, path.clone()))
+        .collect()
+}
+
 /// Checks if the attribute is a method type and has a route path, then modifies it.
-fn modify_attribute_with_scope(attr: &syn::Attribute, scope_path: &str) -> syn::Attribute {
-    match (attr.parse_args::<RouteArgs>(), attr.clone().meta) {
-        (Ok(route_args), syn::Meta::List(meta_list)) if has_allowed_methods_in_scope(attr) => {
+fn modify_attribute_with_scope(
+    attr: &syn::Attribute,
+    scope_path: &str,
+    scope_options: &Punctuated<syn::MetaNameValue, Token![,]>,
+) -> syn::Attribute {
+    // the `method` macro takes the HTTP method before the path
+    let parsed = if attr.path().is_ident("method") {
+        attr.parse_args::<MethodArgs>()
+            .map(|MethodArgs { method, args }| (Some(method), args))
+    } else {
+        attr.parse_args::<RouteArgs>().map(|args| (None, args))
+    };
+
+    match (parsed, attr.clone().meta) {
+        (Ok((method, route_args)), syn::Meta::List(meta_list))
+            if has_allowed_methods_in_scope(attr) =>
+        {
             let modified_path = format!("{}{}", scope_path, route_args.path.value());
+            let method = method.iter();
 
             let options_tokens: Vec<TokenStream2> = route_args
                 .options
                 .iter()
+                .chain(scope_options)
                 .map(|option| {
                     quote! { ,#option }
                 })
@@ -86,7 +243,7 @@ fn modify_attribute_with_scope(attr: &syn::Attribute, scope_path: &str) -> syn::
 
             syn::Attribute {
                 meta: syn::Meta::List(syn::MetaList {
-                    tokens: quote! { #modified_path #combined_options_tokens },
+                    tokens: quote! { #(#method,)* #modified_path #combined_options_tokens },
                     ..meta_list.clone()
                 }),
                 ..attr.clone()
@@ -100,4 +257,6 @@ fn has_allowed_methods_in_scope(attr: &syn::Attribute) -> bool {
     MethodType::from_path(attr.path()).is_ok()
         || attr.path().is_ident("route")
         || attr.path().is_ident("ROUTE")
+        || attr.path().is_ident("ws")
+        || attr.path().is_ident("method")
 }
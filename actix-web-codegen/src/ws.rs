@@ -0,0 +1,173 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::LitStr;
+
+use crate::{
+    input_and_compile_error,
+    route::{auto_register, route_constants, route_metadata, Args, MethodType, RouteArgs},
+};
+
+pub struct WsRoute {
+    /// Name of the handler function being annotated.
+    name: syn::Ident,
+
+    /// Args passed to the `ws` macro.
+    args: Args,
+
+    /// AST of the handler function being annotated.
+    ast: syn::ItemFn,
+
+    /// The doc comment attributes to copy to generated struct, if any.
+    doc_attributes: Vec<syn::Attribute>,
+}
+
+impl WsRoute {
+    fn new(args: RouteArgs, ast: syn::ItemFn) -> syn::Result<Self> {
+        let name = ast.sig.ident.clone();
+
+        // Try and pull out the doc comments so that we can reapply them to the generated struct.
+        // Note that multi line doc comments are converted to multiple doc attributes.
+        let doc_attributes = ast
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .cloned()
+            .collect();
+
+        // WebSocket upgrades are always GET requests
+        let args = Args::new(args, Some(MethodType::Get))?;
+
+        if args.has_trailing_slash_policy() {
+            return Err(syn::Error::new_spanned(
+                &args.path,
+                "trailing_slash is not supported by the #[ws] macro",
+            ));
+        }
+
+        if args.client {
+            return Err(syn::Error::new_spanned(
+                &args.path,
+                "client stubs are not supported by the #[ws] macro",
+            ));
+        }
+
+        if let Some(receiver) = ast.sig.receiver() {
+            return Err(syn::Error::new_spanned(
+                receiver,
+                "WebSocket handlers can not take `self`",
+            ));
+        }
+
+        if ast.sig.inputs.len() != 2 {
+            return Err(syn::Error::new_spanned(
+                &ast.sig.inputs,
+                "WebSocket handlers must take exactly two arguments: \
+                 an `actix_ws::Session` and an `actix_ws::MessageStream`",
+            ));
+        }
+
+        Ok(Self {
+            name,
+            args,
+            ast,
+            doc_attributes,
+        })
+    }
+}
+
+impl ToTokens for WsRoute {
+    fn to_tokens(&self, output: &mut TokenStream2) {
+        let Self {
+            name,
+            ast,
+            args,
+            doc_attributes,
+        } = self;
+
+        #[allow(unused_variables)] // used when force-pub feature is disabled
+        let vis = &ast.vis;
+
+        // TODO(breaking): remove this force-pub forwards-compatibility feature
+        #[cfg(feature = "compat-routing-macros-force-pub")]
+        let vis = syn::Visibility::Public(<syn::Token![pub]>::default());
+
+        let metadata = route_metadata(name, &[args], doc_attributes);
+        let constants = route_constants(name, &[args]);
+        let auto_register = auto_register(name, &[args]);
+
+        let Args {
+            path,
+            resource_name,
+            guards,
+            wrappers,
+            ..
+        } = args;
+
+        let resource_name = resource_name
+            .as_ref()
+            .map_or_else(|| name.to_string(), LitStr::value);
+
+        let stream = quote! {
+            #(#doc_attributes)*
+            #[allow(non_camel_case_types, missing_docs)]
+            #vis struct #name;
+
+            impl ::actix_web::dev::HttpServiceFactory for #name {
+                fn register(self, __config: &mut actix_web::dev::AppService) {
+                    #ast
+
+                    async fn __ws_upgrade(
+                        req: ::actix_web::HttpRequest,
+                        body: ::actix_web::web::Payload,
+                    ) -> ::std::result::Result<::actix_web::HttpResponse, ::actix_web::Error> {
+                        let (res, session, msg_stream) = ::actix_ws::handle(&req, body)?;
+                        ::actix_web::rt::spawn(#name(session, msg_stream));
+                        Ok(res)
+                    }
+
+                    let __resource = ::actix_web::Resource::new(#path)
+                        .name(#resource_name)
+                        .guard(::actix_web::guard::Get())
+                        .guard(::actix_web::guard::fn_guard(|ctx| {
+                            ctx.head()
+                                .headers()
+                                .get(::actix_web::http::header::UPGRADE)
+                                .and_then(|val| val.to_str().ok())
+                                .map_or(false, |val| val.eq_ignore_ascii_case("websocket"))
+                        }))
+                        #(.guard(#guards))*
+                        #(.wrap(#wrappers))*
+                        .to(__ws_upgrade);
+                    ::actix_web::dev::HttpServiceFactory::register(__resource, __config);
+                }
+            }
+
+            #metadata
+            #constants
+            #auto_register
+        };
+
+        output.extend(stream);
+    }
+}
+
+pub(crate) fn with_ws(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = match syn::parse(args) {
+        Ok(args) => args,
+        // on parse error, make IDEs happy; see fn docs
+        Err(err) => return input_and_compile_error(input, err),
+    };
+
+    let ast = match syn::parse::<syn::ItemFn>(input.clone()) {
+        Ok(ast) => ast,
+        // on parse error, make IDEs happy; see fn docs
+        Err(err) => return input_and_compile_error(input, err),
+    };
+
+    match WsRoute::new(args, ast) {
+        Ok(route) => route.into_token_stream().into(),
+        // on macro related error, make IDEs happy; see fn docs
+        Err(err) => input_and_compile_error(input, err),
+    }
+}
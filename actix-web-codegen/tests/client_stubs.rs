@@ -0,0 +1,49 @@
+use actix_web::{web, App, HttpResponse, Responder};
+use actix_web_codegen::{get, post, routes};
+
+#[get("/users/{id:u64}/posts/{slug}", client = true)]
+async fn get_post(path: web::Path<(u64, String)>) -> impl Responder {
+    let (id, slug) = path.into_inner();
+    format!("{id}:{slug}")
+}
+
+#[post("/users", client = true)]
+async fn create_user(body: String) -> impl Responder {
+    HttpResponse::Created().body(body)
+}
+
+// no client stub is generated without opting in
+#[routes]
+#[get("/multi")]
+#[post("/multi")]
+async fn multi() -> impl Responder {
+    HttpResponse::Ok()
+}
+
+#[actix_rt::test]
+async fn test_client_stubs() {
+    let srv = actix_test::start(|| {
+        App::new()
+            .service(get_post)
+            .service(create_user)
+            .service(multi)
+    });
+
+    let base_url = srv.url("");
+    let base_url = base_url.trim_end_matches('/');
+    let client = awc::Client::default();
+
+    let mut res = get_post::client_request(&client, base_url, 42, "hello")
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    assert_eq!(res.body().await.unwrap(), "42:hello");
+
+    let mut res = create_user::client_request(&client, base_url)
+        .send_body("alice")
+        .await
+        .unwrap();
+    assert_eq!(res.status(), awc::http::StatusCode::CREATED);
+    assert_eq!(res.body().await.unwrap(), "alice");
+}
@@ -0,0 +1,81 @@
+use actix_web::{guard::GuardContext, http, http::header, web, App, HttpResponse, Responder};
+use actix_web_codegen::controller;
+
+pub fn image_guard(ctx: &GuardContext<'_>) -> bool {
+    ctx.header::<header::Accept>()
+        .map(|h| h.preference() == "image/*")
+        .unwrap_or(false)
+}
+
+struct UserController;
+
+#[controller("/users")]
+impl UserController {
+    #[get("/{id}")]
+    async fn show(id: web::Path<u32>) -> impl Responder {
+        format!("user {id}")
+    }
+
+    #[post("")]
+    #[put("")]
+    async fn create() -> impl Responder {
+        HttpResponse::Created()
+    }
+
+    #[allow(dead_code)]
+    fn helper() -> &'static str {
+        "not a handler"
+    }
+}
+
+struct ImageController;
+
+#[controller(
+    guard = "image_guard",
+    wrap = "actix_web::middleware::DefaultHeaders::new().add((\"x-controller\", \"image\"))"
+)]
+impl ImageController {
+    #[get("/image")]
+    async fn image() -> impl Responder {
+        HttpResponse::Ok()
+    }
+}
+
+#[actix_rt::test]
+async fn controller_routes_async() {
+    let srv = actix_test::start(|| App::new().service(UserController));
+
+    let request = srv.request(http::Method::GET, srv.url("/users/42"));
+    let mut response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+    let body = response.body().await.unwrap();
+    assert_eq!(body, "user 42");
+
+    let request = srv.request(http::Method::POST, srv.url("/users"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::CREATED);
+
+    let request = srv.request(http::Method::PUT, srv.url("/users"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::CREATED);
+
+    let request = srv.request(http::Method::DELETE, srv.url("/users"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn controller_guard_and_wrap_async() {
+    let srv = actix_test::start(|| App::new().service(ImageController));
+
+    let request = srv.request(http::Method::GET, srv.url("/image"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+
+    let request = srv
+        .request(http::Method::GET, srv.url("/image"))
+        .insert_header(("Accept", "image/*"));
+    let response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.headers().get("x-controller").unwrap(), "image");
+}
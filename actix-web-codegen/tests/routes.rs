@@ -11,10 +11,44 @@ use actix_web::{
     web, App, Error, HttpRequest, HttpResponse, Responder,
 };
 use actix_web_codegen::{
-    connect, delete, get, head, options, patch, post, put, route, routes, trace,
+    connect, delete, get, head, method, options, patch, post, put, route, routes, trace, ws,
 };
 use futures_core::future::LocalBoxFuture;
 
+#[ws("/ws")]
+async fn ws_handler(session: actix_ws::Session, _msg_stream: actix_ws::MessageStream) {
+    let _ = session.close(None).await;
+}
+
+#[get("/users/{id:u64}/posts/{slug}")]
+async fn typed_path_params(path: web::Path<(u64, String)>) -> impl Responder {
+    let (id, slug) = path.into_inner();
+    format!("{id}:{slug}")
+}
+
+#[get("/files/{name}/{path}*")]
+async fn tail_path_params(path: web::Path<(String, String)>) -> impl Responder {
+    let (name, path) = path.into_inner();
+    format!("{name}:{path}")
+}
+
+/// Returns the user's posts.
+///
+/// Posts are sorted by date.
+#[routes]
+#[get(
+    "/users/{id}/posts",
+    summary = "List posts",
+    tag = "users",
+    tag = "posts",
+    response = "200: web::Json<Vec<String>>",
+    response = "404: String"
+)]
+#[head("/users/{id}/posts")]
+async fn route_metadata_test() -> impl Responder {
+    HttpResponse::Ok()
+}
+
 // Make sure that we can name function as 'config'
 #[get("/config")]
 async fn config() -> impl Responder {
@@ -91,6 +125,11 @@ async fn custom_route_test() -> impl Responder {
     HttpResponse::Ok()
 }
 
+#[method("PURGE", "/cache/{key}")]
+async fn purge_test(key: web::Path<String>) -> impl Responder {
+    HttpResponse::Ok().body(format!("purged {key}"))
+}
+
 #[route(
     "/multi",
     method = "GET",
@@ -157,6 +196,14 @@ async fn guard_test() -> impl Responder {
     HttpResponse::Ok()
 }
 
+#[get(
+    "/test/guard-combinators",
+    guard = "any(guard_module::guard, all(actix_web::guard::Header(\"x-a\", \"1\"), not(actix_web::guard::Header(\"x-b\", \"1\"))))"
+)]
+async fn guard_combinators_test() -> impl Responder {
+    HttpResponse::Ok()
+}
+
 pub struct ChangeStatusCode;
 
 impl<S, B> Transform<S, ServiceRequest> for ChangeStatusCode
@@ -206,8 +253,8 @@ where
     }
 }
 
-#[get("/test/wrap", wrap = "ChangeStatusCode")]
-async fn get_wrap(_: web::Path<String>) -> impl Responder {
+#[get("/test/{id}", wrap = "ChangeStatusCode")]
+async fn get_wrap(_: web::Path<u32>) -> impl Responder {
     // panic!("actually never gets called because path failed to extract");
     HttpResponse::Ok()
 }
@@ -382,5 +429,232 @@ async fn test_wrap() {
     assert!(response.headers().contains_key("custom-header"));
     let body = response.body().await.unwrap();
     let body = String::from_utf8(body.to_vec()).unwrap();
-    assert!(body.contains("wrong number of parameters"));
+    assert!(body.contains("can not parse \"wrap\" to a u32"));
+}
+
+#[actix_rt::test]
+async fn test_ws() {
+    let mut srv = actix_test::start(|| App::new().service(ws_handler));
+
+    let request = srv.request(http::Method::GET, srv.url("/ws"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let request = srv
+        .request(http::Method::POST, srv.url("/ws"))
+        .insert_header(("upgrade", "websocket"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert!(srv.ws_at("/ws").await.is_ok());
+}
+
+#[actix_rt::test]
+async fn test_typed_path_params() {
+    let srv = actix_test::start(|| App::new().service(typed_path_params));
+
+    let request = srv.request(http::Method::GET, srv.url("/users/42/posts/hello"));
+    let mut response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.body().await.unwrap(), "42:hello");
+
+    let request = srv.request(http::Method::GET, srv.url("/users/abc/posts/hello"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn test_guard_combinators() {
+    let srv = actix_test::start(|| App::new().service(guard_combinators_test));
+    let url = "/test/guard-combinators";
+
+    let response = srv.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let request = srv.get(url).insert_header(("Accept", "image/*"));
+    let response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+
+    let request = srv.get(url).insert_header(("x-a", "1"));
+    let response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+
+    let request = srv
+        .get(url)
+        .insert_header(("x-a", "1"))
+        .insert_header(("x-b", "1"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_route_metadata() {
+    let routes = route_metadata_test::ROUTES;
+    assert_eq!(routes.len(), 2);
+
+    let get = &routes[0];
+    assert_eq!(get.name(), "route_metadata_test");
+    assert_eq!(get.path(), "/users/{id}/posts");
+    assert_eq!(get.methods(), ["GET"]);
+    assert_eq!(get.summary(), Some("List posts"));
+    assert_eq!(
+        get.description(),
+        Some("Returns the user's posts.\n\nPosts are sorted by date.")
+    );
+    assert_eq!(get.tags(), ["users", "posts"]);
+
+    let responses = get.responses();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0].status(), StatusCode::OK);
+    assert_eq!(
+        responses[0].type_name(),
+        std::any::type_name::<web::Json<Vec<String>>>()
+    );
+    assert_eq!(responses[1].status(), StatusCode::NOT_FOUND);
+
+    let head = &routes[1];
+    assert_eq!(head.methods(), ["HEAD"]);
+    assert_eq!(head.summary(), None);
+    assert!(head.tags().is_empty());
+    assert!(head.responses().is_empty());
+
+    let multi = route_test::ROUTES;
+    assert_eq!(multi[0].methods(), ["GET", "HEAD", "HELLO", "POST"]);
+}
+
+#[actix_rt::test]
+async fn test_route_constants() {
+    assert_eq!(get_param_test::PATH, "/test/{param}");
+    assert_eq!(get_param_test::NAME, "get_param_test");
+    assert_eq!(get_param_test::url_for("it works"), "/test/it%20works");
+    assert_eq!(get_param_test::url_for("a/b%c"), "/test/a%2Fb%25c");
+
+    assert_eq!(
+        tail_path_params::url_for("a/b", "dir/file name"),
+        "/files/a%2Fb/dir/file%20name"
+    );
+
+    assert_eq!(custom_resource_name_test::NAME, "custom");
+    assert_eq!(
+        custom_resource_name_test::url_for(),
+        "/custom_resource_name"
+    );
+
+    assert_eq!(typed_path_params::PATH, r"/users/{id:\d+}/posts/{slug}");
+
+    let url = typed_path_params::url_for(42, "hello");
+    assert_eq!(url, "/users/42/posts/hello");
+
+    let srv = actix_test::start(|| {
+        App::new()
+            .service(typed_path_params)
+            .service(tail_path_params)
+    });
+
+    let request = srv.request(http::Method::GET, srv.url(&url));
+    let mut response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.body().await.unwrap(), "42:hello");
+
+    let url = tail_path_params::url_for("a b", "dir/c d");
+    let request = srv.request(http::Method::GET, srv.url(&url));
+    let mut response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.body().await.unwrap(), "a b:dir/c d");
+}
+
+#[get("/slash/match", trailing_slash = "match")]
+async fn trailing_slash_match() -> impl Responder {
+    HttpResponse::Ok()
+}
+
+#[get("/slash/redirect/{id}", trailing_slash = "redirect")]
+async fn trailing_slash_redirect(id: web::Path<u32>) -> impl Responder {
+    HttpResponse::Ok().body(id.to_string())
+}
+
+#[post("/slash/redirect-to/", trailing_slash = "redirect")]
+async fn trailing_slash_redirect_to() -> impl Responder {
+    HttpResponse::Ok()
+}
+
+#[get("/{tail:.*}/", trailing_slash = "redirect")]
+async fn trailing_slash_redirect_tail(_tail: web::Path<String>) -> impl Responder {
+    HttpResponse::Ok()
+}
+
+#[actix_rt::test]
+async fn test_trailing_slash() {
+    let srv = actix_test::start_with(actix_test::config().disable_redirects(), || {
+        App::new()
+            .service(trailing_slash_match)
+            .service(trailing_slash_redirect)
+            .service(trailing_slash_redirect_to)
+            .service(trailing_slash_redirect_tail)
+    });
+
+    let request = srv.request(http::Method::GET, srv.url("/slash/match"));
+    let response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+
+    let request = srv.request(http::Method::GET, srv.url("/slash/match/"));
+    let response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+
+    let request = srv.request(http::Method::GET, srv.url("/slash/redirect/42"));
+    let mut response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.body().await.unwrap(), "42");
+
+    let request = srv.request(http::Method::GET, srv.url("/slash/redirect/42/?a=b"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(
+        response.headers().get(http::header::LOCATION).unwrap(),
+        "/slash/redirect/42?a=b"
+    );
+
+    // redirects only apply to the route's methods
+    let request = srv.request(http::Method::POST, srv.url("/slash/redirect/42/"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let request = srv.request(http::Method::POST, srv.url("/slash/redirect-to"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(
+        response.headers().get(http::header::LOCATION).unwrap(),
+        "/slash/redirect-to/"
+    );
+
+    // locations that browsers would resolve to another host are escaped
+    for (path, location) in [
+        ("/\\evil.com", "/%5Cevil.com/"),
+        ("//evil.com", "/%2Fevil.com/"),
+    ] {
+        let request = srv.request(http::Method::GET, srv.url(path));
+        let response = request.send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers().get(http::header::LOCATION).unwrap(),
+            location
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_custom_method() {
+    let srv = actix_test::start(|| App::new().service(purge_test));
+
+    let purge = http::Method::from_bytes(b"PURGE").unwrap();
+    let request = srv.request(purge, srv.url("/cache/users"));
+    let mut response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.body().await.unwrap(), "purged users");
+
+    let request = srv.request(http::Method::GET, srv.url("/cache/users"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert_eq!(purge_test::ROUTES[0].methods(), ["PURGE"]);
 }
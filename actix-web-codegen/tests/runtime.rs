@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use actix_web::rt::time::sleep;
+
+#[actix_web::test(worker_threads = 2, timeout = "5s")]
+async fn configured_test_runs() {
+    let res = actix_web::rt::spawn(async { 1 + 1 }).await.unwrap();
+    assert_eq!(res, 2);
+}
+
+#[actix_web::test(timeout = "10ms")]
+#[should_panic(expected = "test timed out after 10ms")]
+async fn configured_test_times_out() {
+    sleep(Duration::from_secs(5)).await;
+}
+
+#[actix_web::test(timeout = "5s")]
+async fn configured_test_returns_result() -> Result<(), std::num::ParseIntError> {
+    let _ = "42".parse::<u32>()?;
+    Ok(())
+}
+
+#[actix_web::test(flavor = "multi_thread")]
+async fn multi_thread_flavor_test_runs() {
+    let res = actix_web::rt::task::spawn_blocking(|| 1 + 1).await.unwrap();
+    assert_eq!(res, 2);
+}
@@ -1,5 +1,5 @@
 use actix_web::{guard::GuardContext, http, http::header, web, App, HttpResponse, Responder};
-use actix_web_codegen::{delete, get, post, route, routes, scope};
+use actix_web_codegen::{delete, get, method, post, route, routes, scope};
 
 pub fn image_guard(ctx: &GuardContext<'_>) -> bool {
     ctx.header::<header::Accept>()
@@ -22,6 +22,11 @@ mod scope_module {
         HttpResponse::Ok().finish()
     }
 
+    #[method("PURGE", "/test/purge")]
+    pub async fn purge() -> impl Responder {
+        HttpResponse::Ok().finish()
+    }
+
     #[get("/twice-test/{value}")]
     pub async fn twice(value: web::Path<String>) -> impl actix_web::Responder {
         let int_value: i32 = value.parse().unwrap_or(0);
@@ -91,6 +96,23 @@ mod mod_scope_v2 {
     }
 }
 
+#[scope(
+    "/guarded",
+    guard = "image_guard",
+    wrap = "actix_web::middleware::DefaultHeaders::new().add((\"x-scope\", \"guarded\"))"
+)]
+mod mod_scope_guarded {
+    use super::*;
+
+    #[get(
+        "/test",
+        wrap = "actix_web::middleware::DefaultHeaders::new().add((\"x-scope\", \"handler\"))"
+    )]
+    pub async fn test() -> impl Responder {
+        HttpResponse::Ok().finish()
+    }
+}
+
 #[actix_rt::test]
 async fn scope_get_async() {
     let srv = actix_test::start(|| App::new().service(scope_module::test));
@@ -198,3 +220,31 @@ async fn scope_v1_v2_async() {
     let body_str = String::from_utf8(body.to_vec()).unwrap();
     assert_eq!(body_str, "version2 works");
 }
+
+#[actix_rt::test]
+async fn scope_guard_and_wrap_async() {
+    let srv = actix_test::start(|| App::new().service(mod_scope_guarded::test));
+
+    let request = srv.request(http::Method::GET, srv.url("/guarded/test"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+
+    let request = srv
+        .request(http::Method::GET, srv.url("/guarded/test"))
+        .insert_header(("Accept", "image/*"));
+    let response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+
+    // handler middleware runs inside scope middleware, so its header is kept
+    assert_eq!(response.headers().get("x-scope").unwrap(), "handler");
+}
+
+#[actix_rt::test]
+async fn scope_custom_method_async() {
+    let srv = actix_test::start(|| App::new().service(scope_module::purge));
+
+    let purge = http::Method::from_bytes(b"PURGE").unwrap();
+    let request = srv.request(purge, srv.url("/test/test/purge"));
+    let response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+}
@@ -10,9 +10,16 @@ fn compile_macros() {
     t.compile_fail("tests/trybuild/route-missing-method-fail.rs");
     t.compile_fail("tests/trybuild/route-duplicate-method-fail.rs");
     t.compile_fail("tests/trybuild/route-malformed-path-fail.rs");
+    t.pass("tests/trybuild/route-typed-params-ok.rs");
+    t.compile_fail("tests/trybuild/route-typed-params-fail.rs");
+    t.compile_fail("tests/trybuild/route-path-arity-fail.rs");
+    t.compile_fail("tests/trybuild/route-trailing-slash-fail.rs");
+    t.compile_fail("tests/trybuild/route-guard-combinator-fail.rs");
+    t.compile_fail("tests/trybuild/route-client-fail.rs");
 
     t.pass("tests/trybuild/route-custom-method.rs");
     t.compile_fail("tests/trybuild/route-custom-lowercase.rs");
+    t.compile_fail("tests/trybuild/method-fail.rs");
 
     t.pass("tests/trybuild/routes-ok.rs");
     t.compile_fail("tests/trybuild/routes-missing-method-fail.rs");
@@ -22,8 +29,18 @@ fn compile_macros() {
     t.compile_fail("tests/trybuild/scope-missing-args.rs");
     t.compile_fail("tests/trybuild/scope-invalid-args.rs");
     t.compile_fail("tests/trybuild/scope-trailing-slash.rs");
+    t.compile_fail("tests/trybuild/scope-duplicate-route-fail.rs");
+
+    t.pass("tests/trybuild/controller-ok.rs");
+    t.compile_fail("tests/trybuild/controller-self-fail.rs");
+
+    t.pass("tests/trybuild/ws-ok.rs");
+    t.compile_fail("tests/trybuild/ws-args-fail.rs");
 
     t.pass("tests/trybuild/docstring-ok.rs");
 
     t.pass("tests/trybuild/test-runtime.rs");
+    t.compile_fail("tests/trybuild/test-runtime-args-fail.rs");
+    t.pass("tests/trybuild/main-runtime-args-ok.rs");
+    t.compile_fail("tests/trybuild/main-runtime-args-fail.rs");
 }
@@ -0,0 +1,26 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_codegen::controller;
+
+struct Controller;
+
+#[controller("/api")]
+impl Controller {
+    #[get("/{id:u32}")]
+    async fn show(id: web::Path<u32>) -> impl Responder {
+        format!("{id}")
+    }
+
+    #[route("/", method = "GET", method = "HEAD")]
+    async fn index() -> impl Responder {
+        HttpResponse::Ok()
+    }
+}
+
+#[actix_web::main]
+async fn main() {
+    let srv = actix_test::start(|| actix_web::App::new().service(Controller));
+
+    let request = srv.request(actix_web::http::Method::GET, srv.url("/api/1"));
+    let response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+}
@@ -0,0 +1,14 @@
+use actix_web::Responder;
+use actix_web_codegen::controller;
+
+struct Controller;
+
+#[controller("/api")]
+impl Controller {
+    #[get("/")]
+    async fn index(&self) -> impl Responder {
+        "Hello World!"
+    }
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+#[actix_web::main(flavor = "current_thread", worker_threads = 2)]
+async fn current_thread_workers() {}
+
+#[actix_web::main(flavor = "single")]
+async fn unknown_flavor() {}
+
+#[actix_web::main(timeout = "30s")]
+async fn timeout() {}
+
+fn main() {}
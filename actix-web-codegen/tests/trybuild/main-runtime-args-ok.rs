@@ -0,0 +1,5 @@
+#[actix_web::main(flavor = "multi_thread", worker_threads = 2)]
+async fn main() {
+    let res = actix_web::rt::spawn(async { 1 + 1 }).await.unwrap();
+    assert_eq!(res, 2);
+}
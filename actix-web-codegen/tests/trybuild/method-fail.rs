@@ -0,0 +1,13 @@
+use actix_web_codegen::method;
+
+#[method("PURGE", "/cache", method = "BAN")]
+async fn multiple() -> &'static str {
+    "Hello World!"
+}
+
+#[method("/cache")]
+async fn missing_method() -> &'static str {
+    "Hello World!"
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use actix_web_codegen::*;
+
+#[route("/multi", method = "GET", method = "POST", client = true)]
+async fn multi() -> String {
+    "multi".to_owned()
+}
+
+#[get("/flag", client = "yes")]
+async fn flag() -> String {
+    "flag".to_owned()
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use actix_web_codegen::*;
+
+#[get("/", guard = "not(actix_web::guard::Get(), actix_web::guard::Post())")]
+async fn index() -> String {
+    "Hello World!".to_owned()
+}
+
+#[get("/any", guard = "any()")]
+async fn empty_any() -> String {
+    "Hello World!".to_owned()
+}
+
+fn main() {}
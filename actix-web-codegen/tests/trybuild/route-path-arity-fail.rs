@@ -0,0 +1,19 @@
+use actix_web::web;
+use actix_web_codegen::*;
+
+#[get("/users/{id}/posts/{slug}")]
+async fn tuple(path: web::Path<(u32, String, u32)>) -> String {
+    path.1.clone()
+}
+
+#[get("/users/{id}/posts/{slug}/comments/{comment}")]
+async fn fewer_fields(path: web::Path<(u32, String)>) -> String {
+    path.1.clone()
+}
+
+#[get("/users/{id}/posts/{slug}")]
+async fn single(slug: web::Path<String>) -> String {
+    slug.into_inner()
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use actix_web_codegen::*;
+
+#[get("/things", trailing_slash = "lenient")]
+async fn unknown_policy() -> String {
+    "things".to_owned()
+}
+
+#[get("/", trailing_slash = "match")]
+async fn root() -> String {
+    "root".to_owned()
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use actix_web::web;
+use actix_web_codegen::*;
+
+#[get("/users/{id:u64}/posts/{slug}")]
+async fn arity(path: web::Path<(u64, String, u32)>) -> String {
+    path.0.to_string()
+}
+
+#[get("/users/{id:u64}/posts/{slug}")]
+async fn mismatch(path: web::Path<(i64, String)>) -> String {
+    path.0.to_string()
+}
+
+fn main() {}
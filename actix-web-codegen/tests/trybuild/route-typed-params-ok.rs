@@ -0,0 +1,31 @@
+use actix_web::web;
+use actix_web_codegen::*;
+
+#[get("/users/{id:u64}/posts/{slug}")]
+async fn tuple(path: web::Path<(u64, String)>) -> String {
+    let (id, slug) = path.into_inner();
+    format!("{id}:{slug}")
+}
+
+#[get("/users/{id:i32}")]
+async fn single(id: web::Path<i32>) -> String {
+    id.to_string()
+}
+
+#[get("/regex/{id:\\d{2}}/{flag:bool}")]
+async fn regex(path: web::Path<(String, bool)>) -> String {
+    format!("{}:{}", path.0, path.1)
+}
+
+#[actix_web::main]
+async fn main() {
+    use actix_web::App;
+
+    let srv = actix_test::start(|| App::new().service(tuple).service(single).service(regex));
+
+    for path in ["/users/1/posts/hi", "/users/-1", "/regex/12/true"] {
+        let request = srv.get(path);
+        let response = request.send().await.unwrap();
+        assert!(response.status().is_success());
+    }
+}
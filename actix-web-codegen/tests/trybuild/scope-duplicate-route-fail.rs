@@ -0,0 +1,27 @@
+use actix_web_codegen::scope;
+
+#[scope("/api")]
+mod api {
+    use actix_web_codegen::{get, route};
+
+    #[get("/hello")]
+    pub async fn hello() -> &'static str {
+        "Hello, world!"
+    }
+
+    #[get("/hello", guard = "super::is_internal")]
+    pub async fn hello_internal() -> &'static str {
+        "Hello, colleague!"
+    }
+
+    #[route("/hello", method = "HEAD", method = "GET")]
+    pub async fn hello_again() -> &'static str {
+        "Hello again!"
+    }
+}
+
+fn is_internal(_: &actix_web::guard::GuardContext<'_>) -> bool {
+    true
+}
+
+fn main() {}
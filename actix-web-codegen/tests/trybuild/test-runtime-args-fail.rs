@@ -0,0 +1,10 @@
+#[actix_web::test(timeout = "30")]
+async fn missing_unit() {}
+
+#[actix_web::test(worker_threads = 0)]
+async fn zero_workers() {}
+
+#[actix_web::test(threads = 4)]
+async fn unknown_arg() {}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use actix_web_codegen::*;
+
+#[ws("/ws")]
+async fn chat(session: actix_ws::Session) {
+    let _ = session.close(None).await;
+}
+
+fn main() {}
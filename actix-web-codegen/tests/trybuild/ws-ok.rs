@@ -0,0 +1,15 @@
+use actix_web_codegen::*;
+
+#[ws("/ws", name = "chat")]
+async fn chat(session: actix_ws::Session, _msg_stream: actix_ws::MessageStream) {
+    let _ = session.close(None).await;
+}
+
+#[actix_web::main]
+async fn main() {
+    use actix_web::App;
+
+    let mut srv = actix_test::start(|| App::new().service(chat));
+
+    assert!(srv.ws_at("/ws").await.is_ok());
+}
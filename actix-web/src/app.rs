@@ -9,7 +9,7 @@ use futures_util::FutureExt as _;
 
 use crate::{
     app_service::{AppEntry, AppInit, AppRoutingFactory},
-    config::ServiceConfig,
+    config::{RemoveOverriddenData, ServiceConfig},
     data::{Data, DataFactory, FnDataFactory},
     dev::ResourceDef,
     error::Error,
@@ -30,6 +30,8 @@ pub struct App<T> {
     data_factories: Vec<FnDataFactory>,
     external: Vec<ResourceDef>,
     extensions: Extensions,
+    data_overrides: Option<Extensions>,
+    data_override_removers: Vec<RemoveOverriddenData>,
 }
 
 impl App<AppEntry> {
@@ -46,6 +48,8 @@ impl App<AppEntry> {
             factory_ref,
             external: Vec::new(),
             extensions: Extensions::new(),
+            data_overrides: None,
+            data_override_removers: Vec::new(),
         }
     }
 }
@@ -117,6 +121,59 @@ where
         self
     }
 
+    /// Overrides application data of type `U` registered anywhere in the application.
+    ///
+    /// Data set here takes precedence over data of the same type registered with
+    /// [`app_data()`](Self::app_data()), [`data_factory()`](Self::data_factory()), or the
+    /// `app_data()` methods of scopes and resources, regardless of the order of calls.
+    ///
+    /// This is intended for tests, where it allows swapping a dependency, such as a database pool,
+    /// for a mock without changing the function that builds the application.
+    ///
+    /// ```
+    /// use actix_web::{test, web, App, HttpResponse};
+    ///
+    /// struct Greeting(&'static str);
+    ///
+    /// fn create_app() -> App<
+    ///     impl actix_web::dev::ServiceFactory<
+    ///         actix_web::dev::ServiceRequest,
+    ///         Config = (),
+    ///         Response = actix_web::dev::ServiceResponse,
+    ///         Error = actix_web::Error,
+    ///         InitError = (),
+    ///     >,
+    /// > {
+    ///     App::new().service(
+    ///         web::scope("/greet")
+    ///             .app_data(web::Data::new(Greeting("hello")))
+    ///             .route("", web::get().to(|greeting: web::Data<Greeting>| async move {
+    ///                 HttpResponse::Ok().body(greeting.0)
+    ///             })),
+    ///     )
+    /// }
+    ///
+    /// # actix_web::rt::System::new().block_on(async {
+    /// let app = test::init_service(
+    ///     create_app().override_app_data(web::Data::new(Greeting("mocked"))),
+    /// )
+    /// .await;
+    ///
+    /// let req = test::TestRequest::get().uri("/greet").to_request();
+    /// let body = test::call_and_read_body(&app, req).await;
+    /// assert_eq!(body, "mocked");
+    /// # });
+    /// ```
+    pub fn override_app_data<U: 'static>(mut self, data: U) -> Self {
+        self.data_overrides
+            .get_or_insert_with(Extensions::new)
+            .insert(data);
+        self.data_override_removers.push(|data| {
+            data.remove::<U>();
+        });
+        self
+    }
+
     /// Add application (root) data after wrapping in `Data<T>`.
     ///
     /// Deprecated in favor of [`app_data`](Self::app_data).
@@ -366,6 +423,8 @@ where
             factory_ref: self.factory_ref,
             external: self.external,
             extensions: self.extensions,
+            data_overrides: self.data_overrides,
+            data_override_removers: self.data_override_removers,
         }
     }
 
@@ -429,6 +488,8 @@ where
             factory_ref: self.factory_ref,
             external: self.external,
             extensions: self.extensions,
+            data_overrides: self.data_overrides,
+            data_override_removers: self.data_override_removers,
         }
     }
 }
@@ -453,6 +514,8 @@ where
             default: self.default,
             factory_ref: self.factory_ref,
             extensions: RefCell::new(Some(self.extensions)),
+            data_overrides: RefCell::new(self.data_overrides),
+            data_override_removers: self.data_override_removers.into_boxed_slice().into(),
         }
     }
 }
@@ -542,6 +605,51 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    #[actix_rt::test]
+    async fn test_override_app_data() {
+        async fn handler(num: web::Data<usize>) -> String {
+            num.to_string()
+        }
+
+        let srv = init_service(
+            App::new()
+                .override_app_data(web::Data::new(1usize))
+                .data_factory(|| ok::<_, ()>(10usize))
+                .service(
+                    web::scope("/scope")
+                        .app_data(web::Data::new(20usize))
+                        .route("", web::get().to(handler)),
+                )
+                .service(
+                    web::resource("/resource")
+                        .app_data(web::Data::new(30usize))
+                        .to(handler),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/scope").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"1"));
+
+        let req = TestRequest::with_uri("/resource").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"1"));
+
+        // other types are unaffected
+        let srv = init_service(
+            App::new()
+                .override_app_data(web::Data::new(1u32))
+                .app_data(web::Data::new(10usize))
+                .route("/", web::get().to(handler)),
+        )
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"10"));
+    }
+
     // allow deprecated App::data
     #[allow(deprecated)]
     #[actix_rt::test]
@@ -8,7 +8,7 @@ use futures_util::future::join_all;
 
 use crate::{
     body::BoxBody,
-    config::{AppConfig, AppService},
+    config::{AppConfig, AppService, RemoveOverriddenData},
     data::FnDataFactory,
     dev::Extensions,
     guard::Guard,
@@ -36,6 +36,8 @@ where
 {
     pub(crate) endpoint: T,
     pub(crate) extensions: RefCell<Option<Extensions>>,
+    pub(crate) data_overrides: RefCell<Option<Extensions>>,
+    pub(crate) data_override_removers: Rc<[RemoveOverriddenData]>,
     pub(crate) async_data_factories: Rc<[FnDataFactory]>,
     pub(crate) services: Rc<RefCell<Vec<Box<dyn AppServiceFactory>>>>,
     pub(crate) default: Option<Rc<BoxedHttpServiceFactory>>,
@@ -71,7 +73,11 @@ where
         });
 
         // create App config to pass to child services
-        let mut config = AppService::new(config, Rc::clone(&default));
+        let mut config = AppService::new(
+            config,
+            Rc::clone(&default),
+            Rc::clone(&self.data_override_removers),
+        );
 
         // register services
         mem::take(&mut *self.services.borrow_mut())
@@ -114,6 +120,9 @@ where
         // take extensions or create new one as app data container.
         let mut app_data = self.extensions.borrow_mut().take().unwrap_or_default();
 
+        // take app data overrides, if any were set
+        let data_overrides = self.data_overrides.borrow_mut().take();
+
         Box::pin(async move {
             // async data factories
             let async_data_factories = factory_futs
@@ -130,6 +139,12 @@ where
                 factory.create(&mut app_data);
             }
 
+            // apply overrides last so they replace root app data of the same types; scope and
+            // resource app data of these types was already removed when they were registered
+            if let Some(data_overrides) = data_overrides {
+                app_data.extend(data_overrides);
+            }
+
             Ok(AppInitService {
                 service,
                 app_data: Rc::new(app_data),
@@ -0,0 +1,199 @@
+//! Bearer authentication extractor.
+
+use std::{borrow::Cow, fmt};
+
+use actix_utils::future::{ready, Ready};
+
+use super::{credentials, AuthenticationError};
+use crate::{dev::Payload, http::StatusCode, web, FromRequest, HttpRequest};
+
+/// Token extracted from an `Authorization` header using the [Bearer] scheme.
+///
+/// Extraction fails with an [`AuthenticationError`] if the header is missing, or with an
+/// `invalid_request` error if it is malformed. The challenge of the error is configured by a
+/// [`BearerAuthConfig`] registered as app data.
+///
+/// # Examples
+/// ```
+/// use actix_web::{auth::BearerAuth, get};
+///
+/// #[get("/")]
+/// async fn index(auth: BearerAuth) -> String {
+///     format!("Your token is {}", auth.token())
+/// }
+/// ```
+///
+/// [Bearer]: https://datatracker.ietf.org/doc/html/rfc6750
+#[derive(Debug, Clone)]
+pub struct BearerAuth {
+    token: String,
+}
+
+impl BearerAuth {
+    /// Returns the bearer token.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Returns true if `token` has the `b64token` syntax of RFC 6750.
+    fn is_valid_token(token: &str) -> bool {
+        let token = token.trim_end_matches('=');
+
+        !token.is_empty()
+            && token
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"-._~+/".contains(&b))
+    }
+}
+
+impl FromRequest for BearerAuth {
+    type Error = AuthenticationError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let error = || {
+            let config = req.app_data::<BearerAuthConfig>().or_else(|| {
+                req.app_data::<web::Data<BearerAuthConfig>>()
+                    .map(|d| d.as_ref())
+            });
+            AuthenticationError::bearer(config.unwrap_or(&BearerAuthConfig::default()))
+        };
+
+        ready(match credentials(req, "Bearer") {
+            Some(token) if Self::is_valid_token(token) => Ok(Self {
+                token: token.to_owned(),
+            }),
+            Some(_) => Err(error().with_error(BearerError::InvalidRequest)),
+            None => Err(error()),
+        })
+    }
+}
+
+/// Configuration of the challenge sent when [`BearerAuth`] extraction fails.
+///
+/// Register as app data on an app, scope, or resource to configure extraction for its handlers.
+///
+/// # Examples
+/// ```
+/// use actix_web::{auth::BearerAuthConfig, web, App};
+///
+/// let app = App::new().service(
+///     web::scope("/api").app_data(BearerAuthConfig::default().realm("api").scope("read")),
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BearerAuthConfig {
+    pub(super) realm: Option<Cow<'static, str>>,
+    pub(super) scope: Option<Cow<'static, str>>,
+}
+
+impl BearerAuthConfig {
+    /// Sets the protection space of the challenge.
+    pub fn realm(mut self, realm: impl Into<Cow<'static, str>>) -> Self {
+        self.realm = Some(realm.into());
+        self
+    }
+
+    /// Sets the space-delimited scope required to access the resource.
+    pub fn scope(mut self, scope: impl Into<Cow<'static, str>>) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+}
+
+/// Error codes of the Bearer scheme, as defined in [RFC 6750 §3.1].
+///
+/// [RFC 6750 §3.1]: https://datatracker.ietf.org/doc/html/rfc6750#section-3.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BearerError {
+    /// The request is malformed. Responds with `400 Bad Request`.
+    InvalidRequest,
+
+    /// The access token is expired, revoked, malformed, or otherwise invalid. Responds with
+    /// `401 Unauthorized`.
+    InvalidToken,
+
+    /// The access token does not grant the required scope. Responds with `403 Forbidden`.
+    InsufficientScope,
+}
+
+impl BearerError {
+    pub(super) fn code(self) -> &'static str {
+        match self {
+            BearerError::InvalidRequest => "invalid_request",
+            BearerError::InvalidToken => "invalid_token",
+            BearerError::InsufficientScope => "insufficient_scope",
+        }
+    }
+
+    pub(super) fn status_code(self) -> StatusCode {
+        match self {
+            BearerError::InvalidRequest => StatusCode::BAD_REQUEST,
+            BearerError::InvalidToken => StatusCode::UNAUTHORIZED,
+            BearerError::InsufficientScope => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+impl fmt::Display for BearerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BearerError::InvalidRequest => "Invalid authentication request",
+            BearerError::InvalidToken => "Invalid access token",
+            BearerError::InsufficientScope => "Insufficient access token scope",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{http::header, test::TestRequest, ResponseError as _};
+
+    #[actix_rt::test]
+    async fn extract() {
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer mF_9.B5f-4.1JqM"))
+            .to_http_request();
+        let auth = BearerAuth::extract(&req).await.unwrap();
+        assert_eq!(auth.token(), "mF_9.B5f-4.1JqM");
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "bearer abc=="))
+            .to_http_request();
+        let auth = BearerAuth::extract(&req).await.unwrap();
+        assert_eq!(auth.token(), "abc==");
+    }
+
+    #[actix_rt::test]
+    async fn extract_failure() {
+        let req = TestRequest::default()
+            .app_data(BearerAuthConfig::default().realm("api"))
+            .to_http_request();
+        let res = BearerAuth::extract(&req)
+            .await
+            .unwrap_err()
+            .error_response();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            res.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+            "Bearer realm=\"api\""
+        );
+
+        for header in ["Bearer", "Bearer a b", "Bearer =abc"] {
+            let req = TestRequest::default()
+                .insert_header((header::AUTHORIZATION, header))
+                .to_http_request();
+            let res = BearerAuth::extract(&req)
+                .await
+                .unwrap_err()
+                .error_response();
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST, "header: {header}");
+            assert_eq!(
+                res.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+                "Bearer error=\"invalid_request\""
+            );
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! TLS client certificate extractor.
+
+use std::{fmt, rc::Rc};
+
+use actix_utils::future::{ready, Ready};
+use bytes::Bytes;
+
+#[cfg(feature = "x509")]
+use super::x509;
+use crate::{dev::Payload, error::ErrorForbidden, Error, FromRequest, HttpRequest};
+
+/// Certificate chain presented by the client of a TLS connection.
+///
+/// When a server is bound using one of the TLS methods of [`HttpServer`](crate::HttpServer), such
+/// as `bind_rustls_0_23()` or `bind_openssl()`, and the client authenticated using a certificate,
+/// the chain is inserted into the connection data. The TLS configuration determines whether client
+/// certificates are requested, required, and verified; this type only exposes what the TLS
+/// backend accepted.
+///
+/// As an extractor, `ClientCert` fails with `403 Forbidden` if the client did not present a
+/// certificate. On routes where a certificate is optional, extract `Option<ClientCert>` instead.
+/// It can also be read from connection data directly, using
+/// [`HttpRequest::conn_data()`], or extracted in the
+/// [`HttpAuthentication`](crate::middleware::HttpAuthentication) middleware.
+///
+/// # Examples
+/// ```
+/// use actix_web::{auth::ClientCert, get};
+///
+/// #[get("/")]
+/// async fn index(cert: ClientCert) -> String {
+///     format!("Hello, client with {} certificates!", cert.chain().len())
+/// }
+///
+/// #[get("/public")]
+/// async fn public(cert: Option<ClientCert>) -> String {
+///     match cert {
+///         Some(cert) => format!("Hello, client with {} certificates!", cert.chain().len()),
+///         None => "Hello, anonymous!".to_owned(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientCert {
+    chain: Rc<[Certificate]>,
+}
+
+impl ClientCert {
+    /// Constructs a certificate chain from DER-encoded certificates, starting with the client's
+    /// own certificate.
+    ///
+    /// Returns `None` if `chain` is empty. This is useful for inserting certificates of custom TLS
+    /// acceptors into connection data using [`HttpServer::on_connect()`].
+    ///
+    /// [`HttpServer::on_connect()`]: crate::HttpServer::on_connect()
+    pub fn from_der<I>(chain: I) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Into<Bytes>,
+    {
+        let chain = chain
+            .into_iter()
+            .map(|der| Certificate { der: der.into() })
+            .collect::<Rc<[_]>>();
+
+        (!chain.is_empty()).then_some(Self { chain })
+    }
+
+    /// Returns the client's own certificate.
+    pub fn leaf(&self) -> &Certificate {
+        &self.chain[0]
+    }
+
+    /// Returns the certificate chain, starting with the client's own certificate.
+    pub fn chain(&self) -> &[Certificate] {
+        &self.chain
+    }
+
+    /// Reads the certificate chain presented by the client of a TLS connection.
+    #[cfg(feature = "__tls")]
+    pub(crate) fn from_connection(io: &dyn std::any::Any) -> Option<Self> {
+        #[cfg(feature = "openssl")]
+        if let Some(io) =
+            io.downcast_ref::<actix_tls::accept::openssl::TlsStream<actix_rt::net::TcpStream>>()
+        {
+            let ssl = io.ssl();
+            let leaf = ssl.peer_certificate()?;
+
+            // on servers, the peer chain does not include the peer's certificate
+            let intermediates = ssl.peer_cert_chain().into_iter().flatten();
+
+            let chain = std::iter::once(&*leaf)
+                .chain(intermediates)
+                .filter_map(|cert| cert.to_der().ok());
+
+            return Self::from_der(chain);
+        }
+
+        macro_rules! from_rustls {
+            ($feature:literal, $module:ident) => {
+                #[cfg(feature = $feature)]
+                if let Some(io) = io.downcast_ref::<actix_tls::accept::$module::TlsStream<
+                    actix_rt::net::TcpStream,
+                >>() {
+                    let (_, conn) = io.get_ref();
+                    let chain = conn.peer_certificates()?;
+                    let chain = chain.iter().map(|cert| Bytes::copy_from_slice(cert.as_ref()));
+                    return Self::from_der(chain);
+                }
+            };
+        }
+
+        from_rustls!("rustls-0_20", rustls_0_20);
+        from_rustls!("rustls-0_21", rustls_0_21);
+        from_rustls!("rustls-0_22", rustls_0_22);
+        from_rustls!("rustls-0_23", rustls_0_23);
+
+        None
+    }
+}
+
+impl FromRequest for ClientCert {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(match req.conn_data::<ClientCert>() {
+            Some(cert) => Ok(cert.clone()),
+            None => {
+                log::debug!(
+                    "Failed to extract `ClientCert` for `{}` handler. The connection is not \
+                    encrypted or the client did not present a certificate.",
+                    req.match_name().unwrap_or(req.path())
+                );
+
+                Err(ErrorForbidden("Client certificate required"))
+            }
+        })
+    }
+}
+
+/// DER-encoded X.509 certificate.
+///
+/// With the `x509` feature enabled, the subject and subject alternative names are parsed on access.
+/// Certificates are not validated; this is the responsibility of the TLS backend.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Certificate {
+    der: Bytes,
+}
+
+impl Certificate {
+    /// Returns the DER encoding of the certificate.
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// Returns the subject distinguished name, formatted as described by [RFC 4514], such as
+    /// `CN=alice,O=Example,C=US`.
+    ///
+    /// Returns `None` if the certificate is malformed.
+    ///
+    /// [RFC 4514]: https://datatracker.ietf.org/doc/html/rfc4514
+    #[cfg(feature = "x509")]
+    pub fn subject(&self) -> Option<String> {
+        x509::subject(&self.der)
+    }
+
+    /// Returns the value of the common name (`CN`) attribute of the subject.
+    ///
+    /// If the subject has multiple common names, the most specific one is returned. Returns `None`
+    /// if the subject has no common name or the certificate is malformed.
+    #[cfg(feature = "x509")]
+    pub fn common_name(&self) -> Option<String> {
+        x509::common_name(&self.der)
+    }
+
+    /// Returns the DNS name, email address, URI, and IP address entries of the subject
+    /// alternative name extension.
+    ///
+    /// Returns an empty list if the certificate has no such extension or is malformed.
+    #[cfg(feature = "x509")]
+    pub fn subject_alt_names(&self) -> Vec<SubjectAltName> {
+        x509::subject_alt_names(&self.der).unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for Certificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("Certificate");
+
+        #[cfg(feature = "x509")]
+        f.field("subject", &self.subject());
+
+        f.finish_non_exhaustive()
+    }
+}
+
+/// Entry of the subject alternative name extension of a [`Certificate`].
+#[cfg(feature = "x509")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SubjectAltName {
+    /// DNS name, such as `example.com`.
+    Dns(String),
+
+    /// Email address, such as `alice@example.com`.
+    Email(String),
+
+    /// URI, such as a SPIFFE ID like `spiffe://example.com/service`.
+    Uri(String),
+
+    /// IP address.
+    Ip(std::net::IpAddr),
+}
+
+#[cfg(test)]
+mod tests {
+    use rcgen::{CertificateParams, DistinguishedName, DnType, KeyPair, SanType};
+
+    use super::*;
+    use crate::{dev::Extensions, http::StatusCode, test::TestRequest};
+
+    fn generate(params: CertificateParams) -> Bytes {
+        let key = KeyPair::generate().unwrap();
+        Bytes::copy_from_slice(params.self_signed(&key).unwrap().der())
+    }
+
+    fn client_cert() -> Bytes {
+        let mut params = CertificateParams::default();
+
+        let mut name = DistinguishedName::new();
+        name.push(DnType::CountryName, "US");
+        name.push(DnType::OrganizationName, "Example, Inc.");
+        name.push(DnType::CommonName, "alice");
+        params.distinguished_name = name;
+
+        params.subject_alt_names = vec![
+            SanType::DnsName("alice.example.com".try_into().unwrap()),
+            SanType::Rfc822Name("alice@example.com".try_into().unwrap()),
+            SanType::URI("spiffe://example.com/alice".try_into().unwrap()),
+            SanType::IpAddress("10.0.0.1".parse().unwrap()),
+            SanType::IpAddress("::1".parse().unwrap()),
+        ];
+
+        generate(params)
+    }
+
+    #[cfg(feature = "x509")]
+    #[test]
+    fn parses_certificates() {
+        let chain =
+            ClientCert::from_der([client_cert(), generate(CertificateParams::default())]).unwrap();
+        assert_eq!(chain.chain().len(), 2);
+
+        let leaf = chain.leaf();
+        assert_eq!(leaf.subject().unwrap(), "CN=alice,O=Example\\, Inc.,C=US");
+        assert_eq!(leaf.common_name().unwrap(), "alice");
+        assert_eq!(
+            leaf.subject_alt_names(),
+            [
+                SubjectAltName::Dns("alice.example.com".to_owned()),
+                SubjectAltName::Email("alice@example.com".to_owned()),
+                SubjectAltName::Uri("spiffe://example.com/alice".to_owned()),
+                SubjectAltName::Ip("10.0.0.1".parse().unwrap()),
+                SubjectAltName::Ip("::1".parse().unwrap()),
+            ]
+        );
+
+        // rcgen's default subject is "CN=rcgen self signed cert"
+        let issuer = &chain.chain()[1];
+        assert_eq!(issuer.common_name().unwrap(), "rcgen self signed cert");
+        assert!(issuer.subject_alt_names().is_empty());
+
+        assert!(ClientCert::from_der(Vec::<Bytes>::new()).is_none());
+
+        let malformed = ClientCert::from_der([Bytes::from_static(b"\x30\x00")]).unwrap();
+        assert!(malformed.leaf().subject().is_none());
+        assert!(malformed.leaf().subject_alt_names().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn extracts_from_connection_data() {
+        let req = TestRequest::default().to_http_request();
+        let res = ClientCert::extract(&req)
+            .await
+            .unwrap_err()
+            .error_response();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert!(Option::<ClientCert>::extract(&req).await.unwrap().is_none());
+
+        let mut conn_data = Extensions::new();
+        let der = client_cert();
+        conn_data.insert(ClientCert::from_der([der.clone()]).unwrap());
+
+        let mut req = TestRequest::default().to_http_request();
+        Rc::get_mut(&mut req.inner).unwrap().conn_data = Some(Rc::new(conn_data));
+        let cert = ClientCert::extract(&req).await.unwrap();
+        assert_eq!(cert.leaf().der(), der);
+        assert!(Option::<ClientCert>::extract(&req).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,261 @@
+//! HTTP and TLS client authentication extractors and errors.
+//!
+//! The [`BasicAuth`] and [`BearerAuth`] extractors parse credentials from the `Authorization`
+//! header of requests using the [Basic] and [Bearer] authentication schemes. When credentials are
+//! missing or malformed, extraction fails with an [`AuthenticationError`], which responds with a
+//! `WWW-Authenticate` challenge.
+//!
+//! Challenges are configured using [`BasicAuthConfig`] and [`BearerAuthConfig`], registered as app
+//! data on an app, scope, or resource. To validate credentials before any handler runs, wrap
+//! services with the [`HttpAuthentication`](crate::middleware::HttpAuthentication) middleware.
+//!
+//! The [`ClientCert`] extractor exposes the certificate chain that clients of TLS connections
+//! authenticated with, for mutual TLS. With the `x509` feature enabled, the subject and subject
+//! alternative names of its certificates are parsed using [`x509-parser`].
+//!
+//! [Basic]: https://datatracker.ietf.org/doc/html/rfc7617
+//! [Bearer]: https://datatracker.ietf.org/doc/html/rfc6750
+//! [`x509-parser`]: https://docs.rs/x509-parser
+
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    http::{
+        header::{self, HeaderValue},
+        StatusCode,
+    },
+    HttpResponse, ResponseError,
+};
+
+mod basic;
+mod bearer;
+mod client_cert;
+#[cfg(feature = "x509")]
+mod x509;
+
+pub use self::{
+    basic::{BasicAuth, BasicAuthConfig},
+    bearer::{BearerAuth, BearerAuthConfig, BearerError},
+    client_cert::{Certificate, ClientCert},
+};
+
+#[cfg(feature = "x509")]
+pub use self::client_cert::SubjectAltName;
+
+/// Authentication error that responds with a `WWW-Authenticate` challenge.
+///
+/// # Examples
+/// ```
+/// use actix_web::{
+///     auth::{AuthenticationError, BearerAuth, BearerAuthConfig, BearerError},
+///     get, HttpRequest,
+/// };
+///
+/// #[get("/")]
+/// async fn index(req: HttpRequest, auth: BearerAuth) -> Result<&'static str, AuthenticationError> {
+///     if auth.token() == "secret" {
+///         return Ok("Welcome!");
+///     }
+///
+///     let config = req.app_data::<BearerAuthConfig>().cloned().unwrap_or_default();
+///
+///     Err(AuthenticationError::bearer(&config)
+///         .with_error(BearerError::InvalidToken)
+///         .with_error_description("The access token expired"))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AuthenticationError {
+    challenge: Challenge,
+}
+
+#[derive(Debug, Clone)]
+enum Challenge {
+    Basic {
+        realm: Option<Cow<'static, str>>,
+    },
+    Bearer {
+        realm: Option<Cow<'static, str>>,
+        scope: Option<Cow<'static, str>>,
+        error: Option<BearerError>,
+        error_description: Option<Cow<'static, str>>,
+    },
+}
+
+impl AuthenticationError {
+    /// Constructs an error with a `Basic` challenge.
+    pub fn basic(config: &BasicAuthConfig) -> Self {
+        Self {
+            challenge: Challenge::Basic {
+                realm: config.realm.clone(),
+            },
+        }
+    }
+
+    /// Constructs an error with a `Bearer` challenge.
+    pub fn bearer(config: &BearerAuthConfig) -> Self {
+        Self {
+            challenge: Challenge::Bearer {
+                realm: config.realm.clone(),
+                scope: config.scope.clone(),
+                error: None,
+                error_description: None,
+            },
+        }
+    }
+
+    /// Sets the error code of a `Bearer` challenge, which also determines the response status.
+    ///
+    /// Has no effect on `Basic` challenges.
+    pub fn with_error(mut self, err: BearerError) -> Self {
+        if let Challenge::Bearer { error, .. } = &mut self.challenge {
+            *error = Some(err);
+        }
+
+        self
+    }
+
+    /// Sets the human-readable error description of a `Bearer` challenge.
+    ///
+    /// Has no effect on `Basic` challenges.
+    pub fn with_error_description(mut self, description: impl Into<Cow<'static, str>>) -> Self {
+        if let Challenge::Bearer {
+            error_description, ..
+        } = &mut self.challenge
+        {
+            *error_description = Some(description.into());
+        }
+
+        self
+    }
+}
+
+impl fmt::Display for AuthenticationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.challenge {
+            Challenge::Basic { .. } | Challenge::Bearer { error: None, .. } => {
+                f.write_str("Authentication required")
+            }
+            Challenge::Bearer {
+                error: Some(error), ..
+            } => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl std::error::Error for AuthenticationError {}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (scheme, params) = match self {
+            Challenge::Basic { realm } => ("Basic", vec![("realm", realm.as_deref())]),
+            Challenge::Bearer {
+                realm,
+                scope,
+                error,
+                error_description,
+            } => (
+                "Bearer",
+                vec![
+                    ("realm", realm.as_deref()),
+                    ("scope", scope.as_deref()),
+                    ("error", error.map(BearerError::code)),
+                    ("error_description", error_description.as_deref()),
+                ],
+            ),
+        };
+
+        f.write_str(scheme)?;
+
+        let params = params
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, value?)));
+
+        for (idx, (name, value)) in params.enumerate() {
+            let sep = if idx == 0 { " " } else { ", " };
+            write!(f, "{sep}{name}=\"")?;
+
+            for ch in value.chars() {
+                if matches!(ch, '"' | '\\') {
+                    f.write_str("\\")?;
+                }
+
+                write!(f, "{ch}")?;
+            }
+
+            f.write_str("\"")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ResponseError for AuthenticationError {
+    fn status_code(&self) -> StatusCode {
+        match &self.challenge {
+            Challenge::Bearer {
+                error: Some(error), ..
+            } => error.status_code(),
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut res = HttpResponse::build(self.status_code());
+
+        if let Ok(challenge) = HeaderValue::from_str(&self.challenge.to_string()) {
+            res.insert_header((header::WWW_AUTHENTICATE, challenge));
+        }
+
+        res.finish()
+    }
+}
+
+/// Returns the credentials of the `Authorization` header of a request, or `None` if the header is
+/// missing or does not use `scheme`.
+fn credentials<'a>(req: &'a crate::HttpRequest, scheme: &str) -> Option<&'a str> {
+    let header = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (req_scheme, credentials) = header.split_once(' ').unwrap_or((header, ""));
+
+    req_scheme
+        .eq_ignore_ascii_case(scheme)
+        .then(|| credentials.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenge_formatting() {
+        let config = BasicAuthConfig::default();
+        let err = AuthenticationError::basic(&config);
+        assert_eq!(err.challenge.to_string(), "Basic");
+
+        let config = BasicAuthConfig::default().realm("Admin \"area\"");
+        let err = AuthenticationError::basic(&config);
+        assert_eq!(err.challenge.to_string(), r#"Basic realm="Admin \"area\"""#);
+
+        let config = BearerAuthConfig::default().realm("api").scope("read write");
+        let err = AuthenticationError::bearer(&config)
+            .with_error(BearerError::InsufficientScope)
+            .with_error_description("Needs write");
+        assert_eq!(
+            err.challenge.to_string(),
+            r#"Bearer realm="api", scope="read write", error="insufficient_scope", error_description="Needs write""#
+        );
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn error_response() {
+        let config = BearerAuthConfig::default().realm("api");
+        let res = AuthenticationError::bearer(&config).error_response();
+
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            res.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+            "Bearer realm=\"api\""
+        );
+    }
+}
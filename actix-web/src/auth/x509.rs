@@ -0,0 +1,181 @@
+//! Accessors for the parts of X.509 certificates exposed by [`Certificate`].
+//!
+//! [`Certificate`]: super::Certificate
+
+use std::{
+    fmt::Write as _,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use x509_parser::{
+    der_parser::asn1_rs::{Any, Tag, ToDer as _},
+    extensions::GeneralName,
+    oid_registry::{self, Oid},
+    prelude::{FromDer as _, X509Certificate},
+};
+
+use super::SubjectAltName;
+
+/// Short names of attribute types in distinguished names, as listed by RFC 4514 §3.
+const ATTRIBUTE_NAMES: &[(Oid<'static>, &str)] = &[
+    (oid_registry::OID_X509_COMMON_NAME, "CN"),
+    (oid_registry::OID_X509_COUNTRY_NAME, "C"),
+    (oid_registry::OID_X509_LOCALITY_NAME, "L"),
+    (oid_registry::OID_X509_STATE_OR_PROVINCE_NAME, "ST"),
+    (oid_registry::OID_X509_STREET_ADDRESS, "STREET"),
+    (oid_registry::OID_X509_ORGANIZATION_NAME, "O"),
+    (oid_registry::OID_X509_ORGANIZATIONAL_UNIT, "OU"),
+    (oid_registry::OID_DOMAIN_COMPONENT, "DC"),
+    (oid_registry::OID_USERID, "UID"),
+];
+
+fn parse(der: &[u8]) -> Option<X509Certificate<'_>> {
+    X509Certificate::from_der(der).ok().map(|(_, cert)| cert)
+}
+
+/// Formats the subject of a certificate as a distinguished name string, in the format of RFC 4514.
+pub(super) fn subject(der: &[u8]) -> Option<String> {
+    let cert = parse(der)?;
+    let rdns = cert.subject().iter_rdn().collect::<Vec<_>>();
+    let mut out = String::new();
+
+    // RFC 4514 lists relative distinguished names in reverse order
+    for (idx, rdn) in rdns.into_iter().rev().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+
+        for (idx, attribute) in rdn.iter().enumerate() {
+            if idx > 0 {
+                out.push('+');
+            }
+
+            let oid = attribute.attr_type();
+
+            match ATTRIBUTE_NAMES.iter().find(|(known, _)| known == oid) {
+                Some((_, name)) => out.push_str(name),
+                None => out.push_str(&oid.to_id_string()),
+            }
+
+            out.push('=');
+
+            match string_value(attribute.attr_value()) {
+                Some(value) => escape_value(&mut out, &value),
+
+                // values of unknown types are written as hex-encoded DER
+                None => {
+                    out.push('#');
+
+                    for b in attribute.attr_value().to_der_vec().ok()? {
+                        write!(out, "{b:02x}").unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    Some(out)
+}
+
+/// Returns the value of the most specific common name attribute of the subject of a certificate.
+pub(super) fn common_name(der: &[u8]) -> Option<String> {
+    parse(der)?
+        .subject()
+        .iter_common_name()
+        .last()
+        .and_then(|attribute| string_value(attribute.attr_value()))
+}
+
+/// Returns the supported entries of the subject alternative name extension of a certificate.
+pub(super) fn subject_alt_names(der: &[u8]) -> Option<Vec<SubjectAltName>> {
+    let cert = parse(der)?;
+
+    let Some(ext) = cert.subject_alternative_name().ok()? else {
+        return Some(Vec::new());
+    };
+
+    let names = ext
+        .value
+        .general_names
+        .iter()
+        .filter_map(|name| match *name {
+            GeneralName::DNSName(name) => Some(SubjectAltName::Dns(name.to_owned())),
+            GeneralName::RFC822Name(name) => Some(SubjectAltName::Email(name.to_owned())),
+            GeneralName::URI(name) => Some(SubjectAltName::Uri(name.to_owned())),
+            GeneralName::IPAddress(ip) => match ip.len() {
+                4 => Some(SubjectAltName::Ip(IpAddr::V4(Ipv4Addr::from(
+                    <[u8; 4]>::try_from(ip).ok()?,
+                )))),
+                16 => Some(SubjectAltName::Ip(IpAddr::V6(Ipv6Addr::from(
+                    <[u8; 16]>::try_from(ip).ok()?,
+                )))),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    Some(names)
+}
+
+/// Decodes the directory string types that are used in practice.
+fn string_value(value: &Any<'_>) -> Option<String> {
+    let data = value.as_bytes();
+
+    match value.header.tag() {
+        Tag::Utf8String => String::from_utf8(data.to_vec()).ok(),
+
+        Tag::PrintableString | Tag::Ia5String => data
+            .is_ascii()
+            .then(|| String::from_utf8_lossy(data).into_owned()),
+
+        // decoded as Latin-1
+        Tag::TeletexString => Some(data.iter().map(|&b| char::from(b)).collect()),
+
+        Tag::BmpString if data.len() % 2 == 0 => {
+            let units = data
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]));
+            char::decode_utf16(units).collect::<Result<_, _>>().ok()
+        }
+
+        _ => None,
+    }
+}
+
+/// Writes an attribute value, escaping characters as required by RFC 4514 §2.4.
+fn escape_value(out: &mut String, value: &str) {
+    let last = value.chars().count().saturating_sub(1);
+
+    for (idx, ch) in value.chars().enumerate() {
+        match ch {
+            '"' | '+' | ',' | ';' | '<' | '>' | '\\' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            '#' if idx == 0 => out.push_str("\\#"),
+            ' ' if idx == 0 || idx == last => out.push_str("\\ "),
+            '\0' => out.push_str("\\00"),
+            _ => out.push(ch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaping() {
+        let mut out = String::new();
+        escape_value(&mut out, "# Doe, \"John\" ");
+        assert_eq!(out, "\\# Doe\\, \\\"John\\\"\\ ");
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(subject(b"").is_none());
+        assert!(subject(&[0x30, 0x82, 0xff]).is_none());
+        assert!(subject_alt_names(&[0x30, 0x03, 0x30, 0x01]).is_none());
+    }
+}
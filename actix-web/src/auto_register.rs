@@ -0,0 +1,74 @@
+//! Automatic registration of handlers declared using routing macros.
+
+use crate::dev::{AppService, HttpServiceFactory};
+
+/// A handler that registered itself for collection by [`collect_services!`](crate::collect_services).
+#[doc(hidden)]
+pub struct AutoService {
+    path: &'static str,
+    register: fn(&mut AppService),
+}
+
+impl AutoService {
+    #[doc(hidden)]
+    pub const fn __new(path: &'static str, register: fn(&mut AppService)) -> Self {
+        Self { path, register }
+    }
+}
+
+inventory::collect!(AutoService);
+
+/// Service factory that registers all handlers declared using routing macros.
+///
+/// Returned by [`collect_services!`](crate::collect_services).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutoServices;
+
+impl HttpServiceFactory for AutoServices {
+    fn register(self, config: &mut AppService) {
+        let mut services = inventory::iter::<AutoService>
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        // registration order is not otherwise defined; sort by path so it is at least consistent
+        services.sort_by_key(|service| service.path);
+
+        for service in services {
+            (service.register)(config);
+        }
+    }
+}
+
+/// Collects all handlers declared using routing macros into a service that registers them.
+///
+/// Requires the `auto-register` crate feature. Handlers declared using routing macros with the
+/// `auto_register = true` argument, in all crates linked into the binary, register themselves for
+/// collection. This avoids maintaining long chains of `.service()` calls that are easy to forget
+/// to update.
+///
+/// Handlers are registered in order of their path. Since resources are matched in registration
+/// order, use guards to disambiguate overlapping routes or register such handlers manually. Note
+/// that handlers registered using `.service()` as well as this macro are registered twice.
+///
+/// # Examples
+/// ```
+/// use actix_web::{collect_services, get, App, HttpResponse};
+///
+/// #[get("/", auto_register = true)]
+/// async fn index() -> HttpResponse {
+///     HttpResponse::Ok().finish()
+/// }
+///
+/// #[get("/health", auto_register = true)]
+/// async fn health() -> HttpResponse {
+///     HttpResponse::Ok().finish()
+/// }
+///
+/// let app = App::new().service(collect_services!());
+/// ```
+#[macro_export]
+macro_rules! collect_services {
+    () => {
+        $crate::dev::AutoServices
+    };
+}
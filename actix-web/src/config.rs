@@ -18,11 +18,15 @@ use crate::{
 
 type Guards = Vec<Box<dyn Guard>>;
 
+/// Removes app data of a type overridden using [`App::override_app_data()`](crate::App::override_app_data).
+pub(crate) type RemoveOverriddenData = fn(&mut Extensions);
+
 /// Application configuration
 pub struct AppService {
     config: AppConfig,
     root: bool,
     default: Rc<BoxedHttpServiceFactory>,
+    data_override_removers: Rc<[RemoveOverriddenData]>,
     #[allow(clippy::type_complexity)]
     services: Vec<(
         ResourceDef,
@@ -34,10 +38,15 @@ pub struct AppService {
 
 impl AppService {
     /// Crate server settings instance.
-    pub(crate) fn new(config: AppConfig, default: Rc<BoxedHttpServiceFactory>) -> Self {
+    pub(crate) fn new(
+        config: AppConfig,
+        default: Rc<BoxedHttpServiceFactory>,
+        data_override_removers: Rc<[RemoveOverriddenData]>,
+    ) -> Self {
         AppService {
             config,
             default,
+            data_override_removers,
             root: true,
             services: Vec::new(),
         }
@@ -69,11 +78,22 @@ impl AppService {
         AppService {
             config: self.config.clone(),
             default: Rc::clone(&self.default),
+            data_override_removers: Rc::clone(&self.data_override_removers),
             services: Vec::new(),
             root: false,
         }
     }
 
+    /// Removes app data of types overridden using [`App::override_app_data()`] from a scope or
+    /// resource's app data, so that lookups find the overrides in the root app data instead.
+    ///
+    /// [`App::override_app_data()`]: crate::App::override_app_data
+    pub(crate) fn remove_overridden_data(&self, data: &mut Extensions) {
+        for remove in self.data_override_removers.iter() {
+            remove(data);
+        }
+    }
+
     /// Returns reference to configuration.
     pub fn config(&self) -> &AppConfig {
         &self.config
@@ -0,0 +1,321 @@
+use std::{
+    any::{type_name, Any},
+    fmt, io,
+    net::SocketAddr,
+    ops::Deref,
+    time::Duration,
+};
+
+use actix_rt::net::TcpStream;
+use actix_utils::future::{err, ok, Ready};
+use socket2::{SockRef, TcpKeepalive};
+
+use crate::{
+    dev::{Extensions, Payload},
+    error::ErrorInternalServerError,
+    Error, FromRequest, HttpRequest,
+};
+
+/// Connection that is about to be handled, passed to [`HttpServer::on_connection()`].
+///
+/// Allows tuning the underlying TCP socket, storing per-connection data that handlers can read
+/// using the [`ConnData`] extractor, and registering callbacks that run when the connection closes.
+///
+/// [`HttpServer::on_connection()`]: crate::HttpServer::on_connection()
+pub struct Connection<'a> {
+    io: &'a dyn Any,
+    extensions: &'a mut Extensions,
+}
+
+impl<'a> Connection<'a> {
+    pub(crate) fn new(io: &'a dyn Any, extensions: &'a mut Extensions) -> Self {
+        Self { io, extensions }
+    }
+
+    /// Returns the underlying connection type.
+    ///
+    /// See [`HttpServer::on_connect()`](crate::HttpServer::on_connect()) for the possible types.
+    pub fn io(&self) -> &dyn Any {
+        self.io
+    }
+
+    /// Returns the TCP stream of the connection, including when it is encrypted using TLS.
+    ///
+    /// Returns `None` for other transports, such as Unix domain sockets.
+    pub fn tcp_stream(&self) -> Option<&TcpStream> {
+        if let Some(stream) = self.io.downcast_ref::<TcpStream>() {
+            return Some(stream);
+        }
+
+        #[cfg(feature = "openssl")]
+        if let Some(stream) = self
+            .io
+            .downcast_ref::<actix_tls::accept::openssl::TlsStream<TcpStream>>()
+        {
+            return Some(stream.get_ref());
+        }
+
+        macro_rules! from_rustls {
+            ($feature:literal, $module:ident) => {
+                #[cfg(feature = $feature)]
+                if let Some(stream) = self
+                    .io
+                    .downcast_ref::<actix_tls::accept::$module::TlsStream<TcpStream>>()
+                {
+                    return Some(stream.get_ref().0);
+                }
+            };
+        }
+
+        from_rustls!("rustls-0_20", rustls_0_20);
+        from_rustls!("rustls-0_21", rustls_0_21);
+        from_rustls!("rustls-0_22", rustls_0_22);
+        from_rustls!("rustls-0_23", rustls_0_23);
+
+        None
+    }
+
+    /// Returns the address of the peer, if the connection uses TCP.
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.tcp_stream()?.peer_addr().ok()
+    }
+
+    /// Returns the local address that the connection was accepted on, if it uses TCP.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.tcp_stream()?.local_addr().ok()
+    }
+
+    /// Sets the `TCP_NODELAY` option of the socket, which disables Nagle's algorithm.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`Unsupported`](io::ErrorKind::Unsupported) if the connection does
+    /// not use TCP, or the error of setting the option.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.require_tcp_stream()?.set_nodelay(nodelay)
+    }
+
+    /// Enables TCP keep-alive probes on the socket after it has been idle for `idle`, or disables
+    /// them if `idle` is `None`.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`Unsupported`](io::ErrorKind::Unsupported) if the connection does
+    /// not use TCP, or the error of setting the option.
+    pub fn set_keepalive(&self, idle: Option<Duration>) -> io::Result<()> {
+        let sock = SockRef::from(self.require_tcp_stream()?);
+
+        match idle {
+            Some(idle) => sock.set_tcp_keepalive(&TcpKeepalive::new().with_time(idle)),
+            None => sock.set_keepalive(false),
+        }
+    }
+
+    /// Inserts per-connection data, replacing any existing data of the same type.
+    ///
+    /// The data can be read in handlers using the [`ConnData`] extractor or
+    /// [`HttpRequest::conn_data()`].
+    pub fn insert<T: 'static>(&mut self, data: T) {
+        self.extensions.insert(data);
+    }
+
+    /// Returns the per-connection data container.
+    pub fn extensions(&self) -> &Extensions {
+        self.extensions
+    }
+
+    /// Returns the per-connection data container mutably.
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        self.extensions
+    }
+
+    /// Registers a callback that is called once the connection has closed.
+    ///
+    /// The callback runs on the worker thread after the connection is closed and all requests
+    /// received on it have been dropped. Callbacks run in the order they were registered.
+    pub fn on_disconnect<F>(&mut self, callback: F)
+    where
+        F: FnOnce() + 'static,
+    {
+        match self.extensions.get_mut::<DisconnectCallbacks>() {
+            Some(callbacks) => callbacks.0.push(Box::new(callback)),
+            None => {
+                self.extensions
+                    .insert(DisconnectCallbacks(vec![Box::new(callback)]));
+            }
+        }
+    }
+
+    fn require_tcp_stream(&self) -> io::Result<&TcpStream> {
+        self.tcp_stream().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Unsupported, "connection does not use TCP")
+        })
+    }
+}
+
+impl fmt::Debug for Connection<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connection")
+            .field("peer_addr", &self.peer_addr())
+            .field("local_addr", &self.local_addr())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Callbacks run when connection data is dropped, which happens after the connection closes.
+struct DisconnectCallbacks(Vec<Box<dyn FnOnce()>>);
+
+impl Drop for DisconnectCallbacks {
+    fn drop(&mut self) {
+        for callback in self.0.drain(..) {
+            callback();
+        }
+    }
+}
+
+/// Per-connection data extractor.
+///
+/// Connection data is arbitrary data attached to a connection, usually in an
+/// [`HttpServer::on_connection()`] callback. It is shared by all requests received on the
+/// connection.
+///
+/// Since extractors must output owned data, only types that `impl Clone` can use this extractor.
+/// Use a shared type such as `Rc<Cell<T>>` for data that should be mutated by handlers, such as a
+/// per-connection request quota.
+///
+/// If the data is not present, extraction fails with `500 Internal Server Error`. Use
+/// `Option<ConnData<T>>` if it is not guaranteed to be present.
+///
+/// # Examples
+/// ```no_run
+/// use std::{cell::Cell, rc::Rc};
+///
+/// use actix_web::{web, App, HttpResponse, HttpServer};
+///
+/// #[derive(Clone)]
+/// struct RequestCount(Rc<Cell<u32>>);
+///
+/// async fn index(count: web::ConnData<RequestCount>) -> HttpResponse {
+///     count.0.set(count.0.get() + 1);
+///
+///     if count.0.get() > 100 {
+///         return HttpResponse::TooManyRequests().finish();
+///     }
+///
+///     HttpResponse::Ok().body(format!("request {} on this connection", count.0.get()))
+/// }
+///
+/// # #[actix_web::main]
+/// # async fn main() -> std::io::Result<()> {
+/// HttpServer::new(|| App::new().route("/", web::get().to(index)))
+///     .on_connection(|conn| {
+///         conn.set_nodelay(true).unwrap();
+///         conn.insert(RequestCount(Rc::default()));
+///
+///         let peer_addr = conn.peer_addr();
+///         conn.on_disconnect(move || log::debug!("{peer_addr:?} disconnected"));
+///     })
+///     .bind(("127.0.0.1", 8080))?
+///     .run()
+///     .await
+/// # }
+/// ```
+///
+/// [`HttpServer::on_connection()`]: crate::HttpServer::on_connection()
+#[derive(Debug, Clone)]
+pub struct ConnData<T: Clone + 'static>(T);
+
+impl<T: Clone + 'static> ConnData<T> {
+    /// Consumes the `ConnData`, returning its wrapped data.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Clone + 'static> Deref for ConnData<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Clone + 'static> FromRequest for ConnData<T> {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if let Some(data) = req.conn_data::<T>() {
+            ok(ConnData(data.clone()))
+        } else {
+            log::debug!(
+                "Failed to construct ConnData extractor. Request path: {:?} (type: {})",
+                req.path(),
+                type_name::<T>(),
+            );
+            err(ErrorInternalServerError("Missing expected connection data"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use crate::test::TestRequest;
+
+    #[actix_rt::test]
+    async fn conn_data_extractor() {
+        let req = TestRequest::default().to_http_request();
+        assert!(ConnData::<u32>::extract(&req).await.is_err());
+
+        let closed = Rc::new(Cell::new(0));
+
+        let mut extensions = Extensions::new();
+        let io = ();
+        let mut conn = Connection::new(&io, &mut extensions);
+        assert!(conn.tcp_stream().is_none());
+        assert_eq!(
+            conn.set_nodelay(true).unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+
+        conn.insert(42_u32);
+        for n in [1, 2] {
+            let closed = Rc::clone(&closed);
+            conn.on_disconnect(move || {
+                assert_eq!(closed.get(), n - 1);
+                closed.set(n);
+            });
+        }
+
+        let mut req = TestRequest::default().to_http_request();
+        Rc::get_mut(&mut req.inner).unwrap().conn_data = Some(Rc::new(extensions));
+
+        let data = ConnData::<u32>::extract(&req).await.unwrap();
+        assert_eq!(*data, 42);
+        assert_eq!(closed.get(), 0);
+
+        drop(req);
+        assert_eq!(closed.get(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn tunes_tcp_sockets() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (client, _) = tokio::join!(TcpStream::connect(addr), listener.accept());
+        let client = client.unwrap();
+
+        let mut extensions = Extensions::new();
+        let conn = Connection::new(&client, &mut extensions);
+        assert_eq!(conn.peer_addr(), Some(addr));
+
+        conn.set_nodelay(true).unwrap();
+        assert!(client.nodelay().unwrap());
+
+        conn.set_keepalive(Some(Duration::from_secs(60))).unwrap();
+        assert!(SockRef::from(&client).keepalive().unwrap());
+        conn.set_keepalive(None).unwrap();
+        assert!(!SockRef::from(&client).keepalive().unwrap());
+    }
+}
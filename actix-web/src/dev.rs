@@ -17,12 +17,26 @@ pub use actix_service::{
     always_ready, fn_factory, fn_service, forward_ready, Service, ServiceFactory, Transform,
 };
 
+#[cfg(feature = "auto-register")]
+#[doc(hidden)]
+pub use inventory;
+
+#[cfg(feature = "auto-register")]
+#[doc(hidden)]
+pub use crate::auto_register::AutoService;
+#[cfg(feature = "auto-register")]
+pub use crate::auto_register::AutoServices;
+
 #[doc(hidden)]
 pub use crate::handler::Handler;
+#[doc(hidden)]
+pub use crate::rmap::push_encoded_segment;
 pub use crate::{
     config::{AppConfig, AppService},
+    connection::Connection,
     info::{ConnectionInfo, PeerAddr},
     rmap::ResourceMap,
+    route_metadata::{ResponseMetadata, RouteMetadata},
     service::{HttpServiceFactory, ServiceRequest, ServiceResponse, WebService},
     types::{JsonBody, Readlines, UrlEncoded},
 };
@@ -5,6 +5,9 @@
 // expanded manually.
 //
 // See <https://github.com/rust-lang/rust/issues/83375>
+#[cfg(feature = "validator")]
+use std::collections::BTreeMap;
+
 pub use actix_http::error::{ContentTypeError, DispatchError, HttpError, ParseError, PayloadError};
 use derive_more::derive::{Display, Error, From};
 use serde_json::error::Error as JsonError;
@@ -17,10 +20,12 @@ use crate::http::StatusCode;
 mod error;
 mod internal;
 mod macros;
+mod problem;
 mod response_error;
 
 pub(crate) use self::macros::{downcast_dyn, downcast_get_type_id};
-pub use self::{error::Error, internal::*, response_error::ResponseError};
+pub(crate) use self::problem::PROBLEM_JSON;
+pub use self::{error::Error, internal::*, problem::Problem, response_error::ResponseError};
 
 /// A convenience [`Result`](std::result::Result) for Actix Web operations.
 ///
@@ -70,6 +75,15 @@ pub enum UrlencodedError {
     )]
     Overflow { size: usize, limit: usize },
 
+    /// Value of a form field is longer than allowed.
+    #[display(
+        "URL encoded field `{}` is longer than allowed (limit: {} bytes).",
+        name,
+        limit
+    )]
+    #[from(skip)]
+    FieldOverflow { name: String, limit: usize },
+
     /// Payload size is now known.
     #[display("Payload size is now known.")]
     UnknownLength,
@@ -82,6 +96,12 @@ pub enum UrlencodedError {
     #[display("Parse error: {}.", _0)]
     Parse(FormDeError),
 
+    /// Nested parse error.
+    #[cfg(feature = "query-deep")]
+    #[display("Parse error: {}.", _0)]
+    #[from(skip)]
+    ParseNested(serde_qs::Error),
+
     /// Encoding error.
     #[display("Encoding error.")]
     Encoding,
@@ -98,7 +118,7 @@ pub enum UrlencodedError {
 impl ResponseError for UrlencodedError {
     fn status_code(&self) -> StatusCode {
         match self {
-            Self::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Overflow { .. } | Self::FieldOverflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
             Self::UnknownLength => StatusCode::LENGTH_REQUIRED,
             Self::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             Self::Payload(err) => err.status_code(),
@@ -161,13 +181,348 @@ impl ResponseError for JsonPayloadError {
     }
 }
 
-/// A set of errors that can occur during parsing request paths
+/// A set of errors that can occur while streaming payloads to temporary files.
+#[cfg(feature = "tempfile")]
+#[derive(Debug, Display, Error, From)]
+#[non_exhaustive]
+pub enum BodyFileError {
+    /// Payload error
+    #[display("Error that occur during reading payload: {}", _0)]
+    Payload(PayloadError),
+
+    /// File I/O error
+    #[display("File I/O error: {}", _0)]
+    Io(std::io::Error),
+}
+
+#[cfg(feature = "tempfile")]
+impl ResponseError for BodyFileError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Payload(err) => err.status_code(),
+            Self::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// A set of errors that can occur during parsing CSV payloads and serializing CSV responses.
+#[cfg(feature = "csv")]
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum CsvPayloadError {
+    /// Content type error
+    #[display("Content type error")]
+    ContentType,
+
+    /// Deserialize error
+    #[display("CSV deserialize error: {}", _0)]
+    Deserialize(csv::Error),
+
+    /// Serialize error
+    #[display("CSV serialize error: {}", _0)]
+    Serialize(csv::Error),
+
+    /// Payload error
+    #[display("Error that occur during reading payload: {}", _0)]
+    Payload(PayloadError),
+}
+
+#[cfg(feature = "csv")]
+impl From<PayloadError> for CsvPayloadError {
+    fn from(err: PayloadError) -> Self {
+        Self::Payload(err)
+    }
+}
+
+#[cfg(feature = "csv")]
+impl ResponseError for CsvPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Serialize(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Payload(err) => err.status_code(),
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// A set of errors that can occur during parsing MessagePack payloads and serializing MessagePack
+/// responses.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum MsgPackPayloadError {
+    /// Payload size is bigger than allowed & content length header set. (default: 2MB)
+    #[display(
+        "MessagePack payload ({} bytes) is larger than allowed (limit: {} bytes).",
+        length,
+        limit
+    )]
+    OverflowKnownLength { length: usize, limit: usize },
+
+    /// Payload size is bigger than allowed but no content length header set. (default: 2MB)
+    #[display("MessagePack payload has exceeded limit ({} bytes).", limit)]
+    Overflow { limit: usize },
+
+    /// Content type error
+    #[display("Content type error")]
+    ContentType,
+
+    /// Deserialize error
+    #[display("MessagePack deserialize error: {}", _0)]
+    Deserialize(rmp_serde::decode::Error),
+
+    /// Serialize error
+    #[display("MessagePack serialize error: {}", _0)]
+    Serialize(rmp_serde::encode::Error),
+
+    /// Payload error
+    #[display("Error that occur during reading payload: {}", _0)]
+    Payload(PayloadError),
+}
+
+#[cfg(feature = "msgpack")]
+impl From<PayloadError> for MsgPackPayloadError {
+    fn from(err: PayloadError) -> Self {
+        Self::Payload(err)
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl ResponseError for MsgPackPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::OverflowKnownLength { .. } | Self::Overflow { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            Self::Serialize(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Payload(err) => err.status_code(),
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// A set of errors that can occur during parsing Protocol Buffers payloads and serializing
+/// Protocol Buffers responses.
+#[cfg(feature = "protobuf")]
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum ProtobufPayloadError {
+    /// Content type error
+    #[display("Content type error")]
+    ContentType,
+
+    /// Protocol Buffers decode error
+    #[display("Protobuf decode error: {}", _0)]
+    Decode(prost::DecodeError),
+
+    /// JSON fallback deserialize error
+    #[display("Json deserialize error: {}", _0)]
+    JsonDeserialize(JsonError),
+
+    /// JSON fallback serialize error
+    #[display("Json serialize error: {}", _0)]
+    JsonSerialize(JsonError),
+
+    /// Payload error
+    #[display("Error that occur during reading payload: {}", _0)]
+    Payload(PayloadError),
+}
+
+#[cfg(feature = "protobuf")]
+impl From<PayloadError> for ProtobufPayloadError {
+    fn from(err: PayloadError) -> Self {
+        Self::Payload(err)
+    }
+}
+
+#[cfg(feature = "protobuf")]
+impl ResponseError for ProtobufPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::JsonSerialize(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Payload(err) => err.status_code(),
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// Error returned by the [`Valid`](crate::web::Valid) extractor when extracted data fails
+/// validation.
+///
+/// The default error response has a `422 Unprocessable Entity` status code and a JSON body listing
+/// the messages of each invalid field, as returned by [`field_messages()`](Self::field_messages):
+///
+/// ```json
+/// { "errors": { "email": ["email"], "items[0].name": ["must not be empty"] } }
+/// ```
+#[cfg(feature = "validator")]
+#[derive(Debug, Display, Error)]
+#[display("Validation error: {}", _0)]
+pub struct ValidateError(#[error(not(source))] validator::ValidationErrors);
+
+#[cfg(feature = "validator")]
+impl ValidateError {
+    pub(crate) fn new(errors: validator::ValidationErrors) -> Self {
+        Self(errors)
+    }
+
+    /// Returns the underlying validation errors.
+    pub fn errors(&self) -> &validator::ValidationErrors {
+        &self.0
+    }
+
+    /// Unwraps into the underlying validation errors.
+    pub fn into_errors(self) -> validator::ValidationErrors {
+        self.0
+    }
+
+    /// Returns the messages of each invalid field, keyed by the path to the field.
+    ///
+    /// Paths of nested fields are joined with `.`, and list items are indexed using `[n]`. Errors
+    /// without a message are represented by their code, such as `length`.
+    pub fn field_messages(&self) -> BTreeMap<String, Vec<String>> {
+        fn collect(
+            errors: &validator::ValidationErrors,
+            prefix: &str,
+            messages: &mut BTreeMap<String, Vec<String>>,
+        ) {
+            use validator::ValidationErrorsKind;
+
+            for (field, kind) in errors.errors() {
+                let path = if prefix.is_empty() {
+                    field.to_string()
+                } else {
+                    format!("{prefix}.{field}")
+                };
+
+                match kind {
+                    ValidationErrorsKind::Field(errors) => {
+                        messages.entry(path).or_default().extend(
+                            errors
+                                .iter()
+                                .map(|err| err.message.as_ref().unwrap_or(&err.code).to_string()),
+                        );
+                    }
+                    ValidationErrorsKind::Struct(errors) => collect(errors, &path, messages),
+                    ValidationErrorsKind::List(items) => {
+                        for (idx, errors) in items {
+                            collect(errors, &format!("{path}[{idx}]"), messages);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut messages = BTreeMap::new();
+        collect(&self.0, "", &mut messages);
+        messages
+    }
+}
+
+/// Return `UnprocessableEntity` with a JSON body of per-field messages for `ValidateError`.
+#[cfg(feature = "validator")]
+impl ResponseError for ValidateError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+
+    fn error_response(&self) -> crate::HttpResponse {
+        crate::HttpResponse::build(self.status_code())
+            .json(serde_json::json!({ "errors": self.field_messages() }))
+    }
+}
+
+/// A set of errors that can occur while producing [`Negotiate`](crate::web::Negotiate) responses.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum NegotiateError {
+    /// None of the available content types are acceptable to the client.
+    #[display("None of the available content types are acceptable")]
+    NotAcceptable,
+
+    /// JSON serialize error
+    #[display("Json serialize error: {}", _0)]
+    Json(JsonError),
+
+    /// CBOR serialize error
+    #[cfg(feature = "cbor")]
+    #[display("CBOR serialize error: {}", _0)]
+    Cbor(ciborium::ser::Error<std::io::Error>),
+
+    /// MessagePack serialize error
+    #[cfg(feature = "msgpack")]
+    #[display("MessagePack serialize error: {}", _0)]
+    MsgPack(rmp_serde::encode::Error),
+
+    /// XML serialize error
+    #[cfg(feature = "xml")]
+    #[display("XML serialize error: {}", _0)]
+    Xml(quick_xml::SeError),
+}
+
+/// Return `NotAcceptable` for `NegotiateError::NotAcceptable` and `InternalServerError` for
+/// serialize errors.
+impl ResponseError for NegotiateError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NotAcceptable => StatusCode::NOT_ACCEPTABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// A set of errors that can occur while resolving the [`RealIp`](crate::web::RealIp) of a client.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum RealIpError {
+    /// The peer address of the connection is not known.
+    #[display("Missing peer address")]
+    MissingPeerAddr,
+
+    /// An address forwarded by a trusted proxy is not a valid IP address.
+    #[display("Invalid forwarded address")]
+    InvalidForwardedAddr,
+}
+
+/// Return `BadRequest` for invalid forwarded addresses and `InternalServerError` otherwise.
+impl ResponseError for RealIpError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::InvalidForwardedAddr => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
 #[derive(Debug, Display, Error)]
 #[non_exhaustive]
 pub enum PathError {
     /// Deserialize error
     #[display("Path deserialize error: {}", _0)]
     Deserialize(serde::de::value::Error),
+
+    /// Deserialize error of a specific path segment.
+    #[display("Path deserialize error: invalid segment `{}`: {}", name, source)]
+    Segment {
+        /// Name of the segment, as given in the resource definition.
+        name: String,
+
+        /// Value of the segment, as found in the request path.
+        value: String,
+
+        /// Underlying deserialize error.
+        source: serde::de::value::Error,
+    },
+}
+
+impl PathError {
+    /// Returns the name of the path segment that failed to deserialize, if known.
+    pub fn segment(&self) -> Option<&str> {
+        match self {
+            Self::Segment { name, .. } => Some(name),
+            Self::Deserialize(_) => None,
+        }
+    }
 }
 
 /// Return `BadRequest` for `PathError`
@@ -184,6 +539,12 @@ pub enum QueryPayloadError {
     /// Query deserialize error.
     #[display("Query deserialize error: {}", _0)]
     Deserialize(serde::de::value::Error),
+
+    /// Nested query deserialize error.
+    #[cfg(feature = "query-deep")]
+    #[display("Query deserialize error: {}", _0)]
+    #[from(skip)]
+    DeserializeDeep(serde_qs::Error),
 }
 
 impl ResponseError for QueryPayloadError {
@@ -192,6 +553,65 @@ impl ResponseError for QueryPayloadError {
     }
 }
 
+/// A set of errors that can occur during parsing pagination query parameters.
+#[derive(Debug, Display, Error)]
+#[non_exhaustive]
+pub enum PaginationError {
+    /// Page number is not a positive integer.
+    #[display("Page number must be a positive integer")]
+    InvalidPage,
+
+    /// Page size is not a positive integer.
+    #[display("Page size must be a positive integer")]
+    InvalidPerPage,
+}
+
+/// Return `BadRequest` for `PaginationError`
+impl ResponseError for PaginationError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// Error returned when none of the byte ranges requested by the client can be satisfied.
+///
+/// Produced by [`Ranges`](crate::web::Ranges).
+#[derive(Debug, Display, Error)]
+#[display("None of the requested ranges are satisfiable for a length of {total_len} bytes")]
+#[non_exhaustive]
+pub struct RangeNotSatisfiable {
+    total_len: u64,
+}
+
+impl RangeNotSatisfiable {
+    pub(crate) fn new(total_len: u64) -> Self {
+        Self { total_len }
+    }
+
+    /// Returns the length of the representation that the ranges were resolved against.
+    pub fn total_len(&self) -> u64 {
+        self.total_len
+    }
+}
+
+/// Return `RangeNotSatisfiable` with a `Content-Range` header for `RangeNotSatisfiable`
+impl ResponseError for RangeNotSatisfiable {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::RANGE_NOT_SATISFIABLE
+    }
+
+    fn error_response(&self) -> crate::HttpResponse {
+        crate::HttpResponse::build(self.status_code())
+            .insert_header(crate::http::header::ContentRange(
+                crate::http::header::ContentRangeSpec::Bytes {
+                    range: None,
+                    instance_length: Some(self.total_len),
+                },
+            ))
+            .body(self.to_string())
+    }
+}
+
 /// Error type returned when reading body as lines.
 #[derive(Debug, Display, Error, From)]
 #[non_exhaustive]
@@ -0,0 +1,248 @@
+use std::{borrow::Cow, fmt};
+
+use actix_http::{body::BoxBody, StatusCode};
+use serde_json::{Map, Value};
+
+use crate::{
+    http::header::{self, HeaderValue},
+    HttpRequest, HttpResponse, Responder, ResponseError,
+};
+
+/// Media type of Problem Details JSON documents.
+pub(crate) const PROBLEM_JSON: &str = "application/problem+json";
+
+/// A Problem Details error, as defined by [RFC 9457].
+///
+/// Responds with an `application/problem+json` document containing the problem's `type`, `title`,
+/// `status`, `detail`, and `instance` members, along with any extension members. Can be returned
+/// from handlers either as an error or as a responder.
+///
+/// To convert other error responses into Problem Details responses, see
+/// [`ErrorHandlers::problem_handler()`](crate::middleware::ErrorHandlers::problem_handler).
+///
+/// # Examples
+/// ```
+/// use actix_web::{error::Problem, http::StatusCode, web};
+///
+/// async fn withdraw(path: web::Path<u32>) -> Result<String, Problem> {
+///     let account = path.into_inner();
+///
+///     Err(Problem::new(StatusCode::FORBIDDEN)
+///         .with_type("https://example.com/probs/out-of-credit")
+///         .with_title("You do not have enough credit.")
+///         .with_detail("Your current balance is 30, but that costs 50.")
+///         .with_instance(format!("/account/{account}/msgs/abc"))
+///         .with_extension("balance", 30))
+/// }
+/// # web::to(withdraw);
+/// ```
+///
+/// [RFC 9457]: https://datatracker.ietf.org/doc/html/rfc9457
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    status: StatusCode,
+    type_uri: Option<Cow<'static, str>>,
+    title: Option<Cow<'static, str>>,
+    detail: Option<Cow<'static, str>>,
+    instance: Option<Cow<'static, str>>,
+    extensions: Map<String, Value>,
+}
+
+impl Problem {
+    /// Constructs a problem with the given status code.
+    ///
+    /// The title defaults to the status code's canonical reason, such as "Not Found".
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            type_uri: None,
+            title: status.canonical_reason().map(Cow::Borrowed),
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Sets the URI reference that identifies the problem type.
+    ///
+    /// When not set, the type is `about:blank`, meaning the problem has no semantics beyond those
+    /// of its status code.
+    pub fn with_type(mut self, type_uri: impl Into<Cow<'static, str>>) -> Self {
+        self.type_uri = Some(type_uri.into());
+        self
+    }
+
+    /// Sets the short, human-readable summary of the problem type.
+    pub fn with_title(mut self, title: impl Into<Cow<'static, str>>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the human-readable explanation specific to this occurrence of the problem.
+    pub fn with_detail(mut self, detail: impl Into<Cow<'static, str>>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the URI reference that identifies this occurrence of the problem.
+    pub fn with_instance(mut self, instance: impl Into<Cow<'static, str>>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Adds an extension member.
+    ///
+    /// Extensions named after one of the standard members are ignored when responding.
+    pub fn with_extension(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.extensions.insert(name.into(), value.into());
+        self
+    }
+
+    /// Returns the status code.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the problem type URI, if set.
+    pub fn type_uri(&self) -> Option<&str> {
+        self.type_uri.as_deref()
+    }
+
+    /// Returns the title, if set.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the detail, if set.
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    /// Returns the instance URI, if set.
+    pub fn instance(&self) -> Option<&str> {
+        self.instance.as_deref()
+    }
+
+    /// Returns the extension members.
+    pub fn extensions(&self) -> &Map<String, Value> {
+        &self.extensions
+    }
+
+    /// Serializes the problem into a Problem Details JSON document.
+    pub fn to_json(&self) -> Value {
+        let mut doc = self.extensions.clone();
+
+        let members = [
+            ("type", self.type_uri.as_deref()),
+            ("title", self.title.as_deref()),
+            ("detail", self.detail.as_deref()),
+            ("instance", self.instance.as_deref()),
+        ];
+
+        for (name, value) in members {
+            match value {
+                Some(value) => doc.insert(name.to_owned(), value.into()),
+                None => doc.remove(name),
+            };
+        }
+
+        doc.insert("status".to_owned(), self.status.as_u16().into());
+
+        Value::Object(doc)
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.title().unwrap_or("Unknown Problem"))?;
+
+        if let Some(detail) = self.detail() {
+            write!(f, ": {detail}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Problem {}
+
+impl ResponseError for Problem {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        HttpResponse::build(self.status)
+            .insert_header((header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON)))
+            .body(self.to_json().to_string())
+    }
+}
+
+impl Responder for Problem {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        self.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{body::to_bytes, test::TestRequest, Error};
+
+    #[actix_rt::test]
+    async fn error_response() {
+        let problem = Problem::new(StatusCode::FORBIDDEN)
+            .with_type("https://example.com/probs/out-of-credit")
+            .with_title("You do not have enough credit.")
+            .with_detail("Your current balance is 30, but that costs 50.")
+            .with_instance("/account/12345/msgs/abc")
+            .with_extension("balance", 30)
+            .with_extension("status", 200);
+
+        assert_eq!(
+            problem.to_string(),
+            "You do not have enough credit.: Your current balance is 30, but that costs 50."
+        );
+
+        let err = Error::from(problem);
+        assert!(err.as_error::<Problem>().is_some());
+
+        let res = err.error_response();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/problem+json"
+        );
+
+        let body = to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "type": "https://example.com/probs/out-of-credit",
+                "title": "You do not have enough credit.",
+                "status": 403,
+                "detail": "Your current balance is 30, but that costs 50.",
+                "instance": "/account/12345/msgs/abc",
+                "balance": 30,
+            })
+        );
+    }
+
+    #[actix_rt::test]
+    async fn responder() {
+        let req = TestRequest::default().to_http_request();
+        let res = Problem::new(StatusCode::NOT_FOUND)
+            .with_extension("detail", "ignored")
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let body = to_bytes(res.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<Value>(&body).unwrap();
+        assert_eq!(body, json!({ "title": "Not Found", "status": 404 }));
+    }
+}
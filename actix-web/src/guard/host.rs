@@ -10,6 +10,9 @@ use super::{Guard, GuardContext};
 /// - fall-back to matching against the request target's host, if present;
 /// - return false if host cannot be determined;
 ///
+/// Hosts starting with `*.`, such as `*.rust-lang.org`, match all subdomains of a domain, but not
+/// the domain itself. Subdomains are matched case-insensitively.
+///
 /// # Matching Scheme
 /// Optionally, this guard can match against the host's scheme. Set the scheme for matching using
 /// `Host(host).scheme(protocol)`. If the request's scheme cannot be determined, it will not prevent
@@ -82,6 +85,18 @@ impl HostGuard {
         self.scheme = Some(scheme.as_ref().to_string());
         self
     }
+
+    fn matches_host(&self, host: &str) -> bool {
+        match self.host.strip_prefix('*') {
+            Some(suffix) if suffix.starts_with('.') => {
+                host.len() > suffix.len()
+                    && host
+                        .get(host.len() - suffix.len()..)
+                        .is_some_and(|end| end.eq_ignore_ascii_case(suffix))
+            }
+            _ => self.host == host,
+        }
+    }
 }
 
 impl Guard for HostGuard {
@@ -96,7 +111,7 @@ impl Guard for HostGuard {
 
         match req_host_uri.host() {
             // fall through to scheme checks
-            Some(uri_host) if self.host == uri_host => {}
+            Some(uri_host) if self.matches_host(uri_host) => {}
 
             // Either:
             // - request's host does not match guard's host;
@@ -206,4 +221,31 @@ mod tests {
         let host = Host("localhost");
         assert!(!host.check(&req.guard_ctx()));
     }
+
+    #[test]
+    fn host_wildcard() {
+        let host = Host("*.rust-lang.org");
+
+        for uri_host in [
+            "www.rust-lang.org",
+            "a.b.rust-lang.org",
+            "WWW.Rust-Lang.org",
+        ] {
+            let req = TestRequest::default()
+                .insert_header((header::HOST, uri_host))
+                .to_srv_request();
+            assert!(host.check(&req.guard_ctx()), "host: {uri_host}");
+        }
+
+        for uri_host in [
+            "rust-lang.org",
+            "evil-rust-lang.org",
+            "www.rust-lang.org.evil.com",
+        ] {
+            let req = TestRequest::default()
+                .insert_header((header::HOST, uri_host))
+                .to_srv_request();
+            assert!(!host.check(&req.guard_ctx()), "host: {uri_host}");
+        }
+    }
 }
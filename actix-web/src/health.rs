@@ -0,0 +1,345 @@
+//! For health checks documentation, see [`HealthChecks`].
+
+use std::{
+    borrow::Cow,
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use futures_core::future::LocalBoxFuture;
+use futures_util::future::join_all;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    dev::{AppService, HttpServiceFactory},
+    http::{
+        header::{CacheControl, CacheDirective},
+        StatusCode,
+    },
+    web, HttpResponse,
+};
+
+type Probe = Arc<dyn Fn() -> LocalBoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// Liveness and readiness endpoints backed by async probes.
+///
+/// Components register named probes, such as a database ping or a queue depth check, as
+/// [liveness](Self::liveness()) or [readiness](Self::readiness()) probes. When registered as a
+/// service, `GET /healthz` runs all liveness probes and `GET /readyz` runs all readiness probes,
+/// concurrently and each with a [timeout](Self::timeout()). Both respond with `200 OK` if all of
+/// their probes succeed, or `503 Service Unavailable` otherwise, and a JSON body such as:
+///
+/// ```json
+/// {
+///   "status": "error",
+///   "checks": {
+///     "database": { "status": "ok" },
+///     "queue": { "status": "error", "error": "queue depth is 1042" }
+///   }
+/// }
+/// ```
+///
+/// # Graceful Shutdown
+/// Clones of a `HealthChecks` share their state, so it can be created outside of the `HttpServer`
+/// factory closure. Once [`drain()`](Self::drain()) is called, the readiness endpoint responds with
+/// `503 Service Unavailable` and a `"draining"` status, so that load balancers stop routing new
+/// traffic to the server. Using the future returned by `drain()` as part of the server's
+/// [shutdown signal](crate::HttpServer::shutdown_signal()) delays the shutdown until load balancers
+/// have noticed.
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use actix_web::{web, App, HttpServer};
+///
+/// # async fn ping_database() -> Result<(), std::io::Error> { Ok(()) }
+/// # async fn termination_signal() {}
+/// #[actix_web::main]
+/// async fn main() -> std::io::Result<()> {
+///     let health = web::health_checks()
+///         .liveness("event_loop", || async { Ok::<_, std::io::Error>(()) })
+///         .readiness("database", ping_database);
+///
+///     let drain = health.clone();
+///
+///     HttpServer::new(move || App::new().service(health.clone()))
+///         .shutdown_signal(async move {
+///             termination_signal().await;
+///             drain.drain(Duration::from_secs(10)).await;
+///         })
+///         .bind(("127.0.0.1", 8080))?
+///         .run()
+///         .await
+/// }
+/// ```
+#[derive(Clone)]
+pub struct HealthChecks {
+    liveness_path: Cow<'static, str>,
+    readiness_path: Cow<'static, str>,
+    liveness: Vec<(Cow<'static, str>, Probe)>,
+    readiness: Vec<(Cow<'static, str>, Probe)>,
+    timeout: Duration,
+    draining: Arc<AtomicBool>,
+}
+
+impl HealthChecks {
+    /// Constructs health checks without probes, serving `/healthz` and `/readyz`.
+    pub fn new() -> Self {
+        Self {
+            liveness_path: Cow::Borrowed("/healthz"),
+            readiness_path: Cow::Borrowed("/readyz"),
+            liveness: Vec::new(),
+            readiness: Vec::new(),
+            timeout: Duration::from_secs(5),
+            draining: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Registers a liveness probe, which fails if the process needs to be restarted.
+    pub fn liveness<F, Fut, E>(mut self, name: impl Into<Cow<'static, str>>, probe: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + 'static,
+        E: fmt::Display,
+    {
+        self.liveness.push((name.into(), boxed_probe(probe)));
+        self
+    }
+
+    /// Registers a readiness probe, which fails if the server can not handle traffic right now.
+    pub fn readiness<F, Fut, E>(mut self, name: impl Into<Cow<'static, str>>, probe: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + 'static,
+        E: fmt::Display,
+    {
+        self.readiness.push((name.into(), boxed_probe(probe)));
+        self
+    }
+
+    /// Sets the time each probe has to complete before it is considered failed.
+    ///
+    /// The default timeout is 5 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the path of the liveness endpoint. The default path is `/healthz`.
+    pub fn liveness_path(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        self.liveness_path = path.into();
+        self
+    }
+
+    /// Sets the path of the readiness endpoint. The default path is `/readyz`.
+    pub fn readiness_path(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        self.readiness_path = path.into();
+        self
+    }
+
+    /// Marks the server as draining, then waits for `period` to elapse.
+    ///
+    /// While draining, the readiness endpoint fails without running any probes.
+    pub fn drain(&self, period: Duration) -> impl Future<Output = ()> + Send + 'static {
+        self.draining.store(true, Ordering::Release);
+        actix_rt::time::sleep(period)
+    }
+
+    /// Returns true if the server has been marked as draining.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+
+    async fn run(
+        probes: Arc<Vec<(Cow<'static, str>, Probe)>>,
+        timeout: Duration,
+        draining: bool,
+    ) -> HttpResponse {
+        // draining servers are not ready, regardless of their probes
+        let probes = if draining { &[][..] } else { &probes[..] };
+
+        let results = join_all(probes.iter().map(|(name, probe)| async move {
+            let res = match actix_rt::time::timeout(timeout, probe()).await {
+                Ok(res) => res,
+                Err(_) => Err(format!("timed out after {timeout:?}")),
+            };
+
+            (name, res)
+        }))
+        .await;
+
+        let mut healthy = !draining;
+        let mut checks = Map::new();
+
+        for (name, res) in results {
+            let check = match res {
+                Ok(()) => json!({ "status": "ok" }),
+                Err(err) => {
+                    healthy = false;
+                    json!({ "status": "error", "error": err })
+                }
+            };
+
+            checks.insert(name.to_string(), check);
+        }
+
+        let (status, status_code) = match (draining, healthy) {
+            (true, _) => ("draining", StatusCode::SERVICE_UNAVAILABLE),
+            (false, true) => ("ok", StatusCode::OK),
+            (false, false) => ("error", StatusCode::SERVICE_UNAVAILABLE),
+        };
+
+        HttpResponse::build(status_code)
+            .insert_header(CacheControl(vec![CacheDirective::NoStore]))
+            .json(json!({ "status": status, "checks": Value::Object(checks) }))
+    }
+}
+
+impl Default for HealthChecks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for HealthChecks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = |probes: &[(Cow<'static, str>, Probe)]| {
+            probes
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        f.debug_struct("HealthChecks")
+            .field("liveness_path", &self.liveness_path)
+            .field("readiness_path", &self.readiness_path)
+            .field("liveness", &names(&self.liveness))
+            .field("readiness", &names(&self.readiness))
+            .field("timeout", &self.timeout)
+            .field("draining", &self.is_draining())
+            .finish()
+    }
+}
+
+fn boxed_probe<F, Fut, E>(probe: F) -> Probe
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), E>> + 'static,
+    E: fmt::Display,
+{
+    Arc::new(move || {
+        let fut = probe();
+        Box::pin(async move { fut.await.map_err(|err| err.to_string()) })
+    })
+}
+
+impl HttpServiceFactory for HealthChecks {
+    fn register(self, config: &mut AppService) {
+        let Self {
+            liveness_path,
+            readiness_path,
+            liveness,
+            readiness,
+            timeout,
+            draining,
+        } = self;
+
+        let liveness = Arc::new(liveness);
+        let readiness = Arc::new(readiness);
+
+        web::resource(liveness_path.into_owned())
+            .route(web::get().to(move || Self::run(Arc::clone(&liveness), timeout, false)))
+            .register(config);
+
+        web::resource(readiness_path.into_owned())
+            .route(web::get().to(move || {
+                let draining = draining.load(Ordering::Acquire);
+                Self::run(Arc::clone(&readiness), timeout, draining)
+            }))
+            .register(config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+    use crate::{
+        test::{self, TestRequest},
+        App,
+    };
+
+    #[actix_rt::test]
+    async fn aggregates_probes() {
+        let health = HealthChecks::new()
+            .liveness("loop", || async { Ok::<_, io::Error>(()) })
+            .readiness("database", || async { Ok::<_, io::Error>(()) })
+            .readiness("queue", || async { Err("queue depth is 1042") })
+            .readiness("slow", || async {
+                actix_rt::time::sleep(Duration::from_secs(60)).await;
+                Ok::<_, io::Error>(())
+            })
+            .timeout(Duration::from_millis(10));
+
+        let app = test::init_service(App::new().service(health)).await;
+
+        let req = TestRequest::with_uri("/healthz").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: Value = test::read_body_json(res).await;
+        assert_eq!(
+            body,
+            json!({ "status": "ok", "checks": { "loop": { "status": "ok" } } })
+        );
+
+        let req = TestRequest::with_uri("/readyz").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: Value = test::read_body_json(res).await;
+        assert_eq!(body["status"], "error");
+        assert_eq!(body["checks"]["database"], json!({ "status": "ok" }));
+        assert_eq!(
+            body["checks"]["queue"],
+            json!({ "status": "error", "error": "queue depth is 1042" })
+        );
+        assert_eq!(
+            body["checks"]["slow"],
+            json!({ "status": "error", "error": "timed out after 10ms" })
+        );
+    }
+
+    #[actix_rt::test]
+    async fn draining() {
+        let health = web::health_checks()
+            .readiness_path("/ready")
+            .readiness("database", || async { Ok::<_, io::Error>(()) });
+
+        let app = test::init_service(App::new().service(health.clone())).await;
+
+        let req = TestRequest::with_uri("/ready").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        assert!(!health.is_draining());
+        health.drain(Duration::ZERO).await;
+        assert!(health.is_draining());
+
+        let req = TestRequest::with_uri("/ready").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: Value = test::read_body_json(res).await;
+        assert_eq!(body["status"], "draining");
+
+        // liveness is unaffected
+        let req = TestRequest::with_uri("/healthz").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
@@ -17,7 +17,7 @@ static X_FORWARDED_HOST: HeaderName = HeaderName::from_static("x-forwarded-host"
 static X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
 
 /// Trim whitespace then any quote marks.
-fn unquote(val: &str) -> &str {
+pub(crate) fn unquote(val: &str) -> &str {
     val.trim().trim_start_matches('"').trim_end_matches('"')
 }
 
@@ -163,7 +163,8 @@ impl ConnectionInfo {
     /// # Security
     /// Do not use this function for security purposes unless you can be sure that the `Forwarded`
     /// and `X-Forwarded-For` headers cannot be spoofed by the client. If you are running without a
-    /// proxy then [obtaining the peer address](Self::peer_addr) would be more appropriate.
+    /// proxy then [obtaining the peer address](Self::peer_addr) would be more appropriate. If you
+    /// are running behind known proxies, use the [`RealIp`](crate::web::RealIp) extractor instead.
     #[inline]
     pub fn realip_remote_addr(&self) -> Option<&str> {
         self.realip_remote_addr
@@ -60,6 +60,7 @@
 //! # Crate Features
 //! - `cookies` - cookies support (enabled by default)
 //! - `macros` - routing and runtime macros (enabled by default)
+//! - `macros-multi-thread` - multi-threaded runtime flavor for the `main` and `test` runtime macros
 //! - `compress-brotli` - brotli content encoding compression support (enabled by default)
 //! - `compress-gzip` - gzip and deflate content encoding compression support (enabled by default)
 //! - `compress-zstd` - zstd content encoding compression support (enabled by default)
@@ -69,6 +70,9 @@
 //! - `rustls-0_22` - HTTPS support via `rustls` 0.22 crate, supports `HTTP/2`
 //! - `rustls-0_23` - HTTPS support via `rustls` 0.23 crate, supports `HTTP/2`
 //! - `secure-cookies` - secure cookies support
+//! - `ws` - WebSocket support, without the actor framework, through the `web::WebSocket` extractor
+//! - `auto-register` - automatic registration of handlers declared using routing macros with
+//!   `auto_register = true`, collected using `collect_services!`
 
 #![doc(html_logo_url = "https://actix.rs/img/logo.png")]
 #![doc(html_favicon_url = "https://actix.rs/favicon.ico")]
@@ -81,17 +85,23 @@ pub use cookie;
 
 mod app;
 mod app_service;
+pub mod auth;
+#[cfg(feature = "auto-register")]
+mod auto_register;
 mod config;
+mod connection;
 mod data;
 pub mod dev;
 pub mod error;
 mod extract;
 pub mod guard;
 mod handler;
+mod health;
 mod helpers;
 pub mod http;
 mod info;
 pub mod middleware;
+mod real_ip;
 mod redirect;
 mod request;
 mod request_data;
@@ -99,6 +109,7 @@ mod resource;
 mod response;
 mod rmap;
 mod route;
+mod route_metadata;
 pub mod rt;
 mod scope;
 mod server;
@@ -107,6 +118,8 @@ pub mod test;
 mod thin_data;
 pub(crate) mod types;
 pub mod web;
+#[cfg(feature = "ws")]
+pub mod ws;
 
 #[doc(inline)]
 pub use crate::error::Result;
@@ -144,6 +157,8 @@ codegen_reexport!(delete);
 codegen_reexport!(trace);
 codegen_reexport!(connect);
 codegen_reexport!(options);
+codegen_reexport!(method);
 codegen_reexport!(scope);
+codegen_reexport!(controller);
 
 pub(crate) type BoxError = Box<dyn std::error::Error>;
@@ -0,0 +1,295 @@
+//! For middleware documentation, see [`AllowedHosts`].
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_http::uri::Authority;
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Either, Ready};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use crate::{
+    body::EitherBody,
+    http::header,
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpResponse,
+};
+
+/// Middleware for rejecting requests for hosts that are not in an allow-list.
+///
+/// Applications that use the host of requests, such as to build absolute URLs with
+/// [`HttpRequest::url_for()`](crate::HttpRequest::url_for()) or in password reset emails, can be
+/// tricked into using a host chosen by an attacker. This middleware responds to requests whose
+/// `Host` header, or request target for HTTP/2 requests, does not match an allowed host with
+/// `400 Bad Request`, before they reach any handler.
+///
+/// Hosts are matched case-insensitively, ignoring ports and trailing dots. Patterns starting with
+/// `*.` match all subdomains of a domain, but not the domain itself.
+///
+/// # Virtual Hosting
+/// Requests for different allowed hosts can be routed to different scopes using the
+/// [`Host`](crate::guard::Host) guard, which supports the same wildcard patterns.
+///
+/// # Examples
+/// ```
+/// use actix_web::{guard, middleware::AllowedHosts, web, App, HttpResponse};
+///
+/// let app = App::new()
+///     .wrap(
+///         AllowedHosts::new()
+///             .allow("example.com")
+///             .allow("*.example.com"),
+///     )
+///     .service(
+///         web::scope("")
+///             .guard(guard::Host("api.example.com"))
+///             .default_service(web::to(|| async { HttpResponse::Ok().body("api") })),
+///     )
+///     .service(
+///         web::scope("")
+///             .guard(guard::Host("*.example.com"))
+///             .default_service(web::to(|| async { HttpResponse::Ok().body("tenant") })),
+///     )
+///     .default_service(web::to(|| async { HttpResponse::Ok().body("website") }));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AllowedHosts {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Allowed hosts, in lowercase and without trailing dots.
+    hosts: Vec<String>,
+
+    /// Domains whose subdomains are allowed, in lowercase and with a leading dot.
+    wildcard_suffixes: Vec<String>,
+}
+
+impl AllowedHosts {
+    /// Constructs a middleware that rejects all requests until hosts are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a host, such as `example.com`, or a wildcard pattern, such as `*.example.com`, to the
+    /// allow-list.
+    ///
+    /// # Panics
+    /// Panics if `host` contains a `*` other than in a leading `*.`, or if called after this
+    /// middleware has been cloned.
+    pub fn allow(mut self, host: impl AsRef<str>) -> Self {
+        let host = normalize_host(host.as_ref());
+
+        let inner = Rc::get_mut(&mut self.inner)
+            .expect("allowed hosts can not be added after the middleware has been cloned");
+
+        let suffix = host.strip_prefix("*.").map(|domain| format!(".{domain}"));
+
+        assert!(
+            !suffix.as_ref().unwrap_or(&host).contains('*'),
+            "wildcards are only supported as a leading `*.` in allowed hosts: {host}"
+        );
+
+        match suffix {
+            Some(suffix) => inner.wildcard_suffixes.push(suffix),
+            None => inner.hosts.push(host),
+        }
+
+        self
+    }
+}
+
+impl Inner {
+    fn is_allowed(&self, req: &ServiceRequest) -> bool {
+        let host = req
+            .headers()
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .or_else(|| req.uri().authority().map(Authority::as_str))
+            .and_then(|host| host.parse::<Authority>().ok());
+
+        let Some(host) = host else {
+            return false;
+        };
+
+        let host = normalize_host(host.host());
+
+        self.hosts.contains(&host)
+            || self
+                .wildcard_suffixes
+                .iter()
+                .any(|suffix| host.len() > suffix.len() && host.ends_with(suffix.as_str()))
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AllowedHosts
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = AllowedHostsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AllowedHostsMiddleware {
+            service,
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+/// Allowed hosts middleware service.
+pub struct AllowedHostsMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S, B> Service<ServiceRequest> for AllowedHostsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Either<AllowedHostsFuture<S, B>, Ready<Result<Self::Response, Self::Error>>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if self.inner.is_allowed(&req) {
+            return Either::left(AllowedHostsFuture {
+                fut: self.service.call(req),
+                _body: PhantomData,
+            });
+        }
+
+        let res = HttpResponse::BadRequest().body("Invalid Host header");
+        Either::right(ready(Ok(req.into_response(res).map_into_right_body())))
+    }
+}
+
+pin_project! {
+    pub struct AllowedHostsFuture<S: Service<ServiceRequest>, B> {
+        #[pin]
+        fut: S::Future,
+        _body: PhantomData<B>,
+    }
+}
+
+impl<S, B> Future for AllowedHostsFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = Result<ServiceResponse<EitherBody<B>>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(self.project().fut.poll(cx))?;
+        Poll::Ready(Ok(res.map_into_left_body()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{self, TestRequest},
+    };
+
+    async fn status(mw: AllowedHosts, req: TestRequest) -> StatusCode {
+        let mw = mw.new_transform(test::ok_service()).await.unwrap();
+        mw.call(req.to_srv_request()).await.unwrap().status()
+    }
+
+    #[actix_rt::test]
+    async fn exact_hosts() {
+        let mw = || AllowedHosts::new().allow("Example.com").allow("127.0.0.1");
+
+        for host in [
+            "example.com",
+            "EXAMPLE.com:8080",
+            "example.com.",
+            "127.0.0.1:80",
+        ] {
+            let req = TestRequest::default().insert_header((header::HOST, host));
+            assert_eq!(status(mw(), req).await, StatusCode::OK, "host: {host}");
+        }
+
+        for host in [
+            "evil.com",
+            "www.example.com",
+            "example.com.evil.com",
+            "user@",
+        ] {
+            let req = TestRequest::default().insert_header((header::HOST, host));
+            assert_eq!(
+                status(mw(), req).await,
+                StatusCode::BAD_REQUEST,
+                "host: {host}"
+            );
+        }
+
+        // host from request target
+        let req = TestRequest::with_uri("https://example.com/");
+        assert_eq!(status(mw(), req).await, StatusCode::OK);
+
+        // no host
+        let req = TestRequest::default();
+        assert_eq!(status(mw(), req).await, StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn wildcard_hosts() {
+        let mw = || AllowedHosts::new().allow("*.example.com");
+
+        for host in ["a.example.com", "a.b.example.com:8080"] {
+            let req = TestRequest::default().insert_header((header::HOST, host));
+            assert_eq!(status(mw(), req).await, StatusCode::OK, "host: {host}");
+        }
+
+        for host in [
+            "example.com",
+            ".example.com",
+            "aexample.com",
+            "evilexample.com",
+            "a.example.com.evil.com",
+        ] {
+            let req = TestRequest::default().insert_header((header::HOST, host));
+            assert_eq!(
+                status(mw(), req).await,
+                StatusCode::BAD_REQUEST,
+                "host: {host}"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic = "wildcards are only supported as a leading `*.`"]
+    fn wildcard_without_dot() {
+        let _ = AllowedHosts::new().allow("*example.com");
+    }
+
+    #[test]
+    #[should_panic = "wildcards are only supported as a leading `*.`"]
+    fn bare_wildcard() {
+        let _ = AllowedHosts::new().allow("*");
+    }
+
+    #[test]
+    #[should_panic = "wildcards are only supported as a leading `*.`"]
+    fn inner_wildcard() {
+        let _ = AllowedHosts::new().allow("api.*.example.com");
+    }
+}
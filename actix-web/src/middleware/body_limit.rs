@@ -0,0 +1,300 @@
+//! For middleware documentation, see [`BodyLimit`].
+
+use std::{
+    cell::Cell,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_http::{error::PayloadError, BoxedPayloadStream};
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+
+use crate::{
+    dev::Payload,
+    http::header::CONTENT_LENGTH,
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpMessage as _, HttpRequest,
+};
+
+/// Middleware for limiting the size of request bodies.
+///
+/// The limit is applied to the payload stream itself, before any extractor runs, so it also covers
+/// handlers that consume the raw [`web::Payload`](crate::web::Payload) stream. Once more than the
+/// limit has been read, or as soon as the body is read if the `Content-Length` header exceeds the
+/// limit, the stream yields [`PayloadError::Overflow`], which extractors turn into a
+/// `413 Payload Too Large` response. Bodies that are never read are not rejected.
+///
+/// The limit counts bytes as they are received, before any decompression.
+///
+/// # Per-Route Limits
+/// When this middleware wraps a [`Scope`](crate::Scope) or [`Resource`](crate::Resource) that is
+/// already covered by another `BodyLimit`, the innermost limit applies, whether it is smaller or
+/// larger than the outer one.
+///
+/// # Extractor Limits
+/// The [`Json`](crate::web::Json), [`Form`](crate::web::Form), [`Bytes`](crate::web::Bytes), and
+/// `String` extractors use this limit in place of their default limits. A registered
+/// [`JsonConfig`](crate::web::JsonConfig), [`FormConfig`](crate::web::FormConfig), or
+/// [`PayloadConfig`](crate::web::PayloadConfig) still takes precedence, but its limit can not raise
+/// the limit of this middleware.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::BodyLimit, web, App, HttpResponse};
+///
+/// let app = App::new()
+///     .wrap(BodyLimit::new(64 * 1024))
+///     .service(
+///         web::resource("/upload")
+///             .wrap(BodyLimit::new(100 * 1024 * 1024))
+///             .route(web::post().to(|body: web::Bytes| async move { body.len().to_string() })),
+///     )
+///     .route("/", web::post().to(|body: String| async move { body }));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimit {
+    limit: usize,
+}
+
+impl BodyLimit {
+    /// Constructs a middleware that limits request bodies to `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        Self { limit }
+    }
+}
+
+/// Limit shared by the limited payload stream and the body limit middleware of a request.
+#[derive(Clone)]
+struct RequestBodyLimit(Rc<Cell<usize>>);
+
+/// Returns the body limit set by [`BodyLimit`] middleware for a request, if any.
+pub(crate) fn request_body_limit(req: &HttpRequest) -> Option<usize> {
+    req.extensions()
+        .get::<RequestBodyLimit>()
+        .map(|limit| limit.0.get())
+}
+
+impl<S, B> Transform<S, ServiceRequest> for BodyLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = BodyLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(BodyLimitMiddleware {
+            service,
+            limit: self.limit,
+        }))
+    }
+}
+
+/// Body limit middleware service.
+pub struct BodyLimitMiddleware<S> {
+    service: S,
+    limit: usize,
+}
+
+impl<S, B> Service<ServiceRequest> for BodyLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = S::Future;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let existing = req.extensions().get::<RequestBodyLimit>().cloned();
+
+        match existing {
+            // an outer body limit middleware already wrapped the payload; override its limit
+            Some(RequestBodyLimit(limit)) => limit.set(self.limit),
+
+            None => {
+                let limit = Rc::new(Cell::new(self.limit));
+                req.extensions_mut()
+                    .insert(RequestBodyLimit(Rc::clone(&limit)));
+
+                let length = req
+                    .headers()
+                    .get(CONTENT_LENGTH)
+                    .and_then(|len| len.to_str().ok())
+                    .and_then(|len| len.parse::<u64>().ok());
+
+                let payload = LimitedPayload {
+                    payload: req.take_payload(),
+                    limit,
+                    length,
+                    read: 0,
+                    done: false,
+                };
+
+                req.set_payload(Payload::from(Box::pin(payload) as BoxedPayloadStream));
+            }
+        }
+
+        self.service.call(req)
+    }
+}
+
+/// Payload stream that yields an overflow error once more than the request's limit has been read.
+struct LimitedPayload {
+    payload: Payload,
+    limit: Rc<Cell<usize>>,
+    length: Option<u64>,
+    read: usize,
+    done: bool,
+}
+
+impl Stream for LimitedPayload {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let limit = this.limit.get();
+
+        if this.length.is_some_and(|len| len > limit as u64) {
+            this.done = true;
+            return Poll::Ready(Some(Err(PayloadError::Overflow)));
+        }
+
+        match ready!(Pin::new(&mut this.payload).poll_next(cx)) {
+            Some(Ok(chunk)) => {
+                this.read += chunk.len();
+
+                if this.read > limit {
+                    this.done = true;
+                    Poll::Ready(Some(Err(PayloadError::Overflow)))
+                } else {
+                    Poll::Ready(Some(Ok(chunk)))
+                }
+            }
+
+            item => Poll::Ready(item),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt as _;
+
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    #[actix_rt::test]
+    async fn limits_extractors() {
+        let app = test::init_service(
+            App::new()
+                .wrap(BodyLimit::new(8))
+                .route("/", web::post().to(|body: String| async move { body }))
+                .route(
+                    "/json",
+                    web::post().to(|body: web::Json<String>| async move { body.into_inner() }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::post().uri("/").set_payload("12345678");
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::post().uri("/").set_payload("123456789");
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let req = TestRequest::post().uri("/json").set_json("1234567890");
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[actix_rt::test]
+    async fn limits_raw_payload() {
+        async fn count(mut body: web::Payload) -> Result<HttpResponse, Error> {
+            let mut len = 0;
+
+            while let Some(chunk) = body.next().await {
+                len += chunk?.len();
+            }
+
+            Ok(HttpResponse::Ok().body(len.to_string()))
+        }
+
+        let app = test::init_service(
+            App::new()
+                .wrap(BodyLimit::new(4))
+                .route("/", web::post().to(count)),
+        )
+        .await;
+
+        let req = TestRequest::post().uri("/").set_payload("1234");
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(test::read_body(res).await, "4");
+
+        let req = TestRequest::post().uri("/").set_payload("12345");
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[actix_rt::test]
+    async fn inner_limit_overrides() {
+        let app = test::init_service(
+            App::new()
+                .wrap(BodyLimit::new(4))
+                .service(
+                    web::scope("/large")
+                        .wrap(BodyLimit::new(64))
+                        .route("", web::post().to(|body: String| async move { body })),
+                )
+                .service(
+                    web::resource("/small")
+                        .wrap(BodyLimit::new(2))
+                        .route(web::post().to(|body: String| async move { body })),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::post().uri("/large").set_payload("1234567890");
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::post().uri("/small").set_payload("123");
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[actix_rt::test]
+    async fn raises_default_extractor_limits() {
+        let app = test::init_service(
+            App::new()
+                .wrap(BodyLimit::new(1024 * 1024))
+                .route("/", web::post().to(|body: web::Bytes| async move { body })),
+        )
+        .await;
+
+        // larger than the default `PayloadConfig` limit of 256kB
+        let body = vec![b'a'; 512 * 1024];
+        let req = TestRequest::post().uri("/").set_payload(body);
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
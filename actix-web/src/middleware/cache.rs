@@ -0,0 +1,1031 @@
+//! For middleware documentation, see [`Cache`].
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use bytes::Bytes;
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    body::{self, BodySize, BoxBody, EitherBody, MessageBody},
+    error,
+    http::{
+        header::{
+            CacheDirective, HeaderMap, HeaderName, HeaderValue, HttpDate, AGE, AUTHORIZATION,
+            CACHE_CONTROL, DATE, EXPIRES, SET_COOKIE, VARY,
+        },
+        Method, StatusCode,
+    },
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpResponse,
+};
+
+const CACHE_STATUS: HeaderName = HeaderName::from_static("cache-status");
+
+/// Storage for responses cached by the [`Cache`] middleware.
+///
+/// Stores are shared by all workers, so implementations must be thread-safe. Methods are called
+/// while handling requests and should return quickly.
+pub trait CacheStore: Send + Sync + 'static {
+    /// Returns the entry stored under `key`, if any.
+    fn get(&self, key: &str) -> Option<CacheEntry>;
+
+    /// Stores `entry` under `key`, replacing any existing entry.
+    fn insert(&self, key: String, entry: CacheEntry);
+
+    /// Removes the entry stored under `key`, if any.
+    fn remove(&self, key: &str);
+}
+
+/// Cached responses for one request target, one for each variant selected by `Vary` headers.
+#[derive(Debug, Clone, Default)]
+pub struct CacheEntry {
+    variants: Vec<CachedResponse>,
+}
+
+impl CacheEntry {
+    /// Returns the approximate number of bytes used by the cached responses.
+    pub fn size(&self) -> usize {
+        self.variants.iter().map(CachedResponse::size).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+
+    /// Values of request headers named by the response's `Vary` headers.
+    vary: Vec<(HeaderName, Vec<HeaderValue>)>,
+
+    /// Time at which the response was received.
+    response_time: SystemTime,
+
+    /// Age of the response when it was received, according to its `Age` header.
+    initial_age: Duration,
+
+    freshness_lifetime: Duration,
+}
+
+impl CachedResponse {
+    fn size(&self) -> usize {
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, value)| name.as_str().len() + value.len())
+            .sum::<usize>();
+
+        self.body.len() + headers
+    }
+
+    fn age(&self, now: SystemTime) -> Duration {
+        self.initial_age + now.duration_since(self.response_time).unwrap_or_default()
+    }
+
+    fn matches(&self, req: &HeaderMap) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, values)| req.get_all(name).eq(values.iter()))
+    }
+}
+
+/// In-memory [`CacheStore`] that evicts the least recently used entries.
+#[derive(Debug)]
+pub struct MemoryCacheStore {
+    capacity: usize,
+    inner: Mutex<Lru>,
+}
+
+#[derive(Debug, Default)]
+struct Lru {
+    entries: HashMap<String, (CacheEntry, u64)>,
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Lru {
+    fn touch(&mut self, key: &str) -> Option<&CacheEntry> {
+        let (entry, last_used) = self.entries.get_mut(key)?;
+
+        self.order.remove(last_used);
+        self.tick += 1;
+        *last_used = self.tick;
+        self.order.insert(self.tick, key.to_owned());
+
+        Some(entry)
+    }
+}
+
+impl MemoryCacheStore {
+    /// Constructs a store that holds entries for at most `capacity` request targets.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "cache capacity must be greater than zero");
+
+        Self {
+            capacity,
+            inner: Mutex::new(Lru::default()),
+        }
+    }
+}
+
+impl CacheStore for MemoryCacheStore {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        self.inner.lock().unwrap().touch(key).cloned()
+    }
+
+    fn insert(&self, key: String, entry: CacheEntry) {
+        let mut lru = self.inner.lock().unwrap();
+
+        lru.tick += 1;
+        let tick = lru.tick;
+
+        if let Some((_, last_used)) = lru.entries.insert(key.clone(), (entry, tick)) {
+            lru.order.remove(&last_used);
+        }
+        lru.order.insert(tick, key);
+
+        while lru.entries.len() > self.capacity {
+            let Some((_, key)) = lru.order.pop_first() else {
+                break;
+            };
+
+            lru.entries.remove(&key);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let mut lru = self.inner.lock().unwrap();
+
+        if let Some((_, last_used)) = lru.entries.remove(key) {
+            lru.order.remove(&last_used);
+        }
+    }
+}
+
+/// Middleware for caching responses, following the rules of [RFC 9111] for shared caches.
+///
+/// Responses to `GET` requests are stored when their `Cache-Control` and `Expires` headers give
+/// them a freshness lifetime, and served from the cache until they become stale. Responses are
+/// not stored when they:
+/// - have `no-store`, `no-cache`, or `private` cache directives;
+/// - have a `Vary: *` or `Set-Cookie` header;
+/// - respond to requests with an `Authorization` header, unless they have `public`, `s-maxage`,
+///   or `must-revalidate` cache directives;
+/// - have a streaming body, or a body larger than the [maximum size](Self::max_body_size()).
+///
+/// Responses are cached per request URL, made of the scheme and host reported by
+/// [`ConnectionInfo`](crate::dev::ConnectionInfo) and the request's path and query, with separate
+/// variants for the request headers named by `Vary` response headers. Requests with `no-cache`, `no-store`, or `max-age=0`
+/// cache directives are always passed to the wrapped services. Served responses have an `Age`
+/// header and a [`Cache-Status`](https://www.rfc-editor.org/rfc/rfc9211) header that reports
+/// whether the cache was hit.
+///
+/// # Invalidation
+/// Successful responses to requests with unsafe methods, like `POST` or `DELETE`, remove cached
+/// responses for the same URL. Cached responses can also be removed using
+/// [`invalidate()`](Self::invalidate()), such as from handlers that receive the middleware as app
+/// data.
+///
+/// # Per-Route Configuration
+/// Clones of the middleware share their store, so clones with different configuration can wrap
+/// different scopes or resources. For example, [`ttl()`](Self::ttl()) can override the freshness
+/// lifetime of responses from a resource that does not send cache headers.
+///
+/// # Middleware Order
+/// To cache compressed responses, register this middleware after [`Compress`], using a later
+/// call to `wrap`. `Compress` adds `Vary: Accept-Encoding` headers, so responses are cached for
+/// each accepted encoding.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{dev::ConnectionInfo, middleware::Cache, web, App, HttpResponse};
+///
+/// let cache = Cache::in_memory(10_000);
+///
+/// let app = App::new()
+///     .app_data(web::ThinData(cache.clone()))
+///     .wrap(cache.clone())
+///     .service(
+///         web::resource("/reports")
+///             .wrap(cache.clone().ttl(Duration::from_secs(60)))
+///             .get(|| async { HttpResponse::Ok().body("report") }),
+///     )
+///     .route(
+///         "/reports/refresh",
+///         web::post().to(
+///             |conn: ConnectionInfo, web::ThinData(cache): web::ThinData<Cache>| async move {
+///                 cache.invalidate(conn.scheme(), conn.host(), "/reports");
+///                 HttpResponse::NoContent()
+///             },
+///         ),
+///     );
+/// ```
+///
+/// [RFC 9111]: https://www.rfc-editor.org/rfc/rfc9111
+/// [`Compress`]: crate::middleware::Compress
+#[derive(Clone)]
+pub struct Cache {
+    store: Arc<dyn CacheStore>,
+    config: Arc<Config>,
+}
+
+#[derive(Debug, Clone)]
+struct Config {
+    max_body_size: usize,
+    default_ttl: Option<Duration>,
+    ttl: Option<Duration>,
+}
+
+impl Cache {
+    /// Constructs a cache middleware that uses `store`.
+    pub fn new(store: impl CacheStore) -> Self {
+        Self {
+            store: Arc::new(store),
+            config: Arc::new(Config {
+                max_body_size: 1024 * 1024,
+                default_ttl: None,
+                ttl: None,
+            }),
+        }
+    }
+
+    /// Constructs a cache middleware that uses a [`MemoryCacheStore`] with the given capacity.
+    pub fn in_memory(capacity: usize) -> Self {
+        Self::new(MemoryCacheStore::new(capacity))
+    }
+
+    /// Sets the size, in bytes, of the largest response body to cache.
+    ///
+    /// Defaults to 1 MiB.
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        Arc::make_mut(&mut self.config).max_body_size = size;
+        self
+    }
+
+    /// Sets the freshness lifetime of responses without `Cache-Control` or `Expires` headers
+    /// that give them one.
+    ///
+    /// Only applies to responses with status codes that are cacheable by default, such as
+    /// `200 OK` and `404 Not Found`. By default, such responses are not cached.
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        Arc::make_mut(&mut self.config).default_ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the freshness lifetime of all cached responses, overriding their `Cache-Control` and
+    /// `Expires` headers.
+    ///
+    /// Responses that must not be stored, according to the rules above, are still not cached.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        Arc::make_mut(&mut self.config).ttl = Some(ttl);
+        self
+    }
+
+    /// Removes cached responses for the URL made of `scheme`, `host`, and `path_and_query`, such
+    /// as `https`, `example.com`, and `/users?page=2`.
+    pub fn invalidate(&self, scheme: &str, host: &str, path_and_query: &str) {
+        self.store.remove(&cache_key(scheme, host, path_and_query));
+    }
+
+    /// Returns the freshness lifetime of a response, or `None` if it must not be stored.
+    fn freshness_lifetime(
+        &self,
+        req_headers: &HeaderMap,
+        res: &ServiceResponse<impl MessageBody>,
+    ) -> Option<Duration> {
+        let status = res.status();
+        let headers = res.headers();
+
+        if status.is_informational()
+            || matches!(
+                status,
+                StatusCode::PARTIAL_CONTENT | StatusCode::NOT_MODIFIED
+            )
+            || headers.contains_key(SET_COOKIE)
+            || vary_names(headers).is_none()
+        {
+            return None;
+        }
+
+        let directives = cache_directives(headers);
+        let has = |directive: &CacheDirective| directives.contains(directive);
+
+        if has(&CacheDirective::NoStore)
+            || has(&CacheDirective::NoCache)
+            || has(&CacheDirective::Private)
+        {
+            return None;
+        }
+
+        if req_headers.contains_key(AUTHORIZATION)
+            && !has(&CacheDirective::Public)
+            && !has(&CacheDirective::MustRevalidate)
+            && !directives
+                .iter()
+                .any(|directive| matches!(directive, CacheDirective::SMaxAge(_)))
+        {
+            return None;
+        }
+
+        if let Some(ttl) = self.config.ttl {
+            return Some(ttl);
+        }
+
+        let s_max_age = directives.iter().find_map(|directive| match directive {
+            CacheDirective::SMaxAge(secs) => Some(*secs),
+            _ => None,
+        });
+        let max_age = directives.iter().find_map(|directive| match directive {
+            CacheDirective::MaxAge(secs) => Some(*secs),
+            _ => None,
+        });
+
+        if let Some(secs) = s_max_age.or(max_age) {
+            return Some(Duration::from_secs(secs.into()));
+        }
+
+        if let Some(expires) = headers.get(EXPIRES) {
+            // invalid dates, like "0", mean the response is already expired
+            let expires = parse_date(expires)?;
+            let date = headers
+                .get(DATE)
+                .and_then(parse_date)
+                .unwrap_or_else(SystemTime::now);
+
+            return Some(expires.duration_since(date).unwrap_or_default());
+        }
+
+        if is_heuristically_cacheable(status) {
+            return self.config.default_ttl;
+        }
+
+        None
+    }
+}
+
+impl fmt::Debug for Cache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cache")
+            .field("max_body_size", &self.config.max_body_size)
+            .field("default_ttl", &self.config.default_ttl)
+            .field("ttl", &self.config.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Cache
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = CacheMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CacheMiddleware {
+            service: Rc::new(service),
+            cache: self.clone(),
+        }))
+    }
+}
+
+/// Cache middleware service.
+pub struct CacheMiddleware<S> {
+    service: Rc<S>,
+    cache: Cache,
+}
+
+impl<S, B> Service<ServiceRequest> for CacheMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let cache = self.cache.clone();
+
+        Box::pin(async move {
+            let key = {
+                let conn = req.connection_info();
+                let path_and_query = req
+                    .uri()
+                    .path_and_query()
+                    .map_or_else(|| req.path(), |pq| pq.as_str());
+
+                cache_key(conn.scheme(), conn.host(), path_and_query)
+            };
+
+            if req.method() != Method::GET {
+                let unsafe_method = !req.method().is_safe();
+                let res = service.call(req).await?;
+
+                if unsafe_method && (res.status().is_success() || res.status().is_redirection()) {
+                    cache.store.remove(&key);
+                }
+
+                return Ok(res.map_into_left_body());
+            }
+
+            let directives = cache_directives(req.headers());
+            let bypass = directives.iter().any(|directive| {
+                matches!(
+                    directive,
+                    CacheDirective::NoCache | CacheDirective::NoStore | CacheDirective::MaxAge(0)
+                )
+            });
+
+            if !bypass {
+                if let Some(res) = lookup(&cache, &key, &req, &directives) {
+                    return Ok(req.into_response(res).map_into_right_body());
+                }
+            }
+
+            let no_store = directives.contains(&CacheDirective::NoStore);
+            let mut res = service.call(req).await?;
+
+            let freshness_lifetime = match no_store {
+                true => None,
+                false => cache.freshness_lifetime(res.request().headers(), &res),
+            };
+
+            let cacheable_size = match res.response().body().size() {
+                BodySize::None => Some(0),
+                BodySize::Sized(size) => usize::try_from(size)
+                    .ok()
+                    .filter(|&size| size <= cache.config.max_body_size),
+                BodySize::Stream => None,
+            };
+
+            let (Some(freshness_lifetime), Some(_)) = (freshness_lifetime, cacheable_size) else {
+                let status = if bypass { "fwd=request" } else { "fwd=miss" };
+                res.headers_mut().insert(CACHE_STATUS, cache_status(status));
+                return Ok(res.map_into_left_body());
+            };
+
+            let (req, res) = res.into_parts();
+            let (mut res, body) = res.into_parts();
+
+            let body = body::to_bytes(body)
+                .await
+                .map_err(|err| error::ErrorInternalServerError(err.into()))?;
+
+            let headers = res.headers();
+            let vary = vary_names(headers)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|name| {
+                    let values = req.headers().get_all(&name).cloned().collect();
+                    (name, values)
+                })
+                .collect();
+
+            let initial_age = headers
+                .get(AGE)
+                .and_then(|age| age.to_str().ok())
+                .and_then(|age| age.parse().ok())
+                .map_or(Duration::ZERO, Duration::from_secs);
+
+            let cached = CachedResponse {
+                status: res.status(),
+                headers: headers.clone(),
+                body: body.clone(),
+                vary,
+                response_time: SystemTime::now(),
+                initial_age,
+                freshness_lifetime,
+            };
+
+            let mut entry = cache.store.get(&key).unwrap_or_default();
+            entry.variants.retain(|variant| variant.vary != cached.vary);
+            entry.variants.push(cached);
+            cache.store.insert(key, entry);
+
+            res.headers_mut()
+                .insert(CACHE_STATUS, cache_status("fwd=miss; stored"));
+
+            let res = res.set_body(EitherBody::right(BoxBody::new(body)));
+            Ok(ServiceResponse::new(req, res))
+        })
+    }
+}
+
+/// Returns a fresh cached response for the request, if any.
+fn lookup(
+    cache: &Cache,
+    key: &str,
+    req: &ServiceRequest,
+    directives: &[CacheDirective],
+) -> Option<HttpResponse> {
+    let entry = cache.store.get(key)?;
+    let cached = entry
+        .variants
+        .iter()
+        .find(|variant| variant.matches(req.headers()))?;
+
+    let age = cached.age(SystemTime::now());
+
+    // requests can ask for responses that stay fresh for longer, or that are younger
+    let mut lifetime = cached.freshness_lifetime;
+    for directive in directives {
+        match directive {
+            CacheDirective::MaxAge(secs) => {
+                lifetime = lifetime.min(Duration::from_secs((*secs).into()))
+            }
+            CacheDirective::MinFresh(secs) => {
+                lifetime = lifetime.saturating_sub(Duration::from_secs((*secs).into()))
+            }
+            _ => {}
+        }
+    }
+
+    if age >= lifetime {
+        return None;
+    }
+
+    let mut res = HttpResponse::with_body(cached.status, cached.body.clone());
+    for (name, value) in &cached.headers {
+        res.headers_mut().append(name.clone(), value.clone());
+    }
+
+    res.headers_mut()
+        .insert(AGE, HeaderValue::from(age.as_secs()));
+    res.headers_mut().insert(
+        CACHE_STATUS,
+        cache_status(&format!(
+            "hit; ttl={}",
+            cached
+                .freshness_lifetime
+                .as_secs()
+                .saturating_sub(age.as_secs())
+        )),
+    );
+
+    Some(res.map_into_boxed_body())
+}
+
+/// Returns the store key of a request URL.
+fn cache_key(scheme: &str, host: &str, path_and_query: &str) -> String {
+    format!(
+        "{}://{}{path_and_query}",
+        scheme.to_ascii_lowercase(),
+        host.to_ascii_lowercase()
+    )
+}
+
+fn cache_status(params: &str) -> HeaderValue {
+    HeaderValue::try_from(format!("actix-web; {params}")).unwrap()
+}
+
+/// Returns the cache directives in `Cache-Control` headers, ignoring invalid ones.
+fn cache_directives(headers: &HeaderMap) -> Vec<CacheDirective> {
+    headers
+        .get_all(CACHE_CONTROL)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|directive| directive.trim().to_ascii_lowercase().parse().ok())
+        .collect()
+}
+
+/// Returns the header names in `Vary` headers, or `None` if they include `*`.
+fn vary_names(headers: &HeaderMap) -> Option<Vec<HeaderName>> {
+    let mut names = Vec::new();
+
+    for value in headers.get_all(VARY) {
+        for name in value.to_str().unwrap_or("*").split(',') {
+            let name = name.trim();
+
+            if name == "*" {
+                return None;
+            }
+
+            if let Ok(name) = HeaderName::try_from(name) {
+                names.push(name);
+            }
+        }
+    }
+
+    Some(names)
+}
+
+fn parse_date(value: &HeaderValue) -> Option<SystemTime> {
+    value
+        .to_str()
+        .ok()?
+        .parse::<HttpDate>()
+        .ok()
+        .map(SystemTime::from)
+}
+
+/// Returns true if responses with the status code are cacheable without explicit freshness.
+///
+/// See <https://www.rfc-editor.org/rfc/rfc9110#section-15.1>.
+fn is_heuristically_cacheable(status: StatusCode) -> bool {
+    matches!(
+        status.as_u16(),
+        200 | 203 | 204 | 206 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::{
+        http::header::{self, TryIntoHeaderValue as _},
+        test::{self, TestRequest},
+        web, App, HttpRequest,
+    };
+
+    async fn app_with(
+        cache: Cache,
+        calls: Rc<Cell<usize>>,
+        res: impl Fn(&HttpRequest) -> HttpResponse + Clone + 'static,
+    ) -> impl Service<actix_http::Request, Response = ServiceResponse<impl MessageBody>, Error = Error>
+    {
+        test::init_service(App::new().wrap(cache).default_service(web::to(
+            move |req: HttpRequest| {
+                calls.set(calls.get() + 1);
+                let res = res(&req);
+                ready(res)
+            },
+        )))
+        .await
+    }
+
+    fn max_age(secs: u32) -> (HeaderName, HeaderValue) {
+        (
+            CACHE_CONTROL,
+            header::CacheControl(vec![CacheDirective::MaxAge(secs)])
+                .try_into_value()
+                .unwrap(),
+        )
+    }
+
+    #[actix_rt::test]
+    async fn caches_fresh_responses() {
+        let calls = Rc::new(Cell::new(0));
+        let app = app_with(Cache::in_memory(10), calls.clone(), |req| {
+            HttpResponse::Ok()
+                .insert_header(max_age(60))
+                .body(format!("hello {}", req.query_string()))
+        })
+        .await;
+
+        let req = TestRequest::with_uri("/?a").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(
+            res.headers().get(CACHE_STATUS).unwrap(),
+            "actix-web; fwd=miss; stored"
+        );
+        assert_eq!(test::read_body(res).await, "hello a");
+
+        let req = TestRequest::with_uri("/?a").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(
+            res.headers().get(CACHE_STATUS).unwrap(),
+            "actix-web; hit; ttl=60"
+        );
+        assert_eq!(res.headers().get(AGE).unwrap(), "0");
+        assert_eq!(res.headers().get(CACHE_CONTROL).unwrap(), "max-age=60");
+        assert_eq!(test::read_body(res).await, "hello a");
+        assert_eq!(calls.get(), 1);
+
+        // different query is a different request target
+        let req = TestRequest::with_uri("/?b").to_request();
+        assert_eq!(test::call_and_read_body(&app, req).await, "hello b");
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn request_directives() {
+        let calls = Rc::new(Cell::new(0));
+        let app = app_with(Cache::in_memory(10), calls.clone(), |_| {
+            HttpResponse::Ok().insert_header(max_age(60)).finish()
+        })
+        .await;
+
+        let req = TestRequest::default()
+            .insert_header((CACHE_CONTROL, "no-store"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(
+            res.headers().get(CACHE_STATUS).unwrap(),
+            "actix-web; fwd=request"
+        );
+
+        // not stored
+        let req = TestRequest::default().to_request();
+        test::call_service(&app, req).await;
+        assert_eq!(calls.get(), 2);
+
+        let req = TestRequest::default()
+            .insert_header((CACHE_CONTROL, "no-cache"))
+            .to_request();
+        test::call_service(&app, req).await;
+        assert_eq!(calls.get(), 3);
+
+        let req = TestRequest::default().to_request();
+        test::call_service(&app, req).await;
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn uncacheable_responses() {
+        let calls = Rc::new(Cell::new(0));
+        let app = app_with(Cache::in_memory(10), calls.clone(), |req| {
+            let mut res = HttpResponse::Ok();
+            res.insert_header(max_age(60));
+
+            match req.path() {
+                "/private" => res.insert_header((CACHE_CONTROL, "private")),
+                "/no-store" => res.insert_header((CACHE_CONTROL, "no-store")),
+                "/vary-star" => res.insert_header((VARY, "*")),
+                "/cookie" => res.insert_header((SET_COOKIE, "a=b")),
+                "/no-freshness" => res.insert_header((CACHE_CONTROL, "public")),
+                _ => &mut res,
+            };
+
+            res.finish()
+        })
+        .await;
+
+        for path in [
+            "/private",
+            "/no-store",
+            "/vary-star",
+            "/cookie",
+            "/no-freshness",
+        ] {
+            for _ in 0..2 {
+                let req = TestRequest::with_uri(path).to_request();
+                let res = test::call_service(&app, req).await;
+                assert_eq!(
+                    res.headers().get(CACHE_STATUS).unwrap(),
+                    "actix-web; fwd=miss"
+                );
+            }
+        }
+
+        assert_eq!(calls.get(), 10);
+    }
+
+    #[actix_rt::test]
+    async fn authorization() {
+        let calls = Rc::new(Cell::new(0));
+        let app = app_with(Cache::in_memory(10), calls.clone(), |req| {
+            let mut res = HttpResponse::Ok();
+            res.insert_header(max_age(60));
+
+            if req.path() == "/public" {
+                res.insert_header((CACHE_CONTROL, "public, max-age=60"));
+            }
+
+            res.finish()
+        })
+        .await;
+
+        for path in ["/", "/", "/public", "/public"] {
+            let req = TestRequest::with_uri(path)
+                .insert_header((AUTHORIZATION, "Bearer token"))
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn vary() {
+        let calls = Rc::new(Cell::new(0));
+        let app = app_with(Cache::in_memory(10), calls.clone(), |req| {
+            let lang = req
+                .headers()
+                .get("accept-language")
+                .map_or("en", |lang| lang.to_str().unwrap())
+                .to_owned();
+
+            HttpResponse::Ok()
+                .insert_header(max_age(60))
+                .insert_header((VARY, "Accept-Language"))
+                .body(lang)
+        })
+        .await;
+
+        for lang in ["de", "fr", "de", "fr"] {
+            let req = TestRequest::default()
+                .insert_header(("accept-language", lang))
+                .to_request();
+            assert_eq!(test::call_and_read_body(&app, req).await, lang);
+        }
+
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn ttl_overrides() {
+        let calls = Rc::new(Cell::new(0));
+        let app = app_with(
+            Cache::in_memory(10).default_ttl(Duration::from_secs(5)),
+            calls.clone(),
+            |req| match req.path() {
+                "/created" => HttpResponse::Created().finish(),
+                _ => HttpResponse::Ok().finish(),
+            },
+        )
+        .await;
+
+        for path in ["/", "/", "/created", "/created"] {
+            let req = TestRequest::with_uri(path).to_request();
+            test::call_service(&app, req).await;
+        }
+
+        // 201 is not cacheable by default
+        assert_eq!(calls.get(), 3);
+
+        let calls = Rc::new(Cell::new(0));
+        let app = app_with(
+            Cache::in_memory(10).ttl(Duration::from_secs(30)),
+            calls.clone(),
+            |_| {
+                HttpResponse::Ok()
+                    .insert_header((CACHE_CONTROL, "max-age=0"))
+                    .finish()
+            },
+        )
+        .await;
+
+        for _ in 0..2 {
+            let req = TestRequest::default().to_request();
+            test::call_service(&app, req).await;
+        }
+
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn invalidation() {
+        let cache = Cache::in_memory(10);
+
+        let calls = Rc::new(Cell::new(0));
+        let app = app_with(cache.clone(), calls.clone(), |_| {
+            HttpResponse::Ok().insert_header(max_age(60)).finish()
+        })
+        .await;
+
+        let get = || TestRequest::with_uri("/item").to_request();
+
+        test::call_service(&app, get()).await;
+        test::call_service(&app, get()).await;
+        assert_eq!(calls.get(), 1);
+
+        let req = TestRequest::post().uri("/item").to_request();
+        test::call_service(&app, req).await;
+        assert_eq!(calls.get(), 2);
+
+        test::call_service(&app, get()).await;
+        test::call_service(&app, get()).await;
+        assert_eq!(calls.get(), 3);
+
+        // other hosts are not invalidated
+        cache.invalidate("http", "example.com", "/item");
+        test::call_service(&app, get()).await;
+        assert_eq!(calls.get(), 3);
+
+        cache.invalidate("http", "LOCALHOST:8080", "/item");
+        test::call_service(&app, get()).await;
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[actix_rt::test]
+    async fn keyed_by_scheme_and_host() {
+        let calls = Rc::new(Cell::new(0));
+        let app = app_with(Cache::in_memory(10), calls.clone(), |req| {
+            let conn = req.connection_info();
+            let body = format!("{}://{}", conn.scheme(), conn.host());
+            HttpResponse::Ok().insert_header(max_age(60)).body(body)
+        })
+        .await;
+
+        let req = |host: &str| {
+            TestRequest::default()
+                .insert_header((header::HOST, host))
+                .to_request()
+        };
+
+        let res = test::call_and_read_body(&app, req("a.example.com")).await;
+        assert_eq!(res, "http://a.example.com");
+        let res = test::call_and_read_body(&app, req("b.example.com")).await;
+        assert_eq!(res, "http://b.example.com");
+        assert_eq!(calls.get(), 2);
+
+        let res = test::call_and_read_body(&app, req("A.example.com")).await;
+        assert_eq!(res, "http://a.example.com");
+        assert_eq!(calls.get(), 2);
+
+        let req = TestRequest::default()
+            .insert_header((header::HOST, "a.example.com"))
+            .insert_header((header::FORWARDED, "proto=https"))
+            .to_request();
+        let res = test::call_and_read_body(&app, req).await;
+        assert_eq!(res, "https://a.example.com");
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn large_and_streaming_bodies() {
+        let calls = Rc::new(Cell::new(0));
+        let app = app_with(
+            Cache::in_memory(10).max_body_size(4),
+            calls.clone(),
+            |req| {
+                let mut res = HttpResponse::Ok();
+                res.insert_header(max_age(60));
+
+                match req.path() {
+                    "/stream" => res.streaming(futures_util::stream::once(async {
+                        Ok::<_, Error>(Bytes::from_static(b"ok"))
+                    })),
+                    _ => res.body("too large"),
+                }
+            },
+        )
+        .await;
+
+        for path in ["/stream", "/stream", "/large", "/large"] {
+            let req = TestRequest::with_uri(path).to_request();
+            test::call_service(&app, req).await;
+        }
+
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn memory_store_evicts_least_recently_used() {
+        let store = MemoryCacheStore::new(2);
+
+        store.insert("a".to_owned(), CacheEntry::default());
+        store.insert("b".to_owned(), CacheEntry::default());
+        assert!(store.get("a").is_some());
+
+        store.insert("c".to_owned(), CacheEntry::default());
+        assert!(store.get("a").is_some());
+        assert!(store.get("b").is_none());
+        assert!(store.get("c").is_some());
+
+        store.remove("a");
+        assert!(store.get("a").is_none());
+    }
+
+    #[test]
+    fn expires_freshness() {
+        let cache = Cache::in_memory(1);
+
+        let res = |headers: &[(HeaderName, &str)]| {
+            let mut res = HttpResponse::Ok();
+            for (name, value) in headers {
+                res.insert_header((name.clone(), *value));
+            }
+            TestRequest::default().to_srv_response(res.finish())
+        };
+
+        let date = "Wed, 21 Oct 2015 07:28:00 GMT";
+        let expires = "Wed, 21 Oct 2015 07:29:00 GMT";
+
+        let headers = HeaderMap::new();
+        assert_eq!(
+            cache.freshness_lifetime(&headers, &res(&[(DATE, date), (EXPIRES, expires)])),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            cache.freshness_lifetime(&headers, &res(&[(EXPIRES, "0")])),
+            None
+        );
+        assert_eq!(
+            cache.freshness_lifetime(&headers, &res(&[(CACHE_CONTROL, "max-age=5, s-maxage=10")])),
+            Some(Duration::from_secs(10))
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! For middleware documentation, see [`CatchPanic`].
+
+use std::{
+    any::Any,
+    future::Future,
+    marker::PhantomData,
+    net::SocketAddr,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_router::{Path, Url};
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use pin_project_lite::pin_project;
+use smallvec::SmallVec;
+
+use crate::{
+    app_service::AppInitServiceState,
+    body::EitherBody,
+    dev::Extensions,
+    http::{Method, Uri, Version},
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpRequest, HttpResponse,
+};
+
+type PanicHook = dyn Fn(&HttpRequest, &(dyn Any + Send));
+
+/// Middleware for converting panics in wrapped services into `500 Internal Server Error`
+/// responses.
+///
+/// Without this middleware, a panicking handler drops its connection, and whether the client
+/// receives a response depends on how far the response was written. With it, panics that occur
+/// while a wrapped service is called or while its response future is polled are caught and
+/// answered with an empty `500 Internal Server Error` response. Panics that occur while streaming
+/// a response body are not caught.
+///
+/// By default, caught panics are logged at the error level. A [hook](Self::on_panic()) can be set
+/// to report them elsewhere, such as to an alerting service. Hooks receive a request with the
+/// method, URI, version, peer address, and app data of the request that caused the panic, but
+/// without its headers, match info, or extensions, since the original request is lost when the
+/// wrapped service panics.
+///
+/// Handlers must still be unwind safe for the application to keep working correctly after a panic.
+/// For example, data behind a `Mutex` that was locked by the panicking handler will be poisoned.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::CatchPanic, web, App};
+///
+/// let app = App::new()
+///     .wrap(CatchPanic::new().on_panic(|req, payload| {
+///         let message = CatchPanic::message(payload).unwrap_or("unknown panic");
+///         eprintln!("{} {} panicked: {message}", req.method(), req.uri());
+///     }))
+///     .route("/", web::get().to(|| async { "hello" }));
+/// ```
+#[derive(Clone, Default)]
+pub struct CatchPanic {
+    hook: Option<Rc<PanicHook>>,
+}
+
+impl CatchPanic {
+    /// Constructs a middleware that logs caught panics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a hook that is called with each caught panic instead of logging it.
+    pub fn on_panic<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&HttpRequest, &(dyn Any + Send)) + 'static,
+    {
+        self.hook = Some(Rc::new(hook));
+        self
+    }
+
+    /// Returns the message of a panic payload, if it is a string.
+    pub fn message(payload: &(dyn Any + Send)) -> Option<&str> {
+        payload
+            .downcast_ref::<&'static str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CatchPanic
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = CatchPanicMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CatchPanicMiddleware {
+            service,
+            hook: self.hook.clone(),
+        }))
+    }
+}
+
+/// Panic catching middleware service.
+pub struct CatchPanicMiddleware<S> {
+    service: S,
+    hook: Option<Rc<PanicHook>>,
+}
+
+impl<S, B> Service<ServiceRequest> for CatchPanicMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = CatchPanicFuture<S, B>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let snapshot = RequestSnapshot::new(req.request());
+        let hook = self.hook.clone();
+
+        match panic::catch_unwind(AssertUnwindSafe(|| self.service.call(req))) {
+            Ok(fut) => CatchPanicFuture {
+                fut: Some(fut),
+                panic_res: None,
+                snapshot: Some(snapshot),
+                hook,
+                _body: PhantomData,
+            },
+
+            Err(payload) => CatchPanicFuture {
+                fut: None,
+                panic_res: Some(snapshot.panic_response(hook.as_deref(), payload)),
+                snapshot: None,
+                hook: None,
+                _body: PhantomData,
+            },
+        }
+    }
+}
+
+pin_project! {
+    pub struct CatchPanicFuture<S: Service<ServiceRequest>, B> {
+        #[pin]
+        fut: Option<S::Future>,
+        panic_res: Option<ServiceResponse>,
+        snapshot: Option<RequestSnapshot>,
+        hook: Option<Rc<PanicHook>>,
+        _body: PhantomData<B>,
+    }
+}
+
+impl<S, B> Future for CatchPanicFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = Result<ServiceResponse<EitherBody<B>>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+
+        let Some(mut fut) = this.fut.as_mut().as_pin_mut() else {
+            let res = this
+                .panic_res
+                .take()
+                .expect("CatchPanicFuture polled after completion");
+            return Poll::Ready(Ok(res.map_into_right_body()));
+        };
+
+        match panic::catch_unwind(AssertUnwindSafe(|| fut.as_mut().poll(cx))) {
+            Ok(Poll::Ready(res)) => Poll::Ready(res.map(ServiceResponse::map_into_left_body)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => {
+                let snapshot = this
+                    .snapshot
+                    .take()
+                    .expect("CatchPanicFuture polled after completion");
+
+                let res = snapshot.panic_response(this.hook.as_deref(), payload);
+                Poll::Ready(Ok(res.map_into_right_body()))
+            }
+        }
+    }
+}
+
+/// Parts of a request that are kept to respond after the request is lost to a panic.
+struct RequestSnapshot {
+    method: Method,
+    uri: Uri,
+    version: Version,
+    peer_addr: Option<SocketAddr>,
+    app_state: Rc<AppInitServiceState>,
+    app_data: SmallVec<[Rc<Extensions>; 4]>,
+    conn_data: Option<Rc<Extensions>>,
+}
+
+impl RequestSnapshot {
+    fn new(req: &HttpRequest) -> Self {
+        Self {
+            method: req.method().clone(),
+            uri: req.uri().clone(),
+            version: req.version(),
+            peer_addr: req.peer_addr(),
+            app_state: Rc::clone(&req.inner.app_state),
+            app_data: req.inner.app_data.clone(),
+            conn_data: req.inner.conn_data.clone(),
+        }
+    }
+
+    fn into_request(self) -> HttpRequest {
+        let (mut head, _) = actix_http::Request::new().into_parts();
+        head.method = self.method;
+        head.uri = self.uri.clone();
+        head.version = self.version;
+        head.peer_addr = self.peer_addr;
+
+        let mut req = HttpRequest::new(
+            Path::new(Url::new(self.uri)),
+            head,
+            self.app_state,
+            Rc::default(),
+            self.conn_data,
+            Default::default(),
+        );
+
+        // new request is not shared yet
+        Rc::get_mut(&mut req.inner).unwrap().app_data = self.app_data;
+
+        req
+    }
+
+    fn panic_response(
+        self,
+        hook: Option<&PanicHook>,
+        payload: Box<dyn Any + Send>,
+    ) -> ServiceResponse {
+        let req = self.into_request();
+
+        match hook {
+            Some(hook) => hook(&req, payload.as_ref()),
+            None => log::error!(
+                "Handler for {} {} panicked: {}",
+                req.method(),
+                req.uri(),
+                CatchPanic::message(payload.as_ref()).unwrap_or("Box<dyn Any>"),
+            ),
+        }
+
+        ServiceResponse::new(req, HttpResponse::InternalServerError().finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use actix_service::fn_service;
+
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App,
+    };
+
+    #[actix_rt::test]
+    async fn catches_handler_panics() {
+        let panics = Rc::new(RefCell::new(Vec::new()));
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(42_u32))
+                .wrap(CatchPanic::new().on_panic({
+                    let panics = Rc::clone(&panics);
+                    move |req, payload| {
+                        assert_eq!(*req.app_data::<web::Data<u32>>().unwrap().get_ref(), 42);
+
+                        panics.borrow_mut().push(format!(
+                            "{} {}: {}",
+                            req.method(),
+                            req.uri(),
+                            CatchPanic::message(payload).unwrap()
+                        ));
+                    }
+                }))
+                .route("/", web::get().to(|| async { "ok" }))
+                .route(
+                    "/panic",
+                    web::post().to(|| async {
+                        if true {
+                            panic!("oh no");
+                        }
+
+                        "unreachable"
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::post().uri("/panic?a=1").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(*panics.borrow(), ["POST /panic?a=1: oh no"]);
+
+        // app keeps working
+        let req = TestRequest::default().to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn catches_call_panics() {
+        let srv = fn_service(|req: ServiceRequest| {
+            if req.path() == "/panic" {
+                panic!("{} panicked", req.path());
+            }
+
+            ready(Ok::<_, Error>(req.into_response(HttpResponse::Ok())))
+        });
+
+        let mw = CatchPanic::new().new_transform(srv).await.unwrap();
+
+        let req = TestRequest::with_uri("/panic").to_srv_request();
+        let res = mw.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let req = TestRequest::default().to_srv_request();
+        let res = mw.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn panic_message() {
+        let payload: Box<dyn Any + Send> = Box::new("static");
+        assert_eq!(CatchPanic::message(payload.as_ref()), Some("static"));
+
+        let payload: Box<dyn Any + Send> = Box::new(String::from("owned"));
+        assert_eq!(CatchPanic::message(payload.as_ref()), Some("owned"));
+
+        let payload: Box<dyn Any + Send> = Box::new(42);
+        assert_eq!(CatchPanic::message(payload.as_ref()), None);
+    }
+}
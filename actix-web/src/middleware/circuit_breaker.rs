@@ -0,0 +1,645 @@
+//! For middleware documentation, see [`CircuitBreaker`].
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{err, ok, ready, Ready};
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    body::{EitherBody, MessageBody},
+    dev::Payload,
+    error,
+    http::header::{HeaderValue, RETRY_AFTER},
+    service::{ServiceRequest, ServiceResponse},
+    Error, FromRequest, HttpMessage as _, HttpRequest, HttpResponse,
+};
+
+/// State of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CircuitState {
+    /// Requests are passed to the wrapped services.
+    Closed,
+
+    /// Requests receive the fallback response without reaching the wrapped services.
+    Open,
+
+    /// A limited number of probe requests are passed to the wrapped services to decide whether to
+    /// close the circuit again.
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Returns the name of the state in snake case, e.g., `half_open`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Middleware that stops passing requests to failing services for a while.
+///
+/// Useful for scopes whose handlers proxy to flaky upstream services. Responses with 5xx status
+/// codes and errors returned by the wrapped services count as failures. Once too many requests
+/// fail, the circuit opens and requests immediately receive a fallback response, giving the
+/// upstream service time to recover. After the [open duration](Self::open_duration()), the
+/// circuit is half-open and a limited number of [probe requests](Self::half_open_probes()) are let
+/// through. If all of them succeed, the circuit closes; if any fails, it opens again.
+///
+/// By default, the circuit opens after 5 consecutive failures. Use
+/// [`failure_rate()`](Self::failure_rate()) to open it based on the fraction of recent requests
+/// that failed instead. The default fallback response is `503 Service Unavailable` with a
+/// `Retry-After` header.
+///
+/// The state is shared by clones of the middleware, including across workers when the instance is
+/// created outside the `HttpServer` factory closure.
+///
+/// # Extractor
+/// `CircuitBreaker` can be extracted in handlers to report its [`state()`](Self::state()), such as
+/// in health endpoints. Handlers wrapped by the middleware extract the breaker that wraps them.
+/// Other handlers extract a breaker registered using [`App::app_data()`](crate::App::app_data()).
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{middleware::CircuitBreaker, web, App, HttpResponse};
+///
+/// let breaker = CircuitBreaker::new()
+///     .consecutive_failures(3)
+///     .open_duration(Duration::from_secs(10))
+///     .fallback(|_req| HttpResponse::BadGateway().body("payments are unavailable"));
+///
+/// let app = App::new()
+///     .app_data(breaker.clone())
+///     .service(
+///         web::scope("/payments")
+///             .wrap(breaker)
+///             .route("", web::post().to(|| async { "paid" })),
+///     )
+///     .route(
+///         "/health/payments",
+///         web::get().to(|breaker: CircuitBreaker| async move { breaker.state().to_string() }),
+///     );
+/// ```
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    config: Arc<Config>,
+    state: Arc<Mutex<State>>,
+}
+
+type FallbackFn = dyn Fn(&ServiceRequest) -> HttpResponse + Send + Sync;
+
+struct Config {
+    trip: Trip,
+    open_duration: Duration,
+    half_open_probes: usize,
+    fallback: Option<Arc<FallbackFn>>,
+}
+
+/// Condition for opening a closed circuit.
+#[derive(Debug, Clone, Copy)]
+enum Trip {
+    Consecutive(usize),
+    Rate { threshold: f64, window: usize },
+}
+
+#[derive(Debug)]
+enum State {
+    Closed {
+        consecutive_failures: usize,
+        recent: VecDeque<bool>,
+    },
+    Open {
+        until: Instant,
+    },
+    HalfOpen {
+        in_flight: usize,
+        succeeded: usize,
+    },
+}
+
+impl State {
+    fn closed() -> Self {
+        State::Closed {
+            consecutive_failures: 0,
+            recent: VecDeque::new(),
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitBreaker {
+    /// Constructs a closed circuit breaker with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            config: Arc::new(Config {
+                trip: Trip::Consecutive(5),
+                open_duration: Duration::from_secs(30),
+                half_open_probes: 1,
+                fallback: None,
+            }),
+            state: Arc::new(Mutex::new(State::closed())),
+        }
+    }
+
+    /// Opens the circuit after `failures` consecutive requests fail.
+    ///
+    /// # Panics
+    /// Panics if `failures` is zero.
+    pub fn consecutive_failures(mut self, failures: usize) -> Self {
+        assert!(failures > 0, "number of failures must be greater than zero");
+
+        self.config_mut().trip = Trip::Consecutive(failures);
+        self
+    }
+
+    /// Opens the circuit once at least `threshold` of the last `window` requests failed.
+    ///
+    /// The circuit does not open until `window` requests have completed since it was last closed.
+    ///
+    /// # Panics
+    /// Panics if `threshold` is not in the range `(0, 1]` or if `window` is zero.
+    pub fn failure_rate(mut self, threshold: f64, window: usize) -> Self {
+        assert!(
+            threshold > 0.0 && threshold <= 1.0,
+            "failure rate threshold must be in the range (0, 1]"
+        );
+        assert!(window > 0, "failure rate window must be greater than zero");
+
+        self.config_mut().trip = Trip::Rate { threshold, window };
+        self
+    }
+
+    /// Sets how long the circuit stays open before probe requests are let through.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn open_duration(mut self, duration: Duration) -> Self {
+        self.config_mut().open_duration = duration;
+        self
+    }
+
+    /// Sets the number of probe requests that must succeed for a half-open circuit to close.
+    ///
+    /// Probe requests are processed concurrently; other requests receive the fallback response
+    /// while they are in flight. Defaults to 1.
+    ///
+    /// # Panics
+    /// Panics if `probes` is zero.
+    pub fn half_open_probes(mut self, probes: usize) -> Self {
+        assert!(probes > 0, "number of probes must be greater than zero");
+
+        self.config_mut().half_open_probes = probes;
+        self
+    }
+
+    /// Sets the function that produces responses while the circuit is open.
+    pub fn fallback<F>(mut self, fallback: F) -> Self
+    where
+        F: Fn(&ServiceRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.config_mut().fallback = Some(Arc::new(fallback));
+        self
+    }
+
+    /// Returns the current state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        let mut state = self.state.lock().unwrap();
+
+        match *state {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until } if Instant::now() < until => CircuitState::Open,
+            State::Open { .. } => {
+                *state = State::HalfOpen {
+                    in_flight: 0,
+                    succeeded: 0,
+                };
+                CircuitState::HalfOpen
+            }
+            State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    fn config_mut(&mut self) -> &mut Config {
+        Arc::get_mut(&mut self.config)
+            .expect("circuit breaker can not be configured after it has been cloned")
+    }
+
+    /// Decides whether a request may be passed to the wrapped service.
+    fn admit(&self) -> Option<Admission> {
+        // moves an open circuit whose open duration has elapsed to half-open
+        self.state();
+
+        match &mut *self.state.lock().unwrap() {
+            State::Closed { .. } => Some(Admission::Normal),
+
+            State::HalfOpen { in_flight, .. } if *in_flight < self.config.half_open_probes => {
+                *in_flight += 1;
+                Some(Admission::Probe)
+            }
+
+            _ => None,
+        }
+    }
+
+    /// Records the outcome of an admitted request.
+    fn record(&self, admission: Admission, failed: bool) {
+        let mut state = self.state.lock().unwrap();
+
+        match (&mut *state, admission) {
+            (
+                State::Closed {
+                    consecutive_failures,
+                    recent,
+                },
+                Admission::Normal,
+            ) => {
+                let trip = match self.config.trip {
+                    Trip::Consecutive(failures) => {
+                        *consecutive_failures = if failed { *consecutive_failures + 1 } else { 0 };
+
+                        *consecutive_failures >= failures
+                    }
+
+                    Trip::Rate { threshold, window } => {
+                        if recent.len() == window {
+                            recent.pop_front();
+                        }
+                        recent.push_back(failed);
+
+                        let failures = recent.iter().filter(|&&failed| failed).count();
+                        recent.len() == window && failures as f64 >= threshold * window as f64
+                    }
+                };
+
+                if trip {
+                    *state = State::Open {
+                        until: Instant::now() + self.config.open_duration,
+                    };
+                }
+            }
+
+            (
+                State::HalfOpen {
+                    in_flight,
+                    succeeded,
+                },
+                Admission::Probe,
+            ) => {
+                *in_flight -= 1;
+
+                if failed {
+                    *state = State::Open {
+                        until: Instant::now() + self.config.open_duration,
+                    };
+                } else {
+                    *succeeded += 1;
+
+                    if *succeeded >= self.config.half_open_probes {
+                        *state = State::closed();
+                    }
+                }
+            }
+
+            // outcomes of requests admitted in an earlier state are ignored
+            _ => {}
+        }
+    }
+
+    /// Releases the probe slot of a request that was cancelled before it completed.
+    fn cancel(&self, admission: Admission) {
+        let mut state = self.state.lock().unwrap();
+
+        if let (State::HalfOpen { in_flight, .. }, Admission::Probe) = (&mut *state, admission) {
+            *in_flight -= 1;
+        }
+    }
+
+    /// Returns the time until an open circuit is half-open.
+    fn remaining_open(&self) -> Duration {
+        match *self.state.lock().unwrap() {
+            State::Open { until } => until.saturating_duration_since(Instant::now()),
+            _ => Duration::ZERO,
+        }
+    }
+
+    fn fallback_response(&self, req: &ServiceRequest) -> HttpResponse {
+        if let Some(fallback) = &self.config.fallback {
+            return fallback(req);
+        }
+
+        // probe requests in flight mean a half-open circuit could close soon
+        let remaining = self.remaining_open().max(Duration::from_secs(1));
+        let secs = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+
+        HttpResponse::ServiceUnavailable()
+            .insert_header((RETRY_AFTER, HeaderValue::from(secs)))
+            .finish()
+    }
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("trip", &self.config.trip)
+            .field("open_duration", &self.config.open_duration)
+            .field("half_open_probes", &self.config.half_open_probes)
+            .field("state", &self.state())
+            .finish()
+    }
+}
+
+/// Extracts the circuit breaker that wraps the handler, or the one registered as app data.
+impl FromRequest for CircuitBreaker {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if let Some(breaker) = req.extensions().get::<CircuitBreaker>() {
+            return ok(breaker.clone());
+        }
+
+        match req.app_data::<CircuitBreaker>() {
+            Some(breaker) => ok(breaker.clone()),
+            None => {
+                log::debug!(
+                    "Failed to extract `CircuitBreaker` for `{}` handler. Wrap the handler with \
+                    the middleware or pass the breaker to `App::app_data()`.",
+                    req.match_name().unwrap_or_else(|| req.path())
+                );
+
+                err(error::ErrorInternalServerError(
+                    "Circuit breaker is not configured correctly. \
+                    View/enable debug logs for more details.",
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Admission {
+    Normal,
+    Probe,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CircuitBreaker
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = CircuitBreakerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CircuitBreakerMiddleware {
+            service: Rc::new(service),
+            breaker: self.clone(),
+        }))
+    }
+}
+
+/// Circuit breaker middleware service.
+pub struct CircuitBreakerMiddleware<S> {
+    service: Rc<S>,
+    breaker: CircuitBreaker,
+}
+
+impl<S, B> Service<ServiceRequest> for CircuitBreakerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        req.extensions_mut().insert(self.breaker.clone());
+
+        let Some(admission) = self.breaker.admit() else {
+            let res = self.breaker.fallback_response(&req);
+            return Box::pin(ready(Ok(req.into_response(res).map_into_right_body())));
+        };
+
+        let service = Rc::clone(&self.service);
+        let guard = OutcomeGuard {
+            breaker: self.breaker.clone(),
+            admission: Some(admission),
+        };
+
+        Box::pin(async move {
+            let res = service.call(req).await;
+
+            let failed = match &res {
+                Ok(res) => res.status().is_server_error(),
+                Err(_) => true,
+            };
+            guard.record(failed);
+
+            res.map(ServiceResponse::map_into_left_body)
+        })
+    }
+}
+
+/// Records the outcome of an admitted request, or its cancellation when dropped.
+struct OutcomeGuard {
+    breaker: CircuitBreaker,
+    admission: Option<Admission>,
+}
+
+impl OutcomeGuard {
+    fn record(mut self, failed: bool) {
+        if let Some(admission) = self.admission.take() {
+            self.breaker.record(admission, failed);
+        }
+    }
+}
+
+impl Drop for OutcomeGuard {
+    fn drop(&mut self) {
+        if let Some(admission) = self.admission.take() {
+            self.breaker.cancel(admission);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_service::IntoService as _;
+
+    use super::*;
+    use crate::{
+        body::BoxBody,
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App,
+    };
+
+    /// Service that responds with the status in the `status` query parameter.
+    fn status_service(
+    ) -> impl Service<ServiceRequest, Response = ServiceResponse, Error = Error> + 'static {
+        (|req: ServiceRequest| {
+            let status = req.query_string().parse().unwrap_or(200);
+            let res = HttpResponse::new(StatusCode::from_u16(status).unwrap());
+            ok::<_, Error>(req.into_response(res))
+        })
+        .into_service()
+    }
+
+    async fn call<S>(mw: &S, status: u16) -> StatusCode
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<EitherBody<BoxBody>>, Error = Error>,
+    {
+        let req = TestRequest::with_uri(&format!("/?{status}")).to_srv_request();
+        mw.call(req).await.unwrap().status()
+    }
+
+    #[actix_rt::test]
+    async fn opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new().consecutive_failures(2);
+        let mw = breaker.new_transform(status_service()).await.unwrap();
+
+        assert_eq!(call(&mw, 500).await, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(call(&mw, 200).await, StatusCode::OK);
+        assert_eq!(call(&mw, 500).await, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        assert_eq!(call(&mw, 502).await, StatusCode::BAD_GATEWAY);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        let req = TestRequest::default().to_srv_request();
+        let res = mw.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "30");
+    }
+
+    #[actix_rt::test]
+    async fn opens_on_failure_rate() {
+        let breaker = CircuitBreaker::new().failure_rate(0.5, 4);
+        let mw = breaker.new_transform(status_service()).await.unwrap();
+
+        call(&mw, 500).await;
+        call(&mw, 500).await;
+        call(&mw, 200).await;
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        call(&mw, 200).await;
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[actix_rt::test]
+    async fn half_open_probes() {
+        let breaker = CircuitBreaker::new()
+            .consecutive_failures(1)
+            .open_duration(Duration::ZERO)
+            .half_open_probes(2);
+        let mw = breaker.new_transform(status_service()).await.unwrap();
+
+        call(&mw, 500).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // a failed probe re-opens the circuit
+        assert_eq!(call(&mw, 500).await, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // in-flight probes exhaust the probe slots
+        let probe1 = mw.call(TestRequest::default().to_srv_request());
+        let probe2 = mw.call(TestRequest::default().to_srv_request());
+        assert_eq!(call(&mw, 200).await, StatusCode::SERVICE_UNAVAILABLE);
+
+        assert_eq!(probe1.await.unwrap().status(), StatusCode::OK);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert_eq!(probe2.await.unwrap().status(), StatusCode::OK);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[actix_rt::test]
+    async fn cancelled_probe_releases_slot() {
+        let breaker = CircuitBreaker::new()
+            .consecutive_failures(1)
+            .open_duration(Duration::ZERO);
+        let mw = breaker.new_transform(status_service()).await.unwrap();
+
+        call(&mw, 500).await;
+
+        drop(mw.call(TestRequest::default().to_srv_request()));
+
+        assert_eq!(call(&mw, 200).await, StatusCode::OK);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[actix_rt::test]
+    async fn custom_fallback() {
+        let breaker = CircuitBreaker::new()
+            .consecutive_failures(1)
+            .fallback(|_req| HttpResponse::BadGateway().body("unavailable"));
+        let mw = breaker.new_transform(status_service()).await.unwrap();
+
+        call(&mw, 500).await;
+        assert_eq!(call(&mw, 200).await, StatusCode::BAD_GATEWAY);
+    }
+
+    #[actix_rt::test]
+    async fn state_extractor() {
+        let breaker = CircuitBreaker::new().consecutive_failures(1);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(breaker.clone())
+                .service(web::scope("/upstream").wrap(breaker.clone()).route(
+                    "",
+                    web::get().to(|breaker: CircuitBreaker| async move {
+                        (breaker.state().as_str(), StatusCode::INTERNAL_SERVER_ERROR)
+                    }),
+                ))
+                .route(
+                    "/health",
+                    web::get()
+                        .to(|breaker: CircuitBreaker| async move { breaker.state().to_string() }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/upstream").to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "closed");
+
+        let req = TestRequest::with_uri("/health").to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "open");
+    }
+
+    #[actix_rt::test]
+    async fn missing_extractor_data() {
+        let req = TestRequest::default().to_http_request();
+        let res = CircuitBreaker::extract(&req).await;
+        assert!(res.is_err());
+    }
+}
@@ -4,10 +4,11 @@ use std::{
     future::Future,
     marker::PhantomData,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
-use actix_http::encoding::Encoder;
+use actix_http::encoding::{Encoder, EncoderConfig};
 use actix_service::{Service, Transform};
 use actix_utils::future::{ok, Either, Ready};
 use futures_core::ready;
@@ -16,7 +17,7 @@ use once_cell::sync::Lazy;
 use pin_project_lite::pin_project;
 
 use crate::{
-    body::{EitherBody, MessageBody},
+    body::{BodySize, EitherBody, MessageBody},
     http::{
         header::{self, AcceptEncoding, ContentEncoding, Encoding, HeaderValue},
         StatusCode,
@@ -46,6 +47,16 @@ use crate::{
 ///
 /// A (naïve) example serving an pre-compressed Gzip file is included below.
 ///
+/// # Configuration
+/// The compression level and window size of each encoding can be tuned. By default, encodings are
+/// configured to favor speed over compression ratio. Responses can also be left uncompressed when
+/// their size is known to be below a [minimum size](Self::min_size()), or when their content type
+/// is not in a [list of allowed content types](Self::content_type()).
+///
+/// Settings can be overridden for a scope or resource by wrapping it with a differently configured
+/// `Compress`. Responses that were handled by an inner `Compress` are not changed by outer ones,
+/// even when the inner one left them uncompressed.
+///
 /// # Examples
 /// To enable automatic payload compression just include `Compress` as a top-level middleware:
 /// ```
@@ -71,10 +82,172 @@ use crate::{
 ///     .default_service(web::to(index_handler));
 /// ```
 ///
+/// Tuning compression globally, and using higher compression levels for a scope of static
+/// assets:
+/// ```
+/// use actix_web::{middleware::Compress, web, App, HttpResponse};
+///
+/// let compress = Compress::default().min_size(1024).zstd_level(3);
+///
+/// let app = App::new()
+///     .wrap(compress.clone())
+///     .service(
+///         web::scope("/assets")
+///             .wrap(
+///                 compress
+///                     .brotli_quality(9)
+///                     .zstd_level(19)
+///                     .content_type(mime::TEXT_STAR)
+///                     .content_type(mime::APPLICATION_JAVASCRIPT),
+///             )
+///             .default_service(web::to(|| async { HttpResponse::Ok().body("body {}") })),
+///     );
+/// ```
+///
 /// [feature flags]: ../index.html#crate-features
 #[derive(Debug, Clone, Default)]
-#[non_exhaustive]
-pub struct Compress;
+pub struct Compress {
+    config: Arc<CompressConfig>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CompressConfig {
+    encoder: EncoderConfig,
+    min_size: u64,
+
+    /// Content types to compress, or empty to compress all types other than images and videos.
+    content_types: Vec<Mime>,
+}
+
+impl Compress {
+    /// Sets the gzip compression level, from 0 (no compression) to 9 (best compression).
+    ///
+    /// Defaults to 1.
+    ///
+    /// # Panics
+    /// Panics if `level` is greater than 9.
+    pub fn gzip_level(mut self, level: u32) -> Self {
+        let config = self.config_mut();
+        config.encoder = config.encoder.gzip_level(level);
+        self
+    }
+
+    /// Sets the deflate compression level, from 0 (no compression) to 9 (best compression).
+    ///
+    /// Defaults to 1.
+    ///
+    /// # Panics
+    /// Panics if `level` is greater than 9.
+    pub fn deflate_level(mut self, level: u32) -> Self {
+        let config = self.config_mut();
+        config.encoder = config.encoder.deflate_level(level);
+        self
+    }
+
+    /// Sets the brotli quality, from 0 (fastest) to 11 (best compression).
+    ///
+    /// Defaults to 3.
+    ///
+    /// # Panics
+    /// Panics if `quality` is greater than 11.
+    pub fn brotli_quality(mut self, quality: u32) -> Self {
+        let config = self.config_mut();
+        config.encoder = config.encoder.brotli_quality(quality);
+        self
+    }
+
+    /// Sets the base-2 logarithm of the brotli window size, from 10 (1 KiB) to 24 (16 MiB).
+    ///
+    /// Defaults to 22 (4 MiB).
+    ///
+    /// # Panics
+    /// Panics if `lgwin` is not between 10 and 24.
+    pub fn brotli_window(mut self, lgwin: u32) -> Self {
+        let config = self.config_mut();
+        config.encoder = config.encoder.brotli_window(lgwin);
+        self
+    }
+
+    /// Sets the zstd compression level, from 1 (fastest) to 22 (best compression).
+    ///
+    /// Defaults to 3.
+    ///
+    /// # Panics
+    /// Panics if `level` is not between 1 and 22.
+    pub fn zstd_level(mut self, level: i32) -> Self {
+        let config = self.config_mut();
+        config.encoder = config.encoder.zstd_level(level);
+        self
+    }
+
+    /// Sets the base-2 logarithm of the zstd window size, from 10 (1 KiB) to 27 (128 MiB).
+    ///
+    /// By default, the window size is chosen based on the compression level. Browsers may refuse
+    /// to decompress responses with windows larger than 8 MiB, which is a window log of 23.
+    ///
+    /// # Panics
+    /// Panics if `window_log` is not between 10 and 27.
+    pub fn zstd_window_log(mut self, window_log: u32) -> Self {
+        let config = self.config_mut();
+        config.encoder = config.encoder.zstd_window_log(window_log);
+        self
+    }
+
+    /// Sets the size, in bytes, of the smallest response body to compress.
+    ///
+    /// Streaming bodies, whose size is not known in advance, are always compressed. Defaults to
+    /// zero.
+    pub fn min_size(mut self, size: u64) -> Self {
+        self.config_mut().min_size = size;
+        self
+    }
+
+    /// Adds a content type to the list of content types to compress.
+    ///
+    /// Parameters of content types are ignored, and a subtype of `*` allows all subtypes of a
+    /// type, such as [`mime::TEXT_STAR`]. Once a content type is added, responses with other, or
+    /// missing, content types are not compressed. By default, all responses are compressed except
+    /// those with `image/*` and `video/*` content types.
+    pub fn content_type(mut self, content_type: Mime) -> Self {
+        self.config_mut().content_types.push(content_type);
+        self
+    }
+
+    fn config_mut(&mut self) -> &mut CompressConfig {
+        Arc::make_mut(&mut self.config)
+    }
+}
+
+impl CompressConfig {
+    fn should_compress(&self, content_type: Option<&HeaderValue>, size: BodySize) -> bool {
+        if matches!(size, BodySize::Sized(size) if size < self.min_size) {
+            return false;
+        }
+
+        let mime = content_type
+            .and_then(|hdr| hdr.to_str().ok())
+            .and_then(|hdr| hdr.parse::<Mime>().ok());
+
+        if self.content_types.is_empty() {
+            return match mime {
+                Some(mime) => !matches!(mime.type_().as_str(), "image" | "video"),
+                None => true,
+            };
+        }
+
+        let Some(mime) = mime else {
+            return false;
+        };
+
+        self.content_types.iter().any(|allowed| {
+            allowed.type_() == mime.type_()
+                && (allowed.subtype() == mime::STAR || allowed.subtype() == mime.subtype())
+        })
+    }
+}
+
+/// Marks responses that were handled by a `Compress` middleware.
+struct Handled;
 
 impl<S, B> Transform<S, ServiceRequest> for Compress
 where
@@ -88,12 +261,16 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ok(CompressMiddleware { service })
+        ok(CompressMiddleware {
+            service,
+            config: Arc::clone(&self.config),
+        })
     }
 }
 
 pub struct CompressMiddleware<S> {
     service: S,
+    config: Arc<CompressConfig>,
 }
 
 impl<S, B> Service<ServiceRequest> for CompressMiddleware<S>
@@ -119,6 +296,7 @@ where
                 return Either::left(CompressResponse {
                     encoding: Encoding::identity(),
                     fut: self.service.call(req),
+                    config: Arc::clone(&self.config),
                     _phantom: PhantomData,
                 })
             }
@@ -146,6 +324,7 @@ where
             Some(encoding) => Either::left(CompressResponse {
                 fut: self.service.call(req),
                 encoding,
+                config: Arc::clone(&self.config),
                 _phantom: PhantomData,
             }),
         }
@@ -160,6 +339,7 @@ pin_project! {
         #[pin]
         fut: S::Future,
         encoding: Encoding,
+        config: Arc<CompressConfig>,
         _phantom: PhantomData<B>,
     }
 }
@@ -175,7 +355,7 @@ where
         let this = self.as_mut().project();
 
         match ready!(this.fut.poll(cx)) {
-            Ok(resp) => {
+            Ok(mut resp) => {
                 let enc = match this.encoding {
                     Encoding::Known(enc) => *enc,
                     Encoding::Unknown(enc) => {
@@ -183,29 +363,27 @@ where
                     }
                 };
 
+                // responses handled by an inner `Compress` follow its configuration
+                let handled = resp.response().extensions().contains::<Handled>();
+                resp.response_mut().extensions_mut().insert(Handled);
+
+                let config: &CompressConfig = this.config;
+
                 Poll::Ready(Ok(resp.map_body(move |head, body| {
                     let content_type = head.headers.get(header::CONTENT_TYPE);
 
-                    fn default_compress_predicate(content_type: Option<&HeaderValue>) -> bool {
-                        match content_type {
-                            None => true,
-                            Some(hdr) => {
-                                match hdr.to_str().ok().and_then(|hdr| hdr.parse::<Mime>().ok()) {
-                                    Some(mime) if mime.type_().as_str() == "image" => false,
-                                    Some(mime) if mime.type_().as_str() == "video" => false,
-                                    _ => true,
-                                }
-                            }
-                        }
-                    }
-
-                    let enc = if default_compress_predicate(content_type) {
+                    let enc = if !handled && config.should_compress(content_type, body.size()) {
                         enc
                     } else {
                         ContentEncoding::Identity
                     };
 
-                    EitherBody::left(Encoder::response(enc, head, body))
+                    EitherBody::left(Encoder::response_with_config(
+                        enc,
+                        head,
+                        body,
+                        &config.encoder,
+                    ))
                 })))
             }
 
@@ -447,6 +625,135 @@ mod tests {
         assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
         assert!(test::read_body(res).await.is_empty());
     }
+
+    #[actix_rt::test]
+    async fn min_size() {
+        let app = test::init_service({
+            App::new()
+                .wrap(Compress::default().min_size(TEXT_DATA.len() as u64 + 1))
+                .route(
+                    "/sized",
+                    web::get().to(|| HttpResponse::Ok().body(TEXT_DATA)),
+                )
+                .route(
+                    "/stream",
+                    web::get().to(|| {
+                        HttpResponse::Ok().streaming(futures_util::stream::once(async {
+                            Ok::<_, Error>(bytes::Bytes::from_static(TEXT_DATA.as_bytes()))
+                        }))
+                    }),
+                )
+        })
+        .await;
+
+        let req =
+            test::TestRequest::with_uri("/sized").insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(test::read_body(res).await, TEXT_DATA.as_bytes());
+
+        let req =
+            test::TestRequest::with_uri("/stream").insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+        assert_eq!(
+            gzip_decode(test::read_body(res).await),
+            TEXT_DATA.as_bytes()
+        );
+    }
+
+    #[actix_rt::test]
+    async fn content_type_allowlist() {
+        let app = test::init_service(
+            App::new()
+                .wrap(Compress::default().content_type(mime::TEXT_STAR))
+                .configure(configure_predicate_test)
+                .route(
+                    "/json",
+                    web::get().to(|| {
+                        HttpResponse::Ok()
+                            .content_type(ContentType::json())
+                            .body(TEXT_DATA)
+                    }),
+                ),
+        )
+        .await;
+
+        let req =
+            test::TestRequest::with_uri("/html").insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_successful_gzip_res_with_content_type(&res, "text/html");
+
+        let req =
+            test::TestRequest::with_uri("/json").insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_successful_identity_res_with_content_type(&res, "application/json");
+    }
+
+    #[actix_rt::test]
+    async fn scope_overrides() {
+        let app = test::init_service({
+            App::new()
+                .wrap(Compress::default())
+                .service(
+                    web::scope("/uncompressed")
+                        .wrap(Compress::default().min_size(u64::MAX))
+                        .route("", web::get().to(|| HttpResponse::Ok().body(TEXT_DATA))),
+                )
+                .service(
+                    web::scope("/best")
+                        .wrap(Compress::default().gzip_level(9))
+                        .route("", web::get().to(|| HttpResponse::Ok().body(TEXT_DATA))),
+                )
+        })
+        .await;
+
+        let req = test::TestRequest::with_uri("/uncompressed")
+            .insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(test::read_body(res).await, TEXT_DATA.as_bytes());
+
+        let req =
+            test::TestRequest::with_uri("/best").insert_header((header::ACCEPT_ENCODING, "gzip"));
+        let res = test::call_service(&app, req.to_request()).await;
+        assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+        assert_eq!(
+            gzip_decode(test::read_body(res).await),
+            TEXT_DATA.as_bytes()
+        );
+    }
+}
+
+#[cfg(feature = "compress-zstd")]
+#[cfg(test)]
+mod tests_zstd {
+    use super::*;
+    use crate::{test, web, App};
+
+    const TEXT_DATA: &str = const_str::repeat!("hello world ", 100);
+
+    #[actix_rt::test]
+    async fn tuned_level_and_window() {
+        let app = test::init_service({
+            App::new()
+                .wrap(Compress::default().zstd_level(19).zstd_window_log(16))
+                .default_service(web::to(move || HttpResponse::Ok().body(TEXT_DATA)))
+        })
+        .await;
+
+        let req = test::TestRequest::default()
+            .insert_header((header::ACCEPT_ENCODING, "zstd"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "zstd");
+
+        let bytes = test::read_body(res).await;
+        assert_eq!(
+            zstd::stream::decode_all(&bytes[..]).unwrap(),
+            TEXT_DATA.as_bytes()
+        );
+    }
 }
 
 #[cfg(feature = "compress-brotli")]
@@ -0,0 +1,411 @@
+//! For middleware documentation, see [`ConcurrencyLimit`].
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    body::{EitherBody, MessageBody},
+    http::header::{HeaderValue, RETRY_AFTER},
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpResponse,
+};
+
+/// Middleware for limiting the number of requests that are processed concurrently.
+///
+/// Requests beyond the limit wait in a first-in, first-out queue until an in-flight request
+/// completes. When the queue is also full, requests are rejected immediately with a
+/// `503 Service Unavailable` response that includes a `Retry-After` header, shedding load instead
+/// of accumulating unbounded numbers of waiting requests. A request counts as in-flight until the
+/// wrapped service has produced its response; streaming the response body is not limited.
+///
+/// Readiness of the wrapped service is forwarded, so services that apply backpressure using
+/// `poll_ready` still cause connections to stop accepting requests.
+///
+/// The limit is shared by clones of the middleware. To limit requests across all workers, create a
+/// single instance outside the `HttpServer` factory closure and clone it into each app. Instances
+/// created inside the closure limit requests per worker. Wrap a [`Scope`](crate::Scope) to limit
+/// only requests to its services.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{middleware::ConcurrencyLimit, web, App};
+///
+/// let limit = ConcurrencyLimit::new(100)
+///     .queue_size(1_000)
+///     .retry_after(Duration::from_secs(5));
+///
+/// let app = App::new().service(
+///     web::scope("/reports")
+///         .wrap(limit.clone())
+///         .route("", web::get().to(|| async { "report" })),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit {
+    limiter: Arc<Limiter>,
+}
+
+impl ConcurrencyLimit {
+    /// Constructs a middleware that processes at most `max_in_flight` requests concurrently.
+    ///
+    /// By default, no requests are queued and rejections suggest retrying after one second.
+    ///
+    /// # Panics
+    /// Panics if `max_in_flight` is zero.
+    pub fn new(max_in_flight: usize) -> Self {
+        assert!(
+            max_in_flight > 0,
+            "concurrency limit must be greater than zero"
+        );
+
+        Self {
+            limiter: Arc::new(Limiter {
+                max_in_flight,
+                queue_size: 0,
+                retry_after: Duration::from_secs(1),
+                state: Mutex::new(State::default()),
+            }),
+        }
+    }
+
+    /// Sets the number of requests that can wait for an in-flight request to complete.
+    ///
+    /// Defaults to zero, which rejects all requests beyond the limit.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn queue_size(mut self, queue_size: usize) -> Self {
+        self.limiter_mut().queue_size = queue_size;
+        self
+    }
+
+    /// Sets the delay suggested by the `Retry-After` header of rejections.
+    ///
+    /// The delay is rounded up to whole seconds. Defaults to one second.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn retry_after(mut self, delay: Duration) -> Self {
+        self.limiter_mut().retry_after = delay;
+        self
+    }
+
+    fn limiter_mut(&mut self) -> &mut Limiter {
+        Arc::get_mut(&mut self.limiter)
+            .expect("concurrency limit can not be configured after it has been cloned")
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ConcurrencyLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = ConcurrencyLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ConcurrencyLimitMiddleware {
+            service: Rc::new(service),
+            limiter: Arc::clone(&self.limiter),
+        }))
+    }
+}
+
+/// Concurrency limit middleware service.
+pub struct ConcurrencyLimitMiddleware<S> {
+    service: Rc<S>,
+    limiter: Arc<Limiter>,
+}
+
+impl<S, B> Service<ServiceRequest> for ConcurrencyLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let acquire = match Limiter::acquire(&self.limiter) {
+            Some(acquire) => acquire,
+            None => {
+                let secs = self.limiter.retry_after.as_secs()
+                    + u64::from(self.limiter.retry_after.subsec_nanos() > 0);
+
+                let res = HttpResponse::ServiceUnavailable()
+                    .insert_header((RETRY_AFTER, HeaderValue::from(secs)))
+                    .finish();
+
+                return Box::pin(ready(Ok(req.into_response(res).map_into_right_body())));
+            }
+        };
+
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            let permit = match acquire {
+                Acquired::Permit(permit) => permit,
+                Acquired::Queued(queued) => queued.await,
+            };
+            let res = service.call(req).await;
+            drop(permit);
+
+            res.map(ServiceResponse::map_into_left_body)
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Limiter {
+    max_in_flight: usize,
+    queue_size: usize,
+    retry_after: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    in_flight: usize,
+    next_id: u64,
+
+    /// Waiting requests, in order of arrival.
+    queue: VecDeque<(u64, Option<Waker>)>,
+
+    /// Waiting requests that were handed a permit but have not been polled since.
+    granted: Vec<u64>,
+}
+
+impl Limiter {
+    /// Acquires a permit, or a place in the queue, unless both are exhausted.
+    fn acquire(this: &Arc<Self>) -> Option<Acquired> {
+        let mut state = this.state.lock().unwrap();
+
+        if state.in_flight < this.max_in_flight && state.queue.is_empty() {
+            state.in_flight += 1;
+
+            return Some(Acquired::Permit(Permit {
+                limiter: Arc::clone(this),
+            }));
+        }
+
+        if state.queue.len() < this.queue_size {
+            let id = state.next_id;
+            state.next_id += 1;
+            state.queue.push_back((id, None));
+
+            return Some(Acquired::Queued(Queued {
+                limiter: Arc::clone(this),
+                id: Some(id),
+            }));
+        }
+
+        None
+    }
+
+    /// Hands the permit of a completed request to the next waiting request, if any.
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        match state.queue.pop_front() {
+            Some((id, waker)) => {
+                state.granted.push(id);
+
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            }
+            None => state.in_flight -= 1,
+        }
+    }
+}
+
+enum Acquired {
+    Permit(Permit),
+    Queued(Queued),
+}
+
+/// Future of a waiting request that resolves to a permit once the request may be processed.
+struct Queued {
+    limiter: Arc<Limiter>,
+
+    /// ID of the waiting request, or `None` once it has resolved.
+    id: Option<u64>,
+}
+
+impl Future for Queued {
+    type Output = Permit;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(id) = self.id {
+            let mut state = self.limiter.state.lock().unwrap();
+
+            match state.granted.iter().position(|&granted| granted == id) {
+                Some(idx) => {
+                    state.granted.swap_remove(idx);
+                }
+
+                None => {
+                    if let Some((_, waker)) =
+                        state.queue.iter_mut().find(|(queued, _)| *queued == id)
+                    {
+                        *waker = Some(cx.waker().clone());
+                    }
+
+                    return Poll::Pending;
+                }
+            }
+
+            drop(state);
+            self.id = None;
+        }
+
+        Poll::Ready(Permit {
+            limiter: Arc::clone(&self.limiter),
+        })
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        let Some(id) = self.id else {
+            // the permit was handed to a `Permit`
+            return;
+        };
+
+        let mut state = self.limiter.state.lock().unwrap();
+
+        if let Some(idx) = state.queue.iter().position(|(queued, _)| *queued == id) {
+            state.queue.remove(idx);
+        } else if let Some(idx) = state.granted.iter().position(|&granted| granted == id) {
+            // pass on the permit that was handed to this request
+            state.granted.swap_remove(idx);
+            drop(state);
+            self.limiter.release();
+        }
+    }
+}
+
+/// Permit of an in-flight request, which is released when dropped.
+struct Permit {
+    limiter: Arc<Limiter>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.limiter.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{self, TestRequest},
+    };
+
+    #[actix_rt::test]
+    async fn rejects_beyond_limit() {
+        let mw = ConcurrencyLimit::new(1)
+            .retry_after(Duration::from_millis(2500))
+            .new_transform(test::ok_service())
+            .await
+            .unwrap();
+
+        // holds its permit until it is polled to completion
+        let first = mw.call(TestRequest::default().to_srv_request());
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "3");
+
+        assert_eq!(first.await.unwrap().status(), StatusCode::OK);
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn queues_up_to_queue_size() {
+        let mw = ConcurrencyLimit::new(1)
+            .queue_size(1)
+            .new_transform(test::ok_service())
+            .await
+            .unwrap();
+
+        let first = mw.call(TestRequest::default().to_srv_request());
+        let queued = mw.call(TestRequest::default().to_srv_request());
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // queued request is polled, and woken, before the first request completes
+        let (queued, first) = futures_util::future::join(queued, first).await;
+        assert_eq!(first.unwrap().status(), StatusCode::OK);
+        assert_eq!(queued.unwrap().status(), StatusCode::OK);
+
+        let state = mw.limiter.state.lock().unwrap();
+        assert_eq!(state.in_flight, 0);
+        assert!(state.queue.is_empty());
+        assert!(state.granted.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn dropped_requests_release_permits() {
+        let mw = ConcurrencyLimit::new(1)
+            .queue_size(2)
+            .new_transform(test::ok_service())
+            .await
+            .unwrap();
+
+        let first = mw.call(TestRequest::default().to_srv_request());
+        let granted = mw.call(TestRequest::default().to_srv_request());
+        let queued = mw.call(TestRequest::default().to_srv_request());
+
+        // permit is handed to the queued request, which is dropped before it is polled
+        drop(first);
+        drop(granted);
+
+        assert_eq!(queued.await.unwrap().status(), StatusCode::OK);
+
+        let state = mw.limiter.state.lock().unwrap();
+        assert_eq!(state.in_flight, 0);
+        assert!(state.queue.is_empty());
+        assert!(state.granted.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_limit() {
+        let _ = ConcurrencyLimit::new(0);
+    }
+}
@@ -0,0 +1,411 @@
+//! For middleware documentation, see [`ConditionalGet`].
+
+use std::{collections::HashSet, hash::BuildHasher as _, rc::Rc};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use foldhash::quality::FixedState;
+use futures_core::future::LocalBoxFuture;
+
+use crate::{
+    body::{self, BodySize, BoxBody, EitherBody, MessageBody},
+    error,
+    http::{
+        header::{self, EntityTag, IfNoneMatch, TryIntoHeaderValue as _},
+        Method, StatusCode,
+    },
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpMessage as _,
+};
+
+/// Middleware for adding strong `ETag` headers to responses and answering conditional requests.
+///
+/// The bodies of `200 OK` responses to `GET` and `HEAD` requests are hashed to create their entity
+/// tags. When the `If-None-Match` header of a request matches the entity tag of its response, the
+/// response is replaced by a `304 Not Modified` response without a body.
+///
+/// Responses that already have an `ETag` header keep it, and are still answered with
+/// `304 Not Modified` when they match. Bodies are only hashed if their size is known in advance and
+/// within the [size limits](Self::min_size()), so streaming responses are passed through unchanged.
+///
+/// Entity tags are stable across restarts and workers, but hashes are not cryptographically secure
+/// and should not be used to detect tampering.
+///
+/// # Middleware Order
+/// Entity tags are created from the bodies produced by the wrapped services. When used with
+/// [`Compress`], register this middleware before it, using an earlier call to `wrap`, so that the
+/// uncompressed body is hashed. Note that compressed responses then share the entity tag of the
+/// uncompressed representation.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::ConditionalGet, web, App};
+///
+/// let app = App::new()
+///     .wrap(ConditionalGet::new().exclude("/events/{id}"))
+///     .route("/", web::get().to(|| async { "Hello, world!" }))
+///     .route("/events/{id}", web::get().to(|| async { "event" }));
+/// ```
+///
+/// [`Compress`]: crate::middleware::Compress
+#[derive(Debug, Clone)]
+pub struct ConditionalGet {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    min_size: usize,
+    max_size: usize,
+    exclude: HashSet<String>,
+}
+
+impl ConditionalGet {
+    /// Constructs a middleware that hashes bodies of up to 1 MiB.
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                min_size: 0,
+                max_size: 1024 * 1024,
+                exclude: HashSet::new(),
+            }),
+        }
+    }
+
+    /// Sets the size, in bytes, of the smallest body to hash.
+    ///
+    /// Small bodies gain little from `304 Not Modified` responses. Defaults to zero.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn min_size(mut self, size: usize) -> Self {
+        self.inner_mut().min_size = size;
+        self
+    }
+
+    /// Sets the size, in bytes, of the largest body to hash.
+    ///
+    /// Bodies are buffered while they are hashed. Defaults to 1 MiB.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.inner_mut().max_size = size;
+        self
+    }
+
+    /// Passes through responses for requests matching the route pattern `pattern` unchanged.
+    ///
+    /// The pattern must be the full pattern of the route, such as `/users/{id}`, including any
+    /// scope prefixes. Requests that do not match a route are compared using their path.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.inner_mut().exclude.insert(pattern.into());
+        self
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Rc::get_mut(&mut self.inner)
+            .expect("conditional GET middleware can not be configured after it has been cloned")
+    }
+}
+
+impl Default for ConditionalGet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ConditionalGet
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = ConditionalGetMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ConditionalGetMiddleware {
+            service: Rc::new(service),
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+/// Conditional GET middleware service.
+pub struct ConditionalGetMiddleware<S> {
+    service: Rc<S>,
+    inner: Rc<Inner>,
+}
+
+impl<S, B> Service<ServiceRequest> for ConditionalGetMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let inner = Rc::clone(&self.inner);
+
+        Box::pin(async move {
+            if !matches!(*req.method(), Method::GET | Method::HEAD) {
+                return service
+                    .call(req)
+                    .await
+                    .map(ServiceResponse::map_into_left_body);
+            }
+
+            let res = service.call(req).await?;
+
+            let excluded = match res.request().match_pattern() {
+                Some(pattern) => inner.exclude.contains(&pattern),
+                None => inner.exclude.contains(res.request().path()),
+            };
+
+            if excluded || res.status() != StatusCode::OK {
+                return Ok(res.map_into_left_body());
+            }
+
+            // keep entity tags set by handlers
+            let etag = res
+                .headers()
+                .get(header::ETAG)
+                .and_then(|etag| etag.to_str().ok()?.parse::<EntityTag>().ok());
+
+            if let Some(etag) = etag {
+                let res = match is_modified(&res, &etag) {
+                    true => res.map_into_left_body(),
+                    false => not_modified(res.map_into_boxed_body()),
+                };
+
+                return Ok(res);
+            }
+
+            let size = match res.response().body().size() {
+                BodySize::None => 0,
+                BodySize::Sized(size) => match usize::try_from(size) {
+                    Ok(size) => size,
+                    Err(_) => return Ok(res.map_into_left_body()),
+                },
+                BodySize::Stream => return Ok(res.map_into_left_body()),
+            };
+
+            if size < inner.min_size || size > inner.max_size {
+                return Ok(res.map_into_left_body());
+            }
+
+            let (req, res) = res.into_parts();
+            let (mut res, body) = res.into_parts();
+
+            let body = body::to_bytes(body)
+                .await
+                .map_err(|err| error::ErrorInternalServerError(err.into()))?;
+
+            let etag = entity_tag(&body);
+            res.headers_mut().insert(
+                header::ETAG,
+                header::ETag(etag.clone()).try_into_value().unwrap(),
+            );
+
+            let res = ServiceResponse::new(req, res.set_body(BoxBody::new(body)));
+
+            let res = match is_modified(&res, &etag) {
+                true => res.map_into_right_body(),
+                false => not_modified(res),
+            };
+
+            Ok(res)
+        })
+    }
+}
+
+/// Creates a strong entity tag from the hash of a body.
+fn entity_tag(body: &[u8]) -> EntityTag {
+    let hi = FixedState::with_seed(0).hash_one(body);
+    let lo = FixedState::with_seed(1).hash_one(body);
+
+    EntityTag::new_strong(format!("{:x}-{hi:016x}{lo:016x}", body.len()))
+}
+
+/// Returns false if the `If-None-Match` header of the request matches `etag`.
+///
+/// See <https://www.rfc-editor.org/rfc/rfc9110#section-13.1.2>.
+fn is_modified<B>(res: &ServiceResponse<B>, etag: &EntityTag) -> bool {
+    match res.request().get_header::<IfNoneMatch>() {
+        Some(IfNoneMatch::Any) => false,
+        Some(IfNoneMatch::Items(items)) => !items.iter().any(|item| item.weak_eq(etag)),
+        None => true,
+    }
+}
+
+/// Replaces a response with a `304 Not Modified` response without a body.
+fn not_modified<B>(res: ServiceResponse<BoxBody>) -> ServiceResponse<EitherBody<B>> {
+    let (req, res) = res.into_parts();
+    let (mut res, _) = res.into_parts();
+
+    *res.status_mut() = StatusCode::NOT_MODIFIED;
+    res.headers_mut().remove(header::CONTENT_LENGTH);
+
+    ServiceResponse::new(req, res.set_body(EitherBody::right(BoxBody::new(()))))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::{
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    async fn etag_of(
+        app: &impl Service<
+            actix_http::Request,
+            Response = ServiceResponse<impl MessageBody>,
+            Error = Error,
+        >,
+        uri: &str,
+    ) -> Option<String> {
+        let req = TestRequest::with_uri(uri).to_request();
+        let res = test::call_service(app, req).await;
+
+        res.headers()
+            .get(header::ETAG)
+            .map(|etag| etag.to_str().unwrap().to_owned())
+    }
+
+    #[actix_rt::test]
+    async fn adds_strong_etags() {
+        let app = test::init_service(
+            App::new()
+                .wrap(ConditionalGet::new())
+                .route("/a", web::get().to(|| async { "a" }))
+                .route("/a2", web::get().to(|| async { "a" }))
+                .route("/b", web::get().to(|| async { "b" })),
+        )
+        .await;
+
+        let a = etag_of(&app, "/a").await.unwrap();
+        assert!(a.starts_with("\"1-"));
+        assert_eq!(etag_of(&app, "/a2").await.unwrap(), a);
+        assert_ne!(etag_of(&app, "/b").await.unwrap(), a);
+    }
+
+    #[actix_rt::test]
+    async fn not_modified() {
+        let app = test::init_service(
+            App::new()
+                .wrap(ConditionalGet::new())
+                .route("/", web::get().to(|| async { "hello" })),
+        )
+        .await;
+
+        let etag = etag_of(&app, "/").await.unwrap();
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, format!("\"other\", {etag}")))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), &etag);
+        assert!(test::read_body(res).await.is_empty());
+
+        // weak comparison
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, format!("W/{etag}")))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "*"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "\"other\""))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(test::read_body(res).await, "hello");
+    }
+
+    #[actix_rt::test]
+    async fn keeps_handler_etags() {
+        let app = test::init_service(App::new().wrap(ConditionalGet::new()).route(
+            "/",
+            web::get().to(|| {
+                ready(
+                    HttpResponse::Ok()
+                        .insert_header(header::ETag(EntityTag::new_weak("v1".to_owned())))
+                        .body("hello"),
+                )
+            }),
+        ))
+        .await;
+
+        assert_eq!(etag_of(&app, "/").await.unwrap(), "W/\"v1\"");
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "\"v1\""))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[actix_rt::test]
+    async fn skipped_responses() {
+        let app =
+            test::init_service(
+                App::new()
+                    .wrap(
+                        ConditionalGet::new()
+                            .min_size(2)
+                            .max_size(4)
+                            .exclude("/excluded/{id}"),
+                    )
+                    .route("/small", web::get().to(|| async { "a" }))
+                    .route("/large", web::get().to(|| async { "hello" }))
+                    .route("/sized", web::get().to(|| async { "abc" }))
+                    .route("/excluded/{id}", web::get().to(|| async { "abc" }))
+                    .route(
+                        "/created",
+                        web::get().to(|| ready(HttpResponse::Created().body("abc"))),
+                    )
+                    .route(
+                        "/stream",
+                        web::get().to(|| {
+                            ready(
+                                HttpResponse::Ok().streaming(futures_util::stream::once(ready(
+                                    Ok::<_, Error>(Bytes::from_static(b"abc")),
+                                ))),
+                            )
+                        }),
+                    ),
+            )
+            .await;
+
+        assert!(etag_of(&app, "/sized").await.is_some());
+
+        for uri in ["/small", "/large", "/excluded/1", "/created", "/stream"] {
+            assert_eq!(etag_of(&app, uri).await, None, "{uri}");
+        }
+
+        let req = TestRequest::post().uri("/sized").to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(!res.headers().contains_key(header::ETAG));
+    }
+}
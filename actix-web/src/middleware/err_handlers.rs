@@ -1,7 +1,9 @@
 //! For middleware documentation, see [`ErrorHandlers`].
 
 use std::{
+    error::Error as StdError,
     future::Future,
+    ops::{Bound, RangeBounds, RangeInclusive},
     pin::Pin,
     rc::Rc,
     task::{Context, Poll},
@@ -12,10 +14,16 @@ use foldhash::HashMap as FoldHashMap;
 use futures_core::{future::LocalBoxFuture, ready};
 use pin_project_lite::pin_project;
 
+use bytes::Bytes;
+
 use crate::{
-    body::EitherBody,
+    body::{self, EitherBody, MessageBody},
     dev::{ServiceRequest, ServiceResponse},
-    http::StatusCode,
+    error::{ErrorInternalServerError, Problem, PROBLEM_JSON},
+    http::{
+        header::{HeaderValue, CONTENT_TYPE},
+        StatusCode,
+    },
     Error, Result,
 };
 
@@ -44,7 +52,12 @@ type DefaultHandler<B> = Option<Rc<ErrorHandler<B>>>;
 ///
 /// To register a default for only client errors (400-499) or only server errors (500-599), use the
 /// [`ErrorHandlers::default_handler_client()`] and [`ErrorHandlers::default_handler_server()`]
-/// methods, respectively.
+/// methods, respectively. Handlers for other ranges of status codes can be registered with the
+/// [`ErrorHandlers::handler_range()`] method; they take precedence over the default handlers.
+///
+/// Handlers are synchronous functions, but async functions can be used through the
+/// [`ErrorHandlers::async_handler()`] adapter. The [`ErrorHandlers::body_handler()`] adapter also
+/// collects the response body first, so that it can be inspected and replaced by the handler.
 ///
 /// Any response with a status code that isn't covered by a specific handler or a default handler
 /// will pass by unchanged by this middleware.
@@ -179,20 +192,53 @@ type DefaultHandler<B> = Option<Rc<ErrorHandler<B>>>;
 ///     )
 ///     .service(web::resource("/").route(web::get().to(HttpResponse::InternalServerError)));
 /// ```
+///
+/// Wrapping all error responses in a JSON envelope, using an async handler:
+///
+/// ```
+/// use actix_web::{
+///     dev::ServiceResponse,
+///     middleware::ErrorHandlers,
+///     web, App, HttpResponse, Result,
+/// };
+/// use bytes::Bytes;
+///
+/// async fn json_envelope(res: ServiceResponse<Bytes>) -> Result<ServiceResponse<String>> {
+///     let (req, res) = res.into_parts();
+///
+///     let status = res.status();
+///     let message = String::from_utf8_lossy(res.body()).into_owned();
+///     let body = serde_json::json!({ "code": status.as_u16(), "message": message });
+///
+///     let res = HttpResponse::build(status)
+///         .content_type("application/json")
+///         .message_body(body.to_string())?;
+///
+///     Ok(ServiceResponse::new(req, res))
+/// }
+///
+/// let app = App::new()
+///     .wrap(ErrorHandlers::new().default_handler(ErrorHandlers::body_handler(json_envelope)))
+///     .service(web::resource("/").route(web::get().to(HttpResponse::InternalServerError)));
+/// ```
 pub struct ErrorHandlers<B> {
     default_client: DefaultHandler<B>,
     default_server: DefaultHandler<B>,
     handlers: Handlers<B>,
+    ranges: Ranges<B>,
 }
 
 type Handlers<B> = Rc<FoldHashMap<StatusCode, Box<ErrorHandler<B>>>>;
 
+type Ranges<B> = Rc<Vec<(RangeInclusive<u16>, Box<ErrorHandler<B>>)>>;
+
 impl<B> Default for ErrorHandlers<B> {
     fn default() -> Self {
         ErrorHandlers {
             default_client: Default::default(),
             default_server: Default::default(),
             handlers: Default::default(),
+            ranges: Default::default(),
         }
     }
 }
@@ -214,6 +260,33 @@ impl<B> ErrorHandlers<B> {
         self
     }
 
+    /// Register error handler for a range of status codes, such as `400..=499` or `502..`.
+    ///
+    /// Status codes with a specific handler (set by calling [`.handler()`][ErrorHandlers::handler])
+    /// are not affected. If ranges overlap, the handler registered first is used. Range handlers
+    /// take precedence over default handlers.
+    pub fn handler_range<F>(mut self, range: impl RangeBounds<u16>, handler: F) -> Self
+    where
+        F: Fn(ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> + 'static,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => u16::MIN,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end,
+            Bound::Excluded(&end) => end.saturating_sub(1),
+            Bound::Unbounded => u16::MAX,
+        };
+
+        Rc::get_mut(&mut self.ranges)
+            .unwrap()
+            .push((start..=end, Box::new(handler)));
+        self
+    }
+
     /// Register a default error handler.
     ///
     /// Any request with a status code that hasn't been given a specific other handler (by calling
@@ -264,21 +337,160 @@ impl<B> ErrorHandlers<B> {
     /// Selects the most appropriate handler for the given status code.
     ///
     /// If the `handlers` map has an entry for that status code, that handler is returned.
-    /// Otherwise, fall back on the appropriate default handler.
+    /// Otherwise, fall back on the first range handler covering it, then on the appropriate
+    /// default handler.
     fn get_handler<'a>(
         status: &StatusCode,
         default_client: Option<&'a ErrorHandler<B>>,
         default_server: Option<&'a ErrorHandler<B>>,
         handlers: &'a Handlers<B>,
+        ranges: &'a Ranges<B>,
     ) -> Option<&'a ErrorHandler<B>> {
         handlers
             .get(status)
             .map(|h| h.as_ref())
+            .or_else(|| {
+                ranges
+                    .iter()
+                    .find(|(range, _)| range.contains(&status.as_u16()))
+                    .map(|(_, h)| h.as_ref())
+            })
             .or_else(|| status.is_client_error().then_some(default_client).flatten())
             .or_else(|| status.is_server_error().then_some(default_server).flatten())
     }
 }
 
+impl<B: 'static> ErrorHandlers<B> {
+    /// Adapts an async function into an error handler.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{
+    ///     dev::ServiceResponse,
+    ///     body::EitherBody,
+    ///     http::{header, StatusCode},
+    ///     middleware::ErrorHandlers,
+    ///     Result,
+    /// };
+    ///
+    /// async fn add_retry_after<B>(
+    ///     mut res: ServiceResponse<B>,
+    /// ) -> Result<ServiceResponse<EitherBody<B>>> {
+    ///     res.response_mut()
+    ///         .headers_mut()
+    ///         .insert(header::RETRY_AFTER, header::HeaderValue::from_static("120"));
+    ///
+    ///     Ok(res.map_into_left_body())
+    /// }
+    ///
+    /// let mw = ErrorHandlers::new().handler(
+    ///     StatusCode::SERVICE_UNAVAILABLE,
+    ///     ErrorHandlers::async_handler(add_retry_after),
+    /// );
+    /// # let _: ErrorHandlers<actix_web::body::BoxBody> = mw;
+    /// ```
+    pub fn async_handler<F, Fut>(
+        handler: F,
+    ) -> impl Fn(ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> + 'static
+    where
+        F: Fn(ServiceResponse<B>) -> Fut + 'static,
+        Fut: Future<Output = Result<ServiceResponse<EitherBody<B>>>> + 'static,
+    {
+        move |res| Ok(ErrorHandlerResponse::Future(Box::pin(handler(res))))
+    }
+}
+
+impl<B> ErrorHandlers<B>
+where
+    B: MessageBody + 'static,
+{
+    /// Adapts an async function that receives the collected response body into an error handler.
+    ///
+    /// The response body is read into memory before the handler is called. The handler can
+    /// inspect it and return a response with the same or a replacement body of any type. An error
+    /// while reading the body is converted into a `500 Internal Server Error` response.
+    ///
+    /// See the [type-level docs](ErrorHandlers) for an example.
+    pub fn body_handler<F, Fut, R>(
+        handler: F,
+    ) -> impl Fn(ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> + 'static
+    where
+        F: Fn(ServiceResponse<Bytes>) -> Fut + 'static,
+        Fut: Future<Output = Result<ServiceResponse<R>>> + 'static,
+        R: MessageBody + 'static,
+    {
+        let handler = Rc::new(handler);
+
+        move |res| {
+            let handler = Rc::clone(&handler);
+
+            Ok(ErrorHandlerResponse::Future(Box::pin(async move {
+                let (req, res) = res.into_parts();
+                let (res, body) = res.into_parts();
+
+                let body = body::to_bytes(body).await.map_err(|err| {
+                    let err: Box<dyn StdError> = err.into();
+                    ErrorInternalServerError(err)
+                })?;
+
+                let res = handler(ServiceResponse::new(req, res.set_body(body))).await?;
+
+                Ok(res.map_into_boxed_body().map_into_right_body())
+            })))
+        }
+    }
+
+    /// Returns an error handler that converts error responses into [`Problem`] Details responses.
+    ///
+    /// Converted responses keep their status code and headers, but their body is replaced with an
+    /// `application/problem+json` document for the same status. For client errors (400-499), a
+    /// non-empty text body is used as the problem's `detail`. Bodies of other errors are discarded
+    /// so that internal error messages are not exposed to clients. Responses that already are
+    /// Problem Details documents, such as those of [`Problem`] errors, are left unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{middleware::ErrorHandlers, web, App, HttpResponse};
+    ///
+    /// let app = App::new()
+    ///     .wrap(ErrorHandlers::new().default_handler(ErrorHandlers::problem_handler()))
+    ///     .service(web::resource("/").route(web::get().to(HttpResponse::InternalServerError)));
+    /// ```
+    pub fn problem_handler(
+    ) -> impl Fn(ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> + 'static {
+        Self::body_handler(|res: ServiceResponse<Bytes>| async move {
+            let (req, res) = res.into_parts();
+
+            let is_problem = res
+                .headers()
+                .get(CONTENT_TYPE)
+                .is_some_and(|ct| ct.as_bytes().starts_with(PROBLEM_JSON.as_bytes()));
+
+            if is_problem {
+                return Ok(ServiceResponse::new(req, res));
+            }
+
+            let status = res.status();
+            let mut problem = Problem::new(status);
+
+            if status.is_client_error() {
+                match std::str::from_utf8(res.body()) {
+                    Ok(detail) if !detail.is_empty() => {
+                        problem = problem.with_detail(detail.to_owned());
+                    }
+                    _ => {}
+                }
+            }
+
+            let mut res = res.set_body(Bytes::from(problem.to_json().to_string()));
+            res.headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+
+            Ok(ServiceResponse::new(req, res))
+        })
+    }
+}
+
 impl<S, B> Transform<S, ServiceRequest> for ErrorHandlers<B>
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
@@ -295,12 +507,14 @@ where
         let handlers = Rc::clone(&self.handlers);
         let default_client = self.default_client.clone();
         let default_server = self.default_server.clone();
+        let ranges = Rc::clone(&self.ranges);
         Box::pin(async move {
             Ok(ErrorHandlersMiddleware {
                 service,
                 default_client,
                 default_server,
                 handlers,
+                ranges,
             })
         })
     }
@@ -312,6 +526,7 @@ pub struct ErrorHandlersMiddleware<S, B> {
     default_client: DefaultHandler<B>,
     default_server: DefaultHandler<B>,
     handlers: Handlers<B>,
+    ranges: Ranges<B>,
 }
 
 impl<S, B> Service<ServiceRequest> for ErrorHandlersMiddleware<S, B>
@@ -330,12 +545,14 @@ where
         let handlers = Rc::clone(&self.handlers);
         let default_client = self.default_client.clone();
         let default_server = self.default_server.clone();
+        let ranges = Rc::clone(&self.ranges);
         let fut = self.service.call(req);
         ErrorHandlersFuture::ServiceFuture {
             fut,
             default_client,
             default_server,
             handlers,
+            ranges,
         }
     }
 }
@@ -352,6 +569,7 @@ pin_project! {
             default_client: DefaultHandler<B>,
             default_server: DefaultHandler<B>,
             handlers: Handlers<B>,
+            ranges: Ranges<B>,
         },
         ErrorHandlerFuture {
             fut: LocalBoxFuture<'static, Result<ServiceResponse<EitherBody<B>>, Error>>,
@@ -372,6 +590,7 @@ where
                 default_client,
                 default_server,
                 handlers,
+                ranges,
             } => {
                 let res = ready!(fut.poll(cx))?;
                 let status = res.status();
@@ -381,6 +600,7 @@ where
                     default_client.as_mut().map(|f| Rc::as_ref(f)),
                     default_server.as_mut().map(|f| Rc::as_ref(f)),
                     handlers,
+                    ranges,
                 );
                 match handler {
                     Some(handler) => match handler(res)? {
@@ -413,6 +633,7 @@ mod tests {
         body,
         http::header::{HeaderValue, CONTENT_TYPE},
         test::{self, TestRequest},
+        HttpResponse, ResponseError as _,
     };
 
     #[actix_rt::test]
@@ -621,4 +842,142 @@ mod tests {
         let resp = test::call_service(&mw_specific, TestRequest::default().to_srv_request()).await;
         assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), "0003");
     }
+
+    #[actix_rt::test]
+    async fn range_handlers() {
+        fn set_content_type<B>(
+            value: &'static str,
+        ) -> impl Fn(ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> {
+            move |mut res| {
+                res.response_mut()
+                    .headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static(value));
+                Ok(ErrorHandlerResponse::Response(res.map_into_left_body()))
+            }
+        }
+
+        let make_mw = |status| async move {
+            ErrorHandlers::new()
+                .default_handler(set_content_type("default"))
+                .handler(StatusCode::BAD_GATEWAY, set_content_type("specific"))
+                .handler_range(502..=504, set_content_type("gateway"))
+                .handler_range(500.., set_content_type("server"))
+                .new_transform(test::status_service(status).into_service())
+                .await
+                .unwrap()
+        };
+
+        for (status, expected) in [
+            (StatusCode::BAD_REQUEST, "default"),
+            (StatusCode::INTERNAL_SERVER_ERROR, "server"),
+            (StatusCode::BAD_GATEWAY, "specific"),
+            (StatusCode::GATEWAY_TIMEOUT, "gateway"),
+            (StatusCode::HTTP_VERSION_NOT_SUPPORTED, "server"),
+        ] {
+            let mw = make_mw(status).await;
+            let resp = test::call_service(&mw, TestRequest::default().to_srv_request()).await;
+            assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), expected);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn async_handler() {
+        async fn error_handler<B>(
+            mut res: ServiceResponse<B>,
+        ) -> Result<ServiceResponse<EitherBody<B>>> {
+            actix_rt::task::yield_now().await;
+
+            res.response_mut()
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("0001"));
+            Ok(res.map_into_left_body())
+        }
+
+        let srv = test::status_service(StatusCode::INTERNAL_SERVER_ERROR);
+
+        let mw = ErrorHandlers::new()
+            .default_handler(ErrorHandlers::async_handler(error_handler))
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let resp = test::call_service(&mw, TestRequest::default().to_srv_request()).await;
+        assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), "0001");
+    }
+
+    #[actix_rt::test]
+    async fn body_handler() {
+        async fn error_handler(res: ServiceResponse<Bytes>) -> Result<ServiceResponse<String>> {
+            let body = format!(
+                "{}: {}",
+                res.status().as_u16(),
+                std::str::from_utf8(res.response().body()).unwrap()
+            );
+            Ok(res.map_body(|_, _| body))
+        }
+
+        let srv = |req: ServiceRequest| {
+            ok(req.into_response(HttpResponse::NotFound().body("no such thing")))
+        };
+
+        let mw = ErrorHandlers::new()
+            .handler_range(400..500, ErrorHandlers::body_handler(error_handler))
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let res = test::call_service(&mw, TestRequest::default().to_srv_request()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(test::read_body(res).await, "404: no such thing");
+    }
+
+    #[actix_rt::test]
+    async fn problem_handler() {
+        let srv = |req: ServiceRequest| {
+            let res = match req.path() {
+                "/client" => HttpResponse::NotFound()
+                    .insert_header(("x-test", "kept"))
+                    .body("no such thing"),
+                "/problem" => Problem::new(StatusCode::CONFLICT).error_response(),
+                _ => HttpResponse::InternalServerError().body("database password is hunter2"),
+            };
+            ok(req.into_response(res))
+        };
+
+        let mw = ErrorHandlers::new()
+            .default_handler(ErrorHandlers::problem_handler())
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let req = TestRequest::with_uri("/client").to_srv_request();
+        let res = test::call_service(&mw, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "application/problem+json"
+        );
+        assert_eq!(res.headers().get("x-test").unwrap(), "kept");
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(
+            body,
+            serde_json::json!({ "title": "Not Found", "status": 404, "detail": "no such thing" })
+        );
+
+        let req = TestRequest::with_uri("/server").to_srv_request();
+        let body: serde_json::Value =
+            test::read_body_json(test::call_service(&mw, req).await).await;
+        assert_eq!(
+            body,
+            serde_json::json!({ "title": "Internal Server Error", "status": 500 })
+        );
+
+        let req = TestRequest::with_uri("/problem").to_srv_request();
+        let body: serde_json::Value =
+            test::read_body_json(test::call_service(&mw, req).await).await;
+        assert_eq!(
+            body,
+            serde_json::json!({ "title": "Conflict", "status": 409 })
+        );
+    }
 }
@@ -215,6 +215,113 @@ impl_middleware_fn_service!(E1, E2, E3, E4, E5, E6, E7);
 impl_middleware_fn_service!(E1, E2, E3, E4, E5, E6, E7, E8);
 impl_middleware_fn_service!(E1, E2, E3, E4, E5, E6, E7, E8, E9);
 
+/// Wraps an async function that receives shared state to be used as a middleware.
+///
+/// This works like [`from_fn`], except that the wrapped function receives a clone of `state` as
+/// its first parameter. This is useful for middleware that need a database pool, a client, or
+/// configuration, without writing the [`Transform`] and [`Service`] implementations by hand.
+/// State that is expensive to clone should be wrapped in an [`Rc`], an [`Arc`], or a
+/// [`Data`](crate::web::Data).
+///
+/// # Examples
+/// ```
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+///
+/// use actix_web::{
+///     body::MessageBody,
+///     dev::{ServiceRequest, ServiceResponse},
+///     middleware::{from_fn_with_state, Next},
+///     App, Error,
+/// };
+///
+/// async fn count_requests(
+///     counter: Arc<AtomicUsize>,
+///     req: ServiceRequest,
+///     next: Next<impl MessageBody>,
+/// ) -> Result<ServiceResponse<impl MessageBody>, Error> {
+///     counter.fetch_add(1, Ordering::Relaxed);
+///     next.call(req).await
+/// }
+///
+/// let counter = Arc::new(AtomicUsize::new(0));
+///
+/// App::new().wrap(from_fn_with_state(counter, count_requests))
+/// # ;
+/// ```
+///
+/// [`Arc`]: std::sync::Arc
+pub fn from_fn_with_state<T, F>(state: T, mw_fn: F) -> MiddlewareFnWithState<T, F> {
+    MiddlewareFnWithState {
+        state,
+        mw_fn: Rc::new(mw_fn),
+    }
+}
+
+/// Middleware transform for [`from_fn_with_state`].
+#[allow(missing_debug_implementations)]
+pub struct MiddlewareFnWithState<T, F> {
+    state: T,
+    mw_fn: Rc<F>,
+}
+
+impl<S, T, F, Fut, B, B2> Transform<S, ServiceRequest> for MiddlewareFnWithState<T, F>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    T: Clone,
+    F: Fn(T, ServiceRequest, Next<B>) -> Fut + 'static,
+    Fut: Future<Output = Result<ServiceResponse<B2>, Error>>,
+    B2: MessageBody,
+{
+    type Response = ServiceResponse<B2>;
+    type Error = Error;
+    type Transform = MiddlewareFnWithStateService<T, F, B>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MiddlewareFnWithStateService {
+            service: boxed::rc_service(service),
+            state: self.state.clone(),
+            mw_fn: Rc::clone(&self.mw_fn),
+        }))
+    }
+}
+
+/// Middleware service for [`from_fn_with_state`].
+#[allow(missing_debug_implementations)]
+pub struct MiddlewareFnWithStateService<T, F, B> {
+    service: RcService<ServiceRequest, ServiceResponse<B>, Error>,
+    state: T,
+    mw_fn: Rc<F>,
+}
+
+impl<T, F, Fut, B, B2> Service<ServiceRequest> for MiddlewareFnWithStateService<T, F, B>
+where
+    T: Clone,
+    F: Fn(T, ServiceRequest, Next<B>) -> Fut,
+    Fut: Future<Output = Result<ServiceResponse<B2>, Error>>,
+    B2: MessageBody,
+{
+    type Response = ServiceResponse<B2>;
+    type Error = Error;
+    type Future = Fut;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        (self.mw_fn)(
+            self.state.clone(),
+            req,
+            Next::<B> {
+                service: Rc::clone(&self.service),
+            },
+        )
+    }
+}
+
 /// Wraps the "next" service in the middleware chain.
 #[allow(missing_debug_implementations)]
 pub struct Next<B> {
@@ -242,6 +349,8 @@ impl<B> Service<ServiceRequest> for Next<B> {
 
 #[cfg(test)]
 mod tests {
+    use std::cell::Cell;
+
     use super::*;
     use crate::{
         http::header::{self, HeaderValue},
@@ -332,6 +441,44 @@ mod tests {
         assert!(res.headers().contains_key(header::WARNING));
     }
 
+    #[actix_rt::test]
+    async fn with_state() {
+        async fn count_requests<B>(
+            counter: Rc<Cell<usize>>,
+            req: ServiceRequest,
+            next: Next<B>,
+        ) -> Result<ServiceResponse<B>, Error> {
+            counter.set(counter.get() + 1);
+            next.call(req).await
+        }
+
+        let counter = Rc::new(Cell::new(0));
+
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn_with_state(Rc::clone(&counter), count_requests))
+                .wrap(from_fn_with_state(
+                    "42",
+                    |value: &'static str, req, next: Next<_>| async move {
+                        let mut res = next.call(req).await?;
+                        res.headers_mut()
+                            .insert(header::WARNING, HeaderValue::from_static(value));
+                        Ok::<_, Error>(res)
+                    },
+                ))
+                .default_service(web::to(HttpResponse::NotFound)),
+        )
+        .await;
+
+        for _ in 0..2 {
+            let req = test::TestRequest::default().to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.headers().get(header::WARNING).unwrap(), "42");
+        }
+
+        assert_eq!(counter.get(), 2);
+    }
+
     #[actix_rt::test]
     async fn closure_capture_and_return_from_fn() {
         let app = test::init_service(
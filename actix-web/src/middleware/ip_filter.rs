@@ -0,0 +1,341 @@
+//! For middleware documentation, see [`IpFilter`].
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    net::IpAddr,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Either, Ready};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use crate::{
+    body::EitherBody,
+    real_ip::Cidr,
+    service::{ServiceRequest, ServiceResponse},
+    web::RealIpConfig,
+    Error, HttpResponse,
+};
+
+/// Middleware for allowing or denying requests based on the IP address of the client.
+///
+/// Addresses are matched against lists of networks in CIDR notation, such as `10.0.0.0/8` or
+/// `2001:db8::/32`, or against single addresses, such as `192.0.2.1`. Denied networks take
+/// precedence over allowed networks. If no networks are allowed, all addresses that are not denied
+/// are allowed. Rejected requests receive a `403 Forbidden` response, or `404 Not Found` if
+/// [`not_found()`](Self::not_found()) is used to hide the existence of the filtered routes.
+///
+/// # Proxies
+/// The client address is resolved in the same way as by the [`RealIp`](crate::web::RealIp)
+/// extractor, using the [`RealIpConfig`] registered as app data. By default, no proxies are trusted
+/// and the peer address of the connection is used. Requests whose client address is unknown or
+/// can not be parsed are rejected.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::IpFilter, web, App};
+///
+/// let app = App::new()
+///     .app_data(web::RealIpConfig::default().trusted_proxy("127.0.0.1"))
+///     .service(
+///         web::scope("/admin")
+///             .wrap(
+///                 IpFilter::new()
+///                     .allow("10.0.0.0/8")
+///                     .deny("10.0.13.0/24")
+///                     .not_found(),
+///             )
+///             .route("", web::get().to(|ip: web::RealIp| async move { format!("hello {ip}") })),
+///     );
+/// ```
+#[derive(Debug, Clone)]
+pub struct IpFilter {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    allowed: Vec<Cidr>,
+    denied: Vec<Cidr>,
+    not_found: bool,
+}
+
+impl IpFilter {
+    /// Constructs a middleware that allows all requests until networks are allowed or denied.
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                allowed: Vec::new(),
+                denied: Vec::new(),
+                not_found: false,
+            }),
+        }
+    }
+
+    /// Allows requests from a network, such as `10.0.0.0/8`, or a single address.
+    ///
+    /// # Panics
+    /// Panics if `network` is not a valid network or address, or if called after this middleware
+    /// has been cloned.
+    pub fn allow(mut self, network: impl AsRef<str>) -> Self {
+        let network = parse_network(network.as_ref());
+        self.inner_mut().allowed.push(network);
+        self
+    }
+
+    /// Denies requests from a network, such as `10.0.0.0/8`, or a single address.
+    ///
+    /// # Panics
+    /// Panics if `network` is not a valid network or address, or if called after this middleware
+    /// has been cloned.
+    pub fn deny(mut self, network: impl AsRef<str>) -> Self {
+        let network = parse_network(network.as_ref());
+        self.inner_mut().denied.push(network);
+        self
+    }
+
+    /// Responds to rejected requests with `404 Not Found` instead of `403 Forbidden`.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn not_found(mut self) -> Self {
+        self.inner_mut().not_found = true;
+        self
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Rc::get_mut(&mut self.inner)
+            .expect("IP filter can not be configured after it has been cloned")
+    }
+}
+
+impl Default for IpFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inner {
+    fn is_allowed(&self, addr: IpAddr) -> bool {
+        if self.denied.iter().any(|net| net.contains(&addr)) {
+            return false;
+        }
+
+        self.allowed.is_empty() || self.allowed.iter().any(|net| net.contains(&addr))
+    }
+
+    fn rejection(&self) -> HttpResponse {
+        if self.not_found {
+            HttpResponse::NotFound().finish()
+        } else {
+            HttpResponse::Forbidden().finish()
+        }
+    }
+}
+
+/// Parses a network in CIDR notation, or a single address.
+fn parse_network(network: &str) -> Cidr {
+    network
+        .parse()
+        .unwrap_or_else(|_| panic!("Invalid network: {network}"))
+}
+
+impl<S, B> Transform<S, ServiceRequest> for IpFilter
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = IpFilterMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(IpFilterMiddleware {
+            service,
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+/// IP filter middleware service.
+pub struct IpFilterMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S, B> Service<ServiceRequest> for IpFilterMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Either<IpFilterFuture<S, B>, Ready<Result<Self::Response, Self::Error>>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let client_ip = RealIpConfig::from_req(req.request()).resolve(req.request());
+
+        match client_ip {
+            Ok(addr) if self.inner.is_allowed(addr) => Either::left(IpFilterFuture {
+                fut: self.service.call(req),
+                _body: PhantomData,
+            }),
+
+            _ => {
+                let res = self.inner.rejection();
+                Either::right(ready(Ok(req.into_response(res).map_into_right_body())))
+            }
+        }
+    }
+}
+
+pin_project! {
+    pub struct IpFilterFuture<S: Service<ServiceRequest>, B> {
+        #[pin]
+        fut: S::Future,
+        _body: PhantomData<B>,
+    }
+}
+
+impl<S, B> Future for IpFilterFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = Result<ServiceResponse<EitherBody<B>>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(self.project().fut.poll(cx))?;
+        Poll::Ready(Ok(res.map_into_left_body()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+    use crate::{
+        http::{
+            header::{self, HeaderName},
+            StatusCode,
+        },
+        test::{self, TestRequest},
+        web::{self, RealIpSource},
+        App,
+    };
+
+    const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+    fn peer(addr: &str) -> SocketAddr {
+        SocketAddr::new(addr.parse().unwrap(), 12345)
+    }
+
+    async fn status(mw: IpFilter, req: TestRequest) -> StatusCode {
+        let mw = mw.new_transform(test::ok_service()).await.unwrap();
+        mw.call(req.to_srv_request()).await.unwrap().status()
+    }
+
+    #[actix_rt::test]
+    async fn allow_and_deny() {
+        let mw = || {
+            IpFilter::new()
+                .allow("10.0.0.0/8")
+                .allow("::1")
+                .deny("10.0.13.0/24")
+        };
+
+        for addr in ["10.0.0.1", "10.255.0.1", "::1", "::ffff:10.0.0.1"] {
+            let req = TestRequest::default().peer_addr(peer(addr));
+            assert_eq!(status(mw(), req).await, StatusCode::OK, "addr: {addr}");
+        }
+
+        for addr in ["10.0.13.37", "192.0.2.1", "::2"] {
+            let req = TestRequest::default().peer_addr(peer(addr));
+            assert_eq!(
+                status(mw(), req).await,
+                StatusCode::FORBIDDEN,
+                "addr: {addr}"
+            );
+        }
+
+        // unknown peer address
+        assert_eq!(
+            status(mw(), TestRequest::default()).await,
+            StatusCode::FORBIDDEN
+        );
+
+        // deny-only filter with fake 404
+        let mw = IpFilter::new().deny("192.0.2.0/24").not_found();
+        let req = TestRequest::default().peer_addr(peer("192.0.2.1"));
+        assert_eq!(status(mw, req).await, StatusCode::NOT_FOUND);
+
+        let mw = IpFilter::new().deny("192.0.2.0/24").not_found();
+        let req = TestRequest::default().peer_addr(peer("198.51.100.1"));
+        assert_eq!(status(mw, req).await, StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn forwarded_headers_from_trusted_proxies() {
+        let mw = || IpFilter::new().allow("192.0.2.0/24");
+        let cfg = || {
+            RealIpConfig::default()
+                .trusted_proxy("10.0.0.0/8")
+                .source(RealIpSource::XForwardedFor)
+        };
+
+        // untrusted peer can not spoof its address
+        let req = TestRequest::default()
+            .peer_addr(peer("198.51.100.1"))
+            .insert_header((X_FORWARDED_FOR, "192.0.2.1"))
+            .app_data(cfg());
+        assert_eq!(status(mw(), req).await, StatusCode::FORBIDDEN);
+
+        // trusted proxy chain is skipped
+        let req = TestRequest::default()
+            .peer_addr(peer("10.0.0.1"))
+            .insert_header((X_FORWARDED_FOR, "198.51.100.1, 192.0.2.1, 10.0.0.2"))
+            .app_data(cfg());
+        assert_eq!(status(mw(), req).await, StatusCode::OK);
+
+        // headers are ignored without a config
+        let req = TestRequest::default()
+            .peer_addr(peer("10.0.0.1"))
+            .insert_header((X_FORWARDED_FOR, "192.0.2.1"));
+        assert_eq!(status(mw(), req).await, StatusCode::FORBIDDEN);
+
+        // invalid addresses are rejected
+        let req = TestRequest::default()
+            .peer_addr(peer("10.0.0.1"))
+            .insert_header((header::FORWARDED, "for=_hidden"))
+            .app_data(RealIpConfig::default().trusted_proxy("10.0.0.0/8"));
+        assert_eq!(status(mw(), req).await, StatusCode::FORBIDDEN);
+    }
+
+    #[actix_rt::test]
+    async fn real_ip_extractor() {
+        let app = test::init_service(
+            App::new()
+                .app_data(RealIpConfig::default().trusted_proxy("127.0.0.1"))
+                .wrap(IpFilter::new().allow("192.0.2.0/24"))
+                .route(
+                    "/",
+                    web::get().to(|ip: web::RealIp| async move { ip.to_string() }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .peer_addr(peer("127.0.0.1"))
+            .insert_header((header::FORWARDED, "for=192.0.2.1"))
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "192.0.2.1");
+    }
+}
@@ -0,0 +1,387 @@
+//! For log file documentation, see [`LogFile`].
+
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write as _},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Number of lines that can wait to be written before further lines are dropped.
+const DEFAULT_QUEUE_SIZE: usize = 8 * 1024;
+
+/// File that [`Logger`](super::Logger) writes access logs to, with size and time based rotation.
+///
+/// Lines are sent to a background thread that writes them through a buffer, so that requests never
+/// wait for the disk. If the disk falls behind so far that the queue of pending lines is full,
+/// further lines are dropped and a warning is logged using the `log` crate.
+///
+/// When the file [exceeds a size] or a [period] ends, it is renamed by appending `.1` to its name,
+/// previously rotated files are renamed from `.1` to `.2` and so on, and a new file is started.
+/// Only the [most recent] rotated files are kept.
+///
+/// Clones share the same file and background thread, so a single instance should be created
+/// outside the `HttpServer` factory closure and cloned into each app. The thread stops once all
+/// clones have been dropped.
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use actix_web::{
+///     middleware::{LogFile, Logger},
+///     App, HttpServer,
+/// };
+///
+/// # #[actix_web::main]
+/// # async fn main() -> std::io::Result<()> {
+/// let log_file = LogFile::builder("/var/log/app/access.log")
+///     .max_size(100 * 1024 * 1024)
+///     .rotate_every(Duration::from_secs(24 * 60 * 60))
+///     .max_files(14)
+///     .open()?;
+///
+/// HttpServer::new(move || App::new().wrap(Logger::default().log_file(log_file.clone())))
+///     .bind(("127.0.0.1", 8080))?
+///     .run()
+///     .await
+/// # }
+/// ```
+///
+/// [exceeds a size]: LogFileBuilder::max_size()
+/// [period]: LogFileBuilder::rotate_every()
+/// [most recent]: LogFileBuilder::max_files()
+#[derive(Clone)]
+pub struct LogFile {
+    sender: mpsc::SyncSender<Message>,
+    dropping: Arc<AtomicBool>,
+}
+
+impl LogFile {
+    /// Opens `path` for appending access logs, without rotation.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        Self::builder(path).open()
+    }
+
+    /// Returns a builder for configuring rotation of the log file at `path`.
+    pub fn builder(path: impl Into<PathBuf>) -> LogFileBuilder {
+        LogFileBuilder {
+            path: path.into(),
+            max_size: None,
+            period: None,
+            max_files: 10,
+            queue_size: DEFAULT_QUEUE_SIZE,
+        }
+    }
+
+    /// Waits until all lines sent before this call have been written to the file.
+    ///
+    /// This blocks the calling thread, so it should not be called while handling requests. It is
+    /// useful before the process exits, since the background thread may otherwise be stopped
+    /// before writing the last lines.
+    pub fn flush(&self) {
+        let (tx, rx) = mpsc::sync_channel(1);
+
+        if self.sender.send(Message::Flush(tx)).is_ok() {
+            let _ = rx.recv();
+        }
+    }
+
+    /// Sends a line to be written to the file, dropping it if the queue is full.
+    pub(crate) fn write_line(&self, line: String) {
+        match self.sender.try_send(Message::Line(line)) {
+            Ok(()) => {
+                if self.dropping.swap(false, Ordering::Relaxed) {
+                    log::warn!("Access log file caught up, resuming writes");
+                }
+            }
+
+            Err(mpsc::TrySendError::Full(_)) => {
+                if !self.dropping.swap(true, Ordering::Relaxed) {
+                    log::warn!("Access log file is falling behind, dropping lines");
+                }
+            }
+
+            Err(mpsc::TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+impl fmt::Debug for LogFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogFile").finish_non_exhaustive()
+    }
+}
+
+/// Builder for a [`LogFile`].
+#[derive(Debug, Clone)]
+pub struct LogFileBuilder {
+    path: PathBuf,
+    max_size: Option<u64>,
+    period: Option<Duration>,
+    max_files: usize,
+    queue_size: usize,
+}
+
+impl LogFileBuilder {
+    /// Rotates the file before a line would make it larger than `bytes`.
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Rotates the file at the end of each `period`.
+    ///
+    /// Periods are aligned to the Unix epoch, so that a period of one day rotates the file at
+    /// midnight UTC and a period of one hour rotates it at the start of every hour.
+    ///
+    /// # Panics
+    /// Panics if `period` is shorter than one second.
+    pub fn rotate_every(mut self, period: Duration) -> Self {
+        assert!(
+            period >= Duration::from_secs(1),
+            "log file rotation period must be at least one second"
+        );
+
+        self.period = Some(period);
+        self
+    }
+
+    /// Sets the number of rotated files that are kept. Older files are deleted.
+    ///
+    /// The default is 10.
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Sets the number of lines that can wait to be written before further lines are dropped.
+    ///
+    /// The default is 8192.
+    pub fn queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = queue_size;
+        self
+    }
+
+    /// Opens the log file and starts the background thread that writes to it.
+    pub fn open(self) -> io::Result<LogFile> {
+        let (sender, receiver) = mpsc::sync_channel(self.queue_size);
+
+        let writer = Writer::open(self)?;
+
+        thread::Builder::new()
+            .name("actix-web-access-log".to_owned())
+            .spawn(move || writer.run(receiver))?;
+
+        Ok(LogFile {
+            sender,
+            dropping: Arc::new(AtomicBool::new(false)),
+        })
+    }
+}
+
+enum Message {
+    Line(String),
+    Flush(mpsc::SyncSender<()>),
+}
+
+/// State of the background thread.
+struct Writer {
+    config: LogFileBuilder,
+    file: BufWriter<File>,
+    size: u64,
+    opened: SystemTime,
+}
+
+impl Writer {
+    fn open(config: LogFileBuilder) -> io::Result<Self> {
+        let file = open_append(&config.path)?;
+        let size = file.metadata()?.len();
+
+        // continue the period of an existing file, so that restarts do not delay its rotation
+        let opened = match file.metadata().and_then(|meta| meta.modified()) {
+            Ok(modified) if size > 0 => modified,
+            _ => SystemTime::now(),
+        };
+
+        Ok(Self {
+            config,
+            file: BufWriter::new(file),
+            size,
+            opened,
+        })
+    }
+
+    fn run(mut self, receiver: mpsc::Receiver<Message>) {
+        while let Ok(msg) = receiver.recv() {
+            self.handle(msg);
+
+            // write queued lines before flushing, to batch writes under load
+            while let Ok(msg) = receiver.try_recv() {
+                self.handle(msg);
+            }
+
+            if let Err(err) = self.file.flush() {
+                log::error!("Failed to write access log file: {err}");
+            }
+        }
+    }
+
+    fn handle(&mut self, msg: Message) {
+        match msg {
+            Message::Line(line) => {
+                if let Err(err) = self.write_line(&line) {
+                    log::error!("Failed to write access log file: {err}");
+                }
+            }
+
+            Message::Flush(done) => {
+                if let Err(err) = self.file.flush() {
+                    log::error!("Failed to write access log file: {err}");
+                }
+
+                let _ = done.send(());
+            }
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        let now = SystemTime::now();
+
+        let too_large = self
+            .config
+            .max_size
+            .is_some_and(|max_size| self.size > 0 && self.size + len > max_size);
+
+        let period_ended = self
+            .config
+            .period
+            .is_some_and(|period| period_ended(self.opened, now, period));
+
+        if too_large || period_ended {
+            self.rotate(now)?;
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.size += len;
+
+        Ok(())
+    }
+
+    fn rotate(&mut self, now: SystemTime) -> io::Result<()> {
+        self.file.flush()?;
+
+        let path = &self.config.path;
+        let rotated = |n: usize| {
+            let mut name = path.clone().into_os_string();
+            name.push(format!(".{n}"));
+            PathBuf::from(name)
+        };
+
+        if self.config.max_files == 0 {
+            fs::remove_file(path)?;
+        } else {
+            match fs::remove_file(rotated(self.config.max_files)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+
+            for n in (1..self.config.max_files).rev() {
+                match fs::rename(rotated(n), rotated(n + 1)) {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                    _ => {}
+                }
+            }
+
+            fs::rename(path, rotated(1))?;
+        }
+
+        self.file = BufWriter::new(open_append(path)?);
+        self.size = 0;
+        self.opened = now;
+
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Returns true if `now` is in a later period than `opened`, with periods aligned to the Unix
+/// epoch.
+fn period_ended(opened: SystemTime, now: SystemTime, period: Duration) -> bool {
+    let index = |time: SystemTime| {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        secs / period.as_secs()
+    };
+
+    index(now) > index(opened)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("actix-web-log-file-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn rotates_by_size() {
+        let dir = temp_dir("size");
+        let path = dir.join("access.log");
+
+        let log_file = LogFile::builder(&path)
+            .max_size(10)
+            .max_files(2)
+            .open()
+            .unwrap();
+
+        for line in ["one", "two", "three", "four", "five"] {
+            log_file.write_line(line.to_owned());
+        }
+        log_file.flush();
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("access.log"), "four\nfive\n");
+        assert_eq!(read("access.log.1"), "three\n");
+        assert_eq!(read("access.log.2"), "one\ntwo\n");
+        assert!(!dir.join("access.log.3").exists());
+
+        // appends to existing files
+        drop(log_file);
+        let log_file = LogFile::open(&path).unwrap();
+        log_file.write_line("six".to_owned());
+        log_file.flush();
+        assert_eq!(read("access.log"), "four\nfive\nsix\n");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn periods() {
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        assert!(!period_ended(at(1), at(86_399), day));
+        assert!(period_ended(at(86_399), at(86_400), day));
+        assert!(period_ended(at(1), at(200_000), day));
+        assert!(!period_ended(at(200_000), at(1), day));
+    }
+}
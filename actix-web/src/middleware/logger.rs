@@ -7,6 +7,7 @@ use std::{
     fmt::{self, Display as _},
     future::Future,
     marker::PhantomData,
+    ops::Range,
     pin::Pin,
     rc::Rc,
     task::{Context, Poll},
@@ -15,7 +16,7 @@ use std::{
 use actix_service::{Service, Transform};
 use actix_utils::future::{ready, Ready};
 use bytes::Bytes;
-use futures_core::ready;
+use futures_core::{future::LocalBoxFuture, ready};
 use log::{debug, warn};
 use pin_project_lite::pin_project;
 #[cfg(feature = "unicode")]
@@ -24,9 +25,13 @@ use regex::Regex;
 use regex_lite::Regex;
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
+use super::LogFile;
 use crate::{
     body::{BodySize, MessageBody},
-    http::header::HeaderName,
+    http::{
+        header::{HeaderMap, HeaderName},
+        StatusCode,
+    },
     service::{ServiceRequest, ServiceResponse},
     Error, Result,
 };
@@ -77,6 +82,31 @@ use crate::{
 /// `%{FOO}xi` | [Custom request replacement](Logger::custom_request_replace) labelled "FOO"
 /// `%{FOO}xo` | [Custom response replacement](Logger::custom_response_replace) labelled "FOO"
 ///
+/// # JSON Format
+/// Using [`json()`](Logger::json) or [`json_fields()`](Logger::json_fields), each log line is a
+/// JSON object instead, for shipping logs to structured pipelines. The value of each field is
+/// rendered from a format string using the variables above. Values are JSON strings, except for
+/// fields formatted using only one of `%s`, `%b`, `%T`, or `%D`, which are JSON numbers.
+///
+/// ```
+/// use actix_web::middleware::Logger;
+///
+/// let logger = Logger::json_fields([
+///     ("request", "%r"),
+///     ("status", "%s"),
+///     ("user_id", "%{USER_ID}xi"),
+/// ])
+/// .custom_request_replace("USER_ID", |req| {
+///     match req.headers().get("X-User-Id").and_then(|id| id.to_str().ok()) {
+///         Some(id) => id.to_owned(),
+///         None => "-".to_owned(),
+///     }
+/// });
+///
+/// // Example Output:
+/// // {"request":"GET /account HTTP/1.1","status":200,"user_id":"42"}
+/// ```
+///
 /// # Security
 /// **\*** "Real IP" remote address is calculated using
 /// [`ConnectionInfo::realip_remote_addr()`](crate::dev::ConnectionInfo::realip_remote_addr())
@@ -92,6 +122,7 @@ struct Inner {
     exclude: HashSet<String>,
     exclude_regex: Vec<Regex>,
     log_target: Cow<'static, str>,
+    log_file: Option<LogFile>,
 }
 
 impl Logger {
@@ -102,6 +133,50 @@ impl Logger {
             exclude: HashSet::new(),
             exclude_regex: Vec::new(),
             log_target: Cow::Borrowed(module_path!()),
+            log_file: None,
+        }))
+    }
+
+    /// Create `Logger` middleware that logs JSON objects with the default set of fields.
+    ///
+    /// The fields are equivalent to the default format:
+    ///
+    /// Field | Format
+    /// ----- | ------
+    /// `remote_addr` | `%a`
+    /// `request` | `%r`
+    /// `status` | `%s`
+    /// `size` | `%b`
+    /// `referer` | `%{Referer}i`
+    /// `user_agent` | `%{User-Agent}i`
+    /// `duration` | `%T`
+    pub fn json() -> Logger {
+        Logger::json_fields([
+            ("remote_addr", "%a"),
+            ("request", "%r"),
+            ("status", "%s"),
+            ("size", "%b"),
+            ("referer", "%{Referer}i"),
+            ("user_agent", "%{User-Agent}i"),
+            ("duration", "%T"),
+        ])
+    }
+
+    /// Create `Logger` middleware that logs JSON objects with the specified fields.
+    ///
+    /// Each field is a pair of its key and a format string for its value. Fields are logged in
+    /// the given order. See the [type-level docs](Logger#json-format) for details.
+    pub fn json_fields<K, V>(fields: impl IntoIterator<Item = (K, V)>) -> Logger
+    where
+        K: Into<String>,
+        V: AsRef<str>,
+    {
+        Logger(Rc::new(Inner {
+            format: Format::json(fields),
+            exclude: HashSet::new(),
+            exclude_regex: Vec::new(),
+            log_target: Cow::Borrowed(module_path!()),
+            log_file: None,
         }))
     }
 
@@ -139,6 +214,23 @@ impl Logger {
         self
     }
 
+    /// Writes access logs to `file` instead of using the `log` crate.
+    ///
+    /// The log target is ignored when writing to a file. See [`LogFile`] for details.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use actix_web::middleware::{LogFile, Logger};
+    ///
+    /// let log_file = LogFile::open("access.log").unwrap();
+    /// let logger = Logger::default().log_file(log_file);
+    /// ```
+    pub fn log_file(mut self, file: LogFile) -> Self {
+        let inner = Rc::get_mut(&mut self.0).unwrap();
+        inner.log_file = Some(file);
+        self
+    }
+
     /// Register a function that receives a ServiceRequest and returns a String for use in the
     /// log line. The label passed as the first argument should match a replacement substring in
     /// the logger format like `%{label}xi`.
@@ -166,9 +258,7 @@ impl Logger {
 
         if let Some(FormatText::CustomRequest(_, request_fn)) = ft {
             // replace into None or previously registered fn using same label
-            request_fn.replace(CustomRequestFn {
-                inner_fn: Rc::new(f),
-            });
+            request_fn.replace(CustomRequestFn::Sync(Rc::new(f)));
         } else {
             // non-printed request replacement function diagnostic
             debug!(
@@ -216,9 +306,101 @@ impl Logger {
         );
 
         if let Some(FormatText::CustomResponse(_, res_fn)) = ft {
-            *res_fn = Some(CustomResponseFn {
-                inner_fn: Rc::new(f),
-            });
+            *res_fn = Some(CustomResponseFn::Sync(Rc::new(f)));
+        } else {
+            debug!(
+                "Attempted to register custom response logging function for non-existent label: {}",
+                label
+            );
+        }
+
+        self
+    }
+
+    /// Register an async function that receives a `ServiceRequest` and resolves to a string for
+    /// use in the log line.
+    ///
+    /// Works like [`custom_request_replace()`](Self::custom_request_replace()), except that the
+    /// returned future can do asynchronous work, such as looking up a tenant name using a client
+    /// from app data. The future must not borrow the request, so clone what it needs first.
+    ///
+    /// Futures run concurrently with the wrapped service. If a future has not resolved when the
+    /// response is ready, the response is delayed until it has.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::{middleware::Logger, web};
+    /// # struct Tenants;
+    /// # impl Tenants { async fn name(&self, _host: &str) -> String { "acme".to_owned() } }
+    /// Logger::new("%{TENANT}xi %r %s").custom_request_replace_async("TENANT", |req| {
+    ///     let tenants = req.app_data::<web::Data<Tenants>>().cloned();
+    ///     let host = req.connection_info().host().to_owned();
+    ///
+    ///     async move {
+    ///         match tenants {
+    ///             Some(tenants) => tenants.name(&host).await,
+    ///             None => "-".to_owned(),
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    pub fn custom_request_replace_async<F, Fut>(mut self, label: &str, f: F) -> Self
+    where
+        F: Fn(&ServiceRequest) -> Fut + 'static,
+        Fut: Future<Output = String> + 'static,
+    {
+        let inner = Rc::get_mut(&mut self.0).unwrap();
+
+        let ft = inner.format.0.iter_mut().find(
+            |ft| matches!(ft, FormatText::CustomRequest(unit_label, _) if label == unit_label),
+        );
+
+        if let Some(FormatText::CustomRequest(_, request_fn)) = ft {
+            request_fn.replace(CustomRequestFn::Async(Rc::new(move |req| Box::pin(f(req)))));
+        } else {
+            debug!(
+                "Attempted to register custom request logging function for nonexistent label: {}",
+                label
+            );
+        }
+
+        self
+    }
+
+    /// Register a function that receives the response status, the response headers, and the size
+    /// of the response body, and returns a string for use in the log line.
+    ///
+    /// The label passed as the first argument should match a replacement substring in
+    /// the logger format like `%{label}xo`.
+    ///
+    /// The function is called when the response body has been sent, or dropped. The size is the
+    /// number of body bytes sent through the logger, so when the logger is registered after the
+    /// [`Compress`](crate::middleware::Compress) middleware, it is the size after compression.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::{http::header::CONTENT_ENCODING, middleware::Logger};
+    /// Logger::new("%r %s %{BODY}xo").custom_response_replace_with_size(
+    ///     "BODY",
+    ///     |_status, headers, size| match headers.get(CONTENT_ENCODING) {
+    ///         Some(encoding) => format!("{size} bytes ({})", encoding.to_str().unwrap_or("-")),
+    ///         None => format!("{size} bytes"),
+    ///     },
+    /// );
+    /// ```
+    pub fn custom_response_replace_with_size(
+        mut self,
+        label: &str,
+        f: impl Fn(StatusCode, &HeaderMap, usize) -> String + 'static,
+    ) -> Self {
+        let inner = Rc::get_mut(&mut self.0).unwrap();
+
+        let ft = inner.format.0.iter_mut().find(
+            |ft| matches!(ft, FormatText::CustomResponse(unit_label, _) if label == unit_label),
+        );
+
+        if let Some(FormatText::CustomResponse(_, res_fn)) = ft {
+            *res_fn = Some(CustomResponseFn::WithSize(Rc::new(f)));
         } else {
             debug!(
                 "Attempted to register custom response logging function for non-existent label: {}",
@@ -242,6 +424,7 @@ impl Default for Logger {
             exclude: HashSet::new(),
             exclude_regex: Vec::new(),
             log_target: Cow::Borrowed(module_path!()),
+            log_file: None,
         }))
     }
 }
@@ -309,24 +492,36 @@ where
         if excluded {
             LoggerResponse {
                 fut: self.service.call(req),
+                res: None,
                 format: None,
+                pending: Vec::new(),
                 time: OffsetDateTime::now_utc(),
                 log_target: Cow::Borrowed(""),
+                log_file: None,
                 _phantom: PhantomData,
             }
         } else {
             let now = OffsetDateTime::now_utc();
             let mut format = self.inner.format.clone();
+            let mut pending = Vec::new();
 
-            for unit in &mut format.0 {
-                unit.render_request(now, &req);
+            for (idx, unit) in format.0.iter_mut().enumerate() {
+                if let FormatText::CustomRequest(_, Some(CustomRequestFn::Async(request_fn))) = unit
+                {
+                    pending.push((idx, request_fn(&req)));
+                } else {
+                    unit.render_request(now, &req);
+                }
             }
 
             LoggerResponse {
                 fut: self.service.call(req),
+                res: None,
                 format: Some(format),
+                pending,
                 time: now,
                 log_target: self.inner.log_target.clone(),
+                log_file: self.inner.log_file.clone(),
                 _phantom: PhantomData,
             }
         }
@@ -341,9 +536,12 @@ pin_project! {
     {
         #[pin]
         fut: S::Future,
+        res: Option<ServiceResponse<B>>,
         time: OffsetDateTime,
         format: Option<Format>,
+        pending: Vec<(usize, LocalBoxFuture<'static, String>)>,
         log_target: Cow<'static, str>,
+        log_file: Option<LogFile>,
         _phantom: PhantomData<B>,
     }
 }
@@ -358,9 +556,32 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
 
-        let res = match ready!(this.fut.poll(cx)) {
-            Ok(res) => res,
-            Err(err) => return Poll::Ready(Err(err)),
+        if this.res.is_none() {
+            match this.fut.poll(cx) {
+                Poll::Ready(Ok(res)) => *this.res = Some(res),
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => {}
+            }
+        }
+
+        // async request replacements run concurrently with the wrapped service
+        if let Some(format) = this.format {
+            this.pending
+                .retain_mut(|(idx, fut)| match fut.as_mut().poll(cx) {
+                    Poll::Ready(text) => {
+                        format.0[*idx] = FormatText::Str(text);
+                        false
+                    }
+                    Poll::Pending => true,
+                });
+        }
+
+        if !this.pending.is_empty() {
+            return Poll::Pending;
+        }
+
+        let Some(res) = this.res.take() else {
+            return Poll::Pending;
         };
 
         if let Some(error) = res.response().error() {
@@ -390,6 +611,7 @@ where
         let time = *this.time;
         let format = this.format.take();
         let log_target = this.log_target.clone();
+        let log_file = this.log_file.take();
 
         Poll::Ready(Ok(res.map_body(move |_, body| StreamLog {
             body,
@@ -397,6 +619,7 @@ where
             format,
             size: 0,
             log_target,
+            log_file,
         })))
     }
 }
@@ -409,22 +632,22 @@ pin_project! {
         size: usize,
         time: OffsetDateTime,
         log_target: Cow<'static, str>,
+        log_file: Option<LogFile>,
     }
 
     impl<B> PinnedDrop for StreamLog<B> {
         fn drop(this: Pin<&mut Self>) {
             if let Some(ref format) = this.format {
-                let render = |fmt: &mut fmt::Formatter<'_>| {
-                    for unit in &format.0 {
-                        unit.render(fmt, this.size, this.time)?;
-                    }
-                    Ok(())
-                };
-
-                log::info!(
-                    target: this.log_target.as_ref(),
-                    "{}", FormatDisplay(&render)
-                );
+                let render =
+                    |fmt: &mut fmt::Formatter<'_>| format.render(fmt, this.size, this.time);
+
+                match this.log_file {
+                    Some(ref file) => file.write_line(FormatDisplay(&render).to_string()),
+                    None => log::info!(
+                        target: this.log_target.as_ref(),
+                        "{}", FormatDisplay(&render)
+                    ),
+                }
             }
         }
     }
@@ -456,8 +679,18 @@ impl<B: MessageBody> MessageBody for StreamLog<B> {
 }
 
 /// A formatting style for the `Logger` consisting of multiple concatenated `FormatText` items.
+///
+/// JSON formats additionally describe which items make up the value of each field.
 #[derive(Debug, Clone)]
-struct Format(Vec<FormatText>);
+struct Format(Vec<FormatText>, Option<Rc<[JsonField]>>);
+
+/// A field of a JSON format, whose value is rendered from a range of the format's items.
+#[derive(Debug)]
+struct JsonField {
+    key: String,
+    units: Range<usize>,
+    numeric: bool,
+}
 
 impl Default for Format {
     /// Return the default formatting style for the `Logger`:
@@ -520,10 +753,91 @@ impl Format {
             results.push(FormatText::Str(s[idx..].to_owned()));
         }
 
-        Format(results)
+        Format(results, None)
+    }
+
+    /// Create a JSON `Format` from pairs of field keys and format strings.
+    fn json<K, V>(fields: impl IntoIterator<Item = (K, V)>) -> Format
+    where
+        K: Into<String>,
+        V: AsRef<str>,
+    {
+        let mut units = Vec::new();
+        let mut layout = Vec::new();
+
+        for (key, format) in fields {
+            let Format(field_units, _) = Format::new(format.as_ref());
+
+            let numeric = matches!(
+                field_units.as_slice(),
+                [FormatText::ResponseStatus
+                    | FormatText::ResponseSize
+                    | FormatText::Time
+                    | FormatText::TimeMillis]
+            );
+
+            let start = units.len();
+            units.extend(field_units);
+
+            layout.push(JsonField {
+                key: key.into(),
+                units: start..units.len(),
+                numeric,
+            });
+        }
+
+        Format(units, Some(layout.into()))
+    }
+
+    /// Render the log line of a request whose response has been rendered.
+    fn render(
+        &self,
+        fmt: &mut fmt::Formatter<'_>,
+        size: usize,
+        entry_time: OffsetDateTime,
+    ) -> Result<(), fmt::Error> {
+        let Some(fields) = &self.1 else {
+            for unit in &self.0 {
+                unit.render(fmt, size, entry_time)?;
+            }
+
+            return Ok(());
+        };
+
+        fmt.write_str("{")?;
+
+        for (idx, field) in fields.iter().enumerate() {
+            if idx > 0 {
+                fmt.write_str(",")?;
+            }
+
+            let render = |fmt: &mut fmt::Formatter<'_>| {
+                for unit in &self.0[field.units.clone()] {
+                    unit.render(fmt, size, entry_time)?;
+                }
+                Ok(())
+            };
+
+            write_json_str(fmt, &field.key)?;
+            fmt.write_str(":")?;
+
+            if field.numeric {
+                FormatDisplay(&render).fmt(fmt)?;
+            } else {
+                write_json_str(fmt, &FormatDisplay(&render).to_string())?;
+            }
+        }
+
+        fmt.write_str("}")
     }
 }
 
+/// Write a string as a JSON string literal.
+fn write_json_str(fmt: &mut fmt::Formatter<'_>, s: &str) -> Result<(), fmt::Error> {
+    let json = serde_json::to_string(s).map_err(|_| fmt::Error)?;
+    fmt.write_str(&json)
+}
+
 /// A string of text to be logged.
 ///
 /// This is either one of the data fields supported by the `Logger`, or a custom `String`.
@@ -546,39 +860,54 @@ enum FormatText {
     EnvironHeader(String),
     CustomRequest(String, Option<CustomRequestFn>),
     CustomResponse(String, Option<CustomResponseFn>),
+    CustomResponseWithSize(ResponseWithSizeFn),
 }
 
-#[derive(Clone)]
-struct CustomRequestFn {
-    inner_fn: Rc<dyn Fn(&ServiceRequest) -> String>,
-}
+type AsyncRequestFn = dyn Fn(&ServiceRequest) -> LocalBoxFuture<'static, String>;
+type WithSizeResponseFn = dyn Fn(StatusCode, &HeaderMap, usize) -> String;
 
-impl CustomRequestFn {
-    fn call(&self, req: &ServiceRequest) -> String {
-        (self.inner_fn)(req)
-    }
+#[derive(Clone)]
+enum CustomRequestFn {
+    Sync(Rc<dyn Fn(&ServiceRequest) -> String>),
+    Async(Rc<AsyncRequestFn>),
 }
 
 impl fmt::Debug for CustomRequestFn {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("custom_request_fn")
+        match self {
+            CustomRequestFn::Sync(_) => f.write_str("custom_request_fn"),
+            CustomRequestFn::Async(_) => f.write_str("custom_request_async_fn"),
+        }
     }
 }
 
 #[derive(Clone)]
-struct CustomResponseFn {
-    inner_fn: Rc<dyn Fn(&ServiceResponse) -> String>,
+enum CustomResponseFn {
+    Sync(Rc<dyn Fn(&ServiceResponse) -> String>),
+    WithSize(Rc<WithSizeResponseFn>),
 }
 
-impl CustomResponseFn {
-    fn call(&self, res: &ServiceResponse) -> String {
-        (self.inner_fn)(res)
+impl fmt::Debug for CustomResponseFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomResponseFn::Sync(_) => f.write_str("custom_response_fn"),
+            CustomResponseFn::WithSize(_) => f.write_str("custom_response_with_size_fn"),
+        }
     }
 }
 
-impl fmt::Debug for CustomResponseFn {
+/// A custom response replacement function that is called with the response body size, along
+/// with the response status and headers it needs.
+#[derive(Clone)]
+struct ResponseWithSizeFn {
+    inner_fn: Rc<WithSizeResponseFn>,
+    status: StatusCode,
+    headers: HeaderMap,
+}
+
+impl fmt::Debug for ResponseWithSizeFn {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("custom_response_fn")
+        f.write_str("custom_response_with_size_fn")
     }
 }
 
@@ -610,6 +939,9 @@ impl FormatText {
                     "-".fmt(fmt)
                 }
             }
+            FormatText::CustomResponseWithSize(res_fn) => {
+                fmt.write_str(&(res_fn.inner_fn)(res_fn.status, &res_fn.headers, size))
+            }
             _ => Ok(()),
         }
     }
@@ -631,7 +963,14 @@ impl FormatText {
 
             FormatText::CustomResponse(_, res_fn) => {
                 let text = match res_fn {
-                    Some(res_fn) => FormatText::Str(res_fn.call(res)),
+                    Some(CustomResponseFn::Sync(res_fn)) => FormatText::Str(res_fn(res)),
+                    Some(CustomResponseFn::WithSize(res_fn)) => {
+                        FormatText::CustomResponseWithSize(ResponseWithSizeFn {
+                            inner_fn: Rc::clone(res_fn),
+                            status: res.status(),
+                            headers: res.headers().clone(),
+                        })
+                    }
                     None => FormatText::Str("-".to_owned()),
                 };
 
@@ -690,7 +1029,9 @@ impl FormatText {
             }
             FormatText::CustomRequest(_, request_fn) => {
                 let s = match request_fn {
-                    Some(f) => FormatText::Str(f.call(req)),
+                    Some(CustomRequestFn::Sync(f)) => FormatText::Str(f(req)),
+                    // async functions are called by the middleware service
+                    Some(CustomRequestFn::Async(_)) => return,
                     None => FormatText::Str("-".to_owned()),
                 };
 
@@ -1000,4 +1341,199 @@ mod tests {
         let req = TestRequest::default().to_srv_request();
         srv.call(req).await.unwrap();
     }
+
+    #[actix_rt::test]
+    async fn test_json_format() {
+        let mut format = Format::json([
+            ("request", "%r"),
+            ("status", "%s"),
+            ("size", "%b"),
+            ("user_agent", "%{User-Agent}i"),
+            ("referer", "%{Referer}i"),
+            ("custom", "status %s"),
+        ]);
+
+        let req = TestRequest::default()
+            .uri("/test/route/yeah?q=test")
+            .insert_header((
+                header::USER_AGENT,
+                header::HeaderValue::from_static("say \"hi\"\t\\"),
+            ))
+            .to_srv_request();
+
+        let now = OffsetDateTime::now_utc();
+        for unit in &mut format.0 {
+            unit.render_request(now, &req);
+        }
+
+        let req = TestRequest::default().to_http_request();
+        let res = ServiceResponse::new(req, HttpResponse::Ok().force_close().finish());
+        for unit in &mut format.0 {
+            unit.render_response(&res);
+        }
+
+        let render = |fmt: &mut fmt::Formatter<'_>| format.render(fmt, 1024, now);
+        let s = FormatDisplay(&render).to_string();
+
+        assert_eq!(
+            s,
+            r#"{"request":"GET /test/route/yeah?q=test HTTP/1.1","status":200,"size":1024,"user_agent":"say \"hi\"\t\\","referer":"-","custom":"status 200"}"#
+        );
+
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(value["user_agent"], "say \"hi\"\t\\");
+    }
+
+    #[actix_rt::test]
+    async fn test_json_format_time() {
+        let mut format = Format::json([("duration", "%T"), ("duration_ms", "%D")]);
+
+        let req = TestRequest::default().to_srv_request();
+        let now = OffsetDateTime::now_utc();
+        for unit in &mut format.0 {
+            unit.render_request(now, &req);
+        }
+
+        let render = |fmt: &mut fmt::Formatter<'_>| format.render(fmt, 1024, now);
+        let s = FormatDisplay(&render).to_string();
+
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert!(value["duration"].is_f64());
+        assert!(value["duration_ms"].is_f64());
+    }
+
+    #[actix_rt::test]
+    async fn test_json_custom_replacement() {
+        let logger = Logger::json_fields([("user", "%{USER}xi"), ("outcome", "%{OUTCOME}xo")])
+            .custom_request_replace("USER", |_req| "a \"quoted\" name".to_owned())
+            .custom_response_replace("OUTCOME", |res| res.status().to_string());
+
+        let mut format = logger.0.format.clone();
+
+        let req = TestRequest::default().to_srv_request();
+        let now = OffsetDateTime::now_utc();
+        for unit in &mut format.0 {
+            unit.render_request(now, &req);
+        }
+
+        let req = TestRequest::default().to_http_request();
+        let res = ServiceResponse::new(req, HttpResponse::NotFound().finish());
+        for unit in &mut format.0 {
+            unit.render_response(&res);
+        }
+
+        let render = |fmt: &mut fmt::Formatter<'_>| format.render(fmt, 0, now);
+        let s = FormatDisplay(&render).to_string();
+
+        assert_eq!(
+            s,
+            r#"{"user":"a \"quoted\" name","outcome":"404 Not Found"}"#
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_json_logger_in_middleware() {
+        let srv = Logger::json()
+            .new_transform(test::ok_service())
+            .await
+            .unwrap();
+
+        let req = TestRequest::default().to_srv_request();
+        let res = srv.call(req).await.unwrap();
+        let _ = test::read_body(res).await;
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_closure_req_log_async() {
+        let logger = Logger::new("%{TENANT}xi %{SYNC}xi").custom_request_replace_async(
+            "TENANT",
+            |req: &ServiceRequest| {
+                let path = req.path().to_owned();
+
+                async move {
+                    actix_rt::task::yield_now().await;
+                    format!("tenant{path}")
+                }
+            },
+        );
+        let logger = logger.custom_request_replace("SYNC", |_req| "sync".to_owned());
+
+        let srv = logger.new_transform(test::ok_service()).await.unwrap();
+
+        let req = TestRequest::with_uri("/acme").to_srv_request();
+        let res = srv.call(req).await.unwrap();
+
+        let body = res.into_body();
+        let format = body.format.as_ref().unwrap();
+        let now = OffsetDateTime::now_utc();
+
+        let render = |fmt: &mut fmt::Formatter<'_>| format.render(fmt, 0, now);
+        let log_output = FormatDisplay(&render).to_string();
+        assert_eq!(log_output, "tenant/acme sync");
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_closure_response_log_with_size() {
+        let logger = Logger::new("%{BODY}xo").custom_response_replace_with_size(
+            "BODY",
+            |status, headers, size| {
+                format!(
+                    "{} {} {size}",
+                    status.as_u16(),
+                    headers.get("x-test").unwrap().to_str().unwrap(),
+                )
+            },
+        );
+
+        let srv = |req: ServiceRequest| {
+            ok(req.into_response(
+                HttpResponse::Ok()
+                    .insert_header(("X-Test", "ttt"))
+                    .body("hello world"),
+            ))
+        };
+        let srv = logger.new_transform(srv.into_service()).await.unwrap();
+
+        let req = TestRequest::default().to_srv_request();
+        let res = srv.call(req).await.unwrap();
+
+        let mut body = res.into_body();
+        let bytes = crate::body::to_bytes(&mut body).await.unwrap();
+        assert_eq!(bytes, "hello world");
+
+        let format = body.format.as_ref().unwrap();
+        let now = OffsetDateTime::now_utc();
+
+        let render = |fmt: &mut fmt::Formatter<'_>| format.render(fmt, body.size, now);
+        let log_output = FormatDisplay(&render).to_string();
+        assert_eq!(log_output, "200 ttt 11");
+    }
+
+    #[actix_rt::test]
+    async fn writes_to_log_file() {
+        let dir = std::env::temp_dir().join(format!("actix-web-logger-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("access.log");
+        let _ = std::fs::remove_file(&path);
+
+        let log_file = LogFile::open(&path).unwrap();
+        let logger = Logger::new("%r %s %b").log_file(log_file.clone());
+
+        let srv = |req: ServiceRequest| ok(req.into_response(HttpResponse::Ok().body("hello")));
+        let srv = logger.new_transform(srv.into_service()).await.unwrap();
+
+        for uri in ["/one", "/two"] {
+            let req = TestRequest::with_uri(uri).to_srv_request();
+            let res = srv.call(req).await.unwrap();
+            crate::body::to_bytes(res.into_body()).await.unwrap();
+        }
+
+        log_file.flush();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "GET /one HTTP/1.1 200 5\nGET /two HTTP/1.1 200 5\n"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
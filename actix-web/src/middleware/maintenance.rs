@@ -0,0 +1,357 @@
+//! For middleware documentation, see [`MaintenanceMode`].
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Either, Ready};
+use bytes::Bytes;
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use crate::{
+    body::EitherBody,
+    http::header::{ContentType, HeaderValue, TryIntoHeaderValue, CONTENT_TYPE, RETRY_AFTER},
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpResponse,
+};
+
+/// Middleware for responding to requests with `503 Service Unavailable` while maintenance mode is
+/// enabled.
+///
+/// Maintenance mode is toggled at runtime using a [`MaintenanceHandle`], such as from an admin
+/// endpoint or a signal handler, so that deploy tooling can drain traffic without restarting the
+/// server. While it is enabled, requests receive a configurable page, with a `Retry-After` header
+/// if a [retry delay](Self::retry_after()) is set. Requests to [allowed paths](Self::allow()),
+/// such as health checks and admin endpoints, are still processed.
+///
+/// The handle is shared by clones of the middleware. To toggle maintenance mode for all workers,
+/// create the middleware outside the `HttpServer` factory closure and clone it into each app.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{
+///     middleware::{MaintenanceHandle, MaintenanceMode},
+///     web, App, HttpResponse,
+/// };
+///
+/// let maintenance = MaintenanceMode::new()
+///     .allow("/health")
+///     .allow("/admin")
+///     .retry_after(Duration::from_secs(120))
+///     .body("<h1>Down for maintenance</h1>");
+///
+/// let app = App::new()
+///     .app_data(web::ThinData(maintenance.handle()))
+///     .wrap(maintenance.clone())
+///     .route("/health", web::get().to(HttpResponse::Ok))
+///     .route(
+///         "/admin/maintenance",
+///         web::post().to(|web::ThinData(handle): web::ThinData<MaintenanceHandle>| async move {
+///             handle.enable();
+///             HttpResponse::NoContent()
+///         }),
+///     );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MaintenanceMode {
+    handle: MaintenanceHandle,
+    config: Arc<Config>,
+}
+
+#[derive(Debug)]
+struct Config {
+    allowed_paths: Vec<String>,
+    retry_after: Option<Duration>,
+    content_type: HeaderValue,
+    body: Bytes,
+}
+
+impl MaintenanceMode {
+    /// Constructs a middleware with maintenance mode disabled.
+    pub fn new() -> Self {
+        Self {
+            handle: MaintenanceHandle::default(),
+            config: Arc::new(Config {
+                allowed_paths: Vec::new(),
+                retry_after: None,
+                content_type: ContentType::plaintext().try_into_value().unwrap(),
+                body: Bytes::from_static(b"Service Unavailable"),
+            }),
+        }
+    }
+
+    /// Returns the handle that toggles maintenance mode for this middleware and its clones.
+    pub fn handle(&self) -> MaintenanceHandle {
+        self.handle.clone()
+    }
+
+    /// Allows requests to `path`, and to paths below it, while maintenance mode is enabled.
+    ///
+    /// For example, allowing `/admin` allows `/admin` and `/admin/users`, but not `/administrators`.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn allow(mut self, path: impl Into<String>) -> Self {
+        let path = path.into();
+        let path = path.trim_end_matches('/').to_owned();
+        self.config_mut().allowed_paths.push(path);
+        self
+    }
+
+    /// Sets the delay suggested by the `Retry-After` header of maintenance responses.
+    ///
+    /// The delay is rounded up to whole seconds. By default, no `Retry-After` header is sent.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn retry_after(mut self, delay: Duration) -> Self {
+        self.config_mut().retry_after = Some(delay);
+        self
+    }
+
+    /// Sets the body of maintenance responses.
+    ///
+    /// Bodies are sent as HTML unless a [content type](Self::content_type()) is set. Defaults to a
+    /// plain text `Service Unavailable` message.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        let config = self.config_mut();
+        config.body = body.into();
+        config.content_type = ContentType::html().try_into_value().unwrap();
+        self
+    }
+
+    /// Sets the content type of maintenance responses.
+    ///
+    /// # Panics
+    /// Panics if the content type is not a valid header value, or if called after this middleware
+    /// has been cloned.
+    pub fn content_type(mut self, content_type: impl TryIntoHeaderValue) -> Self {
+        let content_type = match content_type.try_into_value() {
+            Ok(content_type) => content_type,
+            Err(err) => panic!("Invalid header value: {}", err.into()),
+        };
+
+        self.config_mut().content_type = content_type;
+        self
+    }
+
+    fn config_mut(&mut self) -> &mut Config {
+        Arc::get_mut(&mut self.config)
+            .expect("maintenance mode can not be configured after it has been cloned")
+    }
+}
+
+impl Default for MaintenanceMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    fn is_allowed(&self, path: &str) -> bool {
+        self.allowed_paths.iter().any(|allowed| {
+            path.strip_prefix(allowed.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    fn response(&self) -> HttpResponse {
+        let mut res = HttpResponse::ServiceUnavailable();
+        res.insert_header((CONTENT_TYPE, self.content_type.clone()));
+
+        if let Some(delay) = self.retry_after {
+            let secs = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+            res.insert_header((RETRY_AFTER, HeaderValue::from(secs)));
+        }
+
+        res.body(self.body.clone())
+    }
+}
+
+/// Handle for toggling the maintenance mode of a [`MaintenanceMode`] middleware.
+///
+/// Handles are cheap to clone and can be sent to other threads.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceHandle {
+    enabled: Arc<AtomicBool>,
+}
+
+impl MaintenanceHandle {
+    /// Enables maintenance mode.
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Release);
+    }
+
+    /// Disables maintenance mode.
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Release);
+    }
+
+    /// Returns true if maintenance mode is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for MaintenanceMode
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = MaintenanceModeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaintenanceModeMiddleware {
+            service,
+            handle: self.handle.clone(),
+            config: Arc::clone(&self.config),
+        }))
+    }
+}
+
+/// Maintenance mode middleware service.
+pub struct MaintenanceModeMiddleware<S> {
+    service: S,
+    handle: MaintenanceHandle,
+    config: Arc<Config>,
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceModeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Either<MaintenanceModeFuture<S, B>, Ready<Result<Self::Response, Self::Error>>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !self.handle.is_enabled() || self.config.is_allowed(req.path()) {
+            return Either::left(MaintenanceModeFuture {
+                fut: self.service.call(req),
+                _body: PhantomData,
+            });
+        }
+
+        let res = self.config.response();
+        Either::right(ready(Ok(req.into_response(res).map_into_right_body())))
+    }
+}
+
+pin_project! {
+    pub struct MaintenanceModeFuture<S: Service<ServiceRequest>, B> {
+        #[pin]
+        fut: S::Future,
+        _body: PhantomData<B>,
+    }
+}
+
+impl<S, B> Future for MaintenanceModeFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = Result<ServiceResponse<EitherBody<B>>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(self.project().fut.poll(cx))?;
+        Poll::Ready(Ok(res.map_into_left_body()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{self, TestRequest},
+    };
+
+    #[actix_rt::test]
+    async fn toggles_at_runtime() {
+        let maintenance = MaintenanceMode::new().retry_after(Duration::from_millis(1500));
+        let handle = maintenance.handle();
+
+        let mw = maintenance.new_transform(test::ok_service()).await.unwrap();
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        handle.enable();
+        assert!(handle.is_enabled());
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "2");
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(test::read_body(res).await, "Service Unavailable");
+
+        handle.disable();
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn allowed_paths() {
+        let maintenance = MaintenanceMode::new()
+            .allow("/health")
+            .allow("/admin/")
+            .body("<h1>Maintenance</h1>");
+        maintenance.handle().enable();
+
+        let mw = maintenance.new_transform(test::ok_service()).await.unwrap();
+
+        for path in ["/health", "/admin", "/admin/users"] {
+            let req = TestRequest::with_uri(path).to_srv_request();
+            let res = mw.call(req).await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK, "path: {path}");
+        }
+
+        for path in ["/", "/healthz", "/administrators"] {
+            let req = TestRequest::with_uri(path).to_srv_request();
+            let res = mw.call(req).await.unwrap();
+            assert_eq!(
+                res.status(),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "path: {path}"
+            );
+            assert!(!res.headers().contains_key(RETRY_AFTER));
+            assert_eq!(
+                res.headers().get(CONTENT_TYPE).unwrap(),
+                "text/html; charset=utf-8"
+            );
+            assert_eq!(test::read_body(res).await, "<h1>Maintenance</h1>");
+        }
+    }
+}
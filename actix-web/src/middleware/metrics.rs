@@ -0,0 +1,578 @@
+//! For middleware documentation, see [`Metrics`].
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write as _},
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use bytes::Bytes;
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use crate::{
+    body::{BodySize, MessageBody},
+    http::header::ContentType,
+    service::{ServiceRequest, ServiceResponse},
+    Error, Handler, HttpResponse,
+};
+
+/// Default upper bounds of the request duration histogram buckets, in seconds.
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Upper bounds of the response size histogram buckets, in bytes.
+const SIZE_BUCKETS: &[f64] = &[
+    100.0,
+    1_000.0,
+    10_000.0,
+    100_000.0,
+    1_000_000.0,
+    10_000_000.0,
+];
+
+/// Value of the `route` label for requests that did not match a resource.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Middleware for collecting request metrics in the Prometheus text exposition format.
+///
+/// Three metrics are collected, each labeled by request method and by the pattern of the matched
+/// resource, such as `/users/{id}`, rather than by the raw path:
+/// - `http_requests_total`, a counter that is also labeled by response status;
+/// - `http_request_duration_seconds`, a histogram of the time from receiving the request until
+///   its response body is sent;
+/// - `http_response_size_bytes`, a histogram of the size of sent response bodies.
+///
+/// Requests that do not match a resource, such as those handled by the default service, share
+/// the `route` label value `unmatched` so that unknown paths can not create unbounded numbers of
+/// time series. Errors returned by inner services, instead of responses, are not recorded.
+///
+/// Metrics are shared by clones of the middleware, so a single instance should be created outside
+/// the `HttpServer` factory closure and cloned into each app. Collected metrics can be exposed with
+/// [`handler()`](Self::handler()) or rendered with [`render()`](Self::render()).
+///
+/// # Examples
+/// ```no_run
+/// use actix_web::{middleware::Metrics, web, App, HttpServer};
+///
+/// # #[actix_web::main]
+/// # async fn main() -> std::io::Result<()> {
+/// let metrics = Metrics::new();
+///
+/// HttpServer::new(move || {
+///     App::new()
+///         .wrap(metrics.clone())
+///         .route("/metrics", web::get().to(metrics.handler()))
+///         .route("/users/{id}", web::get().to(|| async { "user" }))
+/// })
+/// .bind(("127.0.0.1", 8080))?
+/// .run()
+/// .await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    registry: Arc<Mutex<Registry>>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// Constructs a metrics middleware with no collected metrics.
+    pub fn new() -> Self {
+        Self {
+            registry: Arc::new(Mutex::new(Registry {
+                namespace: None,
+                duration_buckets: DURATION_BUCKETS.to_vec(),
+                requests: BTreeMap::new(),
+                durations: BTreeMap::new(),
+                sizes: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Prefixes the names of all metrics with `namespace` and an underscore.
+    ///
+    /// # Panics
+    /// Panics if `namespace` is not a valid metric name.
+    pub fn namespace(self, namespace: impl Into<String>) -> Self {
+        let namespace = namespace.into();
+
+        assert!(
+            is_valid_metric_name(&namespace),
+            "invalid metric namespace: {namespace:?}"
+        );
+
+        self.registry.lock().unwrap().namespace = Some(namespace);
+        self
+    }
+
+    /// Sets the upper bounds, in seconds, of the request duration histogram buckets.
+    ///
+    /// By default, the buckets range from 5 milliseconds to 10 seconds.
+    ///
+    /// # Panics
+    /// Panics if `buckets` are not finite and in increasing order.
+    pub fn duration_buckets(self, buckets: impl Into<Vec<f64>>) -> Self {
+        let buckets = buckets.into();
+
+        assert!(
+            buckets.iter().all(|bound| bound.is_finite())
+                && buckets.windows(2).all(|pair| pair[0] < pair[1]),
+            "histogram buckets must be finite and in increasing order"
+        );
+
+        self.registry.lock().unwrap().duration_buckets = buckets;
+        self
+    }
+
+    /// Renders collected metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.registry.lock().unwrap().render(&mut out).unwrap();
+        out
+    }
+
+    /// Returns a handler that responds with the collected metrics.
+    ///
+    /// See the [type-level docs](Self) for an example.
+    pub fn handler(&self) -> impl Handler<(), Output = HttpResponse> {
+        let metrics = self.clone();
+
+        move || {
+            ready(
+                HttpResponse::Ok()
+                    .insert_header(ContentType(
+                        "text/plain; version=0.0.4; charset=utf-8".parse().unwrap(),
+                    ))
+                    .body(metrics.render()),
+            )
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Metrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    type Response = ServiceResponse<MetricsBody<B>>;
+    type Error = Error;
+    type Transform = MetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MetricsMiddleware {
+            service,
+            registry: Arc::clone(&self.registry),
+        }))
+    }
+}
+
+/// Metrics middleware service.
+pub struct MetricsMiddleware<S> {
+    service: S,
+    registry: Arc<Mutex<Registry>>,
+}
+
+impl<S, B> Service<ServiceRequest> for MetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    type Response = ServiceResponse<MetricsBody<B>>;
+    type Error = Error;
+    type Future = MetricsResponse<S, B>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        MetricsResponse {
+            fut: self.service.call(req),
+            start: Instant::now(),
+            registry: Some(Arc::clone(&self.registry)),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+pin_project! {
+    pub struct MetricsResponse<S, B>
+    where
+        B: MessageBody,
+        S: Service<ServiceRequest>,
+    {
+        #[pin]
+        fut: S::Future,
+        start: Instant,
+        registry: Option<Arc<Mutex<Registry>>>,
+        _phantom: PhantomData<B>,
+    }
+}
+
+impl<S, B> Future for MetricsResponse<S, B>
+where
+    B: MessageBody,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = Result<ServiceResponse<MetricsBody<B>>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let res = ready!(this.fut.poll(cx))?;
+
+        let labels = Labels {
+            method: res.request().method().to_string(),
+            route: res
+                .request()
+                .match_pattern()
+                .unwrap_or_else(|| UNMATCHED_ROUTE.to_owned()),
+        };
+        let status = res.status().as_u16();
+        let start = *this.start;
+        let registry = this.registry.take();
+
+        Poll::Ready(Ok(res.map_body(move |_, body| MetricsBody {
+            body,
+            registry,
+            labels,
+            status,
+            start,
+            size: 0,
+        })))
+    }
+}
+
+pin_project! {
+    pub struct MetricsBody<B> {
+        #[pin]
+        body: B,
+        registry: Option<Arc<Mutex<Registry>>>,
+        labels: Labels,
+        status: u16,
+        start: Instant,
+        size: usize,
+    }
+
+    impl<B> PinnedDrop for MetricsBody<B> {
+        fn drop(this: Pin<&mut Self>) {
+            let this = this.project();
+
+            if let Some(registry) = this.registry.take() {
+                // a poisoned registry only means that another thread panicked while recording
+                let mut registry = registry.lock().unwrap_or_else(|err| err.into_inner());
+
+                registry.record(
+                    this.labels.clone(),
+                    *this.status,
+                    this.start.elapsed(),
+                    *this.size,
+                );
+            }
+        }
+    }
+}
+
+impl<B: MessageBody> MessageBody for MetricsBody<B> {
+    type Error = B::Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.project();
+
+        match ready!(this.body.poll_next(cx)) {
+            Some(Ok(chunk)) => {
+                *this.size += chunk.len();
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// Labels shared by all metrics.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Labels {
+    method: String,
+    route: String,
+}
+
+/// Collected metrics.
+#[derive(Debug)]
+struct Registry {
+    namespace: Option<String>,
+    duration_buckets: Vec<f64>,
+    requests: BTreeMap<(Labels, u16), u64>,
+    durations: BTreeMap<Labels, Histogram>,
+    sizes: BTreeMap<Labels, Histogram>,
+}
+
+impl Registry {
+    fn record(&mut self, labels: Labels, status: u16, duration: Duration, size: usize) {
+        *self.requests.entry((labels.clone(), status)).or_default() += 1;
+
+        let buckets = &self.duration_buckets;
+        self.durations
+            .entry(labels.clone())
+            .or_insert_with(|| Histogram::new(buckets))
+            .observe(duration.as_secs_f64());
+
+        self.sizes
+            .entry(labels)
+            .or_insert_with(|| Histogram::new(SIZE_BUCKETS))
+            .observe(size as f64);
+    }
+
+    fn render(&self, out: &mut String) -> fmt::Result {
+        let name = |metric: &str| match &self.namespace {
+            Some(namespace) => format!("{namespace}_{metric}"),
+            None => metric.to_owned(),
+        };
+
+        let requests = name("http_requests_total");
+        writeln!(out, "# HELP {requests} Total number of HTTP requests.")?;
+        writeln!(out, "# TYPE {requests} counter")?;
+        for ((labels, status), count) in &self.requests {
+            writeln!(out, "{requests}{{{labels},status=\"{status}\"}} {count}")?;
+        }
+
+        let durations = name("http_request_duration_seconds");
+        writeln!(out, "# HELP {durations} HTTP request duration in seconds.")?;
+        writeln!(out, "# TYPE {durations} histogram")?;
+        for (labels, histogram) in &self.durations {
+            histogram.render(out, &durations, labels)?;
+        }
+
+        let sizes = name("http_response_size_bytes");
+        writeln!(out, "# HELP {sizes} HTTP response body size in bytes.")?;
+        writeln!(out, "# TYPE {sizes} histogram")?;
+        for (labels, histogram) in &self.sizes {
+            histogram.render(out, &sizes, labels)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Labels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "method=\"")?;
+        write_label_value(f, &self.method)?;
+        write!(f, "\",route=\"")?;
+        write_label_value(f, &self.route)?;
+        write!(f, "\"")
+    }
+}
+
+/// Writes a label value, escaping backslashes, double quotes, and line feeds.
+fn write_label_value(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    for ch in value.chars() {
+        match ch {
+            '\\' => f.write_str("\\\\")?,
+            '"' => f.write_str("\\\"")?,
+            '\n' => f.write_str("\\n")?,
+            ch => f.write_char(ch)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn is_valid_metric_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_' || ch == ':')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == ':')
+}
+
+/// Histogram with non-cumulative bucket counts.
+#[derive(Debug)]
+struct Histogram {
+    bounds: Vec<f64>,
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &[f64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        self.sum += value;
+        self.count += 1;
+
+        // values above the largest bound are only counted by the implicit `+Inf` bucket
+        if let Some(idx) = self.bounds.iter().position(|&bound| value <= bound) {
+            self.counts[idx] += 1;
+        }
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &Labels) -> fmt::Result {
+        let mut cumulative = 0;
+
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative += count;
+            writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {cumulative}")?;
+        }
+
+        writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", self.count)?;
+        writeln!(out, "{name}_sum{{{labels}}} {}", self.sum)?;
+        writeln!(out, "{name}_count{{{labels}}} {}", self.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test::{self, TestRequest},
+        web, App,
+    };
+
+    #[actix_rt::test]
+    async fn labels_by_route_pattern() {
+        let metrics = Metrics::new();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(metrics.clone())
+                .route("/users/{id}", web::get().to(|| async { "user" })),
+        )
+        .await;
+
+        for path in ["/users/1", "/users/2", "/missing"] {
+            let req = TestRequest::with_uri(path).to_request();
+            let _ = test::read_body(test::call_service(&app, req).await).await;
+        }
+
+        let out = metrics.render();
+
+        assert!(out.contains(
+            "http_requests_total{method=\"GET\",route=\"/users/{id}\",status=\"200\"} 2\n"
+        ));
+        assert!(out.contains(
+            "http_requests_total{method=\"GET\",route=\"unmatched\",status=\"404\"} 1\n"
+        ));
+        assert!(!out.contains("/users/1"));
+        assert!(out.contains(
+            "http_request_duration_seconds_count{method=\"GET\",route=\"/users/{id}\"} 2\n"
+        ));
+        assert!(out.contains(
+            "http_response_size_bytes_bucket{method=\"GET\",route=\"/users/{id}\",le=\"100\"} 2\n"
+        ));
+        assert!(
+            out.contains("http_response_size_bytes_sum{method=\"GET\",route=\"/users/{id}\"} 8\n")
+        );
+    }
+
+    #[actix_rt::test]
+    async fn exposition_handler() {
+        let metrics = Metrics::new().namespace("app");
+
+        let app = test::init_service(
+            App::new()
+                .wrap(metrics.clone())
+                .route("/metrics", web::get().to(metrics.handler())),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/metrics").to_request();
+        let _ = test::read_body(test::call_service(&app, req).await).await;
+
+        let req = TestRequest::with_uri("/metrics").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            "text/plain; version=0.0.4; charset=utf-8"
+        );
+
+        let body = test::read_body(res).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("# TYPE app_http_requests_total counter\n"));
+        assert!(body.contains("# TYPE app_http_request_duration_seconds histogram\n"));
+        assert!(body.contains(
+            "app_http_requests_total{method=\"GET\",route=\"/metrics\",status=\"200\"} 1\n"
+        ));
+    }
+
+    #[test]
+    fn histogram_buckets() {
+        let labels = Labels {
+            method: "GET".to_owned(),
+            route: "/".to_owned(),
+        };
+
+        let mut histogram = Histogram::new(&[1.0, 2.0]);
+        histogram.observe(0.5);
+        histogram.observe(2.0);
+        histogram.observe(3.0);
+
+        let mut out = String::new();
+        histogram.render(&mut out, "h", &labels).unwrap();
+
+        assert_eq!(
+            out,
+            "h_bucket{method=\"GET\",route=\"/\",le=\"1\"} 1\n\
+             h_bucket{method=\"GET\",route=\"/\",le=\"2\"} 2\n\
+             h_bucket{method=\"GET\",route=\"/\",le=\"+Inf\"} 3\n\
+             h_sum{method=\"GET\",route=\"/\"} 5.5\n\
+             h_count{method=\"GET\",route=\"/\"} 3\n"
+        );
+    }
+
+    #[test]
+    fn label_escaping() {
+        let labels = Labels {
+            method: "GET".to_owned(),
+            route: "/a\\b\"c\nd".to_owned(),
+        };
+
+        assert_eq!(
+            labels.to_string(),
+            "method=\"GET\",route=\"/a\\\\b\\\"c\\nd\""
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_namespace() {
+        let _ = Metrics::new().namespace("1app");
+    }
+
+    #[test]
+    #[should_panic]
+    fn unordered_buckets() {
+        let _ = Metrics::new().duration_buckets(vec![1.0, 0.5]);
+    }
+}
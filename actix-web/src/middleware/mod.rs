@@ -242,32 +242,68 @@
 //! [`new_transform`]: crate::dev::Transform::new_transform()
 //! [`from_fn`]: crate
 
+mod allowed_hosts;
+pub(crate) mod body_limit;
+mod cache;
+mod catch_panic;
+mod circuit_breaker;
 mod compat;
 #[cfg(feature = "__compress")]
 mod compress;
+mod concurrency_limit;
 mod condition;
+mod conditional_get;
 mod default_headers;
 mod err_handlers;
 mod from_fn;
+mod http_auth;
 mod identity;
+mod ip_filter;
+mod log_file;
 mod logger;
+mod maintenance;
+mod metrics;
 mod normalize;
+#[cfg(feature = "opentelemetry")]
+mod opentelemetry;
+mod redirect_https;
+mod security_headers;
+mod slow_requests;
 
 #[cfg(feature = "__compress")]
 pub use self::compress::Compress;
+#[cfg(feature = "opentelemetry")]
+pub use self::opentelemetry::OpenTelemetry;
 pub use self::{
+    allowed_hosts::AllowedHosts,
+    body_limit::BodyLimit,
+    cache::{Cache, CacheEntry, CacheStore, MemoryCacheStore},
+    catch_panic::CatchPanic,
+    circuit_breaker::{CircuitBreaker, CircuitState},
     compat::Compat,
+    concurrency_limit::ConcurrencyLimit,
     condition::Condition,
+    conditional_get::ConditionalGet,
     default_headers::DefaultHeaders,
     err_handlers::{ErrorHandlerResponse, ErrorHandlers},
-    from_fn::{from_fn, Next},
+    from_fn::{from_fn, from_fn_with_state, Next},
+    http_auth::HttpAuthentication,
     identity::Identity,
+    ip_filter::IpFilter,
+    log_file::{LogFile, LogFileBuilder},
     logger::Logger,
-    normalize::{NormalizePath, TrailingSlash},
+    maintenance::{MaintenanceHandle, MaintenanceMode},
+    metrics::Metrics,
+    normalize::{NormalizePath, NormalizePathRedirect, TrailingSlash},
+    redirect_https::RedirectHttps,
+    security_headers::{CspNonce, SecurityHeaders},
+    slow_requests::{HandlerTimer, SlowRequest, SlowRequestLog},
 };
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
     use crate::{http::StatusCode, App};
 
@@ -283,6 +319,19 @@ mod tests {
                 Ok(ErrorHandlerResponse::Response(res.map_into_left_body()))
             }))
             .wrap(Logger::default())
+            .wrap(Metrics::new())
+            .wrap(ConcurrencyLimit::new(1))
+            .wrap(CircuitBreaker::new())
+            .wrap(Cache::in_memory(1))
+            .wrap(ConditionalGet::new())
+            .wrap(SecurityHeaders::recommended())
+            .wrap(RedirectHttps::new())
+            .wrap(AllowedHosts::new().allow("localhost"))
+            .wrap(MaintenanceMode::new())
+            .wrap(BodyLimit::new(1024))
+            .wrap(CatchPanic::new())
+            .wrap(IpFilter::new().allow("127.0.0.1"))
+            .wrap(SlowRequestLog::new(Duration::from_secs(1)))
             .wrap(NormalizePath::new(TrailingSlash::Trim));
 
         let _ = App::new()
@@ -302,5 +351,13 @@ mod tests {
             let _ = App::new().wrap(Compat::new(Compress::default()));
             let _ = App::new().wrap(Condition::new(true, Compat::new(Compress::default())));
         }
+
+        #[cfg(feature = "opentelemetry")]
+        {
+            let _ = App::new()
+                .wrap(Logger::default())
+                .wrap(OpenTelemetry::new());
+            let _ = App::new().wrap(Condition::new(true, OpenTelemetry::new()));
+        }
     }
 }
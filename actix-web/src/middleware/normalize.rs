@@ -1,17 +1,28 @@
 //! For middleware documentation, see [`NormalizePath`].
 
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
 use actix_http::uri::{PathAndQuery, Uri};
 use actix_service::{Service, Transform};
-use actix_utils::future::{ready, Ready};
+use actix_utils::future::{ready, Either, Ready};
 use bytes::Bytes;
+use futures_core::ready;
+use pin_project_lite::pin_project;
 #[cfg(feature = "unicode")]
 use regex::Regex;
 #[cfg(not(feature = "unicode"))]
 use regex_lite::Regex;
 
 use crate::{
+    body::EitherBody,
+    http::{header::LOCATION, StatusCode},
     service::{ServiceRequest, ServiceResponse},
-    Error,
+    Error, HttpResponse,
 };
 
 /// Determines the behavior of the [`NormalizePath`] middleware.
@@ -44,6 +55,13 @@ pub enum TrailingSlash {
 /// - Appends a trailing slash if one is not present, removes one if present, or keeps trailing
 ///   slashes as-is, depending on which [`TrailingSlash`] variant is supplied
 ///   to [`new`](NormalizePath::new()).
+/// - Optionally, removes `.` and `..` segments, following [RFC 3986 §5.2.4]. (For example,
+///   `/a/./b/../c` becomes `/a/c`.) Percent-encoded dots, like `%2e`, are also recognized. See
+///   [`remove_dot_segments`](NormalizePath::remove_dot_segments()).
+/// - Optionally, converts the path to lowercase. See [`lowercase`](NormalizePath::lowercase()).
+///
+/// Requests are rewritten internally so that routes match the normalized path. To redirect
+/// clients to the normalized path instead, see [`redirect`](NormalizePath::redirect()).
 ///
 /// # Default Behavior
 /// The default constructor chooses to strip trailing slashes from the end of paths with them
@@ -83,8 +101,14 @@ pub enum TrailingSlash {
 /// assert_eq!(res.status(), StatusCode::NOT_FOUND);
 /// # })
 /// ```
+///
+/// [RFC 3986 §5.2.4]: https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4
 #[derive(Debug, Clone, Copy)]
-pub struct NormalizePath(TrailingSlash);
+pub struct NormalizePath {
+    trailing_slash: TrailingSlash,
+    remove_dot_segments: bool,
+    lowercase: bool,
+}
 
 impl Default for NormalizePath {
     fn default() -> Self {
@@ -93,14 +117,18 @@ impl Default for NormalizePath {
             in v4 from `Always` to `Trim`. Update your call to `NormalizePath::new(...)`."
         );
 
-        Self(TrailingSlash::Trim)
+        Self::new(TrailingSlash::Trim)
     }
 }
 
 impl NormalizePath {
     /// Create new `NormalizePath` middleware with the specified trailing slash style.
     pub fn new(trailing_slash_style: TrailingSlash) -> Self {
-        Self(trailing_slash_style)
+        Self {
+            trailing_slash: trailing_slash_style,
+            remove_dot_segments: false,
+            lowercase: false,
+        }
     }
 
     /// Constructs a new `NormalizePath` middleware with [trim](TrailingSlash::Trim) semantics.
@@ -109,6 +137,95 @@ impl NormalizePath {
     pub fn trim() -> Self {
         Self::new(TrailingSlash::Trim)
     }
+
+    /// Removes `.` and `..` segments from paths.
+    ///
+    /// `..` segments at the root of the path are discarded, so normalized paths never escape the
+    /// root.
+    pub fn remove_dot_segments(mut self) -> Self {
+        self.remove_dot_segments = true;
+        self
+    }
+
+    /// Converts paths to lowercase.
+    ///
+    /// The query string is not changed.
+    pub fn lowercase(mut self) -> Self {
+        self.lowercase = true;
+        self
+    }
+
+    /// Returns middleware that redirects requests to the normalized path, using this
+    /// normalization, instead of rewriting them internally.
+    ///
+    /// Use `301 Moved Permanently` or `308 Permanent Redirect`. Unlike `301`, `308` guarantees that
+    /// clients keep the request method and body. See [`NormalizePathRedirect`].
+    ///
+    /// # Panics
+    /// Panics if `status` is not a redirection status code.
+    pub fn redirect(self, status: StatusCode) -> NormalizePathRedirect {
+        assert!(
+            status.is_redirection(),
+            "redirect status code must be in the 3xx range"
+        );
+
+        NormalizePathRedirect {
+            normalize: self,
+            status,
+        }
+    }
+
+    /// Returns the normalized form of `original_path`, or `None` if it is already normalized.
+    fn normalize(&self, merge_slash: &Regex, original_path: &str) -> Option<String> {
+        // An empty path here means that the URI has no valid path. We skip normalization in this
+        // case, because adding a path can make the URI invalid
+        if original_path.is_empty() {
+            return None;
+        }
+
+        let dot_segments_removed;
+        let unslashed_path = if self.remove_dot_segments {
+            let path = merge_slash.replace_all(original_path, "/");
+            dot_segments_removed = remove_dot_segments(&path);
+            dot_segments_removed.as_str()
+        } else {
+            original_path
+        };
+
+        // Either adds a string to the end (duplicates will be removed anyways) or trims all
+        // slashes from the end
+        let path = match self.trailing_slash {
+            TrailingSlash::Always => format!("{}/", unslashed_path),
+            TrailingSlash::MergeOnly => unslashed_path.to_string(),
+            TrailingSlash::Trim => unslashed_path.trim_end_matches('/').to_string(),
+        };
+
+        // normalize multiple /'s to one /
+        let mut path = merge_slash.replace_all(&path, "/").into_owned();
+
+        if self.lowercase {
+            path.make_ascii_lowercase();
+        }
+
+        // Ensure root paths are still resolvable. If resulting path is blank after previous
+        // step it means the path was one or more slashes. Reduce to single slash.
+        if path.is_empty() {
+            path.push('/');
+        }
+
+        // Check whether the path has been changed
+        //
+        // This check was previously implemented as string length comparison
+        //
+        // That approach fails when a trailing slash is added,
+        // and a duplicate slash is removed,
+        // since the length of the strings remains the same
+        //
+        // For example, the path "/v1//s" will be normalized to "/v1/s/"
+        // Both of the paths have the same length,
+        // so the change can not be deduced from the length comparison
+        (path != original_path).then_some(path)
+    }
 }
 
 impl<S, B> Transform<S, ServiceRequest> for NormalizePath
@@ -126,7 +243,7 @@ where
         ready(Ok(NormalizePathNormalization {
             service,
             merge_slash: Regex::new("//+").unwrap(),
-            trailing_slash_behavior: self.0,
+            config: *self,
         }))
     }
 }
@@ -134,7 +251,7 @@ where
 pub struct NormalizePathNormalization<S> {
     service: S,
     merge_slash: Regex,
-    trailing_slash_behavior: TrailingSlash,
+    config: NormalizePath,
 }
 
 impl<S, B> Service<ServiceRequest> for NormalizePathNormalization<S>
@@ -151,56 +268,180 @@ where
     fn call(&self, mut req: ServiceRequest) -> Self::Future {
         let head = req.head_mut();
 
-        let original_path = head.uri.path();
+        if let Some(path) = self.config.normalize(&self.merge_slash, head.uri.path()) {
+            let mut parts = head.uri.clone().into_parts();
+            let path = with_query(&path, &head.uri);
+            parts.path_and_query = Some(PathAndQuery::from_maybe_shared(path).unwrap());
 
-        // An empty path here means that the URI has no valid path. We skip normalization in this
-        // case, because adding a path can make the URI invalid
-        if !original_path.is_empty() {
-            // Either adds a string to the end (duplicates will be removed anyways) or trims all
-            // slashes from the end
-            let path = match self.trailing_slash_behavior {
-                TrailingSlash::Always => format!("{}/", original_path),
-                TrailingSlash::MergeOnly => original_path.to_string(),
-                TrailingSlash::Trim => original_path.trim_end_matches('/').to_string(),
-            };
-
-            // normalize multiple /'s to one /
-            let path = self.merge_slash.replace_all(&path, "/");
-
-            // Ensure root paths are still resolvable. If resulting path is blank after previous
-            // step it means the path was one or more slashes. Reduce to single slash.
-            let path = if path.is_empty() { "/" } else { path.as_ref() };
-
-            // Check whether the path has been changed
-            //
-            // This check was previously implemented as string length comparison
-            //
-            // That approach fails when a trailing slash is added,
-            // and a duplicate slash is removed,
-            // since the length of the strings remains the same
-            //
-            // For example, the path "/v1//s" will be normalized to "/v1/s/"
-            // Both of the paths have the same length,
-            // so the change can not be deduced from the length comparison
-            if path != original_path {
-                let mut parts = head.uri.clone().into_parts();
-                let query = parts.path_and_query.as_ref().and_then(|pq| pq.query());
-
-                let path = match query {
-                    Some(q) => Bytes::from(format!("{}?{}", path, q)),
-                    None => Bytes::copy_from_slice(path.as_bytes()),
-                };
-                parts.path_and_query = Some(PathAndQuery::from_maybe_shared(path).unwrap());
-
-                let uri = Uri::from_parts(parts).unwrap();
-                req.match_info_mut().get_mut().update(&uri);
-                req.head_mut().uri = uri;
-            }
+            let uri = Uri::from_parts(parts).unwrap();
+            req.match_info_mut().get_mut().update(&uri);
+            req.head_mut().uri = uri;
         }
+
         self.service.call(req)
     }
 }
 
+/// Middleware for redirecting requests to their normalized paths.
+///
+/// Requests whose paths are not normalized, following the [`NormalizePath`] it was constructed
+/// from using [`NormalizePath::redirect()`], are responded to with a redirect to the normalized
+/// path. This way, clients and caches use canonical URLs. The query string is kept.
+///
+/// The redirect target is always a path beginning with a single slash, in which backslashes are
+/// percent-encoded, so that it can not be mistaken for a URL of another host.
+///
+/// Different normalization can be applied to a [`Scope`](crate::Scope) by wrapping it with a
+/// differently configured middleware. Only requests that match the scope's prefix reach its
+/// middleware, so redirects are best suited to scopes.
+///
+/// # Examples
+/// Redirecting to canonical, lowercase paths:
+/// ```
+/// use actix_web::{http::StatusCode, middleware::NormalizePath, web, App};
+///
+/// let app = App::new().service(
+///     web::scope("/docs")
+///         .wrap(
+///             NormalizePath::trim()
+///                 .remove_dot_segments()
+///                 .lowercase()
+///                 .redirect(StatusCode::PERMANENT_REDIRECT),
+///         )
+///         .route("/{page}", web::get().to(|| async { "page" })),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct NormalizePathRedirect {
+    normalize: NormalizePath,
+    status: StatusCode,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for NormalizePathRedirect
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = NormalizePathRedirectMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(NormalizePathRedirectMiddleware {
+            service,
+            merge_slash: Regex::new("//+").unwrap(),
+            config: *self,
+        }))
+    }
+}
+
+pub struct NormalizePathRedirectMiddleware<S> {
+    service: S,
+    merge_slash: Regex,
+    config: NormalizePathRedirect,
+}
+
+impl<S, B> Service<ServiceRequest> for NormalizePathRedirectMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future =
+        Either<NormalizePathRedirectFuture<S, B>, Ready<Result<Self::Response, Self::Error>>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let uri = &req.head().uri;
+
+        if let Some(path) = self
+            .config
+            .normalize
+            .normalize(&self.merge_slash, uri.path())
+        {
+            // browsers treat backslashes like slashes, so `/\evil.com` would otherwise be followed
+            // to another host
+            let path = path.replace('\\', "%5C");
+
+            let res = HttpResponse::build(self.config.status)
+                .insert_header((LOCATION, with_query(&path, uri)))
+                .finish();
+
+            return Either::right(ready(Ok(req.into_response(res).map_into_right_body())));
+        }
+
+        Either::left(NormalizePathRedirectFuture {
+            fut: self.service.call(req),
+            _body: PhantomData,
+        })
+    }
+}
+
+pin_project! {
+    pub struct NormalizePathRedirectFuture<S: Service<ServiceRequest>, B> {
+        #[pin]
+        fut: S::Future,
+        _body: PhantomData<B>,
+    }
+}
+
+impl<S, B> Future for NormalizePathRedirectFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = Result<ServiceResponse<EitherBody<B>>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(self.project().fut.poll(cx))?;
+        Poll::Ready(Ok(res.map_into_left_body()))
+    }
+}
+
+/// Appends the query string of `uri`, if any, to `path`.
+fn with_query(path: &str, uri: &Uri) -> Bytes {
+    match uri.query() {
+        Some(q) => Bytes::from(format!("{}?{}", path, q)),
+        None => Bytes::copy_from_slice(path.as_bytes()),
+    }
+}
+
+/// Removes `.` and `..` segments from a path that starts with a slash.
+///
+/// See <https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4>.
+fn remove_dot_segments(path: &str) -> String {
+    let is_dot = |segment: &str| segment == "." || segment.eq_ignore_ascii_case("%2e");
+    let is_dot_dot = |segment: &str| {
+        ["..", ".%2e", "%2e.", "%2e%2e"]
+            .iter()
+            .any(|dot_dot| segment.eq_ignore_ascii_case(dot_dot))
+    };
+
+    let segments = path.strip_prefix('/').unwrap_or(path).split('/');
+    let last = segments.clone().count() - 1;
+    let mut output = Vec::new();
+
+    for (idx, segment) in segments.enumerate() {
+        if is_dot(segment) || is_dot_dot(segment) {
+            if is_dot_dot(segment) {
+                output.pop();
+            }
+
+            // keep the trailing slash of paths like `/a/b/..`
+            if idx == last {
+                output.push("");
+            }
+        } else {
+            output.push(segment);
+        }
+    }
+
+    format!("/{}", output.join("/"))
+}
+
 #[cfg(test)]
 mod tests {
     use actix_http::StatusCode;
@@ -253,7 +494,7 @@ mod tests {
     async fn trim_trailing_slashes() {
         let app = init_service(
             App::new()
-                .wrap(NormalizePath(TrailingSlash::Trim))
+                .wrap(NormalizePath::new(TrailingSlash::Trim))
                 .service(web::resource("/").to(HttpResponse::Ok))
                 .service(web::resource("/v1/something").to(HttpResponse::Ok))
                 .service(
@@ -289,11 +530,13 @@ mod tests {
     #[actix_rt::test]
     async fn trim_root_trailing_slashes_with_query() {
         let app = init_service(
-            App::new().wrap(NormalizePath(TrailingSlash::Trim)).service(
-                web::resource("/")
-                    .guard(fn_guard(|ctx| ctx.head().uri.query() == Some("query=test")))
-                    .to(HttpResponse::Ok),
-            ),
+            App::new()
+                .wrap(NormalizePath::new(TrailingSlash::Trim))
+                .service(
+                    web::resource("/")
+                        .guard(fn_guard(|ctx| ctx.head().uri.query() == Some("query=test")))
+                        .to(HttpResponse::Ok),
+                ),
         )
         .await;
 
@@ -310,7 +553,7 @@ mod tests {
     async fn ensure_trailing_slash() {
         let app = init_service(
             App::new()
-                .wrap(NormalizePath(TrailingSlash::Always))
+                .wrap(NormalizePath::new(TrailingSlash::Always))
                 .service(web::resource("/").to(HttpResponse::Ok))
                 .service(web::resource("/v1/something/").to(HttpResponse::Ok))
                 .service(
@@ -347,7 +590,7 @@ mod tests {
     async fn ensure_root_trailing_slash_with_query() {
         let app = init_service(
             App::new()
-                .wrap(NormalizePath(TrailingSlash::Always))
+                .wrap(NormalizePath::new(TrailingSlash::Always))
                 .service(
                     web::resource("/")
                         .guard(fn_guard(|ctx| ctx.head().uri.query() == Some("query=test")))
@@ -369,7 +612,7 @@ mod tests {
     async fn keep_trailing_slash_unchanged() {
         let app = init_service(
             App::new()
-                .wrap(NormalizePath(TrailingSlash::MergeOnly))
+                .wrap(NormalizePath::new(TrailingSlash::MergeOnly))
                 .service(web::resource("/").to(HttpResponse::Ok))
                 .service(web::resource("/v1/something").to(HttpResponse::Ok))
                 .service(web::resource("/v1/").to(HttpResponse::Ok))
@@ -483,4 +726,88 @@ mod tests {
         let res = normalize.call(req).await.unwrap();
         assert!(res.status().is_success());
     }
+
+    #[actix_rt::test]
+    async fn dot_segments_and_lowercase() {
+        let app = init_service(
+            App::new()
+                .wrap(NormalizePath::trim().remove_dot_segments().lowercase())
+                .service(web::resource("/").to(HttpResponse::Ok))
+                .service(web::resource("/v1/something").to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let test_uris = vec![
+            "/..",
+            "/./",
+            "/v1/./something",
+            "/v1/other/../something",
+            "/v1/other/%2E%2e/something",
+            "/../../v1/something",
+            "/V1//Other/..//Something/.",
+        ];
+
+        for uri in test_uris {
+            let req = TestRequest::with_uri(uri).to_request();
+            let res = call_service(&app, req).await;
+            assert!(res.status().is_success(), "Failed uri: {}", uri);
+        }
+    }
+
+    #[test]
+    fn dot_segment_removal() {
+        assert_eq!(remove_dot_segments("/"), "/");
+        assert_eq!(remove_dot_segments("/a/b/c/./../../g"), "/a/g");
+        assert_eq!(remove_dot_segments("/mid/content=5/../6"), "/mid/6");
+        assert_eq!(remove_dot_segments("/a/b/.."), "/a/");
+        assert_eq!(remove_dot_segments("/a/b/."), "/a/b/");
+        assert_eq!(remove_dot_segments("/../a"), "/a");
+        assert_eq!(remove_dot_segments("/a/...b/.c"), "/a/...b/.c");
+    }
+
+    #[actix_rt::test]
+    async fn redirects() {
+        let srv = |req: ServiceRequest| {
+            assert_eq!("/v1/something", req.path());
+            ready(Ok(req.into_response(HttpResponse::Ok().finish())))
+        };
+
+        let normalize = NormalizePath::trim()
+            .remove_dot_segments()
+            .redirect(StatusCode::PERMANENT_REDIRECT)
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let req = TestRequest::with_uri("/v1/something").to_srv_request();
+        let res = normalize.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let test_uris = vec![
+            ("/v1/something/", "/v1/something"),
+            ("/v1/./something?q=1", "/v1/something?q=1"),
+            ("//evil.com/..", "/"),
+            ("/..//evil.com", "/evil.com"),
+            ("/\\evil.com/", "/%5Cevil.com"),
+            ("//\\evil.com", "/%5Cevil.com"),
+            ("/./\\evil.com?q=\\", "/%5Cevil.com?q=\\"),
+        ];
+
+        for (uri, location) in test_uris {
+            let req = TestRequest::with_uri(uri).to_srv_request();
+            let res = normalize.call(req).await.unwrap();
+            assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT, "uri: {uri}");
+            assert_eq!(
+                res.headers().get_all(LOCATION).collect::<Vec<_>>(),
+                [location],
+                "uri: {uri}"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn redirect_with_non_redirect_status() {
+        let _ = NormalizePath::trim().redirect(StatusCode::OK);
+    }
 }
@@ -0,0 +1,759 @@
+//! For middleware documentation, see [`OpenTelemetry`].
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    net::SocketAddr,
+    pin::Pin,
+    str::FromStr as _,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use bytes::Bytes;
+use futures_core::ready;
+use opentelemetry::{
+    global::{self, BoxedTracer, ObjectSafeTracer},
+    trace::{
+        SpanContext, SpanId, SpanKind, Status, TraceContextExt as _, TraceFlags, TraceId,
+        TraceState, Tracer,
+    },
+    Context as OtelContext, KeyValue,
+};
+use pin_project_lite::pin_project;
+
+use crate::{
+    body::{BodySize, MessageBody},
+    http::{
+        header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+        uri::Authority,
+        Version,
+    },
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpMessage as _,
+};
+
+const TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+const TRACESTATE: HeaderName = HeaderName::from_static("tracestate");
+
+/// Middleware for tracing requests with [OpenTelemetry].
+///
+/// A server span is started for each request and ended once its response body has been sent or
+/// dropped. Spans follow the [semantic conventions for HTTP servers][semconv]: they are named
+/// after the request method and the pattern of the matched resource, such as `GET /users/{id}`,
+/// and record request attributes like `http.request.method`, `url.path`, and `http.route`, as well
+/// as `http.response.status_code`.
+///
+/// Errors, whether returned by inner services or carried by responses, are recorded as
+/// `exception` events. Spans of responses with 5xx status codes, and of requests whose inner
+/// services return errors, have their status set to error.
+///
+/// # Context Propagation
+/// A [W3C Trace Context] `traceparent` header (and `tracestate`, if present) on incoming requests
+/// is used as the parent of the server span. The `traceparent` of the server span is added to
+/// responses, which allows clients to correlate their requests with server traces.
+///
+/// The span context is also inserted into request extensions as an [`opentelemetry::Context`], so
+/// handlers can create child spans or propagate the trace to outgoing requests using
+/// [`ReqData<opentelemetry::Context>`](crate::web::ReqData). Inner services are called and polled
+/// with this context attached as the current context.
+///
+/// # Middleware Order
+/// For spans to cover the full middleware chain, this middleware should be registered last, using
+/// the final call to `App::wrap`, so that it runs before all other middleware.
+///
+/// # Tracer
+/// By default, spans are started using the tracer provider that is registered globally using
+/// [`opentelemetry::global::set_tracer_provider()`] when the app is built. A specific tracer can be
+/// provided using [`tracer()`](Self::tracer()).
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::OpenTelemetry, middleware::Logger, App};
+///
+/// let app = App::new()
+///     .wrap(Logger::default())
+///     .wrap(OpenTelemetry::new());
+/// ```
+///
+/// [OpenTelemetry]: https://opentelemetry.io
+/// [semconv]: https://opentelemetry.io/docs/specs/semconv/http/http-spans/#http-server
+/// [W3C Trace Context]: https://www.w3.org/TR/trace-context/
+#[derive(Debug, Clone, Default)]
+pub struct OpenTelemetry {
+    tracer: Option<Arc<BoxedTracer>>,
+}
+
+impl OpenTelemetry {
+    /// Constructs a tracing middleware that uses the global tracer provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the tracer used to start request spans.
+    pub fn tracer<T>(mut self, tracer: T) -> Self
+    where
+        T: ObjectSafeTracer + Send + Sync + 'static,
+    {
+        self.tracer = Some(Arc::new(BoxedTracer::new(Box::new(tracer))));
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for OpenTelemetry
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    type Response = ServiceResponse<OpenTelemetryBody<B>>;
+    type Error = Error;
+    type Transform = OpenTelemetryMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let tracer = self.tracer.clone().unwrap_or_else(|| {
+            Arc::new(global::tracer_with_scope(
+                opentelemetry::InstrumentationScope::builder("actix-web")
+                    .with_version(env!("CARGO_PKG_VERSION"))
+                    .build(),
+            ))
+        });
+
+        ready(Ok(OpenTelemetryMiddleware { service, tracer }))
+    }
+}
+
+/// OpenTelemetry middleware service.
+pub struct OpenTelemetryMiddleware<S> {
+    service: S,
+    tracer: Arc<BoxedTracer>,
+}
+
+impl<S, B> Service<ServiceRequest> for OpenTelemetryMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    type Response = ServiceResponse<OpenTelemetryBody<B>>;
+    type Error = Error;
+    type Future = OpenTelemetryResponse<S, B>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let parent_cx = extract_context(req.headers());
+
+        let span = self
+            .tracer
+            .span_builder(req.method().to_string())
+            .with_kind(SpanKind::Server)
+            .with_attributes(request_attributes(&req))
+            .start_with_context(&*self.tracer, &parent_cx);
+
+        let otel_cx = parent_cx.with_span(span);
+        req.extensions_mut().insert(otel_cx.clone());
+
+        let fut = {
+            let _guard = otel_cx.clone().attach();
+            self.service.call(req)
+        };
+
+        OpenTelemetryResponse {
+            fut,
+            otel_cx: Some(otel_cx),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+pin_project! {
+    pub struct OpenTelemetryResponse<S, B>
+    where
+        B: MessageBody,
+        S: Service<ServiceRequest>,
+    {
+        #[pin]
+        fut: S::Future,
+        otel_cx: Option<OtelContext>,
+        _phantom: PhantomData<B>,
+    }
+}
+
+impl<S, B> Future for OpenTelemetryResponse<S, B>
+where
+    B: MessageBody,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = Result<ServiceResponse<OpenTelemetryBody<B>>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let res = {
+            let _guard = this
+                .otel_cx
+                .as_ref()
+                .map(|otel_cx| otel_cx.clone().attach());
+            ready!(this.fut.poll(cx))
+        };
+
+        let otel_cx = this.otel_cx.take().expect("polled after completion");
+        let span = otel_cx.span();
+
+        let mut res = match res {
+            Ok(res) => res,
+            Err(err) => {
+                let status = err.as_response_error().status_code();
+
+                span.set_attribute(KeyValue::new(
+                    "http.response.status_code",
+                    i64::from(status.as_u16()),
+                ));
+                record_exception(&otel_cx, &err);
+                span.set_attribute(KeyValue::new("error.type", status.as_str().to_owned()));
+                span.set_status(Status::error(err.to_string()));
+                span.end();
+
+                return Poll::Ready(Err(err));
+            }
+        };
+
+        if let Some(route) = res.request().match_pattern() {
+            span.update_name(format!("{} {route}", res.request().method()));
+            span.set_attribute(KeyValue::new("http.route", route));
+        }
+
+        let status = res.status();
+        span.set_attribute(KeyValue::new(
+            "http.response.status_code",
+            i64::from(status.as_u16()),
+        ));
+
+        if let Some(err) = res.response().error() {
+            record_exception(&otel_cx, err);
+        }
+
+        if status.is_server_error() {
+            span.set_attribute(KeyValue::new("error.type", status.as_str().to_owned()));
+            span.set_status(Status::error(status.to_string()));
+        }
+
+        if let Some(traceparent) = format_traceparent(span.span_context()) {
+            res.headers_mut().insert(TRACEPARENT, traceparent);
+        }
+
+        Poll::Ready(Ok(res.map_body(move |_, body| OpenTelemetryBody {
+            body,
+            otel_cx: Some(otel_cx),
+        })))
+    }
+}
+
+pin_project! {
+    pub struct OpenTelemetryBody<B> {
+        #[pin]
+        body: B,
+        otel_cx: Option<OtelContext>,
+    }
+
+    impl<B> PinnedDrop for OpenTelemetryBody<B> {
+        fn drop(this: Pin<&mut Self>) {
+            if let Some(otel_cx) = this.project().otel_cx.take() {
+                otel_cx.span().end();
+            }
+        }
+    }
+}
+
+impl<B: MessageBody> MessageBody for OpenTelemetryBody<B> {
+    type Error = B::Error;
+
+    #[inline]
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    #[inline]
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        self.project().body.poll_next(cx)
+    }
+}
+
+/// Records an error as an `exception` event on the context's span.
+fn record_exception(otel_cx: &OtelContext, err: &Error) {
+    otel_cx.span().add_event(
+        "exception",
+        vec![KeyValue::new("exception.message", err.to_string())],
+    );
+}
+
+/// Returns semantic convention attributes known when a request is received.
+fn request_attributes(req: &ServiceRequest) -> Vec<KeyValue> {
+    let conn = req.connection_info();
+
+    let mut attrs = vec![
+        KeyValue::new("http.request.method", req.method().to_string()),
+        KeyValue::new("url.scheme", conn.scheme().to_owned()),
+        KeyValue::new("url.path", req.path().to_owned()),
+    ];
+
+    if !req.query_string().is_empty() {
+        attrs.push(KeyValue::new("url.query", req.query_string().to_owned()));
+    }
+
+    match conn.host().parse::<Authority>() {
+        Ok(authority) => {
+            attrs.push(KeyValue::new("server.address", authority.host().to_owned()));
+
+            if let Some(port) = authority.port_u16() {
+                attrs.push(KeyValue::new("server.port", i64::from(port)));
+            }
+        }
+        Err(_) => attrs.push(KeyValue::new("server.address", conn.host().to_owned())),
+    }
+
+    if let Some(version) = protocol_version(req.version()) {
+        attrs.push(KeyValue::new("network.protocol.version", version));
+    }
+
+    if let Some(addr) = conn.realip_remote_addr() {
+        // peer and `Forwarded` addresses may include a port
+        let addr = match addr.parse::<SocketAddr>() {
+            Ok(addr) => addr.ip().to_string(),
+            Err(_) => addr.to_owned(),
+        };
+
+        attrs.push(KeyValue::new("client.address", addr));
+    }
+
+    if let Some(user_agent) = req
+        .headers()
+        .get(USER_AGENT)
+        .and_then(|ua| ua.to_str().ok())
+    {
+        attrs.push(KeyValue::new("user_agent.original", user_agent.to_owned()));
+    }
+
+    attrs
+}
+
+fn protocol_version(version: Version) -> Option<&'static str> {
+    match version {
+        Version::HTTP_09 => Some("0.9"),
+        Version::HTTP_10 => Some("1.0"),
+        Version::HTTP_11 => Some("1.1"),
+        Version::HTTP_2 => Some("2"),
+        Version::HTTP_3 => Some("3"),
+        _ => None,
+    }
+}
+
+/// Returns a context with the remote span described by the request's trace context headers.
+///
+/// Returns an empty context if the `traceparent` header is missing or invalid.
+fn extract_context(headers: &HeaderMap) -> OtelContext {
+    let Some(span_cx) = headers
+        .get(&TRACEPARENT)
+        .and_then(|traceparent| traceparent.to_str().ok())
+        .and_then(|traceparent| {
+            let trace_state = headers
+                .get(&TRACESTATE)
+                .and_then(|state| state.to_str().ok())
+                .and_then(|state| TraceState::from_str(state).ok())
+                .unwrap_or_default();
+
+            parse_traceparent(traceparent, trace_state)
+        })
+    else {
+        return OtelContext::new();
+    };
+
+    OtelContext::new().with_remote_span_context(span_cx)
+}
+
+/// Parses a `traceparent` header value.
+///
+/// See <https://www.w3.org/TR/trace-context/#traceparent-header>.
+fn parse_traceparent(traceparent: &str, trace_state: TraceState) -> Option<SpanContext> {
+    let mut parts = traceparent.trim().split('-');
+
+    let version = parts.next()?;
+    let trace_id = parts.next()?;
+    let span_id = parts.next()?;
+    let flags = parts.next()?;
+
+    let is_hex = |part: &str, len: usize| {
+        part.len() == len
+            && part
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    };
+
+    if !is_hex(version, 2)
+        || version == "ff"
+        // only future versions may have additional fields
+        || (version == "00" && parts.next().is_some())
+        || !is_hex(trace_id, 32)
+        || !is_hex(span_id, 16)
+        || !is_hex(flags, 2)
+    {
+        return None;
+    }
+
+    let trace_id = TraceId::from_hex(trace_id).ok()?;
+    let span_id = SpanId::from_hex(span_id).ok()?;
+    let flags = u8::from_str_radix(flags, 16).ok()?;
+
+    if trace_id == TraceId::INVALID || span_id == SpanId::INVALID {
+        return None;
+    }
+
+    Some(SpanContext::new(
+        trace_id,
+        span_id,
+        TraceFlags::new(flags) & TraceFlags::SAMPLED,
+        true,
+        trace_state,
+    ))
+}
+
+/// Formats a `traceparent` header value, unless the span context is invalid.
+fn format_traceparent(span_cx: &SpanContext) -> Option<HeaderValue> {
+    if !span_cx.is_valid() {
+        return None;
+    }
+
+    let traceparent = format!(
+        "00-{:032x}-{:016x}-{:02x}",
+        span_cx.trace_id(),
+        span_cx.span_id(),
+        span_cx.trace_flags() & TraceFlags::SAMPLED,
+    );
+
+    HeaderValue::from_str(&traceparent).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, sync::Mutex, time::SystemTime};
+
+    use opentelemetry::trace::{Span, SpanBuilder};
+
+    use super::*;
+    use crate::{
+        error,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    /// Span recorded by [`TestTracer`] when it ends.
+    #[derive(Debug, Clone)]
+    struct FinishedSpan {
+        name: String,
+        kind: Option<SpanKind>,
+        span_cx: SpanContext,
+        parent: SpanContext,
+        attributes: Vec<KeyValue>,
+        events: Vec<String>,
+        status: Status,
+    }
+
+    impl FinishedSpan {
+        fn attribute(&self, key: &str) -> Option<String> {
+            self.attributes
+                .iter()
+                .rev()
+                .find(|kv| kv.key.as_str() == key)
+                .map(|kv| kv.value.to_string())
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct TestTracer {
+        finished: Arc<Mutex<Vec<FinishedSpan>>>,
+    }
+
+    impl TestTracer {
+        fn finished(&self) -> Vec<FinishedSpan> {
+            self.finished.lock().unwrap().clone()
+        }
+    }
+
+    impl Tracer for TestTracer {
+        type Span = TestSpan;
+
+        fn build_with_context(&self, builder: SpanBuilder, parent_cx: &OtelContext) -> TestSpan {
+            let parent = parent_cx.span().span_context().clone();
+
+            let trace_id = if parent.is_valid() {
+                parent.trace_id()
+            } else {
+                TraceId::from_bytes([1; 16])
+            };
+
+            TestSpan {
+                finished: Arc::clone(&self.finished),
+                span: Some(FinishedSpan {
+                    name: builder.name.into_owned(),
+                    kind: builder.span_kind,
+                    span_cx: SpanContext::new(
+                        trace_id,
+                        SpanId::from_bytes([2; 8]),
+                        TraceFlags::SAMPLED,
+                        false,
+                        TraceState::default(),
+                    ),
+                    parent,
+                    attributes: builder.attributes.unwrap_or_default(),
+                    events: Vec::new(),
+                    status: Status::Unset,
+                }),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct TestSpan {
+        finished: Arc<Mutex<Vec<FinishedSpan>>>,
+        span: Option<FinishedSpan>,
+    }
+
+    impl Span for TestSpan {
+        fn add_event_with_timestamp<T>(&mut self, name: T, _: SystemTime, _: Vec<KeyValue>)
+        where
+            T: Into<Cow<'static, str>>,
+        {
+            if let Some(span) = &mut self.span {
+                span.events.push(name.into().into_owned());
+            }
+        }
+
+        fn span_context(&self) -> &SpanContext {
+            self.span
+                .as_ref()
+                .map_or(&SpanContext::NONE, |span| &span.span_cx)
+        }
+
+        fn is_recording(&self) -> bool {
+            self.span.is_some()
+        }
+
+        fn set_attribute(&mut self, attribute: KeyValue) {
+            if let Some(span) = &mut self.span {
+                span.attributes.push(attribute);
+            }
+        }
+
+        fn set_status(&mut self, status: Status) {
+            if let Some(span) = &mut self.span {
+                span.status = status;
+            }
+        }
+
+        fn update_name<T>(&mut self, new_name: T)
+        where
+            T: Into<Cow<'static, str>>,
+        {
+            if let Some(span) = &mut self.span {
+                span.name = new_name.into().into_owned();
+            }
+        }
+
+        fn add_link(&mut self, _: SpanContext, _: Vec<KeyValue>) {}
+
+        fn end_with_timestamp(&mut self, _: SystemTime) {
+            if let Some(span) = self.span.take() {
+                self.finished.lock().unwrap().push(span);
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn span_per_request() {
+        let tracer = TestTracer::default();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(OpenTelemetry::new().tracer(tracer.clone()))
+                .route("/users/{id}", web::get().to(|| async { "user" })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/users/42?full=true")
+            .insert_header(("user-agent", "test"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(
+            res.headers().get("traceparent").unwrap(),
+            "00-01010101010101010101010101010101-0202020202020202-01"
+        );
+
+        // span ends once the body is consumed
+        assert!(tracer.finished().is_empty());
+        let _ = test::read_body(res).await;
+
+        let spans = tracer.finished();
+        assert_eq!(spans.len(), 1);
+
+        let span = &spans[0];
+        assert_eq!(span.name, "GET /users/{id}");
+        assert_eq!(span.kind, Some(SpanKind::Server));
+        assert!(!span.parent.is_valid());
+        assert_eq!(span.status, Status::Unset);
+        assert_eq!(span.attribute("http.request.method").unwrap(), "GET");
+        assert_eq!(span.attribute("http.route").unwrap(), "/users/{id}");
+        assert_eq!(span.attribute("url.path").unwrap(), "/users/42");
+        assert_eq!(span.attribute("url.query").unwrap(), "full=true");
+        assert_eq!(span.attribute("url.scheme").unwrap(), "http");
+        assert_eq!(span.attribute("server.address").unwrap(), "localhost");
+        assert_eq!(span.attribute("server.port").unwrap(), "8080");
+        assert_eq!(span.attribute("network.protocol.version").unwrap(), "1.1");
+        assert_eq!(span.attribute("user_agent.original").unwrap(), "test");
+        assert_eq!(span.attribute("http.response.status_code").unwrap(), "200");
+    }
+
+    #[actix_rt::test]
+    async fn unmatched_route() {
+        let tracer = TestTracer::default();
+
+        let app =
+            test::init_service(App::new().wrap(OpenTelemetry::new().tracer(tracer.clone()))).await;
+
+        let req = TestRequest::with_uri("/missing").to_request();
+        let _ = test::read_body(test::call_service(&app, req).await).await;
+
+        let span = &tracer.finished()[0];
+        assert_eq!(span.name, "GET");
+        assert_eq!(span.attribute("http.route"), None);
+        assert_eq!(span.attribute("http.response.status_code").unwrap(), "404");
+        assert_eq!(span.status, Status::Unset);
+    }
+
+    #[actix_rt::test]
+    async fn remote_parent() {
+        let tracer = TestTracer::default();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(OpenTelemetry::new().tracer(tracer.clone()))
+                .route(
+                    "/",
+                    web::get().to(|otel_cx: web::ReqData<OtelContext>| async move {
+                        // the request's context is also the current context in handlers
+                        assert_eq!(
+                            OtelContext::current().span().span_context(),
+                            otel_cx.span().span_context()
+                        );
+
+                        "ok"
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .insert_header((
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            ))
+            .insert_header(("tracestate", "vendor=value"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(res.status().is_success());
+
+        assert_eq!(
+            res.headers().get("traceparent").unwrap(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0202020202020202-01"
+        );
+        drop(res);
+
+        let span = &tracer.finished()[0];
+        assert!(span.parent.is_remote());
+        assert_eq!(
+            span.parent.trace_id(),
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap()
+        );
+        assert_eq!(
+            span.parent.span_id(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap()
+        );
+        assert_eq!(span.parent.trace_state().header(), "vendor=value");
+    }
+
+    #[actix_rt::test]
+    async fn errors() {
+        let tracer = TestTracer::default();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(OpenTelemetry::new().tracer(tracer.clone()))
+                .route(
+                    "/",
+                    web::get().to(|| async {
+                        Err::<HttpResponse, _>(error::ErrorInternalServerError("oops"))
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let _ = test::read_body(test::call_service(&app, req).await).await;
+
+        let span = &tracer.finished()[0];
+        assert_eq!(span.attribute("http.response.status_code").unwrap(), "500");
+        assert_eq!(span.attribute("error.type").unwrap(), "500");
+        assert_eq!(span.events, ["exception"]);
+        assert!(matches!(span.status, Status::Error { .. }));
+    }
+
+    #[actix_rt::test]
+    async fn service_error() {
+        let tracer = TestTracer::default();
+
+        let mw = OpenTelemetry::new()
+            .tracer(tracer.clone())
+            .new_transform(crate::dev::fn_service(|_: ServiceRequest| async {
+                Err::<ServiceResponse, _>(error::ErrorBadGateway("upstream"))
+            }))
+            .await
+            .unwrap();
+
+        let req = TestRequest::default().to_srv_request();
+        assert!(mw.call(req).await.is_err());
+
+        let span = &tracer.finished()[0];
+        assert_eq!(span.attribute("http.response.status_code").unwrap(), "502");
+        assert_eq!(span.events, ["exception"]);
+        assert!(matches!(span.status, Status::Error { .. }));
+    }
+
+    #[test]
+    fn traceparent_parsing() {
+        let parse = |traceparent| parse_traceparent(traceparent, TraceState::default());
+
+        let span_cx = parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+        assert!(span_cx.is_sampled());
+        assert!(span_cx.is_remote());
+
+        let span_cx = parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00").unwrap();
+        assert!(!span_cx.is_sampled());
+
+        // future versions may add fields
+        assert!(parse("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra").is_some());
+
+        assert!(parse("").is_none());
+        assert!(parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra").is_none());
+        assert!(parse("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_none());
+        assert!(parse("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01").is_none());
+        assert!(parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none());
+        assert!(parse("00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01").is_none());
+        assert!(parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7").is_none());
+    }
+}
@@ -0,0 +1,476 @@
+//! For middleware documentation, see [`RedirectHttps`].
+
+use std::{
+    future::Future,
+    iter,
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_http::uri::{Authority, Uri};
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Either, Ready};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use crate::{
+    body::EitherBody,
+    http::{
+        header::{self, HeaderName, LOCATION},
+        StatusCode,
+    },
+    service::{ServiceRequest, ServiceResponse},
+    web::RealIpConfig,
+    Error, HttpResponse,
+};
+
+const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// Middleware for redirecting plain HTTP requests to HTTPS.
+///
+/// Requests are considered secure when they were received over TLS, or when their URI has the
+/// `https` scheme. Other requests receive a redirect response to the same path and query on the
+/// `https` scheme, using the host from the request's `Host` header, unless a [host](Self::host())
+/// is configured. Requests without a usable host receive a `400 Bad Request` response.
+///
+/// # Reverse Proxies
+/// When TLS is terminated by a reverse proxy, the scheme of the original request is taken from
+/// the `Forwarded` or `X-Forwarded-Proto` headers set by the proxy. Since clients can also send
+/// these headers, they are only honored for requests from proxies trusted by the
+/// [`RealIpConfig`] registered as app data. Like the [`RealIp`](crate::web::RealIp) extractor,
+/// the scheme is taken from the right-most entry that was not added by a trusted proxy on behalf
+/// of another trusted proxy.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::StatusCode, middleware::RedirectHttps, web, App};
+///
+/// let app = App::new()
+///     .app_data(web::RealIpConfig::default().trusted_proxy("127.0.0.1"))
+///     .wrap(
+///         RedirectHttps::new()
+///             .status(StatusCode::MOVED_PERMANENTLY)
+///             .host("example.com")
+///             .port(8443),
+///     )
+///     .route("/", web::get().to(|| async { "only served over HTTPS" }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RedirectHttps {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    status: StatusCode,
+    host: Option<String>,
+    port: Option<u16>,
+}
+
+impl RedirectHttps {
+    /// Constructs a middleware that redirects with `308 Permanent Redirect` responses.
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                status: StatusCode::PERMANENT_REDIRECT,
+                host: None,
+                port: None,
+            }),
+        }
+    }
+
+    /// Sets the status code of redirect responses.
+    ///
+    /// Use `301 Moved Permanently` or `308 Permanent Redirect`. Unlike `301`, `308` guarantees that
+    /// clients keep the request method and body. Defaults to `308`.
+    ///
+    /// # Panics
+    /// Panics if `status` is not a redirection status code, or if called after this middleware has
+    /// been cloned.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        assert!(
+            status.is_redirection(),
+            "redirect status code must be in the 3xx range"
+        );
+
+        self.inner_mut().status = status;
+        self
+    }
+
+    /// Sets the host to redirect to, instead of the host of the request.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.inner_mut().host = Some(host.into());
+        self
+    }
+
+    /// Sets the port to redirect to.
+    ///
+    /// By default, redirects use the default HTTPS port, 443. Ports of requests are never kept,
+    /// since they are the ports of plain HTTP listeners.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn port(mut self, port: u16) -> Self {
+        self.inner_mut().port = Some(port);
+        self
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Rc::get_mut(&mut self.inner)
+            .expect("HTTPS redirect can not be configured after it has been cloned")
+    }
+}
+
+impl Default for RedirectHttps {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inner {
+    fn is_secure(&self, req: &ServiceRequest) -> bool {
+        let config = RealIpConfig::from_req(req.request());
+        let trusted = req
+            .peer_addr()
+            .is_some_and(|addr| config.is_trusted(&addr.ip()));
+
+        if trusted {
+            if let Some(proto) = forwarded_proto(req, config) {
+                return proto.eq_ignore_ascii_case("https");
+            }
+        }
+
+        req.app_config().secure() || req.uri().scheme_str() == Some("https")
+    }
+
+    /// Returns the HTTPS URL of the request, or `None` if its host is missing or invalid.
+    fn https_url(&self, req: &ServiceRequest) -> Option<Uri> {
+        let host = match &self.host {
+            Some(host) => host.as_str(),
+            None => req
+                .headers()
+                .get(header::HOST)
+                .and_then(|host| host.to_str().ok())
+                .or_else(|| req.uri().authority().map(Authority::as_str))?,
+        };
+
+        // discard the port of the plain HTTP listener, and any user info
+        let host = host.parse::<Authority>().ok()?;
+        let authority = match self.port {
+            Some(port) if port != 443 => format!("{}:{port}", host.host()),
+            _ => host.host().to_owned(),
+        };
+
+        let path_and_query = req.uri().path_and_query().map_or("/", |pq| pq.as_str());
+
+        Uri::builder()
+            .scheme("https")
+            .authority(authority)
+            .path_and_query(path_and_query)
+            .build()
+            .ok()
+    }
+}
+
+/// Returns the protocol of the original request, from the `Forwarded` or, if it is missing, the
+/// `X-Forwarded-Proto` headers of a request received from a trusted proxy.
+///
+/// Each proxy appends an entry for the connection it received, so entries are walked from the
+/// right-most one while they were received from another trusted proxy. Entries of
+/// `X-Forwarded-Proto` headers are matched with those of `X-Forwarded-For` headers from the right.
+fn forwarded_proto<'a>(req: &'a ServiceRequest, config: &RealIpConfig) -> Option<&'a str> {
+    let header_values = |name: HeaderName| {
+        req.headers()
+            .get_all(name)
+            .filter_map(|hdr| hdr.to_str().ok())
+            .flat_map(|hdr| hdr.split(','))
+            .map(str::trim)
+            .collect::<Vec<_>>()
+    };
+
+    // pairs of protocol and client address, from the right-most entry
+    let mut entries = header_values(header::FORWARDED)
+        .into_iter()
+        .rev()
+        .map(|element| {
+            let mut proto = None;
+            let mut addr = None;
+
+            for pair in element.split(';') {
+                let Some((name, value)) = pair.split_once('=') else {
+                    continue;
+                };
+
+                match name.trim() {
+                    name if name.eq_ignore_ascii_case("proto") => {
+                        proto = Some(value.trim().trim_matches('"'));
+                    }
+                    name if name.eq_ignore_ascii_case("for") => addr = Some(value.trim()),
+                    _ => {}
+                }
+            }
+
+            (proto, addr)
+        })
+        .collect::<Vec<_>>();
+
+    if entries.is_empty() {
+        let addrs = header_values(X_FORWARDED_FOR);
+
+        entries = header_values(X_FORWARDED_PROTO)
+            .into_iter()
+            .rev()
+            .zip(addrs.into_iter().rev().map(Some).chain(iter::repeat(None)))
+            .map(|(proto, addr)| (Some(proto), addr))
+            .collect();
+    }
+
+    let mut proto = None;
+
+    for (entry_proto, addr) in entries {
+        proto = entry_proto;
+
+        if !addr.is_some_and(|addr| config.is_trusted_forwarded_addr(addr)) {
+            break;
+        }
+    }
+
+    proto
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RedirectHttps
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RedirectHttpsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RedirectHttpsMiddleware {
+            service,
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+/// HTTPS redirect middleware service.
+pub struct RedirectHttpsMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S, B> Service<ServiceRequest> for RedirectHttpsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Either<RedirectHttpsFuture<S, B>, Ready<Result<Self::Response, Self::Error>>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if self.inner.is_secure(&req) {
+            return Either::left(RedirectHttpsFuture {
+                fut: self.service.call(req),
+                _body: PhantomData,
+            });
+        }
+
+        let res = match self.inner.https_url(&req) {
+            Some(url) => HttpResponse::build(self.inner.status)
+                .insert_header((LOCATION, url.to_string()))
+                .finish(),
+            None => HttpResponse::BadRequest().finish(),
+        };
+
+        Either::right(ready(Ok(req.into_response(res).map_into_right_body())))
+    }
+}
+
+pin_project! {
+    pub struct RedirectHttpsFuture<S: Service<ServiceRequest>, B> {
+        #[pin]
+        fut: S::Future,
+        _body: PhantomData<B>,
+    }
+}
+
+impl<S, B> Future for RedirectHttpsFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = Result<ServiceResponse<EitherBody<B>>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(self.project().fut.poll(cx))?;
+        Poll::Ready(Ok(res.map_into_left_body()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    use super::*;
+    use crate::test::{self, TestRequest};
+
+    async fn redirect(mw: RedirectHttps, req: TestRequest) -> (StatusCode, Option<String>) {
+        let mw = mw.new_transform(test::ok_service()).await.unwrap();
+        let res = mw.call(req.to_srv_request()).await.unwrap();
+
+        let location = res
+            .headers()
+            .get(LOCATION)
+            .map(|location| location.to_str().unwrap().to_owned());
+
+        (res.status(), location)
+    }
+
+    fn proxy() -> SocketAddr {
+        SocketAddr::from((Ipv4Addr::new(10, 0, 0, 1), 40000))
+    }
+
+    #[actix_rt::test]
+    async fn redirects_plain_requests() {
+        let req =
+            TestRequest::with_uri("/path?query=1").insert_header((header::HOST, "a.com:8080"));
+        assert_eq!(
+            redirect(RedirectHttps::new(), req).await,
+            (
+                StatusCode::PERMANENT_REDIRECT,
+                Some("https://a.com/path?query=1".to_owned())
+            )
+        );
+
+        let req = TestRequest::with_uri("https://a.com/path");
+        assert_eq!(
+            redirect(RedirectHttps::new(), req).await,
+            (StatusCode::OK, None)
+        );
+
+        // no host
+        let req = TestRequest::with_uri("/path");
+        assert_eq!(
+            redirect(RedirectHttps::new(), req).await,
+            (StatusCode::BAD_REQUEST, None)
+        );
+
+        let req = TestRequest::with_uri("/").insert_header((header::HOST, "a.com/evil"));
+        assert_eq!(
+            redirect(RedirectHttps::new(), req).await,
+            (StatusCode::BAD_REQUEST, None)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn rewrites_host_and_port() {
+        let mw = || {
+            RedirectHttps::new()
+                .status(StatusCode::MOVED_PERMANENTLY)
+                .host("b.com")
+                .port(8443)
+        };
+
+        let req = TestRequest::with_uri("/path").insert_header((header::HOST, "a.com"));
+        assert_eq!(
+            redirect(mw(), req).await,
+            (
+                StatusCode::MOVED_PERMANENTLY,
+                Some("https://b.com:8443/path".to_owned())
+            )
+        );
+
+        let req = TestRequest::with_uri("/").insert_header((header::HOST, "a.com"));
+        assert_eq!(
+            redirect(RedirectHttps::new().port(443), req)
+                .await
+                .1
+                .unwrap(),
+            "https://a.com/"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn forwarded_headers_from_trusted_proxies() {
+        let req = || {
+            TestRequest::default()
+                .insert_header((header::HOST, "a.com"))
+                .insert_header((X_FORWARDED_PROTO, "https"))
+                .peer_addr(proxy())
+        };
+        let cfg = || RealIpConfig::default().trusted_proxy("10.0.0.0/8");
+
+        // untrusted
+        let (status, _) = redirect(RedirectHttps::new(), req()).await;
+        assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+
+        let untrusted = req().app_data(RealIpConfig::default().trusted_proxy("192.0.2.0/24"));
+        let (status, _) = redirect(RedirectHttps::new(), untrusted).await;
+        assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+
+        let (status, _) = redirect(RedirectHttps::new(), req().app_data(cfg())).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let req = TestRequest::default()
+            .insert_header((header::HOST, "a.com"))
+            .insert_header((header::FORWARDED, "for=192.0.2.60;proto=http"))
+            .insert_header((X_FORWARDED_PROTO, "https"))
+            .peer_addr(proxy())
+            .app_data(cfg());
+        let (status, _) = redirect(RedirectHttps::new(), req).await;
+        assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+
+        let req = TestRequest::default()
+            .insert_header((header::HOST, "a.com"))
+            .insert_header((header::FORWARDED, "for=192.0.2.60; Proto=\"HTTPS\""))
+            .peer_addr(proxy())
+            .app_data(cfg());
+        let (status, _) = redirect(RedirectHttps::new(), req).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn forwarded_headers_use_nearest_untrusted_hop() {
+        let cfg = || RealIpConfig::default().trusted_proxy("10.0.0.0/8");
+        let req = |name: HeaderName, value: &'static str| {
+            TestRequest::default()
+                .insert_header((header::HOST, "a.com"))
+                .insert_header((name, value))
+                .peer_addr(proxy())
+                .app_data(cfg())
+        };
+
+        // entries prepended by the client are ignored
+        let forwarded = "proto=https, for=192.0.2.60;proto=http";
+        let (status, _) = redirect(RedirectHttps::new(), req(header::FORWARDED, forwarded)).await;
+        assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+
+        let (status, _) =
+            redirect(RedirectHttps::new(), req(X_FORWARDED_PROTO, "https, http")).await;
+        assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+
+        // entries of trusted proxies are skipped
+        let forwarded = "for=192.0.2.60;proto=https, for=10.0.0.2;proto=http";
+        let (status, _) = redirect(RedirectHttps::new(), req(header::FORWARDED, forwarded)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let forwarded = "for=192.0.2.60;proto=http, for=10.0.0.2;proto=https";
+        let (status, _) = redirect(RedirectHttps::new(), req(header::FORWARDED, forwarded)).await;
+        assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+
+        let req = req(X_FORWARDED_PROTO, "https, http")
+            .insert_header((X_FORWARDED_FOR, "192.0.2.60, 10.0.0.2"));
+        let (status, _) = redirect(RedirectHttps::new(), req).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -0,0 +1,638 @@
+//! For middleware documentation, see [`SecurityHeaders`].
+
+use std::{
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use actix_utils::future::{err, ok, ready, Ready};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use crate::{
+    dev::{Payload, Service, Transform},
+    error,
+    http::header::{
+        HeaderMap, HeaderName, HeaderValue, TryIntoHeaderValue, CONTENT_SECURITY_POLICY,
+        CONTENT_SECURITY_POLICY_REPORT_ONLY, REFERRER_POLICY, STRICT_TRANSPORT_SECURITY,
+        X_CONTENT_TYPE_OPTIONS,
+    },
+    service::{ServiceRequest, ServiceResponse},
+    Error, FromRequest, HttpMessage as _, HttpRequest,
+};
+
+const CROSS_ORIGIN_OPENER_POLICY: HeaderName =
+    HeaderName::from_static("cross-origin-opener-policy");
+const CROSS_ORIGIN_EMBEDDER_POLICY: HeaderName =
+    HeaderName::from_static("cross-origin-embedder-policy");
+const PERMISSIONS_POLICY: HeaderName = HeaderName::from_static("permissions-policy");
+
+/// Placeholder in content security policies that is replaced by a nonce for each request.
+const NONCE_PLACEHOLDER: &str = "{nonce}";
+
+/// Middleware for setting security-related response headers.
+///
+/// Like [`DefaultHeaders`], headers that are already set in a response will *not* be overwritten,
+/// so handlers and inner middleware can override them for individual responses.
+///
+/// # Strict Transport Security
+/// The `Strict-Transport-Security` header is only sent in responses to requests whose
+/// [scheme](crate::dev::ConnectionInfo::scheme()) is `https`, as required by [RFC 6797]. When
+/// running behind a proxy, the scheme is taken from the `Forwarded` or `X-Forwarded-Proto`
+/// headers.
+///
+/// # Content Security Policy Nonces
+/// Policies can include the placeholder `{nonce}`, which is replaced by a new random nonce for each
+/// request, such as in `script-src 'nonce-{nonce}'`. Handlers can use the [`CspNonce`] extractor
+/// to add the same nonce to their inline scripts and styles.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{
+///     middleware::{CspNonce, SecurityHeaders},
+///     web, App, HttpResponse,
+/// };
+///
+/// async fn index(nonce: CspNonce) -> HttpResponse {
+///     HttpResponse::Ok()
+///         .content_type("text/html")
+///         .body(format!("<script nonce=\"{nonce}\">console.log(1)</script>"))
+/// }
+///
+/// let app = App::new()
+///     .wrap(
+///         SecurityHeaders::recommended()
+///             .hsts(Duration::from_secs(2 * 365 * 24 * 60 * 60))
+///             .hsts_include_subdomains()
+///             .content_security_policy("default-src 'self'; script-src 'nonce-{nonce}'")
+///             .permissions_policy("camera=(), microphone=()"),
+///     )
+///     .route("/", web::get().to(index));
+/// ```
+///
+/// [`DefaultHeaders`]: crate::middleware::DefaultHeaders
+/// [RFC 6797]: https://www.rfc-editor.org/rfc/rfc6797#section-7.2
+#[derive(Debug, Clone, Default)]
+pub struct SecurityHeaders {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    headers: HeaderMap,
+    hsts: Option<Hsts>,
+    csp: Option<Csp>,
+}
+
+#[derive(Debug)]
+struct Hsts {
+    max_age: Duration,
+    include_subdomains: bool,
+    preload: bool,
+}
+
+impl Hsts {
+    fn header_value(&self) -> HeaderValue {
+        let mut value = format!("max-age={}", self.max_age.as_secs());
+
+        if self.include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+
+        if self.preload {
+            value.push_str("; preload");
+        }
+
+        HeaderValue::try_from(value).unwrap()
+    }
+}
+
+#[derive(Debug)]
+struct Csp {
+    name: HeaderName,
+
+    /// Policy split at nonce placeholders.
+    parts: Vec<String>,
+}
+
+impl Csp {
+    fn uses_nonce(&self) -> bool {
+        self.parts.len() > 1
+    }
+
+    fn header_value(&self, nonce: Option<&CspNonce>) -> HeaderValue {
+        let nonce = nonce.map_or("", CspNonce::as_str);
+        HeaderValue::try_from(self.parts.join(nonce)).unwrap()
+    }
+}
+
+impl SecurityHeaders {
+    /// Constructs a middleware that sets no headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a middleware with headers that are safe for most applications.
+    ///
+    /// Sets:
+    /// - `Strict-Transport-Security: max-age=31536000`
+    /// - `X-Content-Type-Options: nosniff`
+    /// - `Referrer-Policy: strict-origin-when-cross-origin`
+    /// - `Cross-Origin-Opener-Policy: same-origin`
+    pub fn recommended() -> Self {
+        Self::new()
+            .hsts(Duration::from_secs(365 * 24 * 60 * 60))
+            .content_type_options()
+            .referrer_policy("strict-origin-when-cross-origin")
+            .cross_origin_opener_policy("same-origin")
+    }
+
+    /// Sets the `Strict-Transport-Security` header with the given `max-age`.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn hsts(mut self, max_age: Duration) -> Self {
+        let hsts = &mut self.inner_mut().hsts;
+
+        match hsts {
+            Some(hsts) => hsts.max_age = max_age,
+            None => {
+                *hsts = Some(Hsts {
+                    max_age,
+                    include_subdomains: false,
+                    preload: false,
+                })
+            }
+        }
+
+        self
+    }
+
+    /// Adds the `includeSubDomains` directive to the `Strict-Transport-Security` header.
+    ///
+    /// # Panics
+    /// Panics if [`hsts()`](Self::hsts()) has not been called, or if called after this middleware
+    /// has been cloned.
+    pub fn hsts_include_subdomains(mut self) -> Self {
+        self.hsts_mut().include_subdomains = true;
+        self
+    }
+
+    /// Adds the `preload` directive to the `Strict-Transport-Security` header.
+    ///
+    /// Preloading also requires a `max-age` of at least one year and the `includeSubDomains`
+    /// directive.
+    ///
+    /// # Panics
+    /// Panics if [`hsts()`](Self::hsts()) has not been called, or if called after this middleware
+    /// has been cloned.
+    pub fn hsts_preload(mut self) -> Self {
+        self.hsts_mut().preload = true;
+        self
+    }
+
+    /// Sets the `Content-Security-Policy` header.
+    ///
+    /// Occurrences of `{nonce}` in the policy are replaced by a nonce for each request.
+    ///
+    /// # Panics
+    /// Panics if the policy is not a valid header value, or if called after this middleware has
+    /// been cloned.
+    pub fn content_security_policy(self, policy: impl Into<String>) -> Self {
+        self.csp(CONTENT_SECURITY_POLICY, policy.into())
+    }
+
+    /// Sets the `Content-Security-Policy-Report-Only` header, instead of the
+    /// `Content-Security-Policy` header.
+    ///
+    /// Violations of report-only policies are reported but not blocked, which helps to test new
+    /// policies. Occurrences of `{nonce}` in the policy are replaced by a nonce for each request.
+    ///
+    /// # Panics
+    /// Panics if the policy is not a valid header value, or if called after this middleware has
+    /// been cloned.
+    pub fn content_security_policy_report_only(self, policy: impl Into<String>) -> Self {
+        self.csp(CONTENT_SECURITY_POLICY_REPORT_ONLY, policy.into())
+    }
+
+    /// Sets the `X-Content-Type-Options: nosniff` header.
+    ///
+    /// # Panics
+    /// Panics if called after this middleware has been cloned.
+    pub fn content_type_options(self) -> Self {
+        self.header(X_CONTENT_TYPE_OPTIONS, "nosniff")
+    }
+
+    /// Sets the `Referrer-Policy` header, such as `no-referrer`.
+    ///
+    /// # Panics
+    /// Panics if the policy is not a valid header value, or if called after this middleware has
+    /// been cloned.
+    pub fn referrer_policy(self, policy: impl TryIntoHeaderValue) -> Self {
+        self.header(REFERRER_POLICY, policy)
+    }
+
+    /// Sets the `Cross-Origin-Opener-Policy` header, such as `same-origin`.
+    ///
+    /// # Panics
+    /// Panics if the policy is not a valid header value, or if called after this middleware has
+    /// been cloned.
+    pub fn cross_origin_opener_policy(self, policy: impl TryIntoHeaderValue) -> Self {
+        self.header(CROSS_ORIGIN_OPENER_POLICY, policy)
+    }
+
+    /// Sets the `Cross-Origin-Embedder-Policy` header, such as `require-corp`.
+    ///
+    /// # Panics
+    /// Panics if the policy is not a valid header value, or if called after this middleware has
+    /// been cloned.
+    pub fn cross_origin_embedder_policy(self, policy: impl TryIntoHeaderValue) -> Self {
+        self.header(CROSS_ORIGIN_EMBEDDER_POLICY, policy)
+    }
+
+    /// Sets the `Permissions-Policy` header, such as `geolocation=(), camera=()`.
+    ///
+    /// # Panics
+    /// Panics if the policy is not a valid header value, or if called after this middleware has
+    /// been cloned.
+    pub fn permissions_policy(self, policy: impl TryIntoHeaderValue) -> Self {
+        self.header(PERMISSIONS_POLICY, policy)
+    }
+
+    fn header(mut self, name: HeaderName, value: impl TryIntoHeaderValue) -> Self {
+        let value = match value.try_into_value() {
+            Ok(value) => value,
+            Err(err) => panic!("Invalid header value: {}", err.into()),
+        };
+
+        self.inner_mut().headers.insert(name, value);
+        self
+    }
+
+    fn csp(mut self, name: HeaderName, policy: String) -> Self {
+        let csp = Csp {
+            name,
+            parts: policy.split(NONCE_PLACEHOLDER).map(str::to_owned).collect(),
+        };
+
+        // nonces only contain hex digits, so checking the policy without them is sufficient
+        if let Err(err) = HeaderValue::try_from(csp.parts.concat()) {
+            panic!("Invalid header value: {}", error::HttpError::from(err));
+        }
+
+        self.inner_mut().csp = Some(csp);
+        self
+    }
+
+    fn hsts_mut(&mut self) -> &mut Hsts {
+        self.inner_mut()
+            .hsts
+            .as_mut()
+            .expect("`hsts()` must be called before configuring its directives")
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Rc::get_mut(&mut self.inner)
+            .expect("security headers can not be configured after they have been cloned")
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for SecurityHeaders
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = SecurityHeadersMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SecurityHeadersMiddleware {
+            service,
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+/// Security headers middleware service.
+pub struct SecurityHeadersMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S, B> Service<ServiceRequest> for SecurityHeadersMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = SecurityHeadersFuture<S, B>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let secure = req.connection_info().scheme() == "https";
+
+        let nonce = match &self.inner.csp {
+            Some(csp) if csp.uses_nonce() => {
+                let nonce = CspNonce::generate();
+                req.extensions_mut().insert(nonce.clone());
+                Some(nonce)
+            }
+            _ => None,
+        };
+
+        SecurityHeadersFuture {
+            fut: self.service.call(req),
+            inner: Rc::clone(&self.inner),
+            secure,
+            nonce,
+            _body: PhantomData,
+        }
+    }
+}
+
+pin_project! {
+    pub struct SecurityHeadersFuture<S: Service<ServiceRequest>, B> {
+        #[pin]
+        fut: S::Future,
+        inner: Rc<Inner>,
+        secure: bool,
+        nonce: Option<CspNonce>,
+        _body: PhantomData<B>,
+    }
+}
+
+impl<S, B> Future for SecurityHeadersFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = <S::Future as Future>::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut res = ready!(this.fut.poll(cx))?;
+        let headers = res.headers_mut();
+
+        for (name, value) in &this.inner.headers {
+            if !headers.contains_key(name) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+
+        if let Some(hsts) = &this.inner.hsts {
+            if *this.secure && !headers.contains_key(STRICT_TRANSPORT_SECURITY) {
+                headers.insert(STRICT_TRANSPORT_SECURITY, hsts.header_value());
+            }
+        }
+
+        if let Some(csp) = &this.inner.csp {
+            if !headers.contains_key(&csp.name) {
+                headers.insert(csp.name.clone(), csp.header_value(this.nonce.as_ref()));
+            }
+        }
+
+        Poll::Ready(Ok(res))
+    }
+}
+
+/// Nonce of the content security policy set by [`SecurityHeaders`] for the current request.
+///
+/// Only available when the policy includes the `{nonce}` placeholder. Otherwise, extraction fails
+/// with a `500 Internal Server Error` response.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, middleware::CspNonce, HttpResponse};
+///
+/// #[get("/")]
+/// async fn index(nonce: CspNonce) -> HttpResponse {
+///     HttpResponse::Ok()
+///         .content_type("text/html")
+///         .body(format!("<style nonce=\"{nonce}\">body {{ margin: 0 }}</style>"))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspNonce(Rc<str>);
+
+impl CspNonce {
+    /// Generates a nonce from 128 random bits.
+    fn generate() -> Self {
+        let bytes = rand::random::<[u8; 16]>();
+
+        let nonce = bytes
+            .iter()
+            .fold(String::with_capacity(32), |mut nonce, byte| {
+                use std::fmt::Write as _;
+                let _ = write!(nonce, "{byte:02x}");
+                nonce
+            });
+
+        Self(nonce.into())
+    }
+
+    /// Returns the nonce as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CspNonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromRequest for CspNonce {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        match req.extensions().get::<CspNonce>() {
+            Some(nonce) => ok(nonce.clone()),
+            None => {
+                log::debug!(
+                    "Failed to extract `CspNonce` for `{}` handler. Wrap the handler with \
+                    `SecurityHeaders` using a content security policy that includes `{{nonce}}`.",
+                    req.match_name().unwrap_or_else(|| req.path())
+                );
+
+                err(error::ErrorInternalServerError(
+                    "Content security policy nonce is not configured correctly. \
+                    View/enable debug logs for more details.",
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    #[actix_rt::test]
+    async fn recommended_headers() {
+        let mw = SecurityHeaders::recommended()
+            .new_transform(test::ok_service())
+            .await
+            .unwrap();
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        let headers = res.headers();
+        assert_eq!(headers.get(X_CONTENT_TYPE_OPTIONS).unwrap(), "nosniff");
+        assert_eq!(
+            headers.get(REFERRER_POLICY).unwrap(),
+            "strict-origin-when-cross-origin"
+        );
+        assert_eq!(
+            headers.get(CROSS_ORIGIN_OPENER_POLICY).unwrap(),
+            "same-origin"
+        );
+
+        // not sent over plain HTTP
+        assert!(!headers.contains_key(STRICT_TRANSPORT_SECURITY));
+
+        let req = TestRequest::default()
+            .insert_header(("x-forwarded-proto", "https"))
+            .to_srv_request();
+        let res = mw.call(req).await.unwrap();
+        assert_eq!(
+            res.headers().get(STRICT_TRANSPORT_SECURITY).unwrap(),
+            "max-age=31536000"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn hsts_directives() {
+        let mw = SecurityHeaders::new()
+            .hsts(Duration::from_secs(60))
+            .hsts_include_subdomains()
+            .hsts_preload()
+            .new_transform(test::ok_service())
+            .await
+            .unwrap();
+
+        let req = TestRequest::with_uri("https://actix.rs/").to_srv_request();
+        let res = mw.call(req).await.unwrap();
+        assert_eq!(
+            res.headers().get(STRICT_TRANSPORT_SECURITY).unwrap(),
+            "max-age=60; includeSubDomains; preload"
+        );
+        assert!(!res.headers().contains_key(X_CONTENT_TYPE_OPTIONS));
+    }
+
+    #[actix_rt::test]
+    async fn keeps_response_headers() {
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    SecurityHeaders::new()
+                        .referrer_policy("no-referrer")
+                        .content_security_policy("default-src 'self'")
+                        .permissions_policy("camera=()"),
+                )
+                .route(
+                    "/",
+                    web::get().to(|| {
+                        HttpResponse::Ok()
+                            .insert_header((REFERRER_POLICY, "origin"))
+                            .insert_header((CONTENT_SECURITY_POLICY, "default-src *"))
+                            .finish()
+                    }),
+                ),
+        )
+        .await;
+
+        let res = test::call_service(&app, TestRequest::default().to_request()).await;
+        let headers = res.headers();
+        assert_eq!(headers.get(REFERRER_POLICY).unwrap(), "origin");
+        assert_eq!(
+            headers.get(CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src *"
+        );
+        assert_eq!(headers.get(PERMISSIONS_POLICY).unwrap(), "camera=()");
+    }
+
+    #[actix_rt::test]
+    async fn csp_nonce() {
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    SecurityHeaders::new()
+                        .content_security_policy_report_only("script-src 'nonce-{nonce}'"),
+                )
+                .route(
+                    "/",
+                    web::get().to(|nonce: CspNonce| async move { nonce.to_string() }),
+                ),
+        )
+        .await;
+
+        let mut nonces = Vec::new();
+
+        for _ in 0..2 {
+            let res = test::call_service(&app, TestRequest::default().to_request()).await;
+            let policy = res
+                .headers()
+                .get(CONTENT_SECURITY_POLICY_REPORT_ONLY)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_owned();
+            let nonce = test::read_body(res).await;
+
+            assert_eq!(nonce.len(), 32);
+            assert_eq!(
+                policy,
+                format!(
+                    "script-src 'nonce-{}'",
+                    std::str::from_utf8(&nonce).unwrap()
+                )
+            );
+
+            nonces.push(nonce);
+        }
+
+        assert_ne!(nonces[0], nonces[1]);
+    }
+
+    #[actix_rt::test]
+    async fn csp_nonce_without_placeholder() {
+        let app = test::init_service(
+            App::new()
+                .wrap(SecurityHeaders::new().content_security_policy("default-src 'self'"))
+                .route(
+                    "/",
+                    web::get().to(|nonce: CspNonce| async move { nonce.to_string() }),
+                ),
+        )
+        .await;
+
+        let res = test::call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    #[should_panic]
+    fn hsts_directive_without_max_age() {
+        let _ = SecurityHeaders::new().hsts_preload();
+    }
+}
@@ -0,0 +1,519 @@
+//! For middleware documentation, see [`SlowRequestLog`].
+
+use std::{
+    cell::Cell,
+    future::Future,
+    marker::PhantomData,
+    net::SocketAddr,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use actix_service::{Service, Transform};
+use actix_utils::future::{ready, Ready};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+use crate::{
+    http::{Method, StatusCode, Uri},
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpMessage as _,
+};
+
+type SlowRequestHook = dyn Fn(&SlowRequest);
+
+/// Middleware for logging requests that take longer than a threshold to be handled.
+///
+/// Requests whose response takes longer than the threshold to be produced are logged at the warn
+/// level, with their method, matched route pattern (or path, for requests that did not match a
+/// resource), peer address, response status, and a breakdown of the time spent:
+/// - _queue_ time, from this middleware receiving the request until it reaches the
+///   [`HandlerTimer`], such as time spent waiting in a
+///   [`ConcurrencyLimit`](super::ConcurrencyLimit) queue or in other middleware registered
+///   between the two;
+/// - _handler_ time, from the request reaching the [`HandlerTimer`] until the response is
+///   produced.
+///
+/// The time is only broken down for requests passing through a [`HandlerTimer`], which is
+/// registered separately. Time spent streaming the response body is not included. Errors returned
+/// by inner services, instead of responses, are not recorded.
+///
+/// A [hook](Self::on_slow_request()) can additionally be set to report slow requests elsewhere,
+/// such as to a metrics registry.
+///
+/// For accurate queue times, register this middleware last so that it wraps all other middleware,
+/// and the [`HandlerTimer`] first so that it is wrapped by all other middleware.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{
+///     middleware::{HandlerTimer, SlowRequestLog},
+///     web, App,
+/// };
+///
+/// let app = App::new()
+///     .wrap(HandlerTimer)
+///     .wrap(
+///         SlowRequestLog::new(Duration::from_millis(500)).on_slow_request(|req| {
+///             eprintln!("slow route: {}", req.route().unwrap_or("unmatched"));
+///         }),
+///     )
+///     .route("/", web::get().to(|| async { "hello" }));
+/// ```
+#[derive(Clone)]
+pub struct SlowRequestLog {
+    threshold: Duration,
+    log: bool,
+    hook: Option<Rc<SlowRequestHook>>,
+}
+
+impl SlowRequestLog {
+    /// Constructs a middleware that logs requests taking longer than `threshold`.
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            log: true,
+            hook: None,
+        }
+    }
+
+    /// Sets a hook that is called with each slow request, in addition to logging it.
+    pub fn on_slow_request<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&SlowRequest) + 'static,
+    {
+        self.hook = Some(Rc::new(hook));
+        self
+    }
+
+    /// Disables logging of slow requests, so that they are only reported to the hook.
+    pub fn without_logging(mut self) -> Self {
+        self.log = false;
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for SlowRequestLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = SlowRequestLogMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SlowRequestLogMiddleware {
+            service,
+            config: self.clone(),
+        }))
+    }
+}
+
+/// Slow request logging middleware service.
+pub struct SlowRequestLogMiddleware<S> {
+    service: S,
+    config: SlowRequestLog,
+}
+
+impl<S, B> Service<ServiceRequest> for SlowRequestLogMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = SlowRequestLogFuture<S, B>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        // nested instances share the handler start time
+        let handler_start = req.extensions().get::<HandlerStart>().cloned();
+        let handler_start = handler_start.unwrap_or_else(|| {
+            let handler_start = HandlerStart::default();
+            req.extensions_mut().insert(handler_start.clone());
+            handler_start
+        });
+
+        SlowRequestLogFuture {
+            fut: self.service.call(req),
+            start: Instant::now(),
+            handler_start,
+            config: self.config.clone(),
+            _body: PhantomData,
+        }
+    }
+}
+
+pin_project! {
+    pub struct SlowRequestLogFuture<S: Service<ServiceRequest>, B> {
+        #[pin]
+        fut: S::Future,
+        start: Instant,
+        handler_start: HandlerStart,
+        config: SlowRequestLog,
+        _body: PhantomData<B>,
+    }
+}
+
+impl<S, B> Future for SlowRequestLogFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = Result<ServiceResponse<B>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let res = ready!(this.fut.poll(cx))?;
+
+        let end = Instant::now();
+        let duration = end.duration_since(*this.start);
+
+        if duration > this.config.threshold {
+            let req = res.request();
+
+            let handler_start = this.handler_start.0.get();
+            let queue_duration = handler_start.map(|at| at.saturating_duration_since(*this.start));
+
+            let slow = SlowRequest {
+                method: req.method().clone(),
+                uri: req.uri().clone(),
+                route: req.match_pattern(),
+                peer_addr: req.peer_addr(),
+                status: res.status(),
+                duration,
+                queue_duration,
+                handler_duration: end
+                    .saturating_duration_since(handler_start.unwrap_or(*this.start)),
+            };
+
+            if this.config.log {
+                log::warn!("{slow}");
+            }
+
+            if let Some(hook) = &this.config.hook {
+                hook(&slow);
+            }
+        }
+
+        Poll::Ready(Ok(res))
+    }
+}
+
+/// Time at which a request reached the [`HandlerTimer`].
+///
+/// Inserted into request extensions by [`SlowRequestLog`].
+#[derive(Clone, Default)]
+struct HandlerStart(Rc<Cell<Option<Instant>>>);
+
+/// Middleware marking where the handler time of requests timed by [`SlowRequestLog`] begins.
+///
+/// Time before a request reaches this middleware is reported as queue time and time after it as
+/// handler time. Register it first, on the app or on a scope or resource, so that it is wrapped by
+/// all other middleware and the handler time only covers routing and the route handler. When
+/// nested, the innermost instance a request passes through marks the start of the handler time.
+///
+/// Requests that are not timed by a [`SlowRequestLog`] pass through unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HandlerTimer;
+
+impl<S, B> Transform<S, ServiceRequest> for HandlerTimer
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = HandlerTimerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(HandlerTimerMiddleware { service }))
+    }
+}
+
+/// Handler timer middleware service.
+pub struct HandlerTimerMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for HandlerTimerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = S::Future;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if let Some(start) = req.extensions().get::<HandlerStart>() {
+            start.0.set(Some(Instant::now()));
+        }
+
+        self.service.call(req)
+    }
+}
+
+/// Information about a request that took longer than the threshold of a [`SlowRequestLog`].
+#[derive(Debug, Clone)]
+pub struct SlowRequest {
+    method: Method,
+    uri: Uri,
+    route: Option<String>,
+    peer_addr: Option<SocketAddr>,
+    status: StatusCode,
+    duration: Duration,
+    queue_duration: Option<Duration>,
+    handler_duration: Duration,
+}
+
+impl SlowRequest {
+    /// Returns the request method.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the request URI.
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// Returns the pattern of the matched resource, such as `/users/{id}`.
+    ///
+    /// Returns `None` if the request did not match a resource.
+    pub fn route(&self) -> Option<&str> {
+        self.route.as_deref()
+    }
+
+    /// Returns the peer address of the request.
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+
+    /// Returns the response status.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the total time taken to produce the response.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the time taken for the request to reach the [`HandlerTimer`].
+    ///
+    /// Returns `None` if the request did not pass through a [`HandlerTimer`], such as when it was
+    /// answered by a middleware wrapping the timer.
+    pub fn queue_duration(&self) -> Option<Duration> {
+        self.queue_duration
+    }
+
+    /// Returns the time taken to produce the response after the request passed through the
+    /// [`HandlerTimer`].
+    ///
+    /// If the request did not pass through a [`HandlerTimer`], this is the total duration.
+    pub fn handler_duration(&self) -> Duration {
+        self.handler_duration
+    }
+}
+
+impl std::fmt::Display for SlowRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Slow request: {} {}",
+            self.method,
+            self.route.as_deref().unwrap_or_else(|| self.uri.path())
+        )?;
+
+        if let Some(peer_addr) = self.peer_addr {
+            write!(f, " from {peer_addr}")?;
+        }
+
+        write!(
+            f,
+            " responded {} after {:?} (",
+            self.status.as_u16(),
+            self.duration
+        )?;
+
+        if let Some(queue_duration) = self.queue_duration {
+            write!(f, "queue {queue_duration:?}, ")?;
+        }
+
+        write!(f, "handler {:?})", self.handler_duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{
+        middleware::ConcurrencyLimit,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    /// Responds with `res` after 30ms.
+    async fn respond_slowly(res: HttpResponse) -> HttpResponse {
+        actix_rt::time::sleep(Duration::from_millis(30)).await;
+        res
+    }
+
+    #[actix_rt::test]
+    async fn reports_slow_requests() {
+        let slow = Rc::new(RefCell::new(Vec::new()));
+
+        let app = test::init_service(
+            App::new()
+                .wrap(HandlerTimer)
+                .wrap(
+                    SlowRequestLog::new(Duration::from_millis(20))
+                        .without_logging()
+                        .on_slow_request({
+                            let slow = Rc::clone(&slow);
+                            move |req| slow.borrow_mut().push(req.clone())
+                        }),
+                )
+                .route("/fast", web::get().to(HttpResponse::Ok))
+                .route(
+                    "/slow/{id}",
+                    web::get().to(|| respond_slowly(HttpResponse::Accepted().finish())),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/fast").to_request();
+        test::call_service(&app, req).await;
+        assert!(slow.borrow().is_empty());
+
+        let req = TestRequest::with_uri("/slow/42")
+            .peer_addr("127.0.0.1:8080".parse().unwrap())
+            .to_request();
+        test::call_service(&app, req).await;
+
+        let slow = slow.borrow();
+        assert_eq!(slow.len(), 1);
+
+        let req = &slow[0];
+        assert_eq!(req.method(), Method::GET);
+        assert_eq!(req.uri().path(), "/slow/42");
+        assert_eq!(req.route(), Some("/slow/{id}"));
+        assert_eq!(req.status(), StatusCode::ACCEPTED);
+        assert!(req.duration() >= Duration::from_millis(30));
+        assert!(req.handler_duration() >= Duration::from_millis(30));
+        assert!(req.queue_duration().unwrap() < Duration::from_millis(30));
+
+        assert_eq!(
+            req.to_string(),
+            format!(
+                "Slow request: GET /slow/{{id}} from 127.0.0.1:8080 responded 202 after {:?} \
+                (queue {:?}, handler {:?})",
+                req.duration(),
+                req.queue_duration().unwrap(),
+                req.handler_duration(),
+            )
+        );
+    }
+
+    #[actix_rt::test]
+    async fn measures_queue_time() {
+        let slow = Rc::new(RefCell::new(Vec::new()));
+
+        let app = test::init_service(
+            App::new()
+                .wrap(HandlerTimer)
+                .wrap(ConcurrencyLimit::new(1).queue_size(1))
+                .wrap(
+                    SlowRequestLog::new(Duration::from_millis(20)).on_slow_request({
+                        let slow = Rc::clone(&slow);
+                        move |req| slow.borrow_mut().push(req.clone())
+                    }),
+                )
+                .route(
+                    "/",
+                    web::get().to(|| respond_slowly(HttpResponse::Ok().finish())),
+                ),
+        )
+        .await;
+
+        let first = test::call_service(&app, TestRequest::default().to_request());
+        let second = test::call_service(&app, TestRequest::default().to_request());
+        futures_util::future::join(first, second).await;
+
+        let slow = slow.borrow();
+        assert_eq!(slow.len(), 2);
+
+        // the second request waited for the first one to be handled
+        let queued = slow
+            .iter()
+            .find(|req| req.queue_duration().unwrap() >= Duration::from_millis(30))
+            .unwrap();
+        assert!(queued.handler_duration() >= Duration::from_millis(30));
+        assert!(queued.duration() >= Duration::from_millis(60));
+    }
+
+    #[actix_rt::test]
+    async fn unmatched_requests() {
+        let slow = Rc::new(RefCell::new(Vec::new()));
+
+        let app = test::init_service(
+            App::new()
+                .wrap(HandlerTimer)
+                .wrap(crate::middleware::from_fn(|req: ServiceRequest, _| async {
+                    actix_rt::time::sleep(Duration::from_millis(1)).await;
+                    Ok::<_, Error>(req.into_response(HttpResponse::NotFound()))
+                }))
+                .wrap(SlowRequestLog::new(Duration::ZERO).on_slow_request({
+                    let slow = Rc::clone(&slow);
+                    move |req| slow.borrow_mut().push(req.clone())
+                })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/missing").to_request();
+        test::call_service(&app, req).await;
+
+        let slow = slow.borrow();
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].route(), None);
+        assert_eq!(slow[0].queue_duration(), None);
+        assert_eq!(slow[0].handler_duration(), slow[0].duration());
+        assert!(slow[0]
+            .to_string()
+            .starts_with("Slow request: GET /missing responded 404"));
+    }
+
+    #[actix_rt::test]
+    async fn without_handler_timer() {
+        let slow = Rc::new(RefCell::new(Vec::new()));
+
+        let app = test::init_service(
+            App::new()
+                .wrap(SlowRequestLog::new(Duration::ZERO).on_slow_request({
+                    let slow = Rc::clone(&slow);
+                    move |req| slow.borrow_mut().push(req.clone())
+                }))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        test::call_service(&app, TestRequest::default().to_request()).await;
+
+        let slow = slow.borrow();
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].route(), Some("/"));
+        assert_eq!(slow[0].queue_duration(), None);
+        assert_eq!(slow[0].handler_duration(), slow[0].duration());
+    }
+}
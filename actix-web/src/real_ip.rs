@@ -0,0 +1,439 @@
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+use actix_utils::future::{ready, Ready};
+use derive_more::derive::Display;
+
+use crate::{
+    dev::Payload,
+    error::RealIpError,
+    http::header::{self, HeaderName},
+    info::unquote,
+    web, FromRequest, HttpRequest,
+};
+
+static X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// Extractor for the IP address of the client, resolved through trusted proxies.
+///
+/// Unlike [`ConnectionInfo::realip_remote_addr()`], forwarding headers are only used when the
+/// request was received from a proxy that has been [trusted](RealIpConfig::trusted_proxy), so
+/// that clients cannot spoof their address by sending those headers themselves.
+///
+/// Addresses added by proxies are read from right to left, starting at the peer address of the
+/// connection. The first address that is not a trusted proxy is the client's. If all of them are
+/// trusted, the left-most address is used. See [`RealIpSource`] for the supported sources of
+/// forwarded addresses.
+///
+/// By default, no proxies are trusted and the peer address is used.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web, App};
+///
+/// #[get("/")]
+/// async fn index(ip: web::RealIp) -> String {
+///     format!("Your IP address is {ip}")
+/// }
+///
+/// let app = App::new()
+///     .app_data(
+///         web::RealIpConfig::default()
+///             .trusted_proxy("10.0.0.0/8")
+///             .trusted_proxy("fd00::/8")
+///             .source(web::RealIpSource::XForwardedFor),
+///     )
+///     .service(index);
+/// ```
+///
+/// [`ConnectionInfo::realip_remote_addr()`]: crate::dev::ConnectionInfo::realip_remote_addr()
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Display)]
+#[display("{}", _0)]
+pub struct RealIp(pub IpAddr);
+
+impl RealIp {
+    /// Unwrap into inner `IpAddr` value.
+    pub fn into_inner(self) -> IpAddr {
+        self.0
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl FromRequest for RealIp {
+    type Error = RealIpError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let res = RealIpConfig::from_req(req).resolve(req).map(RealIp);
+
+        if let Err(err) = &res {
+            log::debug!(
+                "Failed to resolve client IP address: {err}. Request path: {:?}",
+                req.path()
+            );
+        }
+
+        ready(res)
+    }
+}
+
+/// Source of the addresses that trusted proxies forward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum RealIpSource {
+    /// The `for` parameters of [`Forwarded`](header::FORWARDED) headers, as defined by
+    /// [RFC 7239](https://datatracker.ietf.org/doc/html/rfc7239).
+    #[default]
+    Forwarded,
+
+    /// The `X-Forwarded-For` header.
+    XForwardedFor,
+
+    /// The source address of a PROXY protocol header, stored as a [`ProxyProtocolAddr`] in the
+    /// connection's data.
+    ProxyProtocol,
+}
+
+/// Source address of a connection, as given in its PROXY protocol header.
+///
+/// Actix Web does not parse PROXY protocol headers. Listeners that do can make the source address
+/// available to [`RealIp`] by inserting it into the connection's data, using
+/// [`Connection::insert()`](crate::dev::Connection::insert()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+#[display("{}", _0)]
+pub struct ProxyProtocolAddr(pub SocketAddr);
+
+/// [`RealIp`] extractor configuration.
+#[derive(Debug, Clone, Default)]
+pub struct RealIpConfig {
+    trusted_proxies: Vec<Cidr>,
+    source: RealIpSource,
+}
+
+impl RealIpConfig {
+    /// Trusts proxies with an address in the given range, in CIDR notation (eg. `10.0.0.0/8`).
+    ///
+    /// Single addresses, such as `127.0.0.1`, are also accepted.
+    ///
+    /// # Panics
+    /// Panics if `cidr` is not a valid IP address or CIDR range.
+    pub fn trusted_proxy(mut self, cidr: &str) -> Self {
+        let cidr = cidr
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid trusted proxy CIDR: {cidr:?}"));
+
+        self.trusted_proxies.push(cidr);
+        self
+    }
+
+    /// Sets the source of forwarded addresses. By default, the `Forwarded` header is used.
+    pub fn source(mut self, source: RealIpSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Extract config from app data. Check both `T` and `Data<T>`, in that order, and fall back to
+    /// the default config.
+    pub(crate) fn from_req(req: &HttpRequest) -> &Self {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .unwrap_or(&DEFAULT_CONFIG)
+    }
+
+    pub(crate) fn is_trusted(&self, addr: &IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|cidr| cidr.contains(addr))
+    }
+
+    /// Returns true if `val` is a forwarded address, such as a `for` parameter of a `Forwarded`
+    /// header, of a trusted proxy.
+    pub(crate) fn is_trusted_forwarded_addr(&self, val: &str) -> bool {
+        parse_addr(val).is_some_and(|addr| self.is_trusted(&addr))
+    }
+
+    pub(crate) fn resolve(&self, req: &HttpRequest) -> Result<IpAddr, RealIpError> {
+        let mut client = req.peer_addr().ok_or(RealIpError::MissingPeerAddr)?.ip();
+
+        if !self.is_trusted(&client) {
+            return Ok(client);
+        }
+
+        let forwarded: Vec<&str> = match self.source {
+            RealIpSource::Forwarded => req
+                .headers()
+                .get_all(header::FORWARDED)
+                .filter_map(|hdr| hdr.to_str().ok())
+                .flat_map(|val| val.split([',', ';']))
+                .filter_map(|pair| {
+                    let (name, val) = pair.split_once('=')?;
+                    name.trim().eq_ignore_ascii_case("for").then_some(val)
+                })
+                .collect(),
+
+            RealIpSource::XForwardedFor => req
+                .headers()
+                .get_all(&X_FORWARDED_FOR)
+                .filter_map(|hdr| hdr.to_str().ok())
+                .flat_map(|val| val.split(','))
+                .collect(),
+
+            RealIpSource::ProxyProtocol => {
+                return Ok(req
+                    .conn_data::<ProxyProtocolAddr>()
+                    .map_or(client, |addr| addr.0.ip()));
+            }
+        };
+
+        // the right-most addresses were added by the proxies closest to this server
+        for addr in forwarded.into_iter().rev() {
+            client = parse_addr(addr).ok_or(RealIpError::InvalidForwardedAddr)?;
+
+            if !self.is_trusted(&client) {
+                break;
+            }
+        }
+
+        Ok(client)
+    }
+}
+
+/// Allow shared refs used as default.
+static DEFAULT_CONFIG: RealIpConfig = RealIpConfig {
+    trusted_proxies: Vec::new(),
+    source: RealIpSource::Forwarded,
+};
+
+/// Parses a forwarded address, which may be quoted and include a port.
+fn parse_addr(val: &str) -> Option<IpAddr> {
+    let val = unquote(val);
+
+    IpAddr::from_str(val)
+        .or_else(|_| SocketAddr::from_str(val).map(|addr| addr.ip()))
+        .or_else(|_| IpAddr::from_str(val.trim_start_matches('[').trim_end_matches(']')))
+        .ok()
+}
+
+/// A range of IP addresses, in CIDR notation.
+///
+/// Used for the trusted proxies of [`RealIpConfig`] and other middleware that match client
+/// addresses, such as [`IpFilter`](crate::middleware::IpFilter).
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Cidr {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    /// Returns true if `addr` is in this range. IPv4-mapped IPv6 addresses match IPv4 ranges.
+    pub(crate) fn contains(&self, addr: &IpAddr) -> bool {
+        fn mask(bits: u128, prefix_len: u8) -> u128 {
+            bits.checked_shr(128 - u32::from(prefix_len)).unwrap_or(0)
+        }
+
+        let (net, addr) = match (self.addr, addr.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => (
+                u128::from(u32::from(net)) << 96,
+                u128::from(u32::from(addr)) << 96,
+            ),
+            (IpAddr::V6(net), IpAddr::V6(addr)) => (u128::from(net), u128::from(addr)),
+            _ => return false,
+        };
+
+        mask(net, self.prefix_len) == mask(addr, self.prefix_len)
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = ();
+
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match val.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (val, None),
+        };
+
+        let addr = IpAddr::from_str(addr).map_err(|_| ())?;
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len.parse().map_err(|_| ())?,
+            None => max_len,
+        };
+
+        if prefix_len > max_len {
+            return Err(());
+        }
+
+        // addresses are canonicalized when matched, so IPv4-mapped ranges must be too
+        match addr.to_canonical() {
+            IpAddr::V4(v4) if addr.is_ipv6() && prefix_len >= 96 => Ok(Self {
+                addr: IpAddr::V4(v4),
+                prefix_len: prefix_len - 96,
+            }),
+            _ => Ok(Self { addr, prefix_len }),
+        }
+    }
+}
+
+impl fmt::Debug for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{dev::Extensions, test::TestRequest};
+
+    fn peer(addr: &str) -> TestRequest {
+        TestRequest::default().peer_addr(SocketAddr::new(addr.parse().unwrap(), 8080))
+    }
+
+    async fn real_ip(req: TestRequest) -> Result<IpAddr, RealIpError> {
+        RealIp::extract(&req.to_http_request())
+            .await
+            .map(RealIp::into_inner)
+    }
+
+    fn ip(addr: &str) -> IpAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn cidr() {
+        let cidr = Cidr::from_str("10.1.0.0/16").unwrap();
+        assert!(cidr.contains(&ip("10.1.2.3")));
+        assert!(cidr.contains(&ip("::ffff:10.1.2.3")));
+        assert!(!cidr.contains(&ip("10.2.0.1")));
+        assert!(!cidr.contains(&ip("fd00::1")));
+
+        let cidr = Cidr::from_str("fd00::/8").unwrap();
+        assert!(cidr.contains(&ip("fd12::1")));
+        assert!(!cidr.contains(&ip("fe80::1")));
+
+        assert!(Cidr::from_str("0.0.0.0/0")
+            .unwrap()
+            .contains(&ip("1.2.3.4")));
+        assert!(Cidr::from_str("::1").unwrap().contains(&ip("::1")));
+        assert!(Cidr::from_str("::ffff:10.0.0.0/104")
+            .unwrap()
+            .contains(&ip("10.1.2.3")));
+        assert!(!Cidr::from_str("127.0.0.1")
+            .unwrap()
+            .contains(&ip("127.0.0.2")));
+
+        assert!(Cidr::from_str("10.0.0.0/33").is_err());
+        assert!(Cidr::from_str("10.0.0/8").is_err());
+        assert!(Cidr::from_str("localhost").is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_trusted_proxy() {
+        let _ = RealIpConfig::default().trusted_proxy("10.0.0.0/40");
+    }
+
+    #[actix_rt::test]
+    async fn untrusted_peer() {
+        assert!(matches!(
+            real_ip(TestRequest::default()).await,
+            Err(RealIpError::MissingPeerAddr)
+        ));
+
+        // headers are ignored unless the peer is trusted
+        let req = peer("192.0.2.60")
+            .insert_header((header::FORWARDED, "for=198.51.100.17"))
+            .insert_header((X_FORWARDED_FOR.clone(), "198.51.100.17"));
+        assert_eq!(real_ip(req).await.unwrap(), ip("192.0.2.60"));
+
+        let req = peer("192.0.2.60")
+            .insert_header((header::FORWARDED, "for=198.51.100.17"))
+            .app_data(RealIpConfig::default().trusted_proxy("10.0.0.0/8"));
+        assert_eq!(real_ip(req).await.unwrap(), ip("192.0.2.60"));
+    }
+
+    #[actix_rt::test]
+    async fn forwarded() {
+        let cfg = RealIpConfig::default()
+            .trusted_proxy("10.0.0.0/8")
+            .trusted_proxy("fd00::/8");
+
+        // spoofed left-most address is skipped
+        let req = peer("10.0.0.1")
+            .insert_header((
+                header::FORWARDED,
+                r#"for=1.1.1.1, for="[2001:db8:cafe::17]:4711";proto=https, For=10.0.0.2"#,
+            ))
+            .app_data(cfg.clone());
+        assert_eq!(real_ip(req).await.unwrap(), ip("2001:db8:cafe::17"));
+
+        let req = peer("fd00::1")
+            .append_header((header::FORWARDED, "for=192.0.2.60:8080"))
+            .append_header((header::FORWARDED, "for=10.0.0.2"))
+            .app_data(cfg.clone());
+        assert_eq!(real_ip(req).await.unwrap(), ip("192.0.2.60"));
+
+        // all addresses are trusted
+        let req = peer("10.0.0.1")
+            .insert_header((header::FORWARDED, "for=10.0.0.3, for=10.0.0.2"))
+            .app_data(cfg.clone());
+        assert_eq!(real_ip(req).await.unwrap(), ip("10.0.0.3"));
+
+        // no forwarded addresses
+        let req = peer("10.0.0.1").app_data(cfg.clone());
+        assert_eq!(real_ip(req).await.unwrap(), ip("10.0.0.1"));
+
+        let req = peer("10.0.0.1")
+            .insert_header((header::FORWARDED, "for=unknown, for=10.0.0.2"))
+            .app_data(cfg.clone());
+        assert!(matches!(
+            real_ip(req).await,
+            Err(RealIpError::InvalidForwardedAddr)
+        ));
+
+        // addresses left of the client are not parsed
+        let req = peer("10.0.0.1")
+            .insert_header((header::FORWARDED, "for=unknown, for=192.0.2.60"))
+            .app_data(cfg);
+        assert_eq!(real_ip(req).await.unwrap(), ip("192.0.2.60"));
+    }
+
+    #[actix_rt::test]
+    async fn x_forwarded_for() {
+        let cfg = RealIpConfig::default()
+            .trusted_proxy("10.0.0.0/8")
+            .source(RealIpSource::XForwardedFor);
+
+        let req = peer("10.0.0.1")
+            .insert_header((X_FORWARDED_FOR.clone(), "1.1.1.1, 2001:db8::17, 10.0.0.2"))
+            .insert_header((header::FORWARDED, "for=1.1.1.1"))
+            .app_data(web::Data::new(cfg));
+        assert_eq!(real_ip(req).await.unwrap(), ip("2001:db8::17"));
+    }
+
+    #[actix_rt::test]
+    async fn proxy_protocol() {
+        let cfg = RealIpConfig::default()
+            .trusted_proxy("10.0.0.1")
+            .source(RealIpSource::ProxyProtocol);
+
+        let mut extensions = Extensions::new();
+        extensions.insert(ProxyProtocolAddr("192.0.2.60:1234".parse().unwrap()));
+
+        let mut req = peer("10.0.0.1")
+            .insert_header((header::FORWARDED, "for=1.1.1.1"))
+            .app_data(cfg.clone())
+            .to_http_request();
+        Rc::get_mut(&mut req.inner).unwrap().conn_data = Some(Rc::new(extensions));
+        let client = RealIp::extract(&req).await.unwrap();
+        assert_eq!(client.to_string(), "192.0.2.60");
+
+        let req = peer("10.0.0.1").app_data(cfg);
+        assert_eq!(real_ip(req).await.unwrap(), ip("10.0.0.1"));
+    }
+}
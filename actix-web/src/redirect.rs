@@ -40,14 +40,41 @@ use crate::{
 /// # actix_web::web::to(handler);
 /// ```
 ///
+/// Redirect to a named resource, resolved using [`HttpRequest::url_for()`]:
+/// ```
+/// use actix_web::{web, web::Redirect, App, Responder};
+///
+/// async fn create_post() -> impl Responder {
+///     // sends a 303 redirect to the new post, e.g. "http://localhost:8080/posts/123"
+///     Redirect::to_named("post_detail", ["123"]).see_other()
+/// }
+///
+/// App::new()
+///     .service(web::resource("/posts/{id}").name("post_detail"))
+///     .route("/posts", web::post().to(create_post));
+/// ```
+///
 /// [mdn-redirects]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Redirections#temporary_redirections
 #[derive(Debug, Clone)]
 pub struct Redirect {
     from: Cow<'static, str>,
-    to: Cow<'static, str>,
+    to: Target,
     status_code: StatusCode,
 }
 
+/// Location that a [`Redirect`] sends clients to.
+#[derive(Debug, Clone)]
+enum Target {
+    /// Path or URL, used verbatim.
+    Location(Cow<'static, str>),
+
+    /// Named resource, resolved when responding.
+    Named {
+        name: Cow<'static, str>,
+        elements: Vec<String>,
+    },
+}
+
 impl Redirect {
     /// Construct a new `Redirect` service that matches a path.
     ///
@@ -73,7 +100,7 @@ impl Redirect {
     pub fn new(from: impl Into<Cow<'static, str>>, to: impl Into<Cow<'static, str>>) -> Self {
         Self {
             from: from.into(),
-            to: to.into(),
+            to: Target::Location(to.into()),
             status_code: StatusCode::TEMPORARY_REDIRECT,
         }
     }
@@ -95,11 +122,75 @@ impl Redirect {
     pub fn to(to: impl Into<Cow<'static, str>>) -> Self {
         Self {
             from: "/".into(),
-            to: to.into(),
+            to: Target::Location(to.into()),
+            status_code: StatusCode::TEMPORARY_REDIRECT,
+        }
+    }
+
+    /// Construct a new `Redirect` to a named resource, to use as a responder.
+    ///
+    /// The location is generated when responding, using [`HttpRequest::url_for()`] with the given
+    /// `name` and dynamic segment `elements`. If the URL can not be generated, because no resource
+    /// has that name or the elements do not match its pattern, the error is logged and a
+    /// "500 Internal Server Error" response is sent instead.
+    ///
+    /// Can also be used as a service, by setting the path to match using
+    /// [`from()`](Self::from).
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{web::Redirect, Responder};
+    ///
+    /// async fn old_user_page(user_id: String) -> impl Responder {
+    ///     // sends a permanent 308 redirect to the "user" resource, e.g. "/users/123"
+    ///     Redirect::to_named("user", [user_id]).permanent()
+    /// }
+    /// ```
+    pub fn to_named<U, I>(name: impl Into<Cow<'static, str>>, elements: U) -> Self
+    where
+        U: IntoIterator<Item = I>,
+        I: AsRef<str>,
+    {
+        Self {
+            from: "/".into(),
+            to: Target::Named {
+                name: name.into(),
+                elements: elements
+                    .into_iter()
+                    .map(|el| el.as_ref().to_owned())
+                    .collect(),
+            },
             status_code: StatusCode::TEMPORARY_REDIRECT,
         }
     }
 
+    /// Construct a new `Redirect` to a named resource without dynamic segments, to use as a
+    /// responder.
+    ///
+    /// This method is similar to [`Redirect::to_named()`] but it can be used for resources that
+    /// do not contain variable parts.
+    pub fn to_named_static(name: impl Into<Cow<'static, str>>) -> Self {
+        const NO_PARAMS: [&str; 0] = [];
+        Self::to_named(name, NO_PARAMS)
+    }
+
+    /// Sets the path that this `Redirect` matches when used as a service.
+    ///
+    /// See [`Redirect::new()`] for details on how `from` is matched.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_web::{web, web::Redirect, App};
+    /// App::new()
+    ///     .service(web::resource("/docs/latest").name("docs"))
+    ///     // redirects "/help" to the "docs" resource
+    ///     .service(Redirect::to_named_static("docs").from("/help"));
+    /// ```
+    pub fn from(mut self, from: impl Into<Cow<'static, str>>) -> Self {
+        self.from = from.into();
+        self
+    }
+
     /// Use the "308 Permanent Redirect" status when responding.
     ///
     /// See [this MDN article][mdn-redirects] on why 308 is preferred over 301.
@@ -121,6 +212,18 @@ impl Redirect {
     /// Use the "303 See Other" status when responding.
     ///
     /// This status code is semantically correct as the response to a successful login, for example.
+    /// It is also the status to use when redirecting after a form submission, since clients follow
+    /// it using a `GET` request, which avoids re-submitting the form when the page is reloaded.
+    ///
+    /// ```
+    /// use actix_web::{web, web::Redirect, Responder};
+    ///
+    /// async fn submit_comment(form: web::Form<Vec<(String, String)>>) -> impl Responder {
+    ///     // ... store comment
+    ///     Redirect::to_named_static("comments").see_other()
+    /// }
+    /// # actix_web::web::to(submit_comment);
+    /// ```
     pub fn see_other(self) -> Self {
         self.using_status_code(StatusCode::SEE_OTHER)
     }
@@ -163,15 +266,28 @@ impl HttpServiceFactory for Redirect {
 impl Responder for Redirect {
     type Body = ();
 
-    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let to = match self.to {
+            Target::Location(to) => to,
+            Target::Named { name, elements } => match req.url_for(&name, &elements) {
+                Ok(url) => Cow::Owned(url.into()),
+                Err(err) => {
+                    log::error!(
+                        "redirect target location can not be generated for {name:?}: {err}"
+                    );
+                    return HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, ());
+                }
+            },
+        };
+
         let mut res = HttpResponse::with_body(self.status_code, ());
 
-        if let Ok(hdr_val) = self.to.parse() {
+        if let Ok(hdr_val) = to.parse() {
             res.headers_mut().insert(LOCATION, hdr_val);
         } else {
             log::error!(
                 "redirect target location can not be converted to header value: {:?}",
-                self.to,
+                to,
             );
         }
 
@@ -234,4 +350,41 @@ mod tests {
         let hdr = res.headers().get(&LOCATION).unwrap();
         assert_eq!(hdr.to_str().unwrap(), "https://duck.com");
     }
+
+    #[actix_rt::test]
+    async fn named_redirects() {
+        let svc = test::init_service(
+            App::new()
+                .service(crate::web::resource("/users/{id}").name("user"))
+                .service(crate::web::resource("/login").name("login"))
+                .service(Redirect::to_named_static("login").from("/sign-in"))
+                .route(
+                    "/users",
+                    crate::web::post()
+                        .to(|| async { Redirect::to_named("user", ["42"]).see_other() }),
+                )
+                .route(
+                    "/missing",
+                    crate::web::get().to(|| async { Redirect::to_named_static("missing") }),
+                ),
+        )
+        .await;
+
+        let req = test::TestRequest::post().uri("/users").to_request();
+        let res = svc.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        let hdr = res.headers().get(&LOCATION).unwrap();
+        assert_eq!(hdr.to_str().unwrap(), "http://localhost:8080/users/42");
+
+        let req = test::TestRequest::default().uri("/sign-in").to_request();
+        let res = svc.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::TEMPORARY_REDIRECT);
+        let hdr = res.headers().get(&LOCATION).unwrap();
+        assert_eq!(hdr.to_str().unwrap(), "http://localhost:8080/login");
+
+        let req = test::TestRequest::default().uri("/missing").to_request();
+        let res = svc.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(res.headers().get(&LOCATION).is_none());
+    }
 }
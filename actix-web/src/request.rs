@@ -42,7 +42,7 @@ pub(crate) struct HttpRequestInner {
     pub(crate) app_data: SmallVec<[Rc<Extensions>; 4]>,
     pub(crate) conn_data: Option<Rc<Extensions>>,
     pub(crate) extensions: Rc<RefCell<Extensions>>,
-    app_state: Rc<AppInitServiceState>,
+    pub(crate) app_state: Rc<AppInitServiceState>,
 }
 
 impl HttpRequest {
@@ -202,7 +202,7 @@ impl HttpRequest {
     /// let opt_t = req.conn_data::<PeerCertificate>();
     /// ```
     ///
-    /// [on-connect]: crate::HttpServer::on_connect
+    /// [on-connect]: crate::HttpServer::on_connection
     pub fn conn_data<T: 'static>(&self) -> Option<&T> {
         self.inner
             .conn_data
@@ -308,6 +308,9 @@ impl HttpRequest {
     /// `Scope`'s instance. However, using the same router set up and a request that does not get
     /// captured by the `Scope`, `.app_data::<u32>()` would return the `App`'s instance.
     ///
+    /// Data set with [`App::override_app_data()`](crate::App::override_app_data) takes precedence
+    /// over all other instances of the type.
+    ///
     /// If the state was stored using the [`Data`] wrapper, then it must also be retrieved using
     /// this same type.
     ///
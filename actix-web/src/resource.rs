@@ -438,7 +438,10 @@ where
             default: self.default,
         });
 
-        let resource_data = self.app_data.map(Rc::new);
+        let resource_data = self.app_data.map(|mut data| {
+            config.remove_overridden_data(&mut data);
+            Rc::new(data)
+        });
 
         // wraps endpoint service (including middleware) call and injects app data for this scope
         let endpoint = apply_fn_factory(self.endpoint, move |mut req: ServiceRequest, srv| {
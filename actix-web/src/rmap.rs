@@ -1,18 +1,46 @@
 use std::{
     borrow::Cow,
     cell::RefCell,
-    fmt::Write as _,
+    fmt::{self, Write as _},
     rc::{Rc, Weak},
 };
 
 use actix_router::ResourceDef;
 use foldhash::HashMap as FoldHashMap;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use url::Url;
 
 use crate::{error::UrlGenerationError, request::HttpRequest};
 
 const AVG_PATH_LEN: usize = 24;
 
+/// Characters that are percent-encoded in values of tail segments.
+const TAIL_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Characters that are percent-encoded in values of dynamic segments.
+const SEGMENT: &AsciiSet = &TAIL_SEGMENT.add(b'/');
+
+/// Appends the percent-encoded value of a dynamic segment to a path.
+///
+/// Used by the `url_for` functions generated by the routing macros.
+#[doc(hidden)]
+pub fn push_encoded_segment(path: &mut String, value: &dyn fmt::Display, tail: bool) {
+    let set = if tail { TAIL_SEGMENT } else { SEGMENT };
+    let value = value.to_string();
+
+    path.extend(utf8_percent_encode(&value, set));
+}
+
 #[derive(Clone, Debug)]
 pub struct ResourceMap {
     pattern: ResourceDef,
@@ -238,6 +266,17 @@ impl ResourceMap {
 mod tests {
     use super::*;
 
+    #[test]
+    fn encoded_segments() {
+        let mut path = String::from("/user/");
+        push_encoded_segment(&mut path, &"a b/c%d", false);
+        assert_eq!(path, "/user/a%20b%2Fc%25d");
+
+        let mut path = String::from("/blob/");
+        push_encoded_segment(&mut path, &"main/a b", true);
+        assert_eq!(path, "/blob/main/a%20b");
+    }
+
     #[test]
     fn extract_matched_pattern() {
         let mut root = ResourceMap::new(ResourceDef::root_prefix(""));
@@ -0,0 +1,154 @@
+//! Metadata about handlers declared using routing macros.
+
+use crate::http::StatusCode;
+
+/// Metadata about a route registered by a routing macro, such as [`get`](crate::get).
+///
+/// Routing macros generate a `ROUTES` constant on the handler containing the metadata of each
+/// route it registers, built from the macro's `summary`, `tag`, and `response` attributes and the
+/// handler's doc comments. This is intended for use by external API documentation (e.g., OpenAPI)
+/// generators, so that documentation is derived from the same source as the routes themselves.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web::Json, HttpResponse};
+///
+/// /// Lists all users.
+/// #[get("/users", summary = "List users", tag = "users", response = "200: Json<Vec<String>>")]
+/// async fn list_users() -> Json<Vec<String>> {
+///     Json(vec![])
+/// }
+///
+/// let route = &list_users::ROUTES[0];
+/// assert_eq!(route.path(), "/users");
+/// assert_eq!(route.methods(), ["GET"]);
+/// assert_eq!(route.summary(), Some("List users"));
+/// assert_eq!(route.description(), Some("Lists all users."));
+/// assert_eq!(route.tags(), ["users"]);
+/// assert_eq!(route.responses()[0].status().as_u16(), 200);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RouteMetadata {
+    name: &'static str,
+    path: &'static str,
+    methods: &'static [&'static str],
+    summary: Option<&'static str>,
+    description: Option<&'static str>,
+    tags: &'static [&'static str],
+    responses: &'static [ResponseMetadata],
+}
+
+impl RouteMetadata {
+    #[doc(hidden)]
+    #[allow(clippy::too_many_arguments)]
+    pub const fn __new(
+        name: &'static str,
+        path: &'static str,
+        methods: &'static [&'static str],
+        summary: Option<&'static str>,
+        description: Option<&'static str>,
+        tags: &'static [&'static str],
+        responses: &'static [ResponseMetadata],
+    ) -> Self {
+        Self {
+            name,
+            path,
+            methods,
+            summary,
+            description,
+            tags,
+            responses,
+        }
+    }
+
+    /// Returns the resource name of the route.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the path pattern of the route, including any prefix added by `#[scope]`.
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Returns the HTTP methods that the route responds to, in alphabetical order.
+    pub fn methods(&self) -> &'static [&'static str] {
+        self.methods
+    }
+
+    /// Returns the route's summary, if set using the `summary` attribute.
+    pub fn summary(&self) -> Option<&'static str> {
+        self.summary
+    }
+
+    /// Returns the handler's doc comment, if any, with leading whitespace trimmed from each line.
+    pub fn description(&self) -> Option<&'static str> {
+        self.description
+    }
+
+    /// Returns the tags set using `tag` attributes, in declaration order.
+    pub fn tags(&self) -> &'static [&'static str] {
+        self.tags
+    }
+
+    /// Returns the responses declared using `response` attributes, in declaration order.
+    pub fn responses(&self) -> &'static [ResponseMetadata] {
+        self.responses
+    }
+}
+
+/// A response declared for a route using the `response` attribute of routing macros.
+///
+/// See [`RouteMetadata`].
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseMetadata {
+    status: u16,
+    type_name: fn() -> &'static str,
+}
+
+impl ResponseMetadata {
+    #[doc(hidden)]
+    pub const fn __new(status: u16, type_name: fn() -> &'static str) -> Self {
+        Self { status, type_name }
+    }
+
+    /// Returns the status code of the response.
+    pub fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).expect("routing macros should validate status codes")
+    }
+
+    /// Returns the name of the response type, as given by [`std::any::type_name()`].
+    pub fn type_name(&self) -> &'static str {
+        (self.type_name)()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTES: &[RouteMetadata] = &[RouteMetadata::__new(
+        "index",
+        "/",
+        &["GET", "HEAD"],
+        None,
+        Some("Index page."),
+        &[],
+        &[ResponseMetadata::__new(200, std::any::type_name::<String>)],
+    )];
+
+    #[test]
+    fn accessors() {
+        let route = &ROUTES[0];
+        assert_eq!(route.name(), "index");
+        assert_eq!(route.path(), "/");
+        assert_eq!(route.methods(), ["GET", "HEAD"]);
+        assert_eq!(route.summary(), None);
+        assert_eq!(route.description(), Some("Index page."));
+        assert!(route.tags().is_empty());
+
+        let response = &route.responses()[0];
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.type_name(), "alloc::string::String");
+    }
+}
@@ -71,8 +71,27 @@
 // - Omit the `Arbiter` types because they have limited value here.
 // - Re-export but hide the runtime macros because they won't work directly but are required for
 //   `#[actix_web::main]` and `#[actix_web::test]` to work.
+// - Add a hidden multi-threaded runtime constructor for the same macros' `worker_threads` option.
 
 #[cfg(feature = "macros")]
 #[doc(hidden)]
 pub use actix_macros::{main, test};
 pub use actix_rt::{net, pin, signal, spawn, task, time, Runtime, System, SystemRunner};
+
+/// Creates a multi-threaded Tokio runtime, for use with [`System::with_tokio_rt`].
+///
+/// Used by `#[actix_web::main]` and `#[actix_web::test]` when worker threads are configured.
+#[cfg(feature = "macros-multi-thread")]
+#[doc(hidden)]
+pub fn __multi_thread_runtime(worker_threads: Option<usize>) -> tokio::runtime::Runtime {
+    let mut builder = tokio::runtime::Builder::new_multi_thread();
+
+    if let Some(worker_threads) = worker_threads {
+        builder.worker_threads(worker_threads);
+    }
+
+    builder
+        .enable_all()
+        .build()
+        .expect("Multi-threaded Actix (Tokio) runtime could not be created.")
+}
@@ -1,6 +1,8 @@
 use std::{
     any::Any,
-    cmp, fmt, io,
+    cmp, fmt,
+    future::Future,
+    io,
     marker::PhantomData,
     net,
     sync::{Arc, Mutex},
@@ -17,7 +19,11 @@ use actix_service::{
 #[cfg(feature = "openssl")]
 use actix_tls::accept::openssl::reexports::{AlpnError, SslAcceptor, SslAcceptorBuilder};
 
-use crate::{config::AppConfig, Error};
+#[cfg(feature = "__tls")]
+use crate::auth::ClientCert;
+use crate::{config::AppConfig, dev::Connection, Error};
+
+type OnConnectFn = dyn Fn(&dyn Any, &mut Extensions) + Send + Sync;
 
 struct Socket {
     scheme: &'static str,
@@ -80,7 +86,7 @@ where
     sockets: Vec<Socket>,
     builder: ServerBuilder,
     #[allow(clippy::type_complexity)]
-    on_connect_fn: Option<Arc<dyn Fn(&dyn Any, &mut Extensions) + Send + Sync>>,
+    on_connect_fn: Option<Arc<OnConnectFn>>,
     _phantom: PhantomData<(S, B)>,
 }
 
@@ -271,7 +277,11 @@ where
     ///   Rustls v0.23.
     /// - `actix_web::rt::net::TcpStream` when no encryption is used.
     ///
-    /// See the `on_connect` example for additional details.
+    /// For TLS connections on which the client authenticated using a certificate, the extensions
+    /// already contain its [`ClientCert`](crate::auth::ClientCert) when this function is called.
+    ///
+    /// See the `on_connect` example for additional details, and
+    /// [`on_connection()`](Self::on_connection()) for a higher-level version.
     pub fn on_connect<CB>(self, f: CB) -> HttpServer<F, I, S, B>
     where
         CB: Fn(&dyn Any, &mut Extensions) + Send + Sync + 'static,
@@ -287,6 +297,44 @@ where
         }
     }
 
+    /// Sets function that will be called once before each connection is handled, with a
+    /// [`Connection`] for tuning its socket, storing per-connection data, and registering
+    /// disconnect callbacks.
+    ///
+    /// Per-connection data can be read in handlers using the [`ConnData`](crate::web::ConnData)
+    /// extractor. This replaces any function set using [`on_connect()`](Self::on_connect()), of
+    /// which this is a higher-level version.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use actix_web::{web, App, HttpServer};
+    ///
+    /// # #[actix_web::main]
+    /// # async fn main() -> std::io::Result<()> {
+    /// HttpServer::new(|| App::new().route("/", web::get().to(|| async { "hello" })))
+    ///     .on_connection(|conn| {
+    ///         if let Err(err) = conn.set_keepalive(Some(Duration::from_secs(60))) {
+    ///             log::warn!("Failed to enable TCP keep-alive: {err}");
+    ///         }
+    ///
+    ///         let peer_addr = conn.peer_addr();
+    ///         log::info!("{peer_addr:?} connected");
+    ///         conn.on_disconnect(move || log::info!("{peer_addr:?} disconnected"));
+    ///     })
+    ///     .bind(("127.0.0.1", 8080))?
+    ///     .run()
+    ///     .await
+    /// # }
+    /// ```
+    pub fn on_connection<CB>(self, f: CB) -> HttpServer<F, I, S, B>
+    where
+        CB: Fn(&mut Connection<'_>) + Send + Sync + 'static,
+    {
+        self.on_connect(move |io, ext| f(&mut Connection::new(io, ext)))
+    }
+
     /// Sets server host name.
     ///
     /// Host name is used by application router as a hostname for url generation. Check
@@ -312,6 +360,21 @@ where
         self
     }
 
+    /// Specifies shutdown signal from a future.
+    ///
+    /// Using this method will prevent OS signal handlers being set up.
+    ///
+    /// Typically, a `CancellationToken` will be used, but any future _can_ be. See
+    /// [`HealthChecks::drain()`](crate::web::HealthChecks::drain()) for delaying shutdown until
+    /// load balancers have stopped routing traffic to the server.
+    pub fn shutdown_signal<Fut>(mut self, shutdown_signal: Fut) -> Self
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.builder = self.builder.shutdown_signal(shutdown_signal);
+        self
+    }
+
     /// Sets timeout for graceful worker shutdown of workers.
     ///
     /// After receiving a stop signal, workers have this much time to finish serving requests.
@@ -652,11 +715,10 @@ where
                         .client_request_timeout(c.client_request_timeout)
                         .client_disconnect_timeout(c.client_disconnect_timeout);
 
-                    let svc = if let Some(handler) = on_connect_fn.clone() {
-                        svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    } else {
-                        svc
-                    };
+                    let on_connect_fn = on_connect_fn.clone();
+                    let svc = svc.on_connect_ext(move |io: &_, ext: _| {
+                        tls_on_connect(io as &dyn Any, ext, on_connect_fn.as_deref())
+                    });
 
                     let fac = factory()
                         .into_factory()
@@ -703,11 +765,10 @@ where
                         .client_request_timeout(c.client_request_timeout)
                         .client_disconnect_timeout(c.client_disconnect_timeout);
 
-                    let svc = if let Some(handler) = on_connect_fn.clone() {
-                        svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    } else {
-                        svc
-                    };
+                    let on_connect_fn = on_connect_fn.clone();
+                    let svc = svc.on_connect_ext(move |io: &_, ext: _| {
+                        tls_on_connect(io as &dyn Any, ext, on_connect_fn.as_deref())
+                    });
 
                     let fac = factory()
                         .into_factory()
@@ -769,11 +830,10 @@ where
                         .client_request_timeout(c.client_request_timeout)
                         .client_disconnect_timeout(c.client_disconnect_timeout);
 
-                    let svc = if let Some(handler) = on_connect_fn.clone() {
-                        svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    } else {
-                        svc
-                    };
+                    let on_connect_fn = on_connect_fn.clone();
+                    let svc = svc.on_connect_ext(move |io: &_, ext: _| {
+                        tls_on_connect(io as &dyn Any, ext, on_connect_fn.as_deref())
+                    });
 
                     let fac = factory()
                         .into_factory()
@@ -835,11 +895,10 @@ where
                         .client_request_timeout(c.client_request_timeout)
                         .client_disconnect_timeout(c.client_disconnect_timeout);
 
-                    let svc = if let Some(handler) = on_connect_fn.clone() {
-                        svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    } else {
-                        svc
-                    };
+                    let on_connect_fn = on_connect_fn.clone();
+                    let svc = svc.on_connect_ext(move |io: &_, ext: _| {
+                        tls_on_connect(io as &dyn Any, ext, on_connect_fn.as_deref())
+                    });
 
                     let fac = factory()
                         .into_factory()
@@ -901,11 +960,10 @@ where
                         .client_disconnect_timeout(c.client_disconnect_timeout)
                         .local_addr(addr);
 
-                    let svc = if let Some(handler) = on_connect_fn.clone() {
-                        svc.on_connect_ext(move |io: &_, ext: _| (handler)(io as &dyn Any, ext))
-                    } else {
-                        svc
-                    };
+                    let on_connect_fn = on_connect_fn.clone();
+                    let svc = svc.on_connect_ext(move |io: &_, ext: _| {
+                        tls_on_connect(io as &dyn Any, ext, on_connect_fn.as_deref())
+                    });
 
                     let fac = factory()
                         .into_factory()
@@ -1052,6 +1110,19 @@ where
     }
 }
 
+/// Inserts the certificate chain presented by the client of a TLS connection into its connection
+/// data, then calls the `on_connect` function, if any.
+#[cfg(feature = "__tls")]
+fn tls_on_connect(io: &dyn Any, ext: &mut Extensions, on_connect_fn: Option<&OnConnectFn>) {
+    if let Some(cert) = ClientCert::from_connection(io) {
+        ext.insert(cert);
+    }
+
+    if let Some(on_connect_fn) = on_connect_fn {
+        on_connect_fn(io, ext);
+    }
+}
+
 /// Bind TCP listeners to socket addresses resolved from `addrs` with options.
 fn bind_addrs(addrs: impl net::ToSocketAddrs, backlog: u32) -> io::Result<Vec<net::TcpListener>> {
     let mut err = None;
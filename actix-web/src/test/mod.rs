@@ -9,6 +9,7 @@
 //!
 //! # Calling Test Service
 //! - [`TestRequest`]
+//! - [`MultipartBuilder`]
 //! - [`call_service`]
 //! - [`try_call_service`]
 //! - [`call_and_read_body`]
@@ -25,6 +26,7 @@
 
 pub use actix_http::test::TestBuffer;
 
+mod test_multipart;
 mod test_request;
 mod test_services;
 mod test_utils;
@@ -36,6 +38,7 @@ pub(crate) use self::test_utils::try_init_service;
 #[allow(deprecated)]
 pub use self::test_utils::{read_response, read_response_json};
 pub use self::{
+    test_multipart::MultipartBuilder,
     test_request::TestRequest,
     test_utils::{
         call_and_read_body, call_and_read_body_json, call_service, init_service, read_body,
@@ -0,0 +1,246 @@
+use actix_http::header::HeaderValue;
+use bytes::{BufMut as _, Bytes, BytesMut};
+use mime::Mime;
+
+const CRLF: &[u8] = b"\r\n";
+const HYPHENS: &[u8] = b"--";
+const DEFAULT_BOUNDARY: &str = "------------------------actix-web-test-boundary";
+
+/// Builder for `multipart/form-data` request bodies made of text and file parts.
+///
+/// Use with [`TestRequest::set_multipart()`](super::TestRequest::set_multipart()) or, when using a
+/// test server, the `send_multipart()` method provided by `actix-test`.
+///
+/// The boundary is fixed by default, so that bodies are reproducible, and is extended if any part
+/// happens to contain it.
+///
+/// # Examples
+/// ```
+/// use actix_web::test::{MultipartBuilder, TestRequest};
+///
+/// let form = MultipartBuilder::new()
+///     .text("title", "Holiday")
+///     .file("photo", "beach.jpg", mime::IMAGE_JPEG, &b"\xFF\xD8\xFF"[..]);
+///
+/// let req = TestRequest::post().set_multipart(form).to_http_request();
+///
+/// assert!(req
+///     .headers()
+///     .get("content-type")
+///     .unwrap()
+///     .to_str()
+///     .unwrap()
+///     .starts_with("multipart/form-data; boundary="));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MultipartBuilder {
+    boundary: Option<String>,
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone)]
+struct Part {
+    name: String,
+    filename: Option<String>,
+    content_type: Option<Mime>,
+    data: Bytes,
+}
+
+impl MultipartBuilder {
+    /// Constructs an empty multipart body builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the boundary delimiting parts.
+    ///
+    /// # Panics
+    /// Panics when building the body if any part contains the boundary.
+    pub fn boundary(mut self, boundary: impl Into<String>) -> Self {
+        self.boundary = Some(boundary.into());
+        self
+    }
+
+    /// Appends a text field.
+    pub fn text(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            filename: None,
+            content_type: None,
+            data: Bytes::from(value.into()),
+        });
+        self
+    }
+
+    /// Appends a file field with the given file name and content type.
+    pub fn file(
+        mut self,
+        name: impl Into<String>,
+        filename: impl Into<String>,
+        content_type: Mime,
+        data: impl Into<Bytes>,
+    ) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            filename: Some(filename.into()),
+            content_type: Some(content_type),
+            data: data.into(),
+        });
+        self
+    }
+
+    /// Builds the multipart body and the matching `Content-Type` header value.
+    pub fn build(self) -> (Bytes, HeaderValue) {
+        let boundary = self.resolve_boundary();
+
+        let mut buf = BytesMut::with_capacity(
+            self.parts
+                .iter()
+                .map(|part| part.data.len() + boundary.len() + 128)
+                .sum::<usize>()
+                + boundary.len()
+                + 8,
+        );
+
+        for part in self.parts {
+            buf.put(HYPHENS);
+            buf.put(boundary.as_bytes());
+            buf.put(CRLF);
+
+            buf.put(
+                format!(
+                    "Content-Disposition: form-data; name=\"{}\"",
+                    escape_quoted(&part.name)
+                )
+                .as_bytes(),
+            );
+            if let Some(filename) = &part.filename {
+                buf.put(format!("; filename=\"{}\"", escape_quoted(filename)).as_bytes());
+            }
+            buf.put(CRLF);
+
+            if let Some(content_type) = &part.content_type {
+                buf.put(format!("Content-Type: {content_type}").as_bytes());
+                buf.put(CRLF);
+            }
+
+            buf.put(CRLF);
+            buf.put(part.data);
+            buf.put(CRLF);
+        }
+
+        buf.put(HYPHENS);
+        buf.put(boundary.as_bytes());
+        buf.put(HYPHENS);
+        buf.put(CRLF);
+
+        let content_type =
+            HeaderValue::from_str(&format!("multipart/form-data; boundary=\"{boundary}\""))
+                .expect("multipart boundary is not a valid header value");
+
+        (buf.freeze(), content_type)
+    }
+
+    /// Returns a boundary that no part contains.
+    fn resolve_boundary(&self) -> String {
+        let collides = |boundary: &str| {
+            self.parts
+                .iter()
+                .any(|part| contains(&part.data, boundary.as_bytes()))
+        };
+
+        if let Some(boundary) = &self.boundary {
+            assert!(
+                !collides(boundary),
+                "multipart boundary {boundary:?} is contained in a part"
+            );
+
+            return boundary.clone();
+        }
+
+        let mut boundary = DEFAULT_BOUNDARY.to_owned();
+        let mut suffix = 0;
+
+        while collides(&boundary) {
+            suffix += 1;
+            boundary = format!("{DEFAULT_BOUNDARY}-{suffix}");
+        }
+
+        boundary
+    }
+}
+
+/// Escapes a field or file name for use in a quoted `Content-Disposition` parameter.
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    !needle.is_empty()
+        && haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_and_file_parts() {
+        let (body, content_type) = MultipartBuilder::new()
+            .boundary("abc")
+            .text("title", "Holiday")
+            .file("photo", "beach \"1\".txt", mime::TEXT_PLAIN, "sand")
+            .build();
+
+        assert_eq!(content_type, "multipart/form-data; boundary=\"abc\"");
+        assert_eq!(
+            body,
+            Bytes::from_static(
+                b"--abc\r\n\
+                Content-Disposition: form-data; name=\"title\"\r\n\
+                \r\n\
+                Holiday\r\n\
+                --abc\r\n\
+                Content-Disposition: form-data; name=\"photo\"; filename=\"beach %221%22.txt\"\r\n\
+                Content-Type: text/plain\r\n\
+                \r\n\
+                sand\r\n\
+                --abc--\r\n"
+            ),
+        );
+    }
+
+    #[test]
+    fn empty_form() {
+        let (body, _) = MultipartBuilder::new().boundary("abc").build();
+        assert_eq!(body, Bytes::from_static(b"--abc--\r\n"));
+    }
+
+    #[test]
+    fn default_boundary_avoids_collisions() {
+        let (body, content_type) = MultipartBuilder::new()
+            .text("field", DEFAULT_BOUNDARY)
+            .build();
+
+        let expected = format!("{DEFAULT_BOUNDARY}-1");
+        assert_eq!(
+            content_type,
+            format!("multipart/form-data; boundary=\"{expected}\"").as_str()
+        );
+        assert!(contains(&body, format!("--{expected}--").as_bytes()));
+    }
+
+    #[test]
+    #[should_panic(expected = "is contained in a part")]
+    fn custom_boundary_collision() {
+        MultipartBuilder::new()
+            .boundary("abc")
+            .text("field", "xabcx")
+            .build();
+    }
+}
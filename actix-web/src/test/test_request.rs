@@ -1,6 +1,8 @@
 use std::{borrow::Cow, net::SocketAddr, rc::Rc};
 
-use actix_http::{test::TestRequest as HttpTestRequest, Request};
+use actix_codec::Decoder as _;
+use actix_http::{h1, test::TestRequest as HttpTestRequest, Request, ServiceConfig};
+use bytes::BytesMut;
 use serde::Serialize;
 
 #[cfg(feature = "cookies")]
@@ -10,13 +12,14 @@ use crate::{
     config::AppConfig,
     data::Data,
     dev::{Extensions, Path, Payload, ResourceDef, Service, Url},
+    error::ParseError,
     http::{
-        header::{ContentType, TryIntoHeaderPair},
+        header::{self, ContentType, TryIntoHeaderPair},
         Method, Uri, Version,
     },
     rmap::ResourceMap,
     service::{ServiceRequest, ServiceResponse},
-    test,
+    test::{self, MultipartBuilder},
     web::Bytes,
     HttpRequest, HttpResponse,
 };
@@ -116,6 +119,74 @@ impl TestRequest {
         TestRequest::default().method(Method::DELETE)
     }
 
+    /// Constructs test request by parsing a raw HTTP/1.x request.
+    ///
+    /// The request is parsed the same way as by the HTTP/1 server, which makes this useful for
+    /// testing how an app handles unusual requests, such as those with oddly cased or duplicate
+    /// headers or conflicting body framing. Chunked bodies are decoded. Any bytes following the
+    /// first request's body are ignored, except for upgrade and `CONNECT` requests, whose payload
+    /// is everything following the request head.
+    ///
+    /// # Errors
+    /// Returns an error if the request is malformed or incomplete.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{http::Method, test::TestRequest};
+    ///
+    /// let req = TestRequest::from_raw(
+    ///     "POST /items?page=2 HTTP/1.1\r\n\
+    ///      host: example.com\r\n\
+    ///      Transfer-Encoding: chunked\r\n\
+    ///      \r\n\
+    ///      5\r\nhello\r\n0\r\n\r\n",
+    /// )
+    /// .unwrap()
+    /// .to_http_request();
+    ///
+    /// assert_eq!(req.method(), Method::POST);
+    /// assert_eq!(req.path(), "/items");
+    /// assert_eq!(req.query_string(), "page=2");
+    /// assert_eq!(req.headers().get("host").unwrap(), "example.com");
+    /// ```
+    pub fn from_raw(raw: impl AsRef<[u8]>) -> Result<TestRequest, ParseError> {
+        let mut buf = BytesMut::from(raw.as_ref());
+        let mut codec = h1::Codec::new(ServiceConfig::default());
+
+        let req = match codec.decode(&mut buf)? {
+            Some(h1::Message::Item(req)) => req,
+            _ => return Err(ParseError::Incomplete),
+        };
+
+        let mut payload = BytesMut::new();
+
+        match codec.message_type() {
+            h1::MessageType::None => {}
+
+            // bodies of upgrade and CONNECT requests last until the connection is closed
+            h1::MessageType::Stream => payload.extend_from_slice(&buf),
+
+            h1::MessageType::Payload => loop {
+                match codec.decode(&mut buf)? {
+                    Some(h1::Message::Chunk(Some(chunk))) => payload.extend_from_slice(&chunk),
+                    Some(h1::Message::Chunk(None)) => break,
+                    Some(h1::Message::Item(_)) | None => return Err(ParseError::Incomplete),
+                }
+            },
+        }
+
+        let mut test_req = TestRequest::default()
+            .method(req.method().clone())
+            .uri(&req.uri().to_string())
+            .version(req.version());
+
+        for (name, value) in req.head().headers() {
+            test_req = test_req.append_header((name.clone(), value.clone()));
+        }
+
+        Ok(test_req.set_payload(payload.freeze()))
+    }
+
     /// Sets HTTP version of this request.
     pub fn version(mut self, ver: Version) -> Self {
         self.req.version(ver);
@@ -205,6 +276,16 @@ impl TestRequest {
         self
     }
 
+    /// Builds a `multipart/form-data` body from `form` and sets it as the request payload.
+    ///
+    /// The `Content-Type` header is set to `multipart/form-data` with the form's boundary.
+    pub fn set_multipart(mut self, form: MultipartBuilder) -> Self {
+        let (body, content_type) = form.build();
+        self.req.set_payload(body);
+        self.req.insert_header((header::CONTENT_TYPE, content_type));
+        self
+    }
+
     /// Inserts application data.
     ///
     /// This is equivalent of `App::app_data()` method for testing purpose.
@@ -441,4 +522,84 @@ mod tests {
         let res = app.call(req).await.unwrap();
         assert!(res.status().is_success());
     }
+
+    #[actix_rt::test]
+    async fn test_from_raw() {
+        async fn echo(req: HttpRequest, body: Bytes) -> HttpResponse {
+            let values = req
+                .headers()
+                .get_all("x-dup")
+                .map(|val| val.to_str().unwrap())
+                .collect::<Vec<_>>()
+                .join(",");
+
+            HttpResponse::Ok()
+                .insert_header(("x-dup", values))
+                .body(body)
+        }
+
+        let app = init_service(App::new().route("/echo", web::to(echo))).await;
+
+        let req = TestRequest::from_raw(
+            b"POST /echo HTTP/1.1\r\n\
+            X-DUP: 1\r\n\
+            x-dup: 2\r\n\
+            Content-Length: 5\r\n\
+            \r\n\
+            hello\
+            GET /ignored HTTP/1.1\r\n\r\n",
+        )
+        .unwrap()
+        .to_request();
+
+        let res = app.call(req).await.unwrap();
+        assert_eq!(res.headers().get("x-dup").unwrap(), "1,2");
+        assert_eq!(test::read_body(res).await, "hello");
+
+        let req = TestRequest::from_raw(
+            b"POST /echo HTTP/1.1\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            3\r\nhel\r\n2\r\nlo\r\n0\r\n\r\n",
+        )
+        .unwrap()
+        .to_request();
+
+        let res = app.call(req).await.unwrap();
+        assert_eq!(test::read_body(res).await, "hello");
+
+        let req = TestRequest::from_raw(
+            b"CONNECT /echo HTTP/1.1\r\n\
+            \r\n\
+            hello\r\n\r\nworld",
+        )
+        .unwrap()
+        .to_request();
+
+        let res = app.call(req).await.unwrap();
+        assert_eq!(test::read_body(res).await, "hello\r\n\r\nworld");
+    }
+
+    #[test]
+    fn test_from_raw_errors() {
+        assert!(matches!(
+            TestRequest::from_raw(b"GET / HTTP/1.1\r\nhost: example.com\r\n"),
+            Err(ParseError::Incomplete)
+        ));
+
+        assert!(matches!(
+            TestRequest::from_raw(b"POST / HTTP/1.1\r\ncontent-length: 10\r\n\r\nhello"),
+            Err(ParseError::Incomplete)
+        ));
+
+        assert!(matches!(
+            TestRequest::from_raw(
+                b"POST / HTTP/1.1\r\n\
+                content-length: 5\r\n\
+                content-length: 6\r\n\
+                \r\n"
+            ),
+            Err(ParseError::Header)
+        ));
+    }
 }
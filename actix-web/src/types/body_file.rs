@@ -0,0 +1,376 @@
+//! For temp file body extractor documentation, see [`BodyFile`].
+
+use std::{
+    borrow::Cow,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use actix_http::Payload;
+use futures_core::future::LocalBoxFuture;
+use futures_util::StreamExt as _;
+use mime::Mime;
+use sha2::{Digest as _, Sha256};
+use tempfile::NamedTempFile;
+use tokio::io::AsyncWriteExt as _;
+
+use crate::{
+    error::{BodyFileError, Error, PayloadError},
+    extract::FromRequest,
+    http::header,
+    middleware::body_limit::request_body_limit,
+    request::HttpRequest,
+    web, HttpMessage as _,
+};
+
+/// Extractor that streams the request body to a temporary file on disk.
+///
+/// Useful for large, non-multipart uploads, such as `PUT` requests of big binary files, that
+/// should not be buffered in memory. The body is written to the file as it is received, and the
+/// handler is given the file once the whole body has been written. The temporary file is deleted
+/// when the `BodyFile` is dropped, unless it is persisted first using
+/// [`into_file()`](Self::into_file).
+///
+/// Use [`BodyFileConfig`] to configure extraction options, such as the size limit of the body and
+/// computing its SHA-256 digest.
+///
+/// # Examples
+/// ```
+/// use actix_web::{put, web, HttpResponse};
+///
+/// #[put("/images/{name}")]
+/// async fn upload(name: web::Path<String>, body: web::BodyFile) -> actix_web::Result<HttpResponse> {
+///     let path = format!("/var/images/{name}");
+///
+///     web::block(move || body.into_file().persist(path))
+///         .await?
+///         .map_err(actix_web::error::ErrorInternalServerError)?;
+///
+///     Ok(HttpResponse::Created().finish())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BodyFile {
+    file: NamedTempFile,
+    size: u64,
+    content_type: Option<Mime>,
+    sha256: Option<[u8; 32]>,
+}
+
+impl BodyFile {
+    /// Returns the temporary file.
+    pub fn file(&self) -> &NamedTempFile {
+        &self.file
+    }
+
+    /// Returns the path of the temporary file.
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// Reopens the temporary file for reading, starting from the beginning of the body.
+    ///
+    /// This performs blocking I/O; consider running it on a thread pool using [`web::block()`].
+    pub fn reopen(&self) -> io::Result<File> {
+        self.file.reopen()
+    }
+
+    /// Returns the size of the body, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the media type of the body, from the request's `Content-Type` header.
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.content_type.as_ref()
+    }
+
+    /// Returns the SHA-256 digest of the body, if enabled using [`BodyFileConfig::sha256()`].
+    pub fn sha256(&self) -> Option<&[u8; 32]> {
+        self.sha256.as_ref()
+    }
+
+    /// Unwraps into the temporary file, which can be persisted to a permanent location.
+    pub fn into_file(self) -> NamedTempFile {
+        self.file
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl FromRequest for BodyFile {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = BodyFileConfig::from_req(req).into_owned();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            config.write(&req, payload).await.map_err(|err| {
+                log::debug!(
+                    "Failed to write payload to temp file. Request path: {}",
+                    req.path()
+                );
+
+                match config.err_handler.as_ref() {
+                    Some(err_handler) => err_handler(err, &req),
+                    None => err.into(),
+                }
+            })
+        })
+    }
+}
+
+type BodyFileErrorHandler = Option<Arc<dyn Fn(BodyFileError, &HttpRequest) -> Error + Send + Sync>>;
+
+/// `BodyFile` extractor configuration.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App};
+///
+/// // limit uploads to 1GB, written to a dedicated directory, and compute their digests
+/// let app = App::new().app_data(
+///     web::BodyFileConfig::default()
+///         .limit(1024 * 1024 * 1024)
+///         .directory("/var/uploads/tmp")
+///         .sha256(true),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct BodyFileConfig {
+    limit: usize,
+    directory: Option<PathBuf>,
+    sha256: bool,
+    err_handler: BodyFileErrorHandler,
+}
+
+impl BodyFileConfig {
+    /// Set maximum accepted payload size. By default this limit is 64MB.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Sets the directory that temp files will be created in.
+    ///
+    /// The default temporary file location is platform dependent.
+    pub fn directory(mut self, dir: impl AsRef<Path>) -> Self {
+        self.directory = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Sets whether the SHA-256 digest of bodies is computed while they are written.
+    ///
+    /// By default, digests are not computed.
+    pub fn sha256(mut self, sha256: bool) -> Self {
+        self.sha256 = sha256;
+        self
+    }
+
+    /// Set custom error handler.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(BodyFileError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Extract payload config from app data. Check both `T` and `Data<T>`, in that order, and fall
+    /// back to the default payload config, limited by the `BodyLimit` middleware if present.
+    fn from_req(req: &HttpRequest) -> Cow<'_, Self> {
+        match req
+            .app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+        {
+            Some(config) => Cow::Borrowed(config),
+            None => Cow::Owned(Self {
+                limit: request_body_limit(req).unwrap_or(DEFAULT_LIMIT),
+                ..DEFAULT_CONFIG
+            }),
+        }
+    }
+
+    fn create_tempfile(&self) -> io::Result<NamedTempFile> {
+        match self.directory {
+            Some(ref dir) => NamedTempFile::new_in(dir),
+            None => NamedTempFile::new(),
+        }
+    }
+
+    /// Writes the payload to a new temp file.
+    async fn write(
+        &self,
+        req: &HttpRequest,
+        mut payload: Payload,
+    ) -> Result<BodyFile, BodyFileError> {
+        // reject payloads that are known to be too large before creating a file
+        if let Some(len) = req
+            .headers()
+            .get(&header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok())
+            .and_then(|len| len.parse::<u64>().ok())
+        {
+            if len > self.limit as u64 {
+                return Err(PayloadError::Overflow.into());
+            }
+        }
+
+        let file = self.create_tempfile()?;
+        let mut file_async = tokio::fs::File::from_std(file.reopen()?);
+        let mut hasher = self.sha256.then(Sha256::new);
+        let mut size = 0;
+
+        while let Some(chunk) = payload.next().await {
+            let chunk = chunk?;
+
+            size += chunk.len();
+            if size > self.limit {
+                return Err(PayloadError::Overflow.into());
+            }
+
+            if let Some(ref mut hasher) = hasher {
+                hasher.update(&chunk);
+            }
+
+            file_async.write_all(&chunk).await?;
+        }
+
+        file_async.flush().await?;
+
+        Ok(BodyFile {
+            file,
+            size: size as u64,
+            content_type: req.mime_type().ok().flatten(),
+            sha256: hasher.map(|hasher| hasher.finalize().into()),
+        })
+    }
+}
+
+const DEFAULT_LIMIT: usize = 67_108_864; // 64 mb
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: BodyFileConfig = BodyFileConfig {
+    limit: DEFAULT_LIMIT,
+    directory: None,
+    sha256: false,
+    err_handler: None,
+};
+
+impl Default for BodyFileConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read as _;
+
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::{http::StatusCode, test::TestRequest, HttpResponse};
+
+    fn read_file(body: &BodyFile) -> Vec<u8> {
+        let mut buf = Vec::new();
+        body.reopen().unwrap().read_to_end(&mut buf).unwrap();
+        buf
+    }
+
+    #[actix_rt::test]
+    async fn test_extract() {
+        let (req, mut pl) = TestRequest::put()
+            .insert_header((header::CONTENT_TYPE, "application/octet-stream"))
+            .set_payload(Bytes::from_static(b"hello world"))
+            .to_http_parts();
+
+        let body = BodyFile::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(body.size(), 11);
+        assert_eq!(body.content_type(), Some(&mime::APPLICATION_OCTET_STREAM));
+        assert_eq!(body.sha256(), None);
+        assert_eq!(read_file(&body), b"hello world");
+
+        let path = body.path().to_owned();
+        assert!(path.exists());
+        drop(body);
+        assert!(!path.exists());
+    }
+
+    #[actix_rt::test]
+    async fn test_sha256() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let (req, mut pl) = TestRequest::put()
+            .app_data(BodyFileConfig::default().sha256(true).directory(dir.path()))
+            .set_payload(Bytes::from_static(b"hello world"))
+            .to_http_parts();
+
+        let body = BodyFile::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(body.content_type(), None);
+        assert!(body.path().starts_with(dir.path()));
+
+        let hex = body
+            .sha256()
+            .unwrap()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        assert_eq!(
+            hex,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_limit() {
+        // payloads with a content length that is too large are rejected before being read
+        let (req, mut pl) = TestRequest::put()
+            .app_data(BodyFileConfig::default().limit(4))
+            .insert_header((header::CONTENT_LENGTH, "11"))
+            .to_http_parts();
+
+        let err = BodyFile::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(err.error_response().status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // otherwise, the limit is checked while streaming
+        let (req, mut pl) = TestRequest::put()
+            .app_data(BodyFileConfig::default().limit(4))
+            .set_payload(Bytes::from_static(b"hello world"))
+            .to_http_parts();
+
+        let err = BodyFile::from_request(&req, &mut pl).await.unwrap_err();
+        assert!(matches!(
+            err.as_error::<BodyFileError>(),
+            Some(BodyFileError::Payload(PayloadError::Overflow))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_err_handler() {
+        let (req, mut pl) = TestRequest::put()
+            .app_data(
+                BodyFileConfig::default()
+                    .directory("/nonexistent/directory")
+                    .error_handler(|err, _| {
+                        crate::error::InternalError::from_response(
+                            err,
+                            HttpResponse::InsufficientStorage().finish(),
+                        )
+                        .into()
+                    }),
+            )
+            .set_payload(Bytes::from_static(b"hello world"))
+            .to_http_parts();
+
+        let err = BodyFile::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(
+            err.error_response().status(),
+            StatusCode::INSUFFICIENT_STORAGE
+        );
+    }
+}
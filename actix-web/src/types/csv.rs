@@ -0,0 +1,615 @@
+//! For CSV helper documentation, see [`Csv`].
+
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    fmt, io, ops,
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use actix_http::Payload;
+use bytes::{Bytes, BytesMut};
+use futures_core::{future::LocalBoxFuture, Stream};
+use futures_util::stream;
+use pin_project_lite::pin_project;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{payload::HttpMessageBody, MatchContentType};
+use crate::{
+    body::{BodySize, BoxBody, MessageBody},
+    error::{CsvPayloadError, Error},
+    extract::FromRequest,
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    middleware::body_limit::request_body_limit,
+    request::HttpRequest,
+    web, HttpMessage as _, HttpResponse, Responder,
+};
+
+/// Content type of CSV responses.
+const CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Number of bytes that are buffered before a chunk is sent.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// CSV extractor and responder.
+///
+/// `Csv` has two uses: CSV responses, and extracting typed records from CSV request payloads.
+///
+/// # Extractor
+/// To extract typed records from a request body, the inner type `T` must implement the
+/// [`serde::Deserialize`] trait. Each record of the body is deserialized as a `T`, using the
+/// header row to match columns to fields by default.
+///
+/// The request must have the `text/csv` content type. Use [`CsvConfig`] to configure extraction
+/// options, such as the size limit of the body.
+///
+/// ```
+/// use actix_web::{post, web, App};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Contact {
+///     name: String,
+///     email: String,
+/// }
+///
+/// /// Import contacts from an uploaded CSV file.
+/// #[post("/contacts")]
+/// async fn import(contacts: web::Csv<Contact>) -> String {
+///     format!("Imported {} contacts", contacts.len())
+/// }
+/// ```
+///
+/// # Responder
+/// The `Csv` type serializes its records as a `text/csv` response, with a header row derived from
+/// the field names of `T`. To stream records from an iterator or a stream without collecting them,
+/// use [`CsvStream`].
+///
+/// ```
+/// use actix_web::{get, web};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Contact {
+///     name: String,
+///     email: String,
+/// }
+///
+/// #[get("/contacts.csv")]
+/// async fn export() -> web::Csv<Contact> {
+///     web::Csv(vec![Contact {
+///         name: "Alice".to_owned(),
+///         email: "alice@example.com".to_owned(),
+///     }])
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Csv<T>(pub Vec<T>);
+
+impl<T> Csv<T> {
+    /// Unwraps into inner records.
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Csv<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Csv<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+/// Creates response with OK status code, CSV content type header, and serialized records.
+impl<T: Serialize + 'static> Responder for Csv<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        CsvStream::iter(self.0).respond_to(req)
+    }
+}
+
+/// See [here](#extractor) for example of usage as an extractor.
+impl<T: DeserializeOwned + 'static> FromRequest for Csv<T> {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = CsvConfig::from_req(req).into_owned();
+        let req = req.clone();
+
+        let can_parse = !config.content_type_required
+            || matches!(req.mime_type(), Ok(Some(mime)) if mime.subtype() == "csv");
+
+        let body = can_parse.then(|| HttpMessageBody::new(&req, payload).limit(config.limit));
+
+        Box::pin(async move {
+            let res = match body {
+                Some(body) => match body.await {
+                    Ok(body) => config.deserialize(&body),
+                    Err(err) => Err(CsvPayloadError::Payload(err)),
+                },
+                None => Err(CsvPayloadError::ContentType),
+            };
+
+            res.map(Csv).map_err(|err| {
+                log::debug!(
+                    "Failed to deserialize Csv from payload. Request path: {}",
+                    req.path()
+                );
+
+                match config.err_handler.as_ref() {
+                    Some(err_handler) => err_handler(err, &req),
+                    None => err.into(),
+                }
+            })
+        })
+    }
+}
+
+impl<T: DeserializeOwned + 'static> MatchContentType for Csv<T> {
+    fn match_content_type(req: &HttpRequest) -> bool {
+        matches!(req.mime_type(), Ok(Some(mime)) if mime.subtype() == "csv")
+    }
+}
+
+type CsvErrorHandler = Option<Arc<dyn Fn(CsvPayloadError, &HttpRequest) -> Error + Send + Sync>>;
+
+/// `Csv` extractor configuration.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App};
+///
+/// // limit uploads to 16MB of semicolon-separated values without a header row
+/// let app = App::new().app_data(
+///     web::CsvConfig::default()
+///         .limit(16 * 1024 * 1024)
+///         .delimiter(b';')
+///         .has_headers(false),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct CsvConfig {
+    limit: usize,
+    delimiter: u8,
+    has_headers: bool,
+    content_type_required: bool,
+    err_handler: CsvErrorHandler,
+}
+
+impl CsvConfig {
+    /// Set maximum accepted payload size. By default this limit is 2MB.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set the field delimiter. By default this is a comma.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets whether the first record is a header row, used to match columns to fields.
+    ///
+    /// By default, a header row is expected. Without one, columns are matched to fields by their
+    /// position.
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// Sets whether or not the request must have a `text/csv` content type to be parsed.
+    pub fn content_type_required(mut self, content_type_required: bool) -> Self {
+        self.content_type_required = content_type_required;
+        self
+    }
+
+    /// Set custom error handler.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(CsvPayloadError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Extract payload config from app data. Check both `T` and `Data<T>`, in that order, and fall
+    /// back to the default payload config, limited by the `BodyLimit` middleware if present.
+    fn from_req(req: &HttpRequest) -> Cow<'_, Self> {
+        match req
+            .app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+        {
+            Some(config) => Cow::Borrowed(config),
+            None => Cow::Owned(Self {
+                limit: request_body_limit(req).unwrap_or(DEFAULT_LIMIT),
+                ..DEFAULT_CONFIG
+            }),
+        }
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, body: &[u8]) -> Result<Vec<T>, CsvPayloadError> {
+        ::csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(self.has_headers)
+            .from_reader(body)
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(CsvPayloadError::Deserialize)
+    }
+}
+
+const DEFAULT_LIMIT: usize = 2_097_152; // 2 mb
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: CsvConfig = CsvConfig {
+    limit: DEFAULT_LIMIT,
+    delimiter: b',',
+    has_headers: true,
+    content_type_required: true,
+    err_handler: None,
+};
+
+impl Default for CsvConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+/// Streaming CSV responder.
+///
+/// Serializes each record of a stream as a CSV row while the response is sent, with a header row
+/// derived from the field names of the first record. Fields are quoted and escaped as needed.
+///
+/// If a record fails to serialize, the response body ends with an error and the connection is
+/// closed.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Order {
+///     id: u64,
+///     total: f64,
+/// }
+///
+/// #[get("/orders.csv")]
+/// async fn export() -> impl actix_web::Responder {
+///     let orders = (1..=1000).map(|id| Order { id, total: 9.99 });
+///     web::CsvStream::iter(orders).filename("orders.csv")
+/// }
+/// ```
+pub struct CsvStream<S> {
+    stream: S,
+    delimiter: u8,
+    has_headers: bool,
+    filename: Option<String>,
+}
+
+impl<S> CsvStream<S> {
+    /// Constructs a streaming CSV responder from a stream of serializable records.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            delimiter: b',',
+            has_headers: true,
+            filename: None,
+        }
+    }
+
+    /// Set the field delimiter. By default this is a comma.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets whether a header row is written before the first record. By default it is.
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// Sets a `Content-Disposition` header that asks browsers to download the response as a file
+    /// named `filename`.
+    pub fn filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+}
+
+impl<I: Iterator> CsvStream<stream::Iter<I>> {
+    /// Constructs a streaming CSV responder from an iterator of serializable records.
+    pub fn iter(iter: impl IntoIterator<IntoIter = I>) -> Self {
+        Self::new(stream::iter(iter))
+    }
+}
+
+impl<S> fmt::Debug for CsvStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CsvStream")
+            .field("delimiter", &self.delimiter)
+            .field("has_headers", &self.has_headers)
+            .field("filename", &self.filename)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Responder for CsvStream<S>
+where
+    S: Stream + 'static,
+    S::Item: Serialize,
+{
+    type Body = BoxBody;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse<Self::Body> {
+        let mut res = HttpResponse::Ok();
+        res.content_type(CONTENT_TYPE);
+
+        if let Some(filename) = self.filename {
+            res.insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(filename)],
+            });
+        }
+
+        let buf = SharedBuf::default();
+        let writer = ::csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(self.has_headers)
+            .from_writer(buf.clone());
+
+        res.body(CsvBody {
+            stream: self.stream,
+            writer,
+            buf,
+            done: false,
+        })
+    }
+}
+
+/// Buffer that the CSV writer writes to, shared with the body that sends its contents.
+#[derive(Clone, Default)]
+struct SharedBuf(Rc<RefCell<BytesMut>>);
+
+impl io::Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pin_project! {
+    struct CsvBody<S> {
+        #[pin]
+        stream: S,
+        writer: ::csv::Writer<SharedBuf>,
+        buf: SharedBuf,
+        done: bool,
+    }
+}
+
+/// Takes the rows written so far, if any.
+fn take_chunk(
+    writer: &mut ::csv::Writer<SharedBuf>,
+    buf: &SharedBuf,
+) -> Option<Result<Bytes, CsvPayloadError>> {
+    if let Err(err) = writer.flush() {
+        return Some(Err(CsvPayloadError::Serialize(err.into())));
+    }
+
+    let mut buf = buf.0.borrow_mut();
+    (!buf.is_empty()).then(|| Ok(buf.split().freeze()))
+}
+
+impl<S> MessageBody for CsvBody<S>
+where
+    S: Stream,
+    S::Item: Serialize,
+{
+    type Error = CsvPayloadError;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+
+        while !*this.done {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(record)) => {
+                    this.writer
+                        .serialize(record)
+                        .map_err(CsvPayloadError::Serialize)?;
+
+                    // the writer buffers rows internally and writes them to the buffer in batches
+                    if this.buf.0.borrow().len() >= CHUNK_SIZE {
+                        return Poll::Ready(take_chunk(this.writer, this.buf));
+                    }
+                }
+
+                Poll::Ready(None) => *this.done = true,
+
+                // send buffered rows instead of waiting for the next record
+                Poll::Pending => {
+                    return match take_chunk(this.writer, this.buf) {
+                        Some(chunk) => Poll::Ready(Some(chunk)),
+                        None => Poll::Pending,
+                    };
+                }
+            }
+        }
+
+        Poll::Ready(take_chunk(this.writer, this.buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt as _;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{
+        body,
+        http::{header, StatusCode},
+        test::TestRequest,
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Contact {
+        name: String,
+        email: String,
+    }
+
+    fn contact(name: &str, email: &str) -> Contact {
+        Contact {
+            name: name.to_owned(),
+            email: email.to_owned(),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn extractor() {
+        let (req, mut payload) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "text/csv"))
+            .set_payload("email,name\nalice@example.com,Alice\n\"bob@example.com\",\"Bob, Jr.\"\n")
+            .to_http_parts();
+
+        let contacts = Csv::<Contact>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(
+            contacts.into_inner(),
+            [
+                contact("Alice", "alice@example.com"),
+                contact("Bob, Jr.", "bob@example.com"),
+            ]
+        );
+
+        let (req, mut payload) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "text/csv"))
+            .app_data(CsvConfig::default().delimiter(b';').has_headers(false))
+            .set_payload("Alice;alice@example.com\n")
+            .to_http_parts();
+
+        let contacts = Csv::<Contact>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(*contacts, [contact("Alice", "alice@example.com")]);
+    }
+
+    #[actix_rt::test]
+    async fn extractor_errors() {
+        let status = |err: Error| err.as_response_error().status_code();
+
+        let (req, mut payload) = TestRequest::default()
+            .insert_header(header::ContentType::json())
+            .set_payload("name,email\n")
+            .to_http_parts();
+        let err = Csv::<Contact>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+
+        let (req, mut payload) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "text/csv"))
+            .set_payload("name\nAlice\n")
+            .to_http_parts();
+        let err = Csv::<Contact>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+
+        let (req, mut payload) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "text/csv"))
+            .app_data(CsvConfig::default().limit(10).error_handler(|err, _| {
+                assert!(matches!(err, CsvPayloadError::Payload(_)));
+                crate::error::ErrorForbidden("too large")
+            }))
+            .set_payload("name,email\nAlice,alice@example.com\n")
+            .to_http_parts();
+        let err = Csv::<Contact>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_rt::test]
+    async fn responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = Csv(vec![
+            contact("Alice", "alice@example.com"),
+            contact("Bob \"Bobby\", Jr.", "bob@example.com"),
+        ])
+        .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/csv; charset=utf-8"
+        );
+        assert!(res.headers().get(header::CONTENT_DISPOSITION).is_none());
+
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(
+            body,
+            "name,email\nAlice,alice@example.com\n\"Bob \"\"Bobby\"\", Jr.\",bob@example.com\n"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn stream_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let rows = (0..2000).map(|n| (n, "row"));
+        let res = CsvStream::iter(rows)
+            .delimiter(b'\t')
+            .has_headers(false)
+            .filename("rows.tsv")
+            .respond_to(&req);
+        assert_eq!(
+            res.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            "attachment; filename=\"rows.tsv\""
+        );
+
+        let mut body = res.into_body();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = std::future::poll_fn(|cx| Pin::new(&mut body).poll_next(cx)).await {
+            chunks.push(chunk.unwrap());
+        }
+
+        // large responses are sent in multiple chunks
+        assert!(chunks.len() > 1);
+        let body = chunks.concat();
+        assert!(body.starts_with(b"0\trow\n1\trow\n"));
+        assert!(body.ends_with(b"1999\trow\n"));
+
+        // buffered rows are sent while waiting for records
+        let rows = stream::iter([(1, "row")]).chain(stream::pending());
+        let mut body = CsvStream::new(rows).respond_to(&req).into_body();
+        let chunk = std::future::poll_fn(|cx| Pin::new(&mut body).poll_next(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap(), "1,row\n");
+    }
+}
@@ -276,7 +276,7 @@ where
     }
 }
 
-fn payload_from_bytes(bytes: Bytes) -> dev::Payload {
+pub(super) fn payload_from_bytes(bytes: Bytes) -> dev::Payload {
     let (_, mut h1_payload) = actix_http::h1::Payload::create(true);
     h1_payload.unread_data(bytes);
     dev::Payload::from(h1_payload)
@@ -15,13 +15,17 @@ use bytes::BytesMut;
 use encoding_rs::{Encoding, UTF_8};
 use futures_core::{future::LocalBoxFuture, ready};
 use futures_util::{FutureExt as _, StreamExt as _};
+use percent_encoding::percent_decode;
 use serde::{de::DeserializeOwned, Serialize};
+use url::form_urlencoded;
 
+use super::MatchContentType;
 #[cfg(feature = "__compress")]
 use crate::dev::Decompress;
 use crate::{
     body::EitherBody, error::UrlencodedError, extract::FromRequest, http::header::CONTENT_LENGTH,
-    web, Error, HttpMessage, HttpRequest, HttpResponse, Responder,
+    middleware::body_limit::request_body_limit, web, Error, HttpMessage, HttpRequest, HttpResponse,
+    Responder,
 };
 
 /// URL encoded payload extractor and responder.
@@ -32,7 +36,11 @@ use crate::{
 /// To extract typed data from a request body, the inner type `T` must implement the
 /// [`DeserializeOwned`] trait.
 ///
-/// Use [`FormConfig`] to configure extraction options.
+/// The payload is decoded using the `charset` parameter of the request's content type, which
+/// defaults to UTF-8. This allows forms posted by legacy clients in encodings such as
+/// `windows-1252` to be extracted.
+///
+/// Use [`FormConfig`] to configure extraction options, such as limits on the length of fields.
 ///
 /// ## Examples
 /// ```
@@ -130,16 +138,31 @@ where
 
     #[inline]
     fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
-        let FormConfig { limit, err_handler } = FormConfig::from_req(req).clone();
+        let config = FormConfig::from_req(req).into_owned();
+
+        let mut fut = UrlEncoded::new(req, payload).limit(config.limit);
+        fut.field_limits = config.field_limits.clone();
+        #[cfg(feature = "query-deep")]
+        let fut = fut.nested(config.nested);
 
         FormExtractFut {
-            fut: UrlEncoded::new(req, payload).limit(limit),
+            fut,
             req: req.clone(),
-            err_handler,
+            err_handler: config.err_handler,
         }
     }
 }
 
+impl<T> MatchContentType for Form<T>
+where
+    T: DeserializeOwned + 'static,
+{
+    fn match_content_type(req: &HttpRequest) -> bool {
+        req.content_type()
+            .eq_ignore_ascii_case("application/x-www-form-urlencoded")
+    }
+}
+
 type FormErrHandler = Option<Rc<dyn Fn(UrlencodedError, &HttpRequest) -> Error>>;
 
 pub struct FormExtractFut<T> {
@@ -223,7 +246,10 @@ impl<T: Serialize> Responder for Form<T> {
 #[derive(Clone)]
 pub struct FormConfig {
     limit: usize,
+    field_limits: FieldLimits,
     err_handler: FormErrHandler,
+    #[cfg(feature = "query-deep")]
+    nested: bool,
 }
 
 impl FormConfig {
@@ -233,6 +259,35 @@ impl FormConfig {
         self
     }
 
+    /// Sets the maximum length of each field's value, in bytes, after decoding.
+    ///
+    /// Forms with a longer value are rejected with an [`UrlencodedError::FieldOverflow`] error,
+    /// which contains the name of the field. By default, the length of fields is only limited by
+    /// the [payload size limit](Self::limit).
+    ///
+    /// ```
+    /// use actix_web::{web, App};
+    ///
+    /// // limit fields to 256 bytes, except for the "bio" field
+    /// let app = App::new().app_data(
+    ///     web::FormConfig::default()
+    ///         .field_limit(256)
+    ///         .field_limit_for("bio", 4096),
+    /// );
+    /// ```
+    pub fn field_limit(mut self, limit: usize) -> Self {
+        self.field_limits.default = Some(limit);
+        self
+    }
+
+    /// Sets the maximum length of the value of the field `name`, in bytes, after decoding.
+    ///
+    /// Overrides the limit set by [`field_limit()`](Self::field_limit) for that field.
+    pub fn field_limit_for(mut self, name: impl Into<String>, limit: usize) -> Self {
+        self.field_limits.set(name.into(), limit);
+        self
+    }
+
     /// Set custom error handler
     pub fn error_handler<F>(mut self, f: F) -> Self
     where
@@ -242,20 +297,65 @@ impl FormConfig {
         self
     }
 
+    /// Sets whether nested keys and lists are supported, as produced by many JavaScript libraries.
+    ///
+    /// When enabled, bodies such as `items[0][name]=x&tags[]=a&tags[]=b` are deserialized using
+    /// [`serde_qs`], which supports up to 5 levels of nesting. Disabled by default.
+    ///
+    /// ```
+    /// use actix_web::{post, web, App};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Item {
+    ///     name: String,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Order {
+    ///     items: Vec<Item>,
+    /// }
+    ///
+    /// #[post("/")]
+    /// async fn index(order: web::Form<Order>) -> String {
+    ///     format!("Ordered {} items", order.items.len())
+    /// }
+    ///
+    /// App::new()
+    ///     .app_data(web::FormConfig::default().nested(true))
+    ///     .service(index);
+    /// ```
+    #[cfg(feature = "query-deep")]
+    pub fn nested(mut self, nested: bool) -> Self {
+        self.nested = nested;
+        self
+    }
+
     /// Extract payload config from app data.
     ///
-    /// Checks both `T` and `Data<T>`, in that order, and falls back to the default payload config.
-    fn from_req(req: &HttpRequest) -> &Self {
-        req.app_data::<Self>()
+    /// Checks both `T` and `Data<T>`, in that order, and falls back to the default payload config,
+    /// limited by the `BodyLimit` middleware if present.
+    fn from_req(req: &HttpRequest) -> Cow<'_, Self> {
+        match req
+            .app_data::<Self>()
             .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
-            .unwrap_or(&DEFAULT_CONFIG)
+        {
+            Some(config) => Cow::Borrowed(config),
+            None => Cow::Owned(Self {
+                limit: request_body_limit(req).unwrap_or(DEFAULT_CONFIG.limit),
+                ..DEFAULT_CONFIG
+            }),
+        }
     }
 }
 
 /// Allow shared refs used as default.
 const DEFAULT_CONFIG: FormConfig = FormConfig {
     limit: 16_384, // 2^14 bytes (~16kB)
+    field_limits: FieldLimits::new(),
     err_handler: None,
+    #[cfg(feature = "query-deep")]
+    nested: false,
 };
 
 impl Default for FormConfig {
@@ -264,6 +364,41 @@ impl Default for FormConfig {
     }
 }
 
+/// Limits on the length of decoded form field values.
+#[derive(Debug, Clone)]
+struct FieldLimits {
+    default: Option<usize>,
+    named: Vec<(String, usize)>,
+}
+
+impl FieldLimits {
+    const fn new() -> Self {
+        Self {
+            default: None,
+            named: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.default.is_none() && self.named.is_empty()
+    }
+
+    fn set(&mut self, name: String, limit: usize) {
+        match self.named.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, l)) => *l = limit,
+            None => self.named.push((name, limit)),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<usize> {
+        self.named
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, limit)| *limit)
+            .or(self.default)
+    }
+}
+
 /// Future that resolves to some `T` when parsed from a URL encoded payload.
 ///
 /// Form can be deserialized from any type `T` that implements [`serde::Deserialize`].
@@ -271,6 +406,8 @@ impl Default for FormConfig {
 /// Returns error if:
 /// - content type is not `application/x-www-form-urlencoded`
 /// - content length is greater than [limit](UrlEncoded::limit())
+/// - a field is longer than its [limit](UrlEncoded::field_limit())
+/// - the payload can not be decoded using the `charset` of the request's content type
 pub struct UrlEncoded<T> {
     #[cfg(feature = "__compress")]
     stream: Option<Decompress<Payload>>,
@@ -279,7 +416,10 @@ pub struct UrlEncoded<T> {
 
     limit: usize,
     length: Option<usize>,
+    field_limits: FieldLimits,
     encoding: &'static Encoding,
+    #[cfg(feature = "query-deep")]
+    nested: bool,
     err: Option<UrlencodedError>,
     fut: Option<LocalBoxFuture<'static, Result<T, UrlencodedError>>>,
 }
@@ -325,6 +465,9 @@ impl<T> UrlEncoded<T> {
             stream: Some(payload),
             limit: 32_768,
             length: len,
+            field_limits: FieldLimits::new(),
+            #[cfg(feature = "query-deep")]
+            nested: false,
             fut: None,
             err: None,
         }
@@ -337,7 +480,10 @@ impl<T> UrlEncoded<T> {
             fut: None,
             err: Some(err),
             length: None,
+            field_limits: FieldLimits::new(),
             encoding: UTF_8,
+            #[cfg(feature = "query-deep")]
+            nested: false,
         }
     }
 
@@ -346,6 +492,26 @@ impl<T> UrlEncoded<T> {
         self.limit = limit;
         self
     }
+
+    /// Sets the maximum length of each field's value. See [`FormConfig::field_limit()`].
+    pub fn field_limit(mut self, limit: usize) -> Self {
+        self.field_limits.default = Some(limit);
+        self
+    }
+
+    /// Sets the maximum length of the value of the field `name`. See
+    /// [`FormConfig::field_limit_for()`].
+    pub fn field_limit_for(mut self, name: impl Into<String>, limit: usize) -> Self {
+        self.field_limits.set(name.into(), limit);
+        self
+    }
+
+    /// Sets whether nested keys and lists are supported. See [`FormConfig::nested()`].
+    #[cfg(feature = "query-deep")]
+    pub fn nested(mut self, nested: bool) -> Self {
+        self.nested = nested;
+        self
+    }
 }
 
 impl<T> Future for UrlEncoded<T>
@@ -373,6 +539,9 @@ where
 
         // future
         let encoding = self.encoding;
+        let field_limits = self.field_limits.clone();
+        #[cfg(feature = "query-deep")]
+        let nested = self.nested;
         let mut stream = self.stream.take().unwrap();
 
         self.fut = Some(
@@ -392,16 +561,16 @@ where
                     }
                 }
 
-                if encoding == UTF_8 {
-                    serde_urlencoded::from_bytes::<T>(&body).map_err(UrlencodedError::Parse)
-                } else {
-                    let body = encoding
-                        .decode_without_bom_handling_and_without_replacement(&body)
-                        .map(Cow::into_owned)
-                        .ok_or(UrlencodedError::Encoding)?;
+                let body = normalize(&body, encoding, &field_limits)?;
 
-                    serde_urlencoded::from_str::<T>(&body).map_err(UrlencodedError::Parse)
+                #[cfg(feature = "query-deep")]
+                if nested {
+                    return serde_qs::Config::new(5, false)
+                        .deserialize_bytes::<T>(&body)
+                        .map_err(UrlencodedError::ParseNested);
                 }
+
+                serde_urlencoded::from_bytes::<T>(&body).map_err(UrlencodedError::Parse)
             }
             .boxed_local(),
         );
@@ -410,6 +579,62 @@ where
     }
 }
 
+/// Decodes the fields of a URL encoded payload using `encoding` and checks their limits.
+///
+/// Returns the payload re-encoded as UTF-8, or borrowed as-is if it is already UTF-8 and there are
+/// no limits to check.
+fn normalize<'a>(
+    body: &'a [u8],
+    encoding: &'static Encoding,
+    field_limits: &FieldLimits,
+) -> Result<Cow<'a, [u8]>, UrlencodedError> {
+    if encoding == UTF_8 && field_limits.is_empty() {
+        return Ok(Cow::Borrowed(body));
+    }
+
+    let mut normalized = form_urlencoded::Serializer::new(String::with_capacity(body.len()));
+
+    for field in body.split(|&b| b == b'&').filter(|field| !field.is_empty()) {
+        let (name, value) = match field.iter().position(|&b| b == b'=') {
+            Some(idx) => (&field[..idx], &field[idx + 1..]),
+            None => (field, &[][..]),
+        };
+
+        let name = decode_component(name, encoding)?;
+        let value = decode_component(value, encoding)?;
+
+        if let Some(limit) = field_limits.get(&name) {
+            if value.len() > limit {
+                return Err(UrlencodedError::FieldOverflow { name, limit });
+            }
+        }
+
+        normalized.append_pair(&name, &value);
+    }
+
+    Ok(Cow::Owned(normalized.finish().into_bytes()))
+}
+
+/// Percent-decodes a field name or value, then decodes it using `encoding`.
+///
+/// Invalid UTF-8 is replaced, as done by `serde_urlencoded`, but other encodings must be valid.
+fn decode_component(input: &[u8], encoding: &'static Encoding) -> Result<String, UrlencodedError> {
+    let input = input
+        .iter()
+        .map(|&b| if b == b'+' { b' ' } else { b })
+        .collect::<Vec<_>>();
+    let bytes = Cow::<[u8]>::from(percent_decode(&input));
+
+    if encoding == UTF_8 {
+        return Ok(String::from_utf8_lossy(&bytes).into_owned());
+    }
+
+    encoding
+        .decode_without_bom_handling_and_without_replacement(&bytes)
+        .map(Cow::into_owned)
+        .ok_or(UrlencodedError::Encoding)
+}
+
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
@@ -556,4 +781,134 @@ mod tests {
         let err_str = s.err().unwrap().to_string();
         assert!(err_str.starts_with("URL encoded payload is larger"));
     }
+
+    #[cfg(feature = "query-deep")]
+    #[actix_rt::test]
+    async fn test_nested() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Item {
+            name: String,
+            qty: u32,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Order {
+            items: Vec<Item>,
+            tags: Vec<String>,
+        }
+
+        let body = "items%5B0%5D%5Bname%5D=big+box&items%5B0%5D%5Bqty%5D=2\
+                    &items[1][name]=lid&items[1][qty]=1&tags[]=a&tags[]=b";
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(body)
+            .to_http_parts();
+        let err = Form::<Order>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(body)
+            .app_data(FormConfig::default().nested(true))
+            .to_http_parts();
+        let Form(order) = Form::<Order>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(
+            order,
+            Order {
+                items: vec![
+                    Item {
+                        name: "big box".to_owned(),
+                        qty: 2,
+                    },
+                    Item {
+                        name: "lid".to_owned(),
+                        qty: 1,
+                    },
+                ],
+                tags: vec!["a".to_owned(), "b".to_owned()],
+            }
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_charset() {
+        // "hello=café" encoded as windows-1252
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((
+                CONTENT_TYPE,
+                "application/x-www-form-urlencoded; charset=windows-1252",
+            ))
+            .set_payload(Bytes::from_static(b"hello=caf%E9+cr%E8me&counter=1"))
+            .to_http_parts();
+
+        let Form(info) = Form::<Info>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(info.hello, "café crème");
+
+        // bytes that are not valid in the charset
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((
+                CONTENT_TYPE,
+                "application/x-www-form-urlencoded; charset=shift_jis",
+            ))
+            .set_payload(Bytes::from_static(b"hello=%82&counter=1"))
+            .to_http_parts();
+
+        let err = UrlEncoded::<Info>::new(&req, &mut pl).await.unwrap_err();
+        assert!(matches!(err, UrlencodedError::Encoding));
+    }
+
+    #[actix_rt::test]
+    async fn test_field_limits() {
+        let config = FormConfig::default()
+            .field_limit(5)
+            .field_limit_for("counter", 2);
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(Bytes::from_static(b"hello=w%C3%B6rl&counter=12"))
+            .app_data(config.clone())
+            .to_http_parts();
+
+        let Form(info) = Form::<Info>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(info.hello, "wörl");
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(Bytes::from_static(b"hello=world&counter=123"))
+            .app_data(config.clone())
+            .to_http_parts();
+
+        let err = Form::<Info>::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        match err.as_error::<UrlencodedError>().unwrap() {
+            UrlencodedError::FieldOverflow { name, limit } => {
+                assert_eq!(name, "counter");
+                assert_eq!(*limit, 2);
+            }
+            err => panic!("unexpected error: {err}"),
+        }
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(Bytes::from_static(b"hello=w%C3%B6rld&counter=1"))
+            .to_http_parts();
+
+        let err = UrlEncoded::<Info>::new(&req, &mut pl)
+            .field_limit(5)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "URL encoded field `hello` is longer than allowed (limit: 5 bytes)."
+        );
+    }
 }
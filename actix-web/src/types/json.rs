@@ -1,6 +1,7 @@
 //! For JSON helper documentation, see [`Json`].
 
 use std::{
+    borrow::Cow,
     fmt,
     future::Future,
     marker::PhantomData,
@@ -14,7 +15,9 @@ use actix_http::Payload;
 use bytes::BytesMut;
 use futures_core::{ready, Stream as _};
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Error as JsonError;
 
+use super::MatchContentType;
 #[cfg(feature = "__compress")]
 use crate::dev::Decompress;
 use crate::{
@@ -22,6 +25,7 @@ use crate::{
     error::{Error, JsonPayloadError},
     extract::FromRequest,
     http::header::{ContentLength, Header as _},
+    middleware::body_limit::request_body_limit,
     request::HttpRequest,
     web, HttpMessage, HttpResponse, Responder,
 };
@@ -34,7 +38,9 @@ use crate::{
 /// To extract typed data from a request body, the inner type `T` must implement the
 /// [`serde::Deserialize`] trait.
 ///
-/// Use [`JsonConfig`] to configure extraction options.
+/// Use [`JsonConfig`] to configure extraction options. When the `simd-json` crate feature is
+/// enabled, payloads are deserialized using [`simd-json`](https://docs.rs/simd-json) instead of
+/// `serde_json`.
 ///
 /// ```
 /// use actix_web::{post, web, App};
@@ -157,6 +163,21 @@ impl<T: DeserializeOwned> FromRequest for Json<T> {
     }
 }
 
+impl<T: DeserializeOwned> MatchContentType for Json<T> {
+    fn match_content_type(req: &HttpRequest) -> bool {
+        let config = JsonConfig::from_req(req);
+
+        match req.mime_type() {
+            Ok(Some(mime)) => {
+                mime.subtype() == mime::JSON
+                    || mime.suffix() == Some(mime::JSON)
+                    || config.content_type.as_ref().is_some_and(|pred| pred(mime))
+            }
+            _ => false,
+        }
+    }
+}
+
 type JsonErrorHandler = Option<Arc<dyn Fn(JsonPayloadError, &HttpRequest) -> Error + Send + Sync>>;
 
 pub struct JsonExtractFut<T> {
@@ -230,10 +251,10 @@ impl<T: DeserializeOwned> Future for JsonExtractFut<T> {
 /// ```
 #[derive(Clone)]
 pub struct JsonConfig {
-    limit: usize,
-    err_handler: JsonErrorHandler,
-    content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
-    content_type_required: bool,
+    pub(super) limit: usize,
+    pub(super) err_handler: JsonErrorHandler,
+    pub(super) content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    pub(super) content_type_required: bool,
 }
 
 impl JsonConfig {
@@ -268,11 +289,18 @@ impl JsonConfig {
     }
 
     /// Extract payload config from app data. Check both `T` and `Data<T>`, in that order, and fall
-    /// back to the default payload config.
-    fn from_req(req: &HttpRequest) -> &Self {
-        req.app_data::<Self>()
+    /// back to the default payload config, limited by the `BodyLimit` middleware if present.
+    pub(super) fn from_req(req: &HttpRequest) -> Cow<'_, Self> {
+        match req
+            .app_data::<Self>()
             .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
-            .unwrap_or(&DEFAULT_CONFIG)
+        {
+            Some(config) => Cow::Borrowed(config),
+            None => Cow::Owned(Self {
+                limit: request_body_limit(req).unwrap_or(DEFAULT_LIMIT),
+                ..DEFAULT_CONFIG
+            }),
+        }
     }
 }
 
@@ -367,7 +395,7 @@ impl<T: DeserializeOwned> JsonBody<T> {
             limit: DEFAULT_LIMIT,
             length,
             payload,
-            buf: BytesMut::with_capacity(8192),
+            buf: BytesMut::new(),
             _res: PhantomData,
         }
     }
@@ -423,13 +451,15 @@ impl<T: DeserializeOwned> Future for JsonBody<T> {
                         let buf_len = buf.len() + chunk.len();
                         if buf_len > *limit {
                             return Poll::Ready(Err(JsonPayloadError::Overflow { limit: *limit }));
+                        } else if buf.is_empty() {
+                            // takes over the chunk's buffer when it is not shared, avoiding a copy
+                            *buf = BytesMut::from(chunk);
                         } else {
                             buf.extend_from_slice(&chunk);
                         }
                     }
                     None => {
-                        let json = serde_json::from_slice::<T>(buf)
-                            .map_err(JsonPayloadError::Deserialize)?;
+                        let json = deserialize::<T>(buf).map_err(JsonPayloadError::Deserialize)?;
                         return Poll::Ready(Ok(json));
                     }
                 }
@@ -439,6 +469,18 @@ impl<T: DeserializeOwned> Future for JsonBody<T> {
     }
 }
 
+/// Deserializes a payload collected into `buf`.
+fn deserialize<T: DeserializeOwned>(buf: &mut BytesMut) -> Result<T, JsonError> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "simd-json")] {
+            // parses in place
+            simd_json::serde::from_slice(buf).map_err(serde::de::Error::custom)
+        } else {
+            serde_json::from_slice(buf)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
@@ -654,6 +696,47 @@ mod tests {
         );
     }
 
+    #[actix_rt::test]
+    async fn test_json_body_chunks() {
+        fn chunked_payload(chunks: &'static [&'static str]) -> Payload {
+            let stream = futures_util::stream::iter(
+                chunks
+                    .iter()
+                    .map(|chunk| Ok(Bytes::from_static(chunk.as_bytes()))),
+            );
+            Payload::from(Box::pin(stream) as actix_http::BoxedPayloadStream)
+        }
+
+        let req = TestRequest::default()
+            .insert_header(header::ContentType::json())
+            .to_http_request();
+
+        let mut pl = chunked_payload(&["{\"name\"", ": \"te", "st\"}"]);
+        let json = JsonBody::<MyObject>::new(&req, &mut pl, None, true).await;
+        assert_eq!(
+            json.unwrap(),
+            MyObject {
+                name: "test".to_owned()
+            }
+        );
+
+        let mut pl = chunked_payload(&["{\"name\": ", "\"test\"}"]);
+        let json = JsonBody::<MyObject>::new(&req, &mut pl, None, true)
+            .limit(15)
+            .await;
+        assert!(json_eq(
+            json.err().unwrap(),
+            JsonPayloadError::Overflow { limit: 15 }
+        ));
+
+        let mut pl = chunked_payload(&["{\"name\": ", "\"test\""]);
+        let json = JsonBody::<MyObject>::new(&req, &mut pl, None, true).await;
+        assert!(matches!(
+            json.err().unwrap(),
+            JsonPayloadError::Deserialize(_)
+        ));
+    }
+
     #[actix_rt::test]
     async fn test_with_json_and_bad_content_type() {
         let (req, mut pl) = TestRequest::default()
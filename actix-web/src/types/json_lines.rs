@@ -0,0 +1,492 @@
+//! For newline-delimited JSON helper documentation, see [`JsonLines`] and [`JsonLinesStream`].
+
+use std::{
+    fmt,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_http::Payload;
+use actix_utils::future::{ready, Ready};
+use bytes::{BufMut as _, Bytes, BytesMut};
+use futures_core::{ready, Stream};
+use pin_project_lite::pin_project;
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "__compress")]
+use crate::dev::Decompress;
+use crate::{
+    body::{BodySize, BoxBody, MessageBody},
+    error::{Error, JsonPayloadError},
+    extract::FromRequest,
+    request::HttpRequest,
+    web::JsonConfig,
+    HttpMessage as _, HttpResponse, Responder,
+};
+
+/// Content type of newline-delimited JSON responses.
+const CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Number of bytes that are buffered before a chunk is sent, by default.
+const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Newline-delimited JSON responder.
+///
+/// Serializes each item of a stream as a line of JSON and streams the lines as the response body
+/// with the `application/x-ndjson` content type, as used by log ingestion and data export APIs.
+///
+/// Lines are collected into chunks of up to [`buffer_size()`](Self::buffer_size()) bytes before
+/// being sent. Whenever the stream has no item ready, the buffered lines are sent immediately, so
+/// slow streams do not delay lines that have already been produced.
+///
+/// If an item fails to serialize, the response body ends with an error and the connection is
+/// closed.
+///
+/// To read newline-delimited JSON request bodies, use [`JsonLinesStream`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web};
+/// use futures_util::stream;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Event {
+///     id: u32,
+/// }
+///
+/// #[get("/events")]
+/// async fn export() -> web::JsonLines<impl futures_core::Stream<Item = Event>> {
+///     web::JsonLines::new(stream::iter((1..=3).map(|id| Event { id })))
+/// }
+///
+/// // Response body:
+/// // {"id":1}
+/// // {"id":2}
+/// // {"id":3}
+/// ```
+pub struct JsonLines<S> {
+    stream: S,
+    buffer_size: usize,
+}
+
+impl<S> JsonLines<S> {
+    /// Constructs a newline-delimited JSON responder from a stream of serializable items.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+        }
+    }
+
+    /// Sets the number of bytes that are buffered before a chunk of lines is sent.
+    ///
+    /// The default is 8KiB. A size of 0 sends each line as soon as its item is serialized.
+    pub fn buffer_size(mut self, bytes: usize) -> Self {
+        self.buffer_size = bytes;
+        self
+    }
+
+    /// Unwraps into inner stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> fmt::Debug for JsonLines<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLines")
+            .field("buffer_size", &self.buffer_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Responder for JsonLines<S>
+where
+    S: Stream + 'static,
+    S::Item: Serialize,
+{
+    type Body = BoxBody;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse<Self::Body> {
+        HttpResponse::Ok()
+            .content_type(CONTENT_TYPE)
+            .body(JsonLinesBody {
+                stream: self.stream,
+                buf: BytesMut::new(),
+                buffer_size: self.buffer_size,
+                done: false,
+            })
+    }
+}
+
+pin_project! {
+    struct JsonLinesBody<S> {
+        #[pin]
+        stream: S,
+        buf: BytesMut,
+        buffer_size: usize,
+        done: bool,
+    }
+}
+
+impl<S> MessageBody for JsonLinesBody<S>
+where
+    S: Stream,
+    S::Item: Serialize,
+{
+    type Error = JsonPayloadError;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+
+        while !*this.done {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    serde_json::to_writer(this.buf.writer(), &item)
+                        .map_err(JsonPayloadError::Serialize)?;
+                    this.buf.put_u8(b'\n');
+
+                    if this.buf.len() >= *this.buffer_size {
+                        return Poll::Ready(Some(Ok(this.buf.split().freeze())));
+                    }
+                }
+
+                Poll::Ready(None) => *this.done = true,
+
+                // send buffered lines instead of waiting for the next item
+                Poll::Pending if this.buf.is_empty() => return Poll::Pending,
+                Poll::Pending => return Poll::Ready(Some(Ok(this.buf.split().freeze()))),
+            }
+        }
+
+        if this.buf.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Ready(Some(Ok(this.buf.split().freeze())))
+        }
+    }
+}
+
+/// Newline-delimited JSON extractor.
+///
+/// Deserializes each line of the request body as a `T`, yielding items as a [`Stream`] while the
+/// body is received, so that large uploads do not need to be buffered in memory. Empty lines are
+/// skipped.
+///
+/// Extraction fails if the request does not have one of the `application/x-ndjson`,
+/// `application/ndjson`, `application/jsonl`, or `application/x-jsonlines` content types. The
+/// [`JsonConfig`] of the app is used to allow other content types, to set the maximum size of each
+/// line, and to handle this error.
+///
+/// Each item is an error if its line is invalid or larger than the limit, or if the body fails to
+/// be read. The stream ends after the first error.
+///
+/// To respond with newline-delimited JSON, use [`JsonLines`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web, Error};
+/// use futures_util::StreamExt as _;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct LogEntry {
+///     level: String,
+///     message: String,
+/// }
+///
+/// #[post("/ingest")]
+/// async fn ingest(mut entries: web::JsonLinesStream<LogEntry>) -> Result<String, Error> {
+///     let mut count = 0;
+///
+///     while let Some(entry) = entries.next().await {
+///         let entry = entry?;
+///         println!("[{}] {}", entry.level, entry.message);
+///         count += 1;
+///     }
+///
+///     Ok(format!("ingested {count} entries"))
+/// }
+/// ```
+pub struct JsonLinesStream<T> {
+    #[cfg(feature = "__compress")]
+    payload: Decompress<Payload>,
+    #[cfg(not(feature = "__compress"))]
+    payload: Payload,
+    buf: BytesMut,
+    limit: usize,
+    done: bool,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> fmt::Debug for JsonLinesStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLinesStream")
+            .field("limit", &self.limit)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for JsonLinesStream<T> {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = JsonConfig::from_req(req);
+
+        let can_parse = match (config.content_type_required, req.mime_type()) {
+            (true, Ok(Some(mime))) => {
+                matches!(
+                    mime.subtype().as_str(),
+                    "x-ndjson" | "ndjson" | "jsonl" | "x-jsonlines"
+                ) || config
+                    .content_type
+                    .as_deref()
+                    .is_some_and(|predicate| predicate(mime))
+            }
+            (true, _) => false,
+            (false, _) => true,
+        };
+
+        if !can_parse {
+            log::debug!(
+                "Failed to extract JsonLinesStream from payload. Request path: {}",
+                req.path()
+            );
+
+            let err = JsonPayloadError::ContentType;
+            return ready(Err(match config.err_handler.as_ref() {
+                Some(err_handler) => err_handler(err, req),
+                None => err.into(),
+            }));
+        }
+
+        let payload = {
+            cfg_if::cfg_if! {
+                if #[cfg(feature = "__compress")] {
+                    Decompress::from_headers(payload.take(), req.headers())
+                } else {
+                    payload.take()
+                }
+            }
+        };
+
+        ready(Ok(JsonLinesStream {
+            payload,
+            buf: BytesMut::new(),
+            limit: config.limit,
+            done: false,
+            _item: PhantomData,
+        }))
+    }
+}
+
+impl<T: DeserializeOwned> JsonLinesStream<T> {
+    /// Parses a line, or returns `None` if it is empty.
+    fn parse(&mut self, line: &[u8]) -> Option<Result<T, JsonPayloadError>> {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return None;
+        }
+
+        let res = serde_json::from_slice(line).map_err(JsonPayloadError::Deserialize);
+        self.done |= res.is_err();
+        Some(res)
+    }
+
+    fn fail(&mut self, err: JsonPayloadError) -> Poll<Option<Result<T, JsonPayloadError>>> {
+        self.done = true;
+        self.buf.clear();
+        Poll::Ready(Some(Err(err)))
+    }
+}
+
+impl<T: DeserializeOwned> Stream for JsonLinesStream<T> {
+    type Item = Result<T, JsonPayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            // yield buffered lines before reading more of the body
+            if let Some(idx) = this.buf.iter().position(|&b| b == b'\n') {
+                if idx > this.limit {
+                    return this.fail(JsonPayloadError::Overflow { limit: this.limit });
+                }
+
+                let line = this.buf.split_to(idx + 1);
+                match this.parse(&line) {
+                    Some(res) => return Poll::Ready(Some(res)),
+                    None => continue,
+                }
+            }
+
+            if this.buf.len() > this.limit {
+                return this.fail(JsonPayloadError::Overflow { limit: this.limit });
+            }
+
+            match ready!(Pin::new(&mut this.payload).poll_next(cx)) {
+                Some(Ok(chunk)) => this.buf.extend_from_slice(&chunk),
+                Some(Err(err)) => return this.fail(err.into()),
+
+                // last line does not need to end with a newline
+                None => {
+                    this.done = true;
+                    let line = this.buf.split();
+                    return Poll::Ready(this.parse(&line));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures_util::{stream, StreamExt as _};
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{
+        body,
+        http::{header, StatusCode},
+        test::TestRequest,
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        id: u32,
+    }
+
+    #[actix_rt::test]
+    async fn responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let events = stream::iter((1..=3).map(|id| Event { id }));
+        let res = JsonLines::new(events).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/x-ndjson"
+        );
+
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n");
+    }
+
+    #[actix_rt::test]
+    async fn responder_buffering() {
+        async fn next_chunk(body: &mut BoxBody) -> Option<Bytes> {
+            std::future::poll_fn(|cx| Pin::new(&mut *body).poll_next(cx))
+                .await
+                .map(Result::unwrap)
+        }
+
+        let req = TestRequest::default().to_http_request();
+        let events = || stream::iter((1..=3).map(|id| Event { id }));
+
+        // ready items are sent in one chunk
+        let mut body = JsonLines::new(events()).respond_to(&req).into_body();
+        assert_eq!(next_chunk(&mut body).await.unwrap().len(), 27);
+        assert!(next_chunk(&mut body).await.is_none());
+
+        let mut body = JsonLines::new(events())
+            .buffer_size(0)
+            .respond_to(&req)
+            .into_body();
+        for id in 1..=3 {
+            let chunk = next_chunk(&mut body).await.unwrap();
+            assert_eq!(chunk, format!("{{\"id\":{id}}}\n"));
+        }
+        assert!(next_chunk(&mut body).await.is_none());
+
+        // buffered lines are sent while waiting for items
+        let events = stream::iter([Event { id: 1 }]).chain(stream::pending());
+        let mut body = JsonLines::new(events).respond_to(&req).into_body();
+        assert_eq!(next_chunk(&mut body).await.unwrap(), "{\"id\":1}\n");
+    }
+
+    fn ndjson_request(
+        req: TestRequest,
+        chunks: &'static [&'static [u8]],
+    ) -> (HttpRequest, Payload) {
+        let (req, _) = req
+            .insert_header((header::CONTENT_TYPE, "application/x-ndjson"))
+            .to_http_parts();
+
+        let stream = stream::iter(chunks.iter().map(|chunk| Ok(Bytes::from_static(chunk))));
+        let payload = Payload::from(Box::pin(stream) as actix_http::BoxedPayloadStream);
+
+        (req, payload)
+    }
+
+    #[actix_rt::test]
+    async fn extractor() {
+        let (req, mut payload) = ndjson_request(
+            TestRequest::default(),
+            &[b"{\"id\":1}\n{\"i", b"d\":2}\r\n\n  \n", b"{\"id\":3}"],
+        );
+
+        let events = JsonLinesStream::<Event>::from_request(&req, &mut payload)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(events, [Event { id: 1 }, Event { id: 2 }, Event { id: 3 }]);
+    }
+
+    #[actix_rt::test]
+    async fn extractor_errors() {
+        let (req, mut payload) = TestRequest::default()
+            .insert_header(header::ContentType::json())
+            .to_http_parts();
+        let err = JsonLinesStream::<Event>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+
+        // stream ends after invalid lines
+        let (req, mut payload) =
+            ndjson_request(TestRequest::default(), &[b"{\"id\":1}\nnope\n{\"id\":3}\n"]);
+        let mut events = JsonLinesStream::<Event>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(events.next().await.unwrap().unwrap(), Event { id: 1 });
+        assert!(matches!(
+            events.next().await.unwrap(),
+            Err(JsonPayloadError::Deserialize(_))
+        ));
+        assert!(events.next().await.is_none());
+
+        // lines are limited by JsonConfig
+        let (req, mut payload) = ndjson_request(
+            TestRequest::default().app_data(JsonConfig::default().limit(10)),
+            &[b"{\"id\":1}\n{\"id\":", b"1000000}\n"],
+        );
+        let mut events = JsonLinesStream::<Event>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(events.next().await.unwrap().unwrap(), Event { id: 1 });
+        assert!(matches!(
+            events.next().await.unwrap(),
+            Err(JsonPayloadError::Overflow { limit: 10 })
+        ));
+        assert!(events.next().await.is_none());
+    }
+}
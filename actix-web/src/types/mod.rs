@@ -1,23 +1,57 @@
 //! Common extractors and responders.
 
+#[cfg(feature = "tempfile")]
+mod body_file;
+#[cfg(feature = "csv")]
+mod csv;
 mod either;
 mod form;
 mod header;
 mod html;
 mod json;
+mod json_lines;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+mod negotiate;
+mod one_of;
+mod pagination;
 mod path;
 mod payload;
+#[cfg(feature = "protobuf")]
+mod protobuf;
 mod query;
+#[cfg(feature = "query-deep")]
+mod query_deep;
+mod ranges;
 mod readlines;
+#[cfg(feature = "validator")]
+mod valid;
 
+#[cfg(feature = "tempfile")]
+pub use self::body_file::{BodyFile, BodyFileConfig};
+#[cfg(feature = "csv")]
+pub use self::csv::{Csv, CsvConfig, CsvStream};
+#[cfg(feature = "msgpack")]
+pub use self::msgpack::{MsgPack, MsgPackBody, MsgPackConfig};
+#[cfg(feature = "protobuf")]
+pub use self::protobuf::{Protobuf, ProtobufConfig};
+#[cfg(feature = "query-deep")]
+pub use self::query_deep::{QueryDeep, QueryDeepConfig};
+#[cfg(feature = "validator")]
+pub use self::valid::{Valid, ValidConfig};
 pub use self::{
     either::Either,
     form::{Form, FormConfig, UrlEncoded},
     header::Header,
     html::Html,
     json::{Json, JsonBody, JsonConfig},
+    json_lines::{JsonLines, JsonLinesStream},
+    negotiate::{Accepts, Negotiate},
+    one_of::{MatchContentType, OneOf, OneOfAlternatives},
+    pagination::{CursorPagination, Pagination, PaginationConfig},
     path::{Path, PathConfig},
     payload::{Payload, PayloadConfig},
     query::{Query, QueryConfig},
+    ranges::{PartialContent, Ranges},
     readlines::Readlines,
 };
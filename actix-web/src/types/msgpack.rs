@@ -0,0 +1,612 @@
+//! For MessagePack helper documentation, see [`MsgPack`].
+
+use std::{
+    borrow::Cow,
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    ops,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use actix_http::Payload;
+use bytes::BytesMut;
+use futures_core::{ready, Stream as _};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::MatchContentType;
+#[cfg(feature = "__compress")]
+use crate::dev::Decompress;
+use crate::{
+    body::EitherBody,
+    error::{Error, MsgPackPayloadError},
+    extract::FromRequest,
+    http::header::{ContentLength, Header as _},
+    middleware::body_limit::request_body_limit,
+    request::HttpRequest,
+    web, HttpMessage, HttpResponse, Responder,
+};
+
+/// Content type of MessagePack responses.
+const CONTENT_TYPE: &str = "application/msgpack";
+
+/// MessagePack extractor and responder.
+///
+/// `MsgPack` has two uses: MessagePack responses, and extracting typed data from MessagePack
+/// request payloads. It mirrors [`Json`](super::Json), including its configuration options and
+/// error handling.
+///
+/// # Extractor
+/// To extract typed data from a request body, the inner type `T` must implement the
+/// [`serde::Deserialize`] trait. Structs can be encoded either as maps or as arrays of fields.
+///
+/// Use [`MsgPackConfig`] to configure extraction options.
+///
+/// ```
+/// use actix_web::{post, web, App};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// /// deserialize `Info` from request's body
+/// #[post("/")]
+/// async fn index(info: web::MsgPack<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+/// ```
+///
+/// # Responder
+/// The `MsgPack` type serializes its value as an `application/msgpack` response. The type `T`
+/// must implement [`serde::Serialize`]. Structs are encoded as maps, keyed by field name.
+///
+/// ```
+/// use actix_web::{post, web, HttpRequest};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Info {
+///     name: String,
+/// }
+///
+/// #[post("/{name}")]
+/// async fn index(req: HttpRequest) -> web::MsgPack<Info> {
+///     web::MsgPack(Info {
+///         name: req.match_info().get("name").unwrap().to_owned(),
+///     })
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MsgPack<T>(pub T);
+
+impl<T> MsgPack<T> {
+    /// Unwrap into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for MsgPack<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for MsgPack<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for MsgPack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<T: Serialize> Serialize for MsgPack<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Creates response with OK status code, MessagePack content type header, and serialized
+/// payload.
+///
+/// If serialization failed, creates an Internal Server Error response.
+impl<T: Serialize> Responder for MsgPack<T> {
+    type Body = EitherBody<Vec<u8>>;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse<Self::Body> {
+        match rmp_serde::to_vec_named(&self.0) {
+            Ok(body) => match HttpResponse::Ok()
+                .content_type(CONTENT_TYPE)
+                .message_body(body)
+            {
+                Ok(res) => res.map_into_left_body(),
+                Err(err) => HttpResponse::from_error(err).map_into_right_body(),
+            },
+
+            Err(err) => {
+                HttpResponse::from_error(MsgPackPayloadError::Serialize(err)).map_into_right_body()
+            }
+        }
+    }
+}
+
+/// See [here](#extractor) for example of usage as an extractor.
+impl<T: DeserializeOwned> FromRequest for MsgPack<T> {
+    type Error = Error;
+    type Future = MsgPackExtractFut<T>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = MsgPackConfig::from_req(req);
+
+        let limit = config.limit;
+        let ctype_required = config.content_type_required;
+        let ctype_fn = config.content_type.as_deref();
+        let err_handler = config.err_handler.clone();
+
+        MsgPackExtractFut {
+            req: Some(req.clone()),
+            fut: MsgPackBody::new(req, payload, ctype_fn, ctype_required).limit(limit),
+            err_handler,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> MatchContentType for MsgPack<T> {
+    fn match_content_type(req: &HttpRequest) -> bool {
+        let config = MsgPackConfig::from_req(req);
+
+        match req.mime_type() {
+            Ok(Some(mime)) => {
+                matches!(
+                    mime.subtype().as_str(),
+                    "msgpack" | "x-msgpack" | "vnd.msgpack"
+                ) || config.content_type.as_ref().is_some_and(|pred| pred(mime))
+            }
+            _ => false,
+        }
+    }
+}
+
+type MsgPackErrorHandler =
+    Option<Arc<dyn Fn(MsgPackPayloadError, &HttpRequest) -> Error + Send + Sync>>;
+
+pub struct MsgPackExtractFut<T> {
+    req: Option<HttpRequest>,
+    fut: MsgPackBody<T>,
+    err_handler: MsgPackErrorHandler,
+}
+
+impl<T: DeserializeOwned> Future for MsgPackExtractFut<T> {
+    type Output = Result<MsgPack<T>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let res = ready!(Pin::new(&mut this.fut).poll(cx));
+
+        let res = match res {
+            Err(err) => {
+                let req = this.req.take().unwrap();
+                log::debug!(
+                    "Failed to deserialize MsgPack from payload. Request path: {}",
+                    req.path()
+                );
+
+                if let Some(err_handler) = this.err_handler.as_ref() {
+                    Err((*err_handler)(err, &req))
+                } else {
+                    Err(err.into())
+                }
+            }
+            Ok(data) => Ok(MsgPack(data)),
+        };
+
+        Poll::Ready(res)
+    }
+}
+
+/// `MsgPack` extractor configuration.
+///
+/// # Examples
+/// ```
+/// use actix_web::{error, post, web, App, FromRequest, HttpResponse};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     name: String,
+/// }
+///
+/// // `MsgPack` extraction is bound by custom `MsgPackConfig` applied to App.
+/// #[post("/")]
+/// async fn index(info: web::MsgPack<Info>) -> String {
+///     format!("Welcome {}!", info.name)
+/// }
+///
+/// // custom `MsgPack` extractor configuration
+/// let msgpack_cfg = web::MsgPackConfig::default()
+///     // limit request payload size
+///     .limit(4096)
+///     // also accept application/octet-stream content type
+///     .content_type(|mime| mime == mime::APPLICATION_OCTET_STREAM)
+///     // use custom error handler
+///     .error_handler(|err, req| {
+///         error::InternalError::from_response(err, HttpResponse::Conflict().into()).into()
+///     });
+///
+/// App::new()
+///     .app_data(msgpack_cfg)
+///     .service(index);
+/// ```
+#[derive(Clone)]
+pub struct MsgPackConfig {
+    limit: usize,
+    err_handler: MsgPackErrorHandler,
+    content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    content_type_required: bool,
+}
+
+impl MsgPackConfig {
+    /// Set maximum accepted payload size. By default this limit is 2MB.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set custom error handler.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(MsgPackPayloadError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Set predicate for allowed content types, in addition to MessagePack content types.
+    pub fn content_type<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(mime::Mime) -> bool + Send + Sync + 'static,
+    {
+        self.content_type = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Sets whether or not the request must have a `Content-Type` header to be parsed.
+    pub fn content_type_required(mut self, content_type_required: bool) -> Self {
+        self.content_type_required = content_type_required;
+        self
+    }
+
+    /// Extract payload config from app data. Check both `T` and `Data<T>`, in that order, and fall
+    /// back to the default payload config, limited by the `BodyLimit` middleware if present.
+    fn from_req(req: &HttpRequest) -> Cow<'_, Self> {
+        match req
+            .app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+        {
+            Some(config) => Cow::Borrowed(config),
+            None => Cow::Owned(Self {
+                limit: request_body_limit(req).unwrap_or(DEFAULT_LIMIT),
+                ..DEFAULT_CONFIG
+            }),
+        }
+    }
+}
+
+const DEFAULT_LIMIT: usize = 2_097_152; // 2 mb
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: MsgPackConfig = MsgPackConfig {
+    limit: DEFAULT_LIMIT,
+    err_handler: None,
+    content_type: None,
+    content_type_required: true,
+};
+
+impl Default for MsgPackConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+/// Future that resolves to some `T` when parsed from a MessagePack payload.
+///
+/// Can deserialize any type `T` that implements [`Deserialize`][serde::Deserialize].
+///
+/// Returns error if:
+/// - `Content-Type` is not `application/msgpack`, `application/x-msgpack`, or
+///   `application/vnd.msgpack` when `ctype_required` (passed to [`new`][Self::new]) is `true`.
+/// - `Content-Length` is greater than [limit](MsgPackBody::limit()).
+/// - The payload, when consumed, is not valid MessagePack.
+pub enum MsgPackBody<T> {
+    Error(Option<MsgPackPayloadError>),
+    Body {
+        limit: usize,
+        /// Length as reported by `Content-Length` header, if present.
+        length: Option<usize>,
+        #[cfg(feature = "__compress")]
+        payload: Decompress<Payload>,
+        #[cfg(not(feature = "__compress"))]
+        payload: Payload,
+        buf: BytesMut,
+        _res: PhantomData<T>,
+    },
+}
+
+impl<T> Unpin for MsgPackBody<T> {}
+
+impl<T: DeserializeOwned> MsgPackBody<T> {
+    /// Create a new future to decode a MessagePack request payload.
+    #[allow(clippy::borrow_interior_mutable_const)]
+    pub fn new(
+        req: &HttpRequest,
+        payload: &mut Payload,
+        ctype_fn: Option<&(dyn Fn(mime::Mime) -> bool + Send + Sync)>,
+        ctype_required: bool,
+    ) -> Self {
+        // check content-type
+        let can_parse = match (ctype_required, req.mime_type()) {
+            (true, Ok(Some(mime))) => {
+                matches!(
+                    mime.subtype().as_str(),
+                    "msgpack" | "x-msgpack" | "vnd.msgpack"
+                ) || ctype_fn.is_some_and(|predicate| predicate(mime))
+            }
+
+            // if content-type is expected but not parsable as mime type, bail
+            (true, _) => false,
+
+            // if content-type validation is disabled, assume payload is MessagePack
+            // even when content-type header is missing or invalid mime type
+            (false, _) => true,
+        };
+
+        if !can_parse {
+            return MsgPackBody::Error(Some(MsgPackPayloadError::ContentType));
+        }
+
+        let length = ContentLength::parse(req).ok().map(|x| x.0);
+
+        let payload = {
+            cfg_if::cfg_if! {
+                if #[cfg(feature = "__compress")] {
+                    Decompress::from_headers(payload.take(), req.headers())
+                } else {
+                    payload.take()
+                }
+            }
+        };
+
+        MsgPackBody::Body {
+            limit: DEFAULT_LIMIT,
+            length,
+            payload,
+            buf: BytesMut::with_capacity(8192),
+            _res: PhantomData,
+        }
+    }
+
+    /// Set maximum accepted payload size. The default limit is 2MB.
+    pub fn limit(self, limit: usize) -> Self {
+        match self {
+            MsgPackBody::Body {
+                length,
+                payload,
+                buf,
+                ..
+            } => {
+                if let Some(len) = length {
+                    if len > limit {
+                        return MsgPackBody::Error(Some(
+                            MsgPackPayloadError::OverflowKnownLength { length: len, limit },
+                        ));
+                    }
+                }
+
+                MsgPackBody::Body {
+                    limit,
+                    length,
+                    payload,
+                    buf,
+                    _res: PhantomData,
+                }
+            }
+            MsgPackBody::Error(err) => MsgPackBody::Error(err),
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Future for MsgPackBody<T> {
+    type Output = Result<T, MsgPackPayloadError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        match this {
+            MsgPackBody::Body {
+                limit,
+                buf,
+                payload,
+                ..
+            } => loop {
+                let res = ready!(Pin::new(&mut *payload).poll_next(cx));
+                match res {
+                    Some(chunk) => {
+                        let chunk = chunk?;
+                        let buf_len = buf.len() + chunk.len();
+                        if buf_len > *limit {
+                            return Poll::Ready(Err(MsgPackPayloadError::Overflow {
+                                limit: *limit,
+                            }));
+                        } else {
+                            buf.extend_from_slice(&chunk);
+                        }
+                    }
+                    None => {
+                        let data = rmp_serde::from_slice::<T>(buf)
+                            .map_err(MsgPackPayloadError::Deserialize)?;
+                        return Poll::Ready(Ok(data));
+                    }
+                }
+            },
+            MsgPackBody::Error(err) => Poll::Ready(Err(err.take().unwrap())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{
+        body,
+        error::InternalError,
+        http::{header, StatusCode},
+        test::TestRequest,
+    };
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct MyObject {
+        name: String,
+    }
+
+    fn my_object() -> MyObject {
+        MyObject {
+            name: "test".to_owned(),
+        }
+    }
+
+    fn msgpack_request(body: Vec<u8>) -> TestRequest {
+        TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/msgpack"))
+            .insert_header((header::CONTENT_LENGTH, body.len()))
+            .set_payload(body)
+    }
+
+    #[actix_rt::test]
+    async fn test_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = MsgPack(my_object()).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/msgpack"
+        );
+
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, rmp_serde::to_vec_named(&my_object()).unwrap());
+    }
+
+    #[actix_rt::test]
+    async fn test_extract() {
+        // structs encoded as maps
+        let (req, mut pl) =
+            msgpack_request(rmp_serde::to_vec_named(&my_object()).unwrap()).to_http_parts();
+        let s = MsgPack::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(s.into_inner(), my_object());
+
+        // structs encoded as arrays
+        let (req, mut pl) =
+            msgpack_request(rmp_serde::to_vec(&my_object()).unwrap()).to_http_parts();
+        let s = MsgPack::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(s.name, "test");
+
+        let (req, mut pl) = msgpack_request(rmp_serde::to_vec(&my_object()).unwrap())
+            .insert_header((header::CONTENT_TYPE, "application/vnd.msgpack"))
+            .to_http_parts();
+        let s = MsgPack::<MyObject>::from_request(&req, &mut pl).await;
+        assert!(s.is_ok());
+    }
+
+    #[actix_rt::test]
+    async fn test_extract_errors() {
+        fn payload_error(err: Error) -> &'static MsgPackPayloadError {
+            let err = Box::leak(Box::new(err));
+            err.as_error::<MsgPackPayloadError>().unwrap()
+        }
+
+        let (req, mut pl) = msgpack_request(rmp_serde::to_vec(&my_object()).unwrap())
+            .insert_header(header::ContentType::json())
+            .to_http_parts();
+        let err = MsgPack::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            payload_error(err),
+            MsgPackPayloadError::ContentType
+        ));
+
+        let (req, mut pl) = msgpack_request(rmp_serde::to_vec(&my_object()).unwrap())
+            .insert_header(header::ContentType::json())
+            .app_data(MsgPackConfig::default().content_type(|mime| mime == mime::APPLICATION_JSON))
+            .to_http_parts();
+        let s = MsgPack::<MyObject>::from_request(&req, &mut pl).await;
+        assert!(s.is_ok());
+
+        let (req, mut pl) = msgpack_request(rmp_serde::to_vec(&my_object()).unwrap())
+            .app_data(MsgPackConfig::default().limit(4))
+            .to_http_parts();
+        let err = MsgPack::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            payload_error(err),
+            MsgPackPayloadError::OverflowKnownLength { limit: 4, .. }
+        ));
+
+        let (req, mut pl) = msgpack_request(b"\xc1".to_vec()).to_http_parts();
+        let err = MsgPack::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_error_responder() {
+        let (req, mut pl) = msgpack_request(rmp_serde::to_vec(&my_object()).unwrap())
+            .app_data(MsgPackConfig::default().limit(4).error_handler(|err, _| {
+                let res = HttpResponse::Conflict().finish();
+                InternalError::from_response(err, res).into()
+            }))
+            .to_http_parts();
+
+        let s = MsgPack::<MyObject>::from_request(&req, &mut pl).await;
+        let res = HttpResponse::from_error(s.unwrap_err());
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_rt::test]
+    async fn test_without_content_type() {
+        let (req, mut pl) = TestRequest::default()
+            .set_payload(Bytes::from(rmp_serde::to_vec(&my_object()).unwrap()))
+            .app_data(MsgPackConfig::default().content_type_required(false))
+            .to_http_parts();
+
+        let s = MsgPack::<MyObject>::from_request(&req, &mut pl).await;
+        assert!(s.is_ok());
+    }
+}
@@ -0,0 +1,449 @@
+//! For content negotiation documentation, see [`Accepts`] and [`Negotiate`].
+
+use std::{convert::Infallible, fmt, ops};
+
+use actix_utils::future::{ready, Ready};
+use mime::Mime;
+use serde::Serialize;
+
+use crate::{
+    body::EitherBody,
+    dev::Payload,
+    error::NegotiateError,
+    http::header::{self, Accept, Header as _, Quality},
+    FromRequest, HttpRequest, HttpResponse, Responder,
+};
+
+/// Extractor for the media types a client accepts, as listed in its `Accept` header.
+///
+/// A missing or malformed `Accept` header is treated as accepting any media type, as permitted by
+/// [RFC 7231 §5.3.2]. Use [`negotiate()`](Self::negotiate) to choose among the media types a
+/// handler is able to produce, or respond with [`Negotiate`] to have this done automatically.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web::Accepts, HttpResponse};
+///
+/// #[get("/")]
+/// async fn index(accepts: Accepts) -> HttpResponse {
+///     let available = [mime::TEXT_HTML, mime::TEXT_PLAIN];
+///
+///     match accepts.negotiate(&available) {
+///         Some(mime) if *mime == mime::TEXT_HTML => HttpResponse::Ok().body("<p>Hello!</p>"),
+///         Some(_) => HttpResponse::Ok().body("Hello!"),
+///         None => HttpResponse::NotAcceptable().finish(),
+///     }
+/// }
+/// ```
+///
+/// [RFC 7231 §5.3.2]: https://datatracker.ietf.org/doc/html/rfc7231#section-5.3.2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accepts(Accept);
+
+impl Accepts {
+    /// Reads the accepted media types from the request's `Accept` header.
+    pub fn from_req(req: &HttpRequest) -> Self {
+        Self(Accept::parse(req).unwrap_or_else(|_| Accept(vec![])))
+    }
+
+    /// Returns the acceptable media ranges, from highest to lowest preference.
+    ///
+    /// Ranges with a q-factor of zero, which mark media types as not acceptable, are excluded. See
+    /// [`Accept::ranked()`] for details on how the ranges are sorted.
+    pub fn ranked(&self) -> Vec<Mime> {
+        let accept = Accept(
+            self.0
+                .iter()
+                .filter(|item| item.quality > Quality::ZERO)
+                .cloned()
+                .collect(),
+        );
+
+        accept.ranked()
+    }
+
+    /// Returns the q-factor the client gives to a media type.
+    ///
+    /// The q-factor of the most specific matching media range is used (eg. `text/html` over
+    /// `text/*` over `*/*`). Media type parameters are not considered. Returns [`Quality::ZERO`] if
+    /// the media type is not acceptable.
+    pub fn quality(&self, mime: &Mime) -> Quality {
+        if self.0.is_empty() {
+            return Quality::MAX;
+        }
+
+        let mut quality = Quality::ZERO;
+        let mut max_specificity = None;
+
+        for item in self.0.iter() {
+            let specificity = match (item.item.type_(), item.item.subtype()) {
+                (mime::STAR, mime::STAR) => 0,
+                (ty, mime::STAR) if ty == mime.type_() => 1,
+                (ty, subty) if ty == mime.type_() && subty == mime.subtype() => 2,
+                _ => continue,
+            };
+
+            // only change if strictly more specific, so the first listed range wins ties
+            if max_specificity.map_or(true, |max| specificity > max) {
+                max_specificity = Some(specificity);
+                quality = item.quality;
+            }
+        }
+
+        quality
+    }
+
+    /// Returns true if the client accepts the given media type.
+    pub fn is_acceptable(&self, mime: &Mime) -> bool {
+        self.quality(mime) > Quality::ZERO
+    }
+
+    /// Chooses the media type, among those available, that the client prefers.
+    ///
+    /// Available media types should be given in order of server preference, which is used to
+    /// break ties between types of equal quality. Returns `None` if none of them are acceptable.
+    pub fn negotiate<'a>(&self, available: &'a [Mime]) -> Option<&'a Mime> {
+        let mut preferred = None;
+        let mut max_quality = Quality::ZERO;
+
+        for mime in available {
+            let quality = self.quality(mime);
+
+            if quality > max_quality {
+                max_quality = quality;
+                preferred = Some(mime);
+            }
+        }
+
+        preferred
+    }
+}
+
+impl ops::Deref for Accepts {
+    type Target = Accept;
+
+    fn deref(&self) -> &Accept {
+        &self.0
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl FromRequest for Accepts {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(Self::from_req(req)))
+    }
+}
+
+/// Responder that serializes `T` in the format the client prefers.
+///
+/// The format is chosen by [negotiating](Accepts::negotiate) with the request's `Accept` header.
+/// Available formats, in order of server preference, are:
+/// - JSON (`application/json`), which is used when the client has no preference
+/// - CBOR (`application/cbor`), with the `cbor` feature
+/// - MessagePack (`application/msgpack`, or its `x-` and `vnd.` variants), with the `msgpack`
+///   feature
+/// - XML (`application/xml` or `text/xml`), with the `xml` feature
+///
+/// Responses include a `Vary: accept` header, so that caches take the `Accept` header into
+/// account. If none of the available formats are acceptable, a `406 Not Acceptable` response is
+/// returned, as described by [`NegotiateError`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[get("/user")]
+/// async fn user() -> web::Negotiate<User> {
+///     web::Negotiate(User {
+///         name: "ferris".to_owned(),
+///     })
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Negotiate<T>(pub T);
+
+impl<T> Negotiate<T> {
+    /// Unwrap into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Negotiate<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Negotiate<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Negotiate<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Creates response with the negotiated content type and a `200 OK` status code.
+///
+/// If serialization fails or no format is acceptable, an error response is returned instead.
+impl<T: Serialize> Responder for Negotiate<T> {
+    type Body = EitherBody<Vec<u8>>;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let available = Format::content_types();
+        let available_mimes = available
+            .iter()
+            .map(|(_, mime)| mime.clone())
+            .collect::<Vec<_>>();
+
+        let negotiated = Accepts::from_req(req)
+            .negotiate(&available_mimes)
+            .ok_or(NegotiateError::NotAcceptable)
+            .and_then(|mime| {
+                let (format, _) = available.iter().find(|(_, avail)| avail == mime).unwrap();
+                Ok((mime, format.serialize(&self.0)?))
+            });
+
+        let (mime, body) = match negotiated {
+            Ok(negotiated) => negotiated,
+            Err(err) => {
+                let mut res = HttpResponse::from_error(err);
+                res.headers_mut()
+                    .insert(header::VARY, header::HeaderValue::from_static("accept"));
+                return res.map_into_right_body();
+            }
+        };
+
+        match HttpResponse::Ok()
+            .content_type(mime.clone())
+            .insert_header((header::VARY, "accept"))
+            .message_body(body)
+        {
+            Ok(res) => res.map_into_left_body(),
+            Err(err) => HttpResponse::from_error(err).map_into_right_body(),
+        }
+    }
+}
+
+/// Formats available to [`Negotiate`] responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    #[cfg(feature = "cbor")]
+    Cbor,
+    #[cfg(feature = "msgpack")]
+    MsgPack,
+    #[cfg(feature = "xml")]
+    Xml,
+}
+
+impl Format {
+    /// Returns the content types of each format, in order of server preference.
+    fn content_types() -> Vec<(Format, Mime)> {
+        #[allow(unused_mut)]
+        let mut types = vec![(Format::Json, mime::APPLICATION_JSON)];
+
+        #[cfg(feature = "cbor")]
+        types.push((Format::Cbor, "application/cbor".parse().unwrap()));
+
+        #[cfg(feature = "msgpack")]
+        types.extend(
+            [
+                "application/msgpack",
+                "application/x-msgpack",
+                "application/vnd.msgpack",
+            ]
+            .map(|mime| (Format::MsgPack, mime.parse().unwrap())),
+        );
+
+        #[cfg(feature = "xml")]
+        types.extend([
+            (Format::Xml, "application/xml".parse().unwrap()),
+            (Format::Xml, mime::TEXT_XML),
+        ]);
+
+        types
+    }
+
+    fn serialize<T: Serialize>(self, value: &T) -> Result<Vec<u8>, NegotiateError> {
+        match self {
+            Format::Json => serde_json::to_vec(value).map_err(NegotiateError::Json),
+
+            #[cfg(feature = "cbor")]
+            Format::Cbor => {
+                let mut body = Vec::new();
+                ciborium::into_writer(value, &mut body).map_err(NegotiateError::Cbor)?;
+                Ok(body)
+            }
+
+            #[cfg(feature = "msgpack")]
+            Format::MsgPack => rmp_serde::to_vec_named(value).map_err(NegotiateError::MsgPack),
+
+            #[cfg(feature = "xml")]
+            Format::Xml => quick_xml::se::to_string(value)
+                .map(String::into_bytes)
+                .map_err(NegotiateError::Xml),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{body, http::StatusCode, test::TestRequest};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        name: String,
+    }
+
+    fn user() -> User {
+        User {
+            name: "ferris".to_owned(),
+        }
+    }
+
+    fn accepts(accept: &str) -> Accepts {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, accept))
+            .to_http_request();
+        Accepts::from_req(&req)
+    }
+
+    #[actix_rt::test]
+    async fn test_accepts_extract() {
+        let req = TestRequest::default().to_http_request();
+        let accepts = Accepts::extract(&req).await.unwrap();
+        assert!(accepts.ranked().is_empty());
+        assert!(accepts.is_acceptable(&mime::IMAGE_PNG));
+
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "text/html, application/json;q=0.9, */*;q=0"))
+            .to_http_request();
+        let accepts = Accepts::extract(&req).await.unwrap();
+        assert_eq!(
+            accepts.ranked(),
+            vec![mime::TEXT_HTML, mime::APPLICATION_JSON]
+        );
+    }
+
+    #[test]
+    fn test_quality() {
+        let accept = accepts("text/*;q=0.5, text/html, */*;q=0.1, image/png;q=0");
+        assert_eq!(accept.quality(&mime::TEXT_HTML), Quality::MAX);
+        assert_eq!(accept.quality(&mime::TEXT_PLAIN_UTF_8), header::q(0.5));
+        assert_eq!(accept.quality(&mime::APPLICATION_JSON), header::q(0.1));
+        assert_eq!(accept.quality(&mime::IMAGE_PNG), Quality::ZERO);
+        assert!(!accept.is_acceptable(&mime::IMAGE_PNG));
+
+        assert!(!accepts("text/plain").is_acceptable(&mime::TEXT_HTML));
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let available = [mime::APPLICATION_JSON, mime::TEXT_HTML, mime::TEXT_PLAIN];
+
+        // server preference is used when the client has none
+        assert_eq!(
+            accepts("*/*").negotiate(&available),
+            Some(&mime::APPLICATION_JSON)
+        );
+        assert_eq!(
+            accepts("text/*").negotiate(&available),
+            Some(&mime::TEXT_HTML)
+        );
+        assert_eq!(
+            accepts("text/*, text/html;q=0.5").negotiate(&available),
+            Some(&mime::TEXT_PLAIN)
+        );
+        assert_eq!(accepts("image/*").negotiate(&available), None);
+        assert_eq!(
+            accepts("application/json;q=0, */*").negotiate(&available),
+            Some(&mime::TEXT_HTML)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_negotiate_json() {
+        let req = TestRequest::default().to_http_request();
+        let res = Negotiate(user()).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        assert_eq!(res.headers().get(header::VARY).unwrap(), "accept");
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, r#"{"name":"ferris"}"#);
+    }
+
+    #[actix_rt::test]
+    async fn test_not_acceptable() {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "text/html"))
+            .to_http_request();
+        let res = Negotiate(user()).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(res.headers().get(header::VARY).unwrap(), "accept");
+    }
+
+    #[cfg(feature = "cbor")]
+    #[actix_rt::test]
+    async fn test_negotiate_cbor() {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "application/json;q=0.5, application/cbor"))
+            .to_http_request();
+        let res = Negotiate(user()).respond_to(&req);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/cbor"
+        );
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(ciborium::from_reader::<User, _>(&body[..]).unwrap(), user());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[actix_rt::test]
+    async fn test_negotiate_msgpack() {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "application/x-msgpack"))
+            .to_http_request();
+        let res = Negotiate(user()).respond_to(&req);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/x-msgpack"
+        );
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(rmp_serde::from_slice::<User>(&body).unwrap(), user());
+    }
+
+    #[cfg(feature = "xml")]
+    #[actix_rt::test]
+    async fn test_negotiate_xml() {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "text/xml"))
+            .to_http_request();
+        let res = Negotiate(user()).respond_to(&req);
+        assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), "text/xml");
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "<User><name>ferris</name></User>");
+    }
+}
@@ -0,0 +1,227 @@
+//! For one-of extractor documentation, see [`OneOf`].
+
+use std::fmt;
+
+use bytes::Bytes;
+use futures_core::future::LocalBoxFuture;
+
+use super::either::payload_from_bytes;
+use crate::{dev, Error, FromRequest, HttpRequest};
+
+/// An extractor that can tell whether a request's `Content-Type` is one it understands.
+///
+/// Used by [`OneOf`] to try the alternatives that match the request's content type first. It is
+/// implemented by the payload extractors in this crate, such as [`Json`](super::Json) and
+/// [`Form`](super::Form).
+pub trait MatchContentType: FromRequest {
+    /// Returns true if the request's `Content-Type` is handled by this extractor.
+    ///
+    /// Extractor configuration, such as additional content types allowed by
+    /// [`JsonConfig::content_type()`](super::JsonConfig::content_type), should be taken into
+    /// account. Whether the content type is required at all should not.
+    fn match_content_type(req: &HttpRequest) -> bool;
+}
+
+/// Tuples of extractors that can be combined using [`OneOf`].
+///
+/// Implemented for tuples of 2 to 6 extractors that implement [`MatchContentType`].
+pub trait OneOfAlternatives {
+    /// A tuple of options, one for each alternative.
+    type Options;
+}
+
+/// Combines two or more payload extractors into a single extractor.
+///
+/// Useful for "polymorphic payloads" accepted in several encodings, without nesting
+/// [`Either`](super::Either) types. Alternatives are given as a tuple, such as
+/// `OneOf<(Json<T>, Form<T>)>`, and the extracted value is available as a tuple of options, exactly
+/// one of which is `Some`.
+///
+/// The alternatives that [match](MatchContentType) the request's `Content-Type` are tried first,
+/// followed by the remaining alternatives, each in the order given. The first successful
+/// extraction is used. If all alternatives fail, the error of the first one tried is returned.
+///
+/// It is important to note that this extractor, by necessity, buffers the entire request payload
+/// as part of its implementation. Though, it does respect any `PayloadConfig` maximum size limits.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web::{self, OneOf}};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     name: String,
+/// }
+///
+/// // handler that accepts info as JSON or form-urlencoded.
+/// #[post("/")]
+/// async fn index(info: OneOf<(web::Json<Info>, web::Form<Info>)>) -> String {
+///     let name = match info.into_inner() {
+///         (Some(json), _) => json.into_inner().name,
+///         (_, Some(form)) => form.into_inner().name,
+///         (None, None) => unreachable!(),
+///     };
+///
+///     format!("Welcome {}!", name)
+/// }
+/// ```
+pub struct OneOf<T: OneOfAlternatives> {
+    index: usize,
+    options: T::Options,
+}
+
+impl<T: OneOfAlternatives> OneOf<T> {
+    /// Returns the position, within the tuple of alternatives, of the extractor that was used.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Unwrap into a tuple of options, in which only the extracted alternative is `Some`.
+    pub fn into_inner(self) -> T::Options {
+        self.options
+    }
+}
+
+impl<T> fmt::Debug for OneOf<T>
+where
+    T: OneOfAlternatives,
+    T::Options: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OneOf")
+            .field("index", &self.index)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+macro_rules! one_of_tuple ({ $(($n:tt, $T:ident)),+ } => {
+    impl<$($T),+> OneOfAlternatives for ($($T,)+)
+    where
+        $($T: MatchContentType,)+
+    {
+        type Options = ($(Option<$T>,)+);
+    }
+
+    /// See [here](OneOf#examples) for example of usage as an extractor.
+    impl<$($T),+> FromRequest for OneOf<($($T,)+)>
+    where
+        $($T: MatchContentType + 'static, $T::Future: 'static,)+
+    {
+        type Error = Error;
+        type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+        fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+            let bytes = Bytes::from_request(req, payload);
+            let req = req.clone();
+
+            // matching alternatives first, preserving the given order otherwise
+            let mut order = [$(($n, $T::match_content_type(&req)),)+];
+            order.sort_by_key(|(_, matched)| !matched);
+
+            Box::pin(async move {
+                let bytes = bytes.await?;
+                let no_options = <($(Option<$T>,)+)>::default;
+                let mut first_err = None;
+
+                for (idx, _) in order {
+                    let err: Error = match idx {
+                        $(
+                            $n => match $T::from_request(&req, &mut payload_from_bytes(bytes.clone())).await {
+                                Ok(extracted) => {
+                                    let mut options = no_options();
+                                    options.$n = Some(extracted);
+                                    return Ok(OneOf { index: $n, options });
+                                }
+                                Err(err) => err.into(),
+                            },
+                        )+
+                        _ => unreachable!(),
+                    };
+
+                    first_err.get_or_insert(err);
+                }
+
+                Err(first_err.unwrap())
+            })
+        }
+    }
+});
+
+one_of_tuple! { (0, A), (1, B) }
+one_of_tuple! { (0, A), (1, B), (2, C) }
+one_of_tuple! { (0, A), (1, B), (2, C), (3, D) }
+one_of_tuple! { (0, A), (1, B), (2, C), (3, D), (4, E) }
+one_of_tuple! { (0, A), (1, B), (2, C), (3, D), (4, E), (5, F) }
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{
+        error::UrlencodedError,
+        http::StatusCode,
+        test::TestRequest,
+        web::{Form, FormConfig, Json, JsonConfig},
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Info {
+        hello: String,
+    }
+
+    fn info() -> Info {
+        Info {
+            hello: "world".to_owned(),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn picks_by_content_type() {
+        let (req, mut pl) = TestRequest::default().set_form(info()).to_http_parts();
+        let one_of = OneOf::<(Json<Info>, Form<Info>)>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(one_of.index(), 1);
+        assert!(matches!(one_of.into_inner(), (None, Some(form)) if *form == info()));
+
+        let (req, mut pl) = TestRequest::default().set_json(info()).to_http_parts();
+        let one_of = OneOf::<(Form<Info>, Json<Info>, Json<Info>)>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(one_of.index(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn falls_back_in_order() {
+        // no content type; only the JSON extractor is configured to accept it
+        let (req, mut pl) = TestRequest::default()
+            .set_payload(r#"{"hello":"world"}"#)
+            .app_data(JsonConfig::default().content_type_required(false))
+            .to_http_parts();
+        let one_of = OneOf::<(Form<Info>, Json<Info>)>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(one_of.index(), 1);
+
+        // content type matches the form extractor, which fails because of its limit
+        let (req, mut pl) = TestRequest::default()
+            .set_form(info())
+            .app_data(FormConfig::default().limit(4))
+            .app_data(JsonConfig::default().content_type_required(false))
+            .to_http_parts();
+        let err = OneOf::<(Json<Info>, Form<Info>)>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.as_error::<UrlencodedError>(),
+            Some(UrlencodedError::Overflow { .. })
+        ));
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+}
@@ -0,0 +1,506 @@
+//! For pagination extractor documentation, see [`Pagination`] and [`CursorPagination`].
+
+use std::sync::Arc;
+
+use actix_utils::future::{ready, Ready};
+use url::{form_urlencoded, Url};
+
+use crate::{
+    dev::Payload,
+    error::PaginationError,
+    http::header::{self, HeaderName, HeaderValue},
+    web, Error, FromRequest, HttpRequest,
+};
+
+/// Query parameters read by the pagination extractors.
+const PARAMS: [&str; 3] = ["page", "per_page", "cursor"];
+
+/// Extractor for page-based pagination parameters.
+///
+/// Reads the 1-based `page` number and the `per_page` page size from the request's query string,
+/// ignoring other parameters. Missing parameters take their defaults from [`PaginationConfig`],
+/// and page sizes above its maximum are reduced to the maximum. A page number or page size that is
+/// not a positive integer is rejected with a [`PaginationError`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web, HttpRequest, HttpResponse};
+///
+/// #[get("/items")]
+/// async fn items(req: HttpRequest, page: web::Pagination) -> HttpResponse {
+///     let total_items = 1234;
+///     let query = format!(
+///         "SELECT * FROM items LIMIT {} OFFSET {}",
+///         page.per_page(),
+///         page.offset(),
+///     );
+///
+///     HttpResponse::Ok()
+///         .insert_header(page.link_header(&req, Some(total_items)))
+///         .body(query)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pagination {
+    page: u64,
+    per_page: u64,
+}
+
+impl Pagination {
+    /// Returns the requested page number, starting at 1.
+    pub fn page(&self) -> u64 {
+        self.page
+    }
+
+    /// Returns the number of items per page.
+    pub fn per_page(&self) -> u64 {
+        self.per_page
+    }
+
+    /// Returns the number of items before the requested page.
+    pub fn offset(&self) -> u64 {
+        (self.page - 1).saturating_mul(self.per_page)
+    }
+
+    /// Returns the number of the last page, given the total number of items.
+    pub fn last_page(&self, total_items: u64) -> u64 {
+        total_items.div_ceil(self.per_page).max(1)
+    }
+
+    /// Returns a [`Link`](header::LINK) header with the URLs of the first, previous, next, and last
+    /// pages, relative to the requested page.
+    ///
+    /// URLs are based on the request's [full URL](HttpRequest::full_url), keeping its other query
+    /// parameters. If the total number of items is unknown, the last page is omitted and the next
+    /// page is always included.
+    pub fn link_header(
+        &self,
+        req: &HttpRequest,
+        total_items: Option<u64>,
+    ) -> (HeaderName, HeaderValue) {
+        let per_page = self.per_page.to_string();
+        let page_url =
+            |page: u64| page_url(req, &[("page", &page.to_string()), ("per_page", &per_page)]);
+
+        let last_page = total_items.map(|total| self.last_page(total));
+        let mut links = vec![(page_url(1), "first")];
+
+        if self.page > 1 {
+            let prev = last_page.map_or(self.page, |last| self.page.min(last + 1));
+            links.push((page_url(prev - 1), "prev"));
+        }
+
+        if last_page.map_or(true, |last| self.page < last) {
+            links.push((page_url(self.page + 1), "next"));
+        }
+
+        if let Some(last_page) = last_page {
+            links.push((page_url(last_page), "last"));
+        }
+
+        link_header(links)
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl FromRequest for Pagination {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let config = PaginationConfig::from_req(req);
+
+        let res = config.parse(req).and_then(|(query, per_page)| {
+            let page = match query.page {
+                Some(page) => parse_positive(&page).ok_or(PaginationError::InvalidPage)?,
+                None => 1,
+            };
+
+            Ok(Pagination { page, per_page })
+        });
+
+        ready(res.map_err(|err| config.handle_error(err, req)))
+    }
+}
+
+/// Extractor for cursor-based pagination parameters.
+///
+/// Reads the opaque `cursor` that marks the position to continue from and the `per_page` page size
+/// from the request's query string, ignoring other parameters. Page sizes are handled in the same
+/// way as for [`Pagination`], using [`PaginationConfig`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web, HttpRequest, HttpResponse};
+///
+/// #[get("/events")]
+/// async fn events(req: HttpRequest, page: web::CursorPagination) -> HttpResponse {
+///     let after = page.cursor().unwrap_or("0");
+///     let next_cursor = "42";
+///
+///     HttpResponse::Ok()
+///         .insert_header(page.link_header(&req, Some(next_cursor)))
+///         .body(format!("{} events after {after}", page.per_page()))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CursorPagination {
+    cursor: Option<String>,
+    per_page: u64,
+}
+
+impl CursorPagination {
+    /// Returns the requested cursor, or `None` if the first page is requested.
+    pub fn cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+
+    /// Returns the number of items per page.
+    pub fn per_page(&self) -> u64 {
+        self.per_page
+    }
+
+    /// Returns a [`Link`](header::LINK) header with the URLs of the first page and, if there is
+    /// one, of the next page, which starts at `next_cursor`.
+    ///
+    /// URLs are based on the request's [full URL](HttpRequest::full_url), keeping its other query
+    /// parameters.
+    pub fn link_header(
+        &self,
+        req: &HttpRequest,
+        next_cursor: Option<&str>,
+    ) -> (HeaderName, HeaderValue) {
+        let per_page = self.per_page.to_string();
+        let mut links = vec![(page_url(req, &[("per_page", &per_page)]), "first")];
+
+        if let Some(cursor) = next_cursor {
+            let url = page_url(req, &[("cursor", cursor), ("per_page", &per_page)]);
+            links.push((url, "next"));
+        }
+
+        link_header(links)
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl FromRequest for CursorPagination {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let config = PaginationConfig::from_req(req);
+
+        let res = config.parse(req).map(|(query, per_page)| CursorPagination {
+            cursor: query.cursor.filter(|cursor| !cursor.is_empty()),
+            per_page,
+        });
+
+        ready(res.map_err(|err| config.handle_error(err, req)))
+    }
+}
+
+/// Pagination parameters of a query string, as given.
+#[derive(Default)]
+struct PaginationQuery {
+    page: Option<String>,
+    per_page: Option<String>,
+    cursor: Option<String>,
+}
+
+impl PaginationQuery {
+    /// Reads the pagination parameters from a query string, using the first value of each.
+    fn parse(query: &str) -> Self {
+        let mut params = Self::default();
+
+        for (name, val) in form_urlencoded::parse(query.as_bytes()) {
+            let param = match name.as_ref() {
+                "page" => &mut params.page,
+                "per_page" => &mut params.per_page,
+                "cursor" => &mut params.cursor,
+                _ => continue,
+            };
+
+            param.get_or_insert_with(|| val.into_owned());
+        }
+
+        params
+    }
+}
+
+fn parse_positive(val: &str) -> Option<u64> {
+    val.parse().ok().filter(|&n| n > 0)
+}
+
+/// Returns the request's URL, with the pagination parameters replaced by `params`.
+fn page_url(req: &HttpRequest, params: &[(&str, &str)]) -> Url {
+    let mut url = req.full_url();
+
+    let query = url
+        .query_pairs()
+        .into_owned()
+        .filter(|(name, _)| !PARAMS.contains(&name.as_str()))
+        .collect::<Vec<_>>();
+
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(query)
+        .extend_pairs(params);
+
+    url
+}
+
+fn link_header<'a>(links: impl IntoIterator<Item = (Url, &'a str)>) -> (HeaderName, HeaderValue) {
+    let links = links
+        .into_iter()
+        .map(|(url, rel)| format!("<{url}>; rel=\"{rel}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    // serialized URLs only contain visible ASCII characters
+    let links = HeaderValue::from_str(&links).expect("Link header should be a valid header value");
+
+    (header::LINK, links)
+}
+
+/// Pagination extractors configuration.
+///
+/// Applies to both [`Pagination`] and [`CursorPagination`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{error, web, App, HttpResponse};
+///
+/// let pagination_cfg = web::PaginationConfig::default()
+///     // 50 items per page unless requested otherwise
+///     .default_per_page(50)
+///     // never more than 500 items per page
+///     .max_per_page(500)
+///     // use custom error handler
+///     .error_handler(|err, req| {
+///         error::InternalError::from_response(err, HttpResponse::Conflict().finish()).into()
+///     });
+///
+/// let app = App::new().app_data(pagination_cfg);
+/// ```
+#[derive(Clone)]
+pub struct PaginationConfig {
+    default_per_page: u64,
+    max_per_page: u64,
+    #[allow(clippy::type_complexity)]
+    err_handler: Option<Arc<dyn Fn(PaginationError, &HttpRequest) -> Error + Send + Sync>>,
+}
+
+impl PaginationConfig {
+    /// Set number of items per page when the `per_page` parameter is missing. By default this is
+    /// 20.
+    ///
+    /// # Panics
+    /// Panics if `per_page` is zero.
+    pub fn default_per_page(mut self, per_page: u64) -> Self {
+        assert!(per_page > 0, "default page size must be at least 1");
+        self.default_per_page = per_page;
+        self
+    }
+
+    /// Set maximum number of items per page. By default this is 100.
+    ///
+    /// Larger requested page sizes are reduced to this maximum, as is the default page size.
+    ///
+    /// # Panics
+    /// Panics if `per_page` is zero.
+    pub fn max_per_page(mut self, per_page: u64) -> Self {
+        assert!(per_page > 0, "maximum page size must be at least 1");
+        self.max_per_page = per_page;
+        self
+    }
+
+    /// Set custom error handler
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(PaginationError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Extract pagination config from app data. Check both `T` and `Data<T>`, in that order, and
+    /// fall back to the default config.
+    fn from_req(req: &HttpRequest) -> &Self {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .unwrap_or(&DEFAULT_CONFIG)
+    }
+
+    /// Parses the pagination parameters of the query string, returning them with the page size to
+    /// use.
+    fn parse(&self, req: &HttpRequest) -> Result<(PaginationQuery, u64), PaginationError> {
+        let query = PaginationQuery::parse(req.query_string());
+
+        let per_page = match &query.per_page {
+            Some(per_page) => parse_positive(per_page).ok_or(PaginationError::InvalidPerPage)?,
+            None => self.default_per_page,
+        };
+
+        Ok((query, per_page.min(self.max_per_page)))
+    }
+
+    fn handle_error(&self, err: PaginationError, req: &HttpRequest) -> Error {
+        log::debug!(
+            "Failed to parse pagination query parameters. \
+             Request path: {:?}",
+            req.path()
+        );
+
+        match self.err_handler.as_ref() {
+            Some(err_handler) => (err_handler)(err, req),
+            None => err.into(),
+        }
+    }
+}
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: PaginationConfig = PaginationConfig {
+    default_per_page: 20,
+    max_per_page: 100,
+    err_handler: None,
+};
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_http::StatusCode;
+
+    use super::*;
+    use crate::{error::InternalError, test::TestRequest, HttpResponse};
+
+    #[actix_rt::test]
+    async fn test_pagination() {
+        let req = TestRequest::with_uri("/items").to_http_request();
+        let page = Pagination::extract(&req).await.unwrap();
+        assert_eq!((page.page(), page.per_page(), page.offset()), (1, 20, 0));
+
+        let req = TestRequest::with_uri("/items?sort=name&page=3&per_page=10").to_http_request();
+        let page = Pagination::extract(&req).await.unwrap();
+        assert_eq!((page.page(), page.per_page(), page.offset()), (3, 10, 20));
+
+        // page size is limited to the maximum
+        let req = TestRequest::with_uri("/items?per_page=1000")
+            .app_data(PaginationConfig::default().max_per_page(50))
+            .to_http_request();
+        let page = Pagination::extract(&req).await.unwrap();
+        assert_eq!(page.per_page(), 50);
+
+        let req = TestRequest::with_uri("/items")
+            .app_data(web::Data::new(
+                PaginationConfig::default().default_per_page(200),
+            ))
+            .to_http_request();
+        let page = Pagination::extract(&req).await.unwrap();
+        assert_eq!(page.per_page(), 100);
+
+        for query in ["page=0", "per_page=0", "page=-1", "page=two", "page="] {
+            let req = TestRequest::with_uri(&format!("/items?{query}")).to_http_request();
+            let err = Pagination::extract(&req).await.unwrap_err();
+            assert_eq!(
+                err.as_response_error().status_code(),
+                StatusCode::BAD_REQUEST
+            );
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_link_header() {
+        let req = TestRequest::with_uri("/items?sort=name&page=2&per_page=10").to_http_request();
+        let page = Pagination::extract(&req).await.unwrap();
+        assert_eq!(page.last_page(25), 3);
+        assert_eq!(page.last_page(0), 1);
+
+        let (name, value) = page.link_header(&req, Some(25));
+        assert_eq!(name, header::LINK);
+        assert_eq!(
+            value,
+            "<http://localhost:8080/items?sort=name&page=1&per_page=10>; rel=\"first\", \
+             <http://localhost:8080/items?sort=name&page=1&per_page=10>; rel=\"prev\", \
+             <http://localhost:8080/items?sort=name&page=3&per_page=10>; rel=\"next\", \
+             <http://localhost:8080/items?sort=name&page=3&per_page=10>; rel=\"last\""
+        );
+
+        // last page
+        let (_, value) = page.link_header(&req, Some(20));
+        assert_eq!(
+            value,
+            "<http://localhost:8080/items?sort=name&page=1&per_page=10>; rel=\"first\", \
+             <http://localhost:8080/items?sort=name&page=1&per_page=10>; rel=\"prev\", \
+             <http://localhost:8080/items?sort=name&page=2&per_page=10>; rel=\"last\""
+        );
+
+        // unknown total
+        let req = TestRequest::with_uri("/items").to_http_request();
+        let page = Pagination::extract(&req).await.unwrap();
+        let (_, value) = page.link_header(&req, None);
+        assert_eq!(
+            value,
+            "<http://localhost:8080/items?page=1&per_page=20>; rel=\"first\", \
+             <http://localhost:8080/items?page=2&per_page=20>; rel=\"next\""
+        );
+
+        // pages beyond the last link back to the last page
+        let req = TestRequest::with_uri("/items?page=9").to_http_request();
+        let page = Pagination::extract(&req).await.unwrap();
+        let (_, value) = page.link_header(&req, Some(30));
+        assert_eq!(
+            value,
+            "<http://localhost:8080/items?page=1&per_page=20>; rel=\"first\", \
+             <http://localhost:8080/items?page=2&per_page=20>; rel=\"prev\", \
+             <http://localhost:8080/items?page=2&per_page=20>; rel=\"last\""
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_cursor_pagination() {
+        let req = TestRequest::with_uri("/events?cursor=").to_http_request();
+        let page = CursorPagination::extract(&req).await.unwrap();
+        assert_eq!((page.cursor(), page.per_page()), (None, 20));
+
+        let req =
+            TestRequest::with_uri("/events?cursor=abc%3D&per_page=5&kind=push").to_http_request();
+        let page = CursorPagination::extract(&req).await.unwrap();
+        assert_eq!((page.cursor(), page.per_page()), (Some("abc="), 5));
+
+        let (_, value) = page.link_header(&req, Some("def="));
+        assert_eq!(
+            value,
+            "<http://localhost:8080/events?kind=push&per_page=5>; rel=\"first\", \
+             <http://localhost:8080/events?kind=push&cursor=def%3D&per_page=5>; rel=\"next\""
+        );
+
+        let (_, value) = page.link_header(&req, None);
+        assert_eq!(
+            value,
+            "<http://localhost:8080/events?kind=push&per_page=5>; rel=\"first\""
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_error_handler() {
+        let req = TestRequest::with_uri("/events?per_page=0")
+            .app_data(PaginationConfig::default().error_handler(|err, _| {
+                let res = HttpResponse::UnprocessableEntity().body(err.to_string());
+                InternalError::from_response(err, res).into()
+            }))
+            .to_http_request();
+
+        let err = CursorPagination::extract(&req).await.unwrap_err();
+        let res = err.error_response();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = crate::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "Page size must be a positive integer");
+    }
+}
@@ -2,10 +2,11 @@
 
 use std::sync::Arc;
 
-use actix_router::PathDeserializer;
+use actix_router::{PathDeserializer, Url};
 use actix_utils::future::{ready, Ready};
 use derive_more::derive::{AsRef, Deref, DerefMut, Display, From};
 use serde::de;
+use serde_path_to_error::Segment;
 
 use crate::{
     dev::Payload,
@@ -16,7 +17,8 @@ use crate::{
 
 /// Extract typed data from request path segments.
 ///
-/// Use [`PathConfig`] to configure extraction option.
+/// Use [`PathConfig`] to configure extraction option. When extraction fails, the resulting
+/// [`PathError`] identifies the segment that could not be deserialized, where possible.
 ///
 /// Unlike, [`HttpRequest::match_info`], this extractor will fully percent-decode dynamic segments,
 /// including `/`, `%`, and `+`.
@@ -78,27 +80,43 @@ where
             .or_else(|| req.app_data::<Data<PathConfig>>().map(Data::get_ref))
             .and_then(|c| c.err_handler.clone());
 
-        ready(
-            de::Deserialize::deserialize(PathDeserializer::new(req.match_info()))
-                .map(Path)
-                .map_err(move |err| {
-                    log::debug!(
-                        "Failed during Path extractor deserialization. \
-                         Request path: {:?}",
-                        req.path()
-                    );
-
-                    if let Some(error_handler) = error_handler {
-                        let err = PathError::Deserialize(err);
-                        (error_handler)(err, req)
-                    } else {
-                        ErrorNotFound(err)
-                    }
-                }),
-        )
+        ready(deserialize(req.match_info()).map(Path).map_err(move |err| {
+            log::debug!(
+                "Failed during Path extractor deserialization. \
+                 Request path: {:?}",
+                req.path()
+            );
+
+            if let Some(error_handler) = error_handler {
+                (error_handler)(err, req)
+            } else {
+                ErrorNotFound(err)
+            }
+        }))
     }
 }
 
+/// Deserializes path segments, tracking which segment failed to deserialize.
+fn deserialize<T: de::DeserializeOwned>(params: &actix_router::Path<Url>) -> Result<T, PathError> {
+    serde_path_to_error::deserialize(PathDeserializer::new(params)).map_err(|err| {
+        // struct fields are matched to segments by name, tuple items by position
+        let segment = match err.path().iter().next() {
+            Some(Segment::Map { key }) => params.iter().find(|(name, _)| name == key),
+            Some(Segment::Seq { index }) => params.iter().nth(*index),
+            _ => None,
+        };
+
+        match segment {
+            Some((name, value)) => PathError::Segment {
+                name: name.to_owned(),
+                value: value.to_owned(),
+                source: err.into_inner(),
+            },
+            None => PathError::Deserialize(err.into_inner()),
+        }
+    })
+}
+
 /// Path extractor configuration
 ///
 /// ```
@@ -165,7 +183,7 @@ mod tests {
         value: String,
     }
 
-    #[derive(Deserialize)]
+    #[derive(Debug, Deserialize)]
     struct Test2 {
         key: String,
         value: u32,
@@ -286,4 +304,62 @@ mod tests {
 
         assert_eq!(res.status(), http::StatusCode::CONFLICT);
     }
+
+    #[actix_rt::test]
+    async fn test_segment_errors() {
+        let resource = ResourceDef::new("/{key}/{value}/");
+
+        let mut req = TestRequest::with_uri("/name/abc/").to_srv_request();
+        resource.capture_match_info(req.match_info_mut());
+        let (req, mut pl) = req.into_parts();
+
+        let err = Path::<Test2>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        let res = err.error_response();
+        assert_eq!(res.status(), http::StatusCode::NOT_FOUND);
+        let body = crate::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(
+            body,
+            "Path deserialize error: invalid segment `value`: can not parse \"abc\" to a u32"
+        );
+
+        let mut req = TestRequest::with_uri("/name/abc/")
+            .app_data(PathConfig::default().error_handler(|err, _| {
+                let body = match &err {
+                    PathError::Segment { name, value, .. } => format!("{name}={value}"),
+                    err => format!("{:?}", err.segment()),
+                };
+                error::InternalError::from_response(err, HttpResponse::BadRequest().body(body))
+                    .into()
+            }))
+            .to_srv_request();
+        resource.capture_match_info(req.match_info_mut());
+        let (req, mut pl) = req.into_parts();
+
+        let err = Path::<Test2>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        let res = err.error_response();
+        assert_eq!(res.status(), http::StatusCode::BAD_REQUEST);
+        let body = crate::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "value=abc");
+
+        let err = Path::<(String, u8)>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        let body = crate::body::to_bytes(err.error_response().into_body())
+            .await
+            .unwrap();
+        assert_eq!(body, "value=abc");
+
+        // errors not caused by a single segment
+        let err = Path::<(String, String, String)>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        let body = crate::body::to_bytes(err.error_response().into_body())
+            .await
+            .unwrap();
+        assert_eq!(body, "None");
+    }
 }
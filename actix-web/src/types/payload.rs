@@ -16,8 +16,8 @@ use futures_core::{ready, stream::Stream};
 use mime::Mime;
 
 use crate::{
-    body, dev, error::ErrorBadRequest, http::header, web, Error, FromRequest, HttpMessage,
-    HttpRequest,
+    body, dev, error::ErrorBadRequest, http::header, middleware::body_limit::request_body_limit,
+    web, Error, FromRequest, HttpMessage, HttpRequest,
 };
 
 /// Extract a request's raw payload stream.
@@ -319,11 +319,19 @@ impl PayloadConfig {
     }
 
     /// Extract payload config from app data. Check both `T` and `Data<T>`, in that order, and fall
-    /// back to the default payload config if neither is found.
-    fn from_req(req: &HttpRequest) -> &Self {
-        req.app_data::<Self>()
+    /// back to the default payload config if neither is found, limited by the `BodyLimit`
+    /// middleware if present.
+    fn from_req(req: &HttpRequest) -> Cow<'_, Self> {
+        match req
+            .app_data::<Self>()
             .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
-            .unwrap_or(&DEFAULT_CONFIG)
+        {
+            Some(config) => Cow::Borrowed(config),
+            None => Cow::Owned(Self {
+                limit: request_body_limit(req).unwrap_or(DEFAULT_CONFIG_LIMIT),
+                ..DEFAULT_CONFIG
+            }),
+        }
     }
 }
 
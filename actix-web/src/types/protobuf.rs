@@ -0,0 +1,530 @@
+//! For Protocol Buffers helper documentation, see [`Protobuf`].
+
+use std::{borrow::Cow, fmt, ops, sync::Arc};
+
+use actix_http::Payload;
+use futures_core::future::LocalBoxFuture;
+use prost::Message;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{payload::HttpMessageBody, MatchContentType};
+use crate::{
+    body::EitherBody,
+    error::{Error, ProtobufPayloadError},
+    extract::FromRequest,
+    http::header::{self, Accept, Header as _},
+    middleware::body_limit::request_body_limit,
+    request::HttpRequest,
+    web, HttpMessage as _, HttpResponse, Responder,
+};
+
+/// Content type of Protocol Buffers responses.
+const CONTENT_TYPE: &str = "application/x-protobuf";
+
+/// Protocol Buffers extractor and responder, using [`prost`] messages.
+///
+/// `Protobuf` has two uses: Protocol Buffers responses, and extracting typed messages from
+/// Protocol Buffers request payloads.
+///
+/// Clients that can't speak Protocol Buffers may use JSON instead. Request bodies with a JSON
+/// content type are deserialized using [`serde`], and responses are serialized as JSON when the
+/// client's `Accept` header prefers `application/json`. This means that the message type `T` must
+/// also implement the relevant `serde` trait; for generated code, derives can be added using
+/// `prost-build`'s `type_attribute` option. The JSON fallback can be disabled using
+/// [`ProtobufConfig::json_fallback()`].
+///
+/// # Extractor
+/// To extract a message from a request body, the inner type `T` must implement
+/// [`prost::Message`], [`Default`], and [`serde::Deserialize`].
+///
+/// Protocol Buffers bodies must have one of the `application/x-protobuf`, `application/protobuf`
+/// or `application/vnd.google.protobuf` content types. Use [`ProtobufConfig`] to configure
+/// extraction options.
+///
+/// ```
+/// use actix_web::{post, web};
+/// use serde::Deserialize;
+///
+/// #[derive(Clone, PartialEq, prost::Message, Deserialize)]
+/// struct Info {
+///     #[prost(string, tag = "1")]
+///     username: String,
+/// }
+///
+/// /// decode `Info` from request's body
+/// #[post("/")]
+/// async fn index(info: web::Protobuf<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+/// ```
+///
+/// # Responder
+/// The `Protobuf` type encodes its message as an `application/x-protobuf` response, or as an
+/// `application/json` response when preferred by the client. The type `T` must implement
+/// [`prost::Message`] and [`serde::Serialize`].
+///
+/// ```
+/// use actix_web::{post, web, HttpRequest};
+/// use serde::Serialize;
+///
+/// #[derive(Clone, PartialEq, prost::Message, Serialize)]
+/// struct Info {
+///     #[prost(string, tag = "1")]
+///     name: String,
+/// }
+///
+/// #[post("/{name}")]
+/// async fn index(req: HttpRequest) -> web::Protobuf<Info> {
+///     web::Protobuf(Info {
+///         name: req.match_info().get("name").unwrap().to_owned(),
+///     })
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Protobuf<T>(pub T);
+
+impl<T> Protobuf<T> {
+    /// Unwrap into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Protobuf<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Protobuf<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Protobuf<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Creates response with OK status code and the encoded message.
+///
+/// The message is encoded as JSON if the JSON fallback is enabled and the request's `Accept`
+/// header prefers JSON over Protocol Buffers. If serialization failed, creates an Internal Server
+/// Error response.
+impl<T: Message + Serialize> Responder for Protobuf<T> {
+    type Body = EitherBody<Vec<u8>>;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let json_fallback = ProtobufConfig::from_req(req).json_fallback;
+
+        let (content_type, body) = if json_fallback && prefers_json(req) {
+            match serde_json::to_vec(&self.0) {
+                Ok(body) => (mime::APPLICATION_JSON.as_ref(), body),
+                Err(err) => {
+                    return HttpResponse::from_error(ProtobufPayloadError::JsonSerialize(err))
+                        .map_into_right_body();
+                }
+            }
+        } else {
+            (CONTENT_TYPE, self.0.encode_to_vec())
+        };
+
+        let mut res = HttpResponse::Ok();
+        res.content_type(content_type);
+
+        if json_fallback {
+            res.insert_header((header::VARY, "accept"));
+        }
+
+        match res.message_body(body) {
+            Ok(res) => res.map_into_left_body(),
+            Err(err) => HttpResponse::from_error(err).map_into_right_body(),
+        }
+    }
+}
+
+/// Returns true if the request's `Accept` header ranks JSON above Protocol Buffers.
+fn prefers_json(req: &HttpRequest) -> bool {
+    let accept = match Accept::parse(req) {
+        Ok(accept) => accept,
+        Err(_) => return false,
+    };
+
+    accept
+        .ranked()
+        .into_iter()
+        .find_map(|mime| {
+            if is_protobuf(&mime) || mime.subtype() == mime::STAR {
+                Some(false)
+            } else if is_json(&mime) {
+                Some(true)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(false)
+}
+
+fn is_protobuf(mime: &mime::Mime) -> bool {
+    mime.type_() == mime::APPLICATION
+        && matches!(
+            mime.subtype().as_str(),
+            "x-protobuf" | "protobuf" | "vnd.google.protobuf"
+        )
+}
+
+fn is_json(mime: &mime::Mime) -> bool {
+    mime.subtype() == mime::JSON || mime.suffix() == Some(mime::JSON)
+}
+
+/// See [here](#extractor) for example of usage as an extractor.
+impl<T: Message + Default + DeserializeOwned + 'static> FromRequest for Protobuf<T> {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = ProtobufConfig::from_req(req).into_owned();
+        let req = req.clone();
+
+        let format = match req.mime_type() {
+            Ok(Some(mime)) if is_protobuf(&mime) => Some(Format::Protobuf),
+            Ok(Some(mime)) if config.json_fallback && is_json(&mime) => Some(Format::Json),
+            Ok(Some(mime))
+                if config
+                    .content_type
+                    .as_ref()
+                    .is_some_and(|pred| pred(mime.clone())) =>
+            {
+                Some(Format::Protobuf)
+            }
+            _ if !config.content_type_required => Some(Format::Protobuf),
+            _ => None,
+        };
+
+        let body = format.map(|format| {
+            (
+                format,
+                HttpMessageBody::new(&req, payload).limit(config.limit),
+            )
+        });
+
+        Box::pin(async move {
+            let res = match body {
+                Some((format, body)) => match body.await {
+                    Ok(body) => match format {
+                        Format::Protobuf => T::decode(body).map_err(ProtobufPayloadError::Decode),
+                        Format::Json => serde_json::from_slice(&body)
+                            .map_err(ProtobufPayloadError::JsonDeserialize),
+                    },
+                    Err(err) => Err(ProtobufPayloadError::Payload(err)),
+                },
+                None => Err(ProtobufPayloadError::ContentType),
+            };
+
+            res.map(Protobuf).map_err(|err| {
+                log::debug!(
+                    "Failed to deserialize Protobuf from payload. Request path: {}",
+                    req.path()
+                );
+
+                match config.err_handler.as_ref() {
+                    Some(err_handler) => err_handler(err, &req),
+                    None => err.into(),
+                }
+            })
+        })
+    }
+}
+
+impl<T: Message + Default + DeserializeOwned + 'static> MatchContentType for Protobuf<T> {
+    fn match_content_type(req: &HttpRequest) -> bool {
+        let config = ProtobufConfig::from_req(req);
+
+        match req.mime_type() {
+            Ok(Some(mime)) => {
+                is_protobuf(&mime)
+                    || (config.json_fallback && is_json(&mime))
+                    || config.content_type.as_ref().is_some_and(|pred| pred(mime))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Encoding of a request body.
+enum Format {
+    Protobuf,
+    Json,
+}
+
+type ProtobufErrorHandler =
+    Option<Arc<dyn Fn(ProtobufPayloadError, &HttpRequest) -> Error + Send + Sync>>;
+
+/// `Protobuf` extractor and responder configuration.
+///
+/// # Examples
+/// ```
+/// use actix_web::{error, web, App, HttpResponse};
+///
+/// // custom `Protobuf` configuration
+/// let protobuf_cfg = web::ProtobufConfig::default()
+///     // limit request payload size
+///     .limit(4096)
+///     // only accept and respond with Protocol Buffers
+///     .json_fallback(false)
+///     // use custom error handler
+///     .error_handler(|err, req| {
+///         error::InternalError::from_response(err, HttpResponse::Conflict().into()).into()
+///     });
+///
+/// let app = App::new().app_data(protobuf_cfg);
+/// ```
+#[derive(Clone)]
+pub struct ProtobufConfig {
+    limit: usize,
+    err_handler: ProtobufErrorHandler,
+    content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    content_type_required: bool,
+    json_fallback: bool,
+}
+
+impl ProtobufConfig {
+    /// Set maximum accepted payload size. By default this limit is 2MB.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set custom error handler.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(ProtobufPayloadError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Set predicate for content types, in addition to the Protocol Buffers content types, that are
+    /// decoded as Protocol Buffers.
+    pub fn content_type<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(mime::Mime) -> bool + Send + Sync + 'static,
+    {
+        self.content_type = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Sets whether or not the request must have a `Content-Type` header to be parsed.
+    ///
+    /// When not required, bodies without a recognized content type are decoded as Protocol Buffers.
+    pub fn content_type_required(mut self, content_type_required: bool) -> Self {
+        self.content_type_required = content_type_required;
+        self
+    }
+
+    /// Sets whether JSON is accepted in request bodies and used for responses when preferred by
+    /// the client. Enabled by default.
+    pub fn json_fallback(mut self, json_fallback: bool) -> Self {
+        self.json_fallback = json_fallback;
+        self
+    }
+
+    /// Extract payload config from app data. Check both `T` and `Data<T>`, in that order, and fall
+    /// back to the default payload config, limited by the `BodyLimit` middleware if present.
+    fn from_req(req: &HttpRequest) -> Cow<'_, Self> {
+        match req
+            .app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+        {
+            Some(config) => Cow::Borrowed(config),
+            None => Cow::Owned(Self {
+                limit: request_body_limit(req).unwrap_or(DEFAULT_LIMIT),
+                ..DEFAULT_CONFIG
+            }),
+        }
+    }
+}
+
+const DEFAULT_LIMIT: usize = 2_097_152; // 2 mb
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: ProtobufConfig = ProtobufConfig {
+    limit: DEFAULT_LIMIT,
+    err_handler: None,
+    content_type: None,
+    content_type_required: true,
+    json_fallback: true,
+};
+
+impl Default for ProtobufConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{body, error::InternalError, http::StatusCode, test::TestRequest};
+
+    #[derive(Clone, PartialEq, Message, Serialize, Deserialize)]
+    struct MyObject {
+        #[prost(string, tag = "1")]
+        name: String,
+        #[prost(uint32, tag = "2")]
+        count: u32,
+    }
+
+    fn my_object() -> MyObject {
+        MyObject {
+            name: "test".to_owned(),
+            count: 3,
+        }
+    }
+
+    fn protobuf_request() -> TestRequest {
+        let body = my_object().encode_to_vec();
+
+        TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/x-protobuf"))
+            .insert_header((header::CONTENT_LENGTH, body.len()))
+            .set_payload(body)
+    }
+
+    #[actix_rt::test]
+    async fn test_responder() {
+        let req = TestRequest::default().to_http_request();
+        let res = Protobuf(my_object()).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/x-protobuf"
+        );
+        assert_eq!(res.headers().get(header::VARY).unwrap(), "accept");
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(MyObject::decode(body).unwrap(), my_object());
+
+        let req = TestRequest::default()
+            .insert_header((
+                header::ACCEPT,
+                "application/x-protobuf;q=0.5, application/json",
+            ))
+            .to_http_request();
+        let res = Protobuf(my_object()).respond_to(&req);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, r#"{"name":"test","count":3}"#);
+
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "application/json"))
+            .app_data(ProtobufConfig::default().json_fallback(false))
+            .to_http_request();
+        let res = Protobuf(my_object()).respond_to(&req);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/x-protobuf"
+        );
+        assert!(!res.headers().contains_key(header::VARY));
+    }
+
+    #[actix_rt::test]
+    async fn test_extract() {
+        let (req, mut pl) = protobuf_request().to_http_parts();
+        let s = Protobuf::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(s.into_inner(), my_object());
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(header::ContentType::json())
+            .set_payload(r#"{"name":"test","count":3}"#)
+            .to_http_parts();
+        let s = Protobuf::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(s.into_inner(), my_object());
+
+        let (req, mut pl) = TestRequest::default()
+            .set_payload(my_object().encode_to_vec())
+            .app_data(ProtobufConfig::default().content_type_required(false))
+            .to_http_parts();
+        let s = Protobuf::<MyObject>::from_request(&req, &mut pl).await;
+        assert!(s.is_ok());
+    }
+
+    #[actix_rt::test]
+    async fn test_extract_errors() {
+        fn payload_error(err: &Error) -> &ProtobufPayloadError {
+            err.as_error::<ProtobufPayloadError>().unwrap()
+        }
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(header::ContentType::json())
+            .set_payload(r#"{"name":"test","count":3}"#)
+            .app_data(ProtobufConfig::default().json_fallback(false))
+            .to_http_parts();
+        let err = Protobuf::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            payload_error(&err),
+            ProtobufPayloadError::ContentType
+        ));
+
+        let (req, mut pl) = protobuf_request()
+            .app_data(ProtobufConfig::default().limit(4))
+            .to_http_parts();
+        let err = Protobuf::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            payload_error(&err),
+            ProtobufPayloadError::Payload(_)
+        ));
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        // string field with an invalid wire type
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/protobuf"))
+            .set_payload(&b"\x08\x01"[..])
+            .to_http_parts();
+        let err = Protobuf::<MyObject>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            payload_error(&err),
+            ProtobufPayloadError::Decode(_)
+        ));
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+        assert!(err.to_string().contains("MyObject.name"), "{err}");
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_error_responder() {
+        let (req, mut pl) = protobuf_request()
+            .app_data(ProtobufConfig::default().limit(4).error_handler(|err, _| {
+                let res = HttpResponse::Conflict().finish();
+                InternalError::from_response(err, res).into()
+            }))
+            .to_http_parts();
+
+        let s = Protobuf::<MyObject>::from_request(&req, &mut pl).await;
+        let res = HttpResponse::from_error(s.unwrap_err());
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,285 @@
+//! For nested query parameter extractor documentation, see [`QueryDeep`].
+
+use std::{fmt, ops, sync::Arc};
+
+use actix_utils::future::{ready, Ready};
+use serde::de::DeserializeOwned;
+
+use crate::{dev::Payload, error::QueryPayloadError, Error, FromRequest, HttpRequest};
+
+/// Extract typed information from the request's query, including nested structures and lists.
+///
+/// Unlike [`Query`](super::Query), which only understands flat `key=value` pairs, `QueryDeep`
+/// supports bracketed keys using [`serde_qs`]:
+/// - `user[name]=ferris` deserializes into a nested struct or map.
+/// - `ids[]=1&ids[]=2` or `ids[0]=1&ids[1]=2` deserialize into a `Vec`.
+///
+/// Brackets may be percent-encoded (eg. `ids%5B%5D=1`), as is done by most clients. Use
+/// [`QueryDeepConfig`] to configure the extraction process.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// pub struct Filter {
+///    status: Vec<String>,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// pub struct Search {
+///    filter: Filter,
+/// }
+///
+/// // A valid request path for this handler would be
+/// // `/?filter[status][]=open&filter[status][]=closed`.
+/// #[get("/")]
+/// async fn index(search: web::QueryDeep<Search>) -> String {
+///     format!("Searching for statuses: {:?}", search.filter.status)
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct QueryDeep<T>(pub T);
+
+impl<T> QueryDeep<T> {
+    /// Unwrap into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned> QueryDeep<T> {
+    /// Deserialize a `T` from the query parameter string, using the default configuration.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use actix_web::web::QueryDeep;
+    /// let numbers = QueryDeep::<HashMap<String, Vec<u32>>>::from_query("odd[]=1&odd[]=3").unwrap();
+    /// assert_eq!(numbers["odd"], [1, 3]);
+    /// ```
+    pub fn from_query(query_str: &str) -> Result<Self, QueryPayloadError> {
+        QueryDeepConfig::default().deserialize(query_str).map(Self)
+    }
+}
+
+impl<T> ops::Deref for QueryDeep<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for QueryDeep<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for QueryDeep<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl<T: DeserializeOwned> FromRequest for QueryDeep<T> {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let config = req.app_data::<QueryDeepConfig>().unwrap_or(&DEFAULT_CONFIG);
+
+        let res = config
+            .deserialize(req.query_string())
+            .map(QueryDeep)
+            .map_err(|err| {
+                log::debug!(
+                    "Failed during QueryDeep extractor deserialization. \
+                     Request path: {:?}",
+                    req.path()
+                );
+
+                match config.err_handler.as_ref() {
+                    Some(err_handler) => (err_handler)(err, req),
+                    None => err.into(),
+                }
+            });
+
+        ready(res)
+    }
+}
+
+/// Nested query extractor configuration.
+///
+/// # Examples
+/// ```
+/// use actix_web::{error, web, App, HttpResponse};
+///
+/// // custom `QueryDeep` extractor configuration
+/// let query_cfg = web::QueryDeepConfig::default()
+///     // allow keys such as `a[b][c][d]`
+///     .max_depth(3)
+///     // use custom error handler
+///     .error_handler(|err, req| {
+///         error::InternalError::from_response(err, HttpResponse::Conflict().finish()).into()
+///     });
+///
+/// let app = App::new().app_data(query_cfg);
+/// ```
+#[derive(Clone)]
+pub struct QueryDeepConfig {
+    max_depth: usize,
+    strict: bool,
+    #[allow(clippy::type_complexity)]
+    err_handler: Option<Arc<dyn Fn(QueryPayloadError, &HttpRequest) -> Error + Send + Sync>>,
+}
+
+impl QueryDeepConfig {
+    /// Set maximum nesting depth of keys. By default this is 5.
+    ///
+    /// Brackets beyond this depth are treated as part of the key. Limiting the depth prevents
+    /// clients from forcing the construction of deeply nested values.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets whether percent-encoded brackets in keys are rejected. Disabled by default.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Set custom error handler
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(QueryPayloadError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, query_str: &str) -> Result<T, QueryPayloadError> {
+        serde_qs::Config::new(self.max_depth, self.strict)
+            .deserialize_str(query_str)
+            .map_err(QueryPayloadError::DeserializeDeep)
+    }
+}
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: QueryDeepConfig = QueryDeepConfig {
+    max_depth: 5,
+    strict: false,
+    err_handler: None,
+};
+
+impl Default for QueryDeepConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_http::StatusCode;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{error::InternalError, test::TestRequest, HttpResponse};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Filter {
+        status: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Search {
+        q: String,
+        filter: Filter,
+    }
+
+    fn search() -> Search {
+        Search {
+            q: "bug".to_owned(),
+            filter: Filter {
+                status: vec!["open".to_owned(), "closed".to_owned()],
+            },
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_request_extract() {
+        let req = TestRequest::with_uri("/?q=bug&filter[status][]=open&filter[status][]=closed")
+            .to_srv_request();
+        let (req, mut pl) = req.into_parts();
+        let s = QueryDeep::<Search>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(s.into_inner(), search());
+
+        // percent-encoded brackets and indexed lists
+        let req = TestRequest::with_uri(
+            "/?q=bug&filter%5Bstatus%5D%5B1%5D=closed&filter%5Bstatus%5D%5B0%5D=open",
+        )
+        .to_srv_request();
+        let (req, mut pl) = req.into_parts();
+        let s = QueryDeep::<Search>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(s.into_inner(), search());
+
+        let req = TestRequest::with_uri("/?q=bug").to_srv_request();
+        let (req, mut pl) = req.into_parts();
+        let err = QueryDeep::<Search>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_config() {
+        let req = TestRequest::with_uri("/?q=bug&filter[status][]=open")
+            .app_data(QueryDeepConfig::default().max_depth(0))
+            .to_srv_request();
+        let (req, mut pl) = req.into_parts();
+        assert!(QueryDeep::<Search>::from_request(&req, &mut pl)
+            .await
+            .is_err());
+
+        let req = TestRequest::with_uri("/?q=bug&filter%5Bstatus%5D%5B%5D=open")
+            .app_data(QueryDeepConfig::default().strict(true))
+            .to_srv_request();
+        let (req, mut pl) = req.into_parts();
+        assert!(QueryDeep::<Search>::from_request(&req, &mut pl)
+            .await
+            .is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_error_responder() {
+        let req = TestRequest::with_uri("/name/user1/")
+            .app_data(QueryDeepConfig::default().error_handler(|e, _| {
+                let resp = HttpResponse::UnprocessableEntity().finish();
+                InternalError::from_response(e, resp).into()
+            }))
+            .to_srv_request();
+
+        let (req, mut pl) = req.into_parts();
+        let query = QueryDeep::<Search>::from_request(&req, &mut pl).await;
+
+        assert_eq!(
+            query
+                .unwrap_err()
+                .as_response_error()
+                .error_response()
+                .status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! For byte range documentation, see [`Ranges`] and [`PartialContent`].
+
+use std::convert::Infallible;
+
+use actix_utils::future::{ready, Ready};
+
+use crate::{
+    body::{EitherBody, MessageBody},
+    dev::Payload,
+    error::RangeNotSatisfiable,
+    http::header::{self, ByteRangeSpec, ContentRange, ContentRangeSpec, Header as _, Range},
+    FromRequest, HttpRequest, HttpResponse, Responder,
+};
+
+/// Extractor for the byte ranges requested using the `Range` header.
+///
+/// Ranges can only be resolved once the length of the representation is known, using
+/// [`resolve()`](Self::resolve) or [`single()`](Self::single), so this extractor never fails. As
+/// allowed by [RFC 7233 §3.1], `Range` headers that are malformed or use units other than `bytes`
+/// are ignored, as if the full representation was requested.
+///
+/// The responses to range requests can be created using [`PartialContent`]. Conditional range
+/// requests, using the `If-Range` header, are not evaluated by this extractor; read the header
+/// using [`Header<IfRange>`](super::Header) where needed.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web, Either, HttpResponse};
+///
+/// #[get("/blob")]
+/// async fn blob(ranges: web::Ranges) -> actix_web::Result<Either<web::PartialContent<Vec<u8>>, HttpResponse>> {
+///     let blob = vec![0_u8; 1024];
+///
+///     Ok(match ranges.single(blob.len() as u64)? {
+///         Some((start, end)) => {
+///             let body = blob[start as usize..=end as usize].to_vec();
+///             Either::Left(web::PartialContent::new(body, (start, end), blob.len() as u64))
+///         }
+///         None => Either::Right(HttpResponse::Ok().body(blob)),
+///     })
+/// }
+/// ```
+///
+/// [RFC 7233 §3.1]: https://datatracker.ietf.org/doc/html/rfc7233#section-3.1
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Ranges {
+    specs: Vec<ByteRangeSpec>,
+}
+
+impl Ranges {
+    /// Reads the requested byte ranges from the request's `Range` header.
+    pub fn from_req(req: &HttpRequest) -> Self {
+        let specs = match Range::parse(req) {
+            Ok(Range::Bytes(specs)) => specs,
+            _ => Vec::new(),
+        };
+
+        Self { specs }
+    }
+
+    /// Returns true if byte ranges were requested.
+    pub fn is_requested(&self) -> bool {
+        !self.specs.is_empty()
+    }
+
+    /// Returns the requested byte ranges, in the order they were requested.
+    pub fn specs(&self) -> &[ByteRangeSpec] {
+        &self.specs
+    }
+
+    /// Resolves the requested byte ranges against the length of the representation.
+    ///
+    /// Returns the satisfiable ranges, as end-inclusive `(start, end)` pairs, in the order they
+    /// were requested. Unsatisfiable ranges are left out, as described by
+    /// [`ByteRangeSpec::to_satisfiable_range()`]. Returns `None` if no ranges were requested, in
+    /// which case the full representation should be served.
+    ///
+    /// # Errors
+    /// Returns an error, which responds with `416 Range Not Satisfiable`, if none of the ranges
+    /// are satisfiable.
+    pub fn resolve(&self, total_len: u64) -> Result<Option<Vec<(u64, u64)>>, RangeNotSatisfiable> {
+        if !self.is_requested() {
+            return Ok(None);
+        }
+
+        let ranges = self
+            .specs
+            .iter()
+            .filter_map(|spec| spec.to_satisfiable_range(total_len))
+            .collect::<Vec<_>>();
+
+        if ranges.is_empty() {
+            return Err(RangeNotSatisfiable::new(total_len));
+        }
+
+        Ok(Some(ranges))
+    }
+
+    /// Resolves the requested byte ranges against the length of the representation, into a
+    /// single range.
+    ///
+    /// Useful for services that do not support multipart responses. When multiple ranges are
+    /// requested, the returned range spans all of the satisfiable ones, which is allowed by
+    /// [RFC 7233 §4.1]. Otherwise, works the same as [`resolve()`](Self::resolve).
+    ///
+    /// [RFC 7233 §4.1]: https://datatracker.ietf.org/doc/html/rfc7233#section-4.1
+    pub fn single(&self, total_len: u64) -> Result<Option<(u64, u64)>, RangeNotSatisfiable> {
+        Ok(self.resolve(total_len)?.map(|ranges| {
+            ranges
+                .into_iter()
+                .reduce(|(start, end), (next_start, next_end)| {
+                    (start.min(next_start), end.max(next_end))
+                })
+                .expect("resolved ranges should not be empty")
+        }))
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl FromRequest for Ranges {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(Self::from_req(req)))
+    }
+}
+
+/// Responder for a single range of a representation, with a `206 Partial Content` status code.
+///
+/// Sets the `Content-Range` header from the range and the total length of the representation, and
+/// the `Accept-Ranges` header. The body must contain exactly the bytes of the range. Other headers,
+/// such as `Content-Type`, can be added using [`customize()`](Responder::customize).
+///
+/// See [`Ranges`] for an example of serving range requests.
+#[derive(Debug, Clone)]
+pub struct PartialContent<B> {
+    body: B,
+    range: (u64, u64),
+    total_len: u64,
+}
+
+impl<B> PartialContent<B> {
+    /// Constructs a partial response from the body of an end-inclusive `(start, end)` range of
+    /// a representation that is `total_len` bytes long.
+    ///
+    /// # Panics
+    /// Panics if the range is empty or does not fit within the representation.
+    pub fn new(body: B, range: (u64, u64), total_len: u64) -> Self {
+        let (start, end) = range;
+
+        assert!(
+            start <= end && end < total_len,
+            "range {start}-{end} is not within a representation of {total_len} bytes"
+        );
+
+        Self {
+            body,
+            range,
+            total_len,
+        }
+    }
+
+    /// Returns the end-inclusive `(start, end)` range of the body.
+    pub fn range(&self) -> (u64, u64) {
+        self.range
+    }
+
+    /// Returns the total length of the representation.
+    pub fn total_len(&self) -> u64 {
+        self.total_len
+    }
+
+    /// Unwrap into the body.
+    pub fn into_body(self) -> B {
+        self.body
+    }
+}
+
+impl<B: MessageBody + 'static> Responder for PartialContent<B> {
+    type Body = EitherBody<B>;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse<Self::Body> {
+        let content_range = ContentRange(ContentRangeSpec::Bytes {
+            range: Some(self.range),
+            instance_length: Some(self.total_len),
+        });
+
+        match HttpResponse::PartialContent()
+            .insert_header(content_range)
+            .insert_header((header::ACCEPT_RANGES, "bytes"))
+            .message_body(self.body)
+        {
+            Ok(res) => res.map_into_left_body(),
+            Err(err) => HttpResponse::from_error(err).map_into_right_body(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{body, http::StatusCode, test::TestRequest, ResponseError as _};
+
+    fn ranges(range: &str) -> Ranges {
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, range))
+            .to_http_request();
+        Ranges::from_req(&req)
+    }
+
+    #[actix_rt::test]
+    async fn test_extract() {
+        let req = TestRequest::default().to_http_request();
+        let ranges = Ranges::extract(&req).await.unwrap();
+        assert!(!ranges.is_requested());
+        assert_eq!(ranges.resolve(100).unwrap(), None);
+
+        // ignored headers
+        for range in ["bytes=", "bytes 0-10", "lines=0-10"] {
+            assert!(!self::ranges(range).is_requested(), "{range}");
+        }
+
+        let ranges = self::ranges("bytes=0-9, 50-, -10");
+        assert_eq!(
+            ranges.specs(),
+            [
+                ByteRangeSpec::FromTo(0, 9),
+                ByteRangeSpec::From(50),
+                ByteRangeSpec::Last(10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let ranges = ranges("bytes=0-9, 50-, -10, 200-300");
+        assert_eq!(
+            ranges.resolve(100).unwrap().unwrap(),
+            [(0, 9), (50, 99), (90, 99)]
+        );
+        assert_eq!(ranges.single(100).unwrap(), Some((0, 99)));
+
+        let ranges = self::ranges("bytes=20-29, 10-14");
+        assert_eq!(ranges.single(100).unwrap(), Some((10, 29)));
+
+        let err = ranges.resolve(5).unwrap_err();
+        assert_eq!(err.total_len(), 5);
+        assert!(self::ranges("bytes=0-").single(0).is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_not_satisfiable_response() {
+        let res = ranges("bytes=100-")
+            .resolve(10)
+            .unwrap_err()
+            .error_response();
+        assert_eq!(res.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            res.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes */10"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_partial_content() {
+        let req = TestRequest::default().to_http_request();
+        let res = PartialContent::new("llo", (2, 4), 11).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            res.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 2-4/11"
+        );
+        assert_eq!(res.headers().get(header::ACCEPT_RANGES).unwrap(), "bytes");
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "llo");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_partial_content_out_of_bounds() {
+        let _ = PartialContent::new("", (5, 10), 10);
+    }
+}
@@ -0,0 +1,293 @@
+//! For validated extractor documentation, see [`Valid`].
+
+use std::{
+    fmt,
+    future::Future,
+    ops,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use actix_http::Payload;
+use futures_core::ready;
+use pin_project_lite::pin_project;
+use validator::Validate;
+
+use crate::{
+    error::{Error, ValidateError},
+    extract::FromRequest,
+    request::HttpRequest,
+    web,
+};
+
+/// Extractor wrapper that validates the extracted data.
+///
+/// Wraps another extractor, such as [`Json`](super::Json) or [`Query`](super::Query), and runs
+/// [`validator`] checks on its data once extracted. The inner extractor must dereference to a type
+/// that implements [`Validate`], which is typically derived.
+///
+/// Errors from the inner extractor are returned as-is. Data that fails validation results in a
+/// [`ValidateError`], which responds with `422 Unprocessable Entity` and the messages of each
+/// invalid field by default. Use [`ValidConfig`] to render validation errors differently.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web};
+/// use serde::Deserialize;
+/// use validator::Validate;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct SignUp {
+///     #[validate(email)]
+///     email: String,
+///     #[validate(length(min = 8, message = "must be at least 8 characters"))]
+///     password: String,
+/// }
+///
+/// // this handler is only called if the JSON body is a valid `SignUp`
+/// #[post("/sign-up")]
+/// async fn sign_up(form: web::Valid<web::Json<SignUp>>) -> String {
+///     format!("Welcome {}!", form.email)
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Valid<T>(pub T);
+
+impl<T> Valid<T> {
+    /// Unwrap into inner extractor.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Valid<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Valid<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Valid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl<T> FromRequest for Valid<T>
+where
+    T: FromRequest + ops::Deref,
+    T::Target: Validate,
+{
+    type Error = Error;
+    type Future = ValidExtractFut<T>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        ValidExtractFut {
+            fut: T::from_request(req, payload),
+            req: req.clone(),
+        }
+    }
+}
+
+pin_project! {
+    pub struct ValidExtractFut<T: FromRequest> {
+        #[pin]
+        fut: T::Future,
+        req: HttpRequest,
+    }
+}
+
+impl<T> Future for ValidExtractFut<T>
+where
+    T: FromRequest + ops::Deref,
+    T::Target: Validate,
+{
+    type Output = Result<Valid<T>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let data = ready!(this.fut.poll(cx)).map_err(Into::into)?;
+
+        let res = match (*data).validate() {
+            Ok(()) => Ok(Valid(data)),
+            Err(errors) => {
+                log::debug!(
+                    "Failed to validate extracted data. Request path: {}",
+                    this.req.path()
+                );
+
+                let err = ValidateError::new(errors);
+
+                match ValidConfig::from_req(this.req).err_handler.as_ref() {
+                    Some(err_handler) => Err((err_handler)(err, this.req)),
+                    None => Err(err.into()),
+                }
+            }
+        };
+
+        Poll::Ready(res)
+    }
+}
+
+type ValidErrorHandler = Option<Arc<dyn Fn(ValidateError, &HttpRequest) -> Error + Send + Sync>>;
+
+/// `Valid` extractor configuration.
+///
+/// # Examples
+/// ```
+/// use actix_web::{error, web, App, HttpResponse};
+///
+/// // respond with a list of messages instead of messages keyed by field
+/// let valid_cfg = web::ValidConfig::default().error_handler(|err, _req| {
+///     let messages = err
+///         .field_messages()
+///         .into_iter()
+///         .flat_map(|(field, messages)| {
+///             messages.into_iter().map(move |msg| format!("{field}: {msg}"))
+///         })
+///         .collect::<Vec<_>>();
+///
+///     let res = HttpResponse::BadRequest().json(messages);
+///     error::InternalError::from_response(err, res).into()
+/// });
+///
+/// let app = App::new().app_data(valid_cfg);
+/// ```
+#[derive(Clone, Default)]
+pub struct ValidConfig {
+    err_handler: ValidErrorHandler,
+}
+
+impl ValidConfig {
+    /// Set custom error handler, used to render validation errors.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(ValidateError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Extract validation config from app data. Check both `T` and `Data<T>`, in that order, and
+    /// fall back to the default config.
+    fn from_req(req: &HttpRequest) -> &Self {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .unwrap_or(&DEFAULT_CONFIG)
+    }
+}
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: ValidConfig = ValidConfig { err_handler: None };
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{
+        body,
+        error::InternalError,
+        http::{header, StatusCode},
+        test::TestRequest,
+        web::{Json, Query},
+        HttpResponse,
+    };
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Item {
+        #[validate(length(min = 1, message = "must not be empty"))]
+        name: String,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Order {
+        #[validate(email)]
+        email: String,
+        #[validate(range(min = 1))]
+        qty: u32,
+        #[validate(nested)]
+        items: Vec<Item>,
+    }
+
+    #[actix_rt::test]
+    async fn test_valid() {
+        let (req, mut pl) = TestRequest::default()
+            .set_json(serde_json::json!({
+                "email": "ferris@example.com",
+                "qty": 2,
+                "items": [{ "name": "box" }],
+            }))
+            .to_http_parts();
+
+        let order = Valid::<Json<Order>>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(order.email, "ferris@example.com");
+        assert_eq!(order.into_inner().into_inner().items[0].name, "box");
+    }
+
+    #[actix_rt::test]
+    async fn test_invalid() {
+        let (req, mut pl) = TestRequest::default()
+            .set_json(serde_json::json!({
+                "email": "ferris",
+                "qty": 0,
+                "items": [{ "name": "box" }, { "name": "" }],
+            }))
+            .to_http_parts();
+
+        let err = Valid::<Json<Order>>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+
+        let res = err.error_response();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(
+            body,
+            r#"{"errors":{"email":["email"],"items[1].name":["must not be empty"],"qty":["range"]}}"#
+        );
+
+        // errors from inner extractor are returned as-is
+        let req = TestRequest::with_uri("/?name=box").to_http_request();
+        let err = Valid::<Query<Order>>::extract(&req).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_error_handler() {
+        let req = TestRequest::with_uri("/?name=")
+            .app_data(web::Data::new(ValidConfig::default().error_handler(
+                |err, _| {
+                    let res = HttpResponse::BadRequest().body(err.to_string());
+                    InternalError::from_response(err, res).into()
+                },
+            )))
+            .to_http_request();
+
+        let err = Valid::<Query<Item>>::extract(&req).await.unwrap_err();
+        let res = err.error_response();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "Validation error: name: must not be empty");
+    }
+}
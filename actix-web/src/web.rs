@@ -4,16 +4,36 @@
 //! - [`Data`]: Application data item
 //! - [`ThinData`]: Cheap-to-clone application data item
 //! - [`ReqData`]: Request-local data item
+//! - [`ConnData`]: Connection-local data item
+//! - [`RealIp`]: Client IP address, resolved through trusted proxies
 //! - [`Path`]: URL path parameters / dynamic segments
 //! - [`Query`]: URL query parameters
+//! - `QueryDeep`: Nested URL query parameters (requires `query-deep` feature)
+//! - [`Pagination`] and [`CursorPagination`]: Pagination query parameters
 //! - [`Header`]: Typed header
+//! - [`Ranges`]: Byte ranges requested using the `Range` header
+//! - [`Accepts`]: Media types accepted by the client
 //! - [`Json`]: JSON payload
+//! - [`JsonLinesStream`]: Newline-delimited JSON payload stream
 //! - [`Form`]: URL-encoded payload
+//! - [`OneOf`]: Payload in one of several encodings, chosen by content type
+//! - `Csv`: CSV payload (requires `csv` feature)
+//! - `MsgPack`: MessagePack payload (requires `msgpack` feature)
+//! - `Protobuf`: Protocol Buffers payload (requires `protobuf` feature)
+//! - `BodyFile`: Raw payload streamed to a temporary file (requires `tempfile` feature)
+//! - `WebSocket`: WebSocket connection upgrade (requires `ws` feature)
 //! - [`Bytes`]: Raw payload
+//! - `Valid`: Validated data from another extractor (requires `validator` feature)
 //!
 //! # Responders
 //! - [`Json`]: JSON response
+//! - [`JsonLines`]: Newline-delimited JSON response stream
 //! - [`Form`]: URL-encoded response
+//! - `Csv` and `CsvStream`: CSV response (requires `csv` feature)
+//! - `MsgPack`: MessagePack response (requires `msgpack` feature)
+//! - `Protobuf`: Protocol Buffers response (requires `protobuf` feature)
+//! - [`Negotiate`]: Response in the format preferred by the client
+//! - [`PartialContent`]: Partial response to a range request
 //! - [`Bytes`]: Raw bytes response
 //! - [`Redirect`](Redirect::to): Convenient redirect responses
 
@@ -22,9 +42,18 @@ use std::{borrow::Cow, future::Future};
 use actix_router::IntoPatterns;
 pub use bytes::{Buf, BufMut, Bytes, BytesMut};
 
+#[cfg(feature = "ws")]
+pub use crate::ws::WebSocket;
 pub use crate::{
-    config::ServiceConfig, data::Data, redirect::Redirect, request_data::ReqData,
-    thin_data::ThinData, types::*,
+    config::ServiceConfig,
+    connection::ConnData,
+    data::Data,
+    health::HealthChecks,
+    real_ip::{ProxyProtocolAddr, RealIp, RealIpConfig, RealIpSource},
+    redirect::Redirect,
+    request_data::ReqData,
+    thin_data::ThinData,
+    types::*,
 };
 use crate::{
     error::BlockingError, http::Method, service::WebService, FromRequest, Handler, Resource,
@@ -203,6 +232,22 @@ pub fn redirect(from: impl Into<Cow<'static, str>>, to: impl Into<Cow<'static, s
     Redirect::new(from, to)
 }
 
+/// Creates liveness and readiness endpoints backed by async probes.
+///
+/// See [`HealthChecks`] for more details on probes and graceful shutdown.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App};
+///
+/// let app = App::new().service(
+///     web::health_checks().readiness("cache", || async { Ok::<_, std::io::Error>(()) }),
+/// );
+/// ```
+pub fn health_checks() -> HealthChecks {
+    HealthChecks::new()
+}
+
 /// Executes blocking function on a thread pool, returns future that resolves to result of the
 /// function execution.
 pub fn block<F, R>(f: F) -> impl Future<Output = Result<R, BlockingError>>
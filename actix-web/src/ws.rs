@@ -0,0 +1,747 @@
+//! WebSocket support, without the actor framework.
+//!
+//! See [`WebSocket`] for usage.
+
+use std::{
+    cell::Cell,
+    future::{poll_fn, Future as _},
+    io,
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use actix_codec::{Decoder as _, Encoder as _};
+use actix_http::{
+    body::{BodySize, MessageBody},
+    ws::{Codec, Frame, Item},
+    Payload,
+};
+use actix_utils::future::{ready, Ready};
+use bytes::{Bytes, BytesMut};
+use bytestring::ByteString;
+use derive_more::derive::{Display, Error};
+use futures_core::Stream;
+use tokio::sync::mpsc;
+
+pub use actix_http::ws::{CloseCode, CloseReason, HandshakeError, Message, ProtocolError};
+
+use crate::{
+    http::header::{self, HeaderValue},
+    rt::time::{sleep, Instant, Sleep},
+    web, Error, FromRequest, HttpRequest, HttpResponse,
+};
+
+/// Number of outgoing messages that can be queued before sending waits for the connection.
+const CHANNEL_CAPACITY: usize = 32;
+
+/// Extractor that upgrades a request to a WebSocket connection.
+///
+/// Performs the WebSocket handshake and splits the connection, using
+/// [`into_parts()`](Self::into_parts), into:
+/// - the handshake response, which must be returned from the handler to complete the upgrade;
+/// - a [`Session`], used to send messages;
+/// - a [`MessageStream`], used to receive messages.
+///
+/// Messages are usually handled by a task spawned using [`rt::spawn()`](crate::rt::spawn), so
+/// that the handler can return the response. The connection is closed once a close message is
+/// sent, or once both the session and the message stream are dropped.
+///
+/// Pings from the client are answered automatically, and the connection is kept alive by sending
+/// pings to the client. Fragmented messages are reassembled before they are received. Use
+/// [`WebSocketConfig`] to configure the size limits of frames and messages, and heartbeats.
+///
+/// Requests that are not valid WebSocket handshakes are rejected with a [`HandshakeError`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, rt, web, ws::Message, HttpResponse};
+///
+/// #[get("/echo")]
+/// async fn echo(ws: web::WebSocket) -> HttpResponse {
+///     let (res, session, mut stream) = ws.into_parts();
+///
+///     rt::spawn(async move {
+///         while let Some(Ok(msg)) = stream.recv().await {
+///             let res = match msg {
+///                 Message::Text(text) => session.text(text).await,
+///                 Message::Binary(bin) => session.binary(bin).await,
+///                 _ => Ok(()),
+///             };
+///
+///             if res.is_err() {
+///                 break;
+///             }
+///         }
+///     });
+///
+///     res
+/// }
+/// ```
+pub struct WebSocket {
+    res: HttpResponse,
+    session: Session,
+    stream: MessageStream,
+}
+
+impl WebSocket {
+    /// Splits into the handshake response, the session, and the message stream.
+    pub fn into_parts(self) -> (HttpResponse, Session, MessageStream) {
+        (self.res, self.session, self.stream)
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl FromRequest for WebSocket {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = WebSocketConfig::from_req(req);
+
+        let mut res = match actix_http::ws::handshake(req.head()) {
+            Ok(res) => res,
+            Err(err) => {
+                log::debug!(
+                    "Failed during WebSocket handshake. Request path: {}",
+                    req.path()
+                );
+
+                return ready(Err(match config.err_handler {
+                    Some(ref err_handler) => err_handler(err, req),
+                    None => err.into(),
+                }));
+            }
+        };
+
+        if let Some(protocol) = config.select_protocol(req) {
+            res.insert_header((header::SEC_WEBSOCKET_PROTOCOL, protocol));
+        }
+
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let closed = Arc::new(AtomicBool::new(false));
+        let last_seen = Rc::new(Cell::new(Instant::now()));
+
+        let body = WebSocketBody {
+            rx,
+            codec: Codec::new(),
+            buf: BytesMut::new(),
+            heartbeat: config.heartbeat.map(|(interval, timeout)| Heartbeat {
+                interval,
+                timeout,
+                sleep: Box::pin(sleep(interval)),
+                last_seen: Rc::clone(&last_seen),
+            }),
+            done: false,
+        };
+
+        let session = Session {
+            tx: tx.clone(),
+            closed: Arc::clone(&closed),
+        };
+
+        let stream = MessageStream {
+            payload: payload.take(),
+            codec: Codec::new().max_size(config.frame_size),
+            buf: BytesMut::new(),
+            max_message_size: config.max_message_size,
+            continuation: None,
+            tx,
+            closed,
+            last_seen,
+            eof: false,
+        };
+
+        ready(Ok(WebSocket {
+            res: HttpResponse::from(res.body(body)).map_into_boxed_body(),
+            session,
+            stream,
+        }))
+    }
+}
+
+/// Error returned when sending a message over a closed WebSocket session.
+#[derive(Debug, Display, Error)]
+#[display("WebSocket session is closed")]
+#[non_exhaustive]
+pub struct Closed;
+
+/// Handle used to send messages over a WebSocket connection.
+///
+/// Sessions can be cloned, and sent to other threads, to send messages from multiple tasks.
+#[derive(Debug, Clone)]
+pub struct Session {
+    tx: mpsc::Sender<Message>,
+    closed: Arc<AtomicBool>,
+}
+
+impl Session {
+    /// Sends a text message.
+    pub async fn text(&self, text: impl Into<ByteString>) -> Result<(), Closed> {
+        self.send(Message::Text(text.into())).await
+    }
+
+    /// Sends a binary message.
+    pub async fn binary(&self, bin: impl Into<Bytes>) -> Result<(), Closed> {
+        self.send(Message::Binary(bin.into())).await
+    }
+
+    /// Sends a ping message.
+    pub async fn ping(&self, msg: &[u8]) -> Result<(), Closed> {
+        self.send(Message::Ping(Bytes::copy_from_slice(msg))).await
+    }
+
+    /// Sends a pong message.
+    pub async fn pong(&self, msg: &[u8]) -> Result<(), Closed> {
+        self.send(Message::Pong(Bytes::copy_from_slice(msg))).await
+    }
+
+    /// Sends a close message, after which the connection is closed.
+    ///
+    /// Fails if the session was already closed, including by a clone of this session.
+    pub async fn close(self, reason: Option<CloseReason>) -> Result<(), Closed> {
+        if self.closed.swap(true, Ordering::AcqRel) {
+            return Err(Closed);
+        }
+
+        self.tx
+            .send(Message::Close(reason))
+            .await
+            .map_err(|_| Closed)
+    }
+
+    /// Returns true if the session is closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire) || self.tx.is_closed()
+    }
+
+    async fn send(&self, msg: Message) -> Result<(), Closed> {
+        if self.closed.load(Ordering::Acquire) {
+            return Err(Closed);
+        }
+
+        self.tx.send(msg).await.map_err(|_| Closed)
+    }
+}
+
+/// Stream of messages received over a WebSocket connection.
+///
+/// Yields text, binary, ping, pong, and close messages. Fragmented messages are reassembled, so
+/// continuation messages are never yielded. Pings are answered, and close messages are echoed if
+/// the session was not closed yet, before they are yielded.
+///
+/// The stream ends when the client disconnects, or after a protocol error.
+pub struct MessageStream {
+    payload: Payload,
+    codec: Codec,
+    buf: BytesMut,
+    max_message_size: usize,
+    continuation: Option<(bool, BytesMut)>,
+    tx: mpsc::Sender<Message>,
+    closed: Arc<AtomicBool>,
+    last_seen: Rc<Cell<Instant>>,
+    eof: bool,
+}
+
+impl MessageStream {
+    /// Waits for the next message.
+    pub async fn recv(&mut self) -> Option<Result<Message, ProtocolError>> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Converts a decoded frame into a message, reassembling fragmented messages.
+    fn handle_frame(&mut self, frame: Frame) -> Result<Option<Message>, ProtocolError> {
+        let msg = match frame {
+            Frame::Text(data) => Message::Text(text(data)?),
+            Frame::Binary(data) => Message::Binary(data),
+
+            Frame::Ping(data) => {
+                if self.tx.try_send(Message::Pong(data.clone())).is_err() {
+                    log::debug!("WebSocket pong could not be queued");
+                }
+
+                Message::Ping(data)
+            }
+
+            Frame::Pong(data) => Message::Pong(data),
+
+            Frame::Close(reason) => {
+                if !self.closed.swap(true, Ordering::AcqRel) {
+                    let _ = self.tx.try_send(Message::Close(reason.clone()));
+                }
+
+                Message::Close(reason)
+            }
+
+            Frame::Continuation(item) => return self.handle_continuation(item),
+        };
+
+        Ok(Some(msg))
+    }
+
+    fn handle_continuation(&mut self, item: Item) -> Result<Option<Message>, ProtocolError> {
+        let (first, data, last) = match item {
+            Item::FirstText(data) => (Some(true), data, false),
+            Item::FirstBinary(data) => (Some(false), data, false),
+            Item::Continue(data) => (None, data, false),
+            Item::Last(data) => (None, data, true),
+        };
+
+        let (is_text, buf) = match first {
+            Some(_) if self.continuation.is_some() => {
+                return Err(ProtocolError::ContinuationStarted);
+            }
+            Some(is_text) => self.continuation.insert((is_text, BytesMut::new())),
+            None => self
+                .continuation
+                .as_mut()
+                .ok_or(ProtocolError::ContinuationNotStarted)?,
+        };
+
+        if buf.len() + data.len() > self.max_message_size {
+            return Err(ProtocolError::Overflow);
+        }
+
+        buf.extend_from_slice(&data);
+        let is_text = *is_text;
+
+        if !last {
+            return Ok(None);
+        }
+
+        let (_, buf) = self.continuation.take().unwrap();
+        let data = buf.freeze();
+
+        Ok(Some(if is_text {
+            Message::Text(text(data)?)
+        } else {
+            Message::Binary(data)
+        }))
+    }
+}
+
+impl Stream for MessageStream {
+    type Item = Result<Message, ProtocolError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        while !this.eof {
+            match Pin::new(&mut this.payload).poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => this.buf.extend_from_slice(&chunk),
+                Poll::Ready(Some(Err(err))) => {
+                    this.eof = true;
+                    this.buf.clear();
+                    return Poll::Ready(Some(Err(ProtocolError::Io(io::Error::other(
+                        err.to_string(),
+                    )))));
+                }
+                Poll::Ready(None) => this.eof = true,
+                Poll::Pending => break,
+            }
+        }
+
+        loop {
+            let frame = match this.codec.decode(&mut this.buf) {
+                Ok(Some(frame)) => frame,
+                Ok(None) if this.eof => return Poll::Ready(None),
+                Ok(None) => return Poll::Pending,
+                Err(err) => {
+                    this.eof = true;
+                    this.buf.clear();
+                    return Poll::Ready(Some(Err(err)));
+                }
+            };
+
+            this.last_seen.set(Instant::now());
+
+            match this.handle_frame(frame) {
+                Ok(Some(msg)) => return Poll::Ready(Some(Ok(msg))),
+                Ok(None) => {}
+                Err(err) => {
+                    this.eof = true;
+                    this.buf.clear();
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+        }
+    }
+}
+
+/// Validates the UTF-8 encoding of a text message.
+fn text(data: Bytes) -> Result<ByteString, ProtocolError> {
+    ByteString::try_from(data)
+        .map_err(|err| ProtocolError::Io(io::Error::new(io::ErrorKind::InvalidData, err)))
+}
+
+/// Sends pings to the client, and closes the connection if it stops responding.
+struct Heartbeat {
+    interval: Duration,
+    timeout: Duration,
+    sleep: Pin<Box<Sleep>>,
+    last_seen: Rc<Cell<Instant>>,
+}
+
+/// Response body that encodes the messages sent using a [`Session`].
+struct WebSocketBody {
+    rx: mpsc::Receiver<Message>,
+    codec: Codec,
+    buf: BytesMut,
+    heartbeat: Option<Heartbeat>,
+    done: bool,
+}
+
+impl WebSocketBody {
+    fn encode(&mut self, msg: Message) -> Result<(), ProtocolError> {
+        if matches!(msg, Message::Close(_)) {
+            self.done = true;
+        }
+
+        self.codec.encode(msg, &mut self.buf)
+    }
+}
+
+impl MessageBody for WebSocketBody {
+    type Error = ProtocolError;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        if let Some(hb) = this.heartbeat.as_mut().filter(|_| !this.done) {
+            if hb.sleep.as_mut().poll(cx).is_ready() {
+                let msg = if hb.last_seen.get().elapsed() > hb.timeout {
+                    log::debug!("WebSocket client timed out; closing connection");
+                    Message::Close(Some(CloseCode::Away.into()))
+                } else {
+                    Message::Ping(Bytes::new())
+                };
+
+                let deadline = Instant::now() + hb.interval;
+                hb.sleep.as_mut().reset(deadline);
+                // register the new deadline with the waker
+                let _ = hb.sleep.as_mut().poll(cx);
+
+                this.encode(msg)?;
+            }
+        }
+
+        while !this.done {
+            match this.rx.poll_recv(cx) {
+                Poll::Ready(Some(msg)) => this.encode(msg)?,
+                Poll::Ready(None) => this.done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        if !this.buf.is_empty() {
+            Poll::Ready(Some(Ok(this.buf.split().freeze())))
+        } else if this.done {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+type WebSocketErrorHandler =
+    Option<Arc<dyn Fn(HandshakeError, &HttpRequest) -> Error + Send + Sync>>;
+
+/// [`WebSocket`] extractor configuration.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use actix_web::{web, ws::WebSocketConfig, App};
+///
+/// let app = App::new().app_data(
+///     WebSocketConfig::default()
+///         .frame_size(16 * 1024)
+///         .max_message_size(256 * 1024)
+///         .heartbeat(Duration::from_secs(10), Duration::from_secs(30))
+///         .protocols(["chat"]),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct WebSocketConfig {
+    frame_size: usize,
+    max_message_size: usize,
+    heartbeat: Option<(Duration, Duration)>,
+    protocols: Vec<String>,
+    err_handler: WebSocketErrorHandler,
+}
+
+impl WebSocketConfig {
+    /// Sets the maximum size of received frames. By default this limit is 64kB.
+    pub fn frame_size(mut self, size: usize) -> Self {
+        self.frame_size = size;
+        self
+    }
+
+    /// Sets the maximum size of received messages that are fragmented into continuation frames.
+    /// By default this limit is 1MB.
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+
+    /// Sets the interval at which pings are sent to the client, and the time after which the
+    /// connection is closed if nothing is received from the client.
+    ///
+    /// By default, pings are sent every 5 seconds, with a timeout of 10 seconds. Messages only count
+    /// as received once they are read from the [`MessageStream`].
+    pub fn heartbeat(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.heartbeat = Some((interval, timeout));
+        self
+    }
+
+    /// Disables sending pings to the client and closing unresponsive connections.
+    pub fn no_heartbeat(mut self) -> Self {
+        self.heartbeat = None;
+        self
+    }
+
+    /// Sets the subprotocols supported by the server.
+    ///
+    /// The first protocol requested by the client that is supported is selected using the
+    /// `Sec-WebSocket-Protocol` response header.
+    pub fn protocols<I>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.protocols = protocols.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set custom error handler.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(HandshakeError, &HttpRequest) -> Error + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+
+    /// Extract config from app data. Check both `T` and `Data<T>`, in that order, and fall back to
+    /// the default config.
+    fn from_req(req: &HttpRequest) -> &Self {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .unwrap_or(&DEFAULT_CONFIG)
+    }
+
+    fn select_protocol(&self, req: &HttpRequest) -> Option<HeaderValue> {
+        let requested = req
+            .headers()
+            .get(header::SEC_WEBSOCKET_PROTOCOL)?
+            .to_str()
+            .ok()?;
+
+        requested
+            .split(',')
+            .map(str::trim)
+            .find(|protocol| self.protocols.iter().any(|p| p == protocol))
+            .and_then(|protocol| HeaderValue::from_str(protocol).ok())
+    }
+}
+
+/// Allow shared refs used as default.
+static DEFAULT_CONFIG: WebSocketConfig = WebSocketConfig {
+    frame_size: 65_536,          // 64kB
+    max_message_size: 1_048_576, // 1MB
+    heartbeat: Some((Duration::from_secs(5), Duration::from_secs(10))),
+    protocols: Vec::new(),
+    err_handler: None,
+};
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        body,
+        http::{Method, StatusCode},
+        test::TestRequest,
+    };
+
+    fn handshake() -> TestRequest {
+        TestRequest::default()
+            .insert_header((header::UPGRADE, "websocket"))
+            .insert_header((header::CONNECTION, "upgrade"))
+            .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+            .insert_header((header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ=="))
+    }
+
+    /// Encodes messages as sent by a client.
+    fn client_frames(msgs: impl IntoIterator<Item = Message>) -> Bytes {
+        let mut codec = Codec::new().client_mode();
+        let mut buf = BytesMut::new();
+
+        for msg in msgs {
+            codec.encode(msg, &mut buf).unwrap();
+        }
+
+        buf.freeze()
+    }
+
+    /// Decodes messages as received by a client.
+    fn server_frames(body: Bytes) -> Vec<Frame> {
+        let mut codec = Codec::new().client_mode();
+        let mut buf = BytesMut::from(&body[..]);
+
+        std::iter::from_fn(|| codec.decode(&mut buf).unwrap()).collect()
+    }
+
+    #[actix_rt::test]
+    async fn test_handshake() {
+        let (req, mut pl) = handshake().method(Method::POST).to_http_parts();
+        let err = WebSocket::from_request(&req, &mut pl).await.err().unwrap();
+        assert_eq!(
+            err.as_error::<HandshakeError>(),
+            Some(&HandshakeError::GetMethodRequired)
+        );
+
+        let (req, mut pl) = handshake()
+            .insert_header((header::SEC_WEBSOCKET_PROTOCOL, "graphql-ws, chat"))
+            .app_data(WebSocketConfig::default().protocols(["chat", "graphql-ws"]))
+            .to_http_parts();
+        let ws = WebSocket::from_request(&req, &mut pl).await.unwrap();
+        let (res, _, _) = ws.into_parts();
+
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(
+            res.headers().get(header::SEC_WEBSOCKET_ACCEPT).unwrap(),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        assert_eq!(
+            res.headers().get(header::SEC_WEBSOCKET_PROTOCOL).unwrap(),
+            "graphql-ws"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_messages() {
+        let payload = client_frames([
+            Message::Text("hello".into()),
+            Message::Ping(Bytes::from_static(b"p")),
+            Message::Continuation(Item::FirstBinary(Bytes::from_static(b"ab"))),
+            Message::Continuation(Item::Continue(Bytes::from_static(b"cd"))),
+            Message::Continuation(Item::Last(Bytes::from_static(b"ef"))),
+            Message::Close(Some(CloseCode::Normal.into())),
+        ]);
+
+        let (req, mut pl) = handshake()
+            .app_data(WebSocketConfig::default().no_heartbeat())
+            .set_payload(payload)
+            .to_http_parts();
+        let (res, session, mut stream) = WebSocket::from_request(&req, &mut pl)
+            .await
+            .unwrap()
+            .into_parts();
+
+        let msg = stream.recv().await.unwrap().unwrap();
+        assert_eq!(msg, Message::Text("hello".into()));
+        session.text("hi").await.unwrap();
+
+        let msg = stream.recv().await.unwrap().unwrap();
+        assert_eq!(msg, Message::Ping(Bytes::from_static(b"p")));
+
+        let msg = stream.recv().await.unwrap().unwrap();
+        assert_eq!(msg, Message::Binary(Bytes::from_static(b"abcdef")));
+
+        let msg = stream.recv().await.unwrap().unwrap();
+        assert_eq!(msg, Message::Close(Some(CloseCode::Normal.into())));
+        assert!(session.is_closed());
+        assert!(matches!(session.text("bye").await, Err(Closed)));
+
+        assert!(stream.recv().await.is_none());
+
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let frames = server_frames(body);
+        assert_eq!(
+            frames,
+            [
+                Frame::Text(Bytes::from_static(b"hi")),
+                Frame::Pong(Bytes::from_static(b"p")),
+                Frame::Close(Some(CloseCode::Normal.into())),
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_limits() {
+        let payload = client_frames([
+            Message::Continuation(Item::FirstText(Bytes::from_static(b"abc"))),
+            Message::Continuation(Item::Last(Bytes::from_static(b"def"))),
+        ]);
+
+        let (req, mut pl) = handshake()
+            .app_data(WebSocketConfig::default().max_message_size(5))
+            .set_payload(payload)
+            .to_http_parts();
+        let (_, _, mut stream) = WebSocket::from_request(&req, &mut pl)
+            .await
+            .unwrap()
+            .into_parts();
+
+        let err = stream.recv().await.unwrap().unwrap_err();
+        assert!(matches!(err, ProtocolError::Overflow));
+        assert!(stream.recv().await.is_none());
+
+        let payload = client_frames([Message::Binary(Bytes::from_static(b"too large"))]);
+
+        let (req, mut pl) = handshake()
+            .app_data(WebSocketConfig::default().frame_size(4))
+            .set_payload(payload)
+            .to_http_parts();
+        let (_, _, mut stream) = WebSocket::from_request(&req, &mut pl)
+            .await
+            .unwrap()
+            .into_parts();
+
+        let err = stream.recv().await.unwrap().unwrap_err();
+        assert!(matches!(err, ProtocolError::Overflow));
+    }
+
+    #[actix_rt::test]
+    async fn test_heartbeat() {
+        let (req, mut pl) = handshake()
+            .app_data(
+                WebSocketConfig::default()
+                    .heartbeat(Duration::from_millis(10), Duration::from_millis(25)),
+            )
+            .to_http_parts();
+        let (res, _session, _stream) = WebSocket::from_request(&req, &mut pl)
+            .await
+            .unwrap()
+            .into_parts();
+
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let frames = server_frames(body);
+
+        let (close, pings) = frames.split_last().unwrap();
+        assert!(!pings.is_empty());
+        assert!(pings
+            .iter()
+            .all(|frame| *frame == Frame::Ping(Bytes::new())));
+        assert_eq!(*close, Frame::Close(Some(CloseCode::Away.into())));
+    }
+}
@@ -0,0 +1,57 @@
+use actix_web::{collect_services, get, post, test, App, HttpResponse, Responder};
+
+#[get("/", auto_register = true)]
+async fn index() -> impl Responder {
+    HttpResponse::Ok().body("index")
+}
+
+#[post("/users", auto_register = true)]
+async fn create_user() -> impl Responder {
+    HttpResponse::Created().finish()
+}
+
+mod nested {
+    use actix_web::{get, HttpResponse, Responder};
+
+    #[get("/nested", auto_register = true)]
+    pub(crate) async fn nested() -> impl Responder {
+        HttpResponse::Ok().body("nested")
+    }
+}
+
+#[get("/manual")]
+async fn manual() -> impl Responder {
+    HttpResponse::Ok().body("manual")
+}
+
+#[actix_web::test]
+async fn collects_all_handlers() {
+    let app = test::init_service(App::new().service(collect_services!())).await;
+
+    let req = test::TestRequest::get().uri("/").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "index");
+
+    let req = test::TestRequest::post().uri("/users").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 201);
+
+    let req = test::TestRequest::get().uri("/nested").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "nested");
+}
+
+#[actix_web::test]
+async fn skips_handlers_without_opt_in() {
+    let app = test::init_service(App::new().service(collect_services!())).await;
+
+    let req = test::TestRequest::get().uri("/manual").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 404);
+
+    let app = test::init_service(App::new().service(manual)).await;
+
+    let req = test::TestRequest::get().uri("/manual").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "manual");
+}
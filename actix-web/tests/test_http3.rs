@@ -0,0 +1,63 @@
+extern crate tls_rustls as rustls;
+
+use actix_web::{
+    http::{header, Method, StatusCode},
+    web, App, HttpRequest, HttpResponse,
+};
+use bytes::Bytes;
+use rustls::{pki_types::PrivateKeyDer, ServerConfig as RustlsServerConfig};
+
+fn tls_config() -> RustlsServerConfig {
+    let rcgen::CertifiedKey { cert, key_pair } =
+        rcgen::generate_simple_self_signed(["localhost".to_owned()]).unwrap();
+
+    RustlsServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![cert.der().clone()],
+            PrivateKeyDer::Pkcs8(key_pair.serialize_der().into()),
+        )
+        .unwrap()
+}
+
+#[actix_rt::test]
+async fn test_h3_listener() {
+    let srv = actix_test::start_with(actix_test::config().h3_listener(tls_config()), || {
+        App::new().service(
+            web::resource("/").to(|req: HttpRequest, body: Bytes| async move {
+                HttpResponse::Ok()
+                    .insert_header(("x-version", format!("{:?}", req.version())))
+                    .body(body)
+            }),
+        )
+    });
+
+    let res = srv
+        .h3_request(Method::POST, "/")
+        .insert_header((header::CONTENT_TYPE, "text/plain"))
+        .send_body("over quic")
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get("x-version").unwrap(), "HTTP/3.0");
+    assert_eq!(res.headers().get(header::CONTENT_LENGTH).unwrap(), "9");
+    assert_eq!(res.body(), "over quic");
+
+    let res = srv.h3_request(Method::HEAD, "/").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.body().is_empty());
+
+    // the TCP listener keeps serving requests alongside the QUIC listener
+    let res = srv.get("/").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(srv.accepted_connections(), 3);
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+#[should_panic(expected = "configured with `h3_listener()`")]
+async fn test_h3_request_without_listener() {
+    let srv = actix_test::start(App::new);
+    let _ = srv.h3_request(Method::GET, "/");
+}
@@ -153,3 +153,154 @@ async fn test_start_ssl() {
 
     srv.stop(false).await;
 }
+
+#[actix_rt::test]
+#[cfg(all(feature = "openssl", feature = "x509"))]
+async fn test_ssl_client_cert() {
+    use actix_web::auth::ClientCert;
+    use openssl::{
+        pkey::PKey,
+        ssl::{SslConnector, SslMethod, SslVerifyMode},
+        x509::X509,
+    };
+
+    let addr = actix_test::unused_addr();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        actix_rt::System::new()
+            .block_on(async {
+                let mut builder = ssl_acceptor();
+
+                // request client certificates, and accept any of them
+                builder.set_verify_callback(SslVerifyMode::PEER, |_, _| true);
+
+                let srv = HttpServer::new(|| {
+                    App::new().route(
+                        "/",
+                        web::to(|cert: ClientCert| async move {
+                            let leaf = cert.leaf();
+                            format!(
+                                "{} {}",
+                                leaf.common_name().unwrap(),
+                                leaf.subject_alt_names().len()
+                            )
+                        }),
+                    )
+                })
+                .workers(1)
+                .shutdown_timeout(1)
+                .system_exit()
+                .disable_signals()
+                .bind_openssl(format!("{}", addr), builder)
+                .unwrap();
+
+                let srv = srv.run();
+                tx.send(srv.handle()).unwrap();
+
+                srv.await
+            })
+            .unwrap()
+    });
+    let srv = rx.recv().unwrap();
+
+    let connector = |client_cert: bool| {
+        let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+        builder.set_verify(SslVerifyMode::NONE);
+
+        if client_cert {
+            let rcgen::CertifiedKey { cert, key_pair } =
+                rcgen::generate_simple_self_signed(["client.localhost".to_owned()]).unwrap();
+
+            let cert = X509::from_pem(cert.pem().as_bytes()).unwrap();
+            let key = PKey::private_key_from_pem(key_pair.serialize_pem().as_bytes()).unwrap();
+            builder.set_certificate(&cert).unwrap();
+            builder.set_private_key(&key).unwrap();
+        }
+
+        awc::Client::builder()
+            .connector(
+                awc::Connector::new()
+                    .openssl(builder.build())
+                    .timeout(Duration::from_millis(500)),
+            )
+            .finish()
+    };
+
+    let host = format!("https://{}", addr);
+
+    let mut response = connector(true).get(&host).send().await.unwrap();
+    assert!(response.status().is_success());
+    let body = response.body().await.unwrap();
+    assert_eq!(body, "rcgen self signed cert 1");
+
+    let response = connector(false).get(&host).send().await.unwrap();
+    assert_eq!(response.status(), actix_web::http::StatusCode::FORBIDDEN);
+
+    srv.stop(false).await;
+}
+
+#[actix_rt::test]
+async fn test_on_connection() {
+    use std::{
+        cell::Cell,
+        io::{Read as _, Write as _},
+        net::TcpStream,
+        rc::Rc,
+    };
+
+    let addr = actix_test::unused_addr();
+    let (tx, rx) = mpsc::channel();
+    let (closed_tx, closed_rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        actix_rt::System::new()
+            .block_on(async {
+                let srv = HttpServer::new(|| {
+                    App::new().route(
+                        "/",
+                        web::to(|count: web::ConnData<Rc<Cell<u32>>>| async move {
+                            count.set(count.get() + 1);
+                            count.get().to_string()
+                        }),
+                    )
+                })
+                .workers(1)
+                .on_connection(move |conn| {
+                    conn.set_nodelay(true).unwrap();
+                    conn.set_keepalive(Some(Duration::from_secs(60))).unwrap();
+
+                    let count = Rc::new(Cell::new(0_u32));
+                    conn.insert(Rc::clone(&count));
+
+                    let closed_tx = closed_tx.clone();
+                    conn.on_disconnect(move || closed_tx.send(count.get()).unwrap());
+                })
+                .disable_signals()
+                .bind(addr)
+                .unwrap()
+                .run();
+
+                tx.send(srv.handle()).unwrap();
+
+                srv.await
+            })
+            .unwrap();
+    });
+
+    let srv = rx.recv().unwrap();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\nconnection: close\r\n\r\n")
+        .unwrap();
+
+    let mut res = String::new();
+    stream.read_to_string(&mut res).unwrap();
+    assert!(res.ends_with("\r\n\r\n2"));
+
+    let count = closed_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(count, 2);
+
+    srv.stop(false).await;
+}
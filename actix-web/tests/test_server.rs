@@ -769,6 +769,742 @@ mod plus_rustls {
 
         srv.stop().await;
     }
+
+    #[actix_rt::test]
+    async fn test_rustls_client() {
+        let srv = actix_test::start_with(actix_test::config().rustls_0_23(tls_config()), || {
+            App::new().service(web::resource("/").to(|| async { "secure" }))
+        });
+
+        assert!(srv.url("/").starts_with("https://"));
+
+        let mut res = srv.get("/").send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().await.unwrap(), Bytes::from_static(b"secure"));
+
+        srv.stop().await;
+    }
+}
+
+#[cfg(unix)]
+#[actix_rt::test]
+async fn test_uds_server() {
+    let path = std::env::temp_dir().join(format!("actix-test-{}.sock", std::process::id()));
+
+    let srv = actix_test::start_with(actix_test::config().uds(&path), || {
+        App::new().service(web::resource("/").to(|| async { "over uds" }))
+    });
+
+    assert_eq!(srv.uds_path(), Some(path.as_path()));
+    assert!(path.exists());
+
+    let mut res = srv.get("/").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body().await.unwrap(), Bytes::from_static(b"over uds"));
+
+    srv.stop().await;
+    assert!(!path.exists());
+}
+
+#[actix_rt::test]
+async fn test_response_snapshot() {
+    let srv = actix_test::start(|| {
+        App::new().service(web::resource("/").to(|| async {
+            HttpResponse::Ok()
+                .content_type(mime::TEXT_PLAIN_UTF_8)
+                .insert_header(("x-request-id", "8f14e45f"))
+                .body("session 8f14e45f started")
+        }))
+    });
+
+    let res = srv.get("/").send().await.unwrap();
+    let snapshot = actix_test::SnapshotConfig::new()
+        .redact_header(header::HeaderName::from_static("x-request-id"))
+        .redact(|body| body.replace("8f14e45f", "[id]"))
+        .snapshot(res)
+        .await;
+
+    assert_eq!(
+        snapshot,
+        "200 OK\n\
+         content-type: text/plain; charset=utf-8\n\
+         x-request-id: [redacted]\n\
+         \n\
+         session [id] started\n",
+    );
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_mock_server() {
+    use actix_test::{Expectation, MockResponse, MockServer};
+    use actix_web::http::Method;
+
+    let upstream = MockServer::start();
+
+    upstream
+        .expect(
+            Expectation::new(Method::GET, "/users")
+                .query("page", "2")
+                .times(1)
+                .respond_with(MockResponse::new(StatusCode::OK).body("page two")),
+        )
+        .expect(
+            Expectation::new(Method::POST, "/users")
+                .json(serde_json::json!({ "name": "ferris" }))
+                .respond_with(MockResponse::new(StatusCode::CREATED)),
+        );
+
+    let client = awc::Client::new();
+
+    let mut res = client
+        .get(upstream.url("/users?page=2"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body().await.unwrap(), Bytes::from_static(b"page two"));
+
+    let res = client
+        .post(upstream.url("/users"))
+        .send_json(&serde_json::json!({ "name": "ferris" }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    assert_eq!(upstream.matched_requests(), 2);
+    upstream.verify();
+}
+
+#[actix_rt::test]
+#[should_panic(expected = "received unexpected request DELETE /users/1")]
+async fn test_mock_server_unexpected_request() {
+    let upstream = actix_test::MockServer::start();
+
+    let res = awc::Client::new()
+        .delete(upstream.url("/users/1"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn test_graceful_shutdown() {
+    use std::sync::Arc;
+
+    use tokio::sync::Notify;
+
+    #[derive(Default)]
+    struct Signals {
+        started: Notify,
+        release: Notify,
+    }
+
+    let signals = Arc::new(Signals::default());
+
+    let srv = actix_test::start_with(
+        actix_test::config().shutdown_timeout(Duration::from_secs(5)),
+        {
+            let signals = Arc::clone(&signals);
+
+            move || {
+                App::new()
+                    .app_data(web::Data::from(Arc::clone(&signals)))
+                    .service(
+                        web::resource("/").to(|signals: web::Data<Signals>| async move {
+                            signals.started.notify_one();
+                            signals.release.notified().await;
+                            "done"
+                        }),
+                    )
+            }
+        },
+    );
+
+    assert_eq!(srv.accepted_connections(), 0);
+
+    let in_flight = actix_rt::spawn(srv.get("/").force_close().send());
+    signals.started.notified().await;
+
+    assert_eq!(srv.accepted_connections(), 1);
+    assert_eq!(srv.active_connections(), 1);
+
+    let ((), res) = tokio::join!(srv.stop_gracefully(), async {
+        signals.release.notify_one();
+        in_flight.await.unwrap()
+    });
+
+    let mut res = res.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body().await.unwrap(), Bytes::from_static(b"done"));
+    assert_eq!(srv.active_connections(), 0);
+}
+
+#[actix_rt::test]
+async fn test_send_multipart() {
+    use actix_test::{ClientRequestExt as _, MultipartBuilder};
+    use actix_web::HttpRequest;
+
+    let srv = actix_test::start(|| {
+        App::new().service(
+            web::resource("/").to(|req: HttpRequest, body: Bytes| async move {
+                let content_type = req.headers().get(header::CONTENT_TYPE).unwrap().clone();
+                HttpResponse::Ok().content_type(content_type).body(body)
+            }),
+        )
+    });
+
+    let form = MultipartBuilder::new()
+        .boundary("xyz")
+        .text("title", "Holiday")
+        .file("photo", "beach.txt", mime::TEXT_PLAIN, "sand");
+
+    let mut res = srv.post("/").send_multipart(form.clone()).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "multipart/form-data; boundary=\"xyz\""
+    );
+    assert_eq!(res.body().await.unwrap(), form.build().0);
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_client_cookie_store() {
+    use actix_web::HttpRequest;
+
+    let srv = actix_test::start_with(actix_test::config().cookie_store(), || {
+        App::new()
+            .route(
+                "/login",
+                web::post().to(|| async {
+                    HttpResponse::Ok()
+                        .cookie(Cookie::build("session", "s3cr3t").path("/").finish())
+                        .finish()
+                }),
+            )
+            .route(
+                "/me",
+                web::get().to(|req: HttpRequest| async move {
+                    match req.cookie("session") {
+                        Some(cookie) => HttpResponse::Ok().body(cookie.value().to_owned()),
+                        None => HttpResponse::Unauthorized().finish(),
+                    }
+                }),
+            )
+            .route(
+                "/logout",
+                web::post().to(|| async {
+                    let mut cookie = Cookie::build("session", "").path("/").finish();
+                    cookie.make_removal();
+                    HttpResponse::Ok().cookie(cookie).finish()
+                }),
+            )
+    });
+
+    let res = srv.get("/me").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    srv.post("/login").send().await.unwrap();
+    assert_eq!(srv.cookie("session").unwrap().value(), "s3cr3t");
+
+    let mut res = srv.get("/me").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body().await.unwrap(), Bytes::from_static(b"s3cr3t"));
+
+    srv.post("/logout").send().await.unwrap();
+    assert!(srv.cookie("session").is_none());
+
+    let res = srv.get("/me").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_capture_logs() {
+    use actix_web::middleware::Logger;
+
+    let logs = actix_test::capture_logs();
+
+    let srv = actix_test::start(|| {
+        App::new()
+            .wrap(Logger::new("%s %U %{x-request-id}i").log_target("test_capture_logs"))
+            .route("/fail", web::get().to(HttpResponse::InternalServerError))
+    });
+
+    let res = srv
+        .get("/fail")
+        .insert_header(("x-request-id", "42"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    srv.stop().await;
+
+    logs.assert_logged(|record| {
+        record.level() == log::Level::Info
+            && record.target() == "test_capture_logs"
+            && record.message() == "500 /fail 42"
+    });
+}
+
+#[actix_rt::test]
+async fn test_paused_time() {
+    use actix_test::time::Instant;
+
+    let srv = actix_test::start_with(
+        actix_test::config()
+            .h1()
+            .paused_time()
+            .client_request_timeout(Duration::from_secs(5)),
+        || {
+            App::new().service(web::resource("/").to(|| async {
+                actix_rt::time::sleep(Duration::from_secs(60)).await;
+                "done"
+            }))
+        },
+    );
+
+    let start = Instant::now();
+
+    let mut res = srv.get("/").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body().await.unwrap(), Bytes::from_static(b"done"));
+    assert!(start.elapsed() >= Duration::from_secs(60));
+
+    // a connection that never sends a request is closed after the client request timeout
+    let _stream = actix_rt::net::TcpStream::connect(srv.addr()).await.unwrap();
+    actix_rt::time::sleep(Duration::from_secs(6)).await;
+
+    assert_eq!(srv.accepted_connections(), 2);
+    assert_eq!(srv.active_connections(), 0);
+}
+
+#[actix_rt::test]
+async fn test_call_concurrently() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let hits = Arc::new(AtomicUsize::new(0));
+
+    let srv = actix_test::start({
+        let hits = Arc::clone(&hits);
+
+        move || {
+            let hits = Arc::clone(&hits);
+
+            App::new().service(web::resource("/").to(move || {
+                let hit = hits.fetch_add(1, Ordering::SeqCst);
+
+                async move {
+                    if hit % 10 == 9 {
+                        HttpResponse::ServiceUnavailable().finish()
+                    } else {
+                        HttpResponse::Ok().body(STR)
+                    }
+                }
+            }))
+        }
+    });
+
+    let requests = (0..50).map(|_| srv.get("/"));
+    let report = actix_test::call_concurrently(&srv, requests, 8).await;
+
+    assert_eq!(report.requests(), 50);
+    assert_eq!(report.status_count(StatusCode::OK), 45);
+    assert_eq!(report.status_count(StatusCode::SERVICE_UNAVAILABLE), 5);
+    assert!(report.errors().is_empty());
+    assert_eq!(hits.load(Ordering::SeqCst), 50);
+
+    let min = report.min_latency().unwrap();
+    let max = report.max_latency().unwrap();
+    assert!(min <= report.latency_percentile(50.0).unwrap());
+    assert!(report.latency_percentile(99.0).unwrap() <= max);
+    assert!(report.to_string().starts_with("50 requests in "));
+}
+
+#[actix_rt::test]
+async fn test_assert_body_json_eq() {
+    use actix_test::assert_body_json_eq;
+    use serde_json::json;
+
+    let srv = actix_test::start(|| {
+        App::new().service(web::resource("/").to(|| async {
+            HttpResponse::Ok().json(json!({
+                "id": 42,
+                "name": "alice",
+                "roles": ["admin", "user"],
+            }))
+        }))
+    });
+
+    let res = srv.get("/").send().await.unwrap();
+    assert_body_json_eq!(
+        res,
+        json!({ "id": 1, "name": "alice", "roles": ["admin", "user"] }),
+        ignore = ["/id"],
+    );
+
+    let diffs = actix_test::json_diff(
+        &json!({ "a": { "b~c": 1, "extra": true }, "list": [1, 2] }),
+        &json!({ "a": { "b~c": "1" }, "list": [1], "missing": null }),
+        &[],
+    );
+    assert_eq!(
+        diffs,
+        [
+            r#"/a/b~0c: expected "1", found 1"#,
+            "/a/extra: unexpected field with value true",
+            "/list/1: unexpected element 2",
+            "/missing: missing field, expected null",
+        ],
+    );
+}
+
+#[actix_rt::test]
+#[should_panic(expected = "/name: expected \"bob\", found \"alice\"")]
+async fn test_assert_body_json_eq_mismatch() {
+    use actix_test::assert_body_json_eq;
+    use serde_json::json;
+
+    let srv = actix_test::start(|| {
+        App::new().service(
+            web::resource("/").to(|| async { HttpResponse::Ok().json(json!({ "name": "alice" })) }),
+        )
+    });
+
+    let res = srv.get("/").send().await.unwrap();
+    assert_body_json_eq!(res, json!({ "name": "bob" }));
+}
+
+#[actix_rt::test]
+async fn test_fault_injection() {
+    use actix_test::FaultInjection;
+    use awc::error::{PayloadError, SendRequestError};
+
+    let app = || App::new().service(web::resource("/").to(|| async { STR }));
+
+    let srv = actix_test::start_with(
+        actix_test::config().faults(FaultInjection::new().connection_reset(1.0)),
+        app,
+    );
+    match srv.get("/").send().await {
+        Err(SendRequestError::Send(err)) => {
+            assert_eq!(err.kind(), std::io::ErrorKind::ConnectionReset)
+        }
+        res => panic!("expected connection reset, got {res:?}"),
+    }
+    assert_eq!(srv.accepted_connections(), 1);
+
+    let srv = actix_test::start_with(
+        actix_test::config().faults(FaultInjection::new().truncated_body(1.0)),
+        app,
+    );
+    let mut res = srv.get("/").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(matches!(
+        res.body().await,
+        Err(PayloadError::Incomplete(None))
+    ));
+
+    let srv = actix_test::start_with(
+        actix_test::config().faults(
+            FaultInjection::new()
+                .latency(1.0, Duration::from_millis(100))
+                .truncated_body(0.0),
+        ),
+        app,
+    );
+    let start = std::time::Instant::now();
+    let mut res = srv.get("/").send().await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(res.body().await.unwrap(), Bytes::from_static(STR.as_ref()));
+
+    // seeded faults are reproducible
+    let outcomes = |seed| async move {
+        let srv = actix_test::start_with(
+            actix_test::config().faults(FaultInjection::new().connection_reset(0.5).seed(seed)),
+            || App::new().service(web::resource("/").to(|| async { "ok" })),
+        );
+
+        let mut outcomes = Vec::new();
+        for _ in 0..10 {
+            outcomes.push(srv.get("/").send().await.is_ok());
+        }
+        outcomes
+    };
+    assert_eq!(outcomes(7).await, outcomes(7).await);
+}
+
+#[actix_rt::test]
+async fn test_map_host() {
+    use actix_web::{guard, http::Method, HttpRequest};
+
+    let srv = actix_test::start_with(
+        actix_test::config()
+            .map_host("tenant1.example.test")
+            .map_host("tenant2.example.test"),
+        || {
+            App::new()
+                .service(
+                    web::scope("")
+                        .guard(guard::Host("tenant1.example.test"))
+                        .route("/", web::get().to(|| async { "tenant 1" })),
+                )
+                .default_service(web::to(|req: HttpRequest| async move {
+                    req.connection_info().host().to_owned()
+                }))
+        },
+    );
+
+    let mut res = srv
+        .request(Method::GET, srv.url_for_host("tenant1.example.test", "/"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.body().await.unwrap(), "tenant 1");
+
+    let mut res = srv
+        .request(Method::GET, srv.url_for_host("TENANT2.example.test", "/"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.body().await.unwrap(), "TENANT2.example.test");
+
+    // mapped hosts connect to the server on any port
+    let mut res = srv
+        .request(Method::GET, "http://tenant2.example.test:1234/")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.body().await.unwrap(), "tenant2.example.test:1234");
+
+    // other hosts are resolved as usual
+    let mut res = srv
+        .request(
+            Method::GET,
+            format!("http://localhost:{}/", srv.addr().port()),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        res.body().await.unwrap(),
+        format!("localhost:{}", srv.addr().port())
+    );
+}
+
+#[actix_rt::test]
+async fn test_stream_reader() {
+    use actix_test::{SseEvent, StreamReader};
+    use futures_util::StreamExt as _;
+
+    fn delayed(chunks: &'static [&'static str]) -> HttpResponse {
+        let stream = futures_util::stream::iter(chunks).then(|chunk| async move {
+            actix_rt::time::sleep(Duration::from_millis(50)).await;
+            Ok::<_, Error>(Bytes::from_static(chunk.as_bytes()))
+        });
+
+        HttpResponse::Ok().streaming(stream)
+    }
+
+    let srv = actix_test::start(|| {
+        App::new()
+            .route(
+                "/chunks",
+                web::get().to(|| async { delayed(&["one", "two", "three\nfour"]) }),
+            )
+            .route(
+                "/events",
+                web::get().to(|| async {
+                    delayed(&[
+                        ": comment\n\ndata: first\n\n",
+                        "event: update\r\nid: 2\r\nda",
+                        "ta: line 1\r\ndata:line 2\r\nretry: 100\r\n\r\n",
+                        "data: last\n\n",
+                    ])
+                }),
+            )
+    });
+
+    let res = srv.get("/chunks").send().await.unwrap();
+    let mut reader = StreamReader::new(res);
+    reader.expect_chunk("one").await;
+    reader.expect_chunk("two").await;
+    assert_eq!(reader.read_until("\n").await, "three\n");
+    assert_eq!(reader.buffered(), b"four");
+    reader.expect_chunk("four").await;
+    reader.expect_end().await;
+
+    let res = srv.get("/events").send().await.unwrap();
+    let mut reader = StreamReader::new(res);
+    let events = reader.collect_events(2).await;
+    assert_eq!(
+        events,
+        [
+            SseEvent {
+                data: "first".to_owned(),
+                ..Default::default()
+            },
+            SseEvent {
+                event: Some("update".to_owned()),
+                data: "line 1\nline 2".to_owned(),
+                id: Some("2".to_owned()),
+                retry: Some(100),
+            },
+        ]
+    );
+    assert_eq!(reader.next_event().await.unwrap().data, "last");
+    assert_eq!(reader.next_event().await, None);
+
+    // response bodies from `init_service` can be read too
+    let res = actix_test::call_service(
+        &actix_test::init_service(
+            App::new().route("/", web::get().to(|| async { delayed(&["a", "b"]) })),
+        )
+        .await,
+        actix_test::TestRequest::get().to_request(),
+    )
+    .await;
+    let mut reader = StreamReader::from_body(res.into_body());
+    reader.expect_chunk("a").await;
+    reader.expect_chunk("b").await;
+    reader.expect_end().await;
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_cassette() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use actix_test::{Cassette, CassetteMode};
+
+    let path = std::env::temp_dir().join(format!(
+        "actix-test-cassettes-{}/upstream.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let srv_hits = Arc::clone(&hits);
+    let srv = actix_test::start(move || {
+        let hits = Arc::clone(&srv_hits);
+
+        App::new().default_service(web::to(move |body: Bytes| {
+            let n = hits.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                HttpResponse::Ok()
+                    .insert_header(("x-secret", "hunter2"))
+                    .body(format!("{n}:{}", String::from_utf8_lossy(&body)))
+            }
+        }))
+    });
+
+    fn client(cassette: Cassette) -> awc::Client {
+        awc::Client::builder().wrap(cassette).finish()
+    }
+
+    let cassette = Cassette::new(&path).redact_header("authorization");
+
+    // first run records
+    let recording = client(cassette.clone());
+    let mut res = recording
+        .get(srv.url("/a"))
+        .insert_header(("authorization", "token"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.body().await.unwrap(), "1:");
+    let mut res = recording.post(srv.url("/a")).send_body("x").await.unwrap();
+    assert_eq!(res.body().await.unwrap(), "2:x");
+    let mut res = recording.post(srv.url("/a")).send_body("y").await.unwrap();
+    assert_eq!(res.body().await.unwrap(), "3:y");
+
+    let recorded = std::fs::read_to_string(&path).unwrap();
+    assert!(recorded.contains("[redacted]"));
+    assert!(!recorded.contains("token"));
+
+    // later runs replay without contacting the server
+    let replaying = client(cassette.clone());
+    let mut res = replaying.post(srv.url("/a")).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get("x-secret").unwrap(), "hunter2");
+    assert_eq!(res.body().await.unwrap(), "2:x");
+    let mut res = replaying.post(srv.url("/a")).send().await.unwrap();
+    assert_eq!(res.body().await.unwrap(), "3:y");
+    let mut res = replaying.get(srv.url("/a")).send().await.unwrap();
+    assert_eq!(res.body().await.unwrap(), "1:");
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+    // each recorded response is replayed once
+    assert!(replaying.get(srv.url("/a")).send().await.is_err());
+    assert!(replaying.get(srv.url("/b")).send().await.is_err());
+
+    // recording mode replaces the cassette
+    let recording = client(cassette.mode(CassetteMode::Record));
+    let mut res = recording.get(srv.url("/b")).send().await.unwrap();
+    assert_eq!(res.body().await.unwrap(), "4:");
+    let replaying = client(Cassette::new(&path));
+    assert!(replaying.get(srv.url("/a")).send().await.is_err());
+
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_bind_retry() {
+    let addr = actix_test::unused_addr();
+    let occupied = std::net::TcpListener::bind(addr).unwrap();
+
+    // release the port while the server is retrying
+    let release = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(200));
+        drop(occupied);
+    });
+
+    let srv = actix_test::start_with(
+        actix_test::config()
+            .port(addr.port())
+            .bind_retry(Duration::from_secs(5)),
+        || App::new().route("/", web::get().to(HttpResponse::Ok)),
+    );
+    release.join().unwrap();
+
+    assert_eq!(srv.addr().port(), addr.port());
+    assert!(srv.get("/").send().await.unwrap().status().is_success());
+
+    srv.stop().await;
+}
+
+#[actix_rt::test]
+async fn test_same_thread() {
+    use std::thread;
+
+    let srv = actix_test::start_with(actix_test::config().same_thread(), || {
+        App::new().route(
+            "/",
+            web::get().to(|| async { format!("{:?}", thread::current().id()) }),
+        )
+    });
+
+    let mut res = srv.get("/").send().await.unwrap();
+    assert_eq!(
+        res.body().await.unwrap(),
+        format!("{:?}", thread::current().id())
+    );
+
+    srv.stop().await;
 }
 
 #[actix_rt::test]
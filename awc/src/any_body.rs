@@ -89,6 +89,7 @@ where
         }
     }
 
+    /// Boxes the generic message body, if any.
     pub fn into_boxed(self) -> AnyBody {
         match self {
             Self::None => AnyBody::None,
@@ -137,6 +137,7 @@ pub mod http {
 #[allow(deprecated)]
 pub use self::responses::{ClientResponse, JsonBody, MessageBody, ResponseBody};
 pub use self::{
+    any_body::AnyBody,
     builder::ClientBuilder,
     client::{Client, Connect, Connector},
     connect::{BoxConnectorService, BoxedSocket, ConnectRequest, ConnectResponse},